@@ -0,0 +1,287 @@
+//! Achievements, unlocked once and for all by feats in a game.
+//!
+//! [`detect_achievements`] turns game events into [`Happening`]s, and feeds
+//! them to the [`GameProgress`] of the current game. Each [`Achievement`]
+//! has its own detector in [`Achievement::detect`]. Unlocked achievements
+//! are kept in the [`Stats`], and announced with an [`AchievementUnlocked`]
+//! event.
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use enum_map::Enum;
+
+use crate::{
+    cheat::CheatEvent,
+    game_event::GameEvent,
+    game_flow::{BattleResolved, CoopTurn, Scores, SeedCount},
+    state::GameState,
+    stats::Stats,
+    war::{BattleOutcome, Value},
+    EndReason, Participant,
+};
+
+/// Points the player must have trailed by for a [`Achievement::Comeback`].
+const COMEBACK_DEFICIT: i32 = 15;
+/// Cards to sleeve in a game for [`Achievement::SleeveThree`].
+const SLEEVED_CARDS: usize = 3;
+/// Seeds to hold at the end of a game for [`Achievement::SeedHoarder`].
+const HOARDED_SEEDS: usize = 3;
+
+#[derive(Clone, Copy, Debug, Enum, PartialEq)]
+pub enum Achievement {
+    /// Win without sleeving cards or distracting the bird.
+    FairWin,
+    /// Win after trailing by [`COMEBACK_DEFICIT`] points.
+    Comeback,
+    SleeveThree,
+    /// Win a battle playing a Zero against a Nine.
+    ZeroBeatsNine,
+    /// Win or lose a game with [`HOARDED_SEEDS`] unused seeds.
+    SeedHoarder,
+}
+impl Achievement {
+    pub const ALL: [Self; 5] = [
+        Self::FairWin,
+        Self::Comeback,
+        Self::SleeveThree,
+        Self::ZeroBeatsNine,
+        Self::SeedHoarder,
+    ];
+    /// Name of the achievement in the stats file.
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::FairWin => "fair_win",
+            Self::Comeback => "comeback",
+            Self::SleeveThree => "sleeve_three",
+            Self::ZeroBeatsNine => "zero_beats_nine",
+            Self::SeedHoarder => "seed_hoarder",
+        }
+    }
+    /// Key of the achievement name in [`Strings`].
+    ///
+    /// [`Strings`]: crate::lang::Strings
+    pub fn name_key(self) -> &'static str {
+        match self {
+            Self::FairWin => "achievement.fair_win",
+            Self::Comeback => "achievement.comeback",
+            Self::SleeveThree => "achievement.sleeve_three",
+            Self::ZeroBeatsNine => "achievement.zero_beats_nine",
+            Self::SeedHoarder => "achievement.seed_hoarder",
+        }
+    }
+    /// Whether `happening` unlocks this achievement, `progress` being
+    /// already updated with it.
+    fn detect(self, progress: &GameProgress, happening: &Happening) -> bool {
+        use BattleOutcome::Win;
+        use EndReason::{Loss, Victory};
+        use Happening::*;
+        match (self, happening) {
+            (Self::FairWin, GameOver { end: Victory, .. }) => !progress.cheated,
+            (Self::Comeback, GameOver { end: Victory, .. }) => {
+                progress.worst_deficit >= COMEBACK_DEFICIT
+            }
+            (Self::SleeveThree, Sleeved) => progress.sleeved == SLEEVED_CARDS,
+            (Self::ZeroBeatsNine, Battle { outcome: Win, player, oppo }) => {
+                *player == Value::Zero && *oppo == Value::Nine
+            }
+            (Self::SeedHoarder, GameOver { end: Victory | Loss, seeds }) => *seeds >= HOARDED_SEEDS,
+            _ => false,
+        }
+    }
+}
+
+/// Sent when an achievement is unlocked for the first time.
+pub struct AchievementUnlocked(pub Achievement);
+
+/// Something that happened in a game, relevant to achievements.
+enum Happening {
+    Sleeved,
+    DistractedBird,
+    Scores {
+        player: i32,
+        oppo: i32,
+    },
+    Battle {
+        outcome: BattleOutcome,
+        player: Value,
+        oppo: Value,
+    },
+    GameOver {
+        end: EndReason,
+        seeds: usize,
+    },
+}
+
+/// What happened so far in the current game.
+#[derive(Default)]
+struct GameProgress {
+    cheated: bool,
+    sleeved: usize,
+    /// Most points the player trailed by.
+    worst_deficit: i32,
+}
+impl GameProgress {
+    /// Update the progress with `happening`, returns the achievements it
+    /// unlocks.
+    fn update(&mut self, happening: &Happening) -> Vec<Achievement> {
+        match happening {
+            Happening::Sleeved => {
+                self.cheated = true;
+                self.sleeved += 1;
+            }
+            Happening::DistractedBird => self.cheated = true,
+            Happening::Scores { player, oppo } => {
+                self.worst_deficit = self.worst_deficit.max(oppo - player);
+            }
+            Happening::Battle { .. } | Happening::GameOver { .. } => {}
+        }
+        let unlocked = |a: &Achievement| a.detect(self, happening);
+        Achievement::ALL.into_iter().filter(unlocked).collect()
+    }
+}
+
+fn battle_happening(battle: &BattleResolved) -> Happening {
+    let value = |who| {
+        battle
+            .order
+            .iter()
+            .find(|p| p.who == who)
+            .unwrap()
+            .card
+            .value
+    };
+    Happening::Battle {
+        outcome: battle.outcome,
+        player: value(Participant::Player),
+        oppo: value(Participant::Oppo),
+    }
+}
+
+/// Feed the game events to the [`GameProgress`], and unlock the achievements
+/// they complete.
+fn detect_achievements(
+    mut progress: ResMut<GameProgress>,
+    mut stats: ResMut<Stats>,
+    mut cheats: EventReader<CheatEvent>,
+    mut battles: EventReader<BattleResolved>,
+    mut gameovers: EventReader<GameEvent>,
+    mut unlocks: EventWriter<AchievementUnlocked>,
+    scores: Res<Scores>,
+    seeds: Res<SeedCount>,
+    coop_turn: Res<CoopTurn>,
+) {
+    let mut happenings = Vec::new();
+    for cheat in cheats.iter() {
+        happenings.push(match cheat {
+            CheatEvent::HideInSleeve(_) => Happening::Sleeved,
+            CheatEvent::ConfuseBird(_) => Happening::DistractedBird,
+        });
+    }
+    if scores.is_changed() {
+        let (player, oppo) = (scores.player(), scores.oppo());
+        happenings.push(Happening::Scores { player, oppo });
+    }
+    happenings.extend(battles.iter().map(battle_happening));
+    let seeds = seeds.count(coop_turn.active());
+    for end in gameovers.iter().filter_map(GameEvent::end) {
+        happenings.push(Happening::GameOver { end, seeds });
+    }
+    for happening in &happenings {
+        for achievement in progress.update(happening) {
+            if !stats.achievements[achievement] {
+                stats.achievements[achievement] = true;
+                unlocks.send(AchievementUnlocked(achievement));
+            }
+        }
+    }
+}
+
+fn reset_progress(mut progress: ResMut<GameProgress>) {
+    *progress = GameProgress::default();
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        // After the gameover handler, so that the game is checked before
+        // leaving it resets the scores and seeds.
+        app.init_resource::<GameProgress>()
+            .add_event::<AchievementUnlocked>()
+            .add_system(detect_achievements.after("gameover"))
+            .add_system_set(self.0.on_enter(reset_progress));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Happening::*;
+
+    /// The achievements unlocked by `happenings` in a single game.
+    fn unlocked(happenings: &[Happening]) -> Vec<Achievement> {
+        let mut progress = GameProgress::default();
+        happenings.iter().flat_map(|h| progress.update(h)).collect()
+    }
+
+    fn win(seeds: usize) -> Happening {
+        GameOver { end: EndReason::Victory, seeds }
+    }
+
+    fn battle(outcome: BattleOutcome, player: Value, oppo: Value) -> Happening {
+        Battle { outcome, player, oppo }
+    }
+
+    #[test]
+    fn fair_win_without_any_cheat() {
+        let fair = unlocked(&[Scores { player: 5, oppo: 0 }, win(0)]);
+        assert_eq!(fair, [Achievement::FairWin]);
+        assert_eq!(unlocked(&[Sleeved, win(0)]), []);
+        assert_eq!(unlocked(&[DistractedBird, win(0)]), []);
+        let loss = GameOver { end: EndReason::Loss, seeds: 0 };
+        assert_eq!(unlocked(&[loss]), []);
+    }
+
+    #[test]
+    fn comeback_after_trailing_by_15() {
+        let comeback = |deficit| {
+            let behind = Scores { player: 0, oppo: deficit };
+            let ahead = Scores { player: 40, oppo: deficit };
+            unlocked(&[behind, ahead, DistractedBird, win(0)])
+        };
+        assert_eq!(comeback(15), [Achievement::Comeback]);
+        assert_eq!(comeback(14), []);
+        let caught = GameOver { end: EndReason::CaughtCheating, seeds: 0 };
+        assert_eq!(unlocked(&[Scores { player: 0, oppo: 20 }, caught]), []);
+    }
+
+    #[test]
+    fn sleeve_three_cards_once() {
+        assert_eq!(unlocked(&[Sleeved, Sleeved]), []);
+        assert_eq!(
+            unlocked(&[Sleeved, Sleeved, Sleeved]),
+            [Achievement::SleeveThree]
+        );
+        let four = unlocked(&[Sleeved, Sleeved, Sleeved, Sleeved]);
+        assert_eq!(four, [Achievement::SleeveThree], "unlocked only once");
+    }
+
+    #[test]
+    fn zero_beats_nine_in_a_battle() {
+        use BattleOutcome::{Loss, Win};
+        use Value::{Nine, One, Zero};
+        let zero_nine = unlocked(&[battle(Win, Zero, Nine)]);
+        assert_eq!(zero_nine, [Achievement::ZeroBeatsNine]);
+        assert_eq!(unlocked(&[battle(Win, One, Zero)]), []);
+        assert_eq!(unlocked(&[battle(Loss, Nine, Zero)]), []);
+    }
+
+    #[test]
+    fn seed_hoarder_finishes_with_full_pockets() {
+        let hoarder = unlocked(&[Sleeved, win(3)]);
+        assert_eq!(hoarder, [Achievement::SeedHoarder]);
+        let loss = GameOver { end: EndReason::Loss, seeds: 3 };
+        assert_eq!(unlocked(&[loss]), [Achievement::SeedHoarder]);
+        let conceded = GameOver { end: EndReason::Conceded, seeds: 3 };
+        assert_eq!(unlocked(&[conceded]), []);
+        assert_eq!(unlocked(&[Sleeved, win(2)]), []);
+    }
+}
@@ -1,14 +1,188 @@
 //! Animations.
+//!
+//! Also defines [`exp_approach`] and [`exp_approach_quat`], to smoothly move
+//! things toward a target at a [`GameFeel`] rate, scaled by the
+//! [`GameSpeed`].
+//!
+//! Animated entities often outlive their parent, when a game is torn down
+//! mid-animation, so their [`AnimationCommand`]s tolerate them being gone.
+//!
+//! Timed animations send an [`AnimationFinished`] event when they complete.
+//! Gameplay can wait on the timed animations of entities tagged
+//! [`AwaitAnimation`] with the [`WaitForAnimations`] system param, the
+//! infinite ones ([`Animated::Bob`], [`Animated::Breath`] and
+//! [`Animated::Circle`]) never hold it back.
+use std::f32::consts::TAU;
 use std::f64::consts::PI;
 
+use bevy::ecs::system::{Command, SystemParam};
+use bevy::hierarchy::despawn_with_children_recursive;
 use bevy::prelude::{Plugin as BevyPlugin, *};
-use bevy_debug_text_overlay::screen_print;
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::{Inspectable, RegisterInspectable};
 
+use crate::settings::GameplaySettings;
+
+/// How much shorter gameplay delays are in fast mode.
+const FAST_MODE_FACTOR: f32 = 0.4;
+
 #[derive(Component)]
 pub struct DisableAnimation;
 
+/// Gameplay waits for the timed [`Animated`] of this entity to finish, see
+/// [`WaitForAnimations`].
+#[derive(Component)]
+pub struct AwaitAnimation;
+
+/// The [`Animated`] variants that end.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AnimationKind {
+    RiseAndFade,
+}
+
+/// The timed `kind` animation of `entity` completed.
+///
+/// Sent the frame the animation ends, [`AnimationKind::RiseAndFade`] entities
+/// are despawned right after.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnimationFinished {
+    pub entity: Entity,
+    pub kind: AnimationKind,
+}
+
+/// Whether timed animations that gameplay waits on are still running.
+#[derive(SystemParam)]
+pub struct WaitForAnimations<'w, 's> {
+    awaited: Query<
+        'w,
+        's,
+        (Entity, &'static Animated),
+        (With<AwaitAnimation>, Without<DisableAnimation>),
+    >,
+    finished: EventReader<'w, 's, AnimationFinished>,
+}
+impl<'w, 's> WaitForAnimations<'w, 's> {
+    /// An [`AwaitAnimation`] entity has a timed animation that didn't finish.
+    ///
+    /// Animations disabled with [`DisableAnimation`] don't progress, so they
+    /// aren't waited on. Those that sent their [`AnimationFinished`] are
+    /// done, even if their entity isn't despawned yet.
+    pub fn pending(&mut self) -> bool {
+        let finished: Vec<_> = self.finished.iter().map(|f| (f.entity, f.kind)).collect();
+        let running = |(entity, anim): (Entity, &Animated)| {
+            let kind = anim.kind();
+            kind.map_or(false, |kind| !finished.contains(&(entity, kind)))
+        };
+        self.awaited.iter().any(running)
+    }
+}
+
+/// Rates (per second) at which cards move toward their target, see
+/// [`exp_approach`].
+pub struct GameFeel {
+    /// Cards moving in the player and oppo hands.
+    pub hand_rate: f32,
+    /// Cards moving to the war or score piles.
+    pub pile_rate: f32,
+    /// Cards tilting when dragged over the sleeve.
+    pub sleeve_rate: f32,
+}
+impl Default for GameFeel {
+    fn default() -> Self {
+        Self {
+            hand_rate: 10.0,
+            pile_rate: 10.0,
+            sleeve_rate: 10.0,
+        }
+    }
+}
+
+/// How fast the game goes, set from [`GameplaySettings::fast_mode`].
+///
+/// Gameplay delays and [`GameFeel`] rates are scaled, rather than [`Time`],
+/// so that audio isn't pitch-shifted.
+pub struct GameSpeed {
+    /// Delays are multiplied by this, rates are divided by it.
+    factor: f32,
+}
+impl Default for GameSpeed {
+    fn default() -> Self {
+        Self { factor: 1.0 }
+    }
+}
+impl GameSpeed {
+    fn new(fast_mode: bool) -> Self {
+        let factor = if fast_mode { FAST_MODE_FACTOR } else { 1.0 };
+        Self { factor }
+    }
+    /// `seconds` adjusted to the game speed.
+    pub fn duration(&self, seconds: f32) -> f32 {
+        seconds * self.factor
+    }
+    /// A per second `rate` adjusted to the game speed.
+    pub fn rate(&self, rate: f32) -> f32 {
+        rate / self.factor
+    }
+}
+
+fn update_game_speed(settings: Res<GameplaySettings>, mut speed: ResMut<GameSpeed>) {
+    if settings.is_changed() {
+        *speed = GameSpeed::new(settings.fast_mode);
+    }
+}
+
+/// How much of the remaining distance to close after `dt` seconds at `rate`.
+fn approach_factor(rate: f32, dt: f32) -> f32 {
+    1.0 - (-rate * dt).exp()
+}
+
+/// Move `current` toward `target`, closing the gap exponentially at `rate`
+/// per second.
+///
+/// Unlike `current += (target - current) * rate * dt`, this never overshoots
+/// however long the frame, and reaches the same position at the same time
+/// whatever the frame rate.
+pub fn exp_approach(current: Vec3, target: Vec3, rate: f32, dt: f32) -> Vec3 {
+    current.lerp(target, approach_factor(rate, dt))
+}
+
+/// Rotation equivalent of [`exp_approach`].
+pub fn exp_approach_quat(current: Quat, target: Quat, rate: f32, dt: f32) -> Quat {
+    current.lerp(target, approach_factor(rate, dt))
+}
+
+/// A brief push added on top of the [`Animated`] movement of an entity.
+///
+/// It fades out over time and is removed once it faded out.
+#[derive(Component)]
+pub struct Impulse {
+    /// Direction and maximum distance of the push.
+    pub direction: Vec3,
+    /// Back and forth movements per second, `0.0` for a single push.
+    pub frequency: f32,
+    /// How fast the push fades out.
+    pub decay: f32,
+    start: Option<f64>,
+}
+impl Impulse {
+    pub fn new(direction: Vec3, frequency: f32, decay: f32) -> Self {
+        Self { direction, frequency, decay, start: None }
+    }
+    fn offset(&self, elapsed: f32) -> Vec3 {
+        impulse_offset(self.direction, self.frequency, self.decay, elapsed)
+    }
+}
+fn impulse_offset(direction: Vec3, frequency: f32, decay: f32, elapsed: f32) -> Vec3 {
+    // Don't jump to full strength on the first frame
+    let attack = (elapsed * 10.0).min(1.0);
+    let wave = if frequency == 0.0 {
+        1.0
+    } else {
+        (elapsed * frequency * TAU).sin()
+    };
+    direction * attack * wave * (-decay * elapsed).exp()
+}
+
 /// Modify the transform of entities it's attached to.
 #[cfg_attr(feature = "debug", derive(Inspectable))]
 #[derive(Component)]
@@ -45,6 +219,16 @@ impl Animated {
     pub fn breath(offset: f64, strength: f32, period: f64) -> Self {
         Animated::Breath { offset, strength, period }
     }
+    /// What timed animation this is, `None` if it never ends.
+    pub fn kind(&self) -> Option<AnimationKind> {
+        match self {
+            Animated::RiseAndFade { .. } => Some(AnimationKind::RiseAndFade),
+            Animated::Breath { .. }
+            | Animated::Bob { .. }
+            | Animated::Circle { .. }
+            | Animated::Static => None,
+        }
+    }
 }
 
 #[cfg_attr(feature = "debug", derive(Inspectable))]
@@ -54,17 +238,50 @@ struct AnimationState {
     time: f64,
 }
 
+/// Commands on animated entities, skipped when the entity was despawned
+/// earlier in the frame, typically with its parent.
+enum AnimationCommand {
+    Start(Entity, AnimationState),
+    Despawn(Entity),
+}
+impl Command for AnimationCommand {
+    fn write(self, world: &mut World) {
+        match self {
+            AnimationCommand::Start(entity, state) => {
+                if let Some(mut entity) = world.get_entity_mut(entity) {
+                    entity.insert(state);
+                }
+            }
+            AnimationCommand::Despawn(entity) => {
+                if world.get_entity(entity).is_some() {
+                    despawn_with_children_recursive(world, entity);
+                }
+            }
+        }
+    }
+}
+
 fn enable_animation(
     time: Res<Time>,
     animated: Query<(Entity, &Transform), Added<Animated>>,
     mut cmds: Commands,
 ) {
-    let mut cmd_buffer = Vec::new();
     for (entity, &transform) in animated.iter() {
         let state = AnimationState { transform, time: time.seconds_since_startup() };
-        cmd_buffer.push((entity, (state,)));
+        cmds.add(AnimationCommand::Start(entity, state));
+    }
+}
+
+/// Despawn the [`Animated::RiseAndFade`] popups still fading, so that they
+/// don't linger after the game that spawned them.
+///
+/// Game teardown runs after this, see [`crate::game_flow`].
+pub fn despawn_fading(mut cmds: Commands, animated: Query<(Entity, &Animated)>) {
+    for (entity, animated) in animated.iter() {
+        if matches!(animated, Animated::RiseAndFade { .. }) {
+            cmds.add(AnimationCommand::Despawn(entity));
+        }
     }
-    cmds.insert_or_spawn_batch(cmd_buffer);
 }
 
 fn reset_static(
@@ -80,13 +297,20 @@ fn reset_static(
 fn run_animation(
     time: Res<Time>,
     mut cmds: Commands,
+    mut finished: EventWriter<AnimationFinished>,
     mut animated: Query<
-        (Entity, &mut Transform, &AnimationState, &Animated),
+        (
+            Entity,
+            &mut Transform,
+            &AnimationState,
+            &Animated,
+            Option<&mut Impulse>,
+        ),
         Without<DisableAnimation>,
     >,
 ) {
     let time = time.seconds_since_startup();
-    for (entity, mut trans, init, anim) in animated.iter_mut() {
+    for (entity, mut trans, init, anim, impulse) in animated.iter_mut() {
         match *anim {
             Animated::Static => {}
             Animated::Bob { offset, strength, period } => {
@@ -112,8 +336,10 @@ fn run_animation(
                 let extra_delta = if expiring { delta - duration } else { 0.0 };
                 let scale = 1.0 - extra_delta;
                 if scale <= 0.0 {
-                    screen_print!("Despawning a RiseAndFade animation");
-                    cmds.entity(entity).despawn_recursive();
+                    debug_print!("Despawning a RiseAndFade animation");
+                    let kind = AnimationKind::RiseAndFade;
+                    finished.send(AnimationFinished { entity, kind });
+                    cmds.add(AnimationCommand::Despawn(entity));
                 } else {
                     let offset = delta.min(duration) + extra_delta * 0.7;
                     trans.translation = init.transform.translation + direction * offset;
@@ -126,6 +352,22 @@ fn run_animation(
                 trans.translation = init.transform.translation + trans_offset;
             }
         }
+        if let Some(mut impulse) = impulse {
+            let elapsed = (time - *impulse.start.get_or_insert(time)) as f32;
+            let offset = if impulse.decay * elapsed > 6.0 {
+                cmds.entity(entity).remove::<Impulse>();
+                Vec3::ZERO
+            } else {
+                impulse.offset(elapsed)
+            };
+            // Only some animations reset the translation every frame
+            match *anim {
+                Animated::Breath { .. } | Animated::Static => {
+                    trans.translation = init.transform.translation + offset;
+                }
+                _ => trans.translation += offset,
+            }
+        }
     }
 }
 
@@ -136,8 +378,164 @@ impl BevyPlugin for Plugin {
         app.register_inspectable::<Animated>()
             .register_inspectable::<AnimationState>();
 
-        app.add_system(enable_animation)
+        app.init_resource::<GameFeel>()
+            .init_resource::<GameSpeed>()
+            .add_event::<AnimationFinished>()
+            .add_system(update_game_speed)
+            .add_system(enable_animation)
             .add_system(reset_static)
             .add_system(run_animation.label("animation"));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approach_in_steps(steps: usize, total_time: f32) -> Vec3 {
+        let dt = total_time / steps as f32;
+        (0..steps).fold(Vec3::ZERO, |pos, _| {
+            exp_approach(pos, Vec3::X * 10.0, 10.0, dt)
+        })
+    }
+
+    #[test]
+    fn exp_approach_large_dt_never_overshoots() {
+        for dt in [0.1, 0.5, 1.0, 10.0, 1000.0] {
+            let pos = exp_approach(Vec3::ZERO, Vec3::X * 10.0, 10.0, dt);
+            assert!(pos.x > 0.0 && pos.x <= 10.0, "dt {dt} moved to {pos}");
+        }
+        let rot = exp_approach_quat(Quat::IDENTITY, Quat::from_rotation_z(1.0), 10.0, 1000.0);
+        assert!(rot.angle_between(Quat::from_rotation_z(1.0)) < 0.001);
+    }
+
+    #[test]
+    fn exp_approach_independent_of_frame_rate() {
+        let at_20fps = approach_in_steps(2, 0.1);
+        let at_60fps = approach_in_steps(6, 0.1);
+        let at_144fps = approach_in_steps(144, 1.0);
+        let at_144fps_short = approach_in_steps(14, 0.1);
+        assert!(
+            at_20fps.abs_diff_eq(at_60fps, 0.001),
+            "{at_20fps} != {at_60fps}"
+        );
+        assert!(
+            at_60fps.abs_diff_eq(at_144fps_short, 0.01),
+            "{at_60fps} != {at_144fps_short}"
+        );
+        assert!(at_144fps.abs_diff_eq(Vec3::X * 10.0, 0.001));
+    }
+
+    /// Tear down every parent, like leaving the game does.
+    fn teardown(mut cmds: Commands, parents: Query<Entity, With<Children>>) {
+        for entity in parents.iter() {
+            cmds.entity(entity).despawn_recursive();
+        }
+    }
+
+    #[test]
+    fn popups_torn_down_mid_animation() {
+        let mut world = World::new();
+        world.insert_resource(Time::default());
+        world.init_resource::<Events<AnimationFinished>>();
+        let mut stage = SystemStage::single_threaded()
+            .with_system(teardown.before(enable_animation))
+            .with_system(enable_animation)
+            .with_system(run_animation.after(enable_animation));
+        let popup = |duration| Animated::RiseAndFade { duration, direction: Vec3::Y };
+        let score = world.spawn().insert_bundle(SpatialBundle::default()).id();
+        let starting = world
+            .spawn()
+            .insert_bundle((popup(1.2), Transform::default()))
+            .id();
+        let expiring = world
+            .spawn()
+            .insert_bundle((popup(-1.0), Transform::default()))
+            .insert(AnimationState { transform: Transform::default(), time: 0.0 })
+            .id();
+        world.entity_mut(score).push_children(&[starting, expiring]);
+        for _ in 0..4 {
+            stage.run(&mut world);
+        }
+        assert_eq!(world.entities().len(), 0);
+    }
+
+    #[test]
+    fn timed_animations_finish_once() {
+        use bevy::ecs::system::SystemState;
+        use std::time::Duration;
+
+        let mut world = World::new();
+        world.insert_resource(Time::default());
+        world.init_resource::<Events<AnimationFinished>>();
+        let mut stage = SystemStage::single_threaded()
+            .with_system(enable_animation)
+            .with_system(run_animation.after(enable_animation));
+        let mut frame_at = |world: &mut World, seconds: f32| {
+            let mut time = world.resource_mut::<Time>();
+            let startup = time.startup();
+            time.update_with_instant(startup + Duration::from_secs_f32(seconds));
+            stage.run(world);
+            let mut events = world.resource_mut::<Events<AnimationFinished>>();
+            events.drain().collect::<Vec<_>>()
+        };
+        let popup = Animated::RiseAndFade { duration: 1.0, direction: Vec3::Y };
+        let popup = world
+            .spawn()
+            .insert_bundle((popup, Transform::default(), AwaitAnimation))
+            .id();
+        let bob = (Animated::bob(0.0, 0.1, 1.0), Transform::default());
+        world.spawn().insert_bundle(bob).insert(AwaitAnimation);
+        let mut animations = SystemState::<WaitForAnimations>::new(&mut world);
+
+        assert!(animations.get_mut(&mut world).pending(), "not started yet");
+        assert!(frame_at(&mut world, 0.0).is_empty());
+        assert!(frame_at(&mut world, 1.5).is_empty(), "still fading out");
+        assert!(animations.get_mut(&mut world).pending());
+        let finished = AnimationFinished { entity: popup, kind: AnimationKind::RiseAndFade };
+        assert_eq!(frame_at(&mut world, 2.1), [finished]);
+        assert!(world.get_entity(popup).is_none());
+        let pending = animations.get_mut(&mut world).pending();
+        assert!(!pending, "bobbing never ends");
+        assert!(frame_at(&mut world, 3.0).is_empty());
+
+        let disabled = Animated::RiseAndFade { duration: 1.0, direction: Vec3::Y };
+        let disabled = world
+            .spawn()
+            .insert_bundle((disabled, Transform::default()))
+            .insert_bundle((AwaitAnimation, DisableAnimation))
+            .id();
+        assert!(!animations.get_mut(&mut world).pending(), "would never end");
+
+        world.entity_mut(disabled).remove::<DisableAnimation>();
+        assert!(animations.get_mut(&mut world).pending());
+        let finished = AnimationFinished { entity: disabled, kind: AnimationKind::RiseAndFade };
+        let mut events = world.resource_mut::<Events<AnimationFinished>>();
+        events.send(finished);
+        let pending = animations.get_mut(&mut world).pending();
+        assert!(!pending, "finished, despawned once commands apply");
+    }
+
+    #[test]
+    fn impulse_offset_fades_out() {
+        let push = |elapsed| impulse_offset(Vec3::Y, 0.0, 4.0, elapsed);
+        assert_eq!(push(0.0), Vec3::ZERO);
+        assert!(push(0.05).y < push(0.1).y);
+        assert!(push(0.1).y > 0.5);
+        assert!(push(1.5).y < 0.01);
+        let shake = impulse_offset(Vec3::X, 3.0, 4.0, 1.5);
+        assert!(shake.length() < 0.01);
+    }
+
+    #[test]
+    fn exp_approach_quat_independent_of_frame_rate() {
+        let target = Quat::from_rotation_y(1.5);
+        let step = |steps: usize| {
+            let dt = 0.2 / steps as f32;
+            (0..steps).fold(Quat::IDENTITY, |rot, _| {
+                exp_approach_quat(rot, target, 10.0, dt)
+            })
+        };
+        assert!(step(3).angle_between(step(30)) < 0.05);
+    }
+}
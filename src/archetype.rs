@@ -0,0 +1,196 @@
+//! Player deck archetypes, selected in the main menu.
+//!
+//! Archetypes are listed in [`ARCHETYPES_FILE`], each entry names a player
+//! deck file and describes it in one line. Adding an entry to the file adds
+//! an archetype, no need to recompile.
+//!
+//! The deck of every archetype is loaded as soon as the file is, so that the
+//! main menu can grey out broken ones. The [`SelectedArchetype`] is persisted
+//! with the settings, its deck replaces [`DeckAssets::player`].
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadState, LoadedAsset},
+    prelude::{Plugin as BevyPlugin, *},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::deck::{Deck, DeckAssets};
+
+const ARCHETYPES_FILE: &str = "decks/player.archetypes.ron";
+
+/// An archetype in [`ARCHETYPES_FILE`].
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct ArchetypeEntry {
+    /// Key of the archetype name in [`Strings`].
+    ///
+    /// [`Strings`]: crate::lang::Strings
+    name: String,
+    /// Key of the one line description in [`Strings`].
+    ///
+    /// [`Strings`]: crate::lang::Strings
+    description: String,
+    /// Path of the player deck, in `assets/`.
+    deck: String,
+}
+
+/// The content of [`ARCHETYPES_FILE`].
+#[derive(Debug, TypeUuid)]
+#[uuid = "9b3f2c6e-71d4-4a0f-8e5b-2d6a4c1f0e87"]
+struct Archetypes(Vec<ArchetypeEntry>);
+
+#[derive(Default)]
+struct ArchetypesLoader;
+impl AssetLoader for ArchetypesLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let entries = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(Archetypes(entries)));
+            Ok(())
+        })
+    }
+    // Not just `ron`, which is the opponents file extension
+    fn extensions(&self) -> &[&str] {
+        &["archetypes.ron"]
+    }
+}
+
+struct ArchetypesHandle(Handle<Archetypes>);
+impl FromWorld for ArchetypesHandle {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.get_resource::<AssetServer>().unwrap();
+        Self(assets.load(ARCHETYPES_FILE))
+    }
+}
+
+/// The archetype of the player deck, by its name key in [`ARCHETYPES_FILE`],
+/// so that it survives entries being added. Unknown names select the first
+/// archetype.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SelectedArchetype(pub String);
+
+/// An archetype with its loaded deck.
+pub struct Archetype {
+    pub name_key: String,
+    pub description_key: String,
+    /// Path of the player deck, in `assets/`.
+    pub deck_file: String,
+    pub deck: Handle<Deck>,
+}
+impl Archetype {
+    fn new(entry: &ArchetypeEntry, assets: &AssetServer) -> Self {
+        Self {
+            name_key: entry.name.clone(),
+            description_key: entry.description.clone(),
+            deck_file: entry.deck.clone(),
+            deck: assets.load(&entry.deck),
+        }
+    }
+    /// Whether the deck failed to load or has no cards.
+    pub fn is_broken(&self, server: &AssetServer, decks: &Assets<Deck>) -> bool {
+        match server.get_load_state(&self.deck) {
+            LoadState::Failed => true,
+            _ => decks
+                .get(&self.deck)
+                .map_or(false, |d| d.cards().is_empty()),
+        }
+    }
+}
+
+/// All the archetypes of [`ARCHETYPES_FILE`], empty until it is loaded.
+#[derive(Default)]
+pub struct ArchetypeList(Vec<Archetype>);
+impl ArchetypeList {
+    fn index_of(&self, selected: &SelectedArchetype) -> usize {
+        let found = self.0.iter().position(|a| a.name_key == selected.0);
+        found.unwrap_or(0)
+    }
+    pub fn selected(&self, selected: &SelectedArchetype) -> Option<&Archetype> {
+        self.0.get(self.index_of(selected))
+    }
+    /// The archetype `step` entries away from `selected`, wrapping around.
+    pub fn cycle(&self, selected: &SelectedArchetype, step: isize) -> Option<SelectedArchetype> {
+        let len = self.0.len() as isize;
+        if len == 0 {
+            return None;
+        }
+        let index = (self.index_of(selected) as isize + step).rem_euclid(len);
+        Some(SelectedArchetype(self.0[index as usize].name_key.clone()))
+    }
+}
+
+fn load_archetypes(
+    handle: Res<ArchetypesHandle>,
+    archetypes: Res<Assets<Archetypes>>,
+    assets: Res<AssetServer>,
+    mut list: ResMut<ArchetypeList>,
+    mut events: EventReader<AssetEvent<Archetypes>>,
+) {
+    if events.iter().count() == 0 {
+        return;
+    }
+    if let Some(Archetypes(entries)) = archetypes.get(&handle.0) {
+        list.0 = entries.iter().map(|e| Archetype::new(e, &assets)).collect();
+    }
+}
+
+fn select_archetype(
+    selected: Res<SelectedArchetype>,
+    list: Res<ArchetypeList>,
+    mut deck_assets: ResMut<DeckAssets>,
+) {
+    if !list.is_changed() && !selected.is_changed() {
+        return;
+    }
+    if let Some(archetype) = list.selected(&selected) {
+        deck_assets.set_player(&archetype.deck_file, archetype.deck.clone());
+    }
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Archetypes>()
+            .init_asset_loader::<ArchetypesLoader>()
+            .init_resource::<ArchetypesHandle>()
+            .init_resource::<ArchetypeList>()
+            .add_system(load_archetypes)
+            .add_system(select_archetype.after(load_archetypes));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archetypes_file_parses() {
+        let text = include_str!("../assets/decks/player.archetypes.ron");
+        let entries: Vec<ArchetypeEntry> = ron::de::from_str(text).unwrap();
+        assert_eq!(entries[0].deck, "decks/player.deck");
+        assert!(entries.len() > 1, "there should be several archetypes");
+    }
+
+    #[test]
+    fn cycling_wraps_around() {
+        let archetype = |name: &str| Archetype {
+            name_key: name.to_owned(),
+            description_key: String::new(),
+            deck_file: String::new(),
+            deck: Handle::default(),
+        };
+        let list = ArchetypeList(vec![archetype("a"), archetype("b"), archetype("c")]);
+        let select = |name: &str| SelectedArchetype(name.to_owned());
+        assert_eq!(list.cycle(&select("a"), 1), Some(select("b")));
+        assert_eq!(list.cycle(&select("a"), -1), Some(select("c")));
+        assert_eq!(list.cycle(&select("c"), 1), Some(select("a")));
+        let unknown = select("removed");
+        assert_eq!(list.selected(&unknown).unwrap().name_key, "a");
+        assert_eq!(list.cycle(&unknown, 1), Some(select("b")));
+        assert_eq!(ArchetypeList::default().cycle(&select("a"), 1), None);
+    }
+}
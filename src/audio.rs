@@ -2,6 +2,12 @@
 //!
 //! Defines an [`AudioRequest`] event, reads them in [`play_audio`] system
 //! using the kira backend for mixing and loudness controls.
+//!
+//! Some sound effects have alternative clips, see [`SfxPool`].
+//!
+//! Clips are decoded as they load, the loading screen waits for those a game
+//! plays early, see [`AudioAssets::warmed_up`].
+use bevy::asset::{HandleId, LoadState};
 use bevy::prelude::{Plugin as BevyPlugin, *};
 use bevy_kira_audio::prelude::{AudioChannel as KiraChannel, *};
 use enum_map::{enum_map, EnumMap};
@@ -17,21 +23,131 @@ pub enum AudioChannel {
     Sfx,
     Music,
 }
-struct ChannelVolumes {
+/// The volume of each [`AudioChannel`], set with [`AudioRequest::SetVolume`].
+pub struct ChannelVolumes {
     master: f64,
     sfx: f64,
     music: f64,
 }
+impl ChannelVolumes {
+    /// The volume of `channel`, between `0.0` and `1.0`.
+    pub fn get(&self, channel: AudioChannel) -> f64 {
+        match channel {
+            AudioChannel::Master => self.master,
+            AudioChannel::Sfx => self.sfx,
+            AudioChannel::Music => self.music,
+        }
+    }
+}
 impl Default for ChannelVolumes {
     fn default() -> Self {
         Self { master: 1.0, sfx: 0.5, music: 0.5 }
     }
 }
 
-struct AudioAssets {
-    wood_clink: Handle<AudioSource>,
-    shuffle_long: Handle<AudioSource>,
-    shuffle_short: Handle<AudioSource>,
+/// Maximum playback rate change of pooled sound effects, in both directions.
+const RATE_JITTER: f64 = 0.05;
+/// Minimum seconds between two [`AudioRequest::PlayShuffleShort`].
+const HOVER_DEBOUNCE: f64 = 0.15;
+/// Volume of the shuffle standing in for a missing sleeve return clip.
+const SOFT_VOLUME: f64 = 0.4;
+/// Volume of the word voice lines whispered by the oppo.
+const WHISPER_VOLUME: f64 = 0.15;
+
+/// Alternatives a [`SfxPool`] looks for. Files can't be listed on the web,
+/// so each is loaded, and the missing ones fail to load.
+const MAX_ALTERNATIVES: usize = 4;
+
+/// Alternative clips of a sound effect, to avoid repeating the same one.
+///
+/// Alternatives of `sfx/effect.ogg` are `sfx/effect_01.ogg`,
+/// `sfx/effect_02.ogg` etc, up to [`MAX_ALTERNATIVES`]. Until one of them is
+/// loaded, the pool plays `sfx/effect.ogg`.
+struct SfxPool {
+    main: Handle<AudioSource>,
+    alternatives: Vec<Handle<AudioSource>>,
+    /// The alternative played last.
+    last: Option<HandleId>,
+}
+impl SfxPool {
+    fn load(assets: &AssetServer, path: &str) -> Self {
+        let alternatives = alternative_paths(path)
+            .iter()
+            .map(|p| assets.load(p.as_str()))
+            .collect();
+        Self { main: assets.load(path), alternatives, last: None }
+    }
+    fn clips(&self) -> impl Iterator<Item = &Handle<AudioSource>> {
+        std::iter::once(&self.main).chain(&self.alternatives)
+    }
+    /// A loaded alternative different from the last one, if possible.
+    fn next(&mut self, server: &AssetServer) -> Handle<AudioSource> {
+        let alternatives = self.alternatives.iter();
+        let ready: Vec<_> = alternatives.filter_map(|c| loaded(server, c)).collect();
+        if ready.is_empty() {
+            return self.main.clone_weak();
+        }
+        let previous = ready.iter().position(|clip| Some(clip.id) == self.last);
+        let clip = ready[pick_clip(ready.len(), previous, fastrand::f32())].clone_weak();
+        self.last = Some(clip.id);
+        clip
+    }
+}
+
+/// Paths of the alternatives of `path`, which may not exist.
+fn alternative_paths(path: &str) -> Vec<String> {
+    let (stem, extension) = path.rsplit_once('.').unwrap_or((path, ""));
+    (1..=MAX_ALTERNATIVES)
+        .map(|i| format!("{stem}_{i:02}.{extension}"))
+        .collect()
+}
+
+/// Pick a clip index among `len`, with `roll` between 0 and 1, never the
+/// `previous` one unless it is the only one.
+fn pick_clip(len: usize, previous: Option<usize>, roll: f32) -> usize {
+    match previous {
+        Some(previous) if len > 1 => {
+            let index = ((roll * (len - 1) as f32) as usize).min(len - 2);
+            if index >= previous {
+                index + 1
+            } else {
+                index
+            }
+        }
+        _ => ((roll * len as f32) as usize).min(len.saturating_sub(1)),
+    }
+}
+
+/// `clip` once loaded, `None` while it loads or if it is missing, so that
+/// the caller can fall back to another clip.
+///
+/// Files can't be checked for on the web, so clips that may be missing are
+/// loaded all the same, and fail to load.
+fn loaded(server: &AssetServer, clip: &Handle<AudioSource>) -> Option<Handle<AudioSource>> {
+    (server.get_load_state(clip) == LoadState::Loaded).then(|| clip.clone_weak())
+}
+
+/// Whether a debounced sound can play at `now`, given when it `last` played.
+fn debounce_elapsed(last: Option<f64>, now: f64) -> bool {
+    last.map_or(true, |last| now - last >= HOVER_DEBOUNCE)
+}
+
+fn jittered_rate() -> f64 {
+    1.0 + (fastrand::f64() * 2.0 - 1.0) * RATE_JITTER
+}
+
+pub struct AudioAssets {
+    wood_clink: SfxPool,
+    shuffle_long: SfxPool,
+    shuffle_short: SfxPool,
+    bird_warning: Handle<AudioSource>,
+    jingle: Handle<AudioSource>,
+    /// Cheating clips, they may be missing, see [`loaded`].
+    sleeve_slip: Handle<AudioSource>,
+    sleeve_return: Handle<AudioSource>,
+    caught: Handle<AudioSource>,
+    seed_toss: Handle<AudioSource>,
+    grumble: Handle<AudioSource>,
     music: Handle<AudioSource>,
     words: EnumMap<WordOfPower, Handle<AudioSource>>,
 }
@@ -40,14 +156,80 @@ impl FromWorld for AudioAssets {
         let assets = world.get_resource::<AssetServer>().unwrap();
         Self {
             music: assets.load("sfx/music.ogg"),
-            shuffle_long: assets.load("sfx/shuffle_long.ogg"),
-            shuffle_short: assets.load("sfx/shuffle_short.ogg"),
-            wood_clink: assets.load("wood_clink.ogg"),
+            shuffle_long: SfxPool::load(assets, "sfx/shuffle_long.ogg"),
+            shuffle_short: SfxPool::load(assets, "sfx/shuffle_short.ogg"),
+            bird_warning: assets.load("sfx/bird_warning.wav"),
+            jingle: assets.load("sfx/jingle.wav"),
+            sleeve_slip: assets.load("sfx/sleeve_slip.wav"),
+            sleeve_return: assets.load("sfx/sleeve_return.wav"),
+            caught: assets.load("sfx/caught.wav"),
+            seed_toss: assets.load("sfx/seed_toss.wav"),
+            grumble: assets.load("sfx/grumble.wav"),
+            wood_clink: SfxPool::load(assets, "wood_clink.ogg"),
             words: enum_map! { word => assets.load(&format!("word_audio/{word:?}.ogg")) },
         }
     }
 }
 
+impl AudioAssets {
+    /// Approximate length of the voice line of `word`, in seconds.
+    pub fn word_duration(word: WordOfPower) -> f64 {
+        match word {
+            WordOfPower::Egeq => 1.4,
+            WordOfPower::Qube => 1.2,
+            WordOfPower::Zihbm => 1.6,
+            WordOfPower::Geh => 1.0,
+            WordOfPower::Het => 1.0,
+            WordOfPower::Meb => 1.1,
+        }
+    }
+    /// Clips a game plays early: the word voice lines, card shuffles and
+    /// clinks.
+    fn warm_up_clips(&self) -> impl Iterator<Item = &Handle<AudioSource>> {
+        let pools = [&self.shuffle_long, &self.shuffle_short, &self.wood_clink];
+        let pooled = pools.into_iter().flat_map(SfxPool::clips);
+        self.words.values().chain(pooled)
+    }
+    /// Whether the clips a game plays early are done loading.
+    ///
+    /// The kira loader decodes clips as it loads them, so that they play
+    /// without a hitch once loaded, rather than being decoded when first
+    /// played. Clips that failed to load don't hold the game back, they are
+    /// missing.
+    ///
+    /// Each clip is checked rather than the group: the group state is
+    /// `Failed` as soon as one failed, while others may still be loading.
+    pub fn warmed_up(&self, server: &AssetServer) -> bool {
+        let mut states = self.warm_up_clips().map(|clip| server.get_load_state(clip));
+        states.all(done_loading)
+    }
+}
+
+/// Whether a clip in `state` is loaded, or won't ever be.
+fn done_loading(state: LoadState) -> bool {
+    !matches!(state, LoadState::NotLoaded | LoadState::Loading)
+}
+
+/// Log when each clip a game plays early is loaded, and so decoded.
+///
+/// Clips start loading at startup, the time since startup is how long it
+/// took, all loading at once.
+#[cfg(feature = "debug")]
+fn log_clip_loads(
+    assets: Res<AudioAssets>,
+    server: Res<AssetServer>,
+    time: Res<Time>,
+    mut logged: Local<bevy::utils::HashSet<bevy::asset::HandleId>>,
+) {
+    for clip in assets.warm_up_clips() {
+        if server.get_load_state(clip) == LoadState::Loaded && logged.insert(clip.id) {
+            let path = server.get_handle_path(clip);
+            let elapsed = time.seconds_since_startup();
+            info!("Decoded {path:?}, {elapsed:.2}s after startup");
+        }
+    }
+}
+
 enum Music {}
 enum Sfx {}
 
@@ -59,17 +241,41 @@ pub enum AudioRequest {
     StopSfxLoop,
     PlayWoodClink(SfxParam),
     PlayWord(WordOfPower),
+    /// The oppo whispers a word it drew, its voice line barely audible.
+    PlayWhisper(WordOfPower),
     PlayShuffleLong,
+    /// A card got hovered, at most one per [`HOVER_DEBOUNCE`] seconds.
     PlayShuffleShort,
+    /// The bird squawks, it almost caught the player cheating.
+    PlayBirdWarning,
+    /// The player reached a score [`Milestone`].
+    ///
+    /// [`Milestone`]: crate::game_flow::Milestone
+    PlayJingle,
+    /// A card slipped into the player sleeve.
+    PlaySleeveSlip,
+    /// Sleeved cards slipped back into the player hand.
+    PlaySleeveReturn,
+    /// The bird caught the player cheating.
+    PlayCaught,
+    /// The player tossed seeds to distract the bird.
+    PlaySeedToss,
+    /// The oppo grumbles, annoyed the bird got distracted.
+    PlayGrumble,
+    /// A demon bark voice clip, see [`crate::barks`].
+    PlayVoice(Handle<AudioSource>),
     StartMusic,
     SetVolume(AudioChannel, f64),
 }
 fn play_audio(
-    assets: Res<AudioAssets>,
+    mut assets: ResMut<AudioAssets>,
     music: Res<KiraChannel<Music>>,
     sfx: Res<KiraChannel<Sfx>>,
     mut volumes: ResMut<ChannelVolumes>,
     mut events: EventReader<AudioRequest>,
+    mut last_hover: Local<Option<f64>>,
+    time: Res<Time>,
+    server: Res<AssetServer>,
 ) {
     for event in events.iter() {
         match event {
@@ -95,19 +301,72 @@ fn play_audio(
                 sfx.stop();
             }
             AudioRequest::PlayWoodClink(SfxParam::StartLoop) => {
-                sfx.play(assets.wood_clink.clone_weak()).looped();
+                sfx.play(assets.wood_clink.next(&server)).looped();
             }
             AudioRequest::PlayWoodClink(SfxParam::PlayOnce) => {
-                sfx.play(assets.wood_clink.clone_weak());
+                let clip = assets.wood_clink.next(&server);
+                sfx.play(clip).with_playback_rate(jittered_rate());
             }
             AudioRequest::PlayWord(word) => {
                 sfx.play(assets.words[*word].clone_weak());
             }
+            AudioRequest::PlayWhisper(word) => {
+                let clip = assets.words[*word].clone_weak();
+                sfx.play(clip).with_volume(WHISPER_VOLUME);
+            }
             AudioRequest::PlayShuffleShort => {
-                sfx.play(assets.shuffle_short.clone_weak());
+                let now = time.seconds_since_startup();
+                if !debounce_elapsed(*last_hover, now) {
+                    continue;
+                }
+                *last_hover = Some(now);
+                let clip = assets.shuffle_short.next(&server);
+                sfx.play(clip).with_playback_rate(jittered_rate());
             }
             AudioRequest::PlayShuffleLong => {
-                sfx.play(assets.shuffle_long.clone_weak());
+                let clip = assets.shuffle_long.next(&server);
+                sfx.play(clip).with_playback_rate(jittered_rate());
+            }
+            AudioRequest::PlayBirdWarning => {
+                sfx.play(assets.bird_warning.clone_weak());
+            }
+            AudioRequest::PlayJingle => {
+                sfx.play(assets.jingle.clone_weak());
+            }
+            AudioRequest::PlaySleeveSlip => {
+                let clip = match loaded(&server, &assets.sleeve_slip) {
+                    Some(clip) => clip,
+                    None => assets.shuffle_short.next(&server),
+                };
+                sfx.play(clip).with_playback_rate(jittered_rate());
+            }
+            AudioRequest::PlaySleeveReturn => match loaded(&server, &assets.sleeve_return) {
+                Some(clip) => {
+                    sfx.play(clip);
+                }
+                None => {
+                    let clip = assets.shuffle_short.next(&server);
+                    sfx.play(clip).with_volume(SOFT_VOLUME);
+                }
+            },
+            AudioRequest::PlayCaught => {
+                let clip = loaded(&server, &assets.caught);
+                sfx.play(clip.unwrap_or_else(|| assets.bird_warning.clone_weak()));
+            }
+            AudioRequest::PlaySeedToss => {
+                let clip = match loaded(&server, &assets.seed_toss) {
+                    Some(clip) => clip,
+                    None => assets.wood_clink.next(&server),
+                };
+                sfx.play(clip).with_playback_rate(jittered_rate());
+            }
+            AudioRequest::PlayGrumble => {
+                if let Some(clip) = loaded(&server, &assets.grumble) {
+                    sfx.play(clip).with_playback_rate(jittered_rate());
+                }
+            }
+            AudioRequest::PlayVoice(clip) => {
+                sfx.play(clip.clone());
             }
         }
     }
@@ -123,5 +382,47 @@ impl BevyPlugin for Plugin {
             .add_audio_channel::<Music>()
             .add_audio_channel::<Sfx>()
             .add_system(play_audio.label(AudioRequestSystem));
+
+        #[cfg(feature = "debug")]
+        app.add_system(log_clip_loads);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alternatives_follow_naming_convention() {
+        let paths = alternative_paths("sfx/hit.ogg");
+        assert_eq!(paths.len(), MAX_ALTERNATIVES);
+        assert_eq!(paths[..2], ["sfx/hit_01.ogg", "sfx/hit_02.ogg"]);
+    }
+
+    #[test]
+    fn pick_clip_never_repeats() {
+        for roll in [0.0, 0.25, 0.5, 0.75, 0.999, 1.0] {
+            assert_ne!(pick_clip(3, Some(1), roll), 1);
+            assert_ne!(pick_clip(2, Some(0), roll), 0);
+            assert!(pick_clip(3, None, roll) < 3);
+        }
+        assert_eq!(pick_clip(1, Some(0), 0.5), 0);
+    }
+
+    #[test]
+    fn failed_clips_wait_for_the_others() {
+        use LoadState::*;
+        let warmed_up = |states: &[LoadState]| states.iter().copied().all(done_loading);
+        assert!(warmed_up(&[Loaded, Failed, Unloaded]));
+        assert!(!warmed_up(&[Failed, Loading]), "a failed clip is missing");
+        assert!(!warmed_up(&[Loaded, NotLoaded]));
+    }
+
+    #[test]
+    fn hover_sounds_debounced() {
+        assert!(debounce_elapsed(None, 0.0));
+        assert!(!debounce_elapsed(Some(1.0), 1.0));
+        assert!(!debounce_elapsed(Some(1.0), 1.1));
+        assert!(debounce_elapsed(Some(1.0), 1.0 + HOVER_DEBOUNCE));
     }
 }
@@ -0,0 +1,645 @@
+//! Autosaves, to recover a game interrupted by a crash.
+//!
+//! Entering [`TurnState::New`], [`autosave_turn`] keeps the table in a ring
+//! of the last [`RING_SIZE`] turns, and writes the newest one to [`FILE`] in
+//! the background. Leaving the game marks the file as cleanly exited, the
+//! main menu offers to resume a game saved without the mark, see
+//! [`Autosaves::interrupted`]. The debug console `rewind` command goes back
+//! one turn.
+//!
+//! Only what the game needs to go on is saved: the cards, the turn and score
+//! bookkeeping of [`Progress`], the [`GameRules`] and the opponent.
+//! Animations, word announcements, the bird distraction and the oppo's
+//! thoughts start afresh.
+//!
+//! Resuming a game picks its rules and opponent back right away, then
+//! [`restore_table`] puts the [`GameSave`] back once the table is loaded,
+//! before the first draw: a resumed game skips the deck reveal and the coin
+//! flip. A rewind is put back on the player's turn. The hands are spawned by
+//! the hand plugins, reading [`RestoreHand`].
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    app::AppExit,
+    ecs::system::SystemParam,
+    prelude::{Plugin as BevyPlugin, *},
+    tasks::IoTaskPool,
+};
+
+use crate::{
+    card::SpawnCard,
+    cheat::{BirdEye, SleeveCard},
+    deck::{Deck, OppoDeck, PlayerDeck},
+    game_flow::{GameProgress, PlayedCard, Progress, TurnRequests, WordAnnouncement},
+    oppo_hand::OppoCard,
+    opponent::SelectedOpponent,
+    persist::{self, Entries},
+    pile::{Pile, PileType},
+    player_hand::HandCard,
+    rules::GameRules,
+    state::{GameState, TurnState},
+    war::Card,
+    CardOrigin, Participant,
+};
+
+const FILE: &str = "autosave.txt";
+/// Turns kept for the console `rewind` command.
+const RING_SIZE: usize = 3;
+/// Saves of another version are ignored, bump it when the format changes.
+const FORMAT_VERSION: u32 = 2;
+/// The whole autosave file once the game was left cleanly.
+const CLEAN_EXIT: &str = "clean_exit=true\n";
+
+/// Cards put back in a hand by [`restore_table`], and for the player, in
+/// the sleeve.
+pub struct RestoreHand {
+    pub who: Participant,
+    /// From the left.
+    pub hand: Vec<Card>,
+    /// In the order they entered the sleeve.
+    pub sleeve: Vec<Card>,
+}
+
+/// The table at the start of a turn.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameSave {
+    /// The first card is drawn first.
+    player_deck: Vec<Card>,
+    oppo_deck: Vec<Card>,
+    player_hand: Vec<Card>,
+    oppo_hand: Vec<Card>,
+    sleeve: Vec<Card>,
+    /// Pile cards in the order they were added, with who played them.
+    war_pile: Vec<(Participant, Card)>,
+    player_pile: Vec<(Participant, Card)>,
+    oppo_pile: Vec<(Participant, Card)>,
+    /// See [`BirdEye::sleeved_this_game`].
+    bird_sleeved: usize,
+    progress: Progress,
+    rules: GameRules,
+    /// See [`SelectedOpponent`].
+    opponent: usize,
+}
+impl GameSave {
+    /// The save in `entries`, `None` if of another [`FORMAT_VERSION`] or if
+    /// anything is missing or malformed.
+    fn from_entries(entries: &Entries) -> Option<Self> {
+        if entries.get::<u32>("version")? != FORMAT_VERSION {
+            return None;
+        }
+        let cards = |key| parse_cards(entries.get_str(key)?);
+        let pile = |key| parse_pile(entries.get_str(key)?);
+        Some(Self {
+            player_deck: cards("deck.player")?,
+            oppo_deck: cards("deck.oppo")?,
+            player_hand: cards("hand.player")?,
+            oppo_hand: cards("hand.oppo")?,
+            sleeve: cards("sleeve")?,
+            war_pile: pile("pile.war")?,
+            player_pile: pile("pile.player")?,
+            oppo_pile: pile("pile.oppo")?,
+            bird_sleeved: entries.get("bird.sleeved")?,
+            progress: Progress::from_entries(entries)?,
+            rules: GameRules::from_entries(entries)?,
+            opponent: entries.get("opponent")?,
+        })
+    }
+    fn write_entries(&self, entries: &mut Entries) {
+        entries.set("version", FORMAT_VERSION);
+        entries.set("deck.player", cards_text(&self.player_deck));
+        entries.set("deck.oppo", cards_text(&self.oppo_deck));
+        entries.set("hand.player", cards_text(&self.player_hand));
+        entries.set("hand.oppo", cards_text(&self.oppo_hand));
+        entries.set("sleeve", cards_text(&self.sleeve));
+        entries.set("pile.war", pile_text(&self.war_pile));
+        entries.set("pile.player", pile_text(&self.player_pile));
+        entries.set("pile.oppo", pile_text(&self.oppo_pile));
+        entries.set("bird.sleeved", self.bird_sleeved);
+        self.progress.write_entries(entries);
+        self.rules.write_entries(entries);
+        entries.set("opponent", self.opponent);
+    }
+    fn to_text(&self) -> String {
+        let mut entries = Entries::default();
+        self.write_entries(&mut entries);
+        entries.to_string()
+    }
+}
+
+/// Cards in deck format, separated by spaces.
+fn cards_text(cards: &[Card]) -> String {
+    let cards: Vec<_> = cards.iter().map(Card::to_string).collect();
+    cards.join(" ")
+}
+
+fn parse_cards(text: &str) -> Option<Vec<Card>> {
+    text.split_whitespace()
+        .map(|card| card.parse().ok())
+        .collect()
+}
+
+/// Pile cards as `who:card`, separated by spaces.
+fn pile_text(pile: &[(Participant, Card)]) -> String {
+    let cards: Vec<_> = pile
+        .iter()
+        .map(|(who, card)| format!("{}:{card}", who.id()))
+        .collect();
+    cards.join(" ")
+}
+
+fn parse_pile(text: &str) -> Option<Vec<(Participant, Card)>> {
+    let parse_card = |entry: &str| {
+        let (who, card) = entry.split_once(':')?;
+        Some((Participant::from_id(who)?, card.parse().ok()?))
+    };
+    text.split_whitespace().map(parse_card).collect()
+}
+
+/// The save of the autosave file `entries`, `None` if the game was left
+/// cleanly.
+fn interrupted_save(entries: &Entries) -> Option<GameSave> {
+    if entries.get("clean_exit").unwrap_or(false) {
+        return None;
+    }
+    GameSave::from_entries(entries)
+}
+
+/// The last [`RING_SIZE`] turns of the current game, oldest first.
+#[derive(Default)]
+struct Ring(VecDeque<GameSave>);
+impl Ring {
+    fn push(&mut self, save: GameSave) {
+        if self.0.len() == RING_SIZE {
+            self.0.pop_front();
+        }
+        self.0.push_back(save);
+    }
+    /// The turn before the current one, forgetting both: the restored turn
+    /// is saved again once entered.
+    fn rewind(&mut self) -> Option<GameSave> {
+        if self.0.len() < 2 {
+            return None;
+        }
+        self.0.pop_back();
+        self.0.pop_back()
+    }
+}
+
+/// The autosaves of the current game, and the game to resume, if any.
+#[derive(Default)]
+pub struct Autosaves {
+    ring: Ring,
+    /// Numbers the file writes, so that a slow one never overwrites a newer
+    /// one.
+    sequence: u64,
+    /// Sequence of the last write to [`FILE`].
+    written: Arc<Mutex<u64>>,
+    /// The game of the previous session, not cleanly exited.
+    interrupted: Option<GameSave>,
+    /// The save [`restore_table`] puts back: a resumed game once the table
+    /// is loaded, a rewind on the next player turn.
+    pending: Option<GameSave>,
+}
+impl Autosaves {
+    fn load() -> Self {
+        let interrupted = match persist::load(FILE) {
+            Ok(entries) => interrupted_save(&entries),
+            Err(err) => {
+                warn!("Can't check for an interrupted game: {err:#}");
+                None
+            }
+        };
+        Self { interrupted, ..default() }
+    }
+    /// Whether the previous session ended on a game still going.
+    pub fn interrupted(&self) -> bool {
+        self.interrupted.is_some()
+    }
+    /// Resume the interrupted game once the next one starts, with the
+    /// `rules` and `opponent` it was played with.
+    pub fn resume(&mut self, rules: &mut GameRules, opponent: &mut SelectedOpponent) {
+        if let Some(save) = self.interrupted.take() {
+            *rules = save.rules.clone();
+            opponent.0 = save.opponent;
+            self.pending = Some(save);
+        }
+    }
+    /// Whether a save is waiting for [`restore_table`], the table is about
+    /// to be replaced.
+    pub fn restoring(&self) -> bool {
+        self.pending.is_some()
+    }
+    /// Forget the interrupted game, it isn't offered again.
+    pub fn discard(&mut self) {
+        self.interrupted = None;
+        self.mark_clean();
+    }
+    /// Go back to the start of the previous turn, on the next player turn.
+    /// Returns whether there was one.
+    pub fn rewind(&mut self) -> bool {
+        self.pending = self.ring.rewind();
+        self.pending.is_some()
+    }
+    fn push(&mut self, save: GameSave) {
+        // A game started without answering the recovery dialog overwrites
+        // the interrupted one
+        self.interrupted = None;
+        self.write_async(save.to_text());
+        self.ring.push(save);
+    }
+    /// Mark the file as cleanly exited, in the background.
+    fn mark_clean(&mut self) {
+        self.write_async(CLEAN_EXIT.to_owned());
+    }
+    /// Mark the file as cleanly exited before returning, for when the app
+    /// exits. Writes still running in the background are dropped.
+    fn mark_clean_now(&self) {
+        let mut written = self.written.lock().unwrap_or_else(|err| err.into_inner());
+        if let Err(err) = persist::write(FILE, CLEAN_EXIT) {
+            warn!("Can't mark the autosave as cleanly exited: {err:#}");
+        }
+        *written = u64::MAX;
+    }
+    /// Write `text` to [`FILE`] on the IO task pool. A write failing or
+    /// overtaken by a newer one is skipped, the next turn writes again.
+    fn write_async(&mut self, text: String) {
+        self.sequence += 1;
+        let sequence = self.sequence;
+        let written = Arc::clone(&self.written);
+        let write = async move {
+            let mut written = written.lock().unwrap_or_else(|err| err.into_inner());
+            if *written > sequence {
+                return;
+            }
+            match persist::write(FILE, &text) {
+                Ok(()) => *written = sequence,
+                Err(err) => warn!("Missed an autosave: {err:#}"),
+            }
+        };
+        IoTaskPool::get().spawn(write).detach();
+    }
+}
+
+/// What [`GameSave`] is made of.
+#[derive(SystemParam)]
+struct Table<'w, 's> {
+    progress: GameProgress<'w, 's>,
+    bird: Res<'w, BirdEye>,
+    rules: Res<'w, GameRules>,
+    opponent: Res<'w, SelectedOpponent>,
+    cards: Query<'w, 's, (&'static Card, &'static CardOrigin)>,
+    player_hand: Query<'w, 's, (&'static Card, &'static HandCard)>,
+    oppo_hand: Query<'w, 's, (&'static Card, &'static OppoCard)>,
+    sleeve: Query<'w, 's, (&'static Card, &'static SleeveCard)>,
+    piles: Query<'w, 's, &'static Pile>,
+    player_deck: Query<'w, 's, &'static PlayerDeck>,
+    oppo_deck: Query<'w, 's, &'static OppoDeck>,
+}
+impl<'w, 's> Table<'w, 's> {
+    /// The save of the table, `None` while the decks aren't loaded.
+    fn save(&self) -> Option<GameSave> {
+        let player_deck = self.player_deck.get_single().ok()?;
+        let oppo_deck = self.oppo_deck.get_single().ok()?;
+        let in_order = |mut cards: Vec<(&Card, usize)>| {
+            cards.sort_by_key(|(_, index)| *index);
+            cards.into_iter().map(|(card, _)| card.clone()).collect()
+        };
+        // Piles are read from their stack rather than the `PileCard`s, which
+        // lag a frame behind when a battle is resolved
+        let pile = |which: PileType| {
+            let stack = self.piles.iter().find(|pile| pile.which == which);
+            let stack = stack.map_or(&[][..], Pile::cards).iter();
+            stack
+                .filter_map(|entity| pile_card(&self.cards, *entity))
+                .collect()
+        };
+        let player_hand = self.player_hand.iter().map(|(c, h)| (c, h.index()));
+        let oppo_hand = self.oppo_hand.iter().map(|(c, h)| (c, h.index()));
+        let sleeve = self.sleeve.iter().map(|(c, s)| (c, s.index()));
+        Some(GameSave {
+            player_deck: player_deck.cards().iter().rev().cloned().collect(),
+            oppo_deck: oppo_deck.cards().iter().rev().cloned().collect(),
+            player_hand: in_order(player_hand.collect()),
+            oppo_hand: in_order(oppo_hand.collect()),
+            sleeve: in_order(sleeve.collect()),
+            war_pile: pile(PileType::War),
+            player_pile: pile(PileType::Player),
+            oppo_pile: pile(PileType::Oppo),
+            bird_sleeved: self.bird.sleeved_this_game,
+            progress: self.progress.progress(),
+            rules: self.rules.clone(),
+            opponent: self.opponent.0,
+        })
+    }
+}
+
+/// Save the table as the turn starts, before [`crate::game_flow`] passes
+/// the initiative.
+///
+/// Nothing is saved while a save waits for [`restore_table`], it would
+/// overwrite the one about to be put back.
+fn autosave_turn(mut autosaves: ResMut<Autosaves>, table: Table) {
+    if autosaves.restoring() {
+        return;
+    }
+    if let Some(save) = table.save() {
+        autosaves.push(save);
+    }
+}
+
+/// The card `entity`, with who played it.
+fn pile_card(cards: &Query<(&Card, &CardOrigin)>, entity: Entity) -> Option<(Participant, Card)> {
+    let (card, origin) = cards.get(entity).ok()?;
+    Some((origin.0, card.clone()))
+}
+
+/// Replace the table with the pending save of [`Autosaves`], then start the
+/// turn again.
+///
+/// A resumed game is put back before the first draw, once its decks are
+/// loaded, a rewind on the player's turn. All cards are despawned, even those
+/// on their way to a pile, and the pile cards of the save are spawned anew.
+/// Cards of the war pile wait for the other card of their battle, as if just
+/// played.
+fn restore_table(
+    mut autosaves: ResMut<Autosaves>,
+    mut spawner: SpawnCard,
+    mut piles: Query<&mut Pile>,
+    mut player_deck: Query<&mut PlayerDeck>,
+    mut oppo_deck: Query<&mut OppoDeck>,
+    mut progress: GameProgress,
+    mut bird: ResMut<BirdEye>,
+    mut turn: ResMut<TurnRequests>,
+    mut hands: EventWriter<RestoreHand>,
+    cards: Query<Entity, With<Card>>,
+    state: Res<State<TurnState>>,
+) {
+    let at_rest = matches!(state.current(), TurnState::Starting | TurnState::Player);
+    let loaded = !player_deck.is_empty() && !oppo_deck.is_empty() && !piles.is_empty();
+    if !at_rest || !loaded {
+        return;
+    }
+    let save = match autosaves.pending.take() {
+        Some(save) => save,
+        None => return,
+    };
+    for card in cards.iter() {
+        spawner.cmds.entity(card).despawn_recursive();
+    }
+    spawner.cmds.remove_resource::<WordAnnouncement>();
+    // Set in place rather than inserted, so that the deck points aren't
+    // added to the scores again, see `track_deck_scores`
+    if let Ok(mut deck) = player_deck.get_single_mut() {
+        *deck = PlayerDeck::new(Deck::new(save.player_deck.clone()));
+    }
+    if let Ok(mut deck) = oppo_deck.get_single_mut() {
+        *deck = OppoDeck::new(Deck::new(save.oppo_deck.clone()));
+    }
+    for mut pile in piles.iter_mut() {
+        pile.clear();
+        let cards = match pile.which {
+            PileType::War => &save.war_pile,
+            PileType::Player => &save.player_pile,
+            PileType::Oppo => &save.oppo_pile,
+        };
+        for (who, card) in cards {
+            let mut entity = spawner.spawn_card(card.clone(), *who);
+            let id = entity.id();
+            entity.insert(pile.add_existing(id));
+            if pile.which == PileType::War {
+                entity.insert(PlayedCard::restored());
+            }
+        }
+    }
+    progress.restore(&save.progress);
+    bird.sleeved_this_game = save.bird_sleeved;
+    hands.send(RestoreHand {
+        who: Participant::Player,
+        hand: save.player_hand.clone(),
+        sleeve: save.sleeve.clone(),
+    });
+    hands.send(RestoreHand {
+        who: Participant::Oppo,
+        hand: save.oppo_hand.clone(),
+        sleeve: Vec::new(),
+    });
+    turn.request(TurnState::New);
+}
+
+/// The game is over, it won't be offered for recovery.
+fn forget_game(mut autosaves: ResMut<Autosaves>) {
+    autosaves.mark_clean();
+    autosaves.ring = Ring::default();
+    autosaves.pending = None;
+}
+
+fn mark_clean_on_exit(mut exits: EventReader<AppExit>, autosaves: Res<Autosaves>) {
+    if exits.iter().count() != 0 {
+        autosaves.mark_clean_now();
+    }
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        app.insert_resource(Autosaves::load())
+            .add_event::<RestoreHand>()
+            .add_system_set(TurnState::New.on_enter(autosave_turn.before("new_turn")))
+            .add_system_set(
+                self.0.on_update(
+                    restore_table
+                        .label("restore_table")
+                        .after("track_deck_scores"),
+                ),
+            )
+            .add_system_set(self.0.on_exit(forget_game))
+            .add_system_to_stage(CoreStage::Last, mark_clean_on_exit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::AssetPlugin, core::CorePlugin, ecs::system::SystemState};
+
+    use super::*;
+    use crate::{
+        card::{CardAssets, OppoCardSpawner, PlayerCardSpawner},
+        game_flow::{
+            BattleCount, CoopTurn, Initiative, Inventory, OppoLead, ScoreBonuses, Scores,
+            SeedCount, TurnCount,
+        },
+        rules::WinCondition,
+        war::Value,
+    };
+
+    fn cards(text: &str) -> Vec<Card> {
+        parse_cards(text).unwrap()
+    }
+
+    fn progress(turn: usize) -> Progress {
+        let text = format!(
+            "turn={turn}\nbattles=2\nlast_outcome=tie\ndeck_size.player=12\n\
+             deck_size.oppo=12\nleader=oppo\ncoop_active=second\nseeds.first=1\n\
+             seeds.second=0\ntrinkets.first=0\ntrinkets.second=2\nscore.player=14\n\
+             score.oppo=9\nscore.remaining=60\nbonus.player=3\nbonus.oppo=-1\n"
+        );
+        Progress::from_entries(&Entries::parse(&text)).unwrap()
+    }
+
+    fn save(turn: usize) -> GameSave {
+        use Participant::{Oppo, Player};
+        GameSave {
+            player_deck: cards("3_ 9z 0s"),
+            oppo_deck: cards("7meb 2_"),
+            player_hand: cards("5_ 8het"),
+            oppo_hand: cards("1d 4_ 6_"),
+            sleeve: cards("9w"),
+            war_pile: vec![(Oppo, cards("5z").remove(0))],
+            player_pile: vec![
+                (Player, cards("8_").remove(0)),
+                (Oppo, cards("2s").remove(0)),
+            ],
+            oppo_pile: Vec::new(),
+            bird_sleeved: 1,
+            progress: progress(turn),
+            rules: GameRules {
+                coop: true,
+                win_condition: WinCondition::CollectSet(vec![Value::Zero; 3]),
+                ..default()
+            },
+            opponent: 1,
+        }
+    }
+
+    #[test]
+    fn saves_roundtrip_through_text() {
+        let text = save(5).to_text();
+        assert_eq!(
+            GameSave::from_entries(&Entries::parse(&text)),
+            Some(save(5))
+        );
+        let empty_hands = GameSave {
+            player_hand: Vec::new(),
+            sleeve: Vec::new(),
+            ..save(5)
+        };
+        let text = empty_hands.to_text();
+        let restored = GameSave::from_entries(&Entries::parse(&text));
+        assert_eq!(restored, Some(empty_hands));
+    }
+
+    #[test]
+    fn only_interrupted_games_are_recovered() {
+        let text = save(5).to_text();
+        assert_eq!(interrupted_save(&Entries::parse(&text)), Some(save(5)));
+        assert_eq!(interrupted_save(&Entries::parse(CLEAN_EXIT)), None);
+        let other_version = text.replace("version=2", "version=1");
+        assert_eq!(interrupted_save(&Entries::parse(&other_version)), None);
+        let malformed = text.replace("oppo:5z", "dealer:5z");
+        assert_eq!(interrupted_save(&Entries::parse(&malformed)), None);
+    }
+
+    #[test]
+    fn ring_rewinds_to_the_previous_turn() {
+        let mut ring = Ring::default();
+        assert_eq!(ring.rewind(), None);
+        for turn in 1..=4 {
+            ring.push(save(turn));
+        }
+        assert_eq!(ring.0.len(), RING_SIZE);
+        assert_eq!(ring.rewind(), Some(save(3)));
+        assert_eq!(ring.rewind(), None, "turn 2 is the only one left");
+        ring.push(save(3));
+        assert_eq!(ring.rewind(), Some(save(2)));
+    }
+
+    /// A table like the game scene's before the first draw, without the hand
+    /// plugins, running [`restore_table`].
+    fn table_app() -> App {
+        let mut app = App::new();
+        app.add_plugin(CorePlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Mesh>()
+            .add_asset::<Image>()
+            .add_asset::<StandardMaterial>()
+            .init_resource::<CardAssets>()
+            .init_resource::<GameRules>()
+            .init_resource::<SelectedOpponent>()
+            .init_resource::<TurnCount>()
+            .init_resource::<BattleCount>()
+            .init_resource::<Initiative>()
+            .init_resource::<CoopTurn>()
+            .init_resource::<SeedCount>()
+            .init_resource::<Inventory>()
+            .init_resource::<Scores>()
+            .init_resource::<ScoreBonuses>()
+            .init_resource::<OppoLead>()
+            .init_resource::<BirdEye>()
+            .init_resource::<TurnRequests>()
+            .init_resource::<Autosaves>()
+            .insert_resource(State::new(TurnState::Starting))
+            .add_event::<RestoreHand>()
+            .add_system(restore_table);
+        let world = &mut app.world;
+        world
+            .spawn()
+            .insert_bundle((PlayerCardSpawner, GlobalTransform::default()));
+        world
+            .spawn()
+            .insert_bundle((OppoCardSpawner, GlobalTransform::default()));
+        for which in [PileType::War, PileType::Player, PileType::Oppo] {
+            world.spawn().insert(Pile::new(which));
+        }
+        let deck = |cards: &str| Deck::new(parse_cards(cards).unwrap());
+        world.spawn().insert(PlayerDeck::new(deck("1_ 2_ 3_")));
+        world.spawn().insert(OppoDeck::new(deck("4_ 5_ 6_")));
+        app
+    }
+
+    fn table_save(app: &mut App) -> GameSave {
+        let mut table = SystemState::<Table>::new(&mut app.world);
+        let save = table.get_mut(&mut app.world).save();
+        save.expect("the decks are loaded")
+    }
+
+    #[test]
+    fn restored_tables_are_saved_the_same() {
+        let mut app = table_app();
+        let resume = |app: &mut App, save| {
+            app.world.resource_mut::<Autosaves>().pending = Some(save);
+            app.update();
+            assert!(!app.world.resource::<Autosaves>().restoring());
+        };
+        resume(&mut app, save(5));
+        // The hands are spawned by the hand plugins
+        let events = app.world.resource::<Events<RestoreHand>>();
+        let hands: Vec<_> = events
+            .get_reader()
+            .iter(events)
+            .map(|r| r.hand.clone())
+            .collect();
+        assert_eq!(hands, [save(5).player_hand, save(5).oppo_hand]);
+        let no_hands = GameSave {
+            player_hand: Vec::new(),
+            oppo_hand: Vec::new(),
+            sleeve: Vec::new(),
+            ..save(5)
+        };
+        let saved = table_save(&mut app);
+        assert_eq!(saved, no_hands);
+
+        *app.world.resource_mut::<State<TurnState>>() = State::new(TurnState::Player);
+        resume(&mut app, saved.clone());
+        assert_eq!(table_save(&mut app), saved, "a rewind to the same table");
+
+        *app.world.resource_mut::<State<TurnState>>() = State::new(TurnState::Draw);
+        app.world.resource_mut::<Autosaves>().pending = Some(save(6));
+        app.update();
+        let autosaves = app.world.resource::<Autosaves>();
+        assert!(autosaves.restoring(), "waits for the player's turn");
+    }
+}
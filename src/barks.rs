@@ -0,0 +1,463 @@
+//! Table talk, short barks of the demon reacting to game events.
+//!
+//! Barks are listed per language in `assets/lang/<code>.barks.ron`, a map
+//! from [`BarkEvent`] to lines, so that writers can iterate without
+//! recompiling. A line shows in a speech bubble next to the demon head,
+//! projected on screen with [`project`], and plays its voice clip if it
+//! loaded.
+//!
+//! The [`Barker`] waits [`BARK_COOLDOWN`] seconds between two barks, so that
+//! they never overlap, and goes through all the lines of an event before
+//! repeating one.
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadState, LoadedAsset},
+    prelude::{Plugin as BevyPlugin, *},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use bevy_kira_audio::prelude::AudioSource;
+use enum_map::{Enum, EnumMap};
+use serde::Deserialize;
+
+use crate::{
+    audio::AudioRequest,
+    cheat::{CheatEvent, Distraction},
+    cleanup_marked,
+    game_flow::{BattleResolved, Scores},
+    lang::Language,
+    oppo_mood::OppoHead,
+    rules::GameRules,
+    state::GameState,
+    war::BattleOutcome,
+};
+
+/// Minimum seconds between two barks.
+const BARK_COOLDOWN: f64 = 6.0;
+/// Seconds a speech bubble stays on screen, shorter than [`BARK_COOLDOWN`].
+const BUBBLE_DURATION: f32 = 3.5;
+const BUBBLE_WIDTH: f32 = 260.0;
+/// Expected height of a bubble, to keep it on screen.
+const BUBBLE_HEIGHT: f32 = 70.0;
+/// Screen offset of the bubble bottom left corner from the demon head.
+const BUBBLE_OFFSET: Vec2 = Vec2::new(60.0, 40.0);
+/// Minimum distance between the bubble and the window edges.
+const SCREEN_MARGIN: f32 = 10.0;
+
+/// A game event the demon has something to say about.
+#[derive(Clone, Copy, Debug, Deserialize, Enum, PartialEq, Eq, Hash)]
+pub enum BarkEvent {
+    /// The demon lost a battle by a trinket-worthy margin.
+    BigLoss,
+    /// The player tossed seeds to the bird.
+    SeedUsed,
+    /// A battle ended in a tie.
+    Tie,
+    /// The player is one good battle away from winning, see
+    /// [`is_match_point`].
+    MatchPoint,
+}
+
+/// A line of the demon.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+struct BarkLine {
+    text: String,
+    /// Path of the voice clip, in `assets/`. Lines with a clip missing or
+    /// still loading are text only.
+    #[serde(default)]
+    voice: Option<String>,
+}
+
+/// The content of a `.barks.ron` file.
+#[derive(Debug, TypeUuid)]
+#[uuid = "5c1e8a4d-2f67-4b93-a0d8-7e3b9f6c2a15"]
+struct Barks(HashMap<BarkEvent, Vec<BarkLine>>);
+
+#[derive(Default)]
+struct BarksLoader;
+impl AssetLoader for BarksLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let lines = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(Barks(lines)));
+            Ok(())
+        })
+    }
+    // Not just `ron`, which is the opponents file extension
+    fn extensions(&self) -> &[&str] {
+        &["barks.ron"]
+    }
+}
+
+fn barks_file(language: Language) -> String {
+    format!("lang/{}.barks.ron", language.code())
+}
+
+/// The barks of the current [`Language`], and the voice clips they name.
+#[derive(Default)]
+struct BarkAssets {
+    barks: Handle<Barks>,
+    voices: HashMap<String, Handle<AudioSource>>,
+}
+
+/// Picks the bark lines, see the module doc.
+#[derive(Default)]
+struct Barker {
+    /// When the last bark was said, in seconds since startup.
+    last_bark: Option<f64>,
+    /// Indices of the lines of each event not said yet.
+    pools: EnumMap<BarkEvent, Vec<usize>>,
+    /// Index of the line said last for each event.
+    last_line: EnumMap<BarkEvent, Option<usize>>,
+}
+impl Barker {
+    /// Index of the line to say for `event` at `now`, among `line_count`
+    /// lines. `None` while cooling down from the last bark.
+    ///
+    /// When all the lines were said, they are all back in the pool but the
+    /// last one, so that it is never said twice in a row.
+    fn pick(
+        &mut self,
+        event: BarkEvent,
+        line_count: usize,
+        now: f64,
+        rng: &fastrand::Rng,
+    ) -> Option<usize> {
+        let cooling_down = self
+            .last_bark
+            .map_or(false, |last| now - last < BARK_COOLDOWN);
+        if cooling_down || line_count == 0 {
+            return None;
+        }
+        let last_line = self.last_line[event];
+        let pool = &mut self.pools[event];
+        // The lines may have changed since the pool was filled
+        if pool.is_empty() || pool.iter().any(|i| *i >= line_count) {
+            let fresh = |i: &usize| line_count == 1 || Some(*i) != last_line;
+            *pool = (0..line_count).filter(fresh).collect();
+        }
+        let index = pool.swap_remove(rng.usize(..pool.len()));
+        self.last_line[event] = Some(index);
+        self.last_bark = Some(now);
+        Some(index)
+    }
+}
+
+/// Random source of the bark lines.
+struct BarkRng(fastrand::Rng);
+impl Default for BarkRng {
+    fn default() -> Self {
+        Self(fastrand::Rng::new())
+    }
+}
+
+/// Whether [`BarkEvent::MatchPoint`] was said this game, it is only said
+/// once.
+#[derive(Default)]
+struct MatchPointSaid(bool);
+
+#[derive(Component)]
+struct Bubble {
+    remaining: f32,
+}
+
+/// Screen position of `world` seen through `view_proj`, the camera
+/// projection times its inverse transform, in a `viewport` of that size.
+///
+/// The origin is the bottom left corner, like UI positions. `None` if
+/// `world` is behind the camera.
+fn project(view_proj: Mat4, world: Vec3, viewport: Vec2) -> Option<Vec2> {
+    let clip = view_proj * world.extend(1.0);
+    if clip.w <= 0.0 {
+        return None;
+    }
+    let ndc = clip.truncate().truncate() / clip.w;
+    Some((ndc + Vec2::ONE) / 2.0 * viewport)
+}
+
+/// Bottom left corner of a bubble next to the `head` screen position, kept
+/// inside a `window` of that size.
+fn bubble_corner(head: Vec2, window: Vec2) -> Vec2 {
+    let max = window - Vec2::new(BUBBLE_WIDTH, BUBBLE_HEIGHT) - SCREEN_MARGIN;
+    let corner = head + BUBBLE_OFFSET;
+    corner.min(max).max(Vec2::splat(SCREEN_MARGIN))
+}
+
+/// Whether the player lead is more than half the points still in play, but
+/// not enough to have already won.
+fn is_match_point(scores: &Scores) -> bool {
+    let lead = scores.player() - scores.oppo();
+    let remaining = scores.remaining();
+    lead > 0 && lead <= remaining && 2 * lead > remaining
+}
+
+/// Load the barks of the current language, and the voice clips they name.
+fn load_barks(
+    language: Res<Language>,
+    server: Res<AssetServer>,
+    barks: Res<Assets<Barks>>,
+    mut assets: ResMut<BarkAssets>,
+    mut events: EventReader<AssetEvent<Barks>>,
+) {
+    if language.is_changed() {
+        assets.barks = server.load(&barks_file(*language));
+    }
+    if events.iter().count() == 0 {
+        return;
+    }
+    let lines = match barks.get(&assets.barks) {
+        Some(Barks(lines)) => lines.values().flatten(),
+        None => return,
+    };
+    // Files can't be checked for on the web, missing clips fail to load
+    let voices = lines.filter_map(|line| line.voice.as_ref());
+    assets.voices = voices
+        .map(|p| (p.clone(), server.load(p.as_str())))
+        .collect();
+}
+
+fn trigger_barks(
+    mut battles: EventReader<BattleResolved>,
+    mut cheats: EventReader<CheatEvent>,
+    mut barks: EventWriter<BarkEvent>,
+    mut match_point: ResMut<MatchPointSaid>,
+    scores: Res<Scores>,
+    rules: Res<GameRules>,
+) {
+    for battle in battles.iter() {
+        match battle.outcome {
+            BattleOutcome::Tie => barks.send(BarkEvent::Tie),
+            BattleOutcome::Win if battle.margin >= rules.trinket_margin => {
+                barks.send(BarkEvent::BigLoss);
+            }
+            _ => {}
+        }
+    }
+    for cheat in cheats.iter() {
+        if let CheatEvent::ConfuseBird(Distraction::Seed) = cheat {
+            barks.send(BarkEvent::SeedUsed);
+        }
+    }
+    if scores.is_changed() && !match_point.0 && is_match_point(&scores) {
+        match_point.0 = true;
+        barks.send(BarkEvent::MatchPoint);
+    }
+}
+
+fn say_barks(
+    mut cmds: Commands,
+    mut events: EventReader<BarkEvent>,
+    mut barker: ResMut<Barker>,
+    mut audio: EventWriter<AudioRequest>,
+    rng: Res<BarkRng>,
+    assets: Res<BarkAssets>,
+    barks: Res<Assets<Barks>>,
+    server: Res<AssetServer>,
+    ui_assets: Res<crate::ui::Assets>,
+    time: Res<Time>,
+) {
+    let Barks(all_lines) = match barks.get(&assets.barks) {
+        Some(barks) => barks,
+        None => return,
+    };
+    let now = time.seconds_since_startup();
+    for event in events.iter() {
+        let lines = all_lines.get(event).map_or(&[][..], |l| l.as_slice());
+        let line = match barker.pick(*event, lines.len(), now, &rng.0) {
+            Some(index) => &lines[index],
+            None => continue,
+        };
+        let voice = line.voice.as_ref().and_then(|p| assets.voices.get(p));
+        let voice = voice.filter(|v| server.get_load_state(*v) == LoadState::Loaded);
+        if let Some(voice) = voice {
+            audio.send(AudioRequest::PlayVoice(voice.clone_weak()));
+        }
+        let style = TextStyle {
+            color: Color::BLACK,
+            font: ui_assets.font.clone(),
+            font_size: 26.0,
+        };
+        let mut text = TextBundle::from_section(&line.text, style);
+        text.style.max_size = Size::new(Val::Px(BUBBLE_WIDTH), Val::Undefined);
+        let node = NodeBundle {
+            color: Color::rgba(0.95, 0.92, 0.85, 0.9).into(),
+            style: Style {
+                position_type: PositionType::Absolute,
+                size: Size::new(Val::Px(BUBBLE_WIDTH), Val::Undefined),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            // Hidden until placed by `follow_head`
+            visibility: Visibility { is_visible: false },
+            ..default()
+        };
+        cmds.spawn_bundle(node)
+            .insert_bundle((
+                Name::new("Bark bubble"),
+                Bubble { remaining: BUBBLE_DURATION },
+            ))
+            .with_children(|cmds| {
+                cmds.spawn_bundle(text);
+            });
+    }
+}
+
+/// Keep the bubbles next to the demon head, despawn them once they are done.
+fn follow_head(
+    mut cmds: Commands,
+    mut bubbles: Query<(Entity, &mut Bubble, &mut Style, &mut Visibility)>,
+    cameras: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    head: Query<&GlobalTransform, With<OppoHead>>,
+    windows: Res<Windows>,
+    time: Res<Time>,
+) {
+    let window = windows
+        .get_primary()
+        .map(|w| Vec2::new(w.width(), w.height()));
+    let head_on_screen = || {
+        let (camera, camera_transform) = cameras.get_single().ok()?;
+        let view_proj = camera.projection_matrix() * camera_transform.compute_matrix().inverse();
+        let head = head.get_single().ok()?.translation();
+        project(view_proj, head, camera.logical_viewport_size()?)
+    };
+    let corner = head_on_screen()
+        .zip(window)
+        .map(|(head, window)| bubble_corner(head, window));
+    for (entity, mut bubble, mut style, mut visibility) in bubbles.iter_mut() {
+        bubble.remaining -= time.delta_seconds();
+        if bubble.remaining <= 0.0 {
+            cmds.entity(entity).despawn_recursive();
+            continue;
+        }
+        visibility.is_visible = corner.is_some();
+        if let Some(corner) = corner {
+            style.position.left = Val::Px(corner.x);
+            style.position.bottom = Val::Px(corner.y);
+        }
+    }
+}
+
+fn reset_match_point(mut match_point: ResMut<MatchPointSaid>) {
+    match_point.0 = false;
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        app.add_asset::<Barks>()
+            .init_asset_loader::<BarksLoader>()
+            .init_resource::<BarkAssets>()
+            .init_resource::<Barker>()
+            .init_resource::<BarkRng>()
+            .init_resource::<MatchPointSaid>()
+            .add_event::<BarkEvent>()
+            .add_system(load_barks)
+            .add_system_set(
+                self.0
+                    .on_update(trigger_barks)
+                    .with_system(say_barks.after(trigger_barks))
+                    .with_system(follow_head.after(say_barks)),
+            )
+            .add_system_set(self.0.on_enter(reset_match_point))
+            .add_system_set(self.0.on_exit(cleanup_marked::<Bubble>));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn barks_files_parse() {
+        let files = [
+            include_str!("../assets/lang/en.barks.ron"),
+            include_str!("../assets/lang/fr.barks.ron"),
+        ];
+        let events = [
+            BarkEvent::BigLoss,
+            BarkEvent::SeedUsed,
+            BarkEvent::Tie,
+            BarkEvent::MatchPoint,
+        ];
+        for text in files {
+            let lines: HashMap<BarkEvent, Vec<BarkLine>> = ron::de::from_str(text).unwrap();
+            for event in events {
+                let count = lines.get(&event).map_or(0, Vec::len);
+                assert!(count > 1, "{event:?} should have several lines");
+            }
+        }
+    }
+
+    #[test]
+    fn barks_cool_down() {
+        let rng = fastrand::Rng::with_seed(3);
+        let mut barker = Barker::default();
+        assert!(barker.pick(BarkEvent::Tie, 3, 10.0, &rng).is_some());
+        let soon = 10.0 + BARK_COOLDOWN / 2.0;
+        assert_eq!(barker.pick(BarkEvent::SeedUsed, 3, soon, &rng), None);
+        let later = 10.0 + BARK_COOLDOWN;
+        assert!(barker.pick(BarkEvent::SeedUsed, 3, later, &rng).is_some());
+        let mut silent = Barker::default();
+        assert_eq!(silent.pick(BarkEvent::Tie, 0, 0.0, &rng), None);
+        assert!(silent.last_bark.is_none(), "no line, no cooldown");
+    }
+
+    #[test]
+    fn barks_go_through_the_pool() {
+        let rng = fastrand::Rng::with_seed(11);
+        let mut barker = Barker::default();
+        let mut said = Vec::new();
+        for i in 0..30 {
+            let now = i as f64 * BARK_COOLDOWN;
+            said.push(barker.pick(BarkEvent::BigLoss, 4, now, &rng).unwrap());
+        }
+        let mut first_round = said[..4].to_vec();
+        first_round.sort_unstable();
+        assert_eq!(first_round, [0, 1, 2, 3]);
+        for pair in said.windows(2) {
+            assert_ne!(pair[0], pair[1], "a line is never said twice in a row");
+        }
+        let mut single = Barker::default();
+        assert_eq!(single.pick(BarkEvent::Tie, 1, 0.0, &rng), Some(0));
+        assert_eq!(single.pick(BarkEvent::Tie, 1, BARK_COOLDOWN, &rng), Some(0));
+    }
+
+    #[test]
+    fn projection_matches_viewport() {
+        let viewport = Vec2::new(800.0, 600.0);
+        let center = project(Mat4::IDENTITY, Vec3::new(0.0, 0.0, 0.5), viewport);
+        assert_eq!(center, Some(viewport / 2.0));
+        let corner = project(Mat4::IDENTITY, Vec3::new(1.0, 1.0, 0.5), viewport);
+        assert_eq!(corner, Some(viewport));
+
+        let proj = Mat4::perspective_infinite_reverse_rh(1.0, 4.0 / 3.0, 0.1);
+        let view = Mat4::from_translation(Vec3::new(0.0, 0.0, 5.0)).inverse();
+        let view_proj = proj * view;
+        let ahead = project(view_proj, Vec3::ZERO, viewport).unwrap();
+        assert!((ahead - viewport / 2.0).length() < 1e-3, "{ahead}");
+        let up = project(view_proj, Vec3::Y, viewport).unwrap();
+        assert!(up.y > ahead.y, "up in the world is up on screen");
+        assert_eq!(
+            project(view_proj, Vec3::new(0.0, 0.0, 10.0), viewport),
+            None
+        );
+    }
+
+    #[test]
+    fn bubble_stays_on_screen() {
+        let window = Vec2::new(800.0, 600.0);
+        let head = Vec2::new(300.0, 300.0);
+        assert_eq!(bubble_corner(head, window), head + BUBBLE_OFFSET);
+        let corner = bubble_corner(Vec2::new(790.0, 590.0), window);
+        let top_right = window - Vec2::new(BUBBLE_WIDTH, BUBBLE_HEIGHT) - SCREEN_MARGIN;
+        assert_eq!(corner, top_right);
+        let corner = bubble_corner(Vec2::new(-500.0, -500.0), window);
+        assert_eq!(corner, Vec2::splat(SCREEN_MARGIN));
+    }
+}
@@ -4,9 +4,16 @@
 //! proper graphical objects attached to it. [`SpawnCard`] uses assets defined
 //! in [`CardAssets`].
 //!
-//! The only system here is [`update_card_graphics`].
-use std::f32::consts::{FRAC_PI_2, PI};
+//! [`update_card_graphics`] keeps the card faces in sync with the card, and
+//! [`project_shadows`] moves the blob shadow of each card on the table below
+//! it. [`pulse_announced_word`] makes the word of a played card pulse while
+//! its voice line plays.
+//!
+//! [`apply_card_readability`] swaps the value and word faces of cards for
+//! bolder ones, see [`Readability`].
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
 
+use bevy::asset::LoadState;
 use bevy::ecs::system::{EntityCommands, SystemParam};
 use bevy::math::EulerRot::XYZ;
 use bevy::prelude::{Plugin as BevyPlugin, *};
@@ -17,12 +24,14 @@ use bevy::render::{
     },
     render_resource::PrimitiveTopology,
 };
-use bevy_debug_text_overlay::screen_print;
+use bevy::transform::TransformSystem;
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::{Inspectable, RegisterInspectable};
 use enum_map::{enum_map, EnumMap};
 
 use crate::{
+    game_flow::WordAnnouncement,
+    settings::GameplaySettings,
     war::{Card, Value, WordOfPower},
     CardOrigin, Participant,
 };
@@ -40,6 +49,129 @@ pub struct PlayerCardSpawner;
 pub enum CardStatus {
     Normal,
     Hovered,
+    /// The oppo is considering playing this card.
+    Considered,
+}
+
+/// The back face of a card, its material changes with the [`crate::theme`].
+#[derive(Component)]
+pub struct CardBack;
+
+/// The glow shown behind the word of hovered cards, and of cards the oppo
+/// considers playing.
+#[derive(Component)]
+pub struct CardGlow;
+
+/// Blob shadow of a card on the table, child of the card, see
+/// [`project_shadows`].
+#[derive(Component)]
+struct CardShadow;
+
+/// Height of the table top, where card shadows are cast.
+const TABLE_HEIGHT: f32 = 0.5;
+/// Altitude above the table at which a card shadow fades out completely.
+const SHADOW_FADE_ALTITUDE: f32 = 2.0;
+/// Opacity of the shadow of a card lying on the table.
+const SHADOW_ALPHA: f32 = 0.6;
+/// How much larger than on the table a shadow is at [`SHADOW_FADE_ALTITUDE`].
+const SHADOW_SPREAD: f32 = 0.6;
+/// Size of the shadow of a card lying on the table, relative to the card
+/// scale.
+const SHADOW_SIZE: f32 = 3.0;
+/// Shadow materials, opacities are rounded to one of those to share them.
+const SHADOW_LEVELS: usize = 8;
+/// How much brighter than usual the word of an announced card gets.
+const PULSE_GAIN: f32 = 1.5;
+/// How many times the word of an announced card pulses during its voice line.
+const PULSES: f32 = 2.0;
+/// Distance between neighbouring cards laid in a row, see [`row_offset`].
+const ROW_SPACING: f32 = 1.2;
+/// Widest a row of cards gets, past it cards are laid closer together.
+const ROW_MAX_WIDTH: f32 = 3.6;
+/// Usual scale of the value quad of a card.
+const VALUE_SCALE: Vec3 = Vec3::new(1.0, 1.5, 1.0);
+/// Usual scale of the word quad of a card.
+const WORD_SCALE: Vec3 = Vec3::new(1.5, 1.0, 1.0);
+/// How much larger the value and word of [`Readability::Boosted`] faces are.
+const BOOSTED_SCALE: f32 = 1.2;
+/// How much brighter the word of [`Readability::Boosted`] faces is.
+const BOOSTED_GLOW: f32 = 1.6;
+/// Asset folder of the [`Readability::HighContrast`] faces, it has the same
+/// files as `cards`.
+const HIGH_CONTRAST_FOLDER: &str = "cards/hc";
+
+/// How readable card faces are, following
+/// [`GameplaySettings::high_contrast_cards`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Readability {
+    Regular,
+    /// The bolder textures of [`HIGH_CONTRAST_FOLDER`].
+    HighContrast,
+    /// The regular textures, larger and brighter. Used while the high
+    /// contrast textures load, and in their stead when they are missing.
+    Boosted,
+}
+impl Readability {
+    /// The faces to use, `textures` being the load state of the high contrast
+    /// textures, `None` until they are first requested.
+    fn pick(high_contrast: bool, textures: Option<LoadState>) -> Self {
+        match (high_contrast, textures) {
+            (false, _) => Self::Regular,
+            (true, Some(LoadState::Loaded)) => Self::HighContrast,
+            (true, _) => Self::Boosted,
+        }
+    }
+    /// Scale of the value and word quads relative to their usual one.
+    fn face_scale(self) -> f32 {
+        match self {
+            Self::Boosted => BOOSTED_SCALE,
+            Self::Regular | Self::HighContrast => 1.0,
+        }
+    }
+    /// Emissive of words relative to their [`WordOfPower::color`].
+    fn word_glow(self) -> f32 {
+        match self {
+            Self::Boosted => BOOSTED_GLOW,
+            Self::Regular | Self::HighContrast => 1.0,
+        }
+    }
+}
+
+/// Value and word materials of card faces.
+#[derive(Clone)]
+struct Faces {
+    values: EnumMap<Value, Handle<StandardMaterial>>,
+    words: EnumMap<WordOfPower, Handle<StandardMaterial>>,
+    /// The textures of all the materials, to tell when they are loaded.
+    textures: Vec<Handle<Image>>,
+}
+impl Faces {
+    fn load(
+        folder: &str,
+        readability: Readability,
+        server: &AssetServer,
+        mats: &mut Assets<StandardMaterial>,
+    ) -> Self {
+        let mut textures = Vec::new();
+        let mut add = |path: String, emissive: Color| {
+            let texture = server.load(&path);
+            textures.push(texture.clone());
+            mats.add(StandardMaterial {
+                base_color_texture: Some(texture),
+                alpha_mode: AlphaMode::Mask(0.5),
+                emissive,
+                ..default()
+            })
+        };
+        let glow = readability.word_glow();
+        let values = enum_map! {
+            value => add(format!("{folder}/Value{value:?}.png"), Color::BLACK),
+        };
+        let words = enum_map! {
+            word => add(format!("{folder}/Word{word:?}.png"), word.color() * glow),
+        };
+        Self { values, words, textures }
+    }
 }
 
 #[derive(Component)]
@@ -71,6 +203,7 @@ const CARD_EDGES: [u16; 30] = [
 pub struct SpawnCard<'w, 's> {
     pub cmds: Commands<'w, 's>,
     assets: Res<'w, CardAssets>,
+    materials: ResMut<'w, Assets<StandardMaterial>>,
     player_deck: Query<'w, 's, &'static GlobalTransform, With<PlayerCardSpawner>>,
     oppo_deck: Query<'w, 's, &'static GlobalTransform, With<OppoCardSpawner>>,
 }
@@ -87,6 +220,17 @@ impl<'w, 's> SpawnCard<'w, 's> {
             Participant::Oppo => self.oppo_deck.single().compute_transform(),
             Participant::Player => self.player_deck.single().compute_transform(),
         };
+        // Each word card gets its own glow material, so that hovering a card
+        // doesn't change the glow color of others. The material is freed with
+        // the card, when the last handle to it is dropped.
+        let glow = match word {
+            Some(word) => {
+                let base = self.materials.get(&self.assets.glow).cloned();
+                let glow = StandardMaterial { emissive: word.color(), ..base.unwrap_or_default() };
+                self.materials.add(glow)
+            }
+            None => self.assets.glow.clone(),
+        };
         let cmds = &mut self.cmds;
         let entity = cmds
             .spawn_bundle((
@@ -121,7 +265,7 @@ impl<'w, 's> SpawnCard<'w, 's> {
                 transform: Transform::from_rotation(Quat::from_rotation_y(PI)),
                 ..default()
             })
-            .insert(Name::new("Back face"))
+            .insert_bundle((Name::new("Back face"), CardBack))
             .id();
         cmds.entity(entity).add_child(child);
 
@@ -135,69 +279,342 @@ impl<'w, 's> SpawnCard<'w, 's> {
             material: material.clone(),
             ..default()
         };
+        let face_scale = self.assets.readability.face_scale();
         #[rustfmt::skip]
         let graphics = CardGraphics {
             word: spawn_pbr("Word", PbrBundle {
                 transform: Transform::from_xyz(0.0, -0.8, 0.01)
-                    .with_scale(Vec3::new(1.5, 1.0, 1.0)),
+                    .with_scale(WORD_SCALE * face_scale),
                 visibility: Visibility { is_visible: word.is_some() },
-                ..default_card_pbr(&self.assets.words[word.unwrap_or(Egeq)])
+                ..default_card_pbr(&self.assets.faces.words[word.unwrap_or(Egeq)])
             }),
             value: spawn_pbr("Value", PbrBundle {
                 transform: Transform::from_xyz(0.0, 0.5, 0.01)
-                    .with_scale(Vec3::new(1.0, 1.5, 1.0)),
-                ..default_card_pbr(&self.assets.values[value])
+                    .with_scale(VALUE_SCALE * face_scale),
+                ..default_card_pbr(&self.assets.faces.values[value])
             }),
             glow: spawn_pbr("Glow", PbrBundle {
                 transform: Transform::from_xyz(0.0, -0.8, 0.009)
                     .with_scale(Vec3::new(4.2, 2.2, 0.0)),
                 visibility: Visibility { is_visible: false },
-                ..default_card_pbr(&self.assets.glow)
+                ..default_card_pbr(&glow)
             }),
         };
+        cmds.entity(graphics.glow).insert(CardGlow);
+        let shadow = cmds
+            .spawn_bundle(PbrBundle {
+                mesh: self.assets.quad.clone(),
+                material: self.assets.shadows[SHADOW_LEVELS - 1].clone(),
+                ..default()
+            })
+            .insert_bundle((Name::new("Shadow"), CardShadow))
+            .id();
+        cmds.entity(entity).add_child(shadow);
         let mut ent = cmds.entity(entity);
         ent.insert_bundle((CardStatus::Normal, graphics));
         ent
     }
 }
 
+/// Update the faces of cards that changed, or of all cards when the
+/// [`CardAssets`] faces did.
 #[allow(clippy::type_complexity)]
 fn update_card_graphics(
-    cards: Query<(&Card, &CardStatus, &CardGraphics), Or<(Changed<Card>, Changed<CardStatus>)>>,
+    cards: Query<(
+        &Card,
+        &CardStatus,
+        &CardGraphics,
+        ChangeTrackers<Card>,
+        ChangeTrackers<CardStatus>,
+    )>,
     assets: Res<CardAssets>,
-    mut mat_assets: ResMut<Assets<StandardMaterial>>,
-    mut mats: Query<(&mut Visibility, &mut Handle<StandardMaterial>)>,
+    mut mats: Query<(
+        &mut Visibility,
+        &mut Handle<StandardMaterial>,
+        &mut Transform,
+    )>,
 ) {
-    for (card, status, graphics) in cards.iter() {
-        if let Ok((_, mut mat)) = mats.get_mut(graphics.value) {
-            *mat = assets.values[card.value].clone();
+    let face_scale = assets.readability.face_scale();
+    for (card, status, graphics, card_changes, status_changes) in cards.iter() {
+        let changed = card_changes.is_changed() || status_changes.is_changed();
+        if !changed && !assets.is_changed() {
+            continue;
+        }
+        if let Ok((_, mut mat, mut transform)) = mats.get_mut(graphics.value) {
+            *mat = assets.faces.values[card.value].clone();
+            transform.scale = VALUE_SCALE * face_scale;
         }
-        if let Ok((mut vis, mut mat)) = mats.get_mut(graphics.word) {
+        if let Ok((mut vis, mut mat, mut transform)) = mats.get_mut(graphics.word) {
             vis.is_visible = card.word.is_some();
             if let Some(word) = card.word {
-                *mat = assets.words[word].clone();
+                *mat = assets.faces.words[word].clone();
             }
+            transform.scale = WORD_SCALE * face_scale;
         }
-        if let (Ok((mut vis, mat)), Some(word)) = (mats.get_mut(graphics.glow), card.word) {
-            vis.is_visible = *status == CardStatus::Hovered;
-            if vis.is_visible {
-                let col = word.color();
-                screen_print!(sec: 1, col: col, "Swapping color of card with {word:?}");
-                let mut mat = mat_assets.get_mut(&*mat).unwrap();
-                mat.emissive = col;
+        if let Ok((mut vis, ..)) = mats.get_mut(graphics.glow) {
+            vis.is_visible = glow_visible(card, *status);
+        }
+    }
+}
+
+/// Swap the [`CardAssets`] faces when [`GameplaySettings::high_contrast_cards`]
+/// changes or the high contrast textures finish loading, cards then follow in
+/// [`update_card_graphics`].
+fn apply_card_readability(
+    mut assets: ResMut<CardAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    server: Res<AssetServer>,
+    settings: Res<GameplaySettings>,
+) {
+    let high_contrast = settings.high_contrast_cards;
+    if high_contrast && assets.high_contrast.is_none() {
+        let faces = Faces::load(
+            HIGH_CONTRAST_FOLDER,
+            Readability::HighContrast,
+            &server,
+            &mut materials,
+        );
+        assets.high_contrast = Some(faces);
+    }
+    let textures = assets
+        .high_contrast
+        .as_ref()
+        .map(|faces| server.get_group_load_state(faces.textures.iter().map(|texture| texture.id)));
+    let readability = Readability::pick(high_contrast, textures);
+    if readability != assets.readability {
+        assets.faces = assets.faces_of(readability).clone();
+        assets.readability = readability;
+    }
+}
+
+fn glow_visible(card: &Card, status: CardStatus) -> bool {
+    match status {
+        CardStatus::Hovered => card.word.is_some(),
+        CardStatus::Considered => true,
+        CardStatus::Normal => false,
+    }
+}
+
+/// How bright the word of a card is relative to its usual emissive, when its
+/// voice line is `progress` through, from `0.0` to `1.0`.
+///
+/// It pulses in a slow sine, or fades once with `reduce_motion`, settling
+/// back to `1.0` at the end of the line.
+fn word_pulse(progress: f32, reduce_motion: bool) -> f32 {
+    let progress = progress.clamp(0.0, 1.0);
+    let brightness = if reduce_motion {
+        1.0 - progress
+    } else {
+        (1.0 - (progress * PULSES * TAU).cos()) / 2.0
+    };
+    1.0 + PULSE_GAIN * brightness
+}
+
+/// The card pulsing with its voice line, and the word material it has for
+/// the duration of the line, the shared one can't pulse.
+struct Pulsing {
+    card: Entity,
+    word: Handle<StandardMaterial>,
+}
+
+/// Pulse the word and glow of the card of the [`WordAnnouncement`] while its
+/// voice line plays, then settle them back.
+fn pulse_announced_word(
+    mut cmds: Commands,
+    mut pulsing: Local<Option<Pulsing>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut quads: Query<(&mut Visibility, &mut Handle<StandardMaterial>)>,
+    announcement: Option<Res<WordAnnouncement>>,
+    cards: Query<(&Card, &CardStatus, &CardGraphics)>,
+    assets: Res<CardAssets>,
+    settings: Res<GameplaySettings>,
+    time: Res<Time>,
+) {
+    let now = time.seconds_since_startup();
+    let playing = announcement
+        .as_ref()
+        .filter(|announced| now < announced.ends_at && cards.contains(announced.card));
+    if announcement.is_some() && playing.is_none() {
+        cmds.remove_resource::<WordAnnouncement>();
+    }
+    let playing_card = playing.map(|announced| announced.card);
+    let over = pulsing
+        .as_ref()
+        .filter(|pulsing| Some(pulsing.card) != playing_card);
+    if let Some(settled) = over.map(|pulsing| pulsing.card) {
+        *pulsing = None;
+        if let Ok((card, status, graphics)) = cards.get(settled) {
+            if let (Ok((_, mut mat)), Some(word)) = (quads.get_mut(graphics.word), card.word) {
+                *mat = assets.faces.words[word].clone();
+            }
+            if let Ok((mut vis, mat)) = quads.get_mut(graphics.glow) {
+                vis.is_visible = glow_visible(card, *status);
+                if let (Some(glow), Some(word)) = (materials.get_mut(&*mat), card.word) {
+                    glow.emissive = word.color();
+                }
             }
         }
     }
+    let announced = match playing {
+        Some(announced) => announced,
+        None => return,
+    };
+    let graphics = match cards.get(announced.card) {
+        Ok((_, _, graphics)) => graphics,
+        Err(_) => return,
+    };
+    let word = pulsing.get_or_insert_with(|| {
+        let base = materials.get(&assets.faces.words[announced.word]).cloned();
+        let word = materials.add(base.unwrap_or_default());
+        Pulsing { card: announced.card, word }
+    });
+    let duration = announced.ends_at - announced.started_at;
+    let progress = ((now - announced.started_at) / duration) as f32;
+    let pulse = word_pulse(progress, settings.reduce_motion);
+    let emissive = announced.word.color() * assets.readability.word_glow() * pulse;
+    // Set every frame, `update_card_graphics` resets them when the card changes
+    if let Ok((_, mut mat)) = quads.get_mut(graphics.word) {
+        if *mat != word.word {
+            *mat = word.word.clone();
+        }
+    }
+    let glow = match quads.get_mut(graphics.glow) {
+        Ok((mut vis, mat)) => {
+            vis.is_visible = true;
+            mat.clone()
+        }
+        Err(_) => return,
+    };
+    for handle in [&word.word, &glow] {
+        if let Some(material) = materials.get_mut(handle) {
+            material.emissive = emissive;
+        }
+    }
+}
+
+/// Scale and opacity of the shadow of a card `altitude` above the table: the
+/// higher the card, the larger and fainter its shadow.
+fn shadow_falloff(altitude: f32) -> (f32, f32) {
+    let height = (altitude / SHADOW_FADE_ALTITUDE).clamp(0.0, 1.0);
+    (1.0 + height * SHADOW_SPREAD, SHADOW_ALPHA * (1.0 - height))
+}
+
+/// Index of the shadow material closest to `alpha`, `None` when the shadow
+/// is too faint to be seen.
+fn shadow_level(alpha: f32) -> Option<usize> {
+    let level = (alpha / SHADOW_ALPHA * (SHADOW_LEVELS - 1) as f32).round() as usize;
+    (level != 0).then(|| level.min(SHADOW_LEVELS - 1))
+}
+
+/// Offset of the card at `slot` from the center of a row of `count` cards.
+///
+/// The row is centered whatever `count` is, cards overlap more as it grows
+/// so that it never gets wider than [`ROW_MAX_WIDTH`].
+pub fn row_offset(slot: usize, count: usize) -> f32 {
+    if count <= 1 {
+        return 0.0;
+    }
+    let gaps = (count - 1) as f32;
+    let spacing = ROW_SPACING.min(ROW_MAX_WIDTH / gaps);
+    (slot as f32 - gaps / 2.0) * spacing
+}
+
+/// Lay card shadows flat on the table, right below their card.
+///
+/// Cards are not parented, so their [`Transform`] is their world transform.
+/// Shadows are hidden in low-spec mode.
+#[allow(clippy::type_complexity)]
+fn project_shadows(
+    cards: Query<&Transform, (With<Card>, Without<CardShadow>)>,
+    mut shadows: Query<
+        (
+            &Parent,
+            &mut Transform,
+            &mut Handle<StandardMaterial>,
+            &mut Visibility,
+        ),
+        With<CardShadow>,
+    >,
+    assets: Res<CardAssets>,
+    gameplay: Res<GameplaySettings>,
+) {
+    for (parent, mut transform, mut material, mut vis) in shadows.iter_mut() {
+        let card = match cards.get(parent.get()) {
+            Ok(card) => card,
+            Err(_) => continue,
+        };
+        let (scale, alpha) = shadow_falloff(card.translation.y - TABLE_HEIGHT);
+        let level = shadow_level(alpha).filter(|_| !gameplay.low_spec);
+        if vis.is_visible != level.is_some() {
+            vis.is_visible = level.is_some();
+        }
+        let level = match level {
+            Some(level) => level,
+            None => continue,
+        };
+        if *material != assets.shadows[level] {
+            *material = assets.shadows[level].clone();
+        }
+        // Slightly above the table to avoid z-fighting
+        let on_table = Transform {
+            translation: Vec3::new(card.translation.x, TABLE_HEIGHT + 0.001, card.translation.z),
+            rotation: Quat::from_rotation_x(-FRAC_PI_2),
+            scale: Vec3::splat(SHADOW_SIZE * card.scale.x * scale),
+        };
+        let local = card.compute_matrix().inverse() * on_table.compute_matrix();
+        *transform = Transform::from_matrix(local);
+    }
+}
+
+/// Log cards which transform became non-finite, a NaN never lerps back to
+/// a valid position, so the card is lost for good.
+///
+/// Only logs the first time it happens to a card, later ones are probably
+/// caused by the first.
+#[cfg(debug_assertions)]
+fn find_nan_transforms(
+    cards: Query<(Entity, &Transform, &Card), Changed<Transform>>,
+    mut reported: Local<bevy::utils::HashSet<Entity>>,
+) {
+    for (entity, transform, card) in cards.iter() {
+        let finite = transform.translation.is_finite()
+            && transform.rotation.is_finite()
+            && transform.scale.is_finite();
+        if !finite && reported.insert(entity) {
+            error!("Card {card:?} ({entity:?}) got a non-finite transform: {transform:?}");
+        }
+    }
 }
 
 pub struct CardAssets {
     card: Handle<Mesh>,
-    values: EnumMap<Value, Handle<StandardMaterial>>,
-    backface: Handle<StandardMaterial>,
+    /// Faces of new cards, those of the current [`Readability`].
+    faces: Faces,
+    readability: Readability,
+    regular: Faces,
+    boosted: Faces,
+    /// Loaded the first time high contrast faces are turned on.
+    high_contrast: Option<Faces>,
+    /// Back face of new cards, set by the [`crate::theme`].
+    pub backface: Handle<StandardMaterial>,
+    /// Back face of the [`crate::theme::Theme::Classic`] theme.
+    pub default_backface: Handle<StandardMaterial>,
     frontface: Handle<StandardMaterial>,
     quad: Handle<Mesh>,
-    words: EnumMap<WordOfPower, Handle<StandardMaterial>>,
-    glow: Handle<StandardMaterial>,
+    /// Glow material cards copy from when spawned.
+    pub glow: Handle<StandardMaterial>,
+    /// Card shadow materials, from faintest to darkest, [`SHADOW_LEVELS`]
+    /// of them.
+    shadows: Vec<Handle<StandardMaterial>>,
+}
+impl CardAssets {
+    fn faces_of(&self, readability: Readability) -> &Faces {
+        match readability {
+            Readability::Regular => &self.regular,
+            Readability::Boosted => &self.boosted,
+            Readability::HighContrast => self.high_contrast.as_ref().unwrap_or(&self.boosted),
+        }
+    }
 }
 impl FromWorld for CardAssets {
     fn from_world(world: &mut World) -> Self {
@@ -227,23 +644,38 @@ impl FromWorld for CardAssets {
         );
         card_mesh.set_indices(Some(Indices::U16(CARD_EDGES.into())));
 
+        let backface = add_texture_material!("cards/BackFace.png");
+        let asset_server = world.get_resource::<AssetServer>().unwrap().clone();
+        let blob = asset_server.load("glow.png");
+        let mut mats = world.get_resource_mut::<Assets<_>>().unwrap();
+        let shadows = (0..SHADOW_LEVELS)
+            .map(|level| {
+                let alpha = SHADOW_ALPHA * level as f32 / (SHADOW_LEVELS - 1) as f32;
+                mats.add(StandardMaterial {
+                    base_color: Color::rgba(0.0, 0.0, 0.0, alpha),
+                    base_color_texture: Some(blob.clone()),
+                    alpha_mode: Blend,
+                    unlit: true,
+                    ..default()
+                })
+            })
+            .collect();
+        let regular = Faces::load("cards", Readability::Regular, &asset_server, &mut mats);
+        let boosted = Faces::load("cards", Readability::Boosted, &asset_server, &mut mats);
         let mut meshes = world.get_resource_mut::<Assets<Mesh>>().unwrap();
         Self {
             card: meshes.add(card_mesh),
             quad: meshes.add(shape::Quad::new(Vec2::splat(1.0)).into()),
-            backface: add_texture_material!("cards/BackFace.png"),
+            default_backface: backface.clone(),
+            backface,
             frontface: add_texture_material!("cards/FrontFace.png"),
-            values: enum_map! {
-                value => add_texture_material!(&format!("cards/Value{value:?}.png"), alpha: Mask(0.5)),
-            },
+            faces: regular.clone(),
+            readability: Readability::Regular,
+            regular,
+            boosted,
+            high_contrast: None,
             glow: add_texture_material!("glow.png", alpha: Blend),
-            words: enum_map! {
-                word => add_texture_material!(
-                    &format!("cards/Word{word:?}.png"),
-                    alpha: Mask(0.5),
-                    emissive: word.color()
-                ),
-            },
+            shadows,
         }
     }
 }
@@ -256,7 +688,97 @@ impl BevyPlugin for Plugin {
             .register_inspectable::<Value>()
             .register_inspectable::<WordOfPower>();
 
+        #[cfg(debug_assertions)]
+        app.add_system_to_stage(CoreStage::PostUpdate, find_nan_transforms);
+
         app.init_resource::<CardAssets>()
-            .add_system(update_card_graphics);
+            .add_system(apply_card_readability.before(update_card_graphics))
+            .add_system(update_card_graphics)
+            .add_system(pulse_announced_word.after(update_card_graphics))
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                project_shadows.before(TransformSystem::TransformPropagate),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn announced_words_pulse_then_settle() {
+        assert_eq!(word_pulse(0.0, false), 1.0);
+        assert!((word_pulse(1.0, false) - 1.0).abs() < 1e-5, "settles back");
+        let peak = 1.0 + PULSE_GAIN;
+        assert!((word_pulse(0.5 / PULSES, false) - peak).abs() < 1e-5);
+        let past_the_line = word_pulse(2.0, false);
+        assert_eq!(past_the_line, word_pulse(1.0, false));
+
+        assert_eq!(word_pulse(0.0, true), peak, "reduced motion fades once");
+        assert_eq!(word_pulse(1.0, true), 1.0);
+        let fade: Vec<_> = (0..=10).map(|i| word_pulse(i as f32 / 10., true)).collect();
+        assert!(fade.windows(2).all(|w| w[0] > w[1]), "{fade:?}");
+    }
+
+    #[test]
+    fn high_contrast_faces_fall_back_to_boosted_ones() {
+        use LoadState::{Failed, Loaded, Loading};
+        assert_eq!(Readability::pick(false, None), Readability::Regular);
+        assert_eq!(Readability::pick(false, Some(Loaded)), Readability::Regular);
+        assert_eq!(
+            Readability::pick(true, Some(Loaded)),
+            Readability::HighContrast
+        );
+        for textures in [None, Some(Loading), Some(Failed)] {
+            assert_eq!(Readability::pick(true, textures), Readability::Boosted);
+        }
+        assert_eq!(Readability::Boosted.face_scale(), 1.2);
+        assert!(Readability::Boosted.word_glow() > Readability::Regular.word_glow());
+    }
+
+    #[test]
+    fn shadows_grow_and_fade_with_altitude() {
+        assert_eq!(shadow_falloff(0.0), (1.0, SHADOW_ALPHA));
+        assert_eq!(shadow_falloff(-1.0), (1.0, SHADOW_ALPHA), "below the table");
+        let (low_scale, low_alpha) = shadow_falloff(0.5);
+        let (high_scale, high_alpha) = shadow_falloff(1.5);
+        assert!(1.0 < low_scale && low_scale < high_scale);
+        assert!(SHADOW_ALPHA > low_alpha && low_alpha > high_alpha);
+        let faded = (1.0 + SHADOW_SPREAD, 0.0);
+        assert_eq!(shadow_falloff(SHADOW_FADE_ALTITUDE), faded);
+        assert_eq!(shadow_falloff(10.0), faded);
+    }
+
+    #[test]
+    fn faint_shadows_are_hidden() {
+        assert_eq!(shadow_level(SHADOW_ALPHA), Some(SHADOW_LEVELS - 1));
+        assert_eq!(shadow_level(0.0), None);
+        assert_eq!(shadow_level(SHADOW_ALPHA / 100.0), None);
+        for level in 1..SHADOW_LEVELS {
+            let alpha = SHADOW_ALPHA * level as f32 / (SHADOW_LEVELS - 1) as f32;
+            assert_eq!(shadow_level(alpha), Some(level));
+        }
+    }
+
+    #[test]
+    fn rows_are_centered_and_tighten() {
+        assert_eq!(row_offset(0, 1), 0.0);
+        assert_eq!([0, 1, 2].map(|i| row_offset(i, 3)), [-1.2, 0.0, 1.2]);
+        let mut previous_spacing = ROW_SPACING;
+        for count in 1..=6 {
+            let row: Vec<_> = (0..count).map(|slot| row_offset(slot, count)).collect();
+            let center = row.iter().sum::<f32>() / count as f32;
+            assert!(center.abs() < 1e-5, "{count} cards: {row:?}");
+            let width = row[count - 1] - row[0];
+            assert!(width <= ROW_MAX_WIDTH + 1e-5, "{count} cards: {row:?}");
+            for pair in row.windows(2) {
+                let spacing = pair[1] - pair[0];
+                assert!(spacing > 0.0, "{count} cards: {row:?}");
+                assert!(spacing <= previous_spacing + 1e-5, "{count} cards: {row:?}");
+                previous_spacing = spacing;
+            }
+        }
+        assert!(row_offset(1, 6) - row_offset(0, 6) < ROW_SPACING);
     }
 }
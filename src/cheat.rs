@@ -6,15 +6,61 @@
 //! But it also defines the [`CheatEvent`] events, they are read in the
 //! [`execute_cheat`] system, it controls the game over condition when player
 //! forgot to distract the bird before cheating.
+//!
+//! Hovering the sleeve with a grabbed card while the bird watches doesn't end
+//! the game, but the bird gets suspicious, see [`warn_near_miss`].
+//!
+//! Seeds distract the bird, and so do shiny trinkets, for longer, see
+//! [`Distraction`].
+//!
+//! Getting caught makes the bird vigilant for the next games, see
+//! [`BirdMemory`]. In a co-op game, the bird only remembers the player it
+//! caught, and watches them more closely whenever they are active. How the
+//! player got caught is kept in [`LastCatch`], for the restart menu recap.
+use std::f32::consts::PI;
+
 use bevy::input::keyboard::KeyCode;
 use bevy::prelude::{Plugin as BevyPlugin, *};
-use bevy_debug_text_overlay::screen_print;
+use bevy_mod_raycast::RayCastSource;
+use enum_map::EnumMap;
 
 use crate::{
-    animate::Animated, game_flow::SeedCount, game_ui::EffectEvent, player_hand::GrabbedCard,
-    state::GameState, EndReason, GameOver,
+    animate::{Animated, GameSpeed},
+    audio::AudioRequest,
+    game_event::GameEvent,
+    game_flow::{BattleCount, CoopPlayer, CoopTurn, Inventory, SeedCount},
+    game_ui::EffectEvent,
+    lang::Strings,
+    mood::Mood,
+    opponent::OpponentProfile,
+    player_hand::{GrabbedCard, SleeveArea, CARD_HIT_BOX},
+    rules::GameRules,
+    settings::{Action, GameplaySettings, KeyBindings},
+    state::GameState,
+    war::Card,
+    EndReason,
 };
 
+/// How long the pupil dilates when the bird gets suspicious, in seconds.
+const DILATION_DURATION: f32 = 0.6;
+/// How much larger the pupil gets at its most dilated.
+const MAX_DILATION: f32 = 0.6;
+/// Vertical scale of the bird eye when it is vigilant.
+const NARROWED_EYE: f32 = 0.6;
+/// How long cards take to slide into or out of the sleeve, in seconds.
+const SLIDE_DURATION: f32 = 0.5;
+/// Part of the slide into the sleeve spent reaching the sleeve opening.
+const SLIDE_APPROACH: f32 = 0.4;
+/// Part of the height of sleeved cards that peeks out of the sleeve.
+const PEEK: f32 = 0.15;
+/// Offset of each sleeved card from the previous one, relative to the
+/// sleeve, so that they show as separate slivers.
+const STACK_OFFSET: Vec3 = Vec3::new(0.25, -0.05, 0.01);
+/// Sleeved cards past this many stack on the last one.
+const MAX_STACKED: usize = 3;
+/// Scale of sleeved cards, relative to their scale in hand.
+const SLEEVED_SCALE: f32 = 0.9;
+
 #[derive(Component)]
 pub struct BirdPupilRoot;
 
@@ -28,43 +74,337 @@ pub struct PlayerSleeve;
 #[derive(Debug)]
 pub enum CheatEvent {
     HideInSleeve(Entity),
-    ConfuseBird,
+    ConfuseBird(Distraction),
+}
+
+/// What the player distracts the bird with.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Distraction {
+    /// Seeds, as many as [`BirdEye::distraction_cost`].
+    Seed,
+    /// A shiny trinket from the [`Inventory`], it distracts the bird twice as
+    /// long as seeds, but the oppo gets suspicious for longer.
+    Trinket,
+}
+impl Distraction {
+    /// Cards the player can sleeve before the bird watches again, `sleeves`
+    /// being how many seeds allow.
+    fn sleeves(self, sleeves: usize) -> usize {
+        match self {
+            Distraction::Seed => sleeves,
+            Distraction::Trinket => 2 * sleeves.max(1),
+        }
+    }
 }
 
+/// A card in the sleeve, it slides in then stays in its slot.
 #[derive(Component)]
-pub struct SleeveCard;
+pub struct SleeveCard {
+    /// How many cards were in the sleeve when this one entered it.
+    index: usize,
+    /// Where the card was when it entered the sleeve.
+    from: Option<Transform>,
+    /// Seconds since the card entered the sleeve.
+    elapsed: f32,
+}
+impl SleeveCard {
+    pub fn new(index: usize) -> Self {
+        Self { index, from: None, elapsed: 0.0 }
+    }
+    /// How many cards were in the sleeve when this one entered it.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+    /// Take the card out of the sleeve, it slides out before the hand
+    /// layout takes over.
+    pub fn leave(&self) -> LeavingSleeve {
+        let scale = self.from.map_or(Vec3::ONE, |from| from.scale);
+        LeavingSleeve { index: self.index, scale, elapsed: 0.0 }
+    }
+}
+
+/// A card sliding out of the sleeve back into the hand, removed once out.
+#[derive(Component)]
+pub struct LeavingSleeve {
+    index: usize,
+    /// Scale of the card in hand.
+    scale: Vec3,
+    /// Seconds since the card started leaving the sleeve.
+    elapsed: f32,
+}
+
+/// How the bird caught the player cheating.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Catch {
+    /// The card the player tried to sleeve.
+    pub card: Card,
+    /// Cards the player sleeved unseen earlier in the game.
+    pub earlier_sleeves: usize,
+    /// The battle the player got caught in, see [`BattleCount::current`].
+    pub battle: usize,
+}
+impl Catch {
+    /// Short recap of the catch, for the restart menu.
+    pub fn recap(&self, strings: &Strings) -> String {
+        let key = match self.earlier_sleeves {
+            0 => "gameover.caught_first",
+            1 => "gameover.caught_after_one",
+            _ => "gameover.caught_after_many",
+        };
+        let battle = self.battle.to_string();
+        let value = self.card.value_i32().to_string();
+        let steals = self.earlier_sleeves.to_string();
+        let args = [
+            ("battle", battle.as_str()),
+            ("value", value.as_str()),
+            ("steals", steals.as_str()),
+        ];
+        strings.format(key, &args)
+    }
+}
+
+/// The [`Catch`] that ended the last game, `None` if it didn't end with
+/// [`EndReason::CaughtCheating`] or the player wasn't caught in the act.
+#[derive(Default)]
+pub struct LastCatch(pub Option<Catch>);
 
 pub struct BirdEye {
     pub is_watching: bool,
+    /// Cards successfully sleeved this game, each one makes the bird harder
+    /// to distract, see [`GameRules::distraction_cost`].
+    pub sleeved_this_game: usize,
+    /// The bird remembers catching the player in a previous game.
+    pub vigilant: bool,
+    /// Cards the player can still sleeve before the distracted bird watches
+    /// again.
+    sleeves_left: usize,
 }
 impl Default for BirdEye {
     fn default() -> Self {
-        Self { is_watching: true }
+        Self {
+            is_watching: true,
+            sleeved_this_game: 0,
+            vigilant: false,
+            sleeves_left: 0,
+        }
+    }
+}
+impl BirdEye {
+    /// Seeds needed to distract the bird, never more than the player can
+    /// hold.
+    pub fn distraction_cost(&self, rules: &GameRules) -> usize {
+        let vigilance = if self.vigilant {
+            rules.vigilant_distraction_cost
+        } else {
+            0
+        };
+        let cost = rules.distraction_cost(self.sleeved_this_game) + vigilance;
+        cost.min(rules.seed_cap)
+    }
+    /// Stop watching the player for the next `sleeves` sleeved cards.
+    fn distract(&mut self, sleeves: usize) {
+        self.is_watching = false;
+        self.sleeves_left = sleeves.max(1);
+    }
+    /// Account for a card sleeved while distracted, the bird watches again
+    /// once the player sleeved as many cards as the distraction allows.
+    fn sleeve(&mut self) {
+        self.sleeved_this_game += 1;
+        self.sleeves_left = self.sleeves_left.saturating_sub(1);
+        self.is_watching = self.sleeves_left == 0;
     }
 }
 
+/// How many more games the bird stays vigilant with each [`CoopPlayer`],
+/// after catching them cheating.
+///
+/// Kept between games, but forgotten when going back to the main menu.
+#[derive(Default)]
+pub struct BirdMemory {
+    games_left: EnumMap<CoopPlayer, usize>,
+}
+impl BirdMemory {
+    pub fn is_vigilant(&self, who: CoopPlayer) -> bool {
+        self.games_left[who] != 0
+    }
+    /// Whether the bird is vigilant with any of the players.
+    pub fn remembers_anyone(&self) -> bool {
+        CoopPlayer::ALL.iter().any(|who| self.is_vigilant(*who))
+    }
+    /// Update the memory at the end of a game, `active` being the player
+    /// controlling the hand when it ended.
+    fn game_over(&mut self, reason: &EndReason, rules: &GameRules, active: CoopPlayer) {
+        for (who, games_left) in self.games_left.iter_mut() {
+            *games_left = match reason {
+                EndReason::CaughtCheating if who == active => rules.bird_memory_games,
+                _ => games_left.saturating_sub(1),
+            };
+        }
+    }
+}
+
+/// What decides whether the player can put a card in their sleeve, see
+/// [`can_sleeve`].
+pub struct SleeveState {
+    /// Cards currently in the sleeve.
+    pub sleeved: usize,
+    pub capacity: usize,
+    /// Cards left in the player deck, sleeving a card draws a replacement.
+    pub deck_remaining: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SleeveVerdict {
+    Allowed,
+    /// The sleeve is at capacity.
+    Full,
+    /// No card left in deck to replace the sleeved one.
+    DeckEmpty,
+}
+
+/// Whether one more card can go into the sleeve.
+///
+/// This doesn't account for the bird, sleeving a card while it watches is
+/// allowed, but ends the game.
+pub fn can_sleeve(state: &SleeveState) -> SleeveVerdict {
+    if state.sleeved >= state.capacity {
+        SleeveVerdict::Full
+    } else if state.deck_remaining == 0 {
+        SleeveVerdict::DeckEmpty
+    } else {
+        SleeveVerdict::Allowed
+    }
+}
+
+/// Tracks the grabbed card hovering the sleeve while the bird watches.
+///
+/// This only warns the player, nothing happens unless they actually drop the
+/// card in the sleeve.
+#[derive(Default)]
+struct NearMiss {
+    /// Seconds the grabbed card spent over the sleeve.
+    hovering: f32,
+    warned: bool,
+    /// Seconds since the pupil started dilating, `None` if it isn't.
+    dilation: Option<f32>,
+}
+
 fn cleanup(
+    mut near_miss: ResMut<NearMiss>,
     mut bird_eye: ResMut<BirdEye>,
     mut bird_eye_anim: Query<&mut Animated, With<BirdPupilRoot>>,
 ) {
     *bird_eye = BirdEye::default();
+    *near_miss = NearMiss::default();
     if let Ok(mut bird_eye_anim) = bird_eye_anim.get_single_mut() {
         *bird_eye_anim = Animated::Static;
     }
 }
 
+fn remember_cheater(
+    mut memory: ResMut<BirdMemory>,
+    mut events: EventReader<GameEvent>,
+    rules: Res<GameRules>,
+    coop_turn: Res<CoopTurn>,
+) {
+    for reason in events.iter().filter_map(GameEvent::end) {
+        memory.game_over(&reason, &rules, coop_turn.active());
+    }
+}
+
+fn forget_cheater(mut memory: ResMut<BirdMemory>) {
+    *memory = BirdMemory::default();
+}
+
+fn forget_catch(mut last_catch: ResMut<LastCatch>) {
+    last_catch.0 = None;
+}
+
+/// In a fair game, the bird doesn't watch the player, it just looks around.
+fn wake_bird(
+    memory: Res<BirdMemory>,
+    rules: Res<GameRules>,
+    coop_turn: Res<CoopTurn>,
+    mut bird_eye: ResMut<BirdEye>,
+    mut bird_eye_anim: Query<&mut Animated, With<BirdPupilRoot>>,
+) {
+    bird_eye.vigilant = memory.is_vigilant(coop_turn.active());
+    if rules.fair_game {
+        bird_eye.is_watching = false;
+        if let Ok(mut anim) = bird_eye_anim.get_single_mut() {
+            *anim = Animated::Circle { radius: 0.05, period: 5.0, offset: 0.0 };
+        }
+    }
+}
+
+/// Watch the [`CoopPlayer`] taking over the hand as closely as the bird
+/// remembers them.
+fn watch_active_player(
+    memory: Res<BirdMemory>,
+    coop_turn: Res<CoopTurn>,
+    mut bird_eye: ResMut<BirdEye>,
+) {
+    let vigilant = memory.is_vigilant(coop_turn.active());
+    if coop_turn.is_changed() && bird_eye.vigilant != vigilant {
+        bird_eye.vigilant = vigilant;
+    }
+}
+
+/// Narrow the eye of a vigilant bird.
+fn squint_bird_eye(bird: Res<BirdEye>, mut eye: Query<&mut Transform, With<BirdPupilRoot>>) {
+    if !bird.is_changed() {
+        return;
+    }
+    let vertical_scale = if bird.vigilant { NARROWED_EYE } else { 1.0 };
+    for mut transform in eye.iter_mut() {
+        transform.scale.y = vertical_scale;
+    }
+}
+
+/// Distract the bird with seeds from the pool of the active [`CoopPlayer`].
 fn use_seed(
     mut seed: ResMut<SeedCount>,
     mut cheats: EventWriter<CheatEvent>,
+    mut game_events: EventWriter<GameEvent>,
     mut ui: EventWriter<EffectEvent>,
     input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    bird: Res<BirdEye>,
+    rules: Res<GameRules>,
+    coop_turn: Res<CoopTurn>,
 ) {
-    if input.just_pressed(KeyCode::Space) && seed.consume() {
-        cheats.send(CheatEvent::ConfuseBird);
+    if rules.fair_game {
+        return;
+    }
+    let cost = bird.distraction_cost(&rules);
+    let active = coop_turn.active();
+    if bindings.just_pressed(Action::UseSeed, &input) && seed.consume(active, cost) {
+        cheats.send(CheatEvent::ConfuseBird(Distraction::Seed));
+        game_events.send(GameEvent::SeedUsed(active));
         ui.send(EffectEvent::UseSeed);
     }
 }
 
+/// Distract the bird with a trinket from the [`Inventory`] of the active
+/// [`CoopPlayer`], whatever the seed cost.
+fn use_trinket(
+    mut inventory: ResMut<Inventory>,
+    mut cheats: EventWriter<CheatEvent>,
+    mut ui: EventWriter<EffectEvent>,
+    input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    rules: Res<GameRules>,
+    coop_turn: Res<CoopTurn>,
+) {
+    if rules.fair_game || !bindings.just_pressed(Action::UseTrinket, &input) {
+        return;
+    }
+    if inventory.use_trinket(coop_turn.active()) {
+        cheats.send(CheatEvent::ConfuseBird(Distraction::Trinket));
+        ui.send(EffectEvent::UseTrinket);
+    }
+}
+
 fn control_bird_pupil(
     eye_status: Res<BirdEye>,
     mut eye: Query<&mut Transform, With<BirdPupil>>,
@@ -73,13 +413,13 @@ fn control_bird_pupil(
     if eye_status.is_watching {
         match (grabbed_card.get_single(), eye.get_single_mut()) {
             (Ok(look_at), Ok(mut eye)) => {
-                screen_print!("Tracking player card");
+                debug_print!("Tracking player card");
                 let hand = look_at.translation;
                 let new_trans = Vec3::new(hand.x / 2.7, (hand.y - 6.05) / 1.65, 0.0) * 0.1;
                 eye.translation = new_trans;
             }
             (Err(_), Ok(mut eye)) => {
-                screen_print!("Not tracking player card");
+                debug_print!("Not tracking player card");
                 eye.translation = Vec3::ZERO;
             }
             _ => {}
@@ -87,6 +427,53 @@ fn control_bird_pupil(
     }
 }
 
+fn warn_near_miss(
+    time: Res<Time>,
+    bird: Res<BirdEye>,
+    mut near_miss: ResMut<NearMiss>,
+    mut audio: EventWriter<AudioRequest>,
+    mut ui: EventWriter<EffectEvent>,
+    opponent: Res<OpponentProfile>,
+    sleeve_raycaster: Query<&RayCastSource<SleeveArea>>,
+    grabbed_card: Query<(), With<GrabbedCard>>,
+) {
+    let over_sleeve = sleeve_raycaster
+        .iter()
+        .any(|ray| ray.intersect_top().is_some());
+    if !bird.is_watching || grabbed_card.is_empty() || !over_sleeve {
+        if near_miss.warned {
+            ui.send(EffectEvent::Hide);
+        }
+        near_miss.hovering = 0.0;
+        near_miss.warned = false;
+        return;
+    }
+    near_miss.hovering += time.delta_seconds();
+    if near_miss.hovering > opponent.bird.suspicion_delay && !near_miss.warned {
+        near_miss.warned = true;
+        near_miss.dilation = Some(0.0);
+        audio.send(AudioRequest::PlayBirdWarning);
+        ui.send(EffectEvent::BirdSuspicious);
+    }
+}
+
+fn dilate_pupil(
+    time: Res<Time>,
+    mut near_miss: ResMut<NearMiss>,
+    mut eye: Query<&mut Transform, With<BirdPupil>>,
+) {
+    let elapsed = match near_miss.dilation {
+        Some(elapsed) => elapsed + time.delta_seconds(),
+        None => return,
+    };
+    let progress = (elapsed / DILATION_DURATION).min(1.0);
+    let scale = 1.0 + MAX_DILATION * (progress * PI).sin();
+    for mut transform in eye.iter_mut() {
+        transform.scale = Vec3::new(scale, scale, 1.0);
+    }
+    near_miss.dilation = (progress < 1.0).then(|| elapsed);
+}
+
 // HACK: fix the transform of the child mesh used for detecting we are hovering
 // the sleeve not updating correctly on being loaded.
 // This is because bavy doesn't properly update the transform of children that
@@ -101,51 +488,164 @@ fn update_sleeve_transform(
 
 fn execute_cheat(
     mut bird_eye: Query<&mut Animated, With<BirdPupilRoot>>,
-    mut gameover_events: EventWriter<GameOver>,
+    mut game_events: EventWriter<GameEvent>,
     mut ui: EventWriter<EffectEvent>,
+    mut audio: EventWriter<AudioRequest>,
     mut watch: ResMut<BirdEye>,
     mut cmds: Commands,
     mut events: EventReader<CheatEvent>,
+    mut last_catch: ResMut<LastCatch>,
+    opponent: Res<OpponentProfile>,
+    battles: Res<BattleCount>,
+    sleeved: Query<(), With<SleeveCard>>,
+    cards: Query<&Card>,
+    mut mood: ResMut<Mood>,
 ) {
+    let mut sleeved = sleeved.iter().count();
     for event in events.iter() {
         match event {
-            CheatEvent::ConfuseBird => {
-                watch.is_watching = false;
+            CheatEvent::ConfuseBird(distraction) => {
+                watch.distract(distraction.sleeves(opponent.bird.sleeves_per_distraction));
+                audio.send(AudioRequest::PlaySeedToss);
                 if let Ok(mut anim) = bird_eye.get_single_mut() {
                     *anim = Animated::Circle { radius: 0.1, period: 1.0, offset: 0.0 };
                 }
             }
-            CheatEvent::HideInSleeve(_) if watch.is_watching => {
-                screen_print!("caught cheating");
-                gameover_events.send(GameOver(EndReason::CaughtCheating));
+            CheatEvent::HideInSleeve(entity) if watch.is_watching => {
+                debug_print!("caught cheating");
+                last_catch.0 = cards.get(*entity).ok().map(|card| Catch {
+                    card: card.clone(),
+                    earlier_sleeves: watch.sleeved_this_game,
+                    battle: battles.current(),
+                });
+                if let Some(catch) = &last_catch.0 {
+                    game_events.send(GameEvent::CheatCaught(catch.card.clone()));
+                }
+                *mood = Mood::Caught;
+                game_events.send(GameEvent::GameEnded(EndReason::CaughtCheating));
             }
             CheatEvent::HideInSleeve(entity) => {
-                if let Ok(mut anim) = bird_eye.get_single_mut() {
-                    *anim = Animated::Static;
+                watch.sleeve();
+                if watch.is_watching {
+                    if let Ok(mut anim) = bird_eye.get_single_mut() {
+                        *anim = Animated::Static;
+                    }
+                    ui.send(EffectEvent::EndCheat);
+                }
+                audio.send(AudioRequest::PlaySleeveSlip);
+                if let Ok(card) = cards.get(*entity) {
+                    game_events.send(GameEvent::CardSleeved(card.clone()));
                 }
-                watch.is_watching = true;
-                ui.send(EffectEvent::EndCheat);
-                cmds.entity(*entity).insert(SleeveCard);
+                cmds.entity(*entity).insert(SleeveCard::new(sleeved));
+                sleeved += 1;
             }
         }
     }
 }
 
+/// Smoothly go from `0.0` to `1.0` as `progress` does.
+fn ease(progress: f32) -> f32 {
+    let progress = progress.clamp(0.0, 1.0);
+    progress * progress * (3.0 - 2.0 * progress)
+}
+
+/// Where the card at `index` sits in the sleeve, `depth` from `0.0` (just
+/// out of the sleeve) to `1.0` (only its [`PEEK`] out of it). `scale` is the
+/// scale of the card in hand.
+///
+/// Cards slide along the sleeve Y axis, the sleeve opening is at its origin.
+fn sleeve_transform(sleeve: &Transform, index: usize, depth: f32, scale: Vec3) -> Transform {
+    let scale = scale * SLEEVED_SCALE;
+    let height = CARD_HIT_BOX.y * scale.y;
+    let stack = index.min(MAX_STACKED - 1) as f32;
+    let out = 0.5 - depth * (1.0 - PEEK);
+    let offset = Vec3::Y * out * height + STACK_OFFSET * stack;
+    Transform {
+        translation: sleeve.translation + sleeve.rotation * offset,
+        rotation: sleeve.rotation,
+        scale,
+    }
+}
+
+/// A card `progress` (from `0.0` to `1.0`) into sliding from `from` to its
+/// slot at `index` in the sleeve. It first reaches the sleeve opening, then
+/// slides in.
+fn slide_in(from: &Transform, sleeve: &Transform, index: usize, progress: f32) -> Transform {
+    if progress < SLIDE_APPROACH {
+        let opening = sleeve_transform(sleeve, index, 0.0, from.scale);
+        let t = ease(progress / SLIDE_APPROACH);
+        Transform {
+            translation: from.translation.lerp(opening.translation, t),
+            rotation: from.rotation.slerp(opening.rotation, t),
+            scale: from.scale.lerp(opening.scale, t),
+        }
+    } else {
+        let t = ease((progress - SLIDE_APPROACH) / (1.0 - SLIDE_APPROACH));
+        sleeve_transform(sleeve, index, t, from.scale)
+    }
+}
+
+/// A card `progress` (from `0.0` to `1.0`) into sliding out of its slot at
+/// `index` in the sleeve, back to its `scale` in hand.
+fn slide_out(sleeve: &Transform, index: usize, progress: f32, scale: Vec3) -> Transform {
+    let t = ease(progress);
+    let slot = sleeve_transform(sleeve, index, 1.0 - t, scale);
+    Transform {
+        scale: scale * (SLEEVED_SCALE + (1.0 - SLEEVED_SCALE) * t),
+        ..slot
+    }
+}
+
+/// Turn a sleeved card `flip` (from `0.0` to `1.0`) of the way face down,
+/// see [`GameplaySettings::streaming`].
+fn face_down(transform: Transform, flip: f32) -> Transform {
+    let rotation = transform.rotation * Quat::from_rotation_y(PI * ease(flip));
+    Transform { rotation, ..transform }
+}
+
 fn follow_sleeve(
-    mut cards: Query<&mut Transform, With<SleeveCard>>,
+    mut cards: Query<(&mut Transform, &mut SleeveCard)>,
     sleeve: Query<&GlobalTransform, With<PlayerSleeve>>,
     time: Res<Time>,
+    speed: Res<GameSpeed>,
+    gameplay: Res<GameplaySettings>,
 ) {
-    let card_speed = 10.0 * time.delta_seconds();
-    for mut transform in cards.iter_mut() {
-        let sleeve_pos = sleeve.single().compute_transform();
-        let target = sleeve_pos.translation;
-        let origin = transform.translation;
-        transform.translation += (target - origin) * card_speed;
+    let sleeve = match sleeve.get_single() {
+        Ok(sleeve) => sleeve.compute_transform(),
+        Err(_) => return,
+    };
+    let duration = speed.duration(SLIDE_DURATION);
+    for (mut transform, mut card) in cards.iter_mut() {
+        let from = *card.from.get_or_insert(*transform);
+        card.elapsed += time.delta_seconds();
+        let progress = (card.elapsed / duration).min(1.0);
+        let flip = if gameplay.streaming { progress } else { 0.0 };
+        *transform = face_down(slide_in(&from, &sleeve, card.index, progress), flip);
+    }
+}
 
-        let target = sleeve_pos.rotation;
-        let origin = transform.rotation;
-        transform.rotation = origin.lerp(target, card_speed);
+fn leave_sleeve(
+    mut cmds: Commands,
+    mut cards: Query<(Entity, &mut Transform, &mut LeavingSleeve)>,
+    sleeve: Query<&GlobalTransform, With<PlayerSleeve>>,
+    time: Res<Time>,
+    speed: Res<GameSpeed>,
+    gameplay: Res<GameplaySettings>,
+) {
+    let sleeve = match sleeve.get_single() {
+        Ok(sleeve) => sleeve.compute_transform(),
+        Err(_) => return,
+    };
+    let duration = speed.duration(SLIDE_DURATION);
+    for (entity, mut transform, mut card) in cards.iter_mut() {
+        card.elapsed += time.delta_seconds();
+        let progress = (card.elapsed / duration).min(1.0);
+        let flip = if gameplay.streaming { 1.0 - progress } else { 0.0 };
+        let slid = slide_out(&sleeve, card.index, progress, card.scale);
+        *transform = face_down(slid, flip);
+        if progress >= 1.0 {
+            cmds.entity(entity).remove::<LeavingSleeve>();
+        }
     }
 }
 
@@ -154,11 +654,212 @@ impl BevyPlugin for Plugin {
     fn build(&self, app: &mut App) {
         app.add_event::<CheatEvent>()
             .init_resource::<BirdEye>()
+            .init_resource::<NearMiss>()
+            .init_resource::<BirdMemory>()
+            .init_resource::<LastCatch>()
+            .add_system_set(
+                SystemSet::on_enter(self.0)
+                    .with_system(wake_bird)
+                    .with_system(forget_catch),
+            )
             .add_system_set(SystemSet::on_exit(self.0).with_system(cleanup))
-            .add_system_set(SystemSet::on_update(self.0).with_system(update_sleeve_transform))
-            .add_system(use_seed)
+            .add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(forget_cheater))
+            .add_system_set(
+                SystemSet::on_update(self.0)
+                    .with_system(update_sleeve_transform)
+                    .with_system(use_seed)
+                    .with_system(use_trinket)
+                    .with_system(warn_near_miss)
+                    .with_system(dilate_pupil)
+                    .with_system(watch_active_player)
+                    .with_system(squint_bird_eye.after(watch_active_player)),
+            )
+            // After the cheats, so that the player caught is still active
+            .add_system(remember_cheater.after("cheat"))
             .add_system(follow_sleeve)
+            .add_system(leave_sleeve)
             .add_system(control_bird_pupil)
-            .add_system(execute_cheat);
+            .add_system(execute_cheat.label("cheat"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sleeve_verdict() {
+        let state = |sleeved, deck_remaining| SleeveState { sleeved, capacity: 3, deck_remaining };
+        assert_eq!(can_sleeve(&state(0, 10)), SleeveVerdict::Allowed);
+        assert_eq!(can_sleeve(&state(2, 1)), SleeveVerdict::Allowed);
+        assert_eq!(can_sleeve(&state(3, 10)), SleeveVerdict::Full);
+        assert_eq!(can_sleeve(&state(3, 0)), SleeveVerdict::Full);
+        assert_eq!(can_sleeve(&state(1, 0)), SleeveVerdict::DeckEmpty);
+        let no_sleeve = SleeveState { sleeved: 0, capacity: 0, deck_remaining: 10 };
+        assert_eq!(can_sleeve(&no_sleeve), SleeveVerdict::Full);
+    }
+
+    #[test]
+    fn distraction_gets_more_expensive() {
+        let rules = GameRules { seed_cap: 10, ..default() };
+        let mut bird = BirdEye::default();
+        assert_eq!(bird.distraction_cost(&rules), 1);
+        bird.sleeved_this_game = 2;
+        assert_eq!(bird.distraction_cost(&rules), 3);
+        bird.vigilant = true;
+        assert_eq!(bird.distraction_cost(&rules), 4);
+        let capped = GameRules { seed_cap: 3, ..default() };
+        assert_eq!(bird.distraction_cost(&capped), 3);
+    }
+
+    #[test]
+    fn distraction_lasts_for_some_sleeves() {
+        let mut bird = BirdEye::default();
+        bird.distract(1);
+        assert!(!bird.is_watching);
+        bird.sleeve();
+        assert!(bird.is_watching);
+        bird.distract(2);
+        bird.sleeve();
+        assert!(!bird.is_watching);
+        bird.sleeve();
+        assert!(bird.is_watching);
+        assert_eq!(bird.sleeved_this_game, 3);
+        bird.distract(0);
+        assert!(!bird.is_watching, "a distraction allows at least a sleeve");
+    }
+
+    #[test]
+    fn trinkets_distract_twice_as_long() {
+        let mut bird = BirdEye::default();
+        bird.distract(Distraction::Trinket.sleeves(1));
+        bird.sleeve();
+        assert!(!bird.is_watching);
+        bird.sleeve();
+        assert!(bird.is_watching);
+        assert_eq!(Distraction::Seed.sleeves(2), 2);
+        assert_eq!(Distraction::Trinket.sleeves(2), 4);
+        assert_eq!(Distraction::Trinket.sleeves(0), 2);
+    }
+
+    /// How far along the sleeve axis the top of a sleeved card is, from the
+    /// sleeve opening.
+    fn top_out_of_sleeve(sleeve: &Transform, card: &Transform) -> f32 {
+        let top = card.translation + card.rotation * Vec3::Y * CARD_HIT_BOX.y * card.scale.y / 2.0;
+        (sleeve.rotation.inverse() * (top - sleeve.translation)).y
+    }
+
+    #[test]
+    fn sleeved_cards_peek_out() {
+        let sleeve = Transform::from_xyz(1.0, -2.0, 0.5).with_rotation(Quat::from_rotation_z(0.3));
+        let scale = Vec3::splat(0.5);
+        let height = CARD_HIT_BOX.y * 0.5 * SLEEVED_SCALE;
+        let slot = sleeve_transform(&sleeve, 0, 1.0, scale);
+        assert!((top_out_of_sleeve(&sleeve, &slot) - PEEK * height).abs() < 1e-4);
+        assert!(slot.scale.abs_diff_eq(scale * SLEEVED_SCALE, 1e-5));
+        let opening = sleeve_transform(&sleeve, 0, 0.0, scale);
+        assert!((top_out_of_sleeve(&sleeve, &opening) - height).abs() < 1e-4);
+
+        let slots: Vec<_> = (0..4)
+            .map(|index| sleeve_transform(&sleeve, index, 1.0, scale).translation)
+            .collect();
+        assert_ne!(slots[0], slots[1]);
+        assert_ne!(slots[1], slots[2]);
+        assert_eq!(slots[2], slots[3], "only the first cards are offset");
+    }
+
+    fn assert_close(left: Transform, right: Transform) {
+        let close = left.translation.abs_diff_eq(right.translation, 1e-5)
+            && left.rotation.abs_diff_eq(right.rotation, 1e-5)
+            && left.scale.abs_diff_eq(right.scale, 1e-5);
+        assert!(close, "{left:?} != {right:?}");
+    }
+
+    #[test]
+    fn cards_slide_in_and_out() {
+        let sleeve = Transform::from_xyz(1.0, -2.0, 0.5).with_rotation(Quat::from_rotation_z(0.3));
+        let from = Transform::from_xyz(-3.0, 1.0, 0.2).with_scale(Vec3::splat(0.5));
+        assert_close(slide_in(&from, &sleeve, 1, 0.0), from);
+        let slot = sleeve_transform(&sleeve, 1, 1.0, from.scale);
+        assert_close(slide_in(&from, &sleeve, 1, 1.0), slot);
+        let opening = sleeve_transform(&sleeve, 1, 0.0, from.scale);
+        assert_close(slide_in(&from, &sleeve, 1, SLIDE_APPROACH), opening);
+
+        assert_close(slide_out(&sleeve, 1, 0.0, from.scale), slot);
+        let out = slide_out(&sleeve, 1, 1.0, from.scale);
+        assert_close(out, Transform { scale: from.scale, ..opening });
+    }
+
+    #[test]
+    fn streamed_cards_show_their_back() {
+        let sleeve = Transform::from_rotation(Quat::from_rotation_z(0.3));
+        let slot = sleeve_transform(&sleeve, 0, 1.0, Vec3::ONE);
+        assert_close(face_down(slot, 0.0), slot);
+        let hidden = face_down(slot, 1.0);
+        let facing = |t: Transform| t.rotation * Vec3::Z;
+        assert!(facing(hidden).abs_diff_eq(-facing(slot), 1e-5));
+        assert_eq!(hidden.translation, slot.translation, "flipped in place");
+    }
+
+    #[test]
+    fn catch_recap_names_the_card() {
+        use crate::lang::Language;
+        let strings = Strings::new(Language::English);
+        let catch = |earlier_sleeves| Catch {
+            card: "9d".parse().unwrap(),
+            earlier_sleeves,
+            battle: 7,
+        };
+        let recap = |earlier_sleeves| catch(earlier_sleeves).recap(&strings);
+        let caught = "Caught in round 7 slipping away the 9";
+        assert_eq!(recap(2), format!("{caught}, after 2 successful steals"));
+        assert_eq!(recap(1), format!("{caught}, after 1 successful steal"));
+        assert_eq!(recap(0), format!("{caught}, on your first try"));
+    }
+
+    #[test]
+    fn bird_memory_decays() {
+        use CoopPlayer::First;
+        let rules = GameRules { bird_memory_games: 2, ..default() };
+        let mut memory = BirdMemory::default();
+        memory.game_over(&EndReason::Victory, &rules, First);
+        assert!(!memory.is_vigilant(First));
+
+        memory.game_over(&EndReason::CaughtCheating, &rules, First);
+        assert!(memory.is_vigilant(First));
+        memory.game_over(&EndReason::Loss, &rules, First);
+        assert!(memory.is_vigilant(First));
+        memory.game_over(&EndReason::Conceded, &rules, First);
+        assert!(!memory.is_vigilant(First));
+    }
+
+    #[test]
+    fn getting_caught_again_refreshes_memory() {
+        use CoopPlayer::First;
+        let rules = GameRules { bird_memory_games: 2, ..default() };
+        let mut memory = BirdMemory::default();
+        memory.game_over(&EndReason::CaughtCheating, &rules, First);
+        memory.game_over(&EndReason::Victory, &rules, First);
+        memory.game_over(&EndReason::CaughtCheating, &rules, First);
+        memory.game_over(&EndReason::Victory, &rules, First);
+        assert!(memory.is_vigilant(First));
+        memory.game_over(&EndReason::Victory, &rules, First);
+        assert!(!memory.is_vigilant(First));
+    }
+
+    #[test]
+    fn bird_remembers_who_it_caught() {
+        use CoopPlayer::{First, Second};
+        let rules = GameRules { bird_memory_games: 2, ..default() };
+        let mut memory = BirdMemory::default();
+        memory.game_over(&EndReason::CaughtCheating, &rules, Second);
+        assert!(memory.is_vigilant(Second));
+        assert!(!memory.is_vigilant(First));
+        assert!(memory.remembers_anyone());
+        memory.game_over(&EndReason::CaughtCheating, &rules, First);
+        assert!(memory.is_vigilant(First));
+        memory.game_over(&EndReason::Victory, &rules, Second);
+        assert!(memory.is_vigilant(First));
+        assert!(!memory.is_vigilant(Second), "forgets after two games");
     }
 }
@@ -0,0 +1,193 @@
+//! Coin flip deciding who leads the first battle.
+//!
+//! Once the deck reveal is dismissed, a coin spins in the middle of the
+//! screen for [`FLIP_DURATION`] seconds, or until the player clicks. It lands
+//! on who leads: the [`Initiative`] is set accordingly and announced in the
+//! effect display, then the first draw is requested.
+//!
+//! [`GameRules::first_leader`] skips the flip for a fixed leader.
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use bevy_ui_build_macros::{build_ui, size, style, unit};
+
+use crate::{
+    cleanup_marked,
+    game_flow::{Initiative, TurnRequests},
+    game_ui::EffectEvent,
+    rules::GameRules,
+    state::{GameState, TurnState},
+    Participant,
+};
+
+/// Seconds the coin spins before landing.
+const FLIP_DURATION: f32 = 1.5;
+/// Half turns of the coin per second.
+const SPIN_RATE: f32 = 6.0;
+const COIN_SIZE: f32 = 160.0;
+
+/// Sent to flip the coin, once the deck reveal is done.
+pub struct FlipCoin;
+
+/// Random source of the coin flip.
+struct CoinRng(fastrand::Rng);
+impl Default for CoinRng {
+    fn default() -> Self {
+        Self(fastrand::Rng::new())
+    }
+}
+
+#[derive(Clone, Component)]
+struct CoinRoot;
+
+/// The spinning coin, it lands on `leader`.
+#[derive(Clone, Component)]
+struct Coin {
+    leader: Participant,
+    elapsed: f32,
+}
+
+fn flip(rng: &fastrand::Rng) -> Participant {
+    if rng.bool() {
+        Participant::Player
+    } else {
+        Participant::Oppo
+    }
+}
+
+/// Horizontal scale of a coin landing on `leader`, and the face shown,
+/// `elapsed` seconds into the flip.
+///
+/// The coin spins backward from its landing angle, so that it ends face up
+/// showing the leader.
+fn coin_face(elapsed: f32, leader: Participant) -> (f32, Participant) {
+    let angle = SPIN_RATE * PI * (FLIP_DURATION - elapsed).max(0.0);
+    let half_turns = ((angle + FRAC_PI_2) / PI).floor() as u32;
+    let face = if half_turns % 2 == 0 { leader } else { leader.other() };
+    (angle.cos().abs(), face)
+}
+
+/// Start the first battle with `leader`.
+fn land(
+    leader: Participant,
+    initiative: &mut Initiative,
+    turn: &mut TurnRequests,
+    effects: &mut EventWriter<EffectEvent>,
+) {
+    initiative.lead_first(leader);
+    effects.send(EffectEvent::FirstLeader(leader));
+    turn.request(TurnState::Draw);
+}
+
+fn start_flip(
+    mut cmds: Commands,
+    mut flips: EventReader<FlipCoin>,
+    mut initiative: ResMut<Initiative>,
+    mut turn: ResMut<TurnRequests>,
+    mut effects: EventWriter<EffectEvent>,
+    rng: Res<CoinRng>,
+    rules: Res<GameRules>,
+) {
+    if flips.iter().count() == 0 {
+        return;
+    }
+    if let Some(leader) = rules.first_leader {
+        land(leader, &mut initiative, &mut turn, &mut effects);
+        return;
+    }
+    let leader = flip(&rng.0);
+    let node = NodeBundle {
+        color: Color::NONE.into(),
+        style: style! {
+            position_type: PositionType::Absolute,
+            size: size!(100 pct, 100 pct),
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+        },
+        ..default()
+    };
+    let coin = NodeBundle { color: leader.other().color().into(), ..default() };
+    build_ui! {
+        #[cmd(cmds)]
+        node[; Name::new("Coin flip"), CoinRoot](
+            entity[coin; Name::new("Coin"), Coin { leader, elapsed: 0.0 }, style! {
+                size: size!(COIN_SIZE px, COIN_SIZE px),
+            }]
+        )
+    };
+}
+
+/// Spin the coin, land it once it's done spinning or the player clicks.
+fn spin_coin(
+    mut cmds: Commands,
+    mut coins: Query<(&mut Coin, &mut Transform, &mut UiColor)>,
+    mut initiative: ResMut<Initiative>,
+    mut turn: ResMut<TurnRequests>,
+    mut effects: EventWriter<EffectEvent>,
+    roots: Query<Entity, With<CoinRoot>>,
+    mouse: Res<Input<MouseButton>>,
+    time: Res<Time>,
+) {
+    let (mut coin, mut transform, mut color) = match coins.get_single_mut() {
+        Ok(coin) => coin,
+        Err(_) => return,
+    };
+    coin.elapsed += time.delta_seconds();
+    if coin.elapsed >= FLIP_DURATION || mouse.just_pressed(MouseButton::Left) {
+        land(coin.leader, &mut initiative, &mut turn, &mut effects);
+        for root in roots.iter() {
+            cmds.entity(root).despawn_recursive();
+        }
+        return;
+    }
+    let (width, face) = coin_face(coin.elapsed, coin.leader);
+    transform.scale.x = width;
+    if color.0 != face.color() {
+        color.0 = face.color();
+    }
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        app.init_resource::<CoinRng>()
+            .add_event::<FlipCoin>()
+            .add_system_set(
+                self.0
+                    .on_update(start_flip)
+                    .with_system(spin_coin.after("reveal_click")),
+            )
+            .add_system_set(self.0.on_exit(cleanup_marked::<CoinRoot>));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeded_flips_repeat() {
+        let flips = |seed| {
+            let rng = fastrand::Rng::with_seed(seed);
+            (0..20).map(|_| flip(&rng)).collect::<Vec<_>>()
+        };
+        assert_eq!(flips(7), flips(7));
+        let both = flips(7);
+        assert!(both.contains(&Participant::Player));
+        assert!(both.contains(&Participant::Oppo));
+    }
+
+    #[test]
+    fn coin_lands_on_leader() {
+        for leader in [Participant::Player, Participant::Oppo] {
+            let (width, face) = coin_face(FLIP_DURATION, leader);
+            assert_eq!((width, face), (1.0, leader));
+            let half_turn = 1.0 / SPIN_RATE;
+            let (_, face) = coin_face(FLIP_DURATION - half_turn, leader);
+            assert_eq!(face, leader.other(), "the other face half a turn before");
+            let (width, _) = coin_face(FLIP_DURATION - half_turn / 2.0, leader);
+            assert!(width < 1e-4, "edge on a quarter turn before: {width}");
+        }
+    }
+}
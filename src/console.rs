@@ -0,0 +1,288 @@
+//! Developer console, to set up specific game situations without editing
+//! deck files.
+//!
+//! Commands typed in the console window are parsed with [`parse_command`]
+//! and sent as [`ConsoleCommand`] events, [`run_commands`] then applies them
+//! through the same events and resources gameplay uses. Results are printed
+//! to the debug text overlay.
+//!
+//! * `give player 0z`: spawn a card in the player (or `oppo`) hand
+//! * `seeds 2`: set the seed count of the active player
+//! * `turn draw`: request a [`TurnState`] transition
+//! * `state menu`: go to a [`GameState`]
+//! * `bird off`: distract the bird, `bird on` to have it watch again
+//! * `dump`: print the cards in piles, hands and decks in deck format
+//! * `rewind`: go back to the start of the previous turn, see
+//!   [`crate::autosave`]
+use bevy::ecs::query::QueryItem;
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+
+use crate::{
+    autosave::Autosaves,
+    cheat::{BirdEye, CheatEvent, Distraction, SleeveCard},
+    deck::{OppoDeck, PlayerDeck},
+    game_flow::{CoopTurn, PlayedCard, SeedCount, TurnRequests},
+    pile::{PileCard, PileType},
+    state::{GameState, TurnState},
+    war::Card,
+    CardOrigin, Participant,
+};
+
+/// How long command results stay on the debug overlay, in seconds.
+const RESULT_DURATION: f32 = 10.0;
+
+/// Spawn a card directly in a hand, read by the hand plugins.
+pub struct GiveCard(pub Participant, pub Card);
+
+#[derive(Debug, PartialEq)]
+pub enum ConsoleCommand {
+    Give(Participant, Card),
+    Seeds(usize),
+    Turn(TurnState),
+    Game(GameState),
+    /// Whether the bird should watch the player.
+    Bird(bool),
+    Dump,
+    Rewind,
+}
+
+fn parse_participant(word: &str) -> Result<Participant, String> {
+    match word {
+        "player" => Ok(Participant::Player),
+        "oppo" => Ok(Participant::Oppo),
+        _ => Err(format!("expected player or oppo, got {word:?}")),
+    }
+}
+
+fn parse_turn(word: &str) -> Result<TurnState, String> {
+    use TurnState::*;
+    match word {
+        "draw" => Ok(Draw),
+        "new" => Ok(New),
+        "player" => Ok(Player),
+        "oppo" => Ok(Oppo),
+        "played" => Ok(CardPlayed),
+        _ => Err(format!(
+            "expected draw, new, player, oppo or played, got {word:?}"
+        )),
+    }
+}
+
+fn parse_state(word: &str) -> Result<GameState, String> {
+    use GameState::*;
+    match word {
+        "menu" => Ok(MainMenu),
+        "bindings" => Ok(KeyBindingsMenu),
+        "playing" => Ok(Playing),
+        "restart" => Ok(RestartMenu),
+        "history" => Ok(HistoryMenu),
+        _ => Err(format!(
+            "expected menu, bindings, playing, restart or history, got {word:?}"
+        )),
+    }
+}
+
+fn parse_command(line: &str) -> Result<ConsoleCommand, String> {
+    let words: Vec<_> = line.split_ascii_whitespace().collect();
+    match words.as_slice() {
+        ["give", who, card] => {
+            let card = card.parse().map_err(|err| format!("bad card: {err}"))?;
+            Ok(ConsoleCommand::Give(parse_participant(who)?, card))
+        }
+        ["seeds", count] => {
+            let count = count
+                .parse()
+                .map_err(|_| format!("bad seed count {count:?}"))?;
+            Ok(ConsoleCommand::Seeds(count))
+        }
+        ["turn", turn] => Ok(ConsoleCommand::Turn(parse_turn(turn)?)),
+        ["state", state] => Ok(ConsoleCommand::Game(parse_state(state)?)),
+        ["bird", "on"] => Ok(ConsoleCommand::Bird(true)),
+        ["bird", "off"] => Ok(ConsoleCommand::Bird(false)),
+        ["dump"] => Ok(ConsoleCommand::Dump),
+        ["rewind"] => Ok(ConsoleCommand::Rewind),
+        [] => Err("empty command".to_owned()),
+        [command, ..] => Err(format!("unknown command or arguments for {command:?}")),
+    }
+}
+
+fn deck_text<'a>(cards: impl Iterator<Item = &'a Card>) -> String {
+    let cards: Vec<_> = cards.map(Card::to_string).collect();
+    cards.join(" ")
+}
+
+fn console_window(
+    mut ctx: ResMut<EguiContext>,
+    mut line: Local<String>,
+    mut commands: EventWriter<ConsoleCommand>,
+) {
+    egui::Window::new("Console")
+        .default_open(false)
+        .show(ctx.ctx_mut(), |ui| {
+            let response = ui.text_edit_singleline(&mut *line);
+            if response.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
+                match parse_command(&line) {
+                    Ok(command) => commands.send(command),
+                    Err(err) => debug_print!(sec: RESULT_DURATION, col: Color::RED, "{err}"),
+                }
+                line.clear();
+                response.request_focus();
+            }
+        });
+}
+
+type CardLocation = (
+    &'static Card,
+    &'static CardOrigin,
+    Option<&'static PileCard>,
+    Option<&'static PlayedCard>,
+    Option<&'static SleeveCard>,
+);
+
+fn in_pile(which: PileType, (_, _, pile, played, _): &QueryItem<CardLocation>) -> bool {
+    match pile {
+        Some(pile) => pile.which == which,
+        // Played cards are on their way to the war pile
+        None => which == PileType::War && played.is_some(),
+    }
+}
+
+fn in_hand(who: Participant, (_, origin, pile, played, sleeve): &QueryItem<CardLocation>) -> bool {
+    origin.0 == who && pile.is_none() && played.is_none() && sleeve.is_none()
+}
+
+/// The cards of each pile, hand and deck, in deck format.
+fn dump_cards(
+    cards: &Query<CardLocation>,
+    player_deck: &Query<&PlayerDeck>,
+    oppo_deck: &Query<&OppoDeck>,
+) -> String {
+    use Participant::{Oppo, Player};
+    let list = |name: &str, filter: &dyn Fn(&QueryItem<CardLocation>) -> bool| {
+        let cards = cards.iter().filter(|c| filter(c)).map(|c| c.0);
+        format!("{name}: {}", deck_text(cards))
+    };
+    let mut lines = vec![
+        list("war pile", &|c| in_pile(PileType::War, c)),
+        list("player pile", &|c| in_pile(PileType::Player, c)),
+        list("oppo pile", &|c| in_pile(PileType::Oppo, c)),
+        list("player hand", &|c| in_hand(Player, c)),
+        list("oppo hand", &|c| in_hand(Oppo, c)),
+        list("sleeve", &|c| c.4.is_some()),
+    ];
+    // Decks draw their last card first, deck files list the first drawn first
+    if let Ok(deck) = player_deck.get_single() {
+        lines.push(format!(
+            "player deck: {}",
+            deck_text(deck.cards().iter().rev())
+        ));
+    }
+    if let Ok(deck) = oppo_deck.get_single() {
+        lines.push(format!(
+            "oppo deck: {}",
+            deck_text(deck.cards().iter().rev())
+        ));
+    }
+    lines.join("\n")
+}
+
+fn run_commands(
+    mut commands: EventReader<ConsoleCommand>,
+    mut give: EventWriter<GiveCard>,
+    mut cheats: EventWriter<CheatEvent>,
+    mut turn: ResMut<TurnRequests>,
+    mut state: ResMut<State<GameState>>,
+    mut seeds: ResMut<SeedCount>,
+    mut bird: ResMut<BirdEye>,
+    mut autosaves: ResMut<Autosaves>,
+    coop_turn: Res<CoopTurn>,
+    cards: Query<CardLocation>,
+    player_deck: Query<&PlayerDeck>,
+    oppo_deck: Query<&OppoDeck>,
+) {
+    for command in commands.iter() {
+        match command {
+            ConsoleCommand::Give(who, card) => give.send(GiveCard(*who, card.clone())),
+            ConsoleCommand::Seeds(count) => seeds.set(coop_turn.active(), *count),
+            ConsoleCommand::Turn(next) => turn.request(*next),
+            ConsoleCommand::Game(next) => {
+                if let Err(err) = state.set(*next) {
+                    debug_print!(sec: RESULT_DURATION, col: Color::RED, "can't enter {next:?}: {err:?}");
+                    continue;
+                }
+            }
+            ConsoleCommand::Bird(false) => cheats.send(CheatEvent::ConfuseBird(Distraction::Seed)),
+            ConsoleCommand::Bird(true) => bird.is_watching = true,
+            ConsoleCommand::Dump => {
+                let dump = dump_cards(&cards, &player_deck, &oppo_deck);
+                debug_print!(sec: RESULT_DURATION, "{dump}");
+                continue;
+            }
+            ConsoleCommand::Rewind if !autosaves.rewind() => {
+                debug_print!(sec: RESULT_DURATION, col: Color::RED, "no previous turn to rewind to");
+                continue;
+            }
+            ConsoleCommand::Rewind => {}
+        }
+        debug_print!(sec: RESULT_DURATION, "done: {command:?}");
+    }
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ConsoleCommand>()
+            .add_event::<GiveCard>()
+            .add_system(console_window)
+            .add_system(run_commands.after(console_window));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(text: &str) -> Card {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn parse_valid_commands() {
+        use ConsoleCommand::*;
+        let give = Give(Participant::Player, card("0z"));
+        assert_eq!(parse_command("give player 0z"), Ok(give));
+        let give = Give(Participant::Oppo, card("9_"));
+        assert_eq!(parse_command("  give oppo   9_ "), Ok(give));
+        assert_eq!(parse_command("seeds 2"), Ok(Seeds(2)));
+        assert_eq!(
+            parse_command("turn played"),
+            Ok(Turn(TurnState::CardPlayed))
+        );
+        assert_eq!(parse_command("state menu"), Ok(Game(GameState::MainMenu)));
+        assert_eq!(parse_command("bird off"), Ok(Bird(false)));
+        assert_eq!(parse_command("dump"), Ok(Dump));
+        assert_eq!(parse_command("rewind"), Ok(Rewind));
+    }
+
+    #[test]
+    fn parse_invalid_commands() {
+        assert!(parse_command("").is_err());
+        assert!(parse_command("give player").is_err());
+        assert!(parse_command("give dealer 0z").is_err());
+        assert!(parse_command("give player 0x").is_err());
+        assert!(parse_command("seeds many").is_err());
+        assert!(parse_command("turn paused").is_err());
+        assert!(parse_command("bird maybe").is_err());
+        assert!(parse_command("dump all").is_err());
+        assert!(parse_command("rewind 2").is_err());
+    }
+
+    #[test]
+    fn deck_text_uses_deck_format() {
+        let cards = [card("0z"), card("9_"), card("5s")];
+        let text = deck_text(cards.iter());
+        assert_eq!(text, "0z 9_ 5s");
+        assert!(text.parse::<crate::deck::Deck>().is_ok());
+    }
+}
@@ -0,0 +1,703 @@
+//! The game deck, deck loading from files and drawing cards from deck.
+//!
+//! We define a custom [`Deck`] asset with a custom loader [`DeckLoader`], this
+//! way it is possible for the player to change the decks defined in
+//! `assets/decks/*.deck`, and it is also possible to hot-reload the decks for
+//! quicker iteration time.
+//!
+//! The [`stats`] submodule computes statistics on the cards left in a deck.
+use std::{
+    collections::HashMap,
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadState, LoadedAsset},
+    prelude::{Plugin as BevyPlugin, *},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+#[cfg(feature = "debug")]
+use bevy_inspector_egui::{Inspectable, RegisterInspectable};
+use bevy_scene_hook::is_scene_hooked;
+use enum_map::EnumMap;
+
+pub mod stats;
+
+use crate::{
+    opponent::OpponentProfile,
+    scene::Graveyard,
+    settings::GameplaySettings,
+    state::GameState,
+    war::{Card, ParseError, WordOfPower},
+};
+
+const PLAYER_DECK: &str = "decks/player.deck";
+/// Deck of the default opponent, see [`OpponentProfile`].
+pub const OPPO_DECK: &str = "decks/oppo.deck";
+/// Emissive color added to a deck made only of word cards.
+const WORD_GLOW: Color = Color::rgb(0.12, 0.05, 0.2);
+
+/// Why the decks can't be used to start a game.
+#[derive(Debug, PartialEq)]
+pub enum DeckError {
+    /// The deck file is not loaded yet.
+    Loading(String),
+    /// The deck file is missing or couldn't be read.
+    Invalid(String),
+    /// The deck file has a bad token, see [`ParseError::At`].
+    Malformed { file: String, error: ParseError },
+    /// The deck file has no cards.
+    Empty(String),
+    /// The decks don't have the same number of cards.
+    Mismatched { player: usize, oppo: usize },
+}
+impl DeckError {
+    /// Whether waiting a bit may fix the error.
+    pub fn is_loading(&self) -> bool {
+        matches!(self, DeckError::Loading(_))
+    }
+}
+impl fmt::Display for DeckError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeckError::Loading(file) => write!(f, "assets/{file} is still loading"),
+            DeckError::Invalid(file) => write!(f, "assets/{file} is missing or invalid"),
+            DeckError::Malformed { file, error } => write!(f, "assets/{file}: {error}"),
+            DeckError::Empty(file) => write!(f, "assets/{file} has no cards"),
+            DeckError::Mismatched { player, oppo } => write!(
+                f,
+                "The decks have different sizes: {player} cards for the player, {oppo} for the oppo"
+            ),
+        }
+    }
+}
+
+/// Loading progress of a deck file.
+enum DeckFile<'a> {
+    Loading,
+    Failed,
+    /// Failed with this parse error.
+    Malformed(ParseError),
+    Loaded(&'a Deck),
+}
+
+/// Check that both decks can be played with, the paths being the files of
+/// the `player` and `oppo` decks.
+fn check_decks(
+    (player, player_path): (DeckFile, &str),
+    (oppo, oppo_path): (DeckFile, &str),
+) -> Result<(), DeckError> {
+    let loaded = |file, path: &str| match file {
+        DeckFile::Loading => Err(DeckError::Loading(path.to_owned())),
+        DeckFile::Failed => Err(DeckError::Invalid(path.to_owned())),
+        DeckFile::Malformed(error) => Err(DeckError::Malformed { file: path.to_owned(), error }),
+        DeckFile::Loaded(deck) if deck.remaining() == 0 => Err(DeckError::Empty(path.to_owned())),
+        DeckFile::Loaded(deck) => Ok(deck.remaining()),
+    };
+    let (player, oppo) = (loaded(player, player_path)?, loaded(oppo, oppo_path)?);
+    if player != oppo {
+        return Err(DeckError::Mismatched { player, oppo });
+    }
+    Ok(())
+}
+
+/// The player deck, the oppo deck depends on the [`OpponentProfile`].
+///
+/// The player deck is the one of the selected [`crate::archetype`].
+pub struct DeckAssets {
+    pub player: Handle<Deck>,
+    /// Path of the player deck, in `assets/`.
+    player_file: String,
+    parse_errors: DeckParseErrors,
+}
+impl Clone for DeckAssets {
+    fn clone(&self) -> Self {
+        Self {
+            player: self.player.clone_weak(),
+            player_file: self.player_file.clone(),
+            parse_errors: self.parse_errors.clone(),
+        }
+    }
+}
+impl FromWorld for DeckAssets {
+    fn from_world(world: &mut World) -> Self {
+        let parse_errors = world.get_resource::<DeckParseErrors>().unwrap().clone();
+        let assets = world.get_resource::<AssetServer>().unwrap();
+        Self {
+            player: assets.load(PLAYER_DECK),
+            player_file: PLAYER_DECK.to_owned(),
+            parse_errors,
+        }
+    }
+}
+impl DeckAssets {
+    /// Play with the deck at `file`, already loading in `handle`.
+    pub fn set_player(&mut self, file: &str, handle: Handle<Deck>) {
+        self.player = handle;
+        self.player_file = file.to_owned();
+    }
+    /// Check that the player deck and the `oppo` deck files are loaded and
+    /// can be played with.
+    pub fn check(
+        &self,
+        oppo: &OpponentProfile,
+        server: &AssetServer,
+        decks: &Assets<Deck>,
+    ) -> Result<(), DeckError> {
+        let file = |handle: &Handle<Deck>, path: &str| match server.get_load_state(handle) {
+            // A deck that failed to hot-reload still has its previous version
+            // in `decks`, so this is checked first.
+            LoadState::Failed => match self.parse_errors.get(path) {
+                Some(error) => DeckFile::Malformed(error),
+                None => DeckFile::Failed,
+            },
+            _ => decks
+                .get(handle)
+                .map_or(DeckFile::Loading, DeckFile::Loaded),
+        };
+        let player = file(&self.player, &self.player_file);
+        let oppo_deck = file(&oppo.deck, &oppo.deck_file);
+        check_decks((player, &self.player_file), (oppo_deck, &oppo.deck_file))
+    }
+}
+
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+#[derive(Debug, TypeUuid, Clone)]
+#[uuid = "010293ef-dc29-4d94-aae1-39da45947644"]
+pub struct Deck {
+    cards: Vec<Card>,
+}
+impl Deck {
+    /// A deck of `cards` in file order, the first one is drawn first.
+    pub fn new(mut cards: Vec<Card>) -> Self {
+        cards.reverse();
+        Self { cards }
+    }
+    fn draw(&mut self, count: usize) -> Vec<Card> {
+        let len = self.remaining();
+        let index = len - count.min(len);
+        self.cards.split_off(index)
+    }
+    fn remaining(&self) -> usize {
+        self.cards.len()
+    }
+    fn score(&self) -> i32 {
+        self.cards.iter().map(Card::max_value).sum()
+    }
+    /// Cards left in the deck, the last one is drawn first.
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+    /// The card drawn next, without drawing it.
+    fn peek(&self) -> Option<&Card> {
+        self.cards.last()
+    }
+    fn composition(&self) -> DeckComposition {
+        DeckComposition::new(self.cards())
+    }
+    /// Fraction of the remaining cards with a word of power.
+    fn word_fraction(&self) -> f32 {
+        let words = self.cards.iter().filter(|c| c.word.is_some()).count();
+        words as f32 / self.remaining().max(1) as f32
+    }
+}
+
+/// Summary of what cards a deck contains.
+#[derive(Debug, Default, PartialEq)]
+pub struct DeckComposition {
+    /// Count of cards with values 0 to 3, 4 to 6 and 7 to 9.
+    pub bands: [usize; 3],
+    pub words: EnumMap<WordOfPower, usize>,
+    /// Sum of the card values, not counting bonuses.
+    pub total_value: i32,
+}
+impl DeckComposition {
+    fn new(cards: &[Card]) -> Self {
+        let mut composition = Self::default();
+        for card in cards {
+            let band = match card.value_i32() {
+                0..=3 => 0,
+                4..=6 => 1,
+                _ => 2,
+            };
+            composition.bands[band] += 1;
+            if let Some(word) = card.word {
+                composition.words[word] += 1;
+            }
+            composition.total_value += card.value_i32();
+        }
+        composition
+    }
+}
+
+/// The whitespace separated tokens of `line`, with their byte offset.
+fn tokens(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    line.split(|c: char| c.is_ascii_whitespace())
+        .scan(0, |offset, token| {
+            let start = *offset;
+            // Separators are ascii, one byte long.
+            *offset += token.len() + 1;
+            Some((start, token))
+        })
+        .filter(|(_, token)| !token.is_empty())
+}
+
+/// Check a `[section]` header, sections only organize the file.
+fn parse_section(header: &str) -> Result<(), ParseError> {
+    let name = header.strip_prefix('[').and_then(|h| h.strip_suffix(']'));
+    match name {
+        Some(name) if !name.trim().is_empty() && !name.contains(['[', ']']) => Ok(()),
+        _ => Err(ParseError::BadSection(header.to_owned())),
+    }
+}
+
+/// Read a deck file.
+///
+/// Cards are separated by whitespace, in the format of [`Card::from_str`].
+/// Text after a `#` is a comment. A line starting with `[` is a section
+/// header, such as `[opening]`, the cards of all sections are in the same
+/// deck, in order. Errors are [`ParseError::At`] the offending token.
+impl FromStr for Deck {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut deck = Vec::new();
+        for (index, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default();
+            let at = |offset: usize, token: &str, error| ParseError::At {
+                line: index + 1,
+                column: line[..offset].chars().count() + 1,
+                token: token.to_owned(),
+                error: Box::new(error),
+            };
+            let header = line.trim_start();
+            if header.starts_with('[') {
+                let offset = line.len() - header.len();
+                let header = header.trim_end();
+                parse_section(header).map_err(|err| at(offset, header, err))?;
+                continue;
+            }
+            for (offset, token) in tokens(line) {
+                deck.push(token.parse().map_err(|err| at(offset, token, err))?);
+            }
+        }
+        Ok(Self::new(deck))
+    }
+}
+
+/// Why deck files failed to parse, by asset path.
+///
+/// Shared between the [`DeckLoader`], which runs outside of the ECS, and
+/// [`DeckAssets::check`].
+#[derive(Clone, Default)]
+pub struct DeckParseErrors(Arc<Mutex<HashMap<PathBuf, ParseError>>>);
+impl DeckParseErrors {
+    fn get(&self, path: &str) -> Option<ParseError> {
+        self.0.lock().ok()?.get(Path::new(path)).cloned()
+    }
+    fn set(&self, path: &Path, error: Option<ParseError>) {
+        if let Ok(mut errors) = self.0.lock() {
+            match error {
+                Some(error) => errors.insert(path.to_owned(), error),
+                None => errors.remove(path),
+            };
+        }
+    }
+}
+
+pub struct DeckLoader {
+    errors: DeckParseErrors,
+}
+impl AssetLoader for DeckLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let parsed = std::str::from_utf8(bytes)?.parse::<Deck>();
+            self.errors
+                .set(load_context.path(), parsed.as_ref().err().cloned());
+            load_context.set_default_asset(LoadedAsset::new(parsed?));
+            Ok(())
+        })
+    }
+    fn extensions(&self) -> &[&str] {
+        &["deck"]
+    }
+}
+
+macro_rules! impl_deck_methods {
+    ($what:ident) => (
+        impl $what {
+            impl_deck_methods!(@method score((&)) -> i32);
+            impl_deck_methods!(@method draw((&mut), count: usize) -> Vec<Card>);
+            impl_deck_methods!(@method remaining((&)) -> usize);
+            impl_deck_methods!(@method composition((&)) -> DeckComposition);
+            impl_deck_methods!(@method cards((&)) -> &[Card]);
+            impl_deck_methods!(@method peek((&)) -> Option<&Card>);
+            impl_deck_methods!(@method word_fraction((&)) -> f32);
+            pub fn new(deck: Deck) -> Self {
+                Self(deck)
+            }
+        }
+    );
+    (@method $name:ident (
+        ($($self_param:tt)*)
+        $(, $param_name:ident : $param_type:ty)*)
+        $(-> $ret:ty)?
+    ) => (
+        pub fn $name($($self_param)* self $(, $param_name : $param_type)*) $(-> $ret)? {
+            self.0.$name($($param_name ,)*)
+        }
+    )
+}
+
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+#[derive(Component)]
+pub struct PlayerDeck(Deck);
+impl_deck_methods!(PlayerDeck);
+
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+#[derive(Component)]
+pub struct OppoDeck(Deck);
+impl_deck_methods!(OppoDeck);
+
+fn update_meshes(
+    (player_cards, oppo_cards): (usize, usize),
+    (player, oppo): (Entity, Entity),
+    meshes: &mut Assets<Mesh>,
+    meshes_q: &mut Query<(&Handle<Mesh>, &mut Visibility)>,
+) -> Option<()> {
+    // 18 -> 0.124 ; -- 0 -> -0.9
+    use bevy::render::mesh::VertexAttributeValues::{Float32x2, Float32x3};
+    let mut update_deck = |entity, card_count| {
+        let (handle, mut visibility) = meshes_q.get_mut(entity).ok()?;
+        visibility.is_visible = card_count != 0;
+        let mesh = meshes.get_mut(handle)?;
+        if let Float32x3(positions) = mesh.attribute_mut(Mesh::ATTRIBUTE_POSITION)? {
+            for pos in positions.iter_mut().filter(|v| v[1] > -0.901) {
+                pos[1] = card_count as f32 / 18.0 - 0.9;
+            }
+        }
+        if let Float32x2(uvs) = mesh.attribute_mut(Mesh::ATTRIBUTE_UV_0)? {
+            // the > 0.218 is to only modify uv points for the sides of the deck,
+            // avoiding to modify the uv for the top and bottom face which are
+            // not concerned by the resize.
+            for point in uvs.iter_mut().filter(|p| p[0] > 0.218 && p[1] > 0.001) {
+                point[1] = card_count as f32 / 18. + 0.002;
+            }
+        }
+        Some(())
+    };
+    update_deck(player, player_cards)?;
+    update_deck(oppo, oppo_cards)?;
+    Some(())
+}
+
+fn resize_decks(
+    player_parent: Query<(&Children, &PlayerDeck), Changed<PlayerDeck>>,
+    oppo_parent: Query<(&Children, &OppoDeck), Changed<OppoDeck>>,
+    mut meshes_q: Query<(&Handle<Mesh>, &mut Visibility)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+) {
+    let decks = (player_parent.get_single(), oppo_parent.get_single());
+    if let (Ok((player, player_deck)), Ok((oppo, oppo_deck))) = decks {
+        update_meshes(
+            (player_deck.remaining(), oppo_deck.remaining()),
+            (player[0], oppo[0]),
+            &mut meshes,
+            &mut meshes_q,
+        );
+    }
+}
+
+/// A deck mesh with its own material, so that each deck is tinted
+/// independently.
+#[derive(Component)]
+struct DeckTint {
+    /// Emissive color of the material before tinting.
+    base: Color,
+}
+
+/// `base` with a glow proportional to the `fraction` of word cards in a deck.
+fn tinted_emissive(base: Color, fraction: f32) -> Color {
+    Color::rgb(
+        base.r() + WORD_GLOW.r() * fraction,
+        base.g() + WORD_GLOW.g() * fraction,
+        base.b() + WORD_GLOW.b() * fraction,
+    )
+}
+
+fn tint_deck(
+    entity: Entity,
+    word_fraction: f32,
+    cmds: &mut Commands,
+    materials: &mut Assets<StandardMaterial>,
+    tints: &mut Query<(&mut Handle<StandardMaterial>, Option<&DeckTint>)>,
+) -> Option<()> {
+    let (mut handle, tint) = tints.get_mut(entity).ok()?;
+    let base = match tint {
+        Some(tint) => tint.base,
+        None => {
+            // Both decks share the material from the scene file
+            let material = materials.get(&handle)?.clone();
+            let base = material.emissive;
+            *handle = materials.add(material);
+            cmds.entity(entity).insert(DeckTint { base });
+            base
+        }
+    };
+    materials.get_mut(&handle)?.emissive = tinted_emissive(base, word_fraction);
+    Some(())
+}
+
+/// Make decks glow faintly according to how many word cards they still hold.
+///
+/// Each deck gets its own material, so low spec machines keep the shared one.
+fn tint_decks(
+    player_parent: Query<(&Children, &PlayerDeck), Changed<PlayerDeck>>,
+    oppo_parent: Query<(&Children, &OppoDeck), Changed<OppoDeck>>,
+    mut tints: Query<(&mut Handle<StandardMaterial>, Option<&DeckTint>)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    gameplay: Res<GameplaySettings>,
+    mut cmds: Commands,
+) {
+    if gameplay.low_spec {
+        return;
+    }
+    let player = player_parent
+        .iter()
+        .filter_map(|(c, deck)| Some((*c.first()?, deck.word_fraction())));
+    let oppo = oppo_parent
+        .iter()
+        .filter_map(|(c, deck)| Some((*c.first()?, deck.word_fraction())));
+    for (entity, fraction) in player.chain(oppo) {
+        tint_deck(entity, fraction, &mut cmds, &mut materials, &mut tints);
+    }
+}
+
+fn load_decks(
+    unloaded_decks: Query<(Entity, &Handle<Deck>, &Name), (Without<PlayerDeck>, Without<OppoDeck>)>,
+    mut cmds: Commands,
+    decks: Res<Assets<Deck>>,
+    deck_assets: Res<DeckAssets>,
+    oppo: Res<OpponentProfile>,
+) {
+    for (to_load, handle, name) in unloaded_decks.iter() {
+        // The decks depend on the selected opponent and archetype, the scene
+        // only has placeholder handles for them.
+        let handle = match name.as_str() {
+            "OppoDeck" => &oppo.deck,
+            "PlayerDeck" => &deck_assets.player,
+            _ => handle,
+        };
+        if let Some(deck) = decks.get(handle) {
+            let mut cmds = cmds.entity(to_load);
+            match name.as_str() {
+                "PlayerDeck" => cmds.insert(PlayerDeck::new(deck.clone())),
+                "OppoDeck" => cmds.insert(OppoDeck::new(deck.clone())),
+                _ => &mut cmds,
+            };
+        }
+    }
+}
+
+/// Unload the decks when leaving the game, [`load_decks`] loads them anew
+/// for the next one.
+///
+/// Pausing pushes [`GameState::PauseMenu`] on top of [`GameState::Playing`],
+/// which pauses it without running its `on_exit` systems, so the decks and
+/// the cards drawn from them are kept while paused.
+fn reset_decks(decks: Query<Entity, Or<(With<PlayerDeck>, With<OppoDeck>)>>, mut cmds: Commands) {
+    for to_unload in decks.iter() {
+        cmds.entity(to_unload)
+            .remove::<PlayerDeck>()
+            .remove::<OppoDeck>();
+    }
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        #[cfg(feature = "debug")]
+        app.register_inspectable::<PlayerDeck>()
+            .register_inspectable::<OppoDeck>()
+            .add_system(stats::show_stats);
+
+        let parse_errors = DeckParseErrors::default();
+        app.add_asset::<Deck>()
+            .add_asset_loader(DeckLoader { errors: parse_errors.clone() })
+            .insert_resource(parse_errors)
+            .init_resource::<DeckAssets>()
+            .add_system(resize_decks.with_run_criteria(is_scene_hooked::<Graveyard>))
+            .add_system(tint_decks.with_run_criteria(is_scene_hooked::<Graveyard>))
+            .add_system(load_decks)
+            .add_system_set(self.0.on_exit(reset_decks.after(load_decks)));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::TurnState;
+
+    #[test]
+    fn deck_composition() {
+        let deck: Deck = "0_ 3seed 4doub 6_ 7_ 9seed".parse().unwrap();
+        let composition = deck.composition();
+        assert_eq!(composition.bands, [2, 2, 2]);
+        assert_eq!(composition.words[WordOfPower::Egeq], 2);
+        assert_eq!(composition.words[WordOfPower::Qube], 1);
+        assert_eq!(composition.words[WordOfPower::Zihbm], 0);
+        assert_eq!(composition.total_value, 29);
+    }
+
+    #[test]
+    fn decks_survive_pausing() {
+        let mut world = World::new();
+        world.insert_resource(State::new(GameState::Playing));
+        world.insert_resource(State::new(TurnState::Draw));
+        let mut stage = SystemStage::single_threaded()
+            .with_system_set(State::<GameState>::get_driver())
+            .with_system_set(State::<TurnState>::get_driver())
+            .with_system_set(SystemSet::on_exit(GameState::Playing).with_system(reset_decks));
+        let deck: Deck = "0_ 3seed 4doub 6_ 7_".parse().unwrap();
+        let player = world.spawn().insert(PlayerDeck::new(deck.clone())).id();
+        let oppo = world.spawn().insert(OppoDeck::new(deck)).id();
+        stage.run(&mut world);
+        world.get_mut::<PlayerDeck>(player).unwrap().draw(3);
+
+        world
+            .resource_mut::<State<GameState>>()
+            .push(GameState::PauseMenu)
+            .unwrap();
+        world
+            .resource_mut::<State<TurnState>>()
+            .push(TurnState::Paused)
+            .unwrap();
+        stage.run(&mut world);
+        world.resource_mut::<State<TurnState>>().pop().unwrap();
+        world.resource_mut::<State<GameState>>().pop().unwrap();
+        stage.run(&mut world);
+        assert_eq!(world.get::<PlayerDeck>(player).unwrap().remaining(), 2);
+        assert_eq!(world.get::<OppoDeck>(oppo).unwrap().remaining(), 5);
+        let turn = *world.resource::<State<TurnState>>().current();
+        assert_eq!(turn, TurnState::Draw, "still drawing once resumed");
+
+        world
+            .resource_mut::<State<GameState>>()
+            .replace(GameState::MainMenu)
+            .unwrap();
+        stage.run(&mut world);
+        assert!(world.get::<PlayerDeck>(player).is_none(), "left the game");
+        assert!(world.get::<OppoDeck>(oppo).is_none());
+    }
+
+    #[test]
+    fn word_fraction_counts_remaining_cards() {
+        let mut deck: Deck = "3seed 4doub 6_ 7_".parse().unwrap();
+        assert_eq!(deck.word_fraction(), 0.5);
+        deck.draw(2);
+        assert_eq!(deck.word_fraction(), 1.0);
+        deck.draw(2);
+        assert_eq!(deck.word_fraction(), 0.0);
+    }
+
+    #[test]
+    fn tint_glows_with_word_fraction() {
+        let base = Color::rgb(0.1, 0.2, 0.3);
+        assert_eq!(tinted_emissive(base, 0.0), base);
+        let half = tinted_emissive(base, 0.5);
+        assert_eq!(half, Color::rgb(0.1 + 0.06, 0.2 + 0.025, 0.3 + 0.1));
+        let full = tinted_emissive(base, 1.0);
+        let glow = Color::rgb(0.1 + 0.12, 0.2 + 0.05, 0.3 + 0.2);
+        assert_eq!(full, glow);
+        assert!(base.r() < half.r() && half.r() < full.r());
+    }
+
+    fn error_at(line: usize, column: usize, token: &str, error: ParseError) -> ParseError {
+        let (token, error) = (token.to_owned(), Box::new(error));
+        ParseError::At { line, column, token, error }
+    }
+
+    #[test]
+    fn deck_files_have_comments_and_sections() {
+        let plain: Deck = "0_ 3seed\n4doub".parse().unwrap();
+        let file =
+            "# Curated deck\n\n[opening]  # first cards drawn\n0_ 3seed # low\n\n  [late]\n4doub\n";
+        let organized: Deck = file.parse().unwrap();
+        assert_eq!(organized.cards(), plain.cards());
+        let crlf: Deck = file.replace('\n', "\r\n").parse().unwrap();
+        assert_eq!(crlf.cards(), plain.cards());
+        let empty: Deck = "# nothing yet\r\n[opening]\r\n".parse().unwrap();
+        assert_eq!(empty.remaining(), 0);
+    }
+
+    #[test]
+    fn parse_errors_point_at_token() {
+        let bad_value = "0_ 3seed\n\n4doub  x_ 6_".parse::<Deck>().unwrap_err();
+        let expected = error_at(3, 8, "x_", ParseError::BadValue("x".to_owned()));
+        assert_eq!(bad_value, expected);
+        let bad_word = "# 0_ zzz\r\n[late]\r\n\t7meb 9what"
+            .parse::<Deck>()
+            .unwrap_err();
+        let expected = error_at(3, 7, "9what", ParseError::BadWord("what".to_owned()));
+        assert_eq!(bad_word, expected);
+        let wide = "1_ é_".parse::<Deck>().unwrap_err();
+        let expected = error_at(1, 4, "é_", ParseError::BadValue("é".to_owned()));
+        assert_eq!(wide, expected);
+    }
+
+    #[test]
+    fn sections_must_be_closed_and_named() {
+        for header in ["[late", "[]", "[ ]", "[a]b]"] {
+            let error = ParseError::BadSection(header.to_owned());
+            let file = format!("0_\n  {header}  # comment\n1_");
+            assert_eq!(
+                file.parse::<Deck>().unwrap_err(),
+                error_at(2, 3, header, error)
+            );
+        }
+    }
+
+    #[test]
+    fn decks_must_be_loaded_and_match() {
+        use DeckFile::{Failed, Loaded, Loading, Malformed};
+        let three: Deck = "0_ 3seed 4doub".parse().unwrap();
+        let two: Deck = "0_ 3seed".parse().unwrap();
+        let empty: Deck = "".parse().unwrap();
+        let check = |player, oppo| check_decks((player, PLAYER_DECK), (oppo, OPPO_DECK));
+        assert_eq!(check(Loaded(&three), Loaded(&three)), Ok(()));
+        assert_eq!(
+            check(Loaded(&three), Failed),
+            Err(DeckError::Invalid(OPPO_DECK.to_owned()))
+        );
+        assert_eq!(
+            check(Loading, Loaded(&three)),
+            Err(DeckError::Loading(PLAYER_DECK.to_owned()))
+        );
+        assert_eq!(
+            check(Loaded(&empty), Loaded(&empty)),
+            Err(DeckError::Empty(PLAYER_DECK.to_owned()))
+        );
+        assert_eq!(
+            check(Loaded(&three), Loaded(&two)),
+            Err(DeckError::Mismatched { player: 3, oppo: 2 })
+        );
+        let error = error_at(2, 1, "x_", ParseError::BadValue("x".to_owned()));
+        assert_eq!(
+            check(Loaded(&three), Malformed(error.clone())),
+            Err(DeckError::Malformed { file: OPPO_DECK.to_owned(), error })
+        );
+        let collector = "decks/collector.deck";
+        assert_eq!(
+            check_decks((Loaded(&three), PLAYER_DECK), (Loading, collector)),
+            Err(DeckError::Loading(collector.to_owned()))
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! Statistics on the cards left in a deck, for balancing.
+//!
+//! With the `debug` feature, an egui window shows them live for both decks:
+//! a heatmap of the values left, the expected value of the next draw and how
+//! likely the player is to beat a given oppo card. They are computed from the
+//! private deck contents, which is fine for a debug tool.
+// Only the debug window uses them for now
+#![cfg_attr(not(feature = "debug"), allow(dead_code))]
+use enum_map::EnumMap;
+
+use crate::war::{BattleOutcome, Card, Value};
+
+/// Share of the `cards` with each value, all zero without cards.
+pub fn value_distribution(cards: &[Card]) -> EnumMap<Value, f32> {
+    let mut distribution = EnumMap::default();
+    for card in cards {
+        distribution[card.value] += 1.0;
+    }
+    let total = cards.len().max(1) as f32;
+    for share in distribution.values_mut() {
+        *share /= total;
+    }
+    distribution
+}
+
+/// Average value of a card drawn at random from `cards`, not counting
+/// bonuses. `None` without cards.
+pub fn expected_value(cards: &[Card]) -> Option<f32> {
+    if cards.is_empty() {
+        return None;
+    }
+    let total: i32 = cards.iter().map(Card::value_i32).sum();
+    Some(total as f32 / cards.len() as f32)
+}
+
+/// Probability that a card drawn at random from `cards` beats `against`.
+pub fn beat_probability(cards: &[Card], against: &Card) -> f32 {
+    let wins = cards
+        .iter()
+        .filter(|card| card.beats(against) == BattleOutcome::Win)
+        .count();
+    wins as f32 / cards.len().max(1) as f32
+}
+
+#[cfg(feature = "debug")]
+pub use panel::show_stats;
+
+#[cfg(feature = "debug")]
+mod panel {
+    use bevy::prelude::*;
+    use bevy_inspector_egui::bevy_egui::{egui, EguiContext};
+    use enum_map::Enum;
+
+    use super::{beat_probability, expected_value, value_distribution};
+    use crate::{
+        deck::{OppoDeck, PlayerDeck},
+        war::{Card, Value},
+    };
+
+    /// Cell color of a value holding `share` of a deck, from black to red.
+    fn heat(share: f32) -> egui::Color32 {
+        let intensity = (share * 4.0).min(1.0);
+        egui::Color32::from_rgb((55.0 + 200.0 * intensity) as u8, 55, 55)
+    }
+
+    /// Show the deck statistics window.
+    pub fn show_stats(
+        mut egui: ResMut<EguiContext>,
+        mut against: Local<usize>,
+        player: Query<&PlayerDeck>,
+        oppo: Query<&OppoDeck>,
+    ) {
+        let player = player.get_single().map_or(&[][..], PlayerDeck::cards);
+        let oppo = oppo.get_single().map_or(&[][..], OppoDeck::cards);
+        egui::Window::new("Deck stats").show(egui.ctx_mut(), |ui| {
+            egui::Grid::new("Value heatmap").show(ui, |ui| {
+                ui.label("");
+                for value in 0..Value::LENGTH {
+                    ui.label(value.to_string());
+                }
+                ui.label("Next draw");
+                ui.end_row();
+                for (name, cards) in [("Player", player), ("Oppo", oppo)] {
+                    ui.label(name);
+                    for (_, share) in value_distribution(cards) {
+                        ui.colored_label(heat(share), format!("{:.0}%", share * 100.0));
+                    }
+                    match expected_value(cards) {
+                        Some(expected) => ui.label(format!("{expected:.2}")),
+                        None => ui.label("-"),
+                    };
+                    ui.end_row();
+                }
+            });
+            ui.separator();
+            ui.add(egui::Slider::new(&mut *against, 0..=Value::LENGTH - 1).text("Oppo card"));
+            let card = Card { value: Value::from_usize(*against), word: None };
+            let chance = beat_probability(player, &card) * 100.0;
+            ui.label(format!("The player beats it {chance:.0}% of the time"));
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cards(text: &str) -> Vec<Card> {
+        text.split(' ').map(|c| c.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn distribution_sums_to_one() {
+        let distribution = value_distribution(&cards("0_ 3seed 3_ 9doub"));
+        assert_eq!(distribution[Value::Three], 0.5);
+        assert_eq!(distribution[Value::Nine], 0.25);
+        assert_eq!(distribution[Value::Five], 0.0);
+        assert_eq!(distribution.values().sum::<f32>(), 1.0);
+        let empty = value_distribution(&[]);
+        assert!(empty.values().all(|share| *share == 0.0));
+    }
+
+    #[test]
+    fn expected_value_ignores_bonuses() {
+        assert_eq!(expected_value(&cards("0zero 3doub 6_")), Some(3.0));
+        assert_eq!(expected_value(&[]), None);
+    }
+
+    #[test]
+    fn beat_probability_counts_wins() {
+        let deck = cards("0_ 4_ 5_ 9_");
+        let nine: Card = "9_".parse().unwrap();
+        assert_eq!(beat_probability(&deck, &nine), 0.25, "only the zero");
+        let three: Card = "3_".parse().unwrap();
+        assert_eq!(beat_probability(&deck, &three), 0.75);
+        assert_eq!(beat_probability(&[], &three), 0.0);
+    }
+}
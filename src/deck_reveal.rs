@@ -0,0 +1,344 @@
+//! Side by side summary of both decks, shown before the first draw.
+//!
+//! The opponent's deck is stacked, the reveal shows it to the player, rows
+//! where the opponent is favored are highlighted in red. The coin flip starts
+//! once the reveal is dismissed, or right away if
+//! [`GameplaySettings::deck_reveal`] is off or in streaming mode.
+//!
+//! The reveal also warns the player when the bird is vigilant, see
+//! [`BirdMemory`].
+//!
+//! When the opponent's deck can earn much more points than the player's, the
+//! [`DeckHandicap`] is spelled out, in the reveal or in the effect display if
+//! the reveal is off. It is recorded in the [`crate::history`].
+//!
+//! A resumed game skips both the reveal and the coin flip, see
+//! [`crate::autosave`].
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use bevy_ui_build_macros::{build_ui, rect, size, style, unit};
+
+use crate::{
+    autosave::Autosaves,
+    cheat::BirdMemory,
+    cleanup_marked,
+    coin_flip::FlipCoin,
+    deck::{DeckComposition, OppoDeck, PlayerDeck},
+    game_ui::EffectEvent,
+    lang::Strings,
+    rules::GameRules,
+    settings::GameplaySettings,
+    state::GameState,
+    ui,
+    war::WordOfPower,
+};
+
+/// Seconds the reveal stays on screen if the player doesn't click.
+const REVEAL_DURATION: f32 = 6.0;
+const FAVORED_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
+/// Points the oppo deck must be worth over the player deck to be a
+/// [`DeckHandicap`].
+const HANDICAP_THRESHOLD: i32 = 5;
+
+#[derive(Clone, Component)]
+struct DeckRevealRoot;
+
+enum Reveal {
+    /// Waiting for the decks to be loaded.
+    Pending,
+    Showing(Timer),
+    Done,
+}
+impl Default for Reveal {
+    fn default() -> Self {
+        Reveal::Done
+    }
+}
+
+/// A line of the reveal panel.
+#[derive(Clone, Copy)]
+enum Row {
+    Low,
+    Mid,
+    High,
+    Word(WordOfPower),
+    Total,
+}
+impl Row {
+    fn label(self, strings: &Strings) -> String {
+        match self {
+            Row::Low => strings.get("reveal.low").to_owned(),
+            Row::Mid => strings.get("reveal.mid").to_owned(),
+            Row::High => strings.get("reveal.high").to_owned(),
+            Row::Word(word) => format!("{word:?}"),
+            Row::Total => strings.get("reveal.total").to_owned(),
+        }
+    }
+    fn value(self, composition: &DeckComposition) -> i32 {
+        match self {
+            Row::Low => composition.bands[0] as i32,
+            Row::Mid => composition.bands[1] as i32,
+            Row::High => composition.bands[2] as i32,
+            Row::Word(word) => composition.words[word] as i32,
+            Row::Total => composition.total_value,
+        }
+    }
+    /// Whether this row of the decks gives the opponent an edge.
+    fn oppo_favored(self, player: &DeckComposition, oppo: &DeckComposition) -> bool {
+        let (player, oppo) = (self.value(player), self.value(oppo));
+        match self {
+            Row::Low => oppo < player,
+            Row::Mid => false,
+            Row::High | Row::Word(_) | Row::Total => oppo > player,
+        }
+    }
+}
+
+/// How many more points than the player deck the oppo deck can earn, when
+/// it's stacked enough to tell the player.
+#[derive(Default)]
+pub struct DeckHandicap(pub Option<i32>);
+
+/// The [`DeckHandicap`] of decks that can earn `player` and `oppo` points,
+/// never in a fair game.
+fn handicap(player: i32, oppo: i32, fair_game: bool) -> Option<i32> {
+    let surplus = oppo - player;
+    (surplus > HANDICAP_THRESHOLD && !fair_game).then(|| surplus)
+}
+
+fn rows(player: &DeckComposition, oppo: &DeckComposition) -> Vec<Row> {
+    let words = player
+        .words
+        .iter()
+        .filter(|(word, count)| **count != 0 || oppo.words[*word] != 0)
+        .map(|(word, _)| Row::Word(word));
+    [Row::Low, Row::Mid, Row::High]
+        .into_iter()
+        .chain(words)
+        .chain([Row::Total])
+        .collect()
+}
+
+fn start_reveal(mut reveal: ResMut<Reveal>) {
+    *reveal = Reveal::Pending;
+}
+
+fn show_reveal(
+    mut cmds: Commands,
+    mut reveal: ResMut<Reveal>,
+    mut flips: EventWriter<FlipCoin>,
+    mut effects: EventWriter<EffectEvent>,
+    mut deck_handicap: ResMut<DeckHandicap>,
+    settings: Res<GameplaySettings>,
+    rules: Res<GameRules>,
+    autosaves: Res<Autosaves>,
+    memory: Res<BirdMemory>,
+    ui_assets: Res<ui::Assets>,
+    strings: Res<Strings>,
+    player_deck: Query<&PlayerDeck>,
+    oppo_deck: Query<&OppoDeck>,
+) {
+    if !matches!(*reveal, Reveal::Pending) {
+        return;
+    }
+    let (player, oppo) = match (player_deck.get_single(), oppo_deck.get_single()) {
+        (Ok(player), Ok(oppo)) => {
+            deck_handicap.0 = handicap(player.score(), oppo.score(), rules.fair_game);
+            (player.composition(), oppo.composition())
+        }
+        _ => return,
+    };
+    if autosaves.restoring() {
+        *reveal = Reveal::Done;
+        return;
+    }
+    if !settings.deck_reveal || settings.streaming {
+        if let Some(points) = deck_handicap.0 {
+            effects.send(EffectEvent::DeckHandicap(points));
+        }
+        if memory.remembers_anyone() {
+            effects.send(EffectEvent::BirdRemembers);
+        }
+        *reveal = Reveal::Done;
+        flips.send(FlipCoin);
+        return;
+    }
+    let node = NodeBundle {
+        color: Color::NONE.into(),
+        style: style! { flex_direction: FlexDirection::Row, },
+        ..default()
+    };
+    let cell = |content: &str, color: Color| {
+        let mut bundle = ui_assets.text_bundle(content, 30.0);
+        bundle.text.sections[0].style.color = color;
+        bundle.style = style! { size: size!(180 px, auto), };
+        bundle
+    };
+    let mut spawn_row = |label: &str, player: &str, oppo: &str, oppo_color: Color| {
+        build_ui! {
+            #[cmd(cmds)]
+            node[; Name::new(format!("{label} row"))](
+                entity[cell(label, Color::ANTIQUE_WHITE);],
+                entity[cell(player, Color::ANTIQUE_WHITE);],
+                entity[cell(oppo, oppo_color);],
+            )
+        }
+        .id()
+    };
+    let (you, oppo_label) = (strings.get("reveal.you"), strings.get("reveal.oppo"));
+    let oppo_color = if deck_handicap.0.is_some() {
+        FAVORED_COLOR
+    } else {
+        Color::ANTIQUE_WHITE
+    };
+    let mut lines = vec![spawn_row("", you, oppo_label, oppo_color)];
+    for row in rows(&player, &oppo) {
+        let color = if row.oppo_favored(&player, &oppo) {
+            FAVORED_COLOR
+        } else {
+            Color::ANTIQUE_WHITE
+        };
+        let (player_value, oppo_value) = (row.value(&player), row.value(&oppo));
+        lines.push(spawn_row(
+            &row.label(&strings),
+            &player_value.to_string(),
+            &oppo_value.to_string(),
+            color,
+        ));
+    }
+    let title = cmds
+        .spawn_bundle(ui_assets.large_text(strings.get("reveal.title")))
+        .insert(style! { margin: rect!(0 px, 0 px, 0 px, 20 px,), })
+        .id();
+    let hint = cmds
+        .spawn_bundle(ui_assets.text_bundle(strings.get("reveal.hint"), 30.0))
+        .insert(style! { margin: rect!(0 px, 20 px, 0 px, 0 px,), })
+        .id();
+    lines.insert(0, title);
+    if let Some(points) = deck_handicap.0 {
+        let points = points.to_string();
+        let text = strings.format("effect.deck_handicap", &[("points", points.as_str())]);
+        let mut warning = ui_assets.text_bundle(&text, 40.0);
+        warning.text.sections[0].style.color = FAVORED_COLOR;
+        lines.push(cmds.spawn_bundle(warning).id());
+    }
+    if memory.remembers_anyone() {
+        let mut warning = ui_assets.text_bundle(strings.get("effect.bird_remembers"), 40.0);
+        warning.text.sections[0].style.color = FAVORED_COLOR;
+        lines.push(cmds.spawn_bundle(warning).id());
+    }
+    lines.push(hint);
+    let panel = NodeBundle {
+        color: Color::rgba(0.1, 0.1, 0.1, 0.9).into(),
+        style: style! {
+            position_type: PositionType::Absolute,
+            position: rect!(15 pct),
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+        },
+        ..default()
+    };
+    cmds.spawn_bundle(panel)
+        .insert_bundle((Name::new("Deck reveal"), DeckRevealRoot))
+        .push_children(&lines);
+    *reveal = Reveal::Showing(Timer::from_seconds(REVEAL_DURATION, false));
+}
+
+/// Dismiss the reveal once its time is up or on click.
+///
+/// The dismissing click is consumed, so that it doesn't also inspect a pile
+/// or skip the coin flip, see the `"reveal_click"` label.
+fn dismiss_reveal(
+    mut cmds: Commands,
+    mut reveal: ResMut<Reveal>,
+    mut flips: EventWriter<FlipCoin>,
+    mut mouse: ResMut<Input<MouseButton>>,
+    time: Res<Time>,
+    panels: Query<Entity, With<DeckRevealRoot>>,
+) {
+    let timer = match reveal.as_mut() {
+        Reveal::Showing(timer) => timer,
+        _ => return,
+    };
+    let clicks: Vec<_> = mouse.get_just_pressed().copied().collect();
+    for button in &clicks {
+        mouse.clear_just_pressed(*button);
+    }
+    if timer.tick(time.delta()).finished() || !clicks.is_empty() {
+        for panel in panels.iter() {
+            cmds.entity(panel).despawn_recursive();
+        }
+        *reveal = Reveal::Done;
+        flips.send(FlipCoin);
+    }
+}
+
+fn reset_reveal(mut reveal: ResMut<Reveal>, mut deck_handicap: ResMut<DeckHandicap>) {
+    *reveal = Reveal::Done;
+    *deck_handicap = DeckHandicap::default();
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        app.init_resource::<Reveal>()
+            .init_resource::<DeckHandicap>()
+            .add_system_set(self.0.on_enter(start_reveal))
+            .add_system_set(
+                self.0
+                    .on_update(show_reveal.before("restore_table"))
+                    .with_system(dismiss_reveal.label("reveal_click").after(show_reveal)),
+            )
+            .add_system_set(
+                self.0
+                    .on_exit(cleanup_marked::<DeckRevealRoot>)
+                    .with_system(reset_reveal),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::deck::Deck;
+
+    fn composition(deck: &str) -> DeckComposition {
+        PlayerDeck::new(deck.parse::<Deck>().unwrap()).composition()
+    }
+
+    #[test]
+    fn oppo_favored_rows() {
+        let player = composition("0_ 1_ 2_ 5_ 8seed");
+        let oppo = composition("1_ 5doub 8_ 9doub 9_");
+        assert!(Row::Low.oppo_favored(&player, &oppo));
+        assert!(!Row::Mid.oppo_favored(&player, &oppo));
+        assert!(Row::High.oppo_favored(&player, &oppo));
+        assert!(Row::Word(WordOfPower::Qube).oppo_favored(&player, &oppo));
+        assert!(!Row::Word(WordOfPower::Egeq).oppo_favored(&player, &oppo));
+        assert!(Row::Total.oppo_favored(&player, &oppo));
+        assert!(!Row::Total.oppo_favored(&oppo, &player));
+    }
+
+    #[test]
+    fn handicap_needs_a_stacked_deck() {
+        assert_eq!(handicap(60, 83, false), Some(23));
+        assert_eq!(handicap(60, 60 + HANDICAP_THRESHOLD, false), None);
+        assert_eq!(handicap(83, 60, false), None);
+        assert_eq!(handicap(60, 83, true), None, "equal decks in fair games");
+    }
+
+    #[test]
+    fn rows_only_list_words_in_a_deck() {
+        let player = composition("0_ 8seed");
+        let oppo = composition("1_ 9doub");
+        let words: Vec<_> = rows(&player, &oppo)
+            .into_iter()
+            .filter_map(|row| match row {
+                Row::Word(word) => Some(word),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(words, [WordOfPower::Egeq, WordOfPower::Qube]);
+    }
+}
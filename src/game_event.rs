@@ -0,0 +1,79 @@
+//! The [`GameEvent`]s, what happens in a game, for the systems that only
+//! observe it.
+//!
+//! Stats, achievements, barks or mods can read the [`GameEvent`]s rather than
+//! the internal events of each module, or polling their resources. They are
+//! sent by the systems that decide what happens:
+//!
+//! * [`crate::player_hand`] and [`crate::oppo_hand`]: [`GameEvent::CardDrawn`]
+//! * [`crate::game_flow`]: [`GameEvent::CardPlayed`],
+//!   [`GameEvent::BattleResolved`], [`GameEvent::SeedGained`] and
+//!   [`GameEvent::GameEnded`] when a participant won.
+//! * [`crate::cheat`]: [`GameEvent::SeedUsed`], [`GameEvent::CardSleeved`],
+//!   [`GameEvent::CheatCaught`] and the [`GameEvent::GameEnded`] following
+//!   it.
+//! * the pause menu: [`GameEvent::GameEnded`] when the player concedes.
+//!
+//! # Ordering
+//!
+//! Events follow the order of the game: a card is drawn before it is played,
+//! both cards of a battle are played before its [`GameEvent::BattleResolved`],
+//! itself sent before the next battle's cards are played, in a later frame.
+//! The [`GameEvent::SeedGained`] of a battle's Egeq cards follow its
+//! [`GameEvent::BattleResolved`].
+//! [`GameEvent::CheatCaught`] comes right before the [`GameEvent::GameEnded`]
+//! it causes.
+//!
+//! Events sent by different systems in the same frame are in no set order,
+//! for example a [`GameEvent::SeedUsed`] and a [`GameEvent::CardPlayed`].
+//!
+//! Like all bevy events, readers must read them every frame, or at least
+//! every other frame, not to miss any.
+use bevy::prelude::{Plugin as BevyPlugin, *};
+
+use crate::{game_flow::CoopPlayer, war::BattleOutcome, war::Card, EndReason, Participant};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    /// `card` got into the hand of `who`, from their deck.
+    CardDrawn { who: Participant, card: Card },
+    /// `who` played `card` on the war pile.
+    CardPlayed { who: Participant, card: Card },
+    /// The two cards of the war pile were compared.
+    BattleResolved {
+        /// The battle outcome for the player.
+        outcome: BattleOutcome,
+        /// How many more points the player card is worth than the oppo card,
+        /// bonuses included.
+        margin: i32,
+        /// The points each participant scored in this battle.
+        rewards: Vec<(Participant, i32)>,
+    },
+    /// A seed got into the pool of a [`CoopPlayer`], see
+    /// [`crate::game_flow::SeedCount`].
+    SeedGained(CoopPlayer),
+    /// A [`CoopPlayer`] distracted the bird with seeds.
+    SeedUsed(CoopPlayer),
+    /// The player slipped `Card` in their sleeve, unseen by the bird.
+    CardSleeved(Card),
+    /// The bird caught the player sleeving `Card`.
+    CheatCaught(Card),
+    /// The game is over, the restart menu shows up.
+    GameEnded(EndReason),
+}
+impl GameEvent {
+    /// Why the game ended, if this is a [`GameEvent::GameEnded`].
+    pub fn end(&self) -> Option<EndReason> {
+        match self {
+            GameEvent::GameEnded(end) => Some(*end),
+            _ => None,
+        }
+    }
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<GameEvent>();
+    }
+}
@@ -17,14 +17,21 @@
 //! Note that the game enters [`TurnState::New`] whenever who is playing a card
 //! changes.
 //!
-//! * [`handle_new_turn`]: end game if one of the players cannot win
+//! Systems do not set the [`TurnState`] directly, they add a request to
+//! [`TurnRequests`] and [`apply_turn_requests`] picks which one to apply.
+//!
+//! * [`handle_new_turn`]: end game once the [`WinCondition`] is decided, see
+//!   [`check_win`], otherwise pass the [`Initiative`] following
+//!   [`GameRules::initiative`]
 //! * [`complete_draw`]: Set who's turn it is to play after drawing cards
-//! * [`handle_played`]: Handle played card adding seed if relevant and enter
-//!   `CardPlayed` state.
-//! * [`wait_active`]: Wait a little time after a card is played
+//! * [`handle_played`]: Handle played card and enter `CardPlayed` state.
+//! * [`wait_active`]: Wait a little time after a card is played, longer when
+//!   the oppo leads a battle, announcing its card, see [`OppoLead`]
 //! * [`handle_turn_end`]: Start new turn after swapping initiative,
-//!   if two cards are played, update scores and distribute cards to
-//!   the winner's pile.
+//!   if two cards are played, update scores, pay out seeds and distribute
+//!   cards to the winner's pile.
+//! * [`watchdog`]: Push forward a turn state that should advance on its own
+//!   but got stuck, see [`recovery`].
 //!
 //! Following is the flowchart of the game logic, states are from the
 //! [`TurnState`] `enum`.
@@ -79,41 +86,125 @@
 //! bonuses based on played card [`crate::war::WordOfPower`]s. Currently only four words
 //! are handled. See [`handle_turn_end`] docs for specifics.
 //!
-//! The module provides the [`CardStats`] system parameter for other modules
-//! to query the game scores.
+//! Scores are kept in the [`Scores`] resource, updated as cards are played
+//! and battles resolved rather than recomputed from the piles each frame. In
+//! debug builds, [`handle_new_turn`] checks it against a full recomputation,
+//! see [`CardStats`].
+//!
+//! The battles that earned bonus points are kept as [`BonusGrant`]s in the
+//! [`ScoreBonuses`], for the pile inspection to point out which cards earned
+//! them.
+//!
+//! [`celebrate_milestones`] watches the [`Scores`] for [`Milestone`]s, each
+//! celebrated at most once per game.
 //!
 //! ## Effects
 //!
-//! The [`handle_played`] system directly updates the [`SeedCount`] resource when a
-//! [`PlayCard`] event is received, it then enters [`TurnState::CardPlayed`].
+//! [`Egeq`] cards pay out when their battle is resolved: [`handle_turn_end`]
+//! updates the [`SeedCount`] resource, if the side that played them won or
+//! tied the battle, see [`BattleResolved::egeq_payouts`].
+//!
+//! ## Co-op
+//!
+//! In a co-op game, two local players share the player side. The
+//! [`CoopTurn`] says which one controls the hand, [`handle_turn_end`] hands
+//! it over after each battle. Each has their own pool in the [`SeedCount`]
+//! and their own [`Inventory`].
+//!
+//! ## Trinkets
+//!
+//! Winning a battle by [`GameRules::trinket_margin`] points or more earns a
+//! shiny trinket, see [`BattleResolved::earns_trinket`]. [`handle_turn_end`]
+//! adds it to the [`Inventory`].
+//!
+//! ## Glimpses
+//!
+//! Winning a battle by [`GameRules::glimpse_margin`] points or more reveals
+//! the value of the next card of the oppo deck, at most once per draw, see
+//! [`Glimpse`]. The oppo isn't aware of it.
 
+use bevy::ecs::schedule::StateError;
 use bevy::ecs::system::SystemParam;
 use bevy::prelude::{Plugin as BevyPlugin, *};
-use bevy_debug_text_overlay::screen_print;
+use enum_map::{Enum, EnumMap};
+use std::{cmp::Ordering, collections::VecDeque, marker::PhantomData};
 
 use crate::{
-    audio::AudioRequest,
+    animate::{despawn_fading, GameSpeed, WaitForAnimations},
+    audio::{AudioAssets, AudioRequest},
     cheat::SleeveCard,
     deck::{OppoDeck, PlayerDeck},
+    game_event::GameEvent,
     game_ui::{EffectEvent, ScoreEvent},
-    pile::{Pile, PileCard, PileType},
+    persist::Entries,
+    pile::{Emphasis, Pile, PileCard, PileType, PilesSettled},
+    player_hand::GrabbedCard,
+    rules::{GameRules, InitiativeRule, WinCondition},
+    settings::GameplaySettings,
     state::{GameState, TurnState},
-    war::{BattleOutcome, Card, WordOfPower::Egeq},
-    CardOrigin, EndReason, GameOver, Participant,
+    war::{BattleOutcome, Card, Value, WordOfPower, WordOfPower::Egeq},
+    CardOrigin, EndReason, Participant,
 };
 
 /// Cards in the War pile
 #[derive(Component)]
 #[non_exhaustive]
 pub struct PlayedCard;
+impl PlayedCard {
+    /// A card put back on the war pile by [`crate::autosave`], its battle is
+    /// resolved once the other card is played.
+    pub fn restored() -> Self {
+        Self
+    }
+}
 
 /// Who is playing a card currently
 pub struct Initiative(Participant);
+impl Default for Initiative {
+    fn default() -> Self {
+        Self(Participant::Player)
+    }
+}
 impl Initiative {
+    /// Set who leads the first battle of the game, see [`crate::coin_flip`].
+    pub fn lead_first(&mut self, leader: Participant) {
+        self.0 = leader;
+    }
+    /// Who is playing, or plays next once [`TurnState::New`] was entered.
+    pub fn current(&self) -> Participant {
+        self.0
+    }
+    /// Who plays after the card of the current `turn`, `None` if it depends
+    /// on the outcome of the battle this card ends.
+    pub fn upcoming(&self, turn: &TurnCount, rule: InitiativeRule) -> Option<Participant> {
+        let next_turn = turn.0 + 1;
+        if next_turn % 2 == 0 && rule == InitiativeRule::WinnerLeads {
+            return None;
+        }
+        let mut upcoming = Initiative(self.0);
+        upcoming.advance(next_turn, rule, None);
+        Some(upcoming.0)
+    }
     fn swap(&mut self) {
-        match self.0 {
-            Participant::Oppo => self.0 = Participant::Player,
-            Participant::Player => self.0 = Participant::Oppo,
+        self.0 = self.0.other();
+    }
+    /// Update who plays on `turn`, the [`TurnCount`] of the new turn.
+    ///
+    /// The other participant plays the second card of a battle, on odd
+    /// turns. Even turns start a new battle, its leader depends on the
+    /// `rule` and on the `last_outcome` for the player.
+    fn advance(&mut self, turn: usize, rule: InitiativeRule, last_outcome: Option<BattleOutcome>) {
+        use BattleOutcome::{Loss, Tie, Win};
+        if turn % 2 == 1 {
+            self.swap();
+            return;
+        }
+        match (rule, last_outcome) {
+            (InitiativeRule::Alternate, _) => {}
+            (InitiativeRule::WinnerLeads, Some(Win)) => self.0 = Participant::Player,
+            (InitiativeRule::WinnerLeads, Some(Loss)) => self.0 = Participant::Oppo,
+            // The second card was played by who didn't lead the last battle
+            (InitiativeRule::WinnerLeads, Some(Tie) | None) => self.swap(),
         }
     }
 }
@@ -132,12 +223,155 @@ impl PlayCard {
     }
 }
 
+/// The voice line of the word of `card` is playing, since `started_at` and
+/// until `ends_at`, in seconds since startup.
+///
+/// Inserted by [`handle_played`], the card pulses with the voice line, see
+/// [`crate::card`].
+pub struct WordAnnouncement {
+    pub card: Entity,
+    pub word: WordOfPower,
+    pub started_at: f64,
+    pub ends_at: f64,
+}
+
+/// The oppo card leading the current battle, set by [`handle_played`].
+///
+/// [`wait_active`] announces it for [`GameplaySettings::announce_beat`] more
+/// seconds before the player can answer, so that it isn't missed.
+#[derive(Default)]
+pub struct OppoLead(Option<Entity>);
+
+/// A card played in a battle.
+#[derive(Clone)]
+pub struct Play {
+    pub who: Participant,
+    pub card: Card,
+}
+
+/// Sent by [`handle_turn_end`] when a battle is over.
+pub struct BattleResolved {
+    /// The battle outcome for the player.
+    pub outcome: BattleOutcome,
+    /// Total points handed out in this battle, bonuses included.
+    pub points: i32,
+    /// The cards of the battle, in the order they were played.
+    pub order: [Play; 2],
+    /// How many more points the player card is worth than the oppo card,
+    /// bonuses included.
+    pub margin: i32,
+}
+impl BattleResolved {
+    /// Whether the player earned a shiny trinket in this battle, winning it
+    /// by a wide enough margin.
+    pub fn earns_trinket(&self, rules: &GameRules) -> bool {
+        let wide_win = self.outcome == BattleOutcome::Win && self.margin >= rules.trinket_margin;
+        wide_win && !rules.fair_game
+    }
+    /// Whether the player earned a [`Glimpse`] in this battle.
+    pub fn earns_glimpse(&self, rules: &GameRules) -> bool {
+        self.outcome == BattleOutcome::Win && self.margin >= rules.glimpse_margin
+    }
+    /// How many [`Egeq`] cards of this battle pay out.
+    ///
+    /// Only those of a side that won or tied the battle do, unless
+    /// [`GameRules::egeq_pays_on_loss`].
+    pub fn egeq_payouts(&self, rules: &GameRules) -> usize {
+        let lost = |who| match who {
+            Participant::Player => self.outcome == BattleOutcome::Loss,
+            Participant::Oppo => self.outcome == BattleOutcome::Win,
+        };
+        let egeqs = self
+            .order
+            .iter()
+            .filter(|play| play.card.word == Some(Egeq));
+        egeqs
+            .filter(|play| rules.egeq_pays_on_loss || !lost(play.who))
+            .count()
+    }
+}
+
+/// Whether the player glimpsed the next oppo card since the last
+/// [`TurnState::Draw`], see [`glimpse_oppo_deck`].
+#[derive(Default)]
+pub struct Glimpse {
+    used: bool,
+}
+impl Glimpse {
+    /// True if `resolved` earned a glimpse and none was used since the last
+    /// draw (using it).
+    fn earn(&mut self, resolved: &BattleResolved, rules: &GameRules) -> bool {
+        let earned = !self.used && resolved.earns_glimpse(rules);
+        self.used |= earned;
+        earned
+    }
+}
+
+/// Most [`BonusGrant`]s kept in the [`ScoreBonuses`], the oldest ones are
+/// forgotten past it, in very long games.
+const MAX_BONUS_GRANTS: usize = 128;
+
+/// A battle that earned bonus points, recorded by [`handle_turn_end`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct BonusGrant {
+    /// The battle, counting from 1, see [`BattleCount::current`].
+    pub battle: usize,
+    /// The player card then the oppo card, now in the score piles.
+    pub cards: [(Entity, Card); 2],
+    /// Bonus points the player got.
+    pub player: i32,
+    /// Bonus points the oppo got.
+    pub oppo: i32,
+    /// Points the player got rather than seeds, see
+    /// [`BattleResolved::egeq_payouts`].
+    pub seed_points: i32,
+}
+impl BonusGrant {
+    fn new(battle: usize, player: (Entity, &Card), oppo: (Entity, &Card)) -> Self {
+        BonusGrant {
+            battle,
+            cards: [(player.0, player.1.clone()), (oppo.0, oppo.1.clone())],
+            player: 0,
+            oppo: 0,
+            seed_points: 0,
+        }
+    }
+    fn add_to_owner(&mut self, who: Participant, value: i32) {
+        match who {
+            Participant::Oppo => self.oppo += value,
+            Participant::Player => self.player += value,
+        }
+    }
+    /// Whether `card` is one of the cards of this battle.
+    pub fn involves(&self, card: Entity) -> bool {
+        self.cards.iter().any(|(entity, _)| *entity == card)
+    }
+    /// The words that earned the bonus points, in card order.
+    pub fn words(&self) -> Vec<WordOfPower> {
+        use WordOfPower::{Geh, Qube};
+        let card_bonus = self.player + self.oppo - self.seed_points > 0;
+        let has_zero = self.cards.iter().any(|(_, card)| card.value_i32() == 0);
+        let responsible = |word: &WordOfPower| match word {
+            Geh => has_zero,
+            Qube => card_bonus,
+            Egeq => self.seed_points > 0,
+            _ => false,
+        };
+        let words = self.cards.iter().filter_map(|(_, card)| card.word);
+        words.filter(responsible).collect()
+    }
+}
+
 /// Keep track of extra points obtained from card effects. The "regular"
 /// points are kept track of in the player and oppo [`Pile`]s.
+///
+/// The battles that earned them are kept as [`BonusGrant`]s, up to
+/// [`MAX_BONUS_GRANTS`] of them.
 #[derive(Default)]
 pub struct ScoreBonuses {
     player: i32,
     oppo: i32,
+    grants: VecDeque<BonusGrant>,
 }
 impl ScoreBonuses {
     fn add_to_owner(&mut self, who: Participant, value: i32) {
@@ -146,84 +380,642 @@ impl ScoreBonuses {
             Participant::Player => self.player += value,
         }
     }
+    /// Keep `grant` if it earned any points.
+    fn record(&mut self, grant: BonusGrant) {
+        if grant.player == 0 && grant.oppo == 0 {
+            return;
+        }
+        if self.grants.len() == MAX_BONUS_GRANTS {
+            self.grants.pop_front();
+        }
+        self.grants.push_back(grant);
+    }
+    /// Bonus points the player got this game.
+    pub fn player(&self) -> i32 {
+        self.player
+    }
+    /// The battles that earned bonus points this game, oldest first.
+    pub fn grants(&self) -> impl Iterator<Item = &BonusGrant> {
+        self.grants.iter()
+    }
+    /// The battle in which `card` earned bonus points, if any.
+    pub fn grant_of(&self, card: Entity) -> Option<&BonusGrant> {
+        self.grants.iter().rev().find(|grant| grant.involves(card))
+    }
 }
 
-/// How many seeds the player has.
+/// Scores of both participants, and the points still in play.
+///
+/// Maintained by [`handle_played`], [`handle_turn_end`] and
+/// [`track_deck_scores`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Scores {
+    player: i32,
+    oppo: i32,
+    /// Maximum points the cards not played yet can earn.
+    remaining: i32,
+}
+impl Scores {
+    pub fn player(&self) -> i32 {
+        self.player
+    }
+    pub fn oppo(&self) -> i32 {
+        self.oppo
+    }
+    pub fn remaining(&self) -> i32 {
+        self.remaining
+    }
+    pub fn add_to_owner(&mut self, who: Participant, points: i32) {
+        match who {
+            Participant::Oppo => self.oppo += points,
+            Participant::Player => self.player += points,
+        }
+    }
+    fn deck_loaded(&mut self, deck_score: i32) {
+        self.remaining += deck_score;
+    }
+    fn card_played(&mut self, card: &Card) {
+        self.remaining -= card.max_value();
+    }
+    /// Scores from the value of the cards in the `piles`, the point
+    /// `bonuses`, and the `remaining` points of cards not played yet.
+    fn recompute(piles: (i32, i32), bonuses: &ScoreBonuses, remaining: i32) -> Self {
+        let (player_pile, oppo_pile) = piles;
+        Self {
+            player: player_pile + bonuses.player,
+            oppo: oppo_pile + bonuses.oppo,
+            remaining,
+        }
+    }
+}
+
+/// Points the player must have trailed by for taking the lead to be a
+/// [`Milestone::Comeback`].
+const COMEBACK_DEFICIT: i32 = 10;
+
+/// A score moment worth celebrating.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Milestone {
+    /// The player took the lead after trailing by [`COMEBACK_DEFICIT`]
+    /// points or more.
+    Comeback,
+    /// The player holds more than half of the points of the game.
+    HalfPoints,
+}
+impl Milestone {
+    /// Key of the message celebrating this milestone in [`Strings`].
+    ///
+    /// [`Strings`]: crate::lang::Strings
+    pub fn message_key(self) -> &'static str {
+        match self {
+            Milestone::Comeback => "effect.comeback",
+            Milestone::HalfPoints => "effect.half_points",
+        }
+    }
+}
+
+/// The [`Milestone`]s reached this game, so that none is celebrated twice.
 #[derive(Default)]
-pub struct SeedCount(usize);
+pub struct Milestones {
+    /// Most points the player trailed by this game.
+    worst_deficit: i32,
+    comeback: bool,
+    half_points: bool,
+}
+impl Milestones {
+    /// The milestones reached for the first time this game with `scores`.
+    fn update(&mut self, scores: &Scores) -> Vec<Milestone> {
+        let mut reached = Vec::new();
+        self.worst_deficit = self.worst_deficit.max(scores.oppo - scores.player);
+        let came_back = self.worst_deficit >= COMEBACK_DEFICIT && scores.player > scores.oppo;
+        if came_back && !self.comeback {
+            self.comeback = true;
+            reached.push(Milestone::Comeback);
+        }
+        let total = scores.player + scores.oppo + scores.remaining;
+        if scores.player * 2 > total && total > 0 && !self.half_points {
+            self.half_points = true;
+            reached.push(Milestone::HalfPoints);
+        }
+        reached
+    }
+}
+
+/// Where the cards of a battle go, and the points they bring.
+///
+/// Independent from the game state, the [`crate::sandbox`] resolves battles
+/// with it outside of a game.
+pub struct Battle {
+    pub outcome: BattleOutcome,
+    /// Who gets the player card, and its value plus bonus.
+    pub player_card: (Participant, i32),
+    /// Who gets the oppo card, and its value plus bonus.
+    pub oppo_card: (Participant, i32),
+}
+impl Battle {
+    pub fn new(player: &Card, oppo: &Card) -> Self {
+        use Participant::{Oppo, Player};
+        let (player_bonus, oppo_bonus) = player.bonus_points(oppo);
+        let outcome = player.beats(oppo);
+        let (player_owner, oppo_owner) = match outcome {
+            BattleOutcome::Tie => (Player, Oppo),
+            BattleOutcome::Loss => (Oppo, Oppo),
+            BattleOutcome::Win => (Player, Player),
+        };
+        Self {
+            outcome,
+            player_card: (player_owner, player_bonus + player.value_i32()),
+            oppo_card: (oppo_owner, oppo_bonus + oppo.value_i32()),
+        }
+    }
+    /// Points earned by each participant getting cards.
+    pub fn rewards(&self) -> Vec<(Participant, i32)> {
+        let ((player_owner, player_points), (oppo_owner, oppo_points)) =
+            (self.player_card, self.oppo_card);
+        if player_owner == oppo_owner {
+            vec![(player_owner, player_points + oppo_points)]
+        } else {
+            vec![self.player_card, self.oppo_card]
+        }
+    }
+}
+
+/// Points the player scores in a battle of `player` against `oppo`, their
+/// own card's and the oppo card's if they win it.
+pub fn battle_points(player: &Card, oppo: &Card) -> i32 {
+    let rewards = Battle::new(player, oppo).rewards();
+    let player_rewards = rewards
+        .iter()
+        .filter(|(who, _)| *who == Participant::Player);
+    player_rewards.map(|(_, points)| points).sum()
+}
+
+/// What the player got from gaining a seed, see [`SeedCount::try_add`].
+#[derive(Debug, PartialEq)]
+pub enum SeedGain {
+    Seed,
+    /// The player already has as many seeds as they can hold.
+    Overflow,
+}
+
+/// One of the two local players sharing the player side in a co-op game,
+/// see [`GameRules::coop`]. Only the first one plays otherwise.
+#[derive(Clone, Copy, Debug, Enum, PartialEq)]
+pub enum CoopPlayer {
+    First,
+    Second,
+}
+impl CoopPlayer {
+    pub const ALL: [Self; 2] = [Self::First, Self::Second];
+
+    pub fn other(self) -> Self {
+        match self {
+            Self::First => Self::Second,
+            Self::Second => Self::First,
+        }
+    }
+    /// Key of the player name in [`Strings`].
+    ///
+    /// [`Strings`]: crate::lang::Strings
+    pub fn name_key(self) -> &'static str {
+        match self {
+            Self::First => "coop.first",
+            Self::Second => "coop.second",
+        }
+    }
+    /// Name used in saved files.
+    fn id(self) -> &'static str {
+        match self {
+            Self::First => "first",
+            Self::Second => "second",
+        }
+    }
+}
+
+/// Which [`CoopPlayer`] controls the hand, swapped by [`handle_turn_end`]
+/// after every battle of a co-op game.
+pub struct CoopTurn {
+    active: CoopPlayer,
+}
+impl Default for CoopTurn {
+    fn default() -> Self {
+        Self { active: CoopPlayer::First }
+    }
+}
+impl CoopTurn {
+    pub fn active(&self) -> CoopPlayer {
+        self.active
+    }
+    fn swap(&mut self) {
+        self.active = self.active.other();
+    }
+}
+
+/// How many seeds each [`CoopPlayer`] has, up to [`GameRules::seed_cap`]
+/// each.
+pub struct SeedCount {
+    counts: EnumMap<CoopPlayer, usize>,
+    cap: usize,
+}
 impl SeedCount {
-    pub fn count(&self) -> usize {
-        self.0
+    fn new(cap: usize) -> Self {
+        Self { counts: EnumMap::default(), cap }
+    }
+    pub fn count(&self, who: CoopPlayer) -> usize {
+        self.counts[who]
+    }
+    pub fn cap(&self) -> usize {
+        self.cap
     }
-    /// True if can use a seed (consuming it)
-    pub fn consume(&mut self) -> bool {
-        if self.0 != 0 {
-            self.0 -= 1;
+    pub fn is_full(&self, who: CoopPlayer) -> bool {
+        self.counts[who] >= self.cap
+    }
+    /// Set the seed count of `who`, up to the cap.
+    #[cfg(feature = "debug")]
+    pub fn set(&mut self, who: CoopPlayer, count: usize) {
+        self.counts[who] = count.min(self.cap);
+    }
+    /// True if `who` can use `count` seeds (consuming them)
+    pub fn consume(&mut self, who: CoopPlayer, count: usize) -> bool {
+        if self.counts[who] >= count {
+            self.counts[who] -= count;
             true
         } else {
             false
         }
     }
+    /// Give a seed to `who`, unless they already hold as many as they can,
+    /// in which case they should get [`GameRules::seed_overflow_points`].
+    pub fn try_add(&mut self, who: CoopPlayer) -> SeedGain {
+        if self.is_full(who) {
+            SeedGain::Overflow
+        } else {
+            self.counts[who] += 1;
+            SeedGain::Seed
+        }
+    }
+}
+impl FromWorld for SeedCount {
+    fn from_world(world: &mut World) -> Self {
+        let rules = world.get_resource_or_insert_with(GameRules::default);
+        Self::new(rules.seed_cap)
+    }
 }
 
+/// Distraction items other than seeds held by each [`CoopPlayer`].
+///
+/// Shiny trinkets distract the bird longer than seeds, but make the oppo
+/// suspicious for longer, see [`crate::cheat::Distraction`].
+#[derive(Default)]
+pub struct Inventory {
+    trinkets: EnumMap<CoopPlayer, usize>,
+}
+impl Inventory {
+    pub fn trinkets(&self, who: CoopPlayer) -> usize {
+        self.trinkets[who]
+    }
+    fn add_trinket(&mut self, who: CoopPlayer) {
+        self.trinkets[who] += 1;
+    }
+    /// True if `who` had a trinket to use (consuming it)
+    pub fn use_trinket(&mut self, who: CoopPlayer) -> bool {
+        if self.trinkets[who] == 0 {
+            return false;
+        }
+        self.trinkets[who] -= 1;
+        true
+    }
+}
+
+/// Times [`TurnState::New`] was entered this game, once per card played.
+///
+/// See [`BattleCount`] for how far through the game the players are.
 #[derive(Default)]
 pub struct TurnCount(pub usize);
 
+/// Battles resolved this game, the outcome of the last one, and the size of
+/// both decks when loaded.
+///
+/// Incremented by [`handle_turn_end`], deck sizes are set by
+/// [`track_deck_scores`].
+#[derive(Default)]
+pub struct BattleCount {
+    resolved: usize,
+    /// Outcome of the last battle for the player, read by [`handle_new_turn`]
+    /// to pick the next leader.
+    last_outcome: Option<BattleOutcome>,
+    player_deck: usize,
+    oppo_deck: usize,
+}
+impl BattleCount {
+    /// Battles resolved so far this game.
+    pub fn resolved(&self) -> usize {
+        self.resolved
+    }
+    /// The battle being played, counting from 1, never past [`Self::total`].
+    pub fn current(&self) -> usize {
+        (self.resolved + 1).min(self.total())
+    }
+    /// Battles in a game, 0 until both decks are loaded.
+    pub fn total(&self) -> usize {
+        total_battles(self.player_deck, self.oppo_deck)
+    }
+    /// Fraction of the battles resolved, between 0 and 1.
+    pub fn progress(&self) -> f32 {
+        match self.total() {
+            0 => 0.0,
+            total => self.resolved.min(total) as f32 / total as f32,
+        }
+    }
+}
+
+/// Battles in a game played with decks of the given sizes.
+///
+/// Each battle takes a card from both decks. Sleeved cards replace a draw,
+/// and sleeving is refused once the deck is empty, so every deck card ends
+/// up in exactly one battle, whatever the hand size.
+fn total_battles(player_deck: usize, oppo_deck: usize) -> usize {
+    player_deck.min(oppo_deck)
+}
+
+/// Name of a battle outcome in saved files.
+fn outcome_id(outcome: Option<BattleOutcome>) -> &'static str {
+    match outcome {
+        None => "none",
+        Some(BattleOutcome::Loss) => "loss",
+        Some(BattleOutcome::Tie) => "tie",
+        Some(BattleOutcome::Win) => "win",
+    }
+}
+
+/// The turn, score and item bookkeeping of a game: what an autosave keeps
+/// besides the cards, see [`crate::autosave`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Progress {
+    turn: usize,
+    battles: usize,
+    last_outcome: Option<BattleOutcome>,
+    /// Size of the player and oppo decks when loaded.
+    deck_sizes: [usize; 2],
+    leader: Participant,
+    coop_active: CoopPlayer,
+    seeds: EnumMap<CoopPlayer, usize>,
+    trinkets: EnumMap<CoopPlayer, usize>,
+    scores: Scores,
+    /// Bonus points of the player and the oppo.
+    bonuses: [i32; 2],
+}
+impl Progress {
+    /// The progress in `entries`, `None` if any is missing or malformed.
+    pub fn from_entries(entries: &Entries) -> Option<Self> {
+        let participant = |key| Participant::from_id(entries.get_str(key)?);
+        let coop_player = |key| {
+            let id = entries.get_str(key)?;
+            CoopPlayer::ALL.into_iter().find(|who| who.id() == id)
+        };
+        let per_player = |key: &str| -> Option<EnumMap<CoopPlayer, usize>> {
+            let first = entries.get(&format!("{key}.first"))?;
+            let second = entries.get(&format!("{key}.second"))?;
+            Some(EnumMap::from_array([first, second]))
+        };
+        let outcomes = [
+            None,
+            Some(BattleOutcome::Loss),
+            Some(BattleOutcome::Tie),
+            Some(BattleOutcome::Win),
+        ];
+        let last_outcome = entries.get_str("last_outcome")?;
+        let last_outcome = outcomes
+            .into_iter()
+            .find(|outcome| outcome_id(*outcome) == last_outcome)?;
+        Some(Self {
+            turn: entries.get("turn")?,
+            battles: entries.get("battles")?,
+            last_outcome,
+            deck_sizes: [
+                entries.get("deck_size.player")?,
+                entries.get("deck_size.oppo")?,
+            ],
+            leader: participant("leader")?,
+            coop_active: coop_player("coop_active")?,
+            seeds: per_player("seeds")?,
+            trinkets: per_player("trinkets")?,
+            scores: Scores {
+                player: entries.get("score.player")?,
+                oppo: entries.get("score.oppo")?,
+                remaining: entries.get("score.remaining")?,
+            },
+            bonuses: [entries.get("bonus.player")?, entries.get("bonus.oppo")?],
+        })
+    }
+    pub fn write_entries(&self, entries: &mut Entries) {
+        entries.set("turn", self.turn);
+        entries.set("battles", self.battles);
+        entries.set("last_outcome", outcome_id(self.last_outcome));
+        entries.set("deck_size.player", self.deck_sizes[0]);
+        entries.set("deck_size.oppo", self.deck_sizes[1]);
+        entries.set("leader", self.leader.id());
+        entries.set("coop_active", self.coop_active.id());
+        for who in CoopPlayer::ALL {
+            entries.set(&format!("seeds.{}", who.id()), self.seeds[who]);
+            entries.set(&format!("trinkets.{}", who.id()), self.trinkets[who]);
+        }
+        entries.set("score.player", self.scores.player);
+        entries.set("score.oppo", self.scores.oppo);
+        entries.set("score.remaining", self.scores.remaining);
+        entries.set("bonus.player", self.bonuses[0]);
+        entries.set("bonus.oppo", self.bonuses[1]);
+    }
+}
+
+/// The resources making up the [`Progress`] of the current game.
+#[derive(SystemParam)]
+pub struct GameProgress<'w, 's> {
+    turn_count: ResMut<'w, TurnCount>,
+    battle_count: ResMut<'w, BattleCount>,
+    initiative: ResMut<'w, Initiative>,
+    coop_turn: ResMut<'w, CoopTurn>,
+    seed_count: ResMut<'w, SeedCount>,
+    inventory: ResMut<'w, Inventory>,
+    scores: ResMut<'w, Scores>,
+    score_bonuses: ResMut<'w, ScoreBonuses>,
+    lead: ResMut<'w, OppoLead>,
+    #[system_param(ignore)]
+    _queries: PhantomData<&'s ()>,
+}
+impl<'w, 's> GameProgress<'w, 's> {
+    pub fn progress(&self) -> Progress {
+        let battle_count = &self.battle_count;
+        Progress {
+            turn: self.turn_count.0,
+            battles: battle_count.resolved,
+            last_outcome: battle_count.last_outcome,
+            deck_sizes: [battle_count.player_deck, battle_count.oppo_deck],
+            leader: self.initiative.0,
+            coop_active: self.coop_turn.active,
+            seeds: self.seed_count.counts,
+            trinkets: self.inventory.trinkets,
+            scores: *self.scores,
+            bonuses: [self.score_bonuses.player, self.score_bonuses.oppo],
+        }
+    }
+    /// Go back to `progress`. The [`BonusGrant`]s and the [`OppoLead`] point
+    /// to cards of the table being replaced, they are forgotten.
+    pub fn restore(&mut self, progress: &Progress) {
+        self.turn_count.0 = progress.turn;
+        *self.battle_count = BattleCount {
+            resolved: progress.battles,
+            last_outcome: progress.last_outcome,
+            player_deck: progress.deck_sizes[0],
+            oppo_deck: progress.deck_sizes[1],
+        };
+        self.initiative.0 = progress.leader;
+        self.coop_turn.active = progress.coop_active;
+        self.seed_count.counts = progress.seeds;
+        self.inventory.trinkets = progress.trinkets;
+        *self.scores = progress.scores;
+        *self.score_bonuses = ScoreBonuses {
+            player: progress.bonuses[0],
+            oppo: progress.bonuses[1],
+            grants: VecDeque::new(),
+        };
+        self.lead.0 = None;
+    }
+}
+
+/// [`TurnState`] transitions requested by game systems, applied once per
+/// frame by [`apply_turn_requests`].
+///
+/// Use this rather than setting `State<TurnState>` directly: two systems
+/// setting the state in the same frame would panic with `StateAlreadyQueued`.
+#[derive(Default)]
+pub struct TurnRequests(Vec<TurnState>);
+impl TurnRequests {
+    pub fn request(&mut self, next: TurnState) {
+        self.0.push(next);
+    }
+}
+
+/// When several transitions are requested in the same frame, the one with
+/// the highest priority wins.
+fn transition_priority(state: &TurnState) -> u8 {
+    use TurnState::*;
+    match state {
+        CardPlayed => 3,
+        New => 2,
+        Draw => 1,
+        Player | Oppo | Starting | Paused => 0,
+    }
+}
+
+/// The transition to apply among `requests`, nothing if the game is over.
+///
+/// On equal priority, the first request wins.
+fn resolve_requests(requests: &[TurnState], game_over: bool) -> Option<TurnState> {
+    if game_over {
+        return None;
+    }
+    // `max_by_key` returns the last of equal elements
+    requests
+        .iter()
+        .rev()
+        .copied()
+        .max_by_key(transition_priority)
+}
+
 /// Handle [`PlayCard`] events.
 ///
-/// Directly updates the [`SeedCount`] resource when an [`PlayCard`] event
-/// is received, move the card to the war [`Pile`], and then enter the active
+/// Move the card to the war [`Pile`], and then enter the active
 /// [`TurnState`] corresponding to [`PlayCard::who`] played the card.
+///
+/// Cards already played are ignored, so that playing is idempotent.
 fn handle_played(
     mut events: EventReader<PlayCard>,
+    mut game_events: EventWriter<GameEvent>,
     mut ui_events: EventWriter<EffectEvent>,
     mut cmds: Commands,
     mut pile: Query<&mut Pile>,
-    mut turn: ResMut<State<TurnState>>,
-    mut seed_count: ResMut<SeedCount>,
+    mut turn: ResMut<TurnRequests>,
+    mut scores: ResMut<Scores>,
     mut audio_events: EventWriter<AudioRequest>,
+    mut lead: ResMut<OppoLead>,
     cards: Query<&Card>,
+    played: Query<(), With<PlayedCard>>,
+    time: Res<Time>,
 ) {
     use PileType::War;
-    for PlayCard { card, .. } in events.iter() {
+    for PlayCard { card, who } in events.iter() {
         let msg = "War pile exists";
         let mut pile = pile.iter_mut().find(|p| p.which == War).expect(msg);
+        // A duplicate would stay in the war pile forever, the battle would
+        // never be resolved again
+        if played.get(*card).is_ok() || pile.contains(*card) {
+            debug_print!(sec: 3.0, col: Color::ORANGE, "ignored duplicate play of {card:?}");
+            continue;
+        }
         cmds.entity(*card)
             .insert_bundle((pile.add_existing(*card), PlayedCard));
+        if *who == Participant::Oppo && pile.cards().len() == 1 {
+            lead.0 = Some(*card);
+        }
+        if let Ok(card) = cards.get(*card) {
+            scores.card_played(card);
+            game_events.send(GameEvent::CardPlayed { who: *who, card: card.clone() });
+        }
         let card_word = cards.get(*card).map(|c| c.word);
         audio_events.send(AudioRequest::PlayShuffleLong);
         if let Ok(Some(word)) = card_word {
-            ui_events.send(EffectEvent::Show(word));
             audio_events.send(AudioRequest::PlayWord(word));
+            let started_at = time.seconds_since_startup();
+            let ends_at = started_at + AudioAssets::word_duration(word);
+            cmds.insert_resource(WordAnnouncement { card: *card, word, started_at, ends_at });
         }
-        if let Ok(Some(Egeq)) = card_word {
-            seed_count.0 += 1;
+        // Show the words of both battle cards once the second one is played
+        let words: Vec<_> = pile
+            .cards()
+            .iter()
+            .filter_map(|entity| cards.get(*entity).ok()?.word)
+            .collect();
+        if !words.is_empty() {
+            ui_events.send(EffectEvent::PlayedWords(words));
         }
-        turn.set(TurnState::CardPlayed).unwrap();
+        turn.request(TurnState::CardPlayed);
     }
 }
 
 /// Handle what happens after a card is played
 ///
 /// If there is exactly two cards in the war pile, compute results, move cards
-/// to the winner pile(s) and add any bonus points to [`ScoreBonuses`] if
-/// any card effects were in play this turn. Then enter new turn.
+/// to the winner pile(s), add the points to [`Scores`] and any bonus points to
+/// [`ScoreBonuses`] if any card effects were in play this turn, then send a
+/// [`BattleResolved`] event. Then enter new turn.
+///
+/// The [`CoopTurn::active`] player gets a trinket if they won by a wide
+/// margin, and a seed for each [`Egeq`] card paying out. When they can't
+/// hold more seeds, they get bonus points instead. In a fair game, they
+/// always get points rather than seeds.
+///
+/// Battles earning bonus points are recorded as [`BonusGrant`]s.
 fn handle_turn_end(
-    played_cards: Query<(&CardOrigin, &Card, Entity), With<PlayedCard>>,
+    played_cards: Query<(&CardOrigin, &Card, Entity, &PileCard), With<PlayedCard>>,
     mut piles: Query<&mut Pile>,
     mut cmds: Commands,
     mut score_bonuses: ResMut<ScoreBonuses>,
+    mut scores: ResMut<Scores>,
+    mut game_events: EventWriter<GameEvent>,
+    mut battle_events: EventWriter<BattleResolved>,
+    mut ui_events: EventWriter<EffectEvent>,
+    mut battle_count: ResMut<BattleCount>,
+    mut coop_turn: ResMut<CoopTurn>,
+    mut inventory: ResMut<Inventory>,
+    mut seed_count: ResMut<SeedCount>,
     mut score_update: EventWriter<ScoreEvent>,
+    rules: Res<GameRules>,
 ) {
-    use Participant::{Oppo, Player};
-
-    let war_pile: Vec<_> = played_cards.iter().collect();
-
-    let mut send_score_update = |who, score| {
-        score_update.send(ScoreEvent::Add(who, score));
-    };
+    let mut war_pile: Vec<_> = played_cards.iter().collect();
+    war_pile.sort_by_key(|card| card.3.sequence);
 
-    let mut add_card_to_pile = |entity, bonus, who: Participant| {
+    let mut add_card_to_pile = |entity, points: i32, value: i32, who: Participant| {
         let is_war = |p: &Mut<Pile>| p.which == PileType::War;
         let is_who = |p: &Mut<Pile>| p.which == who.into();
 
@@ -232,34 +1024,67 @@ fn handle_turn_end(
             .insert(pile.add_existing(entity))
             .remove::<PlayedCard>();
         piles.iter_mut().find(is_war).unwrap().remove(entity);
-        score_bonuses.add_to_owner(who, bonus);
-        bonus
+        score_bonuses.add_to_owner(who, points - value);
     };
     match war_pile[..] {
         [card1, card2] => {
             let player_is_1 = card1.0 .0 == Participant::Player;
             let (player, oppo) = if player_is_1 { (card1, card2) } else { (card2, card1) };
-            let (player_bonus, oppo_bonus) = player.1.bonus_points(oppo.1);
-            screen_print!(sec: 2, "player: {player_bonus}, oppo: {oppo_bonus}");
-            match player.1.beats(oppo.1) {
-                BattleOutcome::Tie => {
-                    let p1_bonus = add_card_to_pile(player.2, player_bonus, Player);
-                    let p2_bonus = add_card_to_pile(oppo.2, oppo_bonus, Oppo);
-                    send_score_update(Player, p1_bonus + player.1.value_i32());
-                    send_score_update(Oppo, p2_bonus + oppo.1.value_i32());
-                }
-                BattleOutcome::Loss => {
-                    let p1_bonus = add_card_to_pile(player.2, player_bonus, Oppo);
-                    let p2_bonus = add_card_to_pile(oppo.2, oppo_bonus, Oppo);
-                    let cards_value = player.1.value_i32() + oppo.1.value_i32();
-                    send_score_update(Oppo, p1_bonus + p2_bonus + cards_value);
-                }
-                BattleOutcome::Win => {
-                    let p1_bonus = add_card_to_pile(player.2, player_bonus, Player);
-                    let p2_bonus = add_card_to_pile(oppo.2, oppo_bonus, Player);
-                    let cards_value = player.1.value_i32() + oppo.1.value_i32();
-                    send_score_update(Player, p1_bonus + p2_bonus + cards_value);
-                }
+            let battle = Battle::new(player.1, oppo.1);
+            let (player_owner, player_points) = battle.player_card;
+            let (oppo_owner, oppo_points) = battle.oppo_card;
+            debug_print!(sec: 2, "player: {player_points}, oppo: {oppo_points}");
+            add_card_to_pile(player.2, player_points, player.1.value_i32(), player_owner);
+            add_card_to_pile(oppo.2, oppo_points, oppo.1.value_i32(), oppo_owner);
+            let mut grant = BonusGrant::new(
+                battle_count.resolved + 1,
+                (player.2, player.1),
+                (oppo.2, oppo.1),
+            );
+            grant.add_to_owner(player_owner, player_points - player.1.value_i32());
+            grant.add_to_owner(oppo_owner, oppo_points - oppo.1.value_i32());
+            let rewards = battle.rewards();
+            for (who, points) in &rewards {
+                scores.add_to_owner(*who, *points);
+            }
+            let points = player_points + oppo_points;
+            let play = |(origin, card, ..): (&CardOrigin, &Card, Entity, &PileCard)| Play {
+                who: origin.0,
+                card: card.clone(),
+            };
+            let order = [play(card1), play(card2)];
+            battle_count.resolved += 1;
+            battle_count.last_outcome = Some(battle.outcome);
+            let margin = player_points - oppo_points;
+            let outcome = battle.outcome;
+            game_events.send(GameEvent::BattleResolved { outcome, margin, rewards });
+            let resolved = BattleResolved { outcome, points, order, margin };
+            if resolved.earns_trinket(&rules) {
+                inventory.add_trinket(coop_turn.active());
+                ui_events.send(EffectEvent::TrinketEarned);
+            }
+            for _ in 0..resolved.egeq_payouts(&rules) {
+                let active = coop_turn.active();
+                let points = if rules.fair_game {
+                    ui_events.send(EffectEvent::FairSeed);
+                    rules.fair_seed_points
+                } else if seed_count.try_add(active) == SeedGain::Seed {
+                    game_events.send(GameEvent::SeedGained(active));
+                    continue;
+                } else {
+                    ui_events.send(EffectEvent::SeedOverflow);
+                    rules.seed_overflow_points
+                };
+                score_bonuses.add_to_owner(Participant::Player, points);
+                grant.add_to_owner(Participant::Player, points);
+                grant.seed_points += points;
+                scores.add_to_owner(Participant::Player, points);
+                score_update.send(ScoreEvent::SeedOverflow(points));
+            }
+            score_bonuses.record(grant);
+            battle_events.send(resolved);
+            if rules.coop {
+                coop_turn.swap();
             }
         }
         [] | [_] => {}
@@ -269,17 +1094,59 @@ fn handle_turn_end(
     }
 }
 
+/// Show the value of the next oppo card when the player earns a [`Glimpse`].
+fn glimpse_oppo_deck(
+    mut battles: EventReader<BattleResolved>,
+    mut glimpse: ResMut<Glimpse>,
+    mut ui_events: EventWriter<EffectEvent>,
+    oppo_deck: Query<&OppoDeck>,
+    rules: Res<GameRules>,
+) {
+    let next = oppo_deck.get_single().ok().and_then(OppoDeck::peek);
+    for resolved in battles.iter() {
+        match next {
+            Some(next) if glimpse.earn(resolved, &rules) => {
+                ui_events.send(EffectEvent::Glimpse(next.value_i32()));
+            }
+            _ => {}
+        }
+    }
+}
+
+fn forget_glimpse(mut glimpse: ResMut<Glimpse>) {
+    *glimpse = Glimpse::default();
+}
+
 /// Sets of cards that are not in piles (aka: in hand)
 type HandFilter = (With<CardOrigin>, Without<PileCard>, Without<SleeveCard>);
 
-/// Query scores.
+/// Add the points of newly loaded decks to the [`Scores`], and their size to
+/// the [`BattleCount`].
+fn track_deck_scores(
+    mut scores: ResMut<Scores>,
+    mut battle_count: ResMut<BattleCount>,
+    player_deck: Query<&PlayerDeck, Added<PlayerDeck>>,
+    oppo_deck: Query<&OppoDeck, Added<OppoDeck>>,
+) {
+    for deck in player_deck.iter() {
+        battle_count.player_deck = deck.remaining();
+    }
+    for deck in oppo_deck.iter() {
+        battle_count.oppo_deck = deck.remaining();
+    }
+    let player_scores = player_deck.iter().map(PlayerDeck::score);
+    for deck_score in player_scores.chain(oppo_deck.iter().map(OppoDeck::score)) {
+        scores.deck_loaded(deck_score);
+    }
+}
+
+/// Recompute scores from scratch, to check the [`Scores`] cache.
 ///
 /// A [`Participant`]'s score is exactly the [`crate::war::Value`] of cards in their
-/// [`Pile`] plus any bonus points earned with [`crate::war::WordOfPower`]s. Since it is
-/// not trivial to compute it, this `SystemParam` let you query the scores
-/// through its methods.
+/// [`Pile`] plus any bonus points earned with [`crate::war::WordOfPower`]s.
+#[cfg(debug_assertions)]
 #[derive(SystemParam)]
-pub struct CardStats<'w, 's> {
+struct CardStats<'w, 's> {
     cards: Query<'w, 's, &'static Card>,
     piles: Query<'w, 's, &'static Pile>,
     hands: Query<'w, 's, &'static Card, HandFilter>,
@@ -287,9 +1154,11 @@ pub struct CardStats<'w, 's> {
     player_deck: Query<'w, 's, &'static PlayerDeck>,
     oppo_deck: Query<'w, 's, &'static OppoDeck>,
     score_bonuses: Res<'w, ScoreBonuses>,
+    scores: Res<'w, Scores>,
 }
+#[cfg(debug_assertions)]
 impl<'w, 's> CardStats<'w, 's> {
-    pub fn remaining_score(&self) -> i32 {
+    fn remaining_score(&self) -> i32 {
         let hands_score: i32 = self.hands.iter().map(Card::max_value).sum();
         let sleeve_score: i32 = self.sleeve.iter().map(Card::max_value).sum();
         let player_score = self.player_deck.single().score();
@@ -303,42 +1172,130 @@ impl<'w, 's> CardStats<'w, 's> {
         let pile = self.piles.iter().find(pile).unwrap();
         pile.cards().iter().filter_map(card).map(value).sum()
     }
-    pub fn player_score(&self) -> i32 {
-        self.score(PileType::Player) + self.score_bonuses.player
+    fn assert_cached(&self) {
+        let piles = (self.score(PileType::Player), self.score(PileType::Oppo));
+        let recomputed = Scores::recompute(piles, &self.score_bonuses, self.remaining_score());
+        assert_eq!(*self.scores, recomputed, "Scores cache is out of sync");
     }
-    pub fn oppo_score(&self) -> i32 {
-        self.score(PileType::Oppo) + self.score_bonuses.oppo
+}
+
+/// What [`check_win`] decides the game on.
+#[derive(Clone, Debug, Default)]
+pub struct WinProgress {
+    pub scores: Scores,
+    /// Values of the cards in the player pile, then in the oppo pile.
+    pub collected: [Vec<Value>; 2],
+    /// Battles resolved so far.
+    pub battles: usize,
+}
+
+/// Reads the game into a [`WinProgress`].
+#[derive(SystemParam)]
+struct WinInputs<'w, 's> {
+    scores: Res<'w, Scores>,
+    battle_count: Res<'w, BattleCount>,
+    piles: Query<'w, 's, &'static Pile>,
+    cards: Query<'w, 's, &'static Card>,
+}
+impl<'w, 's> WinInputs<'w, 's> {
+    fn progress(&self) -> WinProgress {
+        let collected = |which: PileType| {
+            let pile = self.piles.iter().filter(|pile| pile.which == which);
+            let cards = pile
+                .flat_map(Pile::cards)
+                .filter_map(|e| self.cards.get(*e).ok());
+            cards.map(|card| card.value).collect()
+        };
+        WinProgress {
+            scores: *self.scores,
+            collected: [collected(PileType::Player), collected(PileType::Oppo)],
+            battles: self.battle_count.resolved,
+        }
     }
 }
 
-/// Check for score-based lose/win conditions and enter selection state.
+/// Whether `collected` holds cards of all the `set` values, as many times as
+/// they are listed.
+fn holds_set(collected: &[Value], set: &[Value]) -> bool {
+    let count = |values: &[Value], value: &Value| values.iter().filter(|v| *v == value).count();
+    set.iter()
+        .all(|value| count(collected, value) >= count(set, value))
+}
+
+/// How the game ends under `condition`, `None` while it goes on.
+pub fn check_win(condition: &WinCondition, progress: &WinProgress) -> Option<EndReason> {
+    let scores = &progress.scores;
+    let higher_score = || match scores.player().cmp(&scores.oppo()) {
+        Ordering::Greater => Some(EndReason::Victory),
+        Ordering::Less => Some(EndReason::Loss),
+        Ordering::Equal => None,
+    };
+    match condition {
+        WinCondition::ScoreGap => decided_end(scores),
+        WinCondition::TargetScore(target) => {
+            let reached = scores.player() >= *target || scores.oppo() >= *target;
+            if reached {
+                higher_score()
+            } else {
+                decided_end(scores)
+            }
+        }
+        WinCondition::CollectSet(set) => {
+            let [player, oppo] = &progress.collected;
+            match (holds_set(player, set), holds_set(oppo, set)) {
+                (true, false) => Some(EndReason::Victory),
+                (false, true) => Some(EndReason::Loss),
+                (true, true) => higher_score(),
+                (false, false) if scores.remaining() <= 0 => decided_end(scores),
+                (false, false) => None,
+            }
+        }
+        WinCondition::SurviveTurns(battles) => match decided_end(scores) {
+            None if progress.battles >= *battles => higher_score(),
+            end => end,
+        },
+    }
+}
+
+/// How the game ends if one of the players can't catch up anymore, even
+/// earning all the remaining points.
+fn decided_end(scores: &Scores) -> Option<EndReason> {
+    let lead = scores.player() - scores.oppo();
+    if lead > scores.remaining() {
+        Some(EndReason::Victory)
+    } else if -lead > scores.remaining() {
+        Some(EndReason::Loss)
+    } else {
+        None
+    }
+}
+
+/// End the game if the [`GameRules::win_condition`] is decided, see
+/// [`check_win`], otherwise enter selection state.
 fn handle_new_turn(
     mut initative: ResMut<Initiative>,
-    mut turn: ResMut<State<TurnState>>,
+    mut turn: ResMut<TurnRequests>,
     mut turn_count: ResMut<TurnCount>,
-    mut gameover_events: EventWriter<GameOver>,
+    mut game_events: EventWriter<GameEvent>,
     hands: Query<(), HandFilter>,
-    card_stats: CardStats,
+    win_inputs: WinInputs,
+    rules: Res<GameRules>,
+    #[cfg(debug_assertions)] card_stats: CardStats,
 ) {
-    screen_print!(sec: 1.0, col: Color::BLUE, "handle turn n*{}", turn_count.0);
-    let player_score = card_stats.player_score();
-    let oppo_score = card_stats.oppo_score();
-    let remaining_scores = card_stats.remaining_score();
-    if player_score - oppo_score > remaining_scores {
-        gameover_events.send(GameOver(EndReason::Victory));
-        return;
-    } else if oppo_score - player_score > remaining_scores {
-        gameover_events.send(GameOver(EndReason::Loss));
+    debug_print!(sec: 1.0, col: Color::BLUE, "handle turn n*{}", turn_count.0);
+    #[cfg(debug_assertions)]
+    card_stats.assert_cached();
+    if let Some(end) = check_win(&rules.win_condition, &win_inputs.progress()) {
+        game_events.send(GameEvent::GameEnded(end));
         return;
     }
     turn_count.0 += 1;
-    if turn_count.0 % 2 == 1 {
-        initative.swap();
-    }
+    let last_outcome = win_inputs.battle_count.last_outcome;
+    initative.advance(turn_count.0, rules.initiative, last_outcome);
     match initative.0 {
-        _ if hands.iter().len() == 0 => turn.overwrite_set(TurnState::Draw).unwrap(),
-        Participant::Oppo => turn.overwrite_set(TurnState::Oppo).unwrap(),
-        Participant::Player => turn.overwrite_set(TurnState::Player).unwrap(),
+        _ if hands.iter().len() == 0 => turn.request(TurnState::Draw),
+        Participant::Oppo => turn.request(TurnState::Oppo),
+        Participant::Player => turn.request(TurnState::Player),
     };
 }
 
@@ -346,67 +1303,1092 @@ fn handle_new_turn(
 /// card selection state.
 fn complete_draw(
     initative: Res<Initiative>,
-    mut turn: ResMut<State<TurnState>>,
+    mut turn: ResMut<TurnRequests>,
     hands: Query<(), HandFilter>,
 ) {
     if hands.iter().count() >= 6 {
         match initative.0 {
-            Participant::Oppo => turn.set(TurnState::Oppo).unwrap(),
-            Participant::Player => turn.set(TurnState::Player).unwrap(),
+            Participant::Oppo => turn.request(TurnState::Oppo),
+            Participant::Player => turn.request(TurnState::Player),
         };
     }
 }
 
+/// Seconds [`TurnState::CardPlayed`] lasts, before the game speed.
+const TURN_INTERLUDE: f32 = 0.5;
+
+/// The timeout is set on the first frame and checked from the next one, so
+/// [`TurnState::CardPlayed`] lasts at least two frames however fast the game.
+///
+/// The interlude is extended until the played cards reached the war pile, see
+/// [`PilesSettled`], and the timed animations tagged
+/// [`crate::animate::AwaitAnimation`] are done. If they never are, the
+/// [`watchdog`] pushes the turn forward after [`STALL_TIMEOUT`] seconds.
+///
+/// When the oppo leads, its card is announced first: the effect display
+/// names it and the card is lifted with an [`Emphasis`], for
+/// [`GameplaySettings::announce_beat`] more seconds. Clicking skips it.
 fn wait_active(
-    mut turn: ResMut<State<TurnState>>,
+    mut cmds: Commands,
+    mut turn: ResMut<TurnRequests>,
     mut timeout: Local<Option<f64>>,
+    mut announcing: Local<bool>,
+    mut lead: ResMut<OppoLead>,
+    mut ui_events: EventWriter<EffectEvent>,
+    cards: Query<&Card>,
+    mouse: Res<Input<MouseButton>>,
+    gameplay: Res<GameplaySettings>,
     time: Res<Time>,
+    speed: Res<GameSpeed>,
+    settled: Res<PilesSettled>,
+    mut animations: WaitForAnimations,
 ) {
-    const TURN_INTERLUDE: f64 = 0.5;
+    let now = time.seconds_since_startup();
     match *timeout {
-        Some(some_timeout) if some_timeout < time.seconds_since_startup() => {
-            turn.set(TurnState::New).unwrap();
+        Some(some_timeout) if some_timeout < now && settled.0 && !animations.pending() => {
+            turn.request(TurnState::New);
             *timeout = None;
+            *announcing = false;
+        }
+        Some(_) if *announcing && mouse.just_pressed(MouseButton::Left) => {
+            *timeout = Some(now);
+            *announcing = false;
         }
         None => {
-            *timeout = Some(time.seconds_since_startup() + TURN_INTERLUDE);
+            let beat = gameplay.announce_beat;
+            let lead = lead.0.take().filter(|_| beat > 0.0);
+            let lead = lead.and_then(|entity| Some((entity, cards.get(entity).ok()?)));
+            let mut interlude = TURN_INTERLUDE;
+            if let Some((entity, card)) = lead {
+                ui_events.send(EffectEvent::ShowPlainCard(card.value));
+                cmds.entity(entity)
+                    .insert(Emphasis::new(speed.duration(beat)));
+                interlude += beat;
+            }
+            *announcing = lead.is_some();
+            *timeout = Some(now + speed.duration(interlude) as f64);
         }
         _ => {}
     };
 }
 
+/// Seconds a turn state that should advance on its own can last before the
+/// [`watchdog`] pushes it forward.
+const STALL_TIMEOUT: f32 = 15.0;
+
+/// What the [`watchdog`] looks at to tell a stuck turn from a slow one.
+#[derive(Clone, Copy, Debug, Default)]
+struct TurnActivity {
+    /// Seconds since the turn state last changed, while playing.
+    stalled_for: f32,
+    /// The player holds a card.
+    grabbed: bool,
+    /// Cards are in the war pile, waiting for their battle.
+    played: bool,
+    /// Cards in both hands, including those being drawn.
+    hand_cards: usize,
+    /// Cards left in both decks.
+    deck_cards: usize,
+}
+
+/// The transition unsticking a game stuck in `state`, `None` if it isn't
+/// stuck.
+///
+/// Only states that advance on their own can get stuck: the player may think
+/// as long as they want in [`TurnState::Player`].
+fn recovery(state: TurnState, activity: TurnActivity) -> Option<TurnState> {
+    use TurnState::*;
+    let idle = activity.stalled_for >= STALL_TIMEOUT && !activity.grabbed;
+    let cards_left = activity.hand_cards != 0 || activity.deck_cards != 0;
+    match state {
+        _ if !idle => None,
+        // Decks not loaded yet, or nothing left to draw
+        Starting | Draw if !cards_left => None,
+        // Decks loaded, the deck reveal and coin flip are long over
+        Starting if !activity.played => Some(Draw),
+        // Everything was drawn on entering the state, nothing else will come
+        Draw if !activity.played => Some(New),
+        // The interlude is long over, the last battle's too
+        CardPlayed => Some(New),
+        Starting | Draw | New | Player | Oppo | Paused => None,
+    }
+}
+
+/// Push forward turn states that should have advanced on their own, when no
+/// system does.
+///
+/// This shouldn't happen, but if a transition is lost, it avoids soft-locking
+/// the game.
+///
+/// The state is compared with the one `seen` last frame rather than checked
+/// with `is_changed`: the state driver touches `State` every frame.
+fn watchdog(
+    mut requests: ResMut<TurnRequests>,
+    mut stalled_for: Local<f32>,
+    mut seen: Local<Option<TurnState>>,
+    turn: Res<State<TurnState>>,
+    time: Res<Time>,
+    grabbed: Query<(), With<GrabbedCard>>,
+    played: Query<(), With<PlayedCard>>,
+    hands: Query<(), HandFilter>,
+    player_deck: Query<&PlayerDeck>,
+    oppo_deck: Query<&OppoDeck>,
+) {
+    if *seen != Some(*turn.current()) {
+        *seen = Some(*turn.current());
+        *stalled_for = 0.0;
+    }
+    *stalled_for += time.delta_seconds();
+    let player_cards = player_deck.iter().map(PlayerDeck::remaining);
+    let deck_cards = player_cards.chain(oppo_deck.iter().map(OppoDeck::remaining));
+    let activity = TurnActivity {
+        stalled_for: *stalled_for,
+        grabbed: !grabbed.is_empty(),
+        played: !played.is_empty(),
+        hand_cards: hands.iter().count(),
+        deck_cards: deck_cards.sum(),
+    };
+    let (current, stalled) = (*turn.current(), *stalled_for);
+    if let Some(next) = recovery(current, activity) {
+        warn!("Turn stuck in {current:?} for {stalled:.0}s, moving on to {next:?}");
+        requests.request(next);
+        *stalled_for = 0.0;
+    }
+}
+
+/// Apply the most important of the [`TurnRequests`] of this frame.
+///
+/// Requests are dropped on game over, and kept for later while the game is
+/// paused or if another transition is already queued.
+fn apply_turn_requests(
+    mut requests: ResMut<TurnRequests>,
+    mut turn: ResMut<State<TurnState>>,
+    mut game_events: EventReader<GameEvent>,
+) {
+    let game_over = game_events.iter().filter_map(GameEvent::end).count() != 0;
+    if requests.0.is_empty() || (*turn.current() == TurnState::Paused && !game_over) {
+        return;
+    }
+    let requested = std::mem::take(&mut requests.0);
+    let next = resolve_requests(&requested, game_over);
+    let mut dropped = requested.iter().filter(|s| Some(**s) != next).peekable();
+    if dropped.peek().is_some() {
+        let dropped: Vec<_> = dropped.collect();
+        debug_print!(sec: 3.0, col: Color::ORANGE, "dropped turn transitions: {dropped:?}");
+    }
+    if let Some(next) = next {
+        match turn.set(next) {
+            Err(StateError::StateAlreadyQueued) => requests.request(next),
+            Err(err) => debug_print!(col: Color::RED, "can't enter {next:?}: {err:?}"),
+            Ok(()) => {}
+        }
+    }
+}
+
+/// Celebrate the [`Milestone`]s newly reached, with a jingle and a message.
+fn celebrate_milestones(
+    scores: Res<Scores>,
+    mut milestones: ResMut<Milestones>,
+    mut ui: EventWriter<EffectEvent>,
+    mut audio: EventWriter<AudioRequest>,
+) {
+    if !scores.is_changed() {
+        return;
+    }
+    for milestone in milestones.update(&scores) {
+        ui.send(EffectEvent::Milestone(milestone));
+        audio.send(AudioRequest::PlayJingle);
+    }
+}
+
 /// Remove all entities related to the game and resets resource values.
 fn cleanup(
     mut cmds: Commands,
     all_cards: Query<Entity, With<Card>>,
     mut turn_count: ResMut<TurnCount>,
+    mut battle_count: ResMut<BattleCount>,
     mut initative: ResMut<Initiative>,
     mut score_bonuses: ResMut<ScoreBonuses>,
+    mut scores: ResMut<Scores>,
+    mut milestones: ResMut<Milestones>,
     mut seed_count: ResMut<SeedCount>,
+    mut inventory: ResMut<Inventory>,
+    mut coop_turn: ResMut<CoopTurn>,
+    mut turn_requests: ResMut<TurnRequests>,
+    mut lead: ResMut<OppoLead>,
+    rules: Res<GameRules>,
 ) {
     turn_count.0 = 0;
+    lead.0 = None;
+    *battle_count = BattleCount::default();
+    turn_requests.0.clear();
     initative.0 = Participant::Player;
     *score_bonuses = ScoreBonuses::default();
-    *seed_count = SeedCount::default();
+    *scores = Scores::default();
+    *milestones = Milestones::default();
+    *seed_count = SeedCount::new(rules.seed_cap);
+    *inventory = Inventory::default();
+    *coop_turn = CoopTurn::default();
     for entity in all_cards.iter() {
         cmds.entity(entity).despawn_recursive();
     }
 }
 
+/// Put the turn back to [`TurnState::Starting`], so that the next game starts
+/// with a fresh [`TurnState::Draw`].
+///
+/// The game can end in any turn state, even while cards are being drawn. Left
+/// in `Draw`, the next game's draw request would be refused as already in
+/// that state. `overwrite_replace` because the pause menu may have queued
+/// popping [`TurnState::Paused`] while the game was left.
+fn reset_turn(mut turn: ResMut<State<TurnState>>) {
+    if *turn.current() == TurnState::Starting {
+        return;
+    }
+    if let Err(err) = turn.overwrite_replace(TurnState::Starting) {
+        debug_print!(col: Color::RED, "can't reset turn: {err:?}");
+    }
+}
+
 pub struct Plugin(pub GameState);
 impl BevyPlugin for Plugin {
     fn build(&self, app: &mut App) {
         use crate::system_helper::EasySystemSetCtor;
         app.add_event::<PlayCard>()
+            .add_event::<BattleResolved>()
             .init_resource::<TurnCount>()
+            .init_resource::<BattleCount>()
             .init_resource::<ScoreBonuses>()
+            .init_resource::<Scores>()
+            .init_resource::<Milestones>()
             .init_resource::<SeedCount>()
-            .insert_resource(Initiative(Participant::Player))
-            .add_system_set(self.0.on_update(handle_played))
-            .add_system_set(self.0.on_exit(cleanup))
-            .add_system_set(TurnState::New.on_enter(handle_new_turn))
+            .init_resource::<Inventory>()
+            .init_resource::<Glimpse>()
+            .init_resource::<CoopTurn>()
+            .init_resource::<GameRules>()
+            .init_resource::<TurnRequests>()
+            .init_resource::<OppoLead>()
+            .init_resource::<Initiative>()
+            .add_system_to_stage(CoreStage::PostUpdate, apply_turn_requests)
+            .add_system_set(
+                self.0
+                    .on_update(handle_played)
+                    .with_system(track_deck_scores.label("track_deck_scores"))
+                    .with_system(celebrate_milestones)
+                    .with_system(glimpse_oppo_deck)
+                    .with_system(watchdog),
+            )
+            .add_system_set(
+                self.0
+                    .on_exit(cleanup.after(despawn_fading))
+                    .with_system(reset_turn),
+            )
+            .add_system_set(TurnState::New.on_enter(handle_new_turn.label("new_turn")))
+            .add_system_set(TurnState::Draw.on_enter(forget_glimpse))
             .add_system_set(TurnState::Draw.on_update(complete_draw))
             .add_system_set(TurnState::CardPlayed.on_update(wait_active))
             .add_system_set(TurnState::CardPlayed.on_exit(handle_turn_end));
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TurnState::*;
+
+    #[test]
+    fn resolve_turn_requests() {
+        let resolve = |requests: &[TurnState]| resolve_requests(requests, false);
+        assert_eq!(resolve(&[]), None);
+        assert_eq!(resolve(&[Player]), Some(Player));
+        assert_eq!(resolve(&[Player, CardPlayed]), Some(CardPlayed));
+        assert_eq!(resolve(&[CardPlayed, Draw]), Some(CardPlayed));
+        assert_eq!(resolve(&[Draw, New]), Some(New));
+        assert_eq!(resolve(&[Oppo, Draw]), Some(Draw));
+        assert_eq!(resolve(&[Oppo, Player]), Some(Oppo));
+        assert_eq!(resolve(&[Player, Oppo]), Some(Player));
+        assert_eq!(resolve_requests(&[CardPlayed, New], true), None);
+    }
+
+    #[test]
+    fn stuck_turns_are_recovered() {
+        let activity = TurnActivity { hand_cards: 4, deck_cards: 10, ..default() };
+        let stuck = TurnActivity { stalled_for: 20.0, ..activity };
+        assert_eq!(recovery(Starting, stuck), Some(Draw));
+        assert_eq!(recovery(Draw, stuck), Some(New));
+        assert_eq!(recovery(CardPlayed, stuck), Some(New));
+        let played = TurnActivity { played: true, ..stuck };
+        assert_eq!(recovery(CardPlayed, played), Some(New));
+        assert_eq!(recovery(Draw, played), None, "a battle is pending");
+        let last_draw = TurnActivity { deck_cards: 0, ..stuck };
+        assert_eq!(recovery(Draw, last_draw), Some(New), "nothing left to draw");
+
+        for state in [Player, Oppo, New, Paused] {
+            assert_eq!(recovery(state, stuck), None, "{state:?} waits");
+        }
+        let thinking = TurnActivity { stalled_for: 600.0, ..stuck };
+        assert_eq!(recovery(Player, thinking), None);
+
+        let slow = TurnActivity { stalled_for: 5.0, ..stuck };
+        assert_eq!(recovery(Starting, slow), None);
+        assert_eq!(recovery(CardPlayed, slow), None);
+        let grabbed = TurnActivity { grabbed: true, ..stuck };
+        assert_eq!(recovery(CardPlayed, grabbed), None);
+        let loading = TurnActivity { hand_cards: 0, deck_cards: 0, ..stuck };
+        assert_eq!(recovery(Starting, loading), None, "decks not loaded yet");
+        let last_battle = TurnActivity { played: true, ..loading };
+        assert_eq!(recovery(CardPlayed, last_battle), Some(New));
+    }
+
+    #[test]
+    fn watchdog_pushes_stuck_turns() {
+        let mut world = World::new();
+        world.insert_resource(State::new(CardPlayed));
+        world.insert_resource(Time::default());
+        world.init_resource::<TurnRequests>();
+        let card = "4_".parse::<Card>().unwrap();
+        world
+            .spawn()
+            .insert_bundle((card, CardOrigin(Participant::Player)));
+        // Like `App::add_state`, the driver touches the state every frame
+        let mut stage = SystemStage::single_threaded()
+            .with_system_set(State::<TurnState>::get_driver())
+            .with_system(watchdog);
+        let mut frame_at = |world: &mut World, seconds: f32| {
+            let mut time = world.resource_mut::<Time>();
+            let startup = time.startup();
+            time.update_with_instant(startup + Duration::from_secs_f32(seconds));
+            stage.run(world);
+            let requests = &mut world.resource_mut::<TurnRequests>().0;
+            requests.drain(..).collect::<Vec<_>>()
+        };
+        assert_eq!(frame_at(&mut world, 0.0), []);
+        assert_eq!(frame_at(&mut world, 10.0), []);
+        assert_eq!(frame_at(&mut world, 16.0), [New], "stuck in the interlude");
+
+        world.resource_mut::<State<TurnState>>().set(Draw).unwrap();
+        assert_eq!(frame_at(&mut world, 20.0), []);
+        assert_eq!(frame_at(&mut world, 30.0), [], "the timeout starts anew");
+        assert_eq!(frame_at(&mut world, 35.0), [New]);
+    }
+
+    #[test]
+    fn milestones_are_celebrated_once() {
+        let mut milestones = Milestones::default();
+        let mut update =
+            |player, oppo, remaining| milestones.update(&Scores { player, oppo, remaining });
+        assert_eq!(update(0, 0, 0), []);
+        assert_eq!(update(2, 12, 60), []);
+        assert_eq!(update(14, 12, 48), [Milestone::Comeback]);
+        assert_eq!(update(10, 14, 48), []);
+        assert_eq!(update(16, 14, 42), []);
+        assert_eq!(update(40, 14, 20), [Milestone::HalfPoints]);
+        assert_eq!(update(60, 14, 0), []);
+    }
+
+    #[test]
+    fn small_leads_are_no_comeback() {
+        let mut milestones = Milestones::default();
+        let mut update =
+            |player, oppo, remaining| milestones.update(&Scores { player, oppo, remaining });
+        assert_eq!(update(0, 9, 60), []);
+        assert_eq!(update(10, 9, 50), []);
+    }
+
+    #[test]
+    fn total_battles_from_deck_sizes() {
+        assert_eq!(total_battles(18, 18), 18);
+        assert_eq!(total_battles(5, 5), 5);
+        assert_eq!(total_battles(12, 9), 9);
+        assert_eq!(total_battles(0, 18), 0);
+    }
+
+    #[test]
+    fn battle_count_progress() {
+        let mut count = BattleCount::default();
+        assert_eq!(
+            (count.current(), count.total(), count.progress()),
+            (0, 0, 0.0)
+        );
+        count.player_deck = 4;
+        count.oppo_deck = 4;
+        assert_eq!((count.current(), count.progress()), (1, 0.0));
+        count.resolved = 1;
+        assert_eq!((count.current(), count.progress()), (2, 0.25));
+        count.resolved = 4;
+        assert_eq!((count.current(), count.progress()), (4, 1.0));
+    }
+
+    /// The leader of each battle of a game with the given battle outcomes,
+    /// `first` leading the first one.
+    fn leaders(
+        first: Participant,
+        rule: InitiativeRule,
+        outcomes: &[BattleOutcome],
+    ) -> Vec<Participant> {
+        let mut initiative = Initiative(first);
+        let mut leaders = vec![initiative.0];
+        for (battle, outcome) in outcomes.iter().enumerate() {
+            let turn = battle * 2 + 1;
+            initiative.advance(turn, rule, None);
+            assert_ne!(initiative.0, leaders[battle], "the other one plays second");
+            initiative.advance(turn + 1, rule, Some(*outcome));
+            leaders.push(initiative.0);
+        }
+        leaders
+    }
+
+    #[test]
+    fn battle_leaders_follow_initiative_rule() {
+        use BattleOutcome::{Loss, Tie, Win};
+        use Participant::{Oppo as O, Player as P};
+        let outcomes = [Loss, Tie, Win, Tie, Loss];
+        let alternate = leaders(P, InitiativeRule::Alternate, &outcomes);
+        assert_eq!(alternate, [P, O, P, O, P, O]);
+        let winner_leads = leaders(P, InitiativeRule::WinnerLeads, &outcomes);
+        assert_eq!(winner_leads, [P, O, O, P, P, O]);
+        let ties = leaders(P, InitiativeRule::WinnerLeads, &[Tie, Tie]);
+        assert_eq!(ties, [P, P, P]);
+    }
+
+    #[test]
+    fn battle_leaders_alternate_from_either_starter() {
+        use BattleOutcome::{Loss, Tie, Win};
+        use Participant::{Oppo as O, Player as P};
+        let outcomes = [Win, Win, Loss, Tie];
+        let alternate = |first| leaders(first, InitiativeRule::Alternate, &outcomes);
+        assert_eq!(alternate(P), [P, O, P, O, P]);
+        assert_eq!(alternate(O), [O, P, O, P, O]);
+        let winner_leads = |first| leaders(first, InitiativeRule::WinnerLeads, &outcomes);
+        assert_eq!(winner_leads(P), [P, P, P, O, O]);
+        assert_eq!(winner_leads(O), [O, P, P, O, O]);
+    }
+
+    #[test]
+    fn seeds_overflow_at_cap() {
+        use CoopPlayer::First;
+        let mut seeds = SeedCount::new(2);
+        assert_eq!(seeds.try_add(First), SeedGain::Seed);
+        assert_eq!(seeds.try_add(First), SeedGain::Seed);
+        assert_eq!(seeds.try_add(First), SeedGain::Overflow);
+        assert_eq!(seeds.count(First), 2);
+        assert!(seeds.consume(First, 1));
+        assert_eq!(seeds.try_add(First), SeedGain::Seed);
+        assert_eq!(seeds.try_add(First), SeedGain::Overflow);
+        assert!(!SeedCount::new(0).consume(First, 1));
+    }
+
+    #[test]
+    fn coop_players_have_their_own_seeds() {
+        use CoopPlayer::{First, Second};
+        let mut seeds = SeedCount::new(2);
+        let mut turn = CoopTurn::default();
+        assert_eq!(turn.active(), First);
+        seeds.try_add(turn.active());
+        seeds.try_add(turn.active());
+        turn.swap();
+        assert_eq!(turn.active(), Second);
+        assert!(!seeds.is_full(turn.active()));
+        assert!(!seeds.consume(turn.active(), 1), "Second has no seeds yet");
+        assert_eq!(seeds.try_add(turn.active()), SeedGain::Seed);
+        assert!(seeds.consume(turn.active(), 1));
+        assert_eq!((seeds.count(First), seeds.count(Second)), (2, 0));
+        turn.swap();
+        assert_eq!(turn.active(), First);
+        assert_eq!(seeds.try_add(turn.active()), SeedGain::Overflow);
+    }
+
+    #[test]
+    fn battle_rewards_go_to_card_owners() {
+        let card = |s: &str| s.parse::<Card>().unwrap();
+        let win = Battle::new(&card("7_"), &card("5_"));
+        assert!(win.rewards() == [(Participant::Player, 12)]);
+        let tie = Battle::new(&card("4doub"), &card("4_"));
+        let rewards = [(Participant::Player, 8), (Participant::Oppo, 8)];
+        assert!(tie.rewards() == rewards);
+    }
+
+    /// Decks so lopsided that the game is over after the first battle, while
+    /// participants still hold the rest of their hand. Leaving the game must
+    /// reset the turn and the resources, and despawn the cards, so that the
+    /// next game draws its hands once, from its own decks.
+    #[test]
+    fn lopsided_games_restart_cleanly() {
+        let mut flow = Flow::new();
+        flow.start("9_ 0_ 0_ 0_", "0_ 0_ 0_ 0_");
+        assert_eq!(flow.turn(), TurnState::Player);
+        flow.battle("9_", "0_");
+        assert_eq!(flow.ended(), Some(EndReason::Victory));
+        assert_eq!(flow.cards(), 6, "the rest of the hands is still out");
+
+        let mut state = flow.world.resource_mut::<State<GameState>>();
+        state.set(GameState::RestartMenu).unwrap();
+        flow.frame();
+        assert_eq!(flow.turn(), Starting);
+        assert_eq!(flow.cards(), 0);
+        assert_eq!(*flow.world.resource::<Scores>(), Scores::default());
+        assert_eq!(flow.world.resource::<BattleCount>().resolved, 0);
+        assert_eq!(flow.world.resource::<TurnCount>().0, 0);
+        assert!(flow.world.resource::<TurnRequests>().0.is_empty());
+
+        let mut state = flow.world.resource_mut::<State<GameState>>();
+        state.set(GameState::Playing).unwrap();
+        flow.frame();
+        flow.start("3_ 5_ 1_ 2_", "4_ 2_ 6_ 1_");
+        assert_eq!(flow.turn(), TurnState::Player);
+        assert_eq!(flow.cards(), 6, "hands are drawn once");
+        let scores = *flow.world.resource::<Scores>();
+        assert_eq!(scores, Scores { player: 0, oppo: 0, remaining: 24 });
+        assert_eq!(flow.world.resource::<BattleCount>().player_deck, 4);
+        flow.battle("3_", "4_");
+        assert_eq!(flow.ended(), None);
+        assert_eq!(flow.world.resource::<BattleCount>().resolved, 1);
+    }
+
+    /// Play a game with words through the turn systems, comparing the cached
+    /// scores to those recomputed from the decks, hands and piles after each
+    /// draw and battle.
+    #[test]
+    fn cached_scores_match_recomputed() {
+        let player = ["0zero", "3_", "5doub", "7swap", "9_", "2seed"];
+        let oppo = ["9_", "0_", "4doub", "8_", "1zero", "6_"];
+        let mut flow = Flow::new();
+        flow.start(&player.join(" "), &oppo.join(" "));
+        assert_eq!(*flow.world.resource::<Scores>(), flow.recomputed_scores());
+        let mut battles = 0;
+        for (player, oppo) in player.into_iter().zip(oppo) {
+            if flow.ended().is_some() {
+                break;
+            }
+            flow.battle(player, oppo);
+            battles += 1;
+            let cached = *flow.world.resource::<Scores>();
+            assert_eq!(cached, flow.recomputed_scores(), "after {player} {oppo}");
+        }
+        assert!(battles > 3, "the hands are drawn again");
+    }
+
+    fn resolved(player: &str, oppo: &str) -> BattleResolved {
+        let card = |s: &str| s.parse::<Card>().unwrap();
+        let (player, oppo) = (card(player), card(oppo));
+        let battle = Battle::new(&player, &oppo);
+        let order = [
+            Play { who: Participant::Player, card: player },
+            Play { who: Participant::Oppo, card: oppo },
+        ];
+        let margin = battle.player_card.1 - battle.oppo_card.1;
+        BattleResolved { outcome: battle.outcome, points: 0, order, margin }
+    }
+
+    #[test]
+    fn trinkets_need_wide_wins() {
+        let rules = GameRules::default();
+        let earns = |player, oppo| resolved(player, oppo).earns_trinket(&rules);
+        assert!(earns("8_", "2_"));
+        assert!(earns("9_", "0_"));
+        assert!(!earns("7_", "2_"), "a point short");
+        assert!(!earns("2_", "8_"), "a wide loss");
+        assert!(!earns("0swap", "9_"), "the lower card wins");
+        let fair = GameRules { fair_game: true, ..default() };
+        assert!(!resolved("9_", "0_").earns_trinket(&fair));
+
+        let mut inventory = Inventory::default();
+        assert!(!inventory.use_trinket(CoopPlayer::First));
+        inventory.add_trinket(CoopPlayer::First);
+        assert!(!inventory.use_trinket(CoopPlayer::Second));
+        assert!(inventory.use_trinket(CoopPlayer::First));
+        assert_eq!(inventory.trinkets(CoopPlayer::First), 0);
+    }
+
+    #[test]
+    fn glimpses_need_wide_wins() {
+        let rules = GameRules::default();
+        let earns = |player, oppo| resolved(player, oppo).earns_glimpse(&rules);
+        assert!(earns("7_", "2_"));
+        assert!(!earns("6_", "2_"), "a point short");
+        assert!(!earns("2_", "7_"), "a wide loss");
+        let fair = GameRules { fair_game: true, ..default() };
+        assert!(resolved("9_", "0_").earns_glimpse(&fair), "fair");
+    }
+
+    #[test]
+    fn one_glimpse_per_draw() {
+        let rules = GameRules::default();
+        let mut glimpse = Glimpse::default();
+        assert!(!glimpse.earn(&resolved("3_", "2_"), &rules));
+        assert!(glimpse.earn(&resolved("9_", "0_"), &rules));
+        let wide = resolved("8_", "1_");
+        assert!(!glimpse.earn(&wide, &rules), "already glimpsed");
+
+        let mut world = World::new();
+        world.insert_resource(glimpse);
+        let mut stage = SystemStage::single_threaded().with_system(forget_glimpse);
+        stage.run(&mut world);
+        let mut glimpse = world.resource_mut::<Glimpse>();
+        assert!(glimpse.earn(&wide, &rules), "after the next draw");
+    }
+
+    #[test]
+    fn egeq_pays_out_unless_lost() {
+        let rules = GameRules::default();
+        let on_loss = GameRules { egeq_pays_on_loss: true, ..default() };
+        let payouts = |player, oppo, rules| resolved(player, oppo).egeq_payouts(rules);
+        assert_eq!(payouts("7s", "3_", &rules), 1);
+        assert_eq!(payouts("5s", "5_", &rules), 1, "a tie");
+        assert_eq!(payouts("2s", "7_", &rules), 0, "a loss");
+        assert_eq!(payouts("2s", "7_", &on_loss), 1);
+        assert_eq!(payouts("7_", "3s", &rules), 0, "the oppo lost");
+        assert_eq!(payouts("7_", "3s", &on_loss), 1);
+        assert_eq!(payouts("2_", "7s", &rules), 1, "the oppo won");
+        assert_eq!(payouts("4s", "4s", &rules), 2);
+        assert_eq!(payouts("7_", "3_", &on_loss), 0);
+    }
+
+    /// Draw three cards for each participant, like the hand plugins.
+    fn draw_hands(
+        mut cmds: Commands,
+        mut player_deck: Query<&mut PlayerDeck>,
+        mut oppo_deck: Query<&mut OppoDeck>,
+    ) {
+        let player = player_deck.single_mut().draw(3).into_iter();
+        let oppo = oppo_deck.single_mut().draw(3).into_iter();
+        let drawn = player.map(|card| (card, Participant::Player));
+        for (card, who) in drawn.chain(oppo.map(|card| (card, Participant::Oppo))) {
+            cmds.spawn().insert_bundle((card, CardOrigin(who)));
+        }
+    }
+
+    /// Whole games through the turn systems, with the scene, the coin flip,
+    /// the hand plugins and [`wait_active`] standing in as test steps.
+    struct Flow {
+        world: World,
+        update: SystemStage,
+        post_update: SystemStage,
+    }
+    impl Flow {
+        fn new() -> Self {
+            let mut world = World::new();
+            world.insert_resource(State::new(GameState::Playing));
+            world.insert_resource(State::new(Starting));
+            world.init_resource::<TurnCount>();
+            world.init_resource::<BattleCount>();
+            world.init_resource::<Initiative>();
+            world.init_resource::<ScoreBonuses>();
+            world.init_resource::<Scores>();
+            world.init_resource::<Milestones>();
+            world.init_resource::<SeedCount>();
+            world.init_resource::<Inventory>();
+            world.init_resource::<CoopTurn>();
+            world.init_resource::<GameRules>();
+            world.init_resource::<TurnRequests>();
+            world.init_resource::<OppoLead>();
+            world.init_resource::<Time>();
+            world.init_resource::<Events<PlayCard>>();
+            world.init_resource::<Events<GameEvent>>();
+            world.init_resource::<Events<BattleResolved>>();
+            world.init_resource::<Events<EffectEvent>>();
+            world.init_resource::<Events<ScoreEvent>>();
+            world.init_resource::<Events<AudioRequest>>();
+            let update = SystemStage::single_threaded()
+                .with_system_set(State::<GameState>::get_driver())
+                .with_system_set(State::<TurnState>::get_driver())
+                .with_system_set(
+                    SystemSet::on_update(GameState::Playing)
+                        .with_system(track_deck_scores)
+                        .with_system(handle_played),
+                )
+                .with_system_set(
+                    SystemSet::on_exit(GameState::Playing)
+                        .with_system(cleanup)
+                        .with_system(reset_turn),
+                )
+                .with_system_set(SystemSet::on_enter(Draw).with_system(draw_hands))
+                .with_system_set(SystemSet::on_update(Draw).with_system(complete_draw))
+                .with_system_set(SystemSet::on_enter(New).with_system(handle_new_turn))
+                .with_system_set(SystemSet::on_exit(CardPlayed).with_system(handle_turn_end));
+            let post_update = SystemStage::single_threaded().with_system(apply_turn_requests);
+            Self { world, update, post_update }
+        }
+        fn frame(&mut self) {
+            self.update.run(&mut self.world);
+            self.post_update.run(&mut self.world);
+        }
+        fn turn(&self) -> TurnState {
+            *self.world.resource::<State<TurnState>>().current()
+        }
+        fn cards(&mut self) -> usize {
+            self.world.query::<&Card>().iter(&self.world).count()
+        }
+        fn ended(&self) -> Option<EndReason> {
+            let events = self.world.resource::<Events<GameEvent>>();
+            events.get_reader().iter(events).find_map(GameEvent::end)
+        }
+        /// Replace the piles and decks with those of a new game, like the
+        /// scene, then draw, like the coin flip.
+        fn start(&mut self, player: &str, oppo: &str) {
+            type Scene = Or<(With<Pile>, With<PlayerDeck>, With<OppoDeck>)>;
+            let mut scene = self.world.query_filtered::<Entity, Scene>();
+            for entity in scene.iter(&self.world).collect::<Vec<_>>() {
+                self.world.despawn(entity);
+            }
+            for pile in [PileType::War, PileType::Player, PileType::Oppo] {
+                self.world.spawn().insert(Pile::new(pile));
+            }
+            let player = PlayerDeck::new(player.parse().unwrap());
+            let oppo = OppoDeck::new(oppo.parse().unwrap());
+            self.world.spawn().insert(player);
+            self.world.spawn().insert(oppo);
+            self.world.resource_mut::<Events<GameEvent>>().clear();
+            self.world.resource_mut::<TurnRequests>().request(Draw);
+            self.settle();
+        }
+        /// Run frames until a participant's turn, or the end of the game.
+        fn settle(&mut self) {
+            for _ in 0..10 {
+                let turn = self.turn();
+                if matches!(turn, TurnState::Player | Oppo) || self.ended().is_some() {
+                    return;
+                }
+                self.frame();
+            }
+            panic!("stuck in {:?}", self.turn());
+        }
+        /// Play `player` and `oppo` from the hands, then end the battle once
+        /// the cards are played.
+        fn battle(&mut self, player: &str, oppo: &str) {
+            type Held = (Entity, &'static Card, &'static CardOrigin);
+            let mut hands = self.world.query_filtered::<Held, HandFilter>();
+            let mut plays = Vec::new();
+            for (who, card) in [(Participant::Player, player), (Participant::Oppo, oppo)] {
+                let card: Card = card.parse().unwrap();
+                let mut hand = hands.iter(&self.world);
+                let held = hand.find(|(_, held, origin)| **held == card && origin.0 == who);
+                plays.push(PlayCard::new(held.expect("the card is in hand").0, who));
+            }
+            let mut events = self.world.resource_mut::<Events<PlayCard>>();
+            plays.into_iter().for_each(|play| events.send(play));
+            // Requests are applied at the end of the frame, entered the next
+            self.frame();
+            self.frame();
+            assert_eq!(self.turn(), CardPlayed);
+            self.world.resource_mut::<TurnRequests>().request(New);
+            self.frame();
+            self.settle();
+        }
+        /// The scores from the cards in the piles, the bonuses, and the
+        /// cards in hands and decks.
+        fn recomputed_scores(&mut self) -> Scores {
+            let world = &mut self.world;
+            let mut hands = world.query_filtered::<&Card, HandFilter>();
+            let mut piles = world.query::<&Pile>();
+            let mut player_deck = world.query::<&PlayerDeck>();
+            let mut oppo_deck = world.query::<&OppoDeck>();
+            let world = &self.world;
+            let mut pile = |which: PileType| -> i32 {
+                let pile = piles.iter(world).find(|pile| pile.which == which).unwrap();
+                let cards = pile.cards().iter().filter_map(|e| world.get::<Card>(*e));
+                cards.map(Card::value_i32).sum()
+            };
+            let hands: i32 = hands.iter(world).map(Card::max_value).sum();
+            let decks = player_deck.single(world).score() + oppo_deck.single(world).score();
+            let piles = (pile(PileType::Player), pile(PileType::Oppo));
+            Scores::recompute(piles, world.resource::<ScoreBonuses>(), hands + decks)
+        }
+    }
+
+    /// Play `plays` in order through [`handle_played`] and
+    /// [`handle_turn_end`], returning the [`GameEvent`]s they sent.
+    fn scripted_world(rules: GameRules, plays: &[(Participant, &str)]) -> World {
+        let mut world = World::new();
+        world.insert_resource(rules);
+        world.init_resource::<SeedCount>();
+        world.init_resource::<TurnRequests>();
+        world.init_resource::<OppoLead>();
+        let mut scores = Scores::default();
+        let cards = plays.iter().map(|(_, card)| card.parse::<Card>().unwrap());
+        scores.deck_loaded(cards.map(|card| card.max_value()).sum());
+        world.insert_resource(scores);
+        world.init_resource::<ScoreBonuses>();
+        world.init_resource::<CoopTurn>();
+        world.init_resource::<BattleCount>();
+        world.init_resource::<Inventory>();
+        world.init_resource::<Events<PlayCard>>();
+        world.init_resource::<Events<GameEvent>>();
+        world.init_resource::<Events<BattleResolved>>();
+        world.init_resource::<Events<EffectEvent>>();
+        world.init_resource::<Events<ScoreEvent>>();
+        world.init_resource::<Events<AudioRequest>>();
+        world.init_resource::<Time>();
+        for pile in [PileType::War, PileType::Player, PileType::Oppo] {
+            world.spawn().insert(Pile::new(pile));
+        }
+        let mut stage = SystemStage::single_threaded()
+            .with_system(handle_played)
+            .with_system(handle_turn_end.after(handle_played));
+        for (who, card) in plays {
+            let card = card.parse::<Card>().unwrap();
+            let entity = world.spawn().insert_bundle((card, CardOrigin(*who))).id();
+            let mut play_events = world.resource_mut::<Events<PlayCard>>();
+            play_events.send(PlayCard::new(entity, *who));
+            // Like `TurnState::CardPlayed`, lasting at least two frames
+            stage.run(&mut world);
+            stage.run(&mut world);
+        }
+        world
+    }
+
+    fn scripted_game(rules: GameRules, plays: &[(Participant, &str)]) -> Vec<GameEvent> {
+        let world = scripted_world(rules, plays);
+        let events = world.resource::<Events<GameEvent>>();
+        events.get_reader().iter(events).cloned().collect()
+    }
+
+    #[test]
+    fn game_events_of_a_scripted_game() {
+        use Participant::{Oppo, Player};
+        let played = |who, card: &str| GameEvent::CardPlayed { who, card: card.parse().unwrap() };
+        let plays = [
+            (Player, "5_"),
+            (Oppo, "3_"),
+            (Oppo, "6_"),
+            (Player, "2s"),
+            (Player, "7s"),
+            (Oppo, "3_"),
+        ];
+        let events = scripted_game(default(), &plays);
+        let expected = [
+            played(Player, "5_"),
+            played(Oppo, "3_"),
+            GameEvent::BattleResolved {
+                outcome: BattleOutcome::Win,
+                margin: 2,
+                rewards: vec![(Player, 8)],
+            },
+            played(Oppo, "6_"),
+            played(Player, "2s"),
+            GameEvent::BattleResolved {
+                outcome: BattleOutcome::Loss,
+                margin: -4,
+                rewards: vec![(Oppo, 8)],
+            },
+            played(Player, "7s"),
+            played(Oppo, "3_"),
+            GameEvent::BattleResolved {
+                outcome: BattleOutcome::Win,
+                margin: 4,
+                rewards: vec![(Player, 10)],
+            },
+            GameEvent::SeedGained(CoopPlayer::First),
+        ];
+        assert_eq!(events, expected);
+    }
+
+    #[test]
+    fn bonus_grants_name_their_cards() {
+        use Participant::{Oppo, Player};
+        use WordOfPower::{Geh, Qube};
+        let rules = GameRules { fair_game: true, ..default() };
+        let plays = [
+            (Player, "5_"),
+            (Oppo, "3_"),
+            (Player, "0z"),
+            (Oppo, "9d"),
+            (Oppo, "2_"),
+            (Player, "4s"),
+        ];
+        let fair_seed_points = rules.fair_seed_points;
+        let world = scripted_world(rules, &plays);
+        let bonuses = world.resource::<ScoreBonuses>();
+        let grants: Vec<_> = bonuses.grants().collect();
+        assert_eq!(grants.len(), 2, "the first battle has no bonus");
+
+        let combo = grants[0];
+        assert_eq!(combo.battle, 2);
+        let cards = combo.cards.clone().map(|(_, card)| card);
+        assert_eq!(cards, ["0z".parse().unwrap(), "9d".parse().unwrap()]);
+        assert_eq!(combo.player + combo.oppo, 24 + 9);
+        assert_eq!(combo.words(), [Geh, Qube]);
+        assert_eq!(bonuses.grant_of(combo.cards[1].0), Some(combo));
+
+        let seed = grants[1];
+        assert_eq!(seed.battle, 3);
+        assert_eq!(seed.player, fair_seed_points);
+        assert_eq!(seed.seed_points, fair_seed_points);
+        assert_eq!(seed.words(), [Egeq]);
+
+        let mut bonuses = ScoreBonuses::default();
+        let card = "1_".parse().unwrap();
+        for battle in 0..MAX_BONUS_GRANTS + 10 {
+            let entity = Entity::from_raw(battle as u32);
+            let mut grant = BonusGrant::new(battle, (entity, &card), (entity, &card));
+            bonuses.record(grant.clone());
+            grant.add_to_owner(Oppo, 2);
+            bonuses.record(grant);
+        }
+        assert_eq!(bonuses.grants().count(), MAX_BONUS_GRANTS);
+        assert_eq!(bonuses.grants().next().map(|g| g.battle), Some(10));
+    }
+
+    #[test]
+    fn win_conditions_of_a_scripted_game() {
+        use EndReason::{Loss, Victory};
+        use Participant::{Oppo, Player};
+        use Value::{Five, Nine, Zero};
+        use WinCondition::*;
+        let plays = [
+            (Player, "0z"),
+            (Oppo, "9_"),
+            (Oppo, "0_"),
+            (Player, "1_"),
+            (Player, "3_"),
+            (Oppo, "5_"),
+        ];
+        let mut world = scripted_world(default(), &plays);
+        let mut inputs = bevy::ecs::system::SystemState::<WinInputs>::new(&mut world);
+        let progress = inputs.get_mut(&mut world).progress();
+        assert_eq!((progress.scores.player, progress.scores.oppo), (22, 8));
+        assert_eq!(progress.scores.remaining, 0, "all cards are played");
+        assert_eq!(progress.battles, 3);
+        let check =
+            |condition: WinCondition, progress: &WinProgress| check_win(&condition, progress);
+        assert_eq!(check(ScoreGap, &progress), Some(Victory));
+        assert_eq!(check(TargetScore(20), &progress), Some(Victory));
+        assert_eq!(
+            check(CollectSet(vec![Zero, Nine, Zero]), &progress),
+            Some(Victory)
+        );
+        assert_eq!(check(CollectSet(vec![Five]), &progress), Some(Loss));
+        assert_eq!(check(SurviveTurns(3), &progress), Some(Victory));
+
+        // Earlier in a game, with points left to earn
+        let mut early = progress.clone();
+        early.scores.remaining = 20;
+        assert_eq!(check(ScoreGap, &early), None);
+        assert_eq!(check(TargetScore(30), &early), None);
+        assert_eq!(check(CollectSet(vec![Zero; 3]), &early), None);
+        assert_eq!(check(SurviveTurns(4), &early), None);
+        early.scores.oppo = 22;
+        assert_eq!(check(TargetScore(20), &early), None, "reached together");
+        assert_eq!(check(SurviveTurns(3), &early), None, "a tie");
+        let two_zeros = CollectSet(vec![Zero; 2]);
+        early.collected[1].extend([Zero, Zero]);
+        assert_eq!(check(two_zeros.clone(), &early), None, "collected together");
+        early.scores.oppo = 30;
+        assert_eq!(check(SurviveTurns(3), &early), Some(Loss), "behind");
+        early.scores.oppo = 50;
+        assert_eq!(check(two_zeros, &early), Some(Loss), "higher score");
+        assert_eq!(check(SurviveTurns(3), &early), Some(Loss), "out of reach");
+
+        // Sets not collected fall back to the score gap at the end
+        assert_eq!(check(CollectSet(vec![Zero; 3]), &progress), Some(Victory));
+        assert_eq!(check(TargetScore(30), &progress), Some(Victory));
+    }
+
+    #[test]
+    fn lost_egeq_seeds_with_the_house_rule() {
+        use Participant::{Oppo, Player};
+        let rules = GameRules { egeq_pays_on_loss: true, ..default() };
+        let events = scripted_game(rules, &[(Oppo, "6_"), (Player, "2s")]);
+        let gained = GameEvent::SeedGained(CoopPlayer::First);
+        assert_eq!(events.last(), Some(&gained));
+        let events = scripted_game(default(), &[(Oppo, "6_"), (Player, "2s")]);
+        assert!(!events.contains(&gained));
+    }
+
+    #[test]
+    fn duplicate_plays_are_ignored() {
+        use Participant::{Oppo, Player};
+        let mut world = scripted_world(default(), &[(Player, "5_")]);
+        let led = world.query_filtered::<Entity, With<Card>>().single(&world);
+        let answer = world.spawn().insert("3_".parse::<Card>().unwrap()).id();
+        let mut play_events = world.resource_mut::<Events<PlayCard>>();
+        // The lead is already played, the answer is sent twice in a frame
+        play_events.send(PlayCard::new(led, Player));
+        play_events.send(PlayCard::new(answer, Oppo));
+        play_events.send(PlayCard::new(answer, Oppo));
+        let mut stage = SystemStage::single_threaded().with_system(handle_played);
+        stage.run(&mut world);
+        stage.run(&mut world);
+
+        let mut piles = world.query::<&Pile>();
+        let war = piles.iter(&world).find(|pile| pile.which == PileType::War);
+        assert_eq!(war.unwrap().cards(), [led, answer]);
+        let events = world.resource::<Events<GameEvent>>();
+        let events = events.get_reader().iter(events);
+        let plays = events.filter(|event| matches!(event, GameEvent::CardPlayed { .. }));
+        assert_eq!(plays.count(), 2, "one per card");
+    }
+
+    #[test]
+    fn upcoming_player_before_the_battle_ends() {
+        use Participant::{Oppo, Player};
+        let (alternate, winner_leads) = (InitiativeRule::Alternate, InitiativeRule::WinnerLeads);
+        // The oppo led the first battle, the player answers
+        let initiative = Initiative(Oppo);
+        assert_eq!(
+            initiative.upcoming(&TurnCount(0), winner_leads),
+            Some(Player)
+        );
+        // The player answered, they lead the next battle
+        let initiative = Initiative(Player);
+        assert_eq!(initiative.upcoming(&TurnCount(1), alternate), Some(Player));
+        assert_eq!(initiative.upcoming(&TurnCount(1), winner_leads), None);
+        assert_eq!(initiative.upcoming(&TurnCount(2), alternate), Some(Oppo));
+    }
+
+    #[test]
+    fn oppo_leads_are_announced() {
+        use std::time::Duration;
+
+        let world = scripted_world(default(), &[(Participant::Oppo, "7_")]);
+        let lead = world.resource::<OppoLead>().0;
+        assert!(lead.is_some(), "the oppo led");
+        let world = scripted_world(default(), &[(Participant::Player, "7_")]);
+        assert_eq!(world.resource::<OppoLead>().0, None);
+
+        let mut world = World::new();
+        world.insert_resource(Time::default());
+        world.init_resource::<TurnRequests>();
+        world.init_resource::<OppoLead>();
+        world.init_resource::<Events<EffectEvent>>();
+        world.init_resource::<Input<MouseButton>>();
+        world.init_resource::<GameplaySettings>();
+        world.init_resource::<GameSpeed>();
+        world.insert_resource(PilesSettled(true));
+        world.init_resource::<Events<crate::animate::AnimationFinished>>();
+        let mut stage = SystemStage::single_threaded().with_system(wait_active);
+        let mut frame_at = |world: &mut World, seconds: f32| {
+            let mut time = world.resource_mut::<Time>();
+            let startup = time.startup();
+            time.update_with_instant(startup + Duration::from_secs_f32(seconds));
+            stage.run(world);
+            world.resource_mut::<Input<MouseButton>>().clear();
+            let shown = world.resource_mut::<Events<EffectEvent>>().drain().count();
+            let requests = world.resource_mut::<TurnRequests>().0.drain(..).count();
+            (shown, requests)
+        };
+        let seven = world.spawn().insert("7_".parse::<Card>().unwrap()).id();
+
+        world.resource_mut::<OppoLead>().0 = Some(seven);
+        assert_eq!(frame_at(&mut world, 0.0), (1, 0), "announced");
+        assert!(world.get::<Emphasis>(seven).is_some());
+        assert_eq!(frame_at(&mut world, 1.0), (0, 0), "interlude and beat");
+        assert_eq!(frame_at(&mut world, 1.4), (0, 1));
+
+        assert_eq!(frame_at(&mut world, 2.0), (0, 0), "the player leads");
+        assert_eq!(frame_at(&mut world, 2.6), (0, 1));
+
+        world.resource_mut::<OppoLead>().0 = Some(seven);
+        assert_eq!(frame_at(&mut world, 3.0), (1, 0));
+        let mut mouse = world.resource_mut::<Input<MouseButton>>();
+        mouse.press(MouseButton::Left);
+        assert_eq!(frame_at(&mut world, 3.1), (0, 0), "skipped");
+        assert_eq!(frame_at(&mut world, 3.2), (0, 1));
+
+        assert_eq!(frame_at(&mut world, 4.0), (0, 0));
+        world.resource_mut::<PilesSettled>().0 = false;
+        assert_eq!(frame_at(&mut world, 4.6), (0, 0), "cards still moving");
+        world.resource_mut::<PilesSettled>().0 = true;
+        assert_eq!(frame_at(&mut world, 4.7), (0, 1));
+    }
+}
@@ -1,16 +1,21 @@
 //! Ui showing game state to player during gameplay
-use std::fmt::Write;
+use std::{collections::VecDeque, fmt::Write};
 
 use bevy::prelude::{Plugin as BevyPlugin, *};
-use bevy_debug_text_overlay::screen_print;
 use bevy_ui_build_macros::{build_ui, rect, size, style, unit};
+use enum_map::{enum_map, EnumMap};
 
 use crate::{
-    animate::Animated,
-    game_flow::{CardStats, SeedCount},
+    animate::{despawn_fading, Animated, GameSpeed},
+    cheat::{BirdEye, SleeveCard},
+    game_event::GameEvent,
+    game_flow::{BattleCount, CoopPlayer, CoopTurn, Inventory, Milestone, Scores, SeedCount},
+    lang::Strings,
     numbers::Number,
+    rules::GameRules,
+    settings::GameplaySettings,
     state::GameState,
-    war::WordOfPower,
+    war::{BattleOutcome, Value, WordOfPower},
     Participant,
 };
 
@@ -26,32 +31,208 @@ pub struct OppoScore;
 #[derive(Component, Clone)]
 struct CardEffectDescription;
 
+/// Row of [`EffectSlot`]s in the effect display, side by side.
+#[derive(Component, Clone)]
+struct EffectSlots;
+
+/// A word of power in the effect display, its image above its flavor text.
+#[derive(Component)]
+struct EffectSlot;
+
+/// Most words shown at once in the effect display, one per battle card.
+const MAX_EFFECT_SLOTS: usize = 2;
+
+/// Seconds a message stays in the effect display once fully written, before
+/// [`GameSpeed`].
+const MESSAGE_DURATION: f32 = 4.0;
+/// Characters of a message written per second, before [`GameSpeed`].
+const REVEAL_RATE: f32 = 120.0;
+
+/// Confetti pieces spawned over the player score for a [`Milestone`].
+const CONFETTI_COUNT: usize = 16;
+
+const TRINKET_COLOR: Color = Color::GOLD;
+/// Color of the bonus points descriptions, and of the stars marking the pile
+/// cards that earned them.
+pub const BONUS_COLOR: Color = Color::YELLOW;
+
+/// A message for the effect display, and how to show it.
+struct EffectMessage {
+    text: String,
+    /// Color and font size of the text, the previous ones are kept if `None`.
+    style: Option<(Color, f32)>,
+    /// Words replacing those of the effect slots, they are kept if `None`.
+    words: Option<Vec<WordOfPower>>,
+    /// See [`EffectDisplay::timeout`].
+    timeout: Option<f32>,
+}
+
+/// The message of the effect display, written a few characters at a time.
+#[derive(Default)]
+struct EffectDisplay {
+    /// Seconds before the effect display is cleared, counting from when the
+    /// message is fully written. `None` when it shows something that stays
+    /// until an [`EffectEvent::Hide`].
+    timeout: Option<f32>,
+    message: String,
+    /// Characters of the message written so far, fractional so that slow
+    /// rates still progress.
+    revealed: f32,
+    /// Timed messages waiting for the shown one to time out, oldest first.
+    queued: VecDeque<EffectMessage>,
+}
+impl EffectDisplay {
+    /// Returns `message` if it should be shown right away, otherwise queue
+    /// it until the timed message shown, and those queued before it, time
+    /// out.
+    ///
+    /// Messages that aren't timed are always shown, the queued ones are
+    /// dropped since they would hide them.
+    fn enqueue(&mut self, message: EffectMessage) -> Option<EffectMessage> {
+        if message.timeout.is_none() {
+            self.queued.clear();
+        } else if self.timeout.is_some() || !self.queued.is_empty() {
+            self.queued.push_back(message);
+            return None;
+        }
+        Some(message)
+    }
+    /// The next queued message, to show once the current one timed out.
+    fn dequeue(&mut self) -> Option<EffectMessage> {
+        self.queued.pop_front()
+    }
+    /// Start writing `message`, replacing the current one even if it isn't
+    /// fully written.
+    fn show(&mut self, message: String, timeout: Option<f32>) {
+        self.message = message;
+        self.revealed = 0.0;
+        self.timeout = timeout;
+    }
+    fn message_len(&self) -> f32 {
+        self.message.chars().count() as f32
+    }
+    fn is_revealed(&self) -> bool {
+        self.revealed >= self.message_len()
+    }
+    /// Write `chars` more characters.
+    fn reveal(&mut self, chars: f32) {
+        self.revealed = (self.revealed + chars).min(self.message_len());
+    }
+    /// Write the whole message at once.
+    fn complete(&mut self) {
+        self.revealed = self.message_len();
+    }
+    /// The part of the message written so far.
+    fn written(&self) -> &str {
+        let end = self.message.char_indices().nth(self.revealed as usize);
+        &self.message[..end.map_or(self.message.len(), |(index, _)| index)]
+    }
+    /// Count down the timeout once the message is fully written, returns
+    /// whether it just expired.
+    fn tick(&mut self, delta: f32) -> bool {
+        if !self.is_revealed() {
+            return false;
+        }
+        match self.timeout.as_mut() {
+            Some(timeout) => {
+                *timeout -= delta;
+                *timeout <= 0.0
+            }
+            None => false,
+        }
+    }
+}
+
+/// Score changes for the score ui, besides the battle points of
+/// [`GameEvent::BattleResolved`].
 pub enum ScoreEvent {
+    /// Points scored outside of a game, in the [`crate::sandbox`].
     Add(Participant, i32),
+    /// The player got points instead of a seed, see [`SeedGain::Overflow`]
+    /// and [`GameRules::fair_game`].
+    ///
+    /// [`SeedGain::Overflow`]: crate::game_flow::SeedGain::Overflow
+    SeedOverflow(i32),
     Reset,
 }
+/// The filled part of the round progress bar, see [`BattleCount::progress`].
+#[derive(Component, Clone)]
+struct RoundProgress;
+
 #[derive(Component, Clone)]
 enum UiInfo {
-    Seeds,
+    /// Battle being played and battles in the game.
+    Round,
+    /// How the game is won, see [`GameRules::win_condition`].
+    Objective,
+    /// Seeds of a [`CoopPlayer`], only the first one outside of co-op games.
+    Seeds(CoopPlayer),
+    /// Trinkets of the active [`CoopPlayer`].
+    Trinkets,
+    /// Which [`CoopPlayer`] controls the hand, only in co-op games.
+    CoopTurn,
+    /// Cards in sleeve and sleeve capacity.
+    Sleeve,
+    /// Seeds needed to distract the bird.
+    DistractionCost,
+    /// Reminds the player that [`GameplaySettings::streaming`] is on.
+    Streaming,
+}
+
+/// Meshes and materials of the [`Milestone`] confetti.
+struct ConfettiAssets {
+    quad: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+impl FromWorld for ConfettiAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let quad = meshes.add(shape::Quad::new(Vec2::splat(0.25)).into());
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let material = materials.add(StandardMaterial {
+            base_color: Participant::Player.color(),
+            unlit: true,
+            double_sided: true,
+            cull_mode: None,
+            ..default()
+        });
+        Self { quad, material }
+    }
 }
 
 struct UiAssets {
     font: Handle<Font>,
+    words: EnumMap<WordOfPower, Handle<Image>>,
+    /// Icon of the trinket counter, tinted with [`TRINKET_COLOR`].
+    trinket: Handle<Image>,
 }
 impl FromWorld for UiAssets {
     fn from_world(world: &mut World) -> Self {
         let assets = world.get_resource::<AssetServer>().unwrap();
-        Self { font: assets.load("Boogaloo-Regular.otf") }
+        Self {
+            font: assets.load("Boogaloo-Regular.otf"),
+            words: enum_map! { word => assets.load(&format!("cards/Word{word:?}.png")) },
+            trinket: assets.load("glow.png"),
+        }
     }
 }
 
-fn spawn_game_ui(mut cmds: Commands, ui_assets: Res<UiAssets>) {
+/// The seed, trinket and sleeve counters are hidden in a fair game, where
+/// there is no cheating. The second seed counter and the active player only
+/// show in a co-op game.
+fn spawn_game_ui(
+    mut cmds: Commands,
+    ui_assets: Res<UiAssets>,
+    strings: Res<Strings>,
+    rules: Res<GameRules>,
+) {
     let text_sized = |content: &str, font_size| {
         let color = Color::NAVY;
         let style = TextStyle { color, font: ui_assets.font.clone(), font_size };
         TextBundle::from_section(content, style)
     };
     let text = |content: &str| text_sized(content, 60.0);
+    let label = |key, font_size| text_sized(strings.get(key), font_size);
     let node = NodeBundle {
         color: Color::NONE.into(),
         style: style! {
@@ -61,6 +242,13 @@ fn spawn_game_ui(mut cmds: Commands, ui_assets: Res<UiAssets>) {
         },
         ..default()
     };
+    let cheat_display = if rules.fair_game { Display::None } else { Display::Flex };
+    let trinket_icon = ImageBundle {
+        image: ui_assets.trinket.clone().into(),
+        color: TRINKET_COLOR.into(),
+        style: style! { size: size!(50 px, 50 px), },
+        ..default()
+    };
     build_ui! {
         #[cmd(cmds)]
         node{ size: size!(100 pct, 100 pct) }[; UiRoot](
@@ -72,6 +260,7 @@ fn spawn_game_ui(mut cmds: Commands, ui_assets: Res<UiAssets>) {
                 flex_direction: FlexDirection::Column,
                 align_items: AlignItems::Center
             }[; Name::new("game ui effect display")](
+                node[; Name::new("Effect slots"), EffectSlots],
                 entity[text(""); CardEffectDescription]
             ),
             node{
@@ -79,10 +268,37 @@ fn spawn_game_ui(mut cmds: Commands, ui_assets: Res<UiAssets>) {
                 flex_direction: FlexDirection::ColumnReverse,
                 align_items: AlignItems::FlexEnd
             }[;Name::new("game ui right column")](
-                node[; Name::new("Seeds")](
-                    node[text_sized("(space to use)", 30.0);],
-                    node[text("Seeds:");],
-                    node[text("0"); UiInfo::Seeds]
+                node{ display: cheat_display }[; Name::new("Seeds")](
+                    node[label("game.seed_hint", 30.0);],
+                    node[label("game.seeds", 60.0);],
+                    node[text("0"); UiInfo::Seeds(CoopPlayer::First)],
+                    if (rules.coop) {
+                        node[text("0"); UiInfo::Seeds(CoopPlayer::Second)]
+                    }
+                ),
+                node{ display: cheat_display }[; Name::new("Trinkets")](
+                    node[label("game.trinket_hint", 30.0);],
+                    entity[trinket_icon;],
+                    node[text("0"); UiInfo::Trinkets]
+                ),
+                node{ display: cheat_display }[; Name::new("Sleeve")](
+                    node[label("game.sleeve", 60.0);],
+                    node[text("0/0"); UiInfo::Sleeve],
+                    node[text_sized("", 30.0); UiInfo::DistractionCost]
+                ),
+                if (rules.coop) {
+                    node[text_sized("", 40.0); Name::new("Coop turn"), UiInfo::CoopTurn]
+                },
+                node{
+                    flex_direction: FlexDirection::ColumnReverse,
+                    align_items: AlignItems::FlexEnd
+                }[; Name::new("Round")](
+                    node[text_sized("", 30.0); Name::new("Streaming indicator"), UiInfo::Streaming],
+                    node[text_sized("", 30.0); Name::new("Objective"), UiInfo::Objective],
+                    node[text_sized("", 40.0); UiInfo::Round],
+                    node{ size: size!(200 px, 10 px) }[; UiColor(Color::rgba(0.1, 0.1, 0.1, 0.6))](
+                        node{ size: size!(0 pct, 100 pct) }[; UiColor(Color::NAVY), RoundProgress]
+                    )
                 ),
             )
         )
@@ -96,41 +312,321 @@ fn despawn_game_ui(mut cmds: Commands, query: Query<Entity, With<UiRoot>>) {
 #[derive(PartialEq)]
 pub enum EffectEvent {
     Show(WordOfPower),
+    /// Show the words of the cards in the war pile, side by side.
+    PlayedWords(Vec<WordOfPower>),
     Hide,
     UseSeed,
+    /// The player distracted the bird with a trinket, see [`Inventory`].
+    UseTrinket,
+    /// The player won a trinket, see [`earns_trinket`].
+    ///
+    /// [`earns_trinket`]: crate::game_flow::BattleResolved::earns_trinket
+    TrinketEarned,
     EndCheat,
+    /// The bird almost caught the player sleeving a card.
+    BirdSuspicious,
+    /// The oppo deck can earn that many more points than the player deck,
+    /// see [`crate::deck_reveal::DeckHandicap`].
+    DeckHandicap(i32),
+    /// The bird caught the player in a previous game, see [`BirdMemory`].
+    ///
+    /// [`BirdMemory`]: crate::cheat::BirdMemory
+    BirdRemembers,
+    /// Describe the order the war pile cards were played in.
+    PlayOrder(String),
+    /// Describe the battle in which an inspected pile card earned bonus
+    /// points, see [`crate::game_flow::BonusGrant`].
+    BonusGrant(String),
+    /// The player got points rather than a seed, having too many already.
+    SeedOverflow,
+    /// The player got points rather than a seed, in a fair game.
+    FairSeed,
+    /// The player glimpsed the value of the next oppo card, see [`Glimpse`].
+    ///
+    /// [`Glimpse`]: crate::game_flow::Glimpse
+    Glimpse(i32),
+    /// The player reached a score milestone, see [`Milestones`].
+    ///
+    /// [`Milestones`]: crate::game_flow::Milestones
+    Milestone(Milestone),
+    /// The coin flip decided who leads the first battle, see
+    /// [`crate::coin_flip`].
+    FirstLeader(Participant),
+    /// The oppo led a battle with a card of this value, announced even when
+    /// it has no word, see [`crate::game_flow`].
+    ShowPlainCard(Value),
+    /// Explain the result of a battle of the [`crate::sandbox`], `player` and
+    /// `oppo` being the points each scored.
+    BattleResult {
+        outcome: BattleOutcome,
+        player: i32,
+        oppo: i32,
+    },
+}
+impl EffectEvent {
+    /// Whether the message times out, rather than staying until replaced.
+    ///
+    /// Timed messages sent in the same frame, such as those of a battle end
+    /// ([`TrinketEarned`], [`SeedOverflow`], [`FairSeed`] and [`Glimpse`]),
+    /// are shown one after the other, see [`EffectDisplay::enqueue`].
+    ///
+    /// [`TrinketEarned`]: EffectEvent::TrinketEarned
+    /// [`SeedOverflow`]: EffectEvent::SeedOverflow
+    /// [`FairSeed`]: EffectEvent::FairSeed
+    /// [`Glimpse`]: EffectEvent::Glimpse
+    fn is_timed(&self) -> bool {
+        use EffectEvent::*;
+        match self {
+            UseSeed | UseTrinket | TrinketEarned | EndCheat | BirdSuspicious | BirdRemembers
+            | SeedOverflow | FairSeed | DeckHandicap(_) | Glimpse(_) | Milestone(_)
+            | FirstLeader(_) | PlayedWords(_) | ShowPlainCard(_) => true,
+            Show(_) | Hide | PlayOrder(_) | BonusGrant(_) | BattleResult { .. } => false,
+        }
+    }
+}
+
+/// Replace the [`EffectSlot`]s of the effect display with `words`.
+fn fill_slots(
+    cmds: &mut Commands,
+    container: Entity,
+    slots: &Query<Entity, With<EffectSlot>>,
+    words: &[WordOfPower],
+    ui_assets: &UiAssets,
+    strings: &Strings,
+) {
+    for slot in slots.iter() {
+        cmds.entity(slot).despawn_recursive();
+    }
+    let font_size = if words.len() > 1 { 45.0 } else { 60.0 };
+    let node = NodeBundle {
+        color: Color::NONE.into(),
+        style: style! {
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::Center,
+            size: size!(auto, auto),
+            margin: rect!(20 px, 0 px),
+        },
+        ..default()
+    };
+    for word in words.iter().take(MAX_EFFECT_SLOTS) {
+        let color = word.color();
+        let style = TextStyle { color, font: ui_assets.font.clone(), font_size };
+        let flavor_text = TextBundle::from_section(strings.get(word.flavor_key()), style);
+        let image = ImageBundle {
+            image: ui_assets.words[*word].clone().into(),
+            style: style! { size: size!(120 px, 120 px), },
+            ..default()
+        };
+        let slot = build_ui! {
+            #[cmd(cmds)]
+            node[; Name::new(format!("{word:?} slot")), EffectSlot](
+                entity[image;],
+                entity[flavor_text;]
+            )
+        }
+        .id();
+        cmds.entity(container).add_child(slot);
+    }
+}
+
+/// Show `message`: write its text, and replace the word slots with its words
+/// once the frame's events are handled, through `new_words`.
+fn show_message(
+    message: EffectMessage,
+    display: &mut EffectDisplay,
+    description: &mut Query<&mut Text, With<CardEffectDescription>>,
+    new_words: &mut Option<Vec<WordOfPower>>,
+) {
+    if let Some((color, font_size)) = message.style {
+        for mut text in description.iter_mut() {
+            text.sections[0].style.color = color;
+            text.sections[0].style.font_size = font_size;
+        }
+    }
+    if message.words.is_some() {
+        *new_words = message.words;
+    }
+    display.show(message.text, message.timeout);
 }
 
+/// Update the effect display, the word slots are replaced once per frame,
+/// following the last event that changed them.
+///
+/// Timed messages sent while another one shows wait for it to time out, see
+/// [`EffectDisplay::enqueue`].
 fn handle_effect_events(
+    mut cmds: Commands,
     mut events: EventReader<EffectEvent>,
+    mut display: ResMut<EffectDisplay>,
     mut description: Query<&mut Text, With<CardEffectDescription>>,
+    container: Query<Entity, With<EffectSlots>>,
+    slots: Query<Entity, With<EffectSlot>>,
+    ui_assets: Res<UiAssets>,
+    strings: Res<Strings>,
+    speed: Res<GameSpeed>,
 ) {
     use EffectEvent::*;
+    let mut new_words = None;
     for event in events.iter() {
+        let timeout = event.is_timed().then(|| speed.duration(MESSAGE_DURATION));
+        let mut message = String::new();
+        let mut style = None;
+        let mut words = None;
         match event {
-            Hide => {
-                let txt_box = &mut description.single_mut().sections[0];
-                txt_box.value.clear();
-            }
-            UseSeed | EndCheat => {
-                let txt_box = &mut description.single_mut().sections[0];
-                txt_box.style.color = Color::ANTIQUE_WHITE;
-                txt_box.style.font_size = 50.0;
-                txt_box.value.clear();
+            Hide => words = Some(Vec::new()),
+            PlayOrder(order) => {
+                style = Some((Color::ANTIQUE_WHITE, 50.0));
+                message.clone_from(order);
+                words = Some(Vec::new());
+            }
+            BonusGrant(grant) => {
+                style = Some((BONUS_COLOR, 50.0));
+                message.clone_from(grant);
+                words = Some(Vec::new());
+            }
+            UseSeed | UseTrinket | EndCheat | BirdSuspicious | BirdRemembers | SeedOverflow
+            | FairSeed => {
+                style = Some((Color::ANTIQUE_WHITE, 50.0));
                 let text = match event {
-                    UseSeed => "Used seed, now is the time to cheat!",
-                    EndCheat => "The bird is watching again!",
-                    Show(_) | Hide => "BUGBUGBUG D:",
+                    UseSeed => strings.get("effect.use_seed"),
+                    UseTrinket => strings.get("effect.use_trinket"),
+                    EndCheat => strings.get("effect.end_cheat"),
+                    BirdSuspicious => strings.get("effect.bird_suspicious"),
+                    BirdRemembers => strings.get("effect.bird_remembers"),
+                    SeedOverflow => strings.get("effect.seed_overflow"),
+                    FairSeed => strings.get("effect.fair_seed"),
+                    Show(_)
+                    | PlayedWords(_)
+                    | Hide
+                    | PlayOrder(_)
+                    | BonusGrant(_)
+                    | TrinketEarned
+                    | DeckHandicap(_)
+                    | Glimpse(_)
+                    | Milestone(_)
+                    | FirstLeader(_)
+                    | ShowPlainCard(_)
+                    | BattleResult { .. } => "BUGBUGBUG D:",
+                };
+                write!(message, "{}", text).unwrap();
+                words = Some(Vec::new());
+            }
+            TrinketEarned => {
+                style = Some((TRINKET_COLOR, 50.0));
+                message.push_str(strings.get("effect.trinket_earned"));
+                words = Some(Vec::new());
+            }
+            DeckHandicap(points) => {
+                style = Some((Participant::Oppo.color(), 50.0));
+                let points = points.to_string();
+                let args = [("points", points.as_str())];
+                message.push_str(&strings.format("effect.deck_handicap", &args));
+                words = Some(Vec::new());
+            }
+            Glimpse(value) => {
+                style = Some((Participant::Oppo.color(), 50.0));
+                let value = value.to_string();
+                let args = [("value", value.as_str())];
+                message.push_str(&strings.format("effect.glimpse", &args));
+                words = Some(Vec::new());
+            }
+            Milestone(milestone) => {
+                style = Some((Color::GOLD, 60.0));
+                message.push_str(strings.get(milestone.message_key()));
+                words = Some(Vec::new());
+            }
+            FirstLeader(leader) => {
+                style = Some((leader.color(), 60.0));
+                let key = match leader {
+                    Participant::Player => "coin.player_leads",
+                    Participant::Oppo => "coin.oppo_leads",
                 };
-                write!(txt_box.value, "{}", text).unwrap();
+                message.push_str(strings.get(key));
+                words = Some(Vec::new());
             }
-            Show(word) => {
-                let txt_box = &mut description.single_mut().sections[0];
-                txt_box.style.color = word.color();
-                txt_box.style.font_size = 60.0;
-                txt_box.value.clear();
-                write!(txt_box.value, "{}", word.flavor_text()).unwrap();
+            // Keeps the words of the card, if any
+            ShowPlainCard(value) => {
+                style = Some((Participant::Oppo.color(), 50.0));
+                let value = (*value as i32).to_string();
+                let args = [("value", value.as_str())];
+                message.push_str(&strings.format("effect.oppo_plays", &args));
             }
+            // Keeps the words, they explain the points
+            BattleResult { outcome, player, oppo } => {
+                let (color, key) = match outcome {
+                    BattleOutcome::Win => (Participant::Player.color(), "sandbox.win"),
+                    BattleOutcome::Loss => (Participant::Oppo.color(), "sandbox.loss"),
+                    BattleOutcome::Tie => (Color::ANTIQUE_WHITE, "sandbox.tie"),
+                };
+                style = Some((color, 50.0));
+                let (player, oppo) = (player.to_string(), oppo.to_string());
+                let args = [("player", player.as_str()), ("oppo", oppo.as_str())];
+                message.push_str(&strings.format(key, &args));
+            }
+            Show(word) => words = Some(vec![*word]),
+            PlayedWords(played) => words = Some(played.clone()),
+        }
+        let message = EffectMessage { text: message, style, words, timeout };
+        if let Some(message) = display.enqueue(message) {
+            show_message(message, &mut display, &mut description, &mut new_words);
+        }
+    }
+    if let (Some(words), Ok(container)) = (new_words, container.get_single()) {
+        fill_slots(&mut cmds, container, &slots, &words, &ui_assets, &strings);
+    }
+}
+
+/// Clear the effect display once its [`EffectDisplay`] timeout passed, or
+/// show the next queued message.
+fn hide_effects(
+    mut cmds: Commands,
+    mut display: ResMut<EffectDisplay>,
+    mut description: Query<&mut Text, With<CardEffectDescription>>,
+    container: Query<Entity, With<EffectSlots>>,
+    slots: Query<Entity, With<EffectSlot>>,
+    ui_assets: Res<UiAssets>,
+    strings: Res<Strings>,
+    time: Res<Time>,
+) {
+    if display.timeout.is_none() || !display.is_revealed() {
+        return;
+    }
+    if !display.tick(time.delta_seconds()) {
+        return;
+    }
+    let mut new_words = Some(Vec::new());
+    match display.dequeue() {
+        Some(message) => show_message(message, &mut display, &mut description, &mut new_words),
+        None => display.show(String::new(), None),
+    }
+    if let (Some(words), Ok(container)) = (new_words, container.get_single()) {
+        fill_slots(&mut cmds, container, &slots, &words, &ui_assets, &strings);
+    }
+}
+
+/// Write the [`EffectDisplay`] message a few characters per frame, all at
+/// once when the player clicks.
+fn reveal_effect_text(
+    mut display: ResMut<EffectDisplay>,
+    mut description: Query<&mut Text, With<CardEffectDescription>>,
+    mouse: Res<Input<MouseButton>>,
+    time: Res<Time>,
+    speed: Res<GameSpeed>,
+) {
+    if display.is_revealed() {
+        // The text still needs updating on the frame it's fully written
+        if !display.is_changed() {
+            return;
+        }
+    } else if mouse.just_pressed(MouseButton::Left) {
+        display.complete();
+    } else {
+        display.reveal(speed.rate(REVEAL_RATE) * time.delta_seconds());
+    }
+    for mut text in description.iter_mut() {
+        let written = display.written();
+        if text.sections[0].value != written {
+            text.sections[0].value = written.to_owned();
         }
     }
 }
@@ -140,30 +636,42 @@ fn update_score(
     mut player_score: Query<ScoreComponents, With<PlayerScore>>,
     mut oppo_score: Query<ScoreComponents, (With<OppoScore>, Without<PlayerScore>)>,
     mut events: EventReader<ScoreEvent>,
+    mut game_events: EventReader<GameEvent>,
     mut cmds: Commands,
-    stats: CardStats,
+    scores: Res<Scores>,
 ) {
+    let mut rise_and_fade = |entity, additional, color| {
+        cmds.entity(entity).with_children(|cmds| {
+            cmds.spawn_bundle((
+                Animated::RiseAndFade { duration: 1.2, direction: Vec3::Y * 2.5 },
+                Number::new(additional, color),
+            ))
+            .insert_bundle(SpatialBundle {
+                transform: Transform::from_translation(Vec3::Y * 2.),
+                ..default()
+            });
+        });
+    };
+    // Battle points come from the game events, the sandbox sends them as
+    // `ScoreEvent::Add`
+    let mut additions = Vec::new();
+    for event in game_events.iter() {
+        if let GameEvent::BattleResolved { rewards, .. } = event {
+            additions.extend_from_slice(rewards);
+        }
+    }
     for event in events.iter() {
         match event {
             ScoreEvent::Add(participant, additional) => {
-                let ((entity, mut number), score) = match *participant {
-                    Participant::Oppo => (oppo_score.single_mut(), stats.oppo_score()),
-                    Participant::Player => (player_score.single_mut(), stats.player_score()),
-                };
-                number.value = score;
-                cmds.entity(entity).with_children(|cmds| {
-                    cmds.spawn_bundle((
-                        Animated::RiseAndFade { duration: 1.2, direction: Vec3::Y * 2.5 },
-                        Number::new(*additional, participant.color()),
-                    ))
-                    .insert_bundle(SpatialBundle {
-                        transform: Transform::from_translation(Vec3::Y * 2.),
-                        ..default()
-                    });
-                });
+                additions.push((*participant, *additional));
+            }
+            ScoreEvent::SeedOverflow(additional) => {
+                let (entity, mut number) = player_score.single_mut();
+                number.value = scores.player();
+                rise_and_fade(entity, *additional, Color::GOLD);
             }
             ScoreEvent::Reset => {
-                screen_print!("Resetting scores!");
+                debug_print!("Resetting scores!");
                 let (_, mut score) = oppo_score.single_mut();
                 score.value = 0;
                 let (_, mut score) = player_score.single_mut();
@@ -171,43 +679,303 @@ fn update_score(
             }
         }
     }
+    for (participant, additional) in additions {
+        let ((entity, mut number), score) = match participant {
+            Participant::Oppo => (oppo_score.single_mut(), scores.oppo()),
+            Participant::Player => (player_score.single_mut(), scores.player()),
+        };
+        number.value = score;
+        rise_and_fade(entity, additional, participant.color());
+    }
+}
+
+/// Burst confetti out of the player score when a [`Milestone`] is reached,
+/// unless the player asked for reduced motion.
+fn spawn_confetti(
+    mut cmds: Commands,
+    mut events: EventReader<EffectEvent>,
+    player_score: Query<Entity, With<PlayerScore>>,
+    confetti: Res<ConfettiAssets>,
+    settings: Res<GameplaySettings>,
+) {
+    let is_milestone = |event: &&EffectEvent| matches!(event, EffectEvent::Milestone(_));
+    let milestones = events.iter().filter(is_milestone).count();
+    if milestones == 0 || settings.reduce_motion {
+        return;
+    }
+    let score = match player_score.get_single() {
+        Ok(score) => score,
+        Err(_) => return,
+    };
+    cmds.entity(score).with_children(|cmds| {
+        for _ in 0..CONFETTI_COUNT {
+            let spread = Vec3::new(fastrand::f32() * 4.0 - 2.0, 0.0, fastrand::f32() - 0.5);
+            let direction = Vec3::Y * (2.0 + fastrand::f32() * 2.0) + spread;
+            let rotation = Quat::from_rotation_z(fastrand::f32() * std::f32::consts::TAU);
+            cmds.spawn_bundle(PbrBundle {
+                mesh: confetti.quad.clone(),
+                material: confetti.material.clone(),
+                transform: Transform::from_translation(Vec3::Y * 2.).with_rotation(rotation),
+                ..default()
+            })
+            .insert_bundle((
+                Animated::RiseAndFade { duration: 1.2, direction },
+                Name::new("Confetti"),
+            ));
+        }
+    });
 }
 
 fn update_game_ui(
     mut ui_infos: Query<(&mut Text, &UiInfo)>,
     player_seeds: Res<SeedCount>,
-    stats: CardStats,
+    inventory: Res<Inventory>,
+    coop_turn: Res<CoopTurn>,
+    bird: Res<BirdEye>,
+    rules: Res<GameRules>,
+    sleeve_cards: Query<(), With<SleeveCard>>,
+    scores: Res<Scores>,
+    battles: Res<BattleCount>,
+    gameplay: Res<GameplaySettings>,
+    strings: Res<Strings>,
 ) {
-    screen_print!("values left: {}", stats.remaining_score());
+    debug_print!("values left: {}", scores.remaining());
     for (mut text, ui_info) in ui_infos.iter_mut() {
         let txt = &mut text.sections[0].value;
         txt.clear();
         match ui_info {
-            UiInfo::Seeds => {
-                let seeds = player_seeds.count();
-                write!(txt, "{seeds}").unwrap();
+            UiInfo::Round if battles.total() == 0 => {}
+            UiInfo::Round => {
+                let (current, total) = (battles.current().to_string(), battles.total().to_string());
+                let args = [("current", current.as_str()), ("total", total.as_str())];
+                txt.push_str(&strings.format("game.round", &args));
+            }
+            UiInfo::Objective => {
+                let objective = rules.win_condition.describe(&strings);
+                let args = [("objective", objective.as_str())];
+                txt.push_str(&strings.format("game.objective", &args));
+            }
+            UiInfo::Seeds(who) if rules.coop => {
+                let (seeds, cap) = (player_seeds.count(*who), player_seeds.cap());
+                let (player, count) = (strings.get(who.name_key()), format!("{seeds}/{cap}"));
+                let args = [("player", player), ("count", count.as_str())];
+                txt.push_str(&strings.format("coop.seeds", &args));
+            }
+            UiInfo::Seeds(who) => {
+                let (seeds, cap) = (player_seeds.count(*who), player_seeds.cap());
+                write!(txt, "{seeds}/{cap}").unwrap();
+            }
+            UiInfo::Trinkets => {
+                write!(txt, "{}", inventory.trinkets(coop_turn.active())).unwrap();
+            }
+            UiInfo::CoopTurn => {
+                let player = strings.get(coop_turn.active().name_key());
+                txt.push_str(&strings.format("coop.active", &[("player", player)]));
+            }
+            UiInfo::Sleeve => {
+                let sleeved = sleeve_cards.iter().count();
+                write!(txt, "{sleeved}/{}", rules.sleeve_capacity).unwrap();
+            }
+            UiInfo::DistractionCost => {
+                let cost = bird.distraction_cost(&rules);
+                let key = if cost == 1 {
+                    "game.distract_one"
+                } else {
+                    "game.distract_many"
+                };
+                txt.push_str(&strings.format(key, &[("cost", cost.to_string().as_str())]));
             }
+            UiInfo::Streaming if gameplay.streaming => txt.push_str(strings.get("game.streaming")),
+            UiInfo::Streaming => {}
         }
     }
 }
+fn update_round_progress(
+    battles: Res<BattleCount>,
+    mut bars: Query<&mut Style, With<RoundProgress>>,
+) {
+    if !battles.is_changed() {
+        return;
+    }
+    for mut style in bars.iter_mut() {
+        style.size.width = Val::Percent(battles.progress() * 100.0);
+    }
+}
+
+fn reset_effect_display(mut display: ResMut<EffectDisplay>) {
+    *display = EffectDisplay::default();
+}
 fn reset_scores(mut events: EventWriter<ScoreEvent>) {
     events.send(ScoreEvent::Reset);
 }
 
-pub struct Plugin(pub GameState);
+/// The game ui of all the `states` with a table.
+pub struct Plugin(pub &'static [GameState]);
 impl BevyPlugin for Plugin {
     fn build(&self, app: &mut App) {
         use crate::system_helper::EasySystemSetCtor;
         app.init_resource::<UiAssets>()
+            .init_resource::<ConfettiAssets>()
+            .init_resource::<EffectDisplay>()
             .add_event::<EffectEvent>()
             .add_event::<ScoreEvent>()
-            .add_system_set(self.0.on_enter(spawn_game_ui).with_system(reset_scores))
-            .add_system(update_score)
-            .add_system_set(
-                self.0
-                    .on_update(update_game_ui)
-                    .with_system(handle_effect_events),
-            )
-            .add_system_set(self.0.on_exit(despawn_game_ui));
+            .add_system(update_score);
+        for &state in self.0 {
+            app.add_system_set(state.on_enter(spawn_game_ui).with_system(reset_scores))
+                .add_system_set(
+                    state
+                        .on_update(update_game_ui)
+                        .with_system(update_round_progress)
+                        .with_system(handle_effect_events)
+                        .with_system(spawn_confetti)
+                        .with_system(hide_effects.after(handle_effect_events))
+                        .with_system(reveal_effect_text.after(hide_effects)),
+                )
+                .add_system_set(
+                    state
+                        .on_exit(despawn_game_ui.after(despawn_fading))
+                        .with_system(despawn_fading)
+                        .with_system(reset_effect_display),
+                );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_are_written_progressively() {
+        let mut display = EffectDisplay::default();
+        assert!(display.is_revealed(), "nothing to write");
+        display.show("Révélé!".to_owned(), Some(1.0));
+        assert_eq!(display.written(), "");
+        display.reveal(0.5);
+        assert_eq!(display.written(), "", "only whole characters are written");
+        display.reveal(1.5);
+        assert_eq!(display.written(), "Ré");
+        display.reveal(100.0);
+        assert_eq!(display.written(), "Révélé!");
+        assert!(display.is_revealed());
+    }
+
+    #[test]
+    fn new_messages_preempt_the_current_one() {
+        let mut display = EffectDisplay::default();
+        display.show("First message".to_owned(), Some(1.0));
+        display.reveal(5.0);
+        display.show("Second".to_owned(), None);
+        assert_eq!(display.written(), "");
+        assert_eq!(display.timeout, None);
+        display.complete();
+        assert_eq!(display.written(), "Second");
+    }
+
+    #[test]
+    fn timed_messages_wait_their_turn() {
+        let mut display = EffectDisplay::default();
+        let message = |text: &str, timeout| {
+            let text = text.to_owned();
+            EffectMessage { text, style: None, words: None, timeout }
+        };
+        let show = |display: &mut EffectDisplay, shown: Option<EffectMessage>| {
+            let shown = shown.expect("a message to show");
+            display.show(shown.text, shown.timeout);
+        };
+        let trinket = display.enqueue(message("Trinket", Some(1.0)));
+        show(&mut display, trinket);
+        assert!(display.enqueue(message("Glimpse", Some(1.0))).is_none());
+        assert!(display.enqueue(message("Seed", Some(1.0))).is_none());
+        assert_eq!(display.written(), "", "the first one is still written");
+
+        display.complete();
+        assert!(display.tick(1.0));
+        let glimpse = display.dequeue();
+        show(&mut display, glimpse);
+        display.complete();
+        assert_eq!(display.written(), "Glimpse");
+        let late = display.enqueue(message("Late", Some(1.0)));
+        assert!(late.is_none(), "behind the seed message");
+
+        let hover = display.enqueue(message("", None));
+        show(&mut display, hover);
+        assert!(
+            display.dequeue().is_none(),
+            "hovers replace queued messages"
+        );
+        assert!(display.enqueue(message("Next", Some(1.0))).is_some());
+    }
+
+    #[test]
+    fn battle_end_messages_are_queued() {
+        use EffectEvent::*;
+        let battle_end = [TrinketEarned, SeedOverflow, FairSeed, Glimpse(7)];
+        let mut display = EffectDisplay::default();
+        let shown = battle_end.iter().filter_map(|event| {
+            let timeout = event.is_timed().then(|| 1.0);
+            let text = String::new();
+            let shown = display.enqueue(EffectMessage { text, style: None, words: None, timeout });
+            shown.map(|shown| display.show(shown.text, shown.timeout))
+        });
+        assert_eq!(shown.count(), 1, "the others wait for the first one");
+        assert_eq!(display.queued.len(), battle_end.len() - 1);
+    }
+
+    #[test]
+    fn same_frame_messages_show_in_turn() {
+        use std::time::Duration;
+
+        use crate::lang::Language;
+
+        let strings = Strings::new(Language::English);
+        let mut world = World::new();
+        world.init_resource::<Events<EffectEvent>>();
+        world.init_resource::<EffectDisplay>();
+        world.init_resource::<GameSpeed>();
+        world.init_resource::<Time>();
+        world.insert_resource(Strings::new(Language::English));
+        world.insert_resource(UiAssets {
+            font: default(),
+            words: enum_map! { _ => default() },
+            trinket: default(),
+        });
+        let text = Text::from_section("", TextStyle::default());
+        world.spawn().insert_bundle((text, CardEffectDescription));
+        let mut stage = SystemStage::single_threaded()
+            .with_system(handle_effect_events)
+            .with_system(hide_effects.after(handle_effect_events));
+        let mut written = |world: &mut World, seconds: f32| {
+            let mut time = world.resource_mut::<Time>();
+            let startup = time.startup();
+            time.update_with_instant(startup + Duration::from_secs_f32(seconds));
+            stage.run(world);
+            let mut display = world.resource_mut::<EffectDisplay>();
+            display.complete();
+            display.written().to_owned()
+        };
+
+        let mut events = world.resource_mut::<Events<EffectEvent>>();
+        events.send(EffectEvent::TrinketEarned);
+        events.send(EffectEvent::Glimpse(7));
+        let trinket = strings.get("effect.trinket_earned");
+        assert_eq!(written(&mut world, 0.0), trinket);
+        assert_eq!(written(&mut world, 1.0), trinket, "still showing");
+        let glimpse = strings.format("effect.glimpse", &[("value", "7")]);
+        assert_eq!(written(&mut world, 1.0 + MESSAGE_DURATION), glimpse);
+    }
+
+    #[test]
+    fn timeout_counts_once_revealed() {
+        let mut display = EffectDisplay::default();
+        display.show("Hello".to_owned(), Some(1.0));
+        assert!(!display.tick(2.0), "still writing");
+        assert_eq!(display.timeout, Some(1.0));
+        display.complete();
+        assert!(!display.tick(0.6));
+        assert!(display.tick(0.6));
+        display.show("Stays".to_owned(), None);
+        display.complete();
+        assert!(!display.tick(10.0));
     }
 }
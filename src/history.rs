@@ -0,0 +1,265 @@
+//! Archive of completed games, persisted between game sessions.
+//!
+//! Each completed game appends a [`GameRecord`] to [`HISTORY_FILE`], one JSON
+//! object per line. Fields this version doesn't know about are ignored, so
+//! that history written by newer versions can still be read. The file keeps
+//! at most [`HISTORY_CAP`] games, the oldest ones are dropped first.
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    deck_reveal::DeckHandicap,
+    game_event::GameEvent,
+    game_flow::{BattleCount, Scores},
+    persist,
+    rules::GameRules,
+    EndReason,
+};
+
+const HISTORY_FILE: &str = "game_history.jsonl";
+/// Most games kept in [`HISTORY_FILE`].
+const HISTORY_CAP: usize = 200;
+
+/// A completed game.
+///
+/// Games recorded before fair games existed weren't fair, and had no
+/// recorded handicap.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct GameRecord {
+    /// When the game ended, in seconds since the unix epoch.
+    pub date: u64,
+    pub end: EndReason,
+    pub player_score: i32,
+    pub oppo_score: i32,
+    /// Rounds played, that is battles resolved, see [`BattleCount`].
+    pub turns: usize,
+    /// The game was played without cheating, see [`GameRules::fair_game`].
+    #[serde(default)]
+    pub fair_game: bool,
+    /// How many more points the oppo deck could earn, 0 if it wasn't a
+    /// [`DeckHandicap`].
+    #[serde(default)]
+    pub deck_handicap: i32,
+}
+impl GameRecord {
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("game records are plain data")
+    }
+    /// Read a line of [`HISTORY_FILE`], `None` if a known field is missing
+    /// or invalid.
+    fn parse(line: &str) -> Option<Self> {
+        serde_json::from_str(line).ok()
+    }
+    /// The UTC date, as `YYYY-MM-DD hh:mm`.
+    pub fn date_text(&self) -> String {
+        let days = (self.date / 86_400) as i64;
+        let (hours, minutes) = (self.date % 86_400 / 3600, self.date % 3600 / 60);
+        let (year, month, day) = civil_from_days(days);
+        format!("{year}-{month:02}-{day:02} {hours:02}:{minutes:02}")
+    }
+    /// Key of the text describing how the game ended.
+    pub fn end_key(&self) -> &'static str {
+        match self.end {
+            EndReason::Victory => "history.end.victory",
+            EndReason::Loss => "history.end.loss",
+            EndReason::CaughtCheating => "history.end.caught_cheating",
+            EndReason::Conceded => "history.end.conceded",
+        }
+    }
+}
+
+/// Year, month and day of the `days`th day since the unix epoch.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+    (year, month, day)
+}
+
+/// `text` without its oldest lines, if it has more than `cap` lines.
+fn prune(text: &str, cap: usize) -> Option<String> {
+    let lines: Vec<_> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    let excess = lines.len().checked_sub(cap).filter(|excess| *excess != 0)?;
+    let kept = lines[excess..].iter().map(|line| format!("{line}\n"));
+    Some(kept.collect())
+}
+
+/// Seconds since the unix epoch.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn now() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH);
+    since_epoch.map_or(0, |duration| duration.as_secs())
+}
+
+/// Seconds since the unix epoch.
+#[cfg(target_arch = "wasm32")]
+pub fn now() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+/// Completed games, oldest first.
+#[derive(Default)]
+pub struct History(Vec<GameRecord>);
+impl History {
+    fn load() -> Self {
+        match persist::read(HISTORY_FILE) {
+            Ok(text) => Self(text.lines().filter_map(GameRecord::parse).collect()),
+            Err(err) => {
+                warn!("Starting with empty game history: {err:#}");
+                Self::default()
+            }
+        }
+    }
+    fn append(&mut self, record: GameRecord) -> anyhow::Result<()> {
+        persist::append_line(HISTORY_FILE, &record.to_json())?;
+        self.0.push(record);
+        if let Some(pruned) = prune(&persist::read(HISTORY_FILE)?, HISTORY_CAP) {
+            persist::write(HISTORY_FILE, &pruned)?;
+        }
+        let excess = self.0.len().saturating_sub(HISTORY_CAP);
+        self.0.drain(..excess);
+        Ok(())
+    }
+    /// The last `count` games, most recent first.
+    pub fn latest(&self, count: usize) -> impl Iterator<Item = &GameRecord> {
+        self.0.iter().rev().take(count)
+    }
+}
+
+fn record_game(
+    mut history: ResMut<History>,
+    mut events: EventReader<GameEvent>,
+    scores: Res<Scores>,
+    battles: Res<BattleCount>,
+    rules: Res<GameRules>,
+    deck_handicap: Res<DeckHandicap>,
+) {
+    for end in events.iter().filter_map(GameEvent::end) {
+        let record = GameRecord {
+            date: now(),
+            end,
+            player_score: scores.player(),
+            oppo_score: scores.oppo(),
+            turns: battles.resolved(),
+            fair_game: rules.fair_game,
+            deck_handicap: deck_handicap.0.unwrap_or(0),
+        };
+        if let Err(err) = history.append(record) {
+            warn!("Couldn't save the game in the history: {err:#}");
+        }
+    }
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        // After the gameover handler, so that the game is recorded before
+        // leaving it resets the scores.
+        app.insert_resource(History::load())
+            .add_system(record_game.after("gameover"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> GameRecord {
+        GameRecord {
+            date: 1_700_000_000,
+            end: EndReason::CaughtCheating,
+            player_score: 23,
+            oppo_score: -4,
+            turns: 9,
+            fair_game: false,
+            deck_handicap: 0,
+        }
+    }
+
+    #[test]
+    fn record_roundtrip() {
+        let record = record();
+        assert_eq!(GameRecord::parse(&record.to_json()), Some(record));
+    }
+
+    #[test]
+    fn parse_ignores_unknown_fields() {
+        let line = r#" { "turns": 9, "mood": {"oppo": [1, "}"]}, "end": "caught_cheating",
+            "player_score": 23, "note": "a \"quoted\" word", "oppo_score": -4,
+            "date": 1700000000, "seed": null } "#;
+        assert_eq!(GameRecord::parse(line), Some(record()));
+    }
+
+    #[test]
+    fn fair_game_is_optional() {
+        let fair = GameRecord { fair_game: true, ..record() };
+        assert_eq!(GameRecord::parse(&fair.to_json()), Some(fair));
+        let before_fair_games = record().to_json().replace(r#","fair_game":false"#, "");
+        assert!(!before_fair_games.contains("fair_game"));
+        assert_eq!(GameRecord::parse(&before_fair_games), Some(record()));
+        let bad_flag = record().to_json().replace("false", "maybe");
+        assert_eq!(GameRecord::parse(&bad_flag), None);
+    }
+
+    #[test]
+    fn deck_handicap_is_optional() {
+        let stacked = GameRecord { deck_handicap: 23, ..record() };
+        assert_eq!(GameRecord::parse(&stacked.to_json()), Some(stacked));
+        let before_handicaps = record().to_json().replace(r#","deck_handicap":0"#, "");
+        assert!(!before_handicaps.contains("deck_handicap"));
+        assert_eq!(GameRecord::parse(&before_handicaps), Some(record()));
+    }
+
+    #[test]
+    fn parse_rejects_incomplete_records() {
+        assert_eq!(GameRecord::parse(""), None);
+        assert_eq!(GameRecord::parse("{}"), None);
+        assert_eq!(GameRecord::parse(r#"{"date":1,"end":"victory"}"#), None);
+        let bad_end = record().to_json().replace("caught_cheating", "timeout");
+        assert_eq!(GameRecord::parse(&bad_end), None);
+        let truncated = record().to_json().replace('}', "");
+        assert_eq!(GameRecord::parse(&truncated), None);
+    }
+
+    #[test]
+    fn end_reasons_are_stored_by_name() {
+        use EndReason::*;
+        for end in [Victory, Loss, CaughtCheating, Conceded] {
+            let json = serde_json::to_string(&end).unwrap();
+            assert_eq!(json, format!("\"{}\"", end.name()));
+        }
+    }
+
+    #[test]
+    fn dates_are_utc() {
+        assert_eq!(record().date_text(), "2023-11-14 22:13");
+        let epoch = GameRecord { date: 0, ..record() };
+        assert_eq!(epoch.date_text(), "1970-01-01 00:00");
+        let leap_day = GameRecord { date: 951_782_400, ..record() };
+        assert_eq!(leap_day.date_text(), "2000-02-29 00:00");
+    }
+
+    #[test]
+    fn prune_drops_oldest_lines() {
+        assert_eq!(prune("a\nb\n", 3), None);
+        assert_eq!(prune("a\nb\nc\n", 3), None);
+        assert_eq!(prune("a\nb\n\nc\nd\n", 3), Some("b\nc\nd\n".to_owned()));
+    }
+}
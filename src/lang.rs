@@ -0,0 +1,234 @@
+//! Translations of the player-facing text.
+//!
+//! Each [`Language`] has a ron map of keys to text in `assets/lang/<code>.ron`,
+//! embedded in the game so that text is available from the first frame. The
+//! [`Strings`] resource holds the table of the selected language, keys
+//! missing from it fall back to English.
+//!
+//! Text that never changes once spawned should have a [`Localized`]
+//! component, so that it follows language changes.
+use std::collections::BTreeMap;
+
+use bevy::prelude::{Plugin as BevyPlugin, *};
+
+const ENGLISH: &str = include_str!("../assets/lang/en.ron");
+const FRENCH: &str = include_str!("../assets/lang/fr.ron");
+
+/// The text of a language, by key.
+type Table = BTreeMap<String, String>;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Language {
+    English,
+    French,
+}
+impl Default for Language {
+    fn default() -> Self {
+        Language::English
+    }
+}
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::French];
+
+    /// Name of the language file, also used to persist the setting.
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::English => "en",
+            Language::French => "fr",
+        }
+    }
+    /// The language name, in that language.
+    pub fn name(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::French => "Français",
+        }
+    }
+    /// The language after this one, for cycling through them in the menu.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|l| *l == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+    fn source(self) -> &'static str {
+        match self {
+            Language::English => ENGLISH,
+            Language::French => FRENCH,
+        }
+    }
+    /// The text of this language, empty if its file is broken.
+    fn table(self) -> Table {
+        ron::de::from_str(self.source()).unwrap_or_else(|err| {
+            error!("Can't read the {} text: {err}", self.name());
+            Table::new()
+        })
+    }
+}
+
+/// The text of the selected [`Language`].
+pub struct Strings(Table);
+impl FromWorld for Strings {
+    fn from_world(world: &mut World) -> Self {
+        let language = world.get_resource::<Language>().copied();
+        Self::new(language.unwrap_or_default())
+    }
+}
+impl Strings {
+    pub fn new(language: Language) -> Self {
+        let mut table = language.table();
+        for (key, english) in Language::English.table() {
+            if !table.contains_key(&key) {
+                #[cfg(debug_assertions)]
+                warn!("No {} text for {key}, using English", language.name());
+                table.insert(key, english);
+            }
+        }
+        Self(table)
+    }
+    /// The text at `key`, or `key` itself if no language has it.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.0.get(key).map(String::as_str).unwrap_or_else(|| {
+            #[cfg(debug_assertions)]
+            warn!("No text for {key}");
+            key
+        })
+    }
+    /// The text at `key`, with each `{name}` replaced by its value in `args`.
+    pub fn format(&self, key: &str, args: &[(&str, &str)]) -> String {
+        let mut text = self.get(key).to_owned();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{name}}}"), value);
+        }
+        text
+    }
+}
+
+/// A text showing the [`Strings`] at this key, updated when the language
+/// changes.
+#[derive(Component)]
+pub struct Localized(pub &'static str);
+
+fn update_strings(language: Res<Language>, mut strings: ResMut<Strings>) {
+    if language.is_changed() && !language.is_added() {
+        *strings = Strings::new(*language);
+    }
+}
+
+fn update_localized(strings: Res<Strings>, mut texts: Query<(&mut Text, &Localized)>) {
+    if !strings.is_changed() {
+        return;
+    }
+    for (mut text, Localized(key)) in texts.iter_mut() {
+        text.sections[0].value = strings.get(key).to_owned();
+    }
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Strings>()
+            .add_system(update_strings)
+            .add_system(update_localized.after(update_strings));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use super::*;
+
+    /// The string literals of a rust source file, escapes left as is.
+    fn string_literals(source: &str) -> Vec<&str> {
+        let mut literals = Vec::new();
+        let mut start = None;
+        let mut chars = source.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match (start, c) {
+                (Some(_), '\\') => {
+                    chars.next();
+                }
+                (Some(begin), '"') => {
+                    literals.push(&source[begin..i]);
+                    start = None;
+                }
+                (None, '"') => start = Some(i + 1),
+                // Skip the `'"'` char literal
+                (None, '\'') if source[i..].starts_with("'\"'") => {
+                    chars.next();
+                    chars.next();
+                }
+                _ => {}
+            }
+        }
+        literals
+    }
+
+    fn looks_like_key(literal: &str) -> bool {
+        let is_key_char = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_';
+        let is_part = |part: &str| !part.is_empty() && part.chars().all(is_key_char);
+        literal.contains('.') && literal.split('.').all(is_part)
+    }
+
+    fn source_files(dir: &Path, files: &mut Vec<String>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                source_files(&path, files);
+            } else if path.extension().map_or(false, |ext| ext == "rs") {
+                files.push(fs::read_to_string(path).unwrap());
+            }
+        }
+    }
+
+    #[test]
+    fn english_has_every_key_in_code() {
+        let english = Language::English.table();
+        let groups: Vec<_> = english.keys().map(|key| key.split('.').next()).collect();
+        let src = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        let mut files = Vec::new();
+        source_files(&src, &mut files);
+        let mut checked = 0;
+        for literal in files.iter().flat_map(|file| string_literals(file)) {
+            let group = literal.split('.').next();
+            if looks_like_key(literal) && groups.contains(&group) {
+                assert!(
+                    english.contains_key(literal),
+                    "No English text for {literal}"
+                );
+                checked += 1;
+            }
+        }
+        assert!(checked > 0, "no keys found in the source files");
+    }
+
+    #[test]
+    fn every_language_has_every_key() {
+        let english = Language::English.table();
+        for language in Language::ALL {
+            let table: Table = ron::de::from_str(language.source())
+                .unwrap_or_else(|err| panic!("{} text doesn't parse: {err}", language.name()));
+            for key in english.keys() {
+                let name = language.name();
+                assert!(table.contains_key(key), "No {name} text for {key}");
+            }
+        }
+    }
+
+    #[test]
+    fn format_fills_in_args() {
+        let strings = Strings::new(Language::English);
+        let text = strings.format(
+            "pile.you_answered",
+            &[("first", "Opponent played 3"), ("value", "5")],
+        );
+        assert_eq!(text, "Opponent played 3, you answered 5");
+        assert_eq!(strings.get("no.such_key"), "no.such_key");
+    }
+
+    #[test]
+    fn french_overrides_english() {
+        let french = Strings::new(Language::French);
+        let english = Strings::new(Language::English);
+        assert_ne!(french.get("menu.start"), english.get("menu.start"));
+    }
+}
@@ -6,33 +6,93 @@
 //! defined. Other modules are mostly helpers for input and ai. [See module
 //! section](#Modules).
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
+/// Show debug text on screen, only with the `debug` feature.
+///
+/// Takes the same arguments as [`bevy_debug_text_overlay::screen_print`].
+/// Without the `debug` feature, it expands to dead code: the text is never
+/// formatted and the overlay crate isn't a dependency.
+#[cfg(feature = "debug")]
+macro_rules! debug_print {
+    ($($args:tt)*) => {
+        bevy_debug_text_overlay::screen_print!($($args)*)
+    };
+}
+#[cfg(not(feature = "debug"))]
+macro_rules! debug_print {
+    (sec: $sec:expr, col: $col:expr, $($text:tt)*) => { debug_print!($($text)*) };
+    (sec: $sec:expr, $($text:tt)*) => { debug_print!($($text)*) };
+    (col: $col:expr, $($text:tt)*) => { debug_print!($($text)*) };
+    // Still type-checks the text, and uses the variables it shows
+    ($($text:tt)*) => {
+        if false {
+            let _ = format_args!($($text)*);
+        }
+    };
+}
+
+mod achievements;
 mod animate;
+mod archetype;
 mod audio;
+mod autosave;
+mod barks;
 mod card;
 mod cheat;
+mod coin_flip;
+#[cfg(feature = "debug")]
+mod console;
 mod deck;
+mod deck_reveal;
+mod game_event;
 mod game_flow;
 mod game_ui;
+mod history;
+mod lang;
+mod mood;
 mod numbers;
 mod oppo_hand;
+mod oppo_mood;
+mod opponent;
+mod persist;
 mod pile;
 mod player_hand;
+#[cfg(feature = "debug")]
+mod raycast_debug;
+mod rules;
+mod sandbox;
 mod scene;
+mod screenshot;
+mod settings;
 mod state;
+mod stats;
 mod system_helper;
+#[cfg(feature = "telemetry")]
+mod telemetry;
+#[cfg(feature = "testing")]
+mod testing;
+mod theme;
+mod tips;
+mod titles;
 mod ui;
 mod war;
 
 use state::{GameState, TurnState};
 use bevy_scene_hook::HookedSceneState;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, PartialEq, Debug)]
 pub enum Participant {
     Player,
     Oppo,
 }
 impl Participant {
+    pub fn other(self) -> Self {
+        match self {
+            Self::Player => Self::Oppo,
+            Self::Oppo => Self::Player,
+        }
+    }
     pub fn color(&self) -> Color {
         match self {
             Participant::Player => Color::rgb_u8(77, 77, 208),
@@ -45,18 +105,40 @@ impl Participant {
             Self::Oppo => "Oppo",
         }
     }
+    /// Name used in saved files, see [`Self::from_id`].
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::Player => "player",
+            Self::Oppo => "oppo",
+        }
+    }
+    pub fn from_id(id: &str) -> Option<Self> {
+        [Self::Player, Self::Oppo]
+            .into_iter()
+            .find(|who| who.id() == id)
+    }
 }
 
-/// Event to trigger a game over.
-#[derive(Debug)]
-pub struct GameOver(pub EndReason);
-
 /// What triggered the game over.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum EndReason {
     Victory,
     Loss,
     CaughtCheating,
+    /// The player gave up from the pause menu
+    Conceded,
+}
+impl EndReason {
+    /// Name used in text keys, same as the serialized one.
+    pub fn name(self) -> &'static str {
+        match self {
+            EndReason::Victory => "victory",
+            EndReason::Loss => "loss",
+            EndReason::CaughtCheating => "caught_cheating",
+            EndReason::Conceded => "conceded",
+        }
+    }
 }
 
 #[derive(Component)]
@@ -77,35 +159,64 @@ fn main() {
             present_mode: bevy::window::PresentMode::Immediate, 
             ..default()
         })
+        // The pause menu confirms closing the window during a game
+        .insert_resource(bevy::window::WindowSettings {
+            close_when_requested: cfg!(target_arch = "wasm32"),
+            ..default()
+        })
         .add_state(GameState::MainMenu)
         .add_state(TurnState::Starting)
         .add_plugins(DefaultPlugins);
 
     #[cfg(feature = "debug")]
     app.add_plugin(bevy_inspector_egui::WorldInspectorPlugin::new())
+        .add_plugin(console::Plugin)
+        .add_plugin(raycast_debug::Plugin)
         .add_plugin(bevy::pbr::wireframe::WireframePlugin)
+        .add_plugin(bevy_debug_text_overlay::OverlayPlugin::default())
         .insert_resource(bevy::render::settings::WgpuSettings {
             features: bevy::render::render_resource::WgpuFeatures::POLYGON_MODE_LINE,
             ..default()
         });
 
-    app.insert_resource(ClearColor(Color::rgb(0.293, 0.3828, 0.4023)))
-        .add_plugin(numbers::Plugin)
+    #[cfg(feature = "telemetry")]
+    app.add_plugin(telemetry::Plugin);
+    #[cfg(feature = "testing")]
+    app.add_plugin(testing::Plugin);
+
+    app.add_plugin(numbers::Plugin)
         .add_plugin(bevy_scene_hook::HookPlugin)
-        .add_plugin(bevy_debug_text_overlay::OverlayPlugin::default())
         .add_plugin(player_hand::Plugin(GameState::Playing))
         .add_plugin(oppo_hand::Plugin(GameState::Playing))
+        .add_plugin(oppo_mood::Plugin(GameState::Playing))
+        .add_plugin(barks::Plugin(GameState::Playing))
         .add_plugin(scene::Plugin)
+        .add_plugin(screenshot::Plugin)
         .add_plugin(deck::Plugin(GameState::Playing))
+        .add_plugin(opponent::Plugin)
+        .add_plugin(archetype::Plugin)
+        .add_plugin(deck_reveal::Plugin(GameState::Playing))
+        .add_plugin(coin_flip::Plugin(GameState::Playing))
         .add_plugin(animate::Plugin)
         .add_plugin(cheat::Plugin(GameState::Playing))
         .add_plugin(audio::Plugin)
         .add_plugin(card::Plugin)
         .add_plugin(ui::Plugin)
-        .add_plugin(pile::Plugin(GameState::Playing))
+        .add_plugin(stats::Plugin)
+        .add_plugin(achievements::Plugin(GameState::Playing))
+        .add_plugin(titles::Plugin(GameState::Playing))
+        .add_plugin(history::Plugin)
+        .add_plugin(settings::Plugin)
+        .add_plugin(lang::Plugin)
+        .add_plugin(theme::Plugin)
+        .add_plugin(mood::Plugin)
+        .add_plugin(tips::Plugin(GameState::WaitLoaded))
+        .add_plugin(pile::Plugin(&[GameState::Playing, GameState::Sandbox]))
+        .add_plugin(game_event::Plugin)
         .add_plugin(game_flow::Plugin(GameState::Playing))
-        .add_plugin(game_ui::Plugin(GameState::Playing))
-        .add_system_set(GameState::Playing.on_enter(first_draw))
+        .add_plugin(autosave::Plugin(GameState::Playing))
+        .add_plugin(game_ui::Plugin(&[GameState::Playing, GameState::Sandbox]))
+        .add_plugin(sandbox::Plugin(GameState::Sandbox))
         .add_system_set(GameState::WaitLoaded.on_enter(setup_load_screen))
         .add_system_set(GameState::WaitLoaded.on_update(complete_load_screen))
         .add_system_set(GameState::WaitLoaded.on_exit(cleanup_marked::<WaitRoot>))
@@ -115,36 +226,57 @@ fn main() {
 }
 
 pub fn cleanup_marked<T: Component>(mut cmds: Commands, query: Query<Entity, With<T>>) {
-    use bevy_debug_text_overlay::screen_print;
-    screen_print!(sec: 3.0, "Cleaned up Something (can't show)");
+    debug_print!(sec: 3.0, "cleaned up {}", std::any::type_name::<T>());
     for entity in query.iter() {
         cmds.entity(entity).despawn_recursive();
     }
 }
 
-fn setup(
-    mut ambiant_light: ResMut<AmbientLight>,
-    mut audio_events: EventWriter<audio::AudioRequest>,
-) {
-    *ambiant_light = AmbientLight { color: Color::WHITE, brightness: 1.0 };
+fn setup(mut audio_events: EventWriter<audio::AudioRequest>) {
     audio_events.send(audio::AudioRequest::StartMusic);
 }
 
 fn complete_load_screen(
     mut state: ResMut<State<GameState>>,
-    scene: HookedSceneState<scene::Graveyard>,
+    scene_check: Res<scene::SceneCheck>,
+    deck_assets: Res<deck::DeckAssets>,
+    decks: Res<Assets<deck::Deck>>,
+    server: Res<AssetServer>,
+    opponent: Res<opponent::OpponentProfile>,
+    audio: Res<audio::AudioAssets>,
 ) {
-    if scene.is_loaded() {
-        state.set(GameState::Playing).expect("no state issues");
+    // The decks may have been hot-reloaded into something broken since the
+    // main menu checked them.
+    match deck_assets.check(&opponent, &server, &decks) {
+        Err(err) if err.is_loading() => {}
+        Err(err) => {
+            debug_print!(sec: 5.0, col: Color::RED, "Can't start: {err}");
+            state.set(GameState::MainMenu).expect("no state issues");
+        }
+        // The main menu tells the player what's wrong with the scene
+        Ok(()) => match *scene_check {
+            scene::SceneCheck::Pending => {}
+            // The first played word would otherwise hitch while it loads
+            scene::SceneCheck::Playable if !audio.warmed_up(&server) => {}
+            scene::SceneCheck::Playable => {
+                state.set(GameState::Playing).expect("no state issues");
+            }
+            scene::SceneCheck::Broken(_) => {
+                state.set(GameState::MainMenu).expect("no state issues");
+            }
+        },
     }
 }
 fn setup_load_screen(
     mut cmds: Commands,
     assets: Res<ui::Assets>,
+    strings: Res<lang::Strings>,
     scene: HookedSceneState<scene::Graveyard>,
+    audio: Res<audio::AudioAssets>,
+    server: Res<AssetServer>,
 ) {
     use bevy_ui_build_macros::{build_ui, size, style, unit};
-    if !scene.is_loaded() {
+    if !scene.is_loaded() || !audio.warmed_up(&server) {
         let node = NodeBundle::default();
         build_ui! {
             #[cmd(cmds)]
@@ -155,12 +287,9 @@ fn setup_load_screen(
                 size: size!(100 pct, 100 pct)
             }[; Name::new("Root loading screen node"), WaitRoot] (
                 entity[ assets.background(); Name::new("Background") ],
-                entity[assets.large_text("Loading..."); ]
+                entity[assets.large_text(strings.get("loading")); ],
+                entity[assets.text_bundle("", 30.0); tips::LoadingTip::default()]
             )
         };
     }
 }
-
-fn first_draw(mut state: ResMut<State<TurnState>>) {
-    state.set(TurnState::Draw).unwrap();
-}
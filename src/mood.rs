@@ -0,0 +1,219 @@
+//! Lighting moods, following the game state.
+//!
+//! The [`Mood`] resource is the lighting the scene heads to: it is set when
+//! entering states, and by [`crate::cheat`] when the player gets caught.
+//! [`fade_lighting`] then blends the [`AmbientLight`] and [`ClearColor`]
+//! toward it in [`FADE_DURATION`] seconds, or snaps to it in low-spec mode.
+//!
+//! The [`Lighting`] of each mood comes from the [`Moods`] table, which the
+//! [`crate::theme`] sets from its own colors with [`Moods::new`], and may
+//! override.
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use enum_map::{Enum, EnumMap};
+
+use crate::{settings::GameplaySettings, state::GameState, system_helper::EasySystemSetCtor};
+
+/// How long the lighting takes to go from one mood to another, in seconds.
+const FADE_DURATION: f32 = 0.8;
+
+#[derive(Enum, Clone, Copy, Debug, PartialEq)]
+pub enum Mood {
+    /// Dim and cool, in the main menu.
+    Menu,
+    /// The full theme lighting.
+    Gameplay,
+    /// Red-shifted, when the bird caught the player cheating.
+    Caught,
+    /// Dim and desaturated, in the pause menu.
+    Paused,
+}
+impl Default for Mood {
+    fn default() -> Self {
+        Mood::Menu
+    }
+}
+
+/// The [`AmbientLight`] and [`ClearColor`] of a [`Mood`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lighting {
+    pub ambient: Color,
+    pub brightness: f32,
+    pub clear: Color,
+}
+impl Lighting {
+    fn lerp(self, target: Self, t: f32) -> Self {
+        // Not `Vec4::lerp`, so that `t == 1.0` is exactly `target`
+        let lerp_color = |from: Color, to: Color| {
+            let (from, to) = (Vec4::from(from.as_rgba_f32()), Vec4::from(to.as_rgba_f32()));
+            let [r, g, b, a] = (from * (1.0 - t) + to * t).to_array();
+            Color::rgba(r, g, b, a)
+        };
+        Lighting {
+            ambient: lerp_color(self.ambient, target.ambient),
+            brightness: self.brightness * (1.0 - t) + target.brightness * t,
+            clear: lerp_color(self.clear, target.clear),
+        }
+    }
+}
+
+/// How a [`Mood`] alters the theme lighting.
+struct Tone {
+    /// Multiplies the ambient and clear colors.
+    tint: [f32; 3],
+    /// Ambient light brightness, the clear color is darkened as much.
+    brightness: f32,
+    /// `0.0` for grays, `1.0` to keep the colors as they are.
+    saturation: f32,
+}
+impl Tone {
+    fn of(mood: Mood) -> Self {
+        let tone = |tint, brightness, saturation| Tone { tint, brightness, saturation };
+        match mood {
+            Mood::Menu => tone([0.8, 0.85, 1.0], 0.6, 0.9),
+            Mood::Gameplay => tone([1.0, 1.0, 1.0], 1.0, 1.0),
+            Mood::Caught => tone([1.0, 0.45, 0.4], 0.9, 1.0),
+            Mood::Paused => tone([1.0, 1.0, 1.0], 0.5, 0.3),
+        }
+    }
+    fn apply(&self, color: Color, darken: f32) -> Color {
+        let [r, g, b, a] = color.as_rgba_f32();
+        let [tr, tg, tb] = self.tint;
+        let tinted = Vec3::new(r * tr, g * tg, b * tb);
+        let luma = Vec3::splat(tinted.dot(Vec3::new(0.2126, 0.7152, 0.0722)));
+        let saturated = tinted * self.saturation + luma * (1.0 - self.saturation);
+        let [r, g, b] = (saturated * darken).to_array();
+        Color::rgba(r, g, b, a)
+    }
+}
+
+/// The [`Lighting`] of each [`Mood`].
+pub struct Moods(pub EnumMap<Mood, Lighting>);
+impl Moods {
+    /// The moods of a theme lit with `ambient` over `clear`.
+    pub fn new(ambient: Color, clear: Color) -> Self {
+        Moods(EnumMap::from_fn(|mood| {
+            let tone = Tone::of(mood);
+            Lighting {
+                ambient: tone.apply(ambient, 1.0),
+                brightness: tone.brightness,
+                clear: tone.apply(clear, tone.brightness),
+            }
+        }))
+    }
+}
+impl Default for Moods {
+    fn default() -> Self {
+        Moods::new(Color::WHITE, Color::BLACK)
+    }
+}
+
+/// Progress of the fade toward the current [`Mood`].
+struct Fade {
+    from: Lighting,
+    elapsed: f32,
+}
+
+/// How far along a fade started `elapsed` seconds ago is, easing in and out.
+fn fade_progress(elapsed: f32) -> f32 {
+    let progress = (elapsed / FADE_DURATION).clamp(0.0, 1.0);
+    progress * progress * (3.0 - 2.0 * progress)
+}
+
+fn fade_lighting(
+    mood: Res<Mood>,
+    moods: Res<Moods>,
+    gameplay: Res<GameplaySettings>,
+    time: Res<Time>,
+    mut fade: Local<Option<Fade>>,
+    mut ambient_light: ResMut<AmbientLight>,
+    mut clear_color: ResMut<ClearColor>,
+) {
+    if mood.is_changed() || moods.is_changed() {
+        let from = Lighting {
+            ambient: ambient_light.color,
+            brightness: ambient_light.brightness,
+            clear: clear_color.0,
+        };
+        *fade = Some(Fade { from, elapsed: 0.0 });
+    }
+    let current = match fade.as_mut() {
+        Some(current) => current,
+        None => return,
+    };
+    current.elapsed += time.delta_seconds();
+    let progress = if gameplay.low_spec {
+        1.0
+    } else {
+        fade_progress(current.elapsed)
+    };
+    let lighting = current.from.lerp(moods.0[*mood], progress);
+    *ambient_light = AmbientLight {
+        color: lighting.ambient,
+        brightness: lighting.brightness,
+    };
+    clear_color.0 = lighting.clear;
+    if progress >= 1.0 {
+        *fade = None;
+    }
+}
+
+fn menu_mood(mut mood: ResMut<Mood>) {
+    *mood = Mood::Menu;
+}
+fn gameplay_mood(mut mood: ResMut<Mood>) {
+    *mood = Mood::Gameplay;
+}
+fn paused_mood(mut mood: ResMut<Mood>) {
+    *mood = Mood::Paused;
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Mood>()
+            .init_resource::<Moods>()
+            .add_system_set(GameState::MainMenu.on_enter(menu_mood))
+            .add_system_set(GameState::Playing.on_enter(gameplay_mood))
+            .add_system_set(SystemSet::on_resume(GameState::Playing).with_system(gameplay_mood))
+            .add_system_set(GameState::PauseMenu.on_enter(paused_mood))
+            .add_system_set(GameState::Sandbox.on_enter(gameplay_mood))
+            .add_system(fade_lighting);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fades_ease_to_the_target() {
+        assert_eq!(fade_progress(0.0), 0.0);
+        assert_eq!(fade_progress(FADE_DURATION / 2.0), 0.5);
+        assert_eq!(fade_progress(FADE_DURATION), 1.0);
+        assert_eq!(fade_progress(10.0), 1.0);
+        let moods = Moods::new(Color::WHITE, Color::rgb(0.3, 0.4, 0.4));
+        let (menu, gameplay) = (moods.0[Mood::Menu], moods.0[Mood::Gameplay]);
+        assert_eq!(menu.lerp(gameplay, 0.0), menu);
+        assert_eq!(menu.lerp(gameplay, 1.0), gameplay);
+    }
+
+    #[test]
+    fn moods_alter_the_theme_lighting() {
+        let clear = Color::rgb(0.25, 0.5, 0.75);
+        let moods = Moods::new(Color::WHITE, clear);
+        let gameplay = moods.0[Mood::Gameplay];
+        assert_eq!(
+            gameplay,
+            Lighting { ambient: Color::WHITE, brightness: 1.0, clear }
+        );
+        let [r, g, b, _] = moods.0[Mood::Caught].ambient.as_rgba_f32();
+        assert!(r > g && r > b, "caught is red-shifted");
+        let [r, _, b, _] = moods.0[Mood::Menu].ambient.as_rgba_f32();
+        assert!(b > r, "the menu is cooler");
+        let paused = moods.0[Mood::Paused];
+        assert!(paused.brightness < gameplay.brightness);
+        let [r, g, b, _] = paused.clear.as_rgba_f32();
+        assert!(b - r < 0.75 - 0.25, "the pause menu is desaturated");
+        assert!(r + g + b < 0.25 + 0.5 + 0.75, "the pause menu is dim");
+    }
+}
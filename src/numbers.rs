@@ -1,4 +1,7 @@
 //! Display numbers in the 3d game world.
+//!
+//! Numbers with a [`CountUp`] component count toward their new value rather
+//! than snapping to it.
 use std::iter;
 
 use bevy::{
@@ -8,18 +11,41 @@ use bevy::{
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::{Inspectable, RegisterInspectable};
 
+use crate::audio::{AudioRequest, SfxParam};
+
+/// Duration of the [`CountUp`] animation, in seconds.
+const COUNT_DURATION: f64 = 0.6;
+/// Play a tick sound every `TICK_EVERY` increments while counting up.
+const TICK_EVERY: i32 = 3;
+
 #[cfg_attr(feature = "debug", derive(Inspectable))]
 #[derive(Component)]
 pub struct Number {
     pub value: i32,
     pub color: Color,
+    /// What is currently shown, when it's not `value`. Only set for numbers
+    /// with a [`CountUp`].
+    #[cfg_attr(feature = "debug", inspectable(ignore))]
+    display_value: Option<i32>,
 }
 impl Number {
     pub fn new(value: i32, color: Color) -> Self {
-        Self { value, color }
+        Self { value, color, display_value: None }
     }
 }
 
+/// Make a [`Number`] count up toward its new `value` instead of snapping to it.
+///
+/// Only increases are animated, a decreasing value (such as when scores are
+/// reset) is shown immediately.
+#[derive(Component, Default)]
+pub struct CountUp {
+    from: i32,
+    target: i32,
+    start: f64,
+    last_tick: i32,
+}
+
 #[derive(Component)]
 struct NumberSprite;
 
@@ -27,6 +53,49 @@ struct NumberSprite;
 const NUMBER_NAMES: [&str; 10] = 
     [ "Zero", "One", "Two", "Three", "Four", "Five", "Six", "Seven", "Eight", "Nine"];
 
+/// Value shown `progress` (from 0 to 1) into counting from `from` to `to`,
+/// eased out so that counting slows down near the end.
+fn counted_value(from: i32, to: i32, progress: f64) -> i32 {
+    let progress = progress.clamp(0.0, 1.0);
+    let eased = 1.0 - (1.0 - progress).powi(3);
+    from + ((to - from) as f64 * eased).round() as i32
+}
+
+fn count_up(
+    time: Res<Time>,
+    mut numbers: Query<(&mut Number, &mut CountUp)>,
+    mut audio: EventWriter<AudioRequest>,
+) {
+    let now = time.seconds_since_startup();
+    for (mut number, mut count) in numbers.iter_mut() {
+        if number.value != count.target {
+            let is_increase = number.value > count.target;
+            let from = if is_increase {
+                number.display_value.unwrap_or(count.target)
+            } else {
+                number.value
+            };
+            *count = CountUp {
+                from,
+                target: number.value,
+                start: now,
+                last_tick: from,
+            };
+        }
+        let progress = (now - count.start) / COUNT_DURATION;
+        let shown = counted_value(count.from, count.target, progress);
+        let display_value = (shown != number.value).then(|| shown);
+        // Only update on actual change, `display_number` rebuilds materials when changed
+        if number.display_value != display_value {
+            number.display_value = display_value;
+        }
+        if shown - count.last_tick >= TICK_EVERY {
+            count.last_tick = shown;
+            audio.send(AudioRequest::PlayWoodClink(SfxParam::PlayOnce));
+        }
+    }
+}
+
 fn add_number(
     new_numbers: Query<Entity, Added<Number>>,
     mut cmds: Commands,
@@ -63,8 +132,9 @@ fn display_number(
     for (parent, mut transform, mut vis, mut material) in sprites.iter_mut() {
         let parent = parent.get();
         // We only do things for numbers which value changed
-        if let Ok(Number { value, color }) = numbers.get(parent) {
-            let initial_iter = || decimals(*value).enumerate();
+        if let Ok(Number { value, color, display_value }) = numbers.get(parent) {
+            let shown = display_value.unwrap_or(*value);
+            let initial_iter = || decimals(shown).enumerate();
             let current_decimal = decimal_streams.entry(parent).or_insert_with(initial_iter);
             if let Some((i, current)) = current_decimal.next() {
                 vis.is_visible = true;
@@ -112,8 +182,14 @@ impl BevyPlugin for Plugin {
         #[cfg(feature = "debug")]
         app.register_inspectable::<Number>();
 
+        // Run in PostUpdate so that values set during Update are already
+        // accounted for by `count_up` when `display_number` shows them.
         app.init_resource::<NumberAssets>()
-            .add_system(display_number)
+            .add_system_to_stage(CoreStage::PostUpdate, count_up.before("display_number"))
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                display_number.label("display_number"),
+            )
             .add_system(add_number);
     }
 }
@@ -139,4 +215,31 @@ mod tests {
         number_assert!(1, 1);
         number_assert!(0,);
     }
+
+    #[test]
+    fn test_counted_value() {
+        assert_eq!(counted_value(3, 18, 0.0), 3);
+        assert_eq!(counted_value(3, 18, 1.0), 18);
+        assert_eq!(
+            counted_value(3, 18, 7.5),
+            18,
+            "progress past the end is clamped"
+        );
+        assert_eq!(
+            counted_value(3, 18, -1.0),
+            3,
+            "progress before the start is clamped"
+        );
+        let steps: Vec<_> = (0..=60)
+            .map(|i| counted_value(0, 15, i as f64 / 60.0))
+            .collect();
+        assert!(
+            steps.windows(2).all(|w| w[0] <= w[1]),
+            "counting never goes backward"
+        );
+        assert!(
+            steps[30] > 15 / 2,
+            "eases out: more than half way at half time"
+        );
+    }
 }
@@ -3,111 +3,587 @@
 //! Select card to play based on which one has been played (if any) and play
 //! them.
 //!
-//! * [`update_oppo_hand`]: Move cards in the hand of the opposition.
-//! * [`play_card`]: system running AI to and play card it selected.
-//! * [`chose_card`]: AI heuristic to select card to play.
-use bevy::prelude::{Plugin as BevyPlugin, *};
+//! * [`update_oppo_hand`]: Move cards in the hand of the opposition, see
+//!   [`row_offset`].
+//! * [`start_thinking`]: system running AI to select the card to play.
+//! * [`think`]: highlight the cards the AI considered, then play the card it
+//!   selected.
+//! * [`chose_card`]: AI heuristic to rank cards to play.
+//! * [`suspect_cheating`]: the oppo plays aggressively the turn after the
+//!   player distracted the bird, the next two turns for a trinket.
+//! * [`PublicInfo`]: what the oppo knows of the game besides its hand, so
+//!   that it denies the player seeds and bonuses late in the game.
+//! * [`plan_lead`]: the oppo decides how it leads its next battle as soon as
+//!   its hand changes, and gives it away with [`Tells`].
+//! * [`whisper_words`]: the oppo whispers the words it draws, a hint for
+//!   attentive players, see [`Whispers`].
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::{Plugin as BevyPlugin, *},
+};
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::{Inspectable, RegisterInspectable};
-use fastrand::usize as randusize;
+use enum_map::EnumMap;
+use fastrand::{f32 as randf32, usize as randusize};
 
+#[cfg(feature = "debug")]
+use crate::console::GiveCard;
 use crate::{
-    card::SpawnCard,
+    animate::{exp_approach, exp_approach_quat, GameFeel, GameSpeed},
+    audio::AudioRequest,
+    autosave::RestoreHand,
+    card::{row_offset, CardStatus, SpawnCard},
+    cheat::{CheatEvent, Distraction},
     deck::OppoDeck,
-    game_flow::{PlayCard, PlayedCard},
+    game_event::GameEvent,
+    game_flow::{CoopTurn, PlayCard, PlayedCard, SeedCount},
+    opponent::{AiWeights, OpponentProfile},
+    pile::PileCard,
+    rules::{GameRules, InitiativeRule},
+    settings::GameplaySettings,
     state::{GameState, TurnState},
     war::{BattleOutcome, Card, Value, WordOfPower},
-    Participant,
+    CardOrigin, Participant,
 };
 
+/// Longest time the oppo spends considering each card, in seconds.
+const CONSIDER_DURATION: f32 = 0.25;
+/// Longest time the oppo spends thinking, in seconds.
+const MAX_THINKING: f32 = 0.9;
+/// How much the considered card is raised above the others.
+const CONSIDERED_LIFT: f32 = 0.3;
+/// Battles left under which the oppo plays the end game, see
+/// [`PublicInfo::is_late`].
+const LATE_GAME_TURNS: usize = 4;
+/// Points a [`Value::Zero`] earns with a [`WordOfPower::Geh`].
+const GEH_ZERO_POINTS: i32 = 12;
+/// Longest time between the oppo drawing a word and whispering it, in
+/// seconds, so that whispers can't be simply counted as cards are drawn.
+const MAX_WHISPER_DELAY: f32 = 2.0;
+
 /// Position of the hand of the opposition
 #[derive(Component)]
 pub struct OppoHand;
 
 #[cfg_attr(feature = "debug", derive(Inspectable))]
 #[derive(Component)]
-struct OppoCard {
+pub struct OppoCard {
     index: usize,
 }
 impl OppoCard {
     fn new(index: usize) -> Self {
         Self { index }
     }
+    /// Position of the card in the hand.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+/// A word the oppo drew, whispered once `delay` more seconds elapsed.
+#[derive(Debug, PartialEq)]
+struct Whisper {
+    word: WordOfPower,
+    delay: f32,
+}
+
+/// The words the oppo drew and is yet to whisper.
+///
+/// Only advanced while playing, whispers wait for the game to resume rather
+/// than play over the pause menu.
+#[derive(Default)]
+struct Whispers(Vec<Whisper>);
+impl Whispers {
+    /// Queue the words of the drawn `cards`, if
+    /// [`GameplaySettings::demon_whispers`] is on, `roll` picking how long
+    /// each waits, between 0 and 1.
+    fn queue(&mut self, cards: &[Card], gameplay: &GameplaySettings, roll: impl Fn() -> f32) {
+        if !gameplay.demon_whispers {
+            return;
+        }
+        let words = cards.iter().filter_map(|card| card.word);
+        let whisper = |word| Whisper { word, delay: roll() * MAX_WHISPER_DELAY };
+        self.0.extend(words.map(whisper));
+    }
+    /// Wait `dt` more seconds, return the words to whisper now.
+    fn advance(&mut self, dt: f32) -> Vec<WordOfPower> {
+        for whisper in &mut self.0 {
+            whisper.delay -= dt;
+        }
+        let due = self.0.iter().filter(|whisper| whisper.delay <= 0.0);
+        let due = due.map(|whisper| whisper.word).collect();
+        self.0.retain(|whisper| whisper.delay > 0.0);
+        due
+    }
 }
 
-fn draw_hand(mut card_spawner: SpawnCard, mut deck: Query<&mut OppoDeck>) {
-    for (i, card) in deck.single_mut().draw(3).into_iter().enumerate() {
+fn draw_hand(
+    mut card_spawner: SpawnCard,
+    mut deck: Query<&mut OppoDeck>,
+    mut game_events: EventWriter<GameEvent>,
+    mut whispers: ResMut<Whispers>,
+    gameplay: Res<GameplaySettings>,
+) {
+    let drawn = deck.single_mut().draw(3);
+    whispers.queue(&drawn, &gameplay, randf32);
+    for (i, card) in drawn.into_iter().enumerate() {
+        let who = Participant::Oppo;
+        game_events.send(GameEvent::CardDrawn { who, card: card.clone() });
         card_spawner
             .spawn_card(card, Participant::Oppo)
             .insert(OppoCard::new(i));
     }
 }
 
+/// Put back the oppo hand of an autosave, see [`crate::autosave`].
+fn restore_hand(mut card_spawner: SpawnCard, mut events: EventReader<RestoreHand>) {
+    let restores = events
+        .iter()
+        .filter(|restore| restore.who == Participant::Oppo);
+    for restore in restores {
+        for (i, card) in restore.hand.iter().enumerate() {
+            card_spawner
+                .spawn_card(card.clone(), Participant::Oppo)
+                .insert(OppoCard::new(i));
+        }
+    }
+}
+
+/// Whisper the words the oppo drew, once their delay elapsed.
+fn whisper_words(
+    mut whispers: ResMut<Whispers>,
+    mut audio: EventWriter<AudioRequest>,
+    time: Res<Time>,
+) {
+    for word in whispers.advance(time.delta_seconds()) {
+        audio.send(AudioRequest::PlayWhisper(word));
+    }
+}
+
+/// Spawn the cards given to the oppo with the debug console.
+#[cfg(feature = "debug")]
+fn give_cards(
+    mut card_spawner: SpawnCard,
+    mut events: EventReader<GiveCard>,
+    hand: Query<&OppoCard>,
+) {
+    let mut index = hand.iter().map(|card| card.index + 1).max().unwrap_or(0);
+    for GiveCard(who, card) in events.iter() {
+        if *who == Participant::Oppo {
+            card_spawner
+                .spawn_card(card.clone(), Participant::Oppo)
+                .insert(OppoCard::new(index));
+            index += 1;
+        }
+    }
+}
+
+/// A card the oppo considered playing, see [`chose_card`].
+#[derive(Debug, PartialEq)]
+struct Candidate {
+    /// Index of the card in the hand.
+    index: usize,
+    /// How desirable playing this card is, higher is better.
+    score: i32,
+}
+
+/// The oppo cards still to consider before playing the last one.
+#[derive(Default)]
+struct Thinking {
+    /// Cards from least to most desirable, the last one is played.
+    candidates: Vec<Entity>,
+    elapsed: f32,
+}
+impl Thinking {
+    fn consider_duration(&self, speed: &GameSpeed) -> f32 {
+        let duration = CONSIDER_DURATION.min(MAX_THINKING / self.candidates.len().max(1) as f32);
+        speed.duration(duration)
+    }
+}
+
+/// The oppo suspects the player is about to cheat, because they distracted
+/// the bird, see [`chose_card`].
+///
+/// Counts the oppo turns it stays suspicious.
+#[derive(Default)]
+struct Suspicion(usize);
+impl Suspicion {
+    /// Suspect the player after they distracted the bird with `distraction`.
+    fn suspect(&mut self, distraction: Distraction) {
+        let turns = match distraction {
+            Distraction::Seed => 1,
+            Distraction::Trinket => 2,
+        };
+        self.0 = self.0.max(turns);
+    }
+    /// Whether the oppo is suspicious this turn, it forgets about it after
+    /// enough turns, whether or not the player actually cheated.
+    fn take(&mut self) -> bool {
+        let suspicious = self.0 != 0;
+        self.0 = self.0.saturating_sub(1);
+        suspicious
+    }
+}
+
+/// What the oppo knows of the game, besides its own hand, see
+/// [`chose_card`].
+#[derive(Clone, Copy)]
+struct PublicInfo {
+    /// Battles left to play, including the current one: the oppo plays one
+    /// card per battle.
+    turns_left: usize,
+    /// Values of the cards the player played so far, in any pile.
+    player_played: EnumMap<Value, bool>,
+}
+impl PublicInfo {
+    /// Whether few enough battles are left that the oppo denies the player
+    /// what it can.
+    fn is_late(&self) -> bool {
+        self.turns_left <= LATE_GAME_TURNS
+    }
+    /// Whether the player likely holds a card of `value`: they haven't
+    /// played one yet.
+    fn player_may_hold(&self, value: Value) -> bool {
+        !self.player_played[value]
+    }
+}
+
+/// What the oppo gives away about its next plays, animated in
+/// [`crate::oppo_mood`].
+///
+/// Opponents without [`AiWeights::tells`] give nothing away.
+#[derive(Debug, Default, PartialEq)]
+pub struct Tells {
+    /// The oppo plans to lead its next battle with its highest card.
+    pub leans: bool,
+    /// The oppo holds a [`WordOfPower::Zihbm`] card.
+    pub taps: bool,
+}
+
+/// The tells of the oppo holding `hand`, planning to lead with `lead`.
+fn read_tells(hand: &[Card], lead: Option<&Card>) -> Tells {
+    let highest = hand.iter().map(|card| card.value).max();
+    let zihbm = Some(WordOfPower::Zihbm);
+    Tells {
+        leans: matches!(lead, Some(lead) if Some(lead.value) == highest),
+        taps: hand.iter().any(|card| card.word == zihbm),
+    }
+}
+
+/// The card the oppo leads its next battle with, decided by [`plan_lead`]
+/// so that its [`Tells`] don't lie.
+#[derive(Default)]
+struct LeadPlan {
+    /// The hand the plan was made for, it is made again when it changes.
+    hand: Vec<Card>,
+    /// Whether the oppo was suspicious when planning, a plan made calmly
+    /// doesn't hold once it turns aggressive, and the other way around.
+    aggressive: bool,
+    lead: Option<Card>,
+}
+impl LeadPlan {
+    /// Index in `hand` of the planned lead, if still planned for `hand` and
+    /// the `aggressive` stance.
+    fn lead_index(&self, hand: &[Card], aggressive: bool) -> Option<usize> {
+        let planned_for = self.hand == hand && self.aggressive == aggressive;
+        let lead = self.lead.as_ref().filter(|_| planned_for)?;
+        hand.iter().position(|card| card == lead)
+    }
+}
+
+/// Make the candidate at `index` in hand the one played.
+fn stick_to_plan(candidates: &mut Vec<Candidate>, index: usize) {
+    if let Some(position) = candidates.iter().position(|c| c.index == index) {
+        let planned = candidates.remove(position);
+        candidates.push(planned);
+    }
+}
+
+/// What the AI looks at to rank its cards, see [`chose_card`].
+#[derive(SystemParam)]
+struct AiInputs<'w, 's> {
+    cards: Query<'w, 's, (Entity, &'static Card, &'static OppoCard)>,
+    pile_cards: Query<'w, 's, (&'static Card, &'static CardOrigin), With<PileCard>>,
+    deck: Query<'w, 's, &'static OppoDeck>,
+    seeds: Res<'w, SeedCount>,
+    coop_turn: Res<'w, CoopTurn>,
+    rules: Res<'w, GameRules>,
+    opponent: Res<'w, OpponentProfile>,
+}
+impl<'w, 's> AiInputs<'w, 's> {
+    /// The cards in the oppo hand, in hand order.
+    fn hand(&self) -> (Vec<Entity>, Vec<Card>) {
+        let mut cards: Vec<_> = self.cards.iter().collect();
+        cards.sort_by_key(|(_, _, OppoCard { index })| *index);
+        cards.into_iter().map(|(e, c, _)| (e, c.clone())).unzip()
+    }
+    fn egeq_cost(&self) -> i32 {
+        if self.rules.fair_game {
+            self.rules.fair_seed_points
+        } else if self.seeds.is_full(self.coop_turn.active()) {
+            self.rules.seed_overflow_points
+        } else {
+            self.opponent.ai.seed_value
+        }
+    }
+    fn public(&self, hand: &[Card]) -> PublicInfo {
+        let mut player_played = EnumMap::default();
+        for (card, CardOrigin(who)) in self.pile_cards.iter() {
+            if *who == Participant::Player {
+                player_played[card.value] = true;
+            }
+        }
+        let deck_remaining = self.deck.get_single().map_or(0, |deck| deck.remaining());
+        let turns_left = hand.len() + deck_remaining;
+        PublicInfo { turns_left, player_played }
+    }
+    /// Rank `hand` against the `played` card, see [`chose_card`].
+    fn rank(&self, played: Option<&Card>, hand: &[Card], aggressive: bool) -> Vec<Candidate> {
+        let stance = Stance {
+            aggressive,
+            initiative: self.rules.initiative,
+            egeq_pays_on_loss: self.rules.egeq_pays_on_loss,
+        };
+        let (public, cost, weights) = (self.public(hand), self.egeq_cost(), &self.opponent.ai);
+        chose_card(played, hand, &public, cost, weights, stance)
+    }
+}
+
+/// Decide how to lead the next battle whenever the oppo hand or its
+/// [`Suspicion`] changes, and update the [`Tells`] accordingly.
+fn plan_lead(
+    mut plan: ResMut<LeadPlan>,
+    mut tells: ResMut<Tells>,
+    inputs: AiInputs,
+    suspicion: Res<Suspicion>,
+) {
+    let (_, hand) = inputs.hand();
+    let aggressive = suspicion.0 != 0;
+    if hand == plan.hand && aggressive == plan.aggressive {
+        return;
+    }
+    let lead = if hand.is_empty() {
+        None
+    } else {
+        let candidates = inputs.rank(None, &hand, aggressive);
+        candidates.last().map(|c| hand[c.index].clone())
+    };
+    let new_tells = if inputs.opponent.ai.tells {
+        read_tells(&hand, lead.as_ref())
+    } else {
+        Tells::default()
+    };
+    if *tells != new_tells {
+        *tells = new_tells;
+    }
+    *plan = LeadPlan { hand, aggressive, lead };
+}
+
+fn suspect_cheating(mut suspicion: ResMut<Suspicion>, mut events: EventReader<CheatEvent>) {
+    for event in events.iter() {
+        if let CheatEvent::ConfuseBird(distraction) = event {
+            suspicion.suspect(*distraction);
+        }
+    }
+}
+
+/// Lay the cards of the oppo in a row centered on its hand, so that it
+/// re-centers as they are played.
 fn update_oppo_hand(
     oppo_hand: Query<&GlobalTransform, With<OppoHand>>,
-    mut cards: Query<(&mut Transform, &OppoCard)>,
+    mut cards: Query<(&mut Transform, &OppoCard, &CardStatus)>,
     time: Res<Time>,
+    feel: Res<GameFeel>,
+    speed: Res<GameSpeed>,
 ) {
-    let card_speed = 10.0 * time.delta_seconds();
+    let (rate, dt) = (speed.rate(feel.hand_rate), time.delta_seconds());
     let hand_transform = oppo_hand.single().compute_transform();
     let hand_pos = hand_transform.translation;
-    for (mut transform, OppoCard { index }) in cards.iter_mut() {
-        let i_f32 = *index as f32;
-        let target = hand_pos + Vec3::new(i_f32 * 1.2 - 1.0, 0.0, 0.0);
-        let origin = transform.translation;
-        transform.translation += (target - origin) * card_speed;
+    let mut cards: Vec<_> = cards.iter_mut().collect();
+    cards.sort_by_key(|(_, OppoCard { index }, _)| *index);
+    let count = cards.len();
+    for (slot, (transform, _, status)) in cards.iter_mut().enumerate() {
+        let lift = if **status == CardStatus::Considered {
+            CONSIDERED_LIFT
+        } else {
+            0.0
+        };
+        let target = hand_pos + Vec3::new(row_offset(slot, count), lift, 0.0);
+        transform.translation = exp_approach(transform.translation, target, rate, dt);
 
         let target = hand_transform.rotation;
-        let origin = transform.rotation;
-        transform.rotation = origin.lerp(target, card_speed);
+        transform.rotation = exp_approach_quat(transform.rotation, target, rate, dt);
     }
 }
 
-fn play_card(
-    mut cmds: Commands,
-    mut card_events: EventWriter<PlayCard>,
-    mut card_transform: Query<&mut Transform, With<OppoCard>>,
-    cards: Query<(Entity, &Card), With<OppoCard>>,
+/// Rank the cards in hand and start considering them one after the other.
+///
+/// The oppo leads with the card it planned in [`plan_lead`].
+fn start_thinking(
+    mut thinking: ResMut<Thinking>,
     war_card: Query<&Card, With<PlayedCard>>,
+    inputs: AiInputs,
+    plan: Res<LeadPlan>,
+    mut suspicion: ResMut<Suspicion>,
 ) {
-    let (entities, cards): (Vec<_>, Vec<_>) = cards.iter().map(|(e, c)| (e, c.clone())).unzip();
+    let (entities, cards) = inputs.hand();
     assert!(!cards.is_empty(), "Oppo must have a least a card on play");
-    let selected_index = chose_card(war_card.get_single().ok(), &cards);
-    let selected = entities[selected_index];
+    let played = war_card.get_single().ok();
+    let aggressive = suspicion.take();
+    let mut candidates = inputs.rank(played, &cards, aggressive);
+    if let (None, Some(planned)) = (played, plan.lead_index(&cards, aggressive)) {
+        stick_to_plan(&mut candidates, planned);
+    }
+    thinking.candidates = candidates.iter().map(|c| entities[c.index]).collect();
+    thinking.elapsed = 0.0;
+}
 
-    // Offset up the card so that it doesn't go through the already-played one
-    let mut trans = card_transform.get_mut(selected).unwrap();
-    trans.translation.y += 1.0;
+/// Highlight the considered cards in order, then play the last one.
+fn think(
+    mut cmds: Commands,
+    mut thinking: ResMut<Thinking>,
+    mut card_events: EventWriter<PlayCard>,
+    mut cards: Query<(&mut Transform, &mut CardStatus), With<OppoCard>>,
+    time: Res<Time>,
+    speed: Res<GameSpeed>,
+) {
+    let selected = match thinking.candidates.last() {
+        Some(selected) => *selected,
+        None => return,
+    };
+    thinking.elapsed += time.delta_seconds();
+    let considering = (thinking.elapsed / thinking.consider_duration(&speed)) as usize;
+    if let Some(considered) = thinking.candidates.get(considering) {
+        for entity in &thinking.candidates {
+            if let Ok((_, mut status)) = cards.get_mut(*entity) {
+                let new_status = if entity == considered {
+                    CardStatus::Considered
+                } else {
+                    CardStatus::Normal
+                };
+                if *status != new_status {
+                    *status = new_status;
+                }
+            }
+        }
+        return;
+    }
+    thinking.candidates.clear();
+    if let Ok((mut trans, mut status)) = cards.get_mut(selected) {
+        *status = CardStatus::Normal;
+        // Offset up the card so that it doesn't go through the already-played one
+        trans.translation.y += 1.0;
+    }
     cmds.entity(selected).remove::<OppoCard>();
     card_events.send(PlayCard::new(selected, Participant::Oppo));
 }
 
-/// Chose from cards in hand which one to play.
-fn chose_card(played: Option<&Card>, in_hand: &[Card]) -> usize {
+fn stop_thinking(
+    mut thinking: ResMut<Thinking>,
+    mut suspicion: ResMut<Suspicion>,
+    mut plan: ResMut<LeadPlan>,
+    mut tells: ResMut<Tells>,
+    mut whispers: ResMut<Whispers>,
+) {
+    *thinking = Thinking::default();
+    *suspicion = Suspicion::default();
+    *plan = LeadPlan::default();
+    *tells = Tells::default();
+    *whispers = Whispers::default();
+}
+
+/// How the oppo plays this turn, on top of its [`AiWeights`].
+#[derive(Clone, Copy)]
+struct Stance {
+    /// The oppo prefers its highest cards, see [`Suspicion`].
+    aggressive: bool,
+    initiative: InitiativeRule,
+    /// [`WordOfPower::Egeq`] cards pay out even on the losing side, see
+    /// [`GameRules::egeq_pays_on_loss`].
+    egeq_pays_on_loss: bool,
+}
+impl Stance {
+    /// Whether to lead with the highest card rather than a random one.
+    ///
+    /// When the winner leads the next battle, the oppo tries to keep the
+    /// lead.
+    fn leads_high(self, weights: &AiWeights) -> bool {
+        weights.lead_high || self.aggressive || self.initiative == InitiativeRule::WinnerLeads
+    }
+}
+
+/// Rank cards in hand, from the least to the most desirable to play.
+///
+/// The last [`Candidate`] is the card to play.
+///
+/// Playing an [`WordOfPower::Egeq`] card gives the player a seed, or points
+/// if they can't hold more, worth `egeq_cost` points to the player. It only
+/// pays out if its side doesn't lose the battle, unless the [`Stance`] says
+/// otherwise.
+///
+/// The `weights` of the opponent change which cards it prefers. An
+/// aggressive [`Stance`] prefers the highest cards: the oppo leads with the
+/// highest one and wins with the highest winning one.
+///
+/// Late in the game, according to the `public` info, the oppo denies the
+/// player what it can. It beats an Egeq card, so that it doesn't pay out,
+/// even with a word card it would rather keep, and doesn't lead with cards
+/// helping the player: an Egeq card, or a Geh card while the player may hold
+/// a Zero.
+fn chose_card(
+    played: Option<&Card>,
+    in_hand: &[Card],
+    public: &PublicInfo,
+    egeq_cost: i32,
+    weights: &AiWeights,
+    stance: Stance,
+) -> Vec<Candidate> {
     // TODO: replace all logic by simple call to Card::bonus_points
     use BattleOutcome::{Tie, Win};
     use Value::Zero;
-    use WordOfPower::Geh;
+    use WordOfPower::{Egeq, Geh};
 
-    let played = if let Some(played) = played {
-        played
-    } else {
-        // Actual random card otherwise it's too easy
-        return randusize(..in_hand.len());
-    };
-    let wins = |this: &&Card| this.beats(played) == Win;
-    let zero12 = |card: &&Card| {
+    let zero12 = |card: &Card, played: &Card| {
         let bonus = played.word == Some(Geh) || card.word == Some(Geh);
-        (card.value == Zero && bonus) as i32 * 12
+        (card.value == Zero && bonus) as i32 * GEH_ZERO_POINTS
     };
-    let card_value = |card: &&Card| card.value as i32 + zero12(card);
-    let lowest_value = || in_hand.iter().min_by_key(card_value);
-    let a_tie = || in_hand.iter().find(|this| this.beats(played) == Tie);
-    let winning = in_hand.iter().filter(wins).min();
-
-    // unwrap: we know we have at least one card (asserted in `play_card`)
-    let chosen = winning.or_else(a_tie).or_else(lowest_value).unwrap();
-    in_hand.iter().position(|e| e == chosen).unwrap()
+    let gift = |card: &Card| (card.word == Some(Egeq)) as i32 * egeq_cost;
+    let lost_gift = |card: &Card| stance.egeq_pays_on_loss as i32 * gift(card);
+    let card_value =
+        |card: &Card, played: &Card| card.value as i32 + zero12(card, played) + lost_gift(card);
+    let late = public.is_late();
+    let beats_egeq = matches!(played, Some(played) if played.word == Some(Egeq));
+    let denies_seed = late && beats_egeq && !stance.egeq_pays_on_loss;
+    let hoarding = |card: &Card| {
+        let spent_to_deny = denies_seed && card.beats(played.unwrap()) == Win;
+        (card.word.is_some() && !spent_to_deny) as i32 * weights.word_hoarding
+    };
+    let helps_player = |card: &Card| {
+        let player_zero = card.word == Some(Geh) && public.player_may_hold(Zero);
+        late as i32 * (gift(card) + player_zero as i32 * GEH_ZERO_POINTS)
+    };
+    let random = played.is_none().then(|| randusize(..in_hand.len()));
+    let rank = |index: usize, card: &Card| match played {
+        None if stance.leads_high(weights) => card.value as i32 - helps_player(card),
+        // Actual random card otherwise it's too easy
+        None => (Some(index) == random) as i32 - helps_player(card),
+        // Prefer the lowest winning card, then a tie, then the lowest value
+        Some(played) => match card.beats(played) {
+            Win if stance.aggressive => 2000 + card.value as i32 - gift(card),
+            Win => 2000 - card.value as i32 - gift(card),
+            Tie => 1000 - gift(card),
+            _ => -card_value(card, played),
+        },
+    };
+    let score = |index: usize, card: &Card| rank(index, card) - hoarding(card);
+    let mut candidates: Vec<_> = in_hand
+        .iter()
+        .enumerate()
+        .map(|(index, card)| Candidate { index, score: score(index, card) })
+        .collect();
+    // On equal scores, the first card in hand is preferred
+    candidates.sort_by_key(|c| (c.score, std::cmp::Reverse(c.index)));
+    candidates
 }
 
 pub struct Plugin(pub GameState);
@@ -115,28 +591,71 @@ impl BevyPlugin for Plugin {
     fn build(&self, app: &mut App) {
         use crate::system_helper::EasySystemSetCtor;
         #[cfg(feature = "debug")]
-        app.register_inspectable::<OppoCard>();
-        app.add_system_set(TurnState::Draw.on_enter(draw_hand))
-            .add_system_set(TurnState::Oppo.on_enter(play_card))
-            .add_system_set(self.0.on_update(update_oppo_hand));
+        app.register_inspectable::<OppoCard>()
+            .add_system_set(self.0.on_update(give_cards));
+        app.init_resource::<Thinking>()
+            .init_resource::<Suspicion>()
+            .init_resource::<LeadPlan>()
+            .init_resource::<Tells>()
+            .init_resource::<Whispers>()
+            .add_system_set(TurnState::Draw.on_enter(draw_hand))
+            .add_system(restore_hand.after("restore_table"))
+            .add_system_set(TurnState::Oppo.on_enter(start_thinking))
+            .add_system_set(TurnState::Oppo.on_update(think))
+            .add_system_set(self.0.on_update(update_oppo_hand))
+            .add_system_set(self.0.on_update(whisper_words))
+            .add_system_set(self.0.on_update(suspect_cheating))
+            .add_system_set(self.0.on_update(plan_lead.after(suspect_cheating)))
+            .add_system_set(self.0.on_exit(stop_thinking));
     }
 }
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const CALM: Stance = Stance {
+        aggressive: false,
+        initiative: InitiativeRule::Alternate,
+        egeq_pays_on_loss: false,
+    };
+
+    fn seed_value() -> i32 {
+        AiWeights::default().seed_value
+    }
+
+    /// Early in the game, before the player played anything.
+    fn early() -> PublicInfo {
+        PublicInfo { turns_left: 20, player_played: EnumMap::default() }
+    }
+
+    /// Late in the game, after the player played cards of the `played` values.
+    fn late(played: &[Value]) -> PublicInfo {
+        let mut player_played = EnumMap::default();
+        for value in played {
+            player_played[*value] = true;
+        }
+        PublicInfo { turns_left: LATE_GAME_TURNS, player_played }
+    }
+
     macro_rules! cards {
         (war $war:tt ; hand: $($hand:tt),+) => ({
             let war: Card = stringify!($war).parse().unwrap();
             (Some(war), [ $(stringify!($hand).parse().unwrap(),)+ ])
         });
+        (lead ; hand: $($hand:tt),+) => ({
+            (None::<Card>, [ $(stringify!($hand).parse::<Card>().unwrap(),)+ ])
+        });
     }
     #[test]
     fn chose_card_test() {
         macro_rules! test_hand {
-            ([$($state:tt)*] is: $expected:tt $(, $msg:expr)?) => ({
+            ([$($state:tt)*] is: $expected:tt $(, $msg:expr)?) => (
+                test_hand!([$($state)*] knowing early(), is: $expected $(, $msg)?)
+            );
+            ([$($state:tt)*] knowing $public:expr, is: $expected:tt $(, $msg:expr)?) => ({
                 let (pile, hand) = cards!($($state)*);
-                let actual = chose_card(pile.as_ref(), &hand);
+                let weights = AiWeights::default();
+                let actual = chose_card(pile.as_ref(), &hand, &$public, seed_value(), &weights, CALM).last().unwrap().index;
                 let expected: Card = stringify!($expected).parse().unwrap();
                 assert_eq!(hand[actual], expected $(, $msg)?);
             })
@@ -155,5 +674,243 @@ mod tests {
         test_hand!([war 9w; hand: 0_, 1_] is: 1_, "do not lose when swap 0/9w");
         test_hand!([war 9_; hand: 0w, 1_] is: 0w, "chose lowest even in losing 0/9");
         test_hand!([war 5_; hand: 5_, 3_] is: 5_, "prefer tie to loss");
+        test_hand!([war 9_; hand: 1s, 3_] is: 1s, "a seed is not worth much");
+    }
+
+    #[test]
+    fn late_leads_keep_words_back() {
+        use Value::Zero;
+        let high_leader = AiWeights { lead_high: true, ..default() };
+        let chosen = |hand: &[Card], public: &PublicInfo| {
+            let candidates = chose_card(None, hand, public, seed_value(), &high_leader, CALM);
+            hand[candidates.last().unwrap().index].to_string()
+        };
+        let (_, hand) = cards!(lead; hand: 3_, 7_);
+        assert_eq!(chosen(&hand, &early()), "7_", "lead high");
+        let (_, hand) = cards!(lead; hand: 6_, 7s);
+        assert_eq!(
+            chosen(&hand, &early()),
+            "7s",
+            "leading a seed is fine early"
+        );
+        assert_eq!(chosen(&hand, &late(&[])), "6_", "no seed to lead late");
+        let (_, hand) = cards!(lead; hand: 3_, 7z);
+        let geh_lead = "no Geh to lead late while the player may hold a Zero";
+        assert_eq!(chosen(&hand, &late(&[])), "3_", "{geh_lead}");
+        assert_eq!(chosen(&hand, &late(&[Zero])), "7z", "Zero already played");
+    }
+
+    #[test]
+    fn deny_seeds_late_in_the_game() {
+        let hoarder = AiWeights { word_hoarding: 4, ..default() };
+        let chosen = |played: Option<&Card>, hand: &[Card], public: &PublicInfo| {
+            let candidates = chose_card(played, hand, public, seed_value(), &hoarder, CALM);
+            candidates.last().unwrap().index
+        };
+        let (pile, hand) = cards!(war 5s; hand: 6doub, 8_);
+        let kept = chosen(pile.as_ref(), &hand, &early());
+        assert_eq!(kept, 1, "keep the word early");
+        let denied = chosen(pile.as_ref(), &hand, &late(&[]));
+        assert_eq!(denied, 0, "spend the word to deny the seed");
+        let (pile, hand) = cards!(war 5_; hand: 6doub, 8_);
+        let late_no_seed = chosen(pile.as_ref(), &hand, &late(&[]));
+        assert_eq!(late_no_seed, 1, "only against seeds");
+        let on_loss = Stance { egeq_pays_on_loss: true, ..CALM };
+        let (pile, hand) = cards!(war 5s; hand: 6doub, 8_);
+        let candidates = chose_card(pile.as_ref(), &hand, &late(&[]), 0, &hoarder, on_loss);
+        let kept = candidates.last().unwrap().index;
+        assert_eq!(kept, 1, "the seed pays out anyway");
+    }
+
+    #[test]
+    fn avoid_giving_overflowing_seeds() {
+        let (pile, hand) = cards!(war 9_; hand: 1s, 2_);
+        let expected: Card = "1s".parse().unwrap();
+        let weights = AiWeights::default();
+        let on_loss = Stance { egeq_pays_on_loss: true, ..CALM };
+        let chosen = |pile: Option<&Card>, hand: &[Card], cost, stance| {
+            let candidates = chose_card(pile, hand, &early(), cost, &weights, stance);
+            hand[candidates.last().unwrap().index].clone()
+        };
+        assert_eq!(chosen(pile.as_ref(), &hand, 0, on_loss), expected);
+        assert_eq!(
+            chosen(pile.as_ref(), &hand, 2, CALM),
+            expected,
+            "a lost seed"
+        );
+        let low: Card = "2_".parse().unwrap();
+        assert_eq!(chosen(pile.as_ref(), &hand, 2, on_loss), low);
+
+        let (pile, hand) = cards!(war 1_; hand: 2s, 3_);
+        let (seed, three) = (hand[0].clone(), hand[1].clone());
+        assert_eq!(chosen(pile.as_ref(), &hand, 0, CALM), seed);
+        assert_eq!(chosen(pile.as_ref(), &hand, 2, CALM), three, "a won seed");
+    }
+
+    #[test]
+    fn candidates_ordered_by_score() {
+        let (pile, hand) = cards!(war 5_; hand: 3_, 7_, 5_, 6_);
+        let weights = AiWeights::default();
+        let public = early();
+        let order: Vec<_> = chose_card(pile.as_ref(), &hand, &public, seed_value(), &weights, CALM)
+            .iter()
+            .map(|c| c.index)
+            .collect();
+        assert_eq!(order, [0, 2, 1, 3], "losing, tie, then winning cards");
+        let (_, hand) = cards!(war 0_; hand: 1_, 2_, 3_);
+        let candidates = chose_card(None, &hand, &early(), seed_value(), &weights, CALM);
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(candidates.iter().filter(|c| c.score == 1).count(), 1);
+    }
+
+    #[test]
+    fn hoarder_keeps_word_cards() {
+        let hoarder = AiWeights { word_hoarding: 4, lead_high: true, ..default() };
+        let chosen = |played: Option<&Card>, hand: &[Card]| {
+            let candidates = chose_card(played, hand, &early(), seed_value(), &hoarder, CALM);
+            hand[candidates.last().unwrap().index].clone()
+        };
+        let (pile, hand) = cards!(war 5_; hand: 6doub, 8_);
+        assert_eq!(
+            chosen(pile.as_ref(), &hand),
+            hand[1],
+            "win without the word"
+        );
+        let (pile, hand) = cards!(war 1_; hand: 2doub, 9_);
+        assert_eq!(
+            chosen(pile.as_ref(), &hand),
+            hand[0],
+            "unless it costs too much"
+        );
+        let (_, hand) = cards!(war 0_; hand: 3_, 9seed, 7_);
+        assert_eq!(chosen(None, &hand), hand[2], "lead high, but keep words");
+        let (_, hand) = cards!(war 0_; hand: 3_, 9_, 7_);
+        assert_eq!(chosen(None, &hand), hand[1], "lead with the highest card");
+    }
+
+    #[test]
+    fn suspicious_oppo_plays_high() {
+        let chosen = |played: Option<&Card>, hand: &[Card], aggressive| {
+            let weights = AiWeights::default();
+            let stance = Stance { aggressive, ..CALM };
+            let candidates = chose_card(played, hand, &early(), seed_value(), &weights, stance);
+            hand[candidates.last().unwrap().index].clone()
+        };
+        let (pile, hand) = cards!(war 1_; hand: 2_, 7_, 5_);
+        assert_eq!(chosen(pile.as_ref(), &hand, false), hand[0]);
+        assert_eq!(chosen(pile.as_ref(), &hand, true), hand[1], "win big");
+        let (pile, hand) = cards!(war 8_; hand: 2_, 7_, 5_);
+        assert_eq!(
+            chosen(pile.as_ref(), &hand, true),
+            hand[0],
+            "still save high cards when losing"
+        );
+        let (_, hand) = cards!(war 0_; hand: 3_, 9_, 7_);
+        assert_eq!(
+            chosen(None, &hand, true),
+            hand[1],
+            "lead with the highest card"
+        );
+    }
+
+    #[test]
+    fn oppo_keeps_the_lead_when_winner_leads() {
+        let chosen = |played: Option<&Card>, hand: &[Card], initiative| {
+            let weights = AiWeights::default();
+            let stance = Stance { initiative, ..CALM };
+            let candidates = chose_card(played, hand, &early(), seed_value(), &weights, stance);
+            hand[candidates.last().unwrap().index].clone()
+        };
+        let (_, hand) = cards!(war 0_; hand: 3_, 9_, 7_);
+        let winner_leads = InitiativeRule::WinnerLeads;
+        assert_eq!(chosen(None, &hand, winner_leads), hand[1], "lead high");
+        let (pile, hand) = cards!(war 1_; hand: 2_, 7_, 5_);
+        assert_eq!(
+            chosen(pile.as_ref(), &hand, winner_leads),
+            hand[0],
+            "still win with the lowest card"
+        );
+    }
+
+    #[test]
+    fn suspicion_lasts_one_turn() {
+        let mut suspicion = Suspicion::default();
+        assert!(!suspicion.take());
+        suspicion.suspect(Distraction::Seed);
+        assert!(suspicion.take());
+        assert!(!suspicion.take(), "forgotten even without cheating");
+    }
+
+    #[test]
+    fn trinket_suspicion_lasts_two_turns() {
+        let mut suspicion = Suspicion::default();
+        suspicion.suspect(Distraction::Trinket);
+        assert!(suspicion.take());
+        suspicion.suspect(Distraction::Seed);
+        assert!(suspicion.take(), "a seed doesn't shorten it");
+        assert!(!suspicion.take());
+    }
+
+    #[test]
+    fn tells_match_planned_leads() {
+        let weights = AiWeights { lead_high: true, ..default() };
+        let plan = |hand: &[Card], public: &PublicInfo| {
+            let candidates = chose_card(None, hand, public, seed_value(), &weights, CALM);
+            hand[candidates.last().unwrap().index].clone()
+        };
+        let (_, hand) = cards!(lead; hand: 3_, 9_, 7_);
+        let lead = plan(&hand, &early());
+        let tells = read_tells(&hand, Some(&lead));
+        assert_eq!(tells, Tells { leans: true, taps: false });
+        let (_, hand) = cards!(lead; hand: 3_, 9z, 7w);
+        let lead = plan(&hand, &late(&[]));
+        assert_eq!(lead, hand[2], "no Geh to lead late");
+        let tells = read_tells(&hand, Some(&lead));
+        assert_eq!(tells, Tells { leans: false, taps: true });
+        assert_eq!(read_tells(&hand, None), Tells { leans: false, taps: true });
+    }
+
+    #[test]
+    fn oppo_whispers_drawn_words() {
+        use WordOfPower::{Geh, Qube};
+        let (_, drawn) = cards!(lead; hand: 0z, 3_, 9d);
+        let gameplay = GameplaySettings::default();
+        let quiet = GameplaySettings { demon_whispers: false, ..default() };
+        let mut whispers = Whispers::default();
+        whispers.queue(&drawn, &quiet, || 0.0);
+        assert!(whispers.0.is_empty(), "whispers are turned off");
+        let (_, plain) = cards!(lead; hand: 1_, 5_, 8_);
+        whispers.queue(&plain, &gameplay, || 0.0);
+        assert!(whispers.0.is_empty(), "no word to whisper");
+
+        let rolls = std::cell::Cell::new(0.5);
+        let roll = || rolls.replace(1.0);
+        whispers.queue(&drawn, &gameplay, roll);
+        let queued = [
+            Whisper { word: Geh, delay: MAX_WHISPER_DELAY / 2.0 },
+            Whisper { word: Qube, delay: MAX_WHISPER_DELAY },
+        ];
+        assert_eq!(whispers.0, queued);
+        assert!(whispers.advance(0.5).is_empty(), "not yet");
+        assert_eq!(whispers.advance(0.5), [Geh]);
+        assert!(whispers.advance(0.5).is_empty());
+        assert_eq!(whispers.advance(MAX_WHISPER_DELAY), [Qube]);
+        assert!(whispers.0.is_empty(), "each word is whispered once");
+    }
+
+    #[test]
+    fn oppo_sticks_to_planned_lead() {
+        let (_, hand) = cards!(lead; hand: 3_, 9_, 7_);
+        let lead = Some(hand[0].clone());
+        let plan = LeadPlan { hand: hand.to_vec(), aggressive: false, lead };
+        let weights = AiWeights::default();
+        let mut candidates = chose_card(None, &hand, &early(), seed_value(), &weights, CALM);
+        stick_to_plan(&mut candidates, plan.lead_index(&hand, false).unwrap());
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(candidates.last().unwrap().index, 0);
+        let (_, other_hand) = cards!(lead; hand: 3_, 9_, 8_);
+        assert_eq!(plan.lead_index(&other_hand, false), None, "another hand");
+        let suspicious = plan.lead_index(&hand, true);
+        assert_eq!(suspicious, None, "planned before turning aggressive");
     }
 }
@@ -0,0 +1,182 @@
+//! Make the oppo react to how the game goes.
+//!
+//! The oppo slumps when losing a large battle, turns toward the bird and
+//! grumbles when the player distracts it, and its eyes glow with anger when
+//! the player manages to hide a card in their sleeve.
+//!
+//! It also has [`Tells`] an observant player can read: it leans forward when
+//! the player turn starts if it plans to lead with its highest card, and taps
+//! a finger while it holds a Zihbm card.
+use bevy::prelude::{Plugin as BevyPlugin, *};
+
+use crate::{
+    animate::Impulse,
+    audio::AudioRequest,
+    cheat::{BirdEye, BirdPupilRoot, CheatEvent},
+    game_flow::BattleResolved,
+    oppo_hand::{OppoHand, Tells},
+    state::{GameState, TurnState},
+    war::BattleOutcome,
+};
+
+/// Points handed out above which losing a battle makes the oppo slump.
+const BIG_BATTLE: i32 = 8;
+/// Intensity of the [`OppoEyeGlow`] light when the oppo is at its angriest.
+const MAX_GLOW: f32 = 800.0;
+/// Seconds it takes for the oppo to calm down.
+const ANGER_DURATION: f32 = 3.0;
+/// How far the oppo head moves toward the bird when it gets distracted.
+const GLANCE_DISTANCE: f32 = 0.15;
+/// Seconds between two finger taps, see [`Tells::taps`].
+const TAP_PERIOD: f32 = 1.6;
+
+#[derive(Component)]
+pub struct OppoHead;
+
+#[derive(Component)]
+pub struct OppoBody;
+
+/// Light making the oppo eyes glow when it's angry.
+#[derive(Component)]
+pub struct OppoEyeGlow;
+
+/// How angry the oppo is, from 0 to 1, it calms down over time.
+#[derive(Default)]
+struct Anger(f32);
+
+fn react_to_battles(
+    mut cmds: Commands,
+    mut events: EventReader<BattleResolved>,
+    head: Query<Entity, With<OppoHead>>,
+    body: Query<Entity, With<OppoBody>>,
+) {
+    for BattleResolved { outcome, points, .. } in events.iter() {
+        if *outcome == BattleOutcome::Win && *points >= BIG_BATTLE {
+            for entity in head.iter() {
+                cmds.entity(entity)
+                    .insert(Impulse::new(Vec3::new(0.0, -0.3, 0.1), 0.0, 1.5));
+            }
+            for entity in body.iter() {
+                cmds.entity(entity)
+                    .insert(Impulse::new(Vec3::Y * -0.1, 0.0, 1.5));
+            }
+        }
+    }
+}
+
+/// Direction from `head` toward `target`, `GLANCE_DISTANCE` long, in the
+/// space of the head parent, where [`Impulse`] offsets are applied.
+fn glance_toward(head: &Transform, head_global: &GlobalTransform, target: Vec3) -> Vec3 {
+    let parent_rotation = head_global.compute_transform().rotation * head.rotation.inverse();
+    let direction = (target - head_global.translation()).normalize_or_zero();
+    parent_rotation.inverse() * direction * GLANCE_DISTANCE
+}
+
+/// The bird may be distracted, but the oppo sits right in front of the player
+/// and notices the card disappearing.
+///
+/// It also notices the player distracting the bird, and turns toward it.
+fn react_to_cheats(
+    mut cmds: Commands,
+    mut events: EventReader<CheatEvent>,
+    mut anger: ResMut<Anger>,
+    mut audio: EventWriter<AudioRequest>,
+    bird: Res<BirdEye>,
+    head: Query<(Entity, &Transform, &GlobalTransform), With<OppoHead>>,
+    bird_eye: Query<&GlobalTransform, With<BirdPupilRoot>>,
+) {
+    for event in events.iter() {
+        match event {
+            CheatEvent::HideInSleeve(_) if !bird.is_watching => {
+                anger.0 = 1.0;
+                for (entity, ..) in head.iter() {
+                    cmds.entity(entity)
+                        .insert(Impulse::new(Vec3::X * 0.05, 6.0, 2.0));
+                }
+            }
+            CheatEvent::ConfuseBird(_) => {
+                audio.send(AudioRequest::PlayGrumble);
+                let bird_eye = match bird_eye.get_single() {
+                    Ok(bird_eye) => bird_eye.translation(),
+                    Err(_) => continue,
+                };
+                for (entity, transform, global) in head.iter() {
+                    let direction = glance_toward(transform, global, bird_eye);
+                    cmds.entity(entity)
+                        .insert(Impulse::new(direction, 0.0, 2.5));
+                }
+            }
+            CheatEvent::HideInSleeve(_) => {}
+        }
+    }
+}
+
+/// Lean forward when the player turn starts, see [`Tells::leans`].
+fn lean_forward(mut cmds: Commands, tells: Res<Tells>, head: Query<Entity, With<OppoHead>>) {
+    if !tells.leans {
+        return;
+    }
+    for entity in head.iter() {
+        cmds.entity(entity)
+            .insert(Impulse::new(Vec3::new(0.0, -0.05, 0.12), 0.0, 0.8));
+    }
+}
+
+/// Tap the hand holding the cards every [`TAP_PERIOD`] seconds, see
+/// [`Tells::taps`].
+fn tap_finger(
+    mut cmds: Commands,
+    mut since_tap: Local<f32>,
+    tells: Res<Tells>,
+    time: Res<Time>,
+    hand: Query<Entity, With<OppoHand>>,
+) {
+    if !tells.taps {
+        *since_tap = 0.0;
+        return;
+    }
+    *since_tap += time.delta_seconds();
+    if *since_tap < TAP_PERIOD {
+        return;
+    }
+    *since_tap = 0.0;
+    for entity in hand.iter() {
+        cmds.entity(entity)
+            .insert(Impulse::new(Vec3::Y * 0.04, 8.0, 6.0));
+    }
+}
+
+fn glow_eyes(
+    time: Res<Time>,
+    mut anger: ResMut<Anger>,
+    mut glow: Query<&mut PointLight, With<OppoEyeGlow>>,
+) {
+    if anger.0 == 0.0 {
+        return;
+    }
+    anger.0 = (anger.0 - time.delta_seconds() / ANGER_DURATION).max(0.0);
+    for mut light in glow.iter_mut() {
+        light.intensity = anger.0 * MAX_GLOW;
+    }
+}
+
+fn cleanup(mut anger: ResMut<Anger>, mut glow: Query<&mut PointLight, With<OppoEyeGlow>>) {
+    anger.0 = 0.0;
+    for mut light in glow.iter_mut() {
+        light.intensity = 0.0;
+    }
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        app.init_resource::<Anger>()
+            .add_system_set(self.0.on_exit(cleanup))
+            .add_system_set(self.0.on_update(tap_finger))
+            .add_system_set(SystemSet::on_enter(TurnState::Player).with_system(lean_forward))
+            .add_system(react_to_battles)
+            .add_system(react_to_cheats.before("cheat"))
+            .add_system(glow_eyes);
+    }
+}
@@ -0,0 +1,294 @@
+//! Opponents the player can face, selected in the main menu.
+//!
+//! Opponents are listed in [`OPPONENTS_FILE`], each entry sets the oppo deck,
+//! how the AI ranks cards, how the bird watches the player and the gameover
+//! texts. Adding an entry to the file adds an opponent, no need to recompile.
+//!
+//! The selected entry becomes the [`OpponentProfile`] resource, read by the
+//! systems it affects.
+//!
+//! With [`GameplaySettings::adaptive_difficulty`], the AI of each game is the
+//! selected one made [`Stats::easing`] steps easier, never harder: the
+//! selected opponent is the most difficult one the player faces.
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::{Plugin as BevyPlugin, *},
+    reflect::TypeUuid,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{
+    deck::{Deck, OPPO_DECK},
+    settings::GameplaySettings,
+    state::GameState,
+    stats::Stats,
+    EndReason,
+};
+
+const OPPONENTS_FILE: &str = "opponents.ron";
+/// Most steps the AI can get easier than the selected opponent, see
+/// [`AiWeights::eased`].
+pub const MAX_EASING: u32 = 3;
+
+/// How the AI ranks cards, see [`crate::oppo_hand`].
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct AiWeights {
+    /// How many points the AI thinks a seed is worth to the player.
+    pub seed_value: i32,
+    /// How much less the AI wants to play a card with a word of power, it
+    /// keeps them in hand unless they are worth it.
+    pub word_hoarding: i32,
+    /// Open battles with the highest card rather than a random one.
+    pub lead_high: bool,
+    /// Give away the next plays, see [`crate::oppo_hand::Tells`]. Off for
+    /// the hardest opponents.
+    pub tells: bool,
+}
+impl Default for AiWeights {
+    fn default() -> Self {
+        Self {
+            seed_value: 1,
+            word_hoarding: 0,
+            lead_high: false,
+            tells: true,
+        }
+    }
+}
+impl AiWeights {
+    /// These weights made `steps` easier, at most [`MAX_EASING`].
+    ///
+    /// Each step gives something away: first the tells, then leading with
+    /// the highest card, then caring about seeds and words.
+    pub fn eased(&self, steps: u32) -> Self {
+        let mut weights = self.clone();
+        for _ in 0..steps.min(MAX_EASING) {
+            if !weights.tells {
+                weights.tells = true;
+            } else if weights.lead_high {
+                weights.lead_high = false;
+            } else {
+                weights.seed_value = (weights.seed_value - 1).max(0);
+                weights.word_hoarding /= 2;
+            }
+        }
+        weights
+    }
+}
+
+/// How the bird watches the player, see [`crate::cheat`].
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct BirdProfile {
+    /// Seconds a grabbed card can hover the sleeve under the bird watch
+    /// before it gets suspicious.
+    pub suspicion_delay: f32,
+    /// Cards the player can sleeve before a distracted bird watches again.
+    pub sleeves_per_distraction: usize,
+}
+impl Default for BirdProfile {
+    fn default() -> Self {
+        Self { suspicion_delay: 1.0, sleeves_per_distraction: 1 }
+    }
+}
+
+/// An opponent in [`OPPONENTS_FILE`].
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+struct OpponentEntry {
+    /// Key of the opponent name in [`Strings`].
+    ///
+    /// [`Strings`]: crate::lang::Strings
+    name: String,
+    /// Path of the oppo deck, in `assets/`.
+    deck: String,
+    #[serde(default)]
+    ai: AiWeights,
+    #[serde(default)]
+    bird: BirdProfile,
+    /// Group of the gameover text keys, `gameover.victory` is shown when
+    /// winning against an opponent with the `gameover` group.
+    #[serde(default = "default_gameover")]
+    gameover: String,
+}
+impl Default for OpponentEntry {
+    fn default() -> Self {
+        Self {
+            name: "opponent.warlock".to_owned(),
+            deck: OPPO_DECK.to_owned(),
+            ai: AiWeights::default(),
+            bird: BirdProfile::default(),
+            gameover: default_gameover(),
+        }
+    }
+}
+
+fn default_gameover() -> String {
+    "gameover".to_owned()
+}
+
+/// The content of [`OPPONENTS_FILE`].
+#[derive(Debug, TypeUuid)]
+#[uuid = "5c7e8d1a-3f0b-4b6e-9d2a-8e41c06f7a93"]
+struct Opponents(Vec<OpponentEntry>);
+
+#[derive(Default)]
+struct OpponentsLoader;
+impl AssetLoader for OpponentsLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), anyhow::Error>> {
+        Box::pin(async move {
+            let entries = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(Opponents(entries)));
+            Ok(())
+        })
+    }
+    fn extensions(&self) -> &[&str] {
+        &["ron"]
+    }
+}
+
+struct OpponentsHandle(Handle<Opponents>);
+impl FromWorld for OpponentsHandle {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.get_resource::<AssetServer>().unwrap();
+        Self(assets.load(OPPONENTS_FILE))
+    }
+}
+
+/// Index of the selected opponent in [`OPPONENTS_FILE`], wraps around.
+#[derive(Default)]
+pub struct SelectedOpponent(pub usize);
+
+/// The opponent of the next games.
+pub struct OpponentProfile {
+    /// Key of the opponent name in [`Strings`].
+    ///
+    /// [`Strings`]: crate::lang::Strings
+    pub name_key: String,
+    /// Path of the oppo deck, in `assets/`.
+    pub deck_file: String,
+    pub deck: Handle<Deck>,
+    /// The AI of the current game, see [`ease_opponent`].
+    pub ai: AiWeights,
+    pub bird: BirdProfile,
+    gameover: String,
+    /// The AI of the opponent entry, the hardest the player faces.
+    selected_ai: AiWeights,
+}
+impl OpponentProfile {
+    fn new(entry: &OpponentEntry, assets: &AssetServer) -> Self {
+        Self {
+            name_key: entry.name.clone(),
+            deck_file: entry.deck.clone(),
+            deck: assets.load(&entry.deck),
+            ai: entry.ai.clone(),
+            bird: entry.bird.clone(),
+            gameover: entry.gameover.clone(),
+            selected_ai: entry.ai.clone(),
+        }
+    }
+    /// Key of the text shown when the game ends for `reason`.
+    pub fn gameover_key(&self, reason: EndReason) -> String {
+        format!("{}.{}", self.gameover, reason.name())
+    }
+}
+impl FromWorld for OpponentProfile {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.get_resource::<AssetServer>().unwrap();
+        Self::new(&OpponentEntry::default(), assets)
+    }
+}
+
+fn select_opponent(
+    selected: Res<SelectedOpponent>,
+    handle: Res<OpponentsHandle>,
+    opponents: Res<Assets<Opponents>>,
+    assets: Res<AssetServer>,
+    mut profile: ResMut<OpponentProfile>,
+    mut events: EventReader<AssetEvent<Opponents>>,
+) {
+    let reloaded = events.iter().count() != 0;
+    if !reloaded && !selected.is_changed() {
+        return;
+    }
+    let entries = match opponents.get(&handle.0) {
+        Some(Opponents(entries)) if !entries.is_empty() => entries,
+        _ => return,
+    };
+    let entry = &entries[selected.0 % entries.len()];
+    *profile = OpponentProfile::new(entry, &assets);
+}
+
+/// Make the AI of the new game easier according to the player's recent
+/// results, when [`GameplaySettings::adaptive_difficulty`] is on.
+fn ease_opponent(
+    mut profile: ResMut<OpponentProfile>,
+    stats: Res<Stats>,
+    gameplay: Res<GameplaySettings>,
+) {
+    let steps = if gameplay.adaptive_difficulty { stats.easing } else { 0 };
+    profile.ai = profile.selected_ai.eased(steps);
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<Opponents>()
+            .init_asset_loader::<OpponentsLoader>()
+            .init_resource::<OpponentsHandle>()
+            .init_resource::<SelectedOpponent>()
+            .init_resource::<OpponentProfile>()
+            .add_system(select_opponent)
+            .add_system_set(SystemSet::on_enter(GameState::Playing).with_system(ease_opponent));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opponents_file_parses() {
+        let text = include_str!("../assets/opponents.ron");
+        let entries: Vec<OpponentEntry> = ron::de::from_str(text).unwrap();
+        assert_eq!(entries[0], OpponentEntry::default());
+        assert!(entries.len() > 1, "there should be several opponents");
+    }
+
+    #[test]
+    fn entries_default_missing_fields() {
+        let text =
+            r#"[(name: "test.opponent", deck: "decks/test.deck", bird: (suspicion_delay: 0.5))]"#;
+        let entries: Vec<OpponentEntry> = ron::de::from_str(text).unwrap();
+        assert_eq!(entries[0].ai, AiWeights::default());
+        assert_eq!(entries[0].bird.suspicion_delay, 0.5);
+        assert_eq!(entries[0].bird.sleeves_per_distraction, 1);
+        assert_eq!(entries[0].gameover, "gameover");
+    }
+
+    #[test]
+    fn easing_gives_weights_away_in_order() {
+        let hardest = AiWeights {
+            seed_value: 2,
+            word_hoarding: 4,
+            lead_high: true,
+            tells: false,
+        };
+        assert_eq!(hardest.eased(0), hardest, "the selected opponent");
+        assert_eq!(
+            hardest.eased(1),
+            AiWeights { tells: true, ..hardest.clone() }
+        );
+        let no_lead = AiWeights { lead_high: false, tells: true, ..hardest.clone() };
+        assert_eq!(hardest.eased(2), no_lead);
+        let easiest = AiWeights { seed_value: 1, word_hoarding: 2, ..no_lead };
+        assert_eq!(hardest.eased(MAX_EASING), easiest);
+        assert_eq!(hardest.eased(MAX_EASING + 5), easiest, "clamped");
+        let zero = AiWeights { seed_value: 0, ..AiWeights::default() };
+        assert_eq!(zero.eased(MAX_EASING), zero);
+    }
+}
@@ -0,0 +1,290 @@
+//! Keep small bits of data between game sessions.
+//!
+//! Files are stored in the user data directory, or the browser local storage
+//! on wasm. Most are lists of `key=value` lines, read with [`load`].
+//!
+//! The settings file is versioned, see the [`settings`] module.
+use std::{collections::BTreeMap, fmt, str::FromStr};
+
+pub mod settings;
+
+/// The content of a persisted file.
+#[derive(Debug, Default, PartialEq)]
+pub struct Entries(BTreeMap<String, String>);
+impl Entries {
+    /// Read `key=value` lines, ignoring lines without a `=`.
+    pub fn parse(text: &str) -> Self {
+        let entries = text.lines().filter_map(|line| line.split_once('='));
+        let entries = entries.map(|(key, value)| (key.trim().to_owned(), value.trim().to_owned()));
+        Self(entries.collect())
+    }
+    /// The value at `key`, `None` if missing or it doesn't parse as `T`.
+    pub fn get<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.0.get(key)?.parse().ok()
+    }
+    pub fn set(&mut self, key: &str, value: impl ToString) {
+        self.0.insert(key.to_owned(), value.to_string());
+    }
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.0.remove(key)
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    /// The value at `key` as written in the file.
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+}
+impl fmt::Display for Entries {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (key, value) in &self.0 {
+            writeln!(f, "{key}={value}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Where files are persisted, the user data directory or the browser local
+/// storage, see [`platform`].
+pub trait Storage {
+    /// The text of `file`, `None` if it doesn't exist.
+    fn read(&self, file: &str) -> anyhow::Result<Option<String>>;
+    /// Overwrite `file` with `text`, a failed write keeps the previous text.
+    fn write(&mut self, file: &str, text: &str) -> anyhow::Result<()>;
+    /// Move `file` to `to`, overwriting it.
+    fn rename(&mut self, file: &str, to: &str) -> anyhow::Result<()>;
+}
+
+/// Files in the user data directory.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct DataDir(std::path::PathBuf);
+#[cfg(not(target_arch = "wasm32"))]
+impl Storage for DataDir {
+    fn read(&self, file: &str) -> anyhow::Result<Option<String>> {
+        use anyhow::Context;
+        let path = self.0.join(file);
+        match std::fs::read_to_string(&path) {
+            Ok(text) => Ok(Some(text)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("Couldn't read {path:?}")),
+        }
+    }
+    /// Write to a temporary file first, then rename it, so that a crash
+    /// mid-write can't leave a truncated file.
+    fn write(&mut self, file: &str, text: &str) -> anyhow::Result<()> {
+        use anyhow::Context;
+        let dir = &self.0;
+        std::fs::create_dir_all(dir).with_context(|| format!("Couldn't create {dir:?}"))?;
+        let temp = dir.join(format!("{file}.tmp"));
+        std::fs::write(&temp, text).with_context(|| format!("Couldn't write {temp:?}"))?;
+        self.rename(&format!("{file}.tmp"), file)
+    }
+    fn rename(&mut self, file: &str, to: &str) -> anyhow::Result<()> {
+        use anyhow::Context;
+        let (from, to) = (self.0.join(file), self.0.join(to));
+        std::fs::rename(&from, &to).with_context(|| format!("Couldn't move {from:?} to {to:?}"))
+    }
+}
+
+/// The user data directory storage.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn platform() -> anyhow::Result<DataDir> {
+    use std::{env::var_os, path::PathBuf};
+    let base = var_os("APPDATA")
+        .or_else(|| var_os("XDG_DATA_HOME"))
+        .map(PathBuf::from)
+        .or_else(|| var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+        .unwrap_or_else(|| PathBuf::from("."));
+    Ok(DataDir(base.join("warlocks-gambit")))
+}
+
+/// Add `line` at the end of `file`, creating it if needed.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn append_line(file: &str, line: &str) -> anyhow::Result<()> {
+    use anyhow::Context;
+    use std::io::Write;
+    let dir = platform()?.0;
+    std::fs::create_dir_all(&dir).with_context(|| format!("Couldn't create {dir:?}"))?;
+    let path = dir.join(file);
+    let mut opened = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .with_context(|| format!("Couldn't open {path:?}"))?;
+    writeln!(opened, "{line}").with_context(|| format!("Couldn't append to {path:?}"))
+}
+
+/// Save `bytes` as `file` in the `screenshots` directory of the user data
+/// directory, where the player can take it out of the game, returning its
+/// path.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn export(file: &str, bytes: &[u8]) -> anyhow::Result<String> {
+    use anyhow::Context;
+    let dir = platform()?.0.join("screenshots");
+    std::fs::create_dir_all(&dir).with_context(|| format!("Couldn't create {dir:?}"))?;
+    let path = dir.join(file);
+    std::fs::write(&path, bytes).with_context(|| format!("Couldn't write {path:?}"))?;
+    Ok(path.display().to_string())
+}
+
+/// Offer `bytes` as a `file` download, returning its name.
+#[cfg(target_arch = "wasm32")]
+pub fn export(file: &str, bytes: &[u8]) -> anyhow::Result<String> {
+    use wasm_bindgen::{JsCast, JsValue};
+    let js_err = |err: JsValue| anyhow::anyhow!("Couldn't download {file}: {err:?}");
+    let window = web_sys::window().ok_or_else(|| anyhow::anyhow!("No browser window"))?;
+    let document = window
+        .document()
+        .ok_or_else(|| anyhow::anyhow!("No browser document"))?;
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes));
+    let mut options = web_sys::BlobPropertyBag::new();
+    options.type_("image/png");
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(&parts, &options);
+    let url = web_sys::Url::create_object_url_with_blob(&blob.map_err(js_err)?);
+    let url = url.map_err(js_err)?;
+    let link = document.create_element("a").map_err(js_err)?;
+    let link: web_sys::HtmlAnchorElement = link.unchecked_into();
+    link.set_href(&url);
+    link.set_download(file);
+    link.click();
+    web_sys::Url::revoke_object_url(&url).map_err(js_err)?;
+    Ok(file.to_owned())
+}
+
+/// The browser key-value storage API, so that [`LocalStorage`] can be tested
+/// outside of a browser.
+#[cfg(any(target_arch = "wasm32", test))]
+pub trait WebStorage {
+    fn get_item(&self, key: &str) -> Result<Option<String>, String>;
+    fn set_item(&self, key: &str, value: &str) -> Result<(), String>;
+    fn remove_item(&self, key: &str) -> Result<(), String>;
+}
+#[cfg(target_arch = "wasm32")]
+impl WebStorage for web_sys::Storage {
+    fn get_item(&self, key: &str) -> Result<Option<String>, String> {
+        web_sys::Storage::get_item(self, key).map_err(|err| format!("{err:?}"))
+    }
+    fn set_item(&self, key: &str, value: &str) -> Result<(), String> {
+        web_sys::Storage::set_item(self, key, value).map_err(|err| format!("{err:?}"))
+    }
+    fn remove_item(&self, key: &str) -> Result<(), String> {
+        web_sys::Storage::remove_item(self, key).map_err(|err| format!("{err:?}"))
+    }
+}
+
+/// Files as items of the browser local storage, setting an item is atomic.
+#[cfg(any(target_arch = "wasm32", test))]
+pub struct LocalStorage<S>(S);
+#[cfg(any(target_arch = "wasm32", test))]
+impl<S: WebStorage> Storage for LocalStorage<S> {
+    fn read(&self, file: &str) -> anyhow::Result<Option<String>> {
+        let key = format!("warlocks-gambit/{file}");
+        let read = self.0.get_item(&key);
+        read.map_err(|err| anyhow::anyhow!("Couldn't read {key}: {err}"))
+    }
+    fn write(&mut self, file: &str, text: &str) -> anyhow::Result<()> {
+        let key = format!("warlocks-gambit/{file}");
+        let written = self.0.set_item(&key, text);
+        written.map_err(|err| anyhow::anyhow!("Couldn't write {key}: {err}"))
+    }
+    fn rename(&mut self, file: &str, to: &str) -> anyhow::Result<()> {
+        if let Some(text) = self.read(file)? {
+            self.write(to, &text)?;
+        }
+        let key = format!("warlocks-gambit/{file}");
+        let removed = self.0.remove_item(&key);
+        removed.map_err(|err| anyhow::anyhow!("Couldn't remove {key}: {err}"))
+    }
+}
+
+/// The browser local storage.
+#[cfg(target_arch = "wasm32")]
+pub fn platform() -> anyhow::Result<LocalStorage<web_sys::Storage>> {
+    let window = web_sys::window().ok_or_else(|| anyhow::anyhow!("No browser window"))?;
+    match window.local_storage() {
+        Ok(Some(storage)) => Ok(LocalStorage(storage)),
+        _ => Err(anyhow::anyhow!("Local storage isn't available")),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn append_line(file: &str, line: &str) -> anyhow::Result<()> {
+    let text = read(file)?;
+    write(file, &format!("{text}{line}\n"))
+}
+
+/// In-memory [`LocalStorage`], for tests.
+#[cfg(test)]
+pub type MemoryStorage = LocalStorage<std::cell::RefCell<BTreeMap<String, String>>>;
+#[cfg(test)]
+impl MemoryStorage {
+    pub fn new(files: &[(&str, &str)]) -> Self {
+        let items = files
+            .iter()
+            .map(|(file, text)| (format!("warlocks-gambit/{file}"), text.to_string()));
+        Self(std::cell::RefCell::new(items.collect()))
+    }
+}
+#[cfg(test)]
+impl WebStorage for std::cell::RefCell<BTreeMap<String, String>> {
+    fn get_item(&self, key: &str) -> Result<Option<String>, String> {
+        Ok(self.borrow().get(key).cloned())
+    }
+    fn set_item(&self, key: &str, value: &str) -> Result<(), String> {
+        self.borrow_mut().insert(key.to_owned(), value.to_owned());
+        Ok(())
+    }
+    fn remove_item(&self, key: &str) -> Result<(), String> {
+        self.borrow_mut().remove(key);
+        Ok(())
+    }
+}
+
+/// The text of `file`, empty if it doesn't exist.
+pub fn read(file: &str) -> anyhow::Result<String> {
+    Ok(platform()?.read(file)?.unwrap_or_default())
+}
+
+/// Overwrite `file` with `text`.
+pub fn write(file: &str, text: &str) -> anyhow::Result<()> {
+    platform()?.write(file, text)
+}
+
+/// Read the `file` entries, a missing file has no entries.
+pub fn load(file: &str) -> anyhow::Result<Entries> {
+    Ok(Entries::parse(&read(file)?))
+}
+
+/// Overwrite `file` with `entries`.
+pub fn save(file: &str, entries: &Entries) -> anyhow::Result<()> {
+    write(file, &entries.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_roundtrip() {
+        let mut entries = Entries::default();
+        entries.set("wins", 3);
+        entries.set("seen_rules", true);
+        let text = entries.to_string();
+        assert_eq!(text, "seen_rules=true\nwins=3\n");
+        assert_eq!(Entries::parse(&text), entries);
+    }
+
+    #[test]
+    fn entries_parse_garbage() {
+        let entries = Entries::parse("wins = 4\nnonsense\nlosses=many\n\n=\n");
+        assert_eq!(entries.get::<u32>("wins"), Some(4));
+        assert_eq!(entries.get::<u32>("losses"), None);
+        assert_eq!(entries.get::<u32>("missing"), None);
+    }
+}
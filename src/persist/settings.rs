@@ -0,0 +1,141 @@
+//! The versioned settings file.
+//!
+//! Files from older versions are migrated to the current [`VERSION`] when
+//! read. An unreadable file is moved to [`BACKUP`] and the game starts with
+//! default settings.
+//!
+//! This only handles the file [`Entries`], the live resources are built from
+//! them in [`crate::settings`], so that both can evolve independently.
+use anyhow::{anyhow, bail};
+use bevy::prelude::warn;
+
+use super::{Entries, Storage};
+
+pub const FILE: &str = "settings.txt";
+pub const BACKUP: &str = "settings.bak";
+/// Version of the settings file written by this version of the game.
+///
+/// 1. Unversioned `key=value` lines.
+/// 2. Gameplay settings under `gameplay.`, like bindings under `key.`.
+pub const VERSION: u32 = 2;
+/// Gameplay settings keys of version 1.
+const V1_GAMEPLAY_KEYS: [&str; 5] = [
+    "deck_reveal",
+    "fast_mode",
+    "reduce_motion",
+    "low_spec",
+    "blunder_warning",
+];
+
+/// The settings read with [`load`].
+#[derive(Debug, Default, PartialEq)]
+pub struct Loaded {
+    pub entries: Entries,
+    /// The file was unreadable and got moved to [`BACKUP`], `entries` is
+    /// empty.
+    pub reset: bool,
+}
+
+/// Upgrade `entries`, written by the settings file `version`, to [`VERSION`].
+fn migrate(mut entries: Entries, version: u32) -> anyhow::Result<Entries> {
+    if version == 0 || version > VERSION {
+        bail!("Unknown settings version {version}");
+    }
+    if version < 2 {
+        for key in V1_GAMEPLAY_KEYS {
+            if let Some(value) = entries.remove(key) {
+                entries.set(&format!("gameplay.{key}"), value);
+            }
+        }
+    }
+    entries.set("version", VERSION);
+    Ok(entries)
+}
+
+/// The settings in `text`, migrated to [`VERSION`].
+fn parse(text: &str) -> anyhow::Result<Entries> {
+    let entries = Entries::parse(text);
+    if entries.is_empty() && !text.trim().is_empty() {
+        bail!("No settings in the file");
+    }
+    let version = match entries.get_str("version") {
+        Some(version) => version
+            .parse()
+            .map_err(|_| anyhow!("Invalid settings version {version}"))?,
+        None => 1,
+    };
+    migrate(entries, version)
+}
+
+/// Read the settings from `storage`, moving the file to [`BACKUP`] if it
+/// can't be read.
+pub fn load(storage: &mut impl Storage) -> Loaded {
+    let parsed = storage.read(FILE).and_then(|text| match text {
+        Some(text) => parse(&text),
+        None => Ok(Entries::default()),
+    });
+    match parsed {
+        Ok(entries) => Loaded { entries, reset: false },
+        Err(err) => {
+            warn!("Resetting settings: {err:#}");
+            if let Err(err) = storage.rename(FILE, BACKUP) {
+                warn!("Couldn't back up the settings: {err:#}");
+            }
+            Loaded { entries: Entries::default(), reset: true }
+        }
+    }
+}
+
+/// Overwrite the settings file with `entries`, adding the [`VERSION`].
+pub fn save(storage: &mut impl Storage, mut entries: Entries) -> anyhow::Result<()> {
+    entries.set("version", VERSION);
+    storage.write(FILE, &entries.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::persist::MemoryStorage;
+
+    const V1: &str = include_str!("fixtures/settings_v1.txt");
+    const V2: &str = include_str!("fixtures/settings_v2.txt");
+    const CORRUPT: &str = include_str!("fixtures/settings_corrupt.txt");
+
+    #[test]
+    fn missing_file_is_default() {
+        let loaded = load(&mut MemoryStorage::new(&[]));
+        assert_eq!(loaded, Loaded::default());
+    }
+
+    #[test]
+    fn migrate_v1() {
+        let mut storage = MemoryStorage::new(&[(FILE, V1)]);
+        let loaded = load(&mut storage);
+        assert!(!loaded.reset);
+        assert_eq!(loaded.entries, Entries::parse(V2));
+        assert_eq!(storage.read(FILE).unwrap().as_deref(), Some(V1));
+    }
+
+    #[test]
+    fn save_load_roundtrip() {
+        let mut storage = MemoryStorage::new(&[]);
+        let entries = Entries::parse("theme=Moonlit\ngameplay.fast_mode=true\n");
+        save(&mut storage, entries).unwrap();
+        let loaded = load(&mut storage).entries;
+        assert_eq!(loaded.get("version"), Some(VERSION));
+        assert_eq!(loaded.get_str("theme"), Some("Moonlit"));
+    }
+
+    #[test]
+    fn corrupt_file_is_backed_up() {
+        for text in [CORRUPT, "version=banana\n", "version=99\ntheme=Moonlit\n"] {
+            let mut storage = MemoryStorage::new(&[(FILE, text)]);
+            let loaded = load(&mut storage);
+            assert_eq!(loaded, Loaded { entries: Entries::default(), reset: true });
+            assert_eq!(storage.read(FILE).unwrap(), None);
+            assert_eq!(storage.read(BACKUP).unwrap().as_deref(), Some(text));
+            let reloaded = load(&mut storage);
+            assert!(!reloaded.reset, "the notice only shows once");
+        }
+    }
+}
@@ -1,11 +1,161 @@
 //! Hotspot for depositing cards: the war pile, and the place where cards go
 //! after a battle.
-use bevy::prelude::{Plugin as BevyPlugin, *};
+//!
+//! Clicking on the player or oppo pile fans out its cards above the table,
+//! see [`InspectingPile`]. Hovering the war pile tells in which order its
+//! cards were played, see [`describe_pile`].
+//!
+//! The fanned out cards that earned bonus points are marked with a star,
+//! hovering them tells in which battle and how many, see [`BonusGrant`].
+//!
+//! What the player points at on the table, piles and the cards in them, is
+//! picked with the [`TableRaycast`] rays, apart from the hand ones, see
+//! [`TablePick`].
+//!
+//! Cards stop moving once they reach their place in the pile, and only move
+//! again when the pile changes, see [`PileCard::settled`]. An [`Emphasis`]
+//! lifts a card off its place for a moment.
+use std::f32::consts::{FRAC_PI_2, PI};
+
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::{Plugin as BevyPlugin, *},
+    render::mesh::{Indices, PrimitiveTopology},
+};
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::{Inspectable, RegisterInspectable};
+use bevy_mod_raycast::{DefaultRaycastingPlugin, RayCastMesh, RayCastMethod, RayCastSource};
 use fastrand::f32 as randf32;
 
-use crate::{state::GameState, Participant};
+use crate::{
+    animate::{exp_approach, exp_approach_quat, GameFeel, GameSpeed},
+    card::CardStatus,
+    game_flow::{BonusGrant, Play, ScoreBonuses},
+    game_ui::{EffectEvent, BONUS_COLOR},
+    lang::Strings,
+    oppo_hand::OppoHand,
+    player_hand::{CardCollisionAssets, CursorPosition, GrabbedCard, PlayerHand},
+    settings::GameplaySettings,
+    state::{GameState, TurnState},
+    war::Card,
+    CardOrigin, Participant,
+};
+
+/// How far the answering card of the war pile is moved toward who played it.
+const ANSWER_OFFSET: f32 = 0.4;
+/// Distance under which a pile card snaps to its place and settles.
+const SETTLE_DISTANCE: f32 = 0.002;
+/// Angle, in radians, under which a pile card snaps to its place and settles.
+const SETTLE_ANGLE: f32 = 0.01;
+/// Inner radius of the [`BonusStar`] points, relative to the outer one.
+const STAR_INNER_RADIUS: f32 = 0.45;
+/// How high an [`Emphasis`] lifts its card, halfway through.
+const EMPHASIS_LIFT: f32 = 0.6;
+
+/// Meshes of the table the player can point at: the pile areas and the
+/// cards in piles, see [`TablePick`].
+///
+/// Hand cards and drop zones have their own rays, so that table meshes never
+/// get in the way of hand hovers.
+pub enum TableRaycast {}
+
+/// What the player points at on the table, from the top [`TableRaycast`] hit.
+///
+/// Only changes when the player points at something else, so that systems
+/// can check [`Res::is_changed`] rather than the rays every frame.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct TablePick {
+    /// The entity the ray hits first: a pile area or a card in a pile.
+    pub hit: Option<Entity>,
+    /// The [`Pile`] pointed at, through its area or one of its cards.
+    pub pile: Option<Entity>,
+    /// The [`PileCard`] pointed at.
+    pub card: Option<Entity>,
+}
+
+/// The entities a [`TableRaycast`] ray may hit, to make a [`TablePick`].
+#[derive(SystemParam)]
+struct TableTargets<'w, 's> {
+    parents: Query<'w, 's, &'static Parent>,
+    piles: Query<'w, 's, (Entity, &'static Pile)>,
+    pile_cards: Query<'w, 's, &'static PileCard>,
+}
+impl<'w, 's> TableTargets<'w, 's> {
+    fn pick(&self, hit: Option<Entity>) -> TablePick {
+        let card = hit.filter(|entity| self.pile_cards.contains(*entity));
+        let pile = match card.and_then(|card| self.pile_cards.get(card).ok()) {
+            Some(card) => self.piles.iter().find(|(_, pile)| pile.which == card.which),
+            None => {
+                let parent = hit.and_then(|area| self.parents.get(area).ok());
+                parent.and_then(|parent| self.piles.get(parent.get()).ok())
+            }
+        };
+        TablePick { hit, pile: pile.map(|(entity, _)| entity), card }
+    }
+}
+
+/// The cards of this pile are fanned out for the player to see them.
+///
+/// Added by clicking on the pile, removed on the next click.
+#[derive(Component)]
+pub struct InspectingPile;
+
+/// Marks an inspected card that earned bonus points, see
+/// [`ScoreBonuses::grant_of`].
+#[derive(Component)]
+struct BonusStar;
+
+struct StarAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+impl FromWorld for StarAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut meshes = world.resource_mut::<Assets<Mesh>>();
+        let mesh = meshes.add(star_mesh());
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let material =
+            materials.add(StandardMaterial { base_color: BONUS_COLOR, unlit: true, ..default() });
+        Self { mesh, material }
+    }
+}
+
+/// The points of a five-pointed star of radius 1, alternating between outer
+/// and inner points, counter-clockwise from the top one.
+fn star_outline() -> [[f32; 2]; 10] {
+    let mut outline = [[0.0; 2]; 10];
+    for (i, point) in outline.iter_mut().enumerate() {
+        let radius = if i % 2 == 0 { 1.0 } else { STAR_INNER_RADIUS };
+        let angle = FRAC_PI_2 + i as f32 * PI / 5.0;
+        *point = [angle.cos() * radius, angle.sin() * radius];
+    }
+    outline
+}
+
+/// A flat star facing +Z, a triangle fan around its center.
+fn star_mesh() -> Mesh {
+    let outline = star_outline();
+    let points = std::iter::once([0.0, 0.0]).chain(outline);
+    let positions: Vec<[f32; 3]> = points.clone().map(|[x, y]| [x, y, 0.0]).collect();
+    let uvs: Vec<[f32; 2]> = points
+        .map(|[x, y]| [x / 2.0 + 0.5, -y / 2.0 + 0.5])
+        .collect();
+    let indices = (0..10u16)
+        .flat_map(|i| [0, i + 1, (i + 1) % 10 + 1])
+        .collect();
+    let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, vec![[0.0, 0.0, 1.0]; 11]);
+    mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+    mesh.set_indices(Some(Indices::U16(indices)));
+    mesh
+}
+
+/// Whether all pile cards reached their position, inspecting piles is not
+/// possible while cards are moving, and turns wait for them, see
+/// [`crate::game_flow`].
+#[derive(Default)]
+pub struct PilesSettled(pub bool);
 
 #[cfg_attr(feature = "debug", derive(Inspectable))]
 #[derive(PartialEq, Clone, Copy)]
@@ -33,9 +183,10 @@ pub struct Pile {
 }
 impl Pile {
     pub fn add_existing(&mut self, entity: Entity) -> PileCard {
+        let sequence = self.stack.len() as u32;
         self.stack.push(entity);
         let which = self.which;
-        PileCard::new(which)
+        PileCard::new(which, sequence)
     }
     pub fn remove(&mut self, entity: Entity) {
         if let Some((old_index, _)) = self.stack.iter().enumerate().find(|(_, e)| **e == entity) {
@@ -48,6 +199,13 @@ impl Pile {
     pub fn cards(&self) -> &[Entity] {
         &self.stack
     }
+    /// Forget all the cards, they must be despawned separately.
+    pub fn clear(&mut self) {
+        self.stack.clear();
+    }
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.stack.contains(&entity)
+    }
 }
 
 #[cfg_attr(feature = "debug", derive(Inspectable))]
@@ -55,47 +213,413 @@ impl Pile {
 pub struct PileCard {
     offset: Transform,
     pub which: PileType,
+    /// Order in which the card was added to the pile, the war pile being
+    /// emptied each turn, this is the order cards were played in this turn.
+    pub sequence: u32,
+    /// The card reached its place in the pile, it isn't moved until the pile
+    /// stack changes or the pile moves.
+    settled: bool,
+}
+
+/// Lift a pile card off its place and settle it back in `duration` seconds,
+/// to draw the eye to it. Removed once over.
+#[derive(Component)]
+pub struct Emphasis {
+    duration: f32,
+    /// Seconds since startup, set once the card first moves with it.
+    start: Option<f64>,
+}
+impl Emphasis {
+    pub fn new(duration: f32) -> Self {
+        Self { duration, start: None }
+    }
+}
+
+/// How high an [`Emphasis`] lasting `duration` lifts its card `elapsed`
+/// seconds in, `None` once it is over.
+fn emphasis_lift(elapsed: f32, duration: f32) -> Option<f32> {
+    let progress = elapsed / duration;
+    (progress < 1.0).then(|| (progress * PI).sin() * EMPHASIS_LIFT)
 }
 
 impl PileCard {
-    fn new(which: PileType) -> Self {
+    fn new(which: PileType, sequence: u32) -> Self {
         let offset = Transform {
             translation: Vec3::new(randf32() * 0.6 - 0.3, 0.0, randf32() * 0.6 - 0.3),
             rotation: Quat::from_rotation_z(randf32() - 0.5),
             scale: Vec3::ONE,
         };
-        Self { offset, which }
+        Self { offset, which, sequence, settled: false }
+    }
+}
+
+/// Move `transform` toward its `target` place in a pile, snapping to it once
+/// close enough. Returns whether it is in place.
+fn approach_pile(transform: &mut Transform, target: &Transform, rate: f32, dt: f32) -> bool {
+    let close = transform.translation.distance(target.translation) < SETTLE_DISTANCE
+        && transform.rotation.angle_between(target.rotation) < SETTLE_ANGLE;
+    if close {
+        transform.translation = target.translation;
+        transform.rotation = target.rotation;
+        return true;
+    }
+    let origin = transform.translation;
+    // give cool effect of falling
+    let horizontal = exp_approach(origin, target.translation, rate, dt);
+    let vertical = exp_approach(origin, target.translation, rate * 0.7, dt);
+    transform.translation = Vec3::new(horizontal.x, vertical.y, horizontal.z);
+    transform.rotation = exp_approach_quat(transform.rotation, target.rotation, rate, dt);
+    false
+}
+
+/// Describe the war pile `plays`, in the order they were made.
+fn play_order_text(strings: &Strings, plays: &[Play]) -> Option<String> {
+    let value = |play: &Play| play.card.value_i32().to_string();
+    let first_key = match plays.first() {
+        Some(Play { who: Participant::Player, .. }) => "pile.you_played",
+        Some(Play { who: Participant::Oppo, .. }) => "pile.oppo_played",
+        None => return None,
+    };
+    let first = strings.format(first_key, &[("value", value(&plays[0]).as_str())]);
+    let second_key = match plays.get(1) {
+        Some(Play { who: Participant::Player, .. }) => "pile.you_answered",
+        Some(Play { who: Participant::Oppo, .. }) => "pile.oppo_answered",
+        None => return Some(first),
+    };
+    let second = value(&plays[1]);
+    let args = [("first", first.as_str()), ("value", second.as_str())];
+    Some(strings.format(second_key, &args))
+}
+
+/// Describe the battle of `grant`, its cards and the bonus points they earned.
+fn bonus_text(strings: &Strings, grant: &BonusGrant) -> String {
+    let card = |card: &Card| match card.word {
+        Some(word) => format!("{}-{word:?}", card.value_i32()),
+        None => card.value_i32().to_string(),
+    };
+    let points = |key, points: i32| {
+        let points = points.to_string();
+        strings.format(key, &[("points", points.as_str())])
+    };
+    let mut bonuses = Vec::new();
+    if grant.player != 0 {
+        bonuses.push(points("pile.bonus_player", grant.player));
+    }
+    if grant.oppo != 0 {
+        bonuses.push(points("pile.bonus_oppo", grant.oppo));
+    }
+    let [(_, player), (_, oppo)] = &grant.cards;
+    let (battle, player, oppo) = (grant.battle.to_string(), card(player), card(oppo));
+    let bonuses = bonuses.join(", ");
+    let args = [
+        ("battle", battle.as_str()),
+        ("player", player.as_str()),
+        ("oppo", oppo.as_str()),
+        ("points", bonuses.as_str()),
+    ];
+    strings.format("pile.bonus", &args)
+}
+
+/// Position relative to the player hand of the `index`th card out of `count`
+/// when fanning out an [`InspectingPile`].
+fn fan_offset(index: usize, count: usize) -> Transform {
+    const RADIUS: f32 = 5.0;
+    let spread = (count as f32 * 0.12).min(1.6);
+    let progress = if count <= 1 {
+        0.5
+    } else {
+        index as f32 / (count - 1) as f32
+    };
+    let angle = (progress - 0.5) * spread;
+    Transform {
+        translation: Vec3::new(
+            angle.sin() * RADIUS,
+            angle.cos() * RADIUS - 2.5,
+            0.3 + index as f32 * 0.01,
+        ),
+        rotation: Quat::from_rotation_z(-angle),
+        scale: Vec3::ONE,
     }
 }
 
+#[allow(clippy::type_complexity)]
 fn move_to_pile(
-    piles: Query<(&GlobalTransform, &Pile)>,
-    mut cards: Query<(&mut Transform, &PileCard)>,
+    piles: Query<(
+        &GlobalTransform,
+        &Pile,
+        Option<&InspectingPile>,
+        ChangeTrackers<Pile>,
+        ChangeTrackers<GlobalTransform>,
+    )>,
+    hand: Query<&GlobalTransform, With<PlayerHand>>,
+    oppo_hand: Query<&GlobalTransform, With<OppoHand>>,
+    mut cards: Query<(
+        &mut Transform,
+        &mut PileCard,
+        Option<&CardOrigin>,
+        Option<&mut Emphasis>,
+    )>,
+    mut cmds: Commands,
+    mut settled: ResMut<PilesSettled>,
     time: Res<Time>,
+    feel: Res<GameFeel>,
+    speed: Res<GameSpeed>,
 ) {
-    let card_speed = 10.0 * time.delta_seconds();
-    for (pile_transform, Pile { stack, .. }) in piles.iter() {
+    let (rate, dt) = (speed.rate(feel.pile_rate), time.delta_seconds());
+    let now = time.seconds_since_startup();
+    let hand = hand.get_single().ok().map(|h| h.compute_transform());
+    let oppo_hand = oppo_hand.get_single().ok().map(|h| h.translation());
+    let mut all_settled = true;
+    for (pile_transform, Pile { stack, which }, inspecting, pile_tracker, moved_tracker) in
+        piles.iter()
+    {
+        // Adding or removing cards changes the stack height of the others
+        let wake = pile_tracker.is_changed() || moved_tracker.is_changed();
         let pile_transform = pile_transform.compute_transform();
         let mut stack_pos = 0_f32;
-        for &entity in stack.iter() {
-            if let Ok((mut transform, PileCard { offset, .. })) = cards.get_mut(entity) {
+        // The answering card of the war pile leans toward who played it
+        let answer_offset = |origin: Option<&CardOrigin>| {
+            let player_hand = hand.map(|h| h.translation);
+            let toward = match origin?.0 {
+                Participant::Player => player_hand?,
+                Participant::Oppo => oppo_hand?,
+            };
+            let direction = (toward - pile_transform.translation) * Vec3::new(1.0, 0.0, 1.0);
+            Some(direction.normalize_or_zero() * ANSWER_OFFSET)
+        };
+        for (i, &entity) in stack.iter().enumerate() {
+            if let Ok((mut transform, mut card, origin, emphasis)) = cards.get_mut(entity) {
+                if let (Some(_), Some(hand)) = (inspecting, hand) {
+                    // Fanned out cards follow the hand, they go back to the
+                    // pile once it isn't inspected anymore
+                    card.settled = false;
+                    let origin = transform.translation;
+                    let fan = fan_offset(i, stack.len());
+                    let target = hand.translation + hand.rotation * fan.translation;
+                    transform.translation = exp_approach(origin, target, rate, dt);
+
+                    let target = hand.rotation * fan.rotation;
+                    transform.rotation = exp_approach_quat(transform.rotation, target, rate, dt);
+                    continue;
+                }
+                let PileCard { offset, sequence, .. } = *card;
+                let answer = if *which == PileType::War && sequence != 0 {
+                    answer_offset(origin).unwrap_or(Vec3::ZERO)
+                } else {
+                    Vec3::ZERO
+                };
+                let lift = emphasis.map_or(0.0, |mut emphasis| {
+                    let start = *emphasis.start.get_or_insert(now);
+                    let lift = emphasis_lift((now - start) as f32, emphasis.duration);
+                    if lift.is_none() {
+                        cmds.entity(entity).remove::<Emphasis>();
+                    }
+                    // Keep following the lift, then settle back in place
+                    card.settled = false;
+                    lift.unwrap_or(0.0)
+                });
                 let pile_pos = pile_transform.translation;
-                let target = pile_pos + offset.translation + Vec3::Y * stack_pos;
-                let origin = transform.translation;
-                // give cool effect of falling
-                let trans_speed = Vec3::new(1., 0.7, 1.) * card_speed;
-                transform.translation += (target - origin) * trans_speed;
-
-                let target = pile_transform.rotation * offset.rotation;
-                let origin = transform.rotation;
-                transform.rotation = origin.lerp(target, card_speed);
+                let height = stack_pos + lift;
+                let target = Transform {
+                    translation: pile_pos + offset.translation + answer + Vec3::Y * height,
+                    rotation: pile_transform.rotation * offset.rotation,
+                    ..default()
+                };
                 stack_pos += 0.008;
+                all_settled &= transform.translation.distance_squared(target.translation) < 0.01;
+                if wake {
+                    card.settled = false;
+                }
+                // Don't touch settled transforms, to avoid propagating them
+                if !card.settled {
+                    card.settled = approach_pile(&mut transform, &target, rate, dt);
+                }
             }
         }
     }
+    settled.0 = all_settled;
+}
+
+fn update_table_raycast(
+    mut source: Query<&mut RayCastSource<TableRaycast>>,
+    cursor: Res<CursorPosition>,
+    windows: Res<Windows>,
+) {
+    if let Some(position) = cursor.valid(&windows) {
+        for mut pick_source in source.iter_mut() {
+            pick_source.cast_method = RayCastMethod::Screenspace(position);
+        }
+    }
+}
+
+/// Update the [`TablePick`] from the top [`TableRaycast`] hit.
+fn pick_table(
+    mut pick: ResMut<TablePick>,
+    raycast: Query<&RayCastSource<TableRaycast>>,
+    targets: TableTargets,
+) {
+    let hit = raycast.iter().find_map(|ray| ray.intersect_top());
+    let picked = targets.pick(hit.map(|(entity, _)| entity));
+    if *pick != picked {
+        *pick = picked;
+    }
+}
+
+/// Let the [`TableRaycast`] rays hit cards once they are in a pile.
+fn add_card_hit_boxes(
+    mut cmds: Commands,
+    collision: Res<CardCollisionAssets>,
+    cards: Query<Entity, Added<PileCard>>,
+) {
+    for card in cards.iter() {
+        let ray_mesh = RayCastMesh::<TableRaycast>::default();
+        cmds.entity(card)
+            .insert_bundle((collision.card_hit_box(), ray_mesh));
+    }
 }
 
-pub struct Plugin(pub GameState);
+/// Show in which order the war pile cards were played while it is hovered,
+/// and the [`BonusGrant`] of the hovered card of an inspected pile.
+fn describe_pile(
+    mut ui: EventWriter<EffectEvent>,
+    mut described: Local<Option<String>>,
+    strings: Res<Strings>,
+    pick: Res<TablePick>,
+    bonuses: Res<ScoreBonuses>,
+    piles: Query<&Pile>,
+    inspected: Query<(), With<InspectingPile>>,
+    cards: Query<(&Card, &CardOrigin, &PileCard)>,
+) {
+    let inspecting = pick.pile.map_or(false, |pile| inspected.contains(pile));
+    let grant = pick
+        .card
+        .filter(|_| inspecting)
+        .and_then(|card| bonuses.grant_of(card));
+    let war_pile = pick
+        .pile
+        .and_then(|pile| piles.get(pile).ok())
+        .filter(|pile| pile.which == PileType::War);
+    let mut plays: Vec<_> = war_pile
+        .iter()
+        .flat_map(|pile| pile.cards())
+        .filter_map(|entity| cards.get(*entity).ok())
+        .map(|(card, origin, pile_card)| {
+            let play = Play { who: origin.0, card: card.clone() };
+            (pile_card.sequence, play)
+        })
+        .collect();
+    plays.sort_by_key(|(sequence, _)| *sequence);
+    let plays: Vec<_> = plays.into_iter().map(|(_, play)| play).collect();
+    let description = match grant {
+        Some(grant) => Some(bonus_text(&strings, grant)),
+        None => play_order_text(&strings, &plays),
+    };
+    if *described == description {
+        return;
+    }
+    *described = description.clone();
+    ui.send(match description {
+        Some(text) if grant.is_some() => EffectEvent::BonusGrant(text),
+        Some(text) => EffectEvent::PlayOrder(text),
+        None => EffectEvent::Hide,
+    });
+}
+
+/// Mark with a [`BonusStar`] the cards of inspected piles that earned bonus
+/// points, remove the stars once the piles are put back.
+fn mark_bonus_cards(
+    mut cmds: Commands,
+    assets: Res<StarAssets>,
+    bonuses: Res<ScoreBonuses>,
+    inspected: Query<&Pile, With<InspectingPile>>,
+    stars: Query<(Entity, &Parent), With<BonusStar>>,
+) {
+    let starred: Vec<Entity> = inspected
+        .iter()
+        .flat_map(Pile::cards)
+        .copied()
+        .filter(|card| bonuses.grant_of(*card).is_some())
+        .collect();
+    for (star, card) in stars.iter() {
+        if !starred.contains(&card.get()) {
+            cmds.entity(star).despawn_recursive();
+        }
+    }
+    for &card in &starred {
+        if stars.iter().any(|(_, parent)| parent.get() == card) {
+            continue;
+        }
+        let star = cmds
+            .spawn_bundle(PbrBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.material.clone(),
+                transform: Transform::from_xyz(0.7, 1.15, 0.02).with_scale(Vec3::splat(0.25)),
+                ..default()
+            })
+            .insert_bundle((BonusStar, Name::new("Bonus star")))
+            .id();
+        cmds.entity(card).add_child(star);
+    }
+}
+
+/// Fan out the clicked pile, or put back the inspected one on any click.
+///
+/// Piles can't be inspected while cards are moving around, while the player
+/// is interacting with their hand, or in streaming mode, see
+/// [`GameplaySettings::streaming`].
+#[allow(clippy::type_complexity)]
+fn inspect_pile(
+    mut cmds: Commands,
+    mouse: Res<Input<MouseButton>>,
+    turn: Res<State<TurnState>>,
+    settled: Res<PilesSettled>,
+    pick: Res<TablePick>,
+    inspected: Query<(Entity, ChangeTrackers<Pile>), With<InspectingPile>>,
+    piles: Query<&Pile>,
+    hand_cards: Query<&CardStatus, Without<PileCard>>,
+    grabbed: Query<(), With<GrabbedCard>>,
+    gameplay: Res<GameplaySettings>,
+) {
+    // A battle ended while inspecting, show the cards going to the piles
+    for (entity, pile) in inspected.iter() {
+        if pile.is_changed() || gameplay.streaming {
+            cmds.entity(entity).remove::<InspectingPile>();
+        }
+    }
+    if !mouse.just_pressed(MouseButton::Left) || gameplay.streaming {
+        return;
+    }
+    if !inspected.is_empty() {
+        for (entity, _) in inspected.iter() {
+            cmds.entity(entity).remove::<InspectingPile>();
+        }
+        return;
+    }
+    let busy_hand = !grabbed.is_empty() || hand_cards.iter().any(|s| *s == CardStatus::Hovered);
+    if !settled.0 || *turn.current() == TurnState::CardPlayed || busy_hand {
+        return;
+    }
+    if let Some(pile) = pick.pile {
+        let is_score_pile = piles.get(pile).map_or(false, |p| p.which != PileType::War);
+        if is_score_pile {
+            cmds.entity(pile).insert(InspectingPile);
+        }
+    }
+}
+
+fn cleanup(
+    mut cmds: Commands,
+    mut pick: ResMut<TablePick>,
+    inspected: Query<Entity, With<InspectingPile>>,
+) {
+    *pick = TablePick::default();
+    for entity in inspected.iter() {
+        cmds.entity(entity).remove::<InspectingPile>();
+    }
+}
+
+/// The piles of all the `states` with a table.
+pub struct Plugin(pub &'static [GameState]);
 impl BevyPlugin for Plugin {
     fn build(&self, app: &mut App) {
         use crate::system_helper::EasySystemSetCtor;
@@ -103,6 +627,216 @@ impl BevyPlugin for Plugin {
         app.register_inspectable::<PileCard>()
             .register_inspectable::<Pile>();
 
-        app.add_system_set(self.0.on_update(move_to_pile));
+        app.add_plugin(DefaultRaycastingPlugin::<TableRaycast>::default())
+            .init_resource::<PilesSettled>()
+            .init_resource::<StarAssets>()
+            .init_resource::<TablePick>();
+        for &state in self.0 {
+            app.add_system_set(state.on_exit(cleanup)).add_system_set(
+                SystemSet::on_update(state)
+                    .with_system(move_to_pile)
+                    .with_system(add_card_hit_boxes)
+                    .with_system(update_table_raycast)
+                    .with_system(pick_table.label("table_pick"))
+                    .with_system(describe_pile.after("table_pick"))
+                    .with_system(mark_bonus_cards)
+                    .with_system(inspect_pile.after("table_pick").after("reveal_click")),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::Language;
+    use bevy::ecs::system::SystemState;
+
+    fn play(who: Participant, card: &str) -> Play {
+        Play { who, card: card.parse().unwrap() }
+    }
+
+    #[test]
+    fn play_order_descriptions() {
+        use Participant::{Oppo, Player};
+        let strings = Strings::new(Language::English);
+        let play_order_text = |plays: &[Play]| play_order_text(&strings, plays);
+        assert_eq!(play_order_text(&[]), None);
+        let player_first = [play(Player, "7_"), play(Oppo, "3seed")];
+        let text = play_order_text(&player_first);
+        assert_eq!(text.as_deref(), Some("You played 7, opponent answered 3"));
+        let oppo_first = [play(Oppo, "0_"), play(Player, "9doub")];
+        let text = play_order_text(&oppo_first);
+        assert_eq!(text.as_deref(), Some("Opponent played 0, you answered 9"));
+        let text = play_order_text(&oppo_first[..1]);
+        assert_eq!(text.as_deref(), Some("Opponent played 0"));
+    }
+
+    #[test]
+    fn bonus_descriptions_and_stars() {
+        let strings = Strings::new(Language::English);
+        let card = |entity, card: &str| (Entity::from_raw(entity), card.parse().unwrap());
+        let mut grant = BonusGrant {
+            battle: 6,
+            cards: [card(1, "0z"), card(2, "9d")],
+            player: 24,
+            oppo: 0,
+            seed_points: 0,
+        };
+        let text = bonus_text(&strings, &grant);
+        assert_eq!(text, "Round 6: 0-Geh vs 9-Qube → +24");
+        grant.cards[1] = card(2, "4_");
+        grant.oppo = 2;
+        let text = bonus_text(&strings, &grant);
+        assert_eq!(text, "Round 6: 0-Geh vs 4 → +24, +2 for the opponent");
+
+        let outline = star_outline();
+        let top = Vec2::from(outline[0]);
+        assert!(top.distance(Vec2::Y) < 1e-6, "points up");
+        let inner = Vec2::from(outline[1]).length();
+        assert!((inner - STAR_INNER_RADIUS).abs() < 1e-6);
+        let mesh = star_mesh();
+        let indices: Vec<_> = mesh.indices().unwrap().iter().collect();
+        assert_eq!(indices.len(), 30);
+        assert_eq!(indices[27..], [0, 10, 1], "the fan closes");
+    }
+
+    #[test]
+    fn pile_cards_settle_and_wake() {
+        let step = |transform: &mut Transform, target: &Transform| {
+            approach_pile(transform, target, 10.0, 1.0 / 60.0)
+        };
+        let mut pile = Pile::new(PileType::Player);
+        let bottom = pile.add_existing(Entity::from_raw(1));
+        let top = pile.add_existing(Entity::from_raw(2));
+        assert!(
+            !bottom.settled && !top.settled,
+            "new cards move to the pile"
+        );
+        assert_eq!(top.sequence, 1);
+
+        let target = Transform::from_xyz(1.0, 0.508, -2.0).with_rotation(top.offset.rotation);
+        let mut transform = Transform::from_xyz(-3.0, 4.0, 0.0);
+        let mut frames = 0;
+        while !step(&mut transform, &target) {
+            frames += 1;
+            assert!(frames < 600, "cards settle in a few seconds at most");
+        }
+        assert_eq!(transform, target, "settled cards are exactly in place");
+        assert!(step(&mut transform, &target));
+        assert_eq!(transform, target, "and stay there");
+
+        // Removing the bottom card lowers the top one, which must move again
+        pile.remove(Entity::from_raw(1));
+        assert_eq!(pile.cards(), [Entity::from_raw(2)]);
+        assert!(!pile.contains(Entity::from_raw(1)));
+        assert!(pile.contains(Entity::from_raw(2)));
+        let lowered = Transform::from_xyz(1.0, 0.5, -2.0).with_rotation(top.offset.rotation);
+        assert!(!step(&mut transform, &lowered));
+        assert_ne!(transform, target);
+    }
+
+    #[test]
+    fn moving_piles_wake_settled_cards() {
+        use std::time::Duration;
+
+        let mut world = World::new();
+        world.insert_resource(Time::default());
+        world.init_resource::<GameFeel>();
+        world.init_resource::<GameSpeed>();
+        world.init_resource::<PilesSettled>();
+        let mut stage = SystemStage::single_threaded().with_system(move_to_pile);
+        let mut frames = 0_u32;
+        // Runs a frame, returning how many ran so far
+        let mut frame = |world: &mut World| {
+            frames += 1;
+            let mut time = world.resource_mut::<Time>();
+            let startup = time.startup();
+            time.update_with_instant(startup + Duration::from_secs_f32(frames as f32 / 60.0));
+            stage.run(world);
+            frames
+        };
+        let mut pile = Pile::new(PileType::Player);
+        let (bottom, top) = (world.spawn().id(), world.spawn().id());
+        for card in [bottom, top] {
+            let pile_card = pile.add_existing(card);
+            world
+                .entity_mut(card)
+                .insert_bundle((Transform::from_xyz(2.0, 3.0, 1.0), pile_card));
+        }
+        let pile = world
+            .spawn()
+            .insert_bundle((pile, GlobalTransform::default()))
+            .id();
+        let settled = |world: &World, card| world.get::<PileCard>(card).unwrap().settled;
+        let height = |world: &World, card| {
+            let offset = world.get::<PileCard>(card).unwrap().offset;
+            world.get::<Transform>(card).unwrap().translation.y - offset.translation.y
+        };
+
+        while !(settled(&world, bottom) && settled(&world, top)) {
+            let frames = frame(&mut world);
+            assert!(frames < 600, "cards settle in a few seconds at most");
+        }
+        assert!(world.resource::<PilesSettled>().0);
+        assert_eq!(height(&world, top), 0.008, "stacked on the bottom card");
+
+        let nudged = Transform::from_xyz(5.0, 5.0, 5.0);
+        *world.get_mut::<Transform>(bottom).unwrap() = nudged;
+        frame(&mut world);
+        let bottom_transform = *world.get::<Transform>(bottom).unwrap();
+        assert_eq!(bottom_transform, nudged, "settled cards are left alone");
+        assert!(!world.resource::<PilesSettled>().0);
+
+        world.get_mut::<Pile>(pile).unwrap().remove(bottom);
+        frame(&mut world);
+        assert_ne!(height(&world, top), 0.008, "the top card moves down");
+        while !settled(&world, top) {
+            let frames = frame(&mut world);
+            assert!(frames < 1200, "and settles again");
+        }
+        assert_eq!(height(&world, top), 0.0);
+
+        world
+            .entity_mut(pile)
+            .insert(GlobalTransform::from_xyz(0.0, 1.0, 0.0));
+        frame(&mut world);
+        assert!(!settled(&world, top), "moved piles wake their cards");
+    }
+
+    #[test]
+    fn emphasis_lifts_then_settles() {
+        assert_eq!(emphasis_lift(0.0, 0.8), Some(0.0));
+        assert_eq!(emphasis_lift(0.4, 0.8), Some(EMPHASIS_LIFT));
+        let settling = emphasis_lift(0.7, 0.8).unwrap();
+        assert!(0.0 < settling && settling < EMPHASIS_LIFT);
+        assert_eq!(emphasis_lift(0.8, 0.8), None);
+        assert_eq!(emphasis_lift(0.0, 0.0), None, "a zero beat doesn't lift");
+    }
+
+    #[test]
+    fn table_picks_find_piles() {
+        let mut world = World::new();
+        let war = world.spawn().insert(Pile::new(PileType::War)).id();
+        let player = world.spawn().insert(Pile::new(PileType::Player)).id();
+        let area = world.spawn().id();
+        world.entity_mut(war).push_children(&[area]);
+        let card = world.spawn().id();
+        let pile_card = Pile::new(PileType::Player).add_existing(card);
+        world.entity_mut(card).insert(pile_card);
+        let stray = world.spawn().id();
+        let stray_child = world.spawn().id();
+        world.entity_mut(stray).push_children(&[stray_child]);
+
+        let mut targets = SystemState::<TableTargets>::new(&mut world);
+        let targets = targets.get_mut(&mut world);
+        assert_eq!(targets.pick(None), TablePick::default());
+        let on_area = TablePick { hit: Some(area), pile: Some(war), card: None };
+        assert_eq!(targets.pick(Some(area)), on_area);
+        let on_card = targets.pick(Some(card));
+        assert_eq!(on_card.pile, Some(player), "through the card pile");
+        assert_eq!(on_card.card, Some(card));
+        let on_stray = TablePick { hit: Some(stray_child), ..default() };
+        assert_eq!(targets.pick(Some(stray_child)), on_stray, "not a pile area");
     }
 }
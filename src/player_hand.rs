@@ -12,28 +12,64 @@
 //! * [`DrawParams`] defines how to spawn a card with all the collision meshes
 //!   setup.
 //! * [`CardCollisionAssets`] defines the meshes used for collision detection.
-use std::f32::consts::FRAC_PI_4;
+//!
+//! The hand can also be played with the keyboard: number keys select a card,
+//! the [`Action::PlaySelected`] key (`Enter` by default) plays it and the
+//! [`Action::SleeveSelected`] key (`S` by default) slips it into the sleeve.
+//! [`ActiveInput`] tracks which of the mouse or keyboard was used last, so
+//! that they don't fight over which card is hovered.
+//!
+//! Raycasts are cast every frame from the [`CursorPosition`], so that cards
+//! sliding under a still cursor get hovered. The hover sound and word
+//! descriptions are skipped for [`LAYOUT_SETTLE`] seconds after the hand
+//! layout changes, see [`LayoutChangedAt`], so that cards passing under the
+//! cursor as the hand re-sorts don't fire a burst of them.
+//!
+//! While a card is dragged, each [`DropZone`] it can be dropped in is
+//! highlighted, the one it would go to when dropped brighter.
+//!
+//! With the blunder warning on, playing a card that loses the battle while
+//! another card in hand wouldn't marks it as a [`PendingPlay`] instead. The
+//! blunder dialog answers with a [`ResolvePendingPlay`].
+//!
+//! A card clicked while the opponent card or the new turn is being handled
+//! is remembered as a [`BufferedSelection`], and hovered when the player turn
+//! starts.
+//!
+//! Card hit boxes are the size of the card mesh and rotate with the card, so
+//! they match the visible card at every [`fan_transform`] position. With the
+//! `debug` feature, [`crate::raycast_debug`] shows where the rays hit.
+use std::{cmp::Ordering, f32::consts::FRAC_PI_4};
 
 use bevy::{
     ecs::{query::QueryItem, system::SystemParam},
     math::EulerRot::XYZ,
-    pbr::wireframe::Wireframe,
     prelude::{Plugin as BevyPlugin, *},
+    window::WindowId,
 };
 #[cfg(feature = "debug")]
 use bevy_inspector_egui::{Inspectable, RegisterInspectable};
 use bevy_mod_raycast::{DefaultRaycastingPlugin, RayCastMesh, RayCastMethod, RayCastSource};
+use enum_map::{enum_map, Enum, EnumMap};
 
+#[cfg(feature = "debug")]
+use crate::console::GiveCard;
 use crate::{
-    animate::DisableAnimation,
-    audio::AudioRequest::{self, PlayShuffleLong, PlayShuffleShort},
+    animate::{exp_approach, exp_approach_quat, DisableAnimation, GameFeel, GameSpeed},
+    audio::AudioRequest::{self, PlayShuffleLong, PlayShuffleShort, PlaySleeveReturn},
+    autosave::RestoreHand,
     card::{CardStatus, SpawnCard},
-    cheat::{CheatEvent, SleeveCard},
+    cheat::{can_sleeve, CheatEvent, LeavingSleeve, SleeveCard, SleeveState, SleeveVerdict},
     deck::PlayerDeck,
-    game_flow::PlayCard,
+    game_event::GameEvent,
+    game_flow::{Initiative, PlayCard, PlayedCard, TurnCount},
     game_ui::EffectEvent,
+    numbers::Number,
+    rules::GameRules,
+    settings::{Action, GameplaySettings, HandSort, KeyBindings},
     state::{GameState, TurnState},
-    war::Card,
+    stats::Stats,
+    war::{BattleOutcome, Card, WordOfPower},
     Participant,
 };
 
@@ -46,9 +82,126 @@ pub struct PlayerHand;
 #[derive(Component)]
 pub struct GrabbedCard;
 
+/// Which input device the player last used to interact with their hand.
+///
+/// The mouse hover logic is disabled while the keyboard is active, and vice
+/// versa, so that the last device used wins.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ActiveInput {
+    Mouse,
+    Keyboard,
+}
+impl Default for ActiveInput {
+    fn default() -> Self {
+        Self::Mouse
+    }
+}
+
+/// The last known cursor position, kept even when the cursor doesn't move,
+/// in any state.
+#[derive(Default)]
+pub struct CursorPosition(Option<(WindowId, Vec2)>);
+impl CursorPosition {
+    /// The cursor position, if it can be used for screenspace raycasts in
+    /// the current state of its window.
+    pub fn valid(&self, windows: &Windows) -> Option<Vec2> {
+        let (id, position) = self.0?;
+        let window = windows.get(id)?;
+        let window_size = Vec2::new(window.width(), window.height());
+        cursor_in_window(position, window_size).then(|| position)
+    }
+}
+
+/// Keys selecting the card at the corresponding [`HandCard::index`].
+const HAND_KEYS: [[KeyCode; 2]; 3] = [
+    [KeyCode::Key1, KeyCode::Numpad1],
+    [KeyCode::Key2, KeyCode::Numpad2],
+    [KeyCode::Key3, KeyCode::Numpad3],
+];
+/// Plays the selected card, on top of the [`Action::PlaySelected`] binding,
+/// to go with the numpad [`HAND_KEYS`].
+const NUMPAD_PLAY_KEY: KeyCode = KeyCode::NumpadEnter;
+
+/// The number shown over a hand card in keyboard mode, telling which key
+/// selects it.
+#[derive(Component)]
+struct KeyHint;
+
 /// Mesh for selecting the card.
 pub enum HandRaycast {}
 
+/// Size of the card hit box, the size of the card mesh.
+pub const CARD_HIT_BOX: Vec2 = Vec2::new(2.0, 3.0);
+/// How much further from the hand hovered cards are.
+const HOVER_LIFT: f32 = 1.0;
+/// How much the hand moves up when the sleeve is raised.
+const SLEEVE_LIFT: f32 = 1.5;
+/// Seconds hover changes are silent after the hand layout changed, scaled by
+/// the [`GameSpeed`], see [`LayoutChangedAt`].
+const LAYOUT_SETTLE: f32 = 0.5;
+
+/// When cards were last added to the hand or moved in it, by a sort or a
+/// card leaving it, in seconds since startup.
+///
+/// Cards then slide under a still cursor, hovering them is not the player's
+/// doing, so [`send_hover_feedback`] keeps quiet for a moment.
+struct LayoutChangedAt(f64);
+impl Default for LayoutChangedAt {
+    fn default() -> Self {
+        Self(f64::NEG_INFINITY)
+    }
+}
+impl LayoutChangedAt {
+    /// Whether the hand may still be moving at `now`, `settle` seconds being
+    /// how long it takes to settle.
+    fn is_settling(&self, now: f64, settle: f32) -> bool {
+        now - self.0 < settle as f64
+    }
+}
+
+/// A card hovered or unhovered with the mouse this frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct HoverChange {
+    card: Entity,
+    word: Option<WordOfPower>,
+    hovered: bool,
+}
+
+/// The [`HoverChange`]s of this frame, turned into sounds and descriptions by
+/// [`send_hover_feedback`].
+#[derive(Default)]
+struct HoverChanges(Vec<HoverChange>);
+
+/// What the player hears and sees for the hover `changes` of a frame: whether
+/// to play the hover sound, and the word description events.
+///
+/// A card unhovered and hovered again in the same frame didn't change. When
+/// `silent`, nothing is hovered as far as the player can tell, the
+/// descriptions of unhovered cards are still hidden so that they don't linger.
+fn hover_feedback(changes: &[HoverChange], silent: bool) -> (bool, Vec<EffectEvent>) {
+    let toggled_back = |change: &HoverChange| {
+        let undone =
+            |other: &HoverChange| other.card == change.card && other.hovered != change.hovered;
+        changes.iter().any(undone)
+    };
+    let (mut sound, mut events, mut hide) = (false, Vec::new(), false);
+    for change in changes.iter().filter(|change| !toggled_back(change)) {
+        match (change.hovered, change.word) {
+            (true, _) if silent => {}
+            (true, word) => {
+                sound = true;
+                events.extend(word.map(EffectEvent::Show));
+            }
+            (false, word) => hide |= word.is_some(),
+        }
+    }
+    // A new description replaces the old one
+    if hide && events.is_empty() {
+        events.push(EffectEvent::Hide);
+    }
+    (sound, events)
+}
+
 /// Marks the mesh that represents where if we disengage the card (relese the
 /// grab button), it will go back into the hand.
 pub enum HandDisengageArea {}
@@ -74,7 +227,7 @@ const AREA_EDGES: [u16; 24] = [
 
 #[cfg_attr(feature = "debug", derive(Inspectable))]
 #[derive(Component)]
-struct HandCard {
+pub struct HandCard {
     index: usize,
     dragging: bool,
     underlay: Entity,
@@ -83,6 +236,10 @@ impl HandCard {
     fn new(index: usize, underlay: Entity) -> Self {
         Self { index, underlay, dragging: false }
     }
+    /// Position of the card in the hand, from the left.
+    pub fn index(&self) -> usize {
+        self.index
+    }
 }
 
 /// Meshes used for collision detection.
@@ -100,6 +257,12 @@ impl Clone for CardCollisionAssets {
         }
     }
 }
+impl CardCollisionAssets {
+    /// A card sized quad, for rays to hit cards.
+    pub fn card_hit_box(&self) -> Handle<Mesh> {
+        self.bounding_box.clone_weak()
+    }
+}
 impl FromWorld for CardCollisionAssets {
     fn from_world(world: &mut World) -> Self {
         use bevy::render::{
@@ -117,16 +280,100 @@ impl FromWorld for CardCollisionAssets {
         mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, Float32x3([[0., 0., 1.]; 9].into()));
         let mut meshes = world.get_resource_mut::<Assets<Mesh>>().unwrap();
         Self {
-            bounding_box: meshes.add(shape::Quad::new(Vec2::new(2.3, 3.3)).into()),
-            underlay: meshes.add(shape::Quad::new(Vec2::new(2.4, 6.3)).into()),
+            bounding_box: meshes.add(shape::Quad::new(CARD_HIT_BOX).into()),
+            underlay: meshes.add(shape::Quad::new(CARD_HIT_BOX).into()),
             circle: meshes.add(mesh),
         }
     }
 }
 
+/// Where a dragged card can be dropped.
+///
+/// Also a highlight overlay shown over the matching area while dragging,
+/// overlays have no `RayCastMesh` so they never intercept raycasts.
+#[derive(Clone, Copy, Component, Debug, Enum, PartialEq)]
+pub enum DropZone {
+    /// The [`SleeveArea`], hides the card for later.
+    Sleeve,
+    /// Anywhere out of the [`HandDisengageArea`], plays the card.
+    WarPile,
+    /// The [`HandDisengageArea`], puts the card back in hand.
+    Disengage,
+}
+impl DropZone {
+    fn color(self) -> Color {
+        match self {
+            DropZone::Sleeve => Color::GOLD,
+            DropZone::WarPile => Participant::Player.color(),
+            DropZone::Disengage => Color::ANTIQUE_WHITE,
+        }
+    }
+}
+
+/// Where a card dropped now goes, the same rules as [`play_card`].
+fn drop_target(over_sleeve: bool, can_sleeve: bool, over_disengage: bool) -> DropZone {
+    if over_sleeve && can_sleeve {
+        DropZone::Sleeve
+    } else if !over_disengage {
+        DropZone::WarPile
+    } else {
+        DropZone::Disengage
+    }
+}
+
+/// Materials of the [`DropZone`] overlays, `bright` for the zone under the
+/// dragged card.
+pub struct DropZoneAssets {
+    dim: EnumMap<DropZone, Handle<StandardMaterial>>,
+    bright: EnumMap<DropZone, Handle<StandardMaterial>>,
+}
+impl Clone for DropZoneAssets {
+    fn clone(&self) -> Self {
+        Self {
+            dim: enum_map! { zone => self.dim[zone].clone_weak() },
+            bright: enum_map! { zone => self.bright[zone].clone_weak() },
+        }
+    }
+}
+impl FromWorld for DropZoneAssets {
+    fn from_world(world: &mut World) -> Self {
+        let mut materials = world.resource_mut::<Assets<StandardMaterial>>();
+        let mut tinted = |zone: DropZone, alpha: f32| {
+            let mut base_color = zone.color();
+            base_color.set_a(alpha);
+            materials.add(StandardMaterial {
+                base_color,
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                double_sided: true,
+                cull_mode: None,
+                ..default()
+            })
+        };
+        Self {
+            dim: enum_map! { zone => tinted(zone, 0.12) },
+            bright: enum_map! { zone => tinted(zone, 0.35) },
+        }
+    }
+}
+impl DropZoneAssets {
+    /// Spawn the overlay of `zone`, as child of its `area` mesh.
+    pub fn spawn_overlay(&self, cmds: &mut ChildBuilder, zone: DropZone, area: &Handle<Mesh>) {
+        let overlay = PbrBundle {
+            mesh: area.clone_weak(),
+            material: self.dim[zone].clone_weak(),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        };
+        cmds.spawn_bundle(overlay)
+            .insert_bundle((zone, Name::new(format!("{zone:?} drop zone"))));
+    }
+}
+
 // Underlay mesh is for preventing cards going up/down very fast when hovering over two
 // of them at the same time. It acts as a screen that prevents raycasts from reaching
 // under it. By enabling/disabling visibility, it's possible to enable/disable the underlay.
+// It covers the position the card had before being hovered, see `underlay_transform`.
 #[derive(Component)]
 struct Underlay;
 
@@ -137,38 +384,112 @@ struct DrawParams<'w, 's> {
     assets: Res<'w, CardCollisionAssets>,
     deck: Query<'w, 's, &'static mut PlayerDeck>,
     audio: EventWriter<'w, 's, AudioRequest>,
+    game_events: EventWriter<'w, 's, GameEvent>,
+    rules: Res<'w, GameRules>,
 }
 impl<'w, 's> DrawParams<'w, 's> {
     fn deck(&mut self) -> Mut<PlayerDeck> {
         self.deck.single_mut()
     }
+    /// Whether one more card can go into the sleeve, given how many are
+    /// already in it. Never in a fair game.
+    fn can_sleeve(&mut self, sleeved: usize) -> bool {
+        if self.rules.fair_game {
+            return false;
+        }
+        let deck_remaining = self.deck().remaining();
+        let capacity = self.rules.sleeve_capacity;
+        let state = SleeveState { sleeved, capacity, deck_remaining };
+        can_sleeve(&state) == SleeveVerdict::Allowed
+    }
     fn draw(&mut self, count: usize) {
-        self.audio.send(PlayShuffleLong);
-        for (i, card) in self.deck().draw(count).into_iter().enumerate() {
-            let cmds = &mut self.card_spawner.cmds;
-            let underlay = cmds
-                .spawn_bundle((
-                    Transform::from_xyz(0.0, 0.0, -0.001),
-                    GlobalTransform::default(),
-                    RayCastMesh::<HandRaycast>::default(),
-                    self.assets.underlay.clone(),
-                    Visibility { is_visible: false },
-                    Wireframe,
-                    Underlay,
-                    ComputedVisibility::default(),
-                ))
-                .id();
-            self.card_spawner
-                .spawn_card(card, Participant::Player)
-                .add_child(underlay)
-                .insert_bundle((
-                    HandCard::new(i, underlay),
-                    Wireframe,
-                    RayCastMesh::<HandRaycast>::default(),
-                    self.assets.bounding_box.clone(),
-                    Visibility::default(),
-                    ComputedVisibility::default(),
-                ));
+        let drawn = self.deck().draw(count);
+        if !drawn.is_empty() {
+            self.audio.send(PlayShuffleLong);
+        }
+        for (i, card) in drawn.into_iter().enumerate() {
+            let drawn = GameEvent::CardDrawn { who: Participant::Player, card: card.clone() };
+            self.game_events.send(drawn);
+            self.spawn_hand_card(card, i);
+        }
+    }
+    /// Spawn `card` in the player hand at `index`, returns the card and its
+    /// underlay.
+    fn spawn_hand_card(&mut self, card: Card, index: usize) -> (Entity, Entity) {
+        let cmds = &mut self.card_spawner.cmds;
+        let underlay = cmds
+            .spawn_bundle((
+                Transform::from_xyz(0.0, 0.0, -0.001),
+                GlobalTransform::default(),
+                RayCastMesh::<HandRaycast>::default(),
+                self.assets.underlay.clone(),
+                Visibility { is_visible: false },
+                Underlay,
+                ComputedVisibility::default(),
+            ))
+            .id();
+        let key_hint = cmds
+            .spawn_bundle((KeyHint, Name::new("Key hint")))
+            .insert(Number::new(index as i32 + 1, Color::ANTIQUE_WHITE))
+            .insert_bundle(SpatialBundle {
+                transform: Transform::from_xyz(0.0, 2.0, 0.02).with_scale(Vec3::splat(0.4)),
+                visibility: Visibility { is_visible: false },
+                ..default()
+            })
+            .id();
+        let card = self
+            .card_spawner
+            .spawn_card(card, Participant::Player)
+            .add_child(underlay)
+            .add_child(key_hint)
+            .insert_bundle((
+                HandCard::new(index, underlay),
+                RayCastMesh::<HandRaycast>::default(),
+                self.assets.bounding_box.clone(),
+                Visibility::default(),
+                ComputedVisibility::default(),
+            ))
+            .id();
+        (card, underlay)
+    }
+}
+
+/// Put back the player hand and sleeve of an autosave, see
+/// [`crate::autosave`].
+///
+/// Sleeved cards are spawned like hand cards then slipped in the sleeve,
+/// as [`send_sleeve`] does.
+fn restore_hand(mut card_drawer: DrawParams, mut events: EventReader<RestoreHand>) {
+    let restores = events
+        .iter()
+        .filter(|restore| restore.who == Participant::Player);
+    for restore in restores {
+        for (i, card) in restore.hand.iter().enumerate() {
+            card_drawer.spawn_hand_card(card.clone(), i);
+        }
+        for (i, card) in restore.sleeve.iter().enumerate() {
+            let (entity, underlay) = card_drawer.spawn_hand_card(card.clone(), i);
+            let cmds = &mut card_drawer.card_spawner.cmds;
+            cmds.entity(entity)
+                .remove::<HandCard>()
+                .insert(SleeveCard::new(i));
+            cmds.entity(underlay).remove::<RayCastMesh<HandRaycast>>();
+        }
+    }
+}
+
+/// Spawn the cards given to the player with the debug console.
+#[cfg(feature = "debug")]
+fn give_cards(
+    mut card_drawer: DrawParams,
+    mut events: EventReader<GiveCard>,
+    hand: Query<&HandCard>,
+) {
+    let mut index = hand.iter().count();
+    for GiveCard(who, card) in events.iter() {
+        if *who == Participant::Player {
+            card_drawer.spawn_hand_card(card.clone(), index);
+            index += 1;
         }
     }
 }
@@ -178,50 +499,121 @@ impl<'w, 's> DrawParams<'w, 's> {
 fn draw_hand(
     mut card_drawer: DrawParams,
     mut cmds: Commands,
-    sleeve_cards: Query<Entity, With<SleeveCard>>,
-    parents: Query<(Entity, &Parent), (With<Underlay>, With<RayCastMesh<HandRaycast>>)>,
+    sleeve_cards: Query<(Entity, &SleeveCard)>,
+    parents: Query<(Entity, &Parent), With<Underlay>>,
 ) {
     let underlay_of = |e| parents.iter().find_map(|(c, p)| (p.get() == e).then(|| c));
     let unsleeved: Vec<_> = sleeve_cards.iter().collect();
     card_drawer.draw(3 - unsleeved.len());
-    for entity in unsleeved.into_iter() {
+    if !unsleeved.is_empty() {
+        card_drawer.audio.send(PlaySleeveReturn);
+    }
+    for (entity, sleeve_card) in unsleeved.into_iter() {
+        let underlay = underlay_of(entity).unwrap();
+        cmds.entity(underlay)
+            .insert(RayCastMesh::<HandRaycast>::default());
         cmds.entity(entity)
             .remove::<SleeveCard>()
-            .insert(HandCard::new(0, underlay_of(entity).unwrap()));
+            .insert(sleeve_card.leave())
+            .insert(HandCard::new(0, underlay));
+    }
+}
+
+/// Whether `cursor` can be used for screenspace raycasts.
+///
+/// Minimized windows are 0×0 for a few frames on Windows, raycasting from
+/// them results in NaN positions.
+pub fn cursor_in_window(cursor: Vec2, window_size: Vec2) -> bool {
+    let in_bounds = cursor.cmpge(Vec2::ZERO).all() && cursor.cmple(window_size).all();
+    window_size.cmpgt(Vec2::ZERO).all() && cursor.is_finite() && in_bounds
+}
+
+fn track_cursor(mut position: ResMut<CursorPosition>, mut cursor: EventReader<CursorMoved>) {
+    if let Some(cursor) = cursor.iter().last() {
+        position.0 = Some((cursor.id, cursor.position));
     }
 }
 
+/// Where to move the dragged card, `None` if the cursor raycast is degenerate.
+fn drag_target(position: Vec3) -> Option<Vec3> {
+    position.is_finite().then(|| position)
+}
+
 /// Update the `bevy_mod_raycast` `RayCastSource` each frame so that it tracks
 /// the cursor position.
+///
+/// This runs even when the cursor doesn't move, cards drawn or moved under
+/// it and the hand rising with the sleeve are then picked up.
 fn update_raycast(
     mut hand: Query<&mut RayCastSource<HandRaycast>>,
     mut disengage: Query<&mut RayCastSource<HandDisengageArea>>,
     mut sleeve: Query<&mut RayCastSource<SleeveArea>>,
-    mut cursor: EventReader<CursorMoved>,
+    cursor: Res<CursorPosition>,
+    windows: Res<Windows>,
 ) {
-    if let Some(cursor) = cursor.iter().last() {
+    if let Some(position) = cursor.valid(&windows) {
         for mut pick_source in hand.iter_mut() {
-            pick_source.cast_method = RayCastMethod::Screenspace(cursor.position);
+            pick_source.cast_method = RayCastMethod::Screenspace(position);
         }
         for mut pick_source in disengage.iter_mut() {
-            pick_source.cast_method = RayCastMethod::Screenspace(cursor.position);
+            pick_source.cast_method = RayCastMethod::Screenspace(position);
         }
         for mut pick_source in sleeve.iter_mut() {
-            pick_source.cast_method = RayCastMethod::Screenspace(cursor.position);
+            pick_source.cast_method = RayCastMethod::Screenspace(position);
+        }
+    }
+}
+
+/// Switch [`ActiveInput`] to the device the player just used.
+///
+/// Switching to the mouse un-hovers the card selected with the keyboard, the
+/// mouse hover logic then takes over.
+fn update_active_input(
+    mut active: ResMut<ActiveInput>,
+    mut cursor: EventReader<CursorMoved>,
+    mut hand_cards: Query<(Entity, &Card, &HandCard, &mut CardStatus)>,
+    mut changes: ResMut<HoverChanges>,
+    keys: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+) {
+    let play_keys = [NUMPAD_PLAY_KEY, bindings.key(Action::PlaySelected)];
+    let mut hand_keys = HAND_KEYS.iter().flatten().chain(&play_keys);
+    let used_keyboard = hand_keys.any(|key| keys.just_pressed(*key));
+    let new_input = if used_keyboard || bindings.just_pressed(Action::SleeveSelected, &keys) {
+        ActiveInput::Keyboard
+    } else if cursor.iter().count() != 0 {
+        ActiveInput::Mouse
+    } else {
+        return;
+    };
+    if *active == new_input {
+        return;
+    }
+    *active = new_input;
+    if new_input == ActiveInput::Mouse {
+        let selected = |c: &(Entity, &Card, &HandCard, Mut<CardStatus>)| {
+            *c.3 == CardStatus::Hovered && !c.2.dragging
+        };
+        for (entity, card, _, mut status) in hand_cards.iter_mut().filter(selected) {
+            *status = CardStatus::Normal;
+            let change = HoverChange { card: entity, word: card.word, hovered: false };
+            changes.0.push(change);
         }
     }
 }
 
 /// Set the [`CardStatus`] of cards, un-hovering cards not under cursor and
 /// hovering ones that just came under it.
+///
+/// The changes are recorded for [`send_hover_feedback`].
 fn hover_card(
     hand_raycaster: Query<&RayCastSource<HandRaycast>>,
     mouse: Res<Input<MouseButton>>,
+    input: Res<ActiveInput>,
     mut hand_cards: Query<(Entity, &Card, &mut CardStatus)>,
-    mut audio: EventWriter<AudioRequest>,
-    mut ui_events: EventWriter<EffectEvent>,
+    mut changes: ResMut<HoverChanges>,
 ) {
-    if mouse.pressed(MouseButton::Left) {
+    if mouse.pressed(MouseButton::Left) || *input != ActiveInput::Mouse {
         return;
     }
     let query = hand_raycaster.get_single().map(|ray| ray.intersect_top());
@@ -230,23 +622,306 @@ fn hover_card(
         if hand_cards.get(card_under_cursor).is_err() {
             return;
         }
-        let mut already_new_word_description = false;
         for (entity, card, mut hover) in hand_cards.iter_mut() {
             let is_under_cursor = entity == card_under_cursor;
             let is_hovering = *hover == CardStatus::Hovered;
-            if is_under_cursor && !is_hovering {
-                *hover = CardStatus::Hovered;
-                if let Some(word) = card.word {
-                    already_new_word_description = true;
-                    ui_events.send(EffectEvent::Show(word));
-                }
-                audio.send(PlayShuffleShort);
+            if is_under_cursor != is_hovering {
+                *hover = if is_under_cursor {
+                    CardStatus::Hovered
+                } else {
+                    CardStatus::Normal
+                };
+                let change = HoverChange {
+                    card: entity,
+                    word: card.word,
+                    hovered: is_under_cursor,
+                };
+                changes.0.push(change);
             }
-            if !is_under_cursor && is_hovering {
-                if card.word.is_some() && !already_new_word_description {
-                    ui_events.send(EffectEvent::Hide);
-                }
-                *hover = CardStatus::Normal;
+        }
+    }
+}
+
+/// Play the hover sound and show the word descriptions for the mouse
+/// [`HoverChanges`] of this frame, see [`hover_feedback`].
+fn send_hover_feedback(
+    mut changes: ResMut<HoverChanges>,
+    mut audio: EventWriter<AudioRequest>,
+    mut ui_events: EventWriter<EffectEvent>,
+    layout: Res<LayoutChangedAt>,
+    time: Res<Time>,
+    speed: Res<GameSpeed>,
+) {
+    if changes.0.is_empty() {
+        return;
+    }
+    let settle = speed.duration(LAYOUT_SETTLE);
+    let silent = layout.is_settling(time.seconds_since_startup(), settle);
+    let (sound, events) = hover_feedback(&changes.0, silent);
+    if sound {
+        audio.send(PlayShuffleShort);
+    }
+    for event in events {
+        ui_events.send(event);
+    }
+    changes.0.clear();
+}
+
+/// Hover the card which [`HAND_KEYS`] key was just pressed, un-hovering the
+/// previously selected one.
+fn select_with_keyboard(
+    keys: Res<Input<KeyCode>>,
+    input: Res<ActiveInput>,
+    mut hand_cards: Query<(&Card, &HandCard, &mut CardStatus)>,
+    mut audio: EventWriter<AudioRequest>,
+    mut ui_events: EventWriter<EffectEvent>,
+) {
+    let pressed = |hand_keys: &[KeyCode; 2]| hand_keys.iter().any(|k| keys.just_pressed(*k));
+    let selected = match HAND_KEYS.iter().position(pressed) {
+        Some(selected) if *input == ActiveInput::Keyboard => selected,
+        _ => return,
+    };
+    let dragging = hand_cards.iter().any(|c| c.1.dragging);
+    if dragging || !hand_cards.iter().any(|c| c.1.index == selected) {
+        return;
+    }
+    let mut already_new_word_description = false;
+    for (card, HandCard { index, .. }, mut hover) in hand_cards.iter_mut() {
+        let is_selected = *index == selected;
+        let is_hovering = *hover == CardStatus::Hovered;
+        if is_selected && !is_hovering {
+            *hover = CardStatus::Hovered;
+            if let Some(word) = card.word {
+                already_new_word_description = true;
+                ui_events.send(EffectEvent::Show(word));
+            }
+            audio.send(PlayShuffleShort);
+        }
+        if !is_selected && is_hovering {
+            if card.word.is_some() && !already_new_word_description {
+                ui_events.send(EffectEvent::Hide);
+            }
+            *hover = CardStatus::Normal;
+        }
+    }
+}
+
+/// A hand card clicked while waiting for the player turn, hovered once it
+/// starts so that the player only has to confirm it.
+///
+/// Forgotten when the hand changes or the game ends.
+#[derive(Default)]
+struct BufferedSelection(Option<Entity>);
+
+/// Remember the hand card clicked during the interlude before a player turn,
+/// see [`BufferedSelection`].
+fn buffer_selection(
+    mut buffered: ResMut<BufferedSelection>,
+    hand_raycaster: Query<&RayCastSource<HandRaycast>>,
+    hand_cards: Query<(), With<HandCard>>,
+    mouse: Res<Input<MouseButton>>,
+    turn_state: Res<State<TurnState>>,
+    initiative: Res<Initiative>,
+    turn_count: Res<TurnCount>,
+    rules: Res<GameRules>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let upcoming = match turn_state.current() {
+        TurnState::New => Some(initiative.current()),
+        _ => initiative.upcoming(&turn_count, rules.initiative),
+    };
+    if upcoming != Some(Participant::Player) {
+        return;
+    }
+    let query = hand_raycaster.get_single().map(|ray| ray.intersect_top());
+    if let Ok(Some((card_under_cursor, _))) = query {
+        // Underlays have no `HandCard`
+        if hand_cards.get(card_under_cursor).is_ok() {
+            buffered.0 = Some(card_under_cursor);
+        }
+    }
+}
+
+/// Hover the [`BufferedSelection`] card, if still in hand, when the player
+/// turn starts.
+fn select_buffered(
+    mut buffered: ResMut<BufferedSelection>,
+    mut hand_cards: Query<(Entity, &Card, &mut CardStatus), With<HandCard>>,
+    mut audio: EventWriter<AudioRequest>,
+    mut ui_events: EventWriter<EffectEvent>,
+) {
+    let selected = match buffered.0.take() {
+        Some(selected) if hand_cards.get(selected).is_ok() => selected,
+        _ => return,
+    };
+    for (entity, card, mut hover) in hand_cards.iter_mut() {
+        if entity == selected {
+            *hover = CardStatus::Hovered;
+            if let Some(word) = card.word {
+                ui_events.send(EffectEvent::Show(word));
+            }
+        } else if *hover == CardStatus::Hovered {
+            *hover = CardStatus::Normal;
+        }
+    }
+    audio.send(PlayShuffleShort);
+}
+
+fn forget_buffered_selection(mut buffered: ResMut<BufferedSelection>) {
+    buffered.0 = None;
+}
+
+/// A hand card the player tried to play though it loses the battle, waiting
+/// for the player to confirm it, see [`is_blunder`].
+#[derive(Component)]
+pub struct PendingPlay;
+
+/// The player's answer to the blunder warning on the [`PendingPlay`] card.
+pub struct ResolvePendingPlay {
+    pub play_anyway: bool,
+}
+
+/// Whether playing `card` is a blunder: it loses against the `led` card, while
+/// another card in `hand` would win or tie. Never when the player leads.
+fn is_blunder(card: &Card, led: Option<&Card>, hand: &[Card]) -> bool {
+    use BattleOutcome::Loss;
+    let led = match led {
+        Some(led) => led,
+        None => return false,
+    };
+    card.beats(led) == Loss && hand.iter().any(|other| other.beats(led) != Loss)
+}
+
+/// System parameter checking whether playing a hand card is a blunder, when
+/// the blunder warning is on.
+#[derive(SystemParam)]
+struct BlunderCheck<'w, 's> {
+    gameplay: Res<'w, GameplaySettings>,
+    stats: Res<'w, Stats>,
+    war_card: Query<'w, 's, &'static Card, With<PlayedCard>>,
+    hand: Query<'w, 's, &'static Card, With<HandCard>>,
+    pending: Query<'w, 's, (), With<PendingPlay>>,
+}
+impl<'w, 's> BlunderCheck<'w, 's> {
+    /// Whether the player is still answering the blunder warning.
+    fn is_pending(&self) -> bool {
+        !self.pending.is_empty()
+    }
+    fn is_blunder(&self, entity: Entity) -> bool {
+        if !self.gameplay.warns_blunders(self.stats.games()) {
+            return false;
+        }
+        let card = match self.hand.get(entity) {
+            Ok(card) => card,
+            Err(_) => return false,
+        };
+        let hand: Vec<Card> = self.hand.iter().cloned().collect();
+        is_blunder(card, self.war_card.get_single().ok(), &hand)
+    }
+}
+
+/// Play the hand card `entity`.
+///
+/// It leaves the hand and can't be hovered anymore in the same command, so
+/// that it can't be picked and played again on the next frames.
+fn send_play(
+    cmds: &mut Commands,
+    card_events: &mut EventWriter<PlayCard>,
+    entity: Entity,
+    underlay: Entity,
+) {
+    cmds.entity(entity)
+        .remove_bundle::<(HandCard, RayCastMesh<HandRaycast>)>();
+    cmds.entity(underlay).remove::<RayCastMesh<HandRaycast>>();
+    card_events.send(PlayCard::new(entity, Participant::Player));
+}
+
+/// Slip the hand card `entity` into the sleeve.
+///
+/// Like [`send_play`], its `underlay` stops catching raycasts, until
+/// [`draw_hand`] brings the card back in hand.
+fn send_sleeve(
+    cmds: &mut Commands,
+    cheat_events: &mut EventWriter<CheatEvent>,
+    entity: Entity,
+    underlay: Entity,
+) {
+    cmds.entity(entity).remove::<HandCard>();
+    cmds.entity(underlay).remove::<RayCastMesh<HandRaycast>>();
+    cheat_events.send(CheatEvent::HideInSleeve(entity));
+}
+
+/// Check that only the underlays of cards in hand catch raycasts, so that
+/// underlays of played or sleeved cards can't steal hovers from the hand.
+#[cfg(debug_assertions)]
+fn check_underlays(
+    hand: Query<(), With<HandCard>>,
+    underlays: Query<(), (With<Underlay>, With<RayCastMesh<HandRaycast>>)>,
+) {
+    let (hand, underlays) = (hand.iter().count(), underlays.iter().count());
+    debug_assert_eq!(underlays, hand, "an underlay outlived its hand card");
+}
+
+/// Play or sleeve the card selected with the keyboard, following the same
+/// rules as [`play_card`] does for the mouse.
+fn play_with_keyboard(
+    keys: Res<Input<KeyCode>>,
+    input: Res<ActiveInput>,
+    mut cmds: Commands,
+    mut hand_cards: Query<(Entity, &mut CardStatus, &HandCard)>,
+    mut card_events: EventWriter<PlayCard>,
+    mut cheat_events: EventWriter<CheatEvent>,
+    mut card_drawer: DrawParams,
+    sleeve_cards: Query<(), With<SleeveCard>>,
+    bindings: Res<KeyBindings>,
+    blunders: BlunderCheck,
+) {
+    let play =
+        bindings.just_pressed(Action::PlaySelected, &keys) || keys.just_pressed(NUMPAD_PLAY_KEY);
+    let sleeve = bindings.just_pressed(Action::SleeveSelected, &keys);
+    if *input != ActiveInput::Keyboard || !(play || sleeve) {
+        return;
+    }
+    if hand_cards.iter().any(|c| c.2.dragging) || blunders.is_pending() {
+        return;
+    }
+    let selected = hand_cards.iter_mut().find(|c| *c.1 == CardStatus::Hovered);
+    let (entity, mut status, underlay) = match selected {
+        Some((entity, status, card)) => (entity, status, card.underlay),
+        None => return,
+    };
+    if play && blunders.is_blunder(entity) {
+        cmds.entity(entity).insert(PendingPlay);
+    } else if play {
+        *status = CardStatus::Normal;
+        send_play(&mut cmds, &mut card_events, entity, underlay);
+    } else if card_drawer.can_sleeve(sleeve_cards.iter().count()) {
+        *status = CardStatus::Normal;
+        send_sleeve(&mut cmds, &mut cheat_events, entity, underlay);
+        card_drawer.draw(1);
+    }
+}
+
+/// Show the key selecting each card over it when playing with the keyboard.
+fn update_key_hints(
+    input: Res<ActiveInput>,
+    cards: Query<&HandCard>,
+    mut hints: Query<(&Parent, &mut Number, &mut Visibility), With<KeyHint>>,
+) {
+    let keyboard = *input == ActiveInput::Keyboard;
+    for (parent, mut number, mut vis) in hints.iter_mut() {
+        let card = cards.get(parent.get());
+        let is_visible = keyboard && matches!(card, Ok(card) if !card.dragging);
+        if vis.is_visible != is_visible {
+            vis.is_visible = is_visible;
+        }
+        // Only update on actual change, `Number` rebuilds its materials when changed
+        if let Ok(HandCard { index, .. }) = card {
+            let key = *index as i32 + 1;
+            if number.value != key {
+                number.value = key;
             }
         }
     }
@@ -258,6 +933,40 @@ enum HandEvent {
     LowerSleeve,
 }
 
+/// Whether the sleeve is raised to take the dragged card.
+#[derive(Default)]
+struct SleeveLift {
+    raised: bool,
+}
+impl SleeveLift {
+    /// How much to move the hand up on `event`, `None` if it doesn't change
+    /// the sleeve.
+    fn handle(&mut self, event: &HandEvent) -> Option<f32> {
+        match event {
+            HandEvent::RaiseSleeve if !self.raised => {
+                self.raised = true;
+                Some(SLEEVE_LIFT)
+            }
+            HandEvent::LowerSleeve if self.raised => {
+                self.raised = false;
+                Some(-SLEEVE_LIFT)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Move the `hand` up by `lift`, it isn't animated while the sleeve is raised.
+fn lift_hand(cmds: &mut Commands, hand: Entity, trans: &mut Transform, lift: f32) {
+    if lift > 0.0 {
+        cmds.entity(hand).insert(DisableAnimation);
+    } else {
+        cmds.entity(hand).remove::<DisableAnimation>();
+    }
+    let offset = lift * trans.up();
+    trans.translation += offset;
+}
+
 /// Handle player interaction with cards in hand.
 fn play_card(
     mouse: Res<Input<MouseButton>>,
@@ -271,8 +980,12 @@ fn play_card(
     mut cheat_events: EventWriter<CheatEvent>,
     mut card_drawer: DrawParams,
     sleeve_cards: Query<(), With<SleeveCard>>,
+    blunders: BlunderCheck,
 ) {
     use CardStatus::Hovered;
+    if blunders.is_pending() {
+        return;
+    }
     let query = hand_raycaster.get_single().map(|ray| ray.intersect_top());
     let is_disengaging = || disengage_raycaster.single().intersect_top().is_some();
     let is_sleeving = || sleeve_raycaster.single().intersect_top().is_some();
@@ -292,30 +1005,35 @@ fn play_card(
             }
             (_, false) => {}
             (_, true) if mouse.just_released(MouseButton::Left) => {
-                let cards_remaining = card_drawer.deck().remaining() != 0;
-                let can_sleeve = sleeve_cards.iter().count() < 3 && cards_remaining;
+                let can_sleeve = card_drawer.can_sleeve(sleeve_cards.iter().count());
                 cmds.entity(entity).remove::<GrabbedCard>();
                 *hover_state = CardStatus::Normal;
-                if is_sleeving() && can_sleeve {
-                    cmds.entity(entity).remove::<HandCard>();
-                    cheat_events.send(CheatEvent::HideInSleeve(entity));
-                    hand_events.send(HandEvent::LowerSleeve);
-                    card_drawer.draw(1);
-                } else if !is_disengaging() {
-                    cmds.entity(entity).remove::<HandCard>();
-                    cmds.entity(entity).remove::<RayCastMesh<HandRaycast>>();
-                    card_events.send(PlayCard::new(entity, Participant::Player));
-                } else {
-                    card.dragging = false;
+                match drop_target(is_sleeving(), can_sleeve, is_disengaging()) {
+                    DropZone::Sleeve => {
+                        send_sleeve(&mut cmds, &mut cheat_events, entity, card.underlay);
+                        hand_events.send(HandEvent::LowerSleeve);
+                        card_drawer.draw(1);
+                    }
+                    DropZone::WarPile if blunders.is_blunder(entity) => {
+                        cmds.entity(entity).insert(PendingPlay);
+                        card.dragging = false;
+                    }
+                    DropZone::WarPile => {
+                        send_play(&mut cmds, &mut card_events, entity, card.underlay);
+                    }
+                    DropZone::Disengage => card.dragging = false,
                 }
                 break;
             }
             (_, true) => {
                 let word_cursor = if let Ok(Some((_, i))) = query { i } else { break };
-                let cursor_pos = word_cursor.position();
-                let cards_remaining = card_drawer.deck().remaining() != 0;
+                let cursor_pos = if let Some(pos) = drag_target(word_cursor.position()) {
+                    pos
+                } else {
+                    break;
+                };
                 // FIXME: use size_hint().0 when bevy#4244 pr is merged
-                let can_sleeve = sleeve_cards.iter().count() < 3 && cards_remaining;
+                let can_sleeve = card_drawer.can_sleeve(sleeve_cards.iter().count());
                 trans.translation = cursor_pos;
                 if is_sleeving() && can_sleeve {
                     hand_events.send(HandEvent::RaiseSleeve);
@@ -328,6 +1046,24 @@ fn play_card(
     }
 }
 
+/// Play the [`PendingPlay`] card, or put it back in the hand, as the player
+/// answered the blunder warning.
+fn resolve_pending_play(
+    mut cmds: Commands,
+    mut answers: EventReader<ResolvePendingPlay>,
+    mut card_events: EventWriter<PlayCard>,
+    pending: Query<(Entity, &HandCard), With<PendingPlay>>,
+) {
+    for answer in answers.iter() {
+        for (entity, card) in pending.iter() {
+            cmds.entity(entity).remove::<PendingPlay>();
+            if answer.play_anyway {
+                send_play(&mut cmds, &mut card_events, entity, card.underlay);
+            }
+        }
+    }
+}
+
 // TODO: tilt hand backward when enemy is playing so that it's more explicitly
 // the player's turn
 // TODO: animate sleeve movement
@@ -338,33 +1074,50 @@ fn update_sleeve(
     mut hand: Query<(Entity, &mut Transform), With<PlayerHand>>,
     mut cards: Query<(&mut Transform, &HandCard), Without<PlayerHand>>,
     mut events: EventReader<HandEvent>,
-    mut raised: Local<bool>,
+    mut sleeve: ResMut<SleeveLift>,
     time: Res<Time>,
+    feel: Res<GameFeel>,
+    speed: Res<GameSpeed>,
 ) {
     let (hand, mut trans) = hand.single_mut();
-    if *raised {
+    if sleeve.raised {
         if let Some((mut trans, _)) = cards.iter_mut().find(|c| c.1.dragging) {
             let delta = time.delta_seconds();
             let (x, y, _) = trans.rotation.to_euler(XYZ);
             let target_rot = Quat::from_euler(XYZ, x, y, 0.1);
-            trans.rotation = trans.rotation.lerp(target_rot, delta * 10.0);
+            let rate = speed.rate(feel.sleeve_rate);
+            trans.rotation = exp_approach_quat(trans.rotation, target_rot, rate, delta);
         }
     }
     for event in events.iter() {
-        match event {
-            HandEvent::RaiseSleeve if !*raised => {
-                cmds.entity(hand).insert(DisableAnimation);
-                *raised = true;
-                let offset = 1.5 * trans.up();
-                trans.translation += offset;
-            }
-            HandEvent::LowerSleeve if *raised => {
-                *raised = false;
-                cmds.entity(hand).remove::<DisableAnimation>();
-                let offset = 1.5 * trans.up();
-                trans.translation -= offset;
-            }
-            _ => {}
+        if let Some(lift) = sleeve.handle(event) {
+            lift_hand(&mut cmds, hand, &mut trans, lift);
+        }
+    }
+}
+
+/// Let go of the hand when the player turn ends, or is paused: lower the
+/// sleeve and drop the dragged card, since the systems handling them stop
+/// running.
+fn release_hand(
+    mut cmds: Commands,
+    mut hand: Query<(Entity, &mut Transform), With<PlayerHand>>,
+    mut cards: Query<(&mut HandCard, &mut CardStatus)>,
+    mut sleeve: ResMut<SleeveLift>,
+    grabbed: Query<Entity, With<GrabbedCard>>,
+) {
+    if let Some(lift) = sleeve.handle(&HandEvent::LowerSleeve) {
+        if let Ok((hand, mut trans)) = hand.get_single_mut() {
+            lift_hand(&mut cmds, hand, &mut trans, lift);
+        }
+    }
+    for entity in grabbed.iter() {
+        cmds.entity(entity).remove::<GrabbedCard>();
+    }
+    for (mut card, mut status) in cards.iter_mut() {
+        if card.dragging {
+            card.dragging = false;
+            *status = CardStatus::Normal;
         }
     }
 }
@@ -375,43 +1128,136 @@ type HoverQuery = (
     &'static HandCard,
 );
 
-/// Animate card movements into the player hand, skipping the dragged one.
+/// Where the card at `index` sits in the hand fan, relative to the hand.
+fn fan_transform(index: usize, hovered: bool) -> Transform {
+    let i_f32 = 0.7 * index as f32;
+    let distance = if hovered { 1.0 + HOVER_LIFT } else { 1.0 };
+    let y_offset = i_f32.cos() * distance;
+    let x_offset = i_f32.sin() * distance;
+    let z_offset = i_f32 * -0.01;
+    Transform {
+        translation: Vec3::new(x_offset - 0.3, y_offset, z_offset + 0.04),
+        rotation: Quat::from_rotation_z(FRAC_PI_4 * -i_f32),
+        ..default()
+    }
+}
+
+/// The underlay transform, relative to the `card` it is a child of, so that
+/// it stays at the `previous` card position, slightly behind it.
+fn underlay_transform(card: &Transform, previous: &Transform) -> Transform {
+    let relative = card.compute_matrix().inverse() * previous.compute_matrix();
+    let underlay = Transform::from_matrix(relative);
+    underlay.with_translation(underlay.translation - Vec3::Z * 0.001)
+}
+
+/// Animate card movements into the player hand, skipping the dragged one and
+/// the ones still sliding out of the sleeve.
 fn update_hand(
     hand: Query<&GlobalTransform, With<PlayerHand>>,
-    mut cards: Query<HoverQuery>,
+    mut cards: Query<HoverQuery, Without<LeavingSleeve>>,
+    mut underlays: Query<&mut Transform, (With<Underlay>, Without<HandCard>)>,
     time: Res<Time>,
+    feel: Res<GameFeel>,
+    speed: Res<GameSpeed>,
 ) {
-    let card_speed = 10.0 * time.delta_seconds();
+    let (rate, dt) = (speed.rate(feel.hand_rate), time.delta_seconds());
     let hand_transform = hand.single().compute_transform();
     let (hand_pos, hand_rot) = (hand_transform.translation, hand_transform.rotation);
+    let in_hand = |fan: Transform| Transform {
+        translation: hand_pos + hand_rot * fan.translation,
+        rotation: hand_rot * fan.rotation,
+        ..default()
+    };
     let not_dragging = |c: &QueryItem<HoverQuery>| !c.2.dragging;
-    for (mut transform, hover, HandCard { index, .. }) in cards.iter_mut().filter(not_dragging) {
+    for (mut transform, hover, card) in cards.iter_mut().filter(not_dragging) {
         let is_hovering = *hover == CardStatus::Hovered;
-        let i_f32 = 0.7 * *index as f32;
-        let hover_mul = if is_hovering { 2.0 } else { 1.0 };
-        let y_offset = i_f32.cos() * hover_mul;
-        let x_offset = i_f32.sin() * hover_mul;
-        let z_offset = i_f32 * -0.01;
-        let target = Vec3::new(x_offset - 0.3, y_offset, z_offset + 0.04);
-        let target = hand_pos + hand_rot * target;
-        let origin = transform.translation;
-        transform.translation += (target - origin) * card_speed;
+        let target = in_hand(fan_transform(card.index, is_hovering));
+        transform.translation = exp_approach(transform.translation, target.translation, rate, dt);
+        transform.rotation = exp_approach_quat(transform.rotation, target.rotation, rate, dt);
 
-        let rot_offset = Quat::from_rotation_z(FRAC_PI_4 * -i_f32);
-        let target = hand_transform.rotation * rot_offset;
-        let origin = transform.rotation;
-        transform.rotation = origin.lerp(target, card_speed);
+        if let Ok(mut underlay) = underlays.get_mut(card.underlay) {
+            let previous = in_hand(fan_transform(card.index, false));
+            *underlay = underlay_transform(&transform, &previous);
+        }
     }
 }
 
+/// Compare hand cards, given with their current index, by `sort`. Cards
+/// equal by `sort` keep their current order.
+fn hand_order(
+    sort: HandSort,
+    (a_index, a): (usize, &Card),
+    (b_index, b): (usize, &Card),
+) -> Ordering {
+    let by_sort = match sort {
+        HandSort::Manual => Ordering::Equal,
+        HandSort::ValueAscending => a.value.cmp(&b.value),
+        HandSort::ValueDescending => b.value.cmp(&a.value),
+        HandSort::WordsFirst => b.word.is_some().cmp(&a.word.is_some()),
+    };
+    by_sort.then(a_index.cmp(&b_index))
+}
+
 /// Reorder cards in hand.
 ///
-/// So that they are held like a human would, even after using one.
-fn update_hand_indexes(mut cards: Query<&mut HandCard>) {
+/// So that they are held like a human would, even after using one, and
+/// sorted by [`GameplaySettings::hand_sort`]. The hand isn't sorted while a
+/// card is dragged, so that cards don't move under the cursor.
+///
+/// New and moved cards stamp the [`LayoutChangedAt`].
+fn update_hand_indexes(
+    mut cards: Query<(&mut HandCard, &Card)>,
+    added: Query<(), Added<HandCard>>,
+    mut layout: ResMut<LayoutChangedAt>,
+    gameplay: Res<GameplaySettings>,
+    time: Res<Time>,
+) {
+    let mut changed = !added.is_empty();
     let mut cards: Vec<_> = cards.iter_mut().collect();
-    cards.sort_by_key(|c| c.index);
-    for (index, card) in cards.iter_mut().enumerate() {
-        card.index = index;
+    let dragging = cards.iter().any(|(card, _)| card.dragging);
+    let sort = if dragging { HandSort::Manual } else { gameplay.hand_sort };
+    cards.sort_by(|a, b| hand_order(sort, (a.0.index, a.1), (b.0.index, b.1)));
+    for (index, (card, _)) in cards.iter_mut().enumerate() {
+        if card.index != index {
+            card.index = index;
+            changed = true;
+        }
+    }
+    if changed {
+        layout.0 = time.seconds_since_startup();
+    }
+}
+
+/// Show the [`DropZone`] overlays while a card is dragged, brightening the
+/// one it would be dropped in. The sleeve is only shown when sleeving is
+/// allowed.
+fn highlight_drop_zones(
+    grabbed: Query<(), With<GrabbedCard>>,
+    disengage_raycaster: Query<&RayCastSource<HandDisengageArea>>,
+    sleeve_raycaster: Query<&RayCastSource<SleeveArea>>,
+    sleeve_cards: Query<(), With<SleeveCard>>,
+    mut card_drawer: DrawParams,
+    mut zones: Query<(&DropZone, &mut Visibility, &mut Handle<StandardMaterial>)>,
+    assets: Res<DropZoneAssets>,
+) {
+    let dragging = !grabbed.is_empty();
+    let can_sleeve = dragging && card_drawer.can_sleeve(sleeve_cards.iter().count());
+    let over_sleeve = sleeve_raycaster
+        .iter()
+        .any(|ray| ray.intersect_top().is_some());
+    let over_disengage = disengage_raycaster
+        .iter()
+        .any(|ray| ray.intersect_top().is_some());
+    let target = drop_target(over_sleeve, can_sleeve, over_disengage);
+    for (zone, mut visibility, mut material) in zones.iter_mut() {
+        let is_visible = dragging && (*zone != DropZone::Sleeve || can_sleeve);
+        if visibility.is_visible != is_visible {
+            visibility.is_visible = is_visible;
+        }
+        let tint = if *zone == target { &assets.bright } else { &assets.dim };
+        if *material != tint[*zone] {
+            *material = tint[*zone].clone_weak();
+        }
     }
 }
 
@@ -431,25 +1277,288 @@ pub struct Plugin(pub GameState);
 impl BevyPlugin for Plugin {
     fn build(&self, app: &mut App) {
         #[cfg(feature = "debug")]
-        app.register_inspectable::<HandCard>();
+        app.register_inspectable::<HandCard>()
+            .add_system_set(SystemSet::on_update(self.0).with_system(give_cards));
         app.add_plugin(DefaultRaycastingPlugin::<HandRaycast>::default())
             .add_plugin(DefaultRaycastingPlugin::<SleeveArea>::default())
             .add_plugin(DefaultRaycastingPlugin::<HandDisengageArea>::default())
             .add_event::<HandEvent>()
+            .add_event::<ResolvePendingPlay>()
             .init_resource::<CardCollisionAssets>()
-            .add_system_set(SystemSet::on_enter(TurnState::Draw).with_system(draw_hand))
+            .init_resource::<ActiveInput>()
+            .init_resource::<CursorPosition>()
+            .init_resource::<DropZoneAssets>()
+            .init_resource::<SleeveLift>()
+            .init_resource::<BufferedSelection>()
+            .init_resource::<LayoutChangedAt>()
+            .init_resource::<HoverChanges>()
+            .add_system(track_cursor.before("cursor"))
+            .add_system(restore_hand.after("restore_table"))
+            .add_system_set(
+                SystemSet::on_enter(TurnState::Draw)
+                    .with_system(draw_hand)
+                    .with_system(forget_buffered_selection),
+            )
+            .add_system_set(
+                SystemSet::on_update(TurnState::CardPlayed).with_system(buffer_selection),
+            )
+            .add_system_set(SystemSet::on_update(TurnState::New).with_system(buffer_selection))
+            .add_system_set(SystemSet::on_enter(TurnState::Player).with_system(select_buffered))
+            .add_system_set(
+                SystemSet::on_enter(TurnState::Oppo).with_system(forget_buffered_selection),
+            )
             .add_system_set(
                 SystemSet::on_update(TurnState::Player)
-                    .with_system(hover_card.label("select"))
-                    .with_system(hovered_covers_previous_position)
+                    .with_system(update_active_input.label("input"))
+                    .with_system(hover_card.label("select").after("input"))
+                    .with_system(select_with_keyboard.label("select").after("input"))
+                    // Same frame as hover changes, so that the underlay
+                    // never lags behind the hovered card
+                    .with_system(hovered_covers_previous_position.after("select"))
+                    .with_system(send_hover_feedback.after("select"))
                     .with_system(play_card.label("play").after("select"))
-                    .with_system(update_raycast),
+                    .with_system(play_with_keyboard.label("play").after("select")),
             )
+            .add_system_set(SystemSet::on_exit(TurnState::Player).with_system(release_hand))
+            .add_system_set(SystemSet::on_pause(TurnState::Player).with_system(release_hand))
             .add_system_set(
                 SystemSet::on_update(self.0)
+                    .with_system(update_raycast.label("cursor"))
                     .with_system(update_sleeve.after("animation"))
+                    .with_system(resolve_pending_play.before("play"))
                     .with_system(update_hand.after("play"))
-                    .with_system(update_hand_indexes),
-            );
+                    .with_system(highlight_drop_zones.after("play"))
+                    .with_system(update_hand_indexes.before("select"))
+                    .with_system(update_key_hints),
+            )
+            .add_system_set(SystemSet::on_exit(self.0).with_system(forget_buffered_selection));
+        #[cfg(debug_assertions)]
+        app.add_system_set(SystemSet::on_update(self.0).with_system(check_underlays));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_in_window_rejects_degenerate_windows() {
+        let window = Vec2::new(1280.0, 720.0);
+        assert!(cursor_in_window(Vec2::new(640.0, 360.0), window));
+        assert!(cursor_in_window(Vec2::ZERO, window));
+        assert!(!cursor_in_window(Vec2::new(640.0, 360.0), Vec2::ZERO));
+        assert!(!cursor_in_window(Vec2::ZERO, Vec2::ZERO));
+        assert!(!cursor_in_window(Vec2::new(-1.0, 360.0), window));
+        assert!(!cursor_in_window(Vec2::new(640.0, 721.0), window));
+        assert!(!cursor_in_window(Vec2::new(f32::NAN, 360.0), window));
+    }
+
+    #[test]
+    fn drop_target_follows_play_rules() {
+        assert_eq!(drop_target(true, true, true), DropZone::Sleeve);
+        assert_eq!(drop_target(true, false, true), DropZone::Disengage);
+        assert_eq!(drop_target(true, false, false), DropZone::WarPile);
+        assert_eq!(drop_target(false, true, true), DropZone::Disengage);
+        assert_eq!(drop_target(false, true, false), DropZone::WarPile);
+    }
+
+    #[test]
+    fn underlays_leave_raycasts_with_their_card() {
+        let mut world = World::new();
+        world.init_resource::<Events<PlayCard>>();
+        world.init_resource::<Events<CheatEvent>>();
+        let mut hand_card = |index| {
+            let raycast = RayCastMesh::<HandRaycast>::default;
+            let underlay = world.spawn().insert_bundle((Underlay, raycast())).id();
+            let card = HandCard::new(index, underlay);
+            let card = world.spawn().insert_bundle((card, raycast())).id();
+            (card, underlay)
+        };
+        let (played, sleeved) = (hand_card(0), hand_card(1));
+        hand_card(2);
+        let leave_hand = move |mut cmds: Commands,
+                               mut plays: EventWriter<PlayCard>,
+                               mut cheats: EventWriter<CheatEvent>| {
+            send_play(&mut cmds, &mut plays, played.0, played.1);
+            send_sleeve(&mut cmds, &mut cheats, sleeved.0, sleeved.1);
+        };
+        SystemStage::single_threaded()
+            .with_system(leave_hand)
+            .run(&mut world);
+
+        let mut underlays =
+            world.query_filtered::<Entity, (With<Underlay>, With<RayCastMesh<HandRaycast>>)>();
+        assert_eq!(underlays.iter(&world).count(), 1, "only the card in hand");
+        #[cfg(debug_assertions)]
+        SystemStage::single_threaded()
+            .with_system(check_underlays)
+            .run(&mut world);
+    }
+
+    /// Whether the hit box at `transform` covers the hand space `point`.
+    fn hits(transform: &Transform, point: Vec3) -> bool {
+        let local = transform.compute_matrix().inverse().transform_point3(point);
+        local.x.abs() <= CARD_HIT_BOX.x / 2.0 && local.y.abs() <= CARD_HIT_BOX.y / 2.0
+    }
+
+    /// Hand space position of `corner`, relative to the center of the 2x3
+    /// card at `index`. Cards sit on a unit circle around the hand, 0.7
+    /// radians apart, and lean 45° further per radian.
+    fn fanned_corner(index: usize, corner: Vec2) -> Vec3 {
+        let angle = 0.7 * index as f32;
+        let (sin, cos) = (-FRAC_PI_4 * angle).sin_cos();
+        let x = corner.x * cos - corner.y * sin;
+        let y = corner.x * sin + corner.y * cos;
+        Vec3::new(angle.sin() - 0.3 + x, angle.cos() + y, 0.0)
+    }
+
+    #[test]
+    fn hit_boxes_match_fanned_cards() {
+        let corners = [(1.0, 1.5), (-1.0, 1.5), (1.0, -1.5), (-1.0, -1.5)];
+        for index in 0..3 {
+            let card = fan_transform(index, false);
+            for (x, y) in corners {
+                let corner = Vec2::new(x, y);
+                let inside = fanned_corner(index, corner * 0.95);
+                assert!(hits(&card, inside), "{corner} of card {index}");
+                let outside = fanned_corner(index, corner * 1.05);
+                assert!(!hits(&card, outside), "past {corner} of card {index}");
+            }
+        }
+    }
+
+    #[test]
+    fn hovers_are_quiet_while_the_hand_settles() {
+        use WordOfPower::Qube;
+        let layout = LayoutChangedAt(10.0);
+        assert!(layout.is_settling(10.2, LAYOUT_SETTLE));
+        assert!(!layout.is_settling(10.6, LAYOUT_SETTLE));
+        assert!(!layout.is_settling(10.3, 0.2), "shorter in fast mode");
+        assert!(!LayoutChangedAt::default().is_settling(0.0, LAYOUT_SETTLE));
+
+        let (seven, nine) = (Entity::from_raw(7), Entity::from_raw(9));
+        let change = |card, word, hovered| HoverChange { card, word, hovered };
+        let moved = [change(seven, None, false), change(nine, Some(Qube), true)];
+        let (sound, events) = hover_feedback(&moved, false);
+        assert!(sound && events == [EffectEvent::Show(Qube)]);
+        let (sound, events) = hover_feedback(&moved, true);
+        assert!(!sound && events.is_empty(), "slid under the cursor");
+
+        let left_word = [change(nine, Some(Qube), false), change(seven, None, true)];
+        let (sound, events) = hover_feedback(&left_word, true);
+        assert!(
+            !sound && events == [EffectEvent::Hide],
+            "no lingering description"
+        );
+        let (sound, events) = hover_feedback(&left_word, false);
+        assert!(sound && events == [EffectEvent::Hide]);
+
+        let toggled = [
+            change(nine, Some(Qube), false),
+            change(nine, Some(Qube), true),
+        ];
+        let (sound, events) = hover_feedback(&toggled, false);
+        assert!(!sound && events.is_empty(), "unhovered and hovered again");
+    }
+
+    #[test]
+    fn underlay_covers_previous_position_only() {
+        for index in 0..3 {
+            let (card, previous) = (fan_transform(index, true), fan_transform(index, false));
+            let underlay = card.mul_transform(underlay_transform(&card, &previous));
+            for corner in [Vec3::new(1.0, 1.5, 0.0), Vec3::new(-1.0, -1.5, 0.0)] {
+                let expected = previous.mul_vec3(corner) - Vec3::Z * 0.001;
+                let actual = underlay.mul_vec3(corner);
+                assert!(actual.abs_diff_eq(expected, 1e-4), "card {index}: {actual}");
+            }
+            // Nothing above the hovered card is hidden from the raycast
+            let above = card.mul_vec3(Vec3::new(0.0, 1.6, 0.0));
+            assert!(!hits(&underlay, above), "above card {index}");
+            assert!(!hits(&card, above), "above card {index}");
+        }
+    }
+
+    /// The cards of `hand` in the order `sort` puts them, cards are given in
+    /// their current order.
+    fn sorted(sort: HandSort, hand: &[&str]) -> Vec<String> {
+        let cards: Vec<Card> = hand.iter().map(|s| s.parse().unwrap()).collect();
+        let mut indexed: Vec<_> = cards.iter().enumerate().collect();
+        indexed.sort_by(|a, b| hand_order(sort, *a, *b));
+        indexed.iter().map(|(_, card)| card.to_string()).collect()
+    }
+
+    #[test]
+    fn manual_sort_keeps_hand_order() {
+        let hand = ["7_", "2d", "5_"];
+        assert_eq!(sorted(HandSort::Manual, &hand), hand);
+    }
+
+    #[test]
+    fn value_sorts_keep_ties_in_hand_order() {
+        let hand = ["7_", "2d", "7s", "5_"];
+        let ascending = sorted(HandSort::ValueAscending, &hand);
+        assert_eq!(ascending, ["2d", "5_", "7_", "7s"]);
+        let descending = sorted(HandSort::ValueDescending, &hand);
+        assert_eq!(descending, ["7_", "7s", "5_", "2d"]);
+        let swapped = sorted(HandSort::ValueAscending, &["7s", "7_"]);
+        assert_eq!(swapped, ["7s", "7_"], "equal values keep their order");
+    }
+
+    #[test]
+    fn words_first_keeps_hand_order_otherwise() {
+        let hand = ["7_", "2d", "1_", "9w"];
+        let words_first = sorted(HandSort::WordsFirst, &hand);
+        assert_eq!(words_first, ["2d", "9w", "7_", "1_"]);
+    }
+
+    #[test]
+    fn blunders_lose_when_another_card_wouldnt() {
+        let card = |s: &str| s.parse::<Card>().unwrap();
+        let hand = [card("2_"), card("7_")];
+        let led = card("5_");
+        assert!(is_blunder(&hand[0], Some(&led), &hand));
+        assert!(!is_blunder(&hand[1], Some(&led), &hand));
+        // A tie is a better play than a loss
+        let tie = [card("2_"), card("5_")];
+        assert!(is_blunder(&tie[0], Some(&led), &tie));
+    }
+
+    #[test]
+    fn no_blunder_when_leading_or_every_card_loses() {
+        let card = |s: &str| s.parse::<Card>().unwrap();
+        let hand = [card("2_"), card("7_")];
+        assert!(!is_blunder(&hand[0], None, &hand));
+        let losing = [card("1_"), card("2_")];
+        assert!(!is_blunder(&losing[0], Some(&card("5_")), &losing));
+    }
+
+    #[test]
+    fn blunders_account_for_swap_words() {
+        let card = |s: &str| s.parse::<Card>().unwrap();
+        // The swap word makes the 2 win against the 5, and the 7 lose
+        let hand = [card("2w"), card("7w")];
+        let led = card("5_");
+        assert!(is_blunder(&hand[1], Some(&led), &hand));
+        assert!(!is_blunder(&hand[0], Some(&led), &hand));
+    }
+
+    #[test]
+    fn drag_target_rejects_non_finite_positions() {
+        let position = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!(drag_target(position), Some(position));
+        assert_eq!(drag_target(Vec3::new(f32::NAN, 2.0, 3.0)), None);
+        assert_eq!(drag_target(Vec3::new(1.0, f32::INFINITY, 3.0)), None);
+    }
+
+    #[test]
+    fn turn_end_lowers_raised_sleeve() {
+        let mut sleeve = SleeveLift::default();
+        assert_eq!(sleeve.handle(&HandEvent::LowerSleeve), None);
+        assert_eq!(sleeve.handle(&HandEvent::RaiseSleeve), Some(SLEEVE_LIFT));
+        assert_eq!(sleeve.handle(&HandEvent::RaiseSleeve), None);
+        // What release_hand does when the turn ends over the sleeve zone
+        assert_eq!(sleeve.handle(&HandEvent::LowerSleeve), Some(-SLEEVE_LIFT));
+        assert!(!sleeve.raised);
+        assert_eq!(sleeve.handle(&HandEvent::LowerSleeve), None);
     }
 }
@@ -0,0 +1,220 @@
+//! Raycast debugging tools, only built with the `debug` feature.
+//!
+//! [`TOGGLE_KEY`] shows where the [`HandRaycast`], [`SleeveArea`] and
+//! [`HandDisengageArea`] rays hit, as small spheres. The hand hit box under
+//! the cursor is drawn as a wireframe, and the debug overlay prints the hit
+//! entity and the cursor position each time it changes.
+//!
+//! [`CAPTURE_KEY`] records the cursor position and the entity each ray hits
+//! during the next [`CAPTURE_FRAMES`] frames to [`TRACE_FILE`], to attach to
+//! selection bug reports.
+use bevy::{
+    ecs::system::SystemParam,
+    pbr::wireframe::Wireframe,
+    prelude::{Plugin as BevyPlugin, *},
+};
+use bevy_mod_raycast::RayCastSource;
+use enum_map::{enum_map, Enum, EnumMap};
+
+use crate::{
+    persist,
+    player_hand::{CursorPosition, HandDisengageArea, HandRaycast, SleeveArea},
+};
+
+const TOGGLE_KEY: KeyCode = KeyCode::F3;
+const CAPTURE_KEY: KeyCode = KeyCode::F4;
+/// Frames recorded in a trace, about 5 seconds at 60 fps.
+const CAPTURE_FRAMES: usize = 300;
+const TRACE_FILE: &str = "raycast_trace.csv";
+const TRACE_HEADER: &str = "frame,cursor_x,cursor_y,hand,sleeve,disengage";
+const MARKER_RADIUS: f32 = 0.05;
+
+#[derive(Clone, Copy, Debug, Enum, PartialEq)]
+enum Area {
+    Hand,
+    Sleeve,
+    Disengage,
+}
+impl Area {
+    fn color(self) -> Color {
+        match self {
+            Area::Hand => Color::CYAN,
+            Area::Sleeve => Color::GOLD,
+            Area::Disengage => Color::FUCHSIA,
+        }
+    }
+}
+
+/// The entity each ray hits first, and where.
+type Hits = EnumMap<Area, Option<(Entity, Vec3)>>;
+
+#[derive(SystemParam)]
+struct Raycasts<'w, 's> {
+    hand: Query<'w, 's, &'static RayCastSource<HandRaycast>>,
+    sleeve: Query<'w, 's, &'static RayCastSource<SleeveArea>>,
+    disengage: Query<'w, 's, &'static RayCastSource<HandDisengageArea>>,
+}
+impl<'w, 's> Raycasts<'w, 's> {
+    fn hits(&self) -> Hits {
+        fn top<T: Send + Sync + 'static>(
+            rays: &Query<&RayCastSource<T>>,
+        ) -> Option<(Entity, Vec3)> {
+            let (entity, hit) = rays.iter().find_map(|ray| ray.intersect_top())?;
+            Some((entity, hit.position()))
+        }
+        enum_map! {
+            Area::Hand => top(&self.hand),
+            Area::Sleeve => top(&self.sleeve),
+            Area::Disengage => top(&self.disengage),
+        }
+    }
+}
+
+/// Whether the ray hits are shown.
+#[derive(Default)]
+struct ShowHits(bool);
+
+/// Sphere marking where the ray of an [`Area`] hits.
+#[derive(Component)]
+struct HitMarker(Area);
+
+/// A line of [`TRACE_FILE`], hits are empty when the ray hits nothing.
+fn trace_line(frame: usize, cursor: Option<Vec2>, hits: &Hits) -> String {
+    let cursor = cursor.map_or_else(|| ",".to_owned(), |c| format!("{},{}", c.x, c.y));
+    let hit = |area| hits[area].map_or_else(String::new, |(entity, _)| format!("{entity:?}"));
+    let (hand, sleeve, disengage) = (hit(Area::Hand), hit(Area::Sleeve), hit(Area::Disengage));
+    format!("{frame},{cursor},{hand},{sleeve},{disengage}")
+}
+
+fn spawn_markers(
+    mut cmds: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let sphere = meshes.add(Mesh::from(shape::UVSphere {
+        radius: MARKER_RADIUS,
+        ..default()
+    }));
+    for (area, _) in Hits::default() {
+        let material =
+            materials.add(StandardMaterial { base_color: area.color(), unlit: true, ..default() });
+        cmds.spawn_bundle(PbrBundle {
+            mesh: sphere.clone(),
+            material,
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert_bundle((HitMarker(area), Name::new(format!("{area:?} hit marker"))));
+    }
+}
+
+fn toggle_hits(mut show: ResMut<ShowHits>, mut keys: ResMut<Input<KeyCode>>) {
+    if keys.just_pressed(TOGGLE_KEY) {
+        show.0 = !show.0;
+        debug_print!(sec: 3.0, "raycast debug: {}", if show.0 { "on" } else { "off" });
+        keys.reset(TOGGLE_KEY);
+    }
+}
+
+/// Move the [`HitMarker`]s to the ray hits, outline the hit hand hit box and
+/// print it when it changes.
+fn show_hits(
+    mut cmds: Commands,
+    mut outlined: Local<Option<Entity>>,
+    mut markers: Query<(&HitMarker, &mut Transform, &mut Visibility)>,
+    show: Res<ShowHits>,
+    raycasts: Raycasts,
+    cursor: Res<CursorPosition>,
+    windows: Res<Windows>,
+) {
+    let hits = if show.0 { raycasts.hits() } else { Hits::default() };
+    for (HitMarker(area), mut transform, mut visibility) in markers.iter_mut() {
+        let hit = hits[*area];
+        if visibility.is_visible != hit.is_some() {
+            visibility.is_visible = hit.is_some();
+        }
+        if let Some((_, position)) = hit {
+            transform.translation = position;
+        }
+    }
+    let hit = hits[Area::Hand].map(|(entity, _)| entity);
+    if hit == *outlined {
+        return;
+    }
+    if let Some(previous) = *outlined {
+        // The card may have been played since
+        if let Some(mut previous) = cmds.get_entity(previous) {
+            previous.remove::<Wireframe>();
+        }
+    }
+    if let Some(hit) = hit {
+        cmds.entity(hit).insert(Wireframe);
+    }
+    if show.0 {
+        let cursor = cursor.valid(&windows);
+        debug_print!(sec: 3.0, "hand hit: {hit:?}, cursor: {cursor:?}");
+    }
+    *outlined = hit;
+}
+
+/// Record a trace of [`CAPTURE_FRAMES`] frames once [`CAPTURE_KEY`] is
+/// pressed, and write it to [`TRACE_FILE`].
+fn capture_trace(
+    mut trace: Local<Option<Vec<String>>>,
+    mut keys: ResMut<Input<KeyCode>>,
+    raycasts: Raycasts,
+    cursor: Res<CursorPosition>,
+    windows: Res<Windows>,
+) {
+    if keys.just_pressed(CAPTURE_KEY) && trace.is_none() {
+        debug_print!(sec: 3.0, "capturing {CAPTURE_FRAMES} frames of raycast trace");
+        *trace = Some(vec![TRACE_HEADER.to_owned()]);
+        keys.reset(CAPTURE_KEY);
+    }
+    let lines = match trace.as_mut() {
+        Some(lines) => lines,
+        None => return,
+    };
+    let frame = lines.len() - 1;
+    lines.push(trace_line(frame, cursor.valid(&windows), &raycasts.hits()));
+    if frame + 1 < CAPTURE_FRAMES {
+        return;
+    }
+    let text = lines
+        .iter()
+        .map(|line| format!("{line}\n"))
+        .collect::<String>();
+    match persist::write(TRACE_FILE, &text) {
+        Ok(()) => debug_print!(sec: 5.0, "raycast trace saved to {TRACE_FILE}"),
+        Err(err) => debug_print!(sec: 5.0, col: Color::RED, "{err:#}"),
+    }
+    *trace = None;
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShowHits>()
+            .add_startup_system(spawn_markers)
+            .add_system(toggle_hits)
+            .add_system(show_hits.after("cursor").after(toggle_hits))
+            .add_system(capture_trace.after("cursor"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trace_lines_match_header() {
+        let hand = Entity::from_raw(12);
+        let mut hits = Hits::default();
+        hits[Area::Hand] = Some((hand, Vec3::ZERO));
+        let line = trace_line(3, Some(Vec2::new(640.0, 360.5)), &hits);
+        assert_eq!(line, format!("3,640,360.5,{hand:?},,"));
+        let columns = TRACE_HEADER.split(',').count();
+        assert_eq!(line.split(',').count(), columns);
+        assert_eq!(trace_line(0, None, &Hits::default()), "0,,,,,");
+    }
+}
@@ -0,0 +1,240 @@
+//! Tunable game rules.
+use crate::{lang::Strings, persist::Entries, war::Value, Participant};
+
+/// Who leads each battle, see [`GameRules::initiative`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InitiativeRule {
+    /// The participants take turns leading battles.
+    Alternate,
+    /// The winner of a battle leads the next one, ties keep the same leader.
+    WinnerLeads,
+}
+impl InitiativeRule {
+    const ALL: [Self; 2] = [Self::Alternate, Self::WinnerLeads];
+
+    /// Key of the rule name in [`Strings`].
+    ///
+    /// [`Strings`]: crate::lang::Strings
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Self::Alternate => "initiative.alternate",
+            Self::WinnerLeads => "initiative.winner_leads",
+        }
+    }
+    /// The rule after this one, for cycling through them in the menu.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|r| *r == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+    /// Name of the rule in saves.
+    fn id(self) -> &'static str {
+        match self {
+            Self::Alternate => "alternate",
+            Self::WinnerLeads => "winner_leads",
+        }
+    }
+    fn from_id(id: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|rule| rule.id() == id)
+    }
+}
+
+/// How a game is won, checked before each turn by
+/// [`crate::game_flow::check_win`].
+///
+/// The conditions other than [`WinCondition::ScoreGap`] fall back to it once
+/// they can't be met anymore, see their docs.
+#[derive(Clone, Debug, PartialEq)]
+pub enum WinCondition {
+    /// Lead by more points than the cards not played yet can earn.
+    ScoreGap,
+    /// Be the first to score that many points, the higher score winning if
+    /// both get there in the same battle. Decided by the score gap if neither
+    /// gets there.
+    TargetScore(i32),
+    /// Be the first to have cards of these values in the score pile, as many
+    /// times as they are listed. Decided by the score gap once all cards are
+    /// played.
+    CollectSet(Vec<Value>),
+    /// Last that many battles without the score gap deciding the game
+    /// against the player, the higher score winning after them.
+    SurviveTurns(usize),
+}
+impl WinCondition {
+    /// The conditions the main menu cycles through.
+    fn presets() -> [Self; 4] {
+        use WinCondition::*;
+        [
+            ScoreGap,
+            TargetScore(40),
+            CollectSet(vec![Value::Zero; 3]),
+            SurviveTurns(8),
+        ]
+    }
+    /// The condition after this one, for cycling through them in the menu.
+    pub fn next(&self) -> Self {
+        let presets = Self::presets();
+        let index = presets.iter().position(|c| c == self).unwrap_or(0);
+        presets[(index + 1) % presets.len()].clone()
+    }
+    /// Short name of the condition, for the menu.
+    pub fn label(&self, strings: &Strings) -> String {
+        let key = match self {
+            WinCondition::ScoreGap => "objective.label.score_gap",
+            WinCondition::TargetScore(_) => "objective.label.target_score",
+            WinCondition::CollectSet(_) => "objective.label.collect_set",
+            WinCondition::SurviveTurns(_) => "objective.label.survive_turns",
+        };
+        strings.format(key, &[("value", self.value().as_str())])
+    }
+    /// One line objective, for the game ui and the restart menu.
+    pub fn describe(&self, strings: &Strings) -> String {
+        let key = match self {
+            WinCondition::ScoreGap => "objective.score_gap",
+            WinCondition::TargetScore(_) => "objective.target_score",
+            WinCondition::CollectSet(_) => "objective.collect_set",
+            WinCondition::SurviveTurns(_) => "objective.survive_turns",
+        };
+        strings.format(key, &[("value", self.value().as_str())])
+    }
+    fn value(&self) -> String {
+        match self {
+            WinCondition::ScoreGap => String::new(),
+            WinCondition::TargetScore(points) => points.to_string(),
+            WinCondition::CollectSet(values) => {
+                let values: Vec<_> = values.iter().map(|v| (*v as i32).to_string()).collect();
+                values.join(", ")
+            }
+            WinCondition::SurviveTurns(battles) => battles.to_string(),
+        }
+    }
+    /// The condition in saves: its name, then its value if any, such as
+    /// `collect_set 0 0 0`.
+    fn to_text(&self) -> String {
+        match self {
+            WinCondition::ScoreGap => "score_gap".to_owned(),
+            WinCondition::TargetScore(points) => format!("target_score {points}"),
+            WinCondition::CollectSet(values) => {
+                let values = values.iter().map(|v| format!(" {}", *v as i32));
+                format!("collect_set{}", values.collect::<String>())
+            }
+            WinCondition::SurviveTurns(battles) => format!("survive_turns {battles}"),
+        }
+    }
+    fn parse(text: &str) -> Option<Self> {
+        let mut words = text.split_whitespace();
+        let condition = match words.next()? {
+            "score_gap" => WinCondition::ScoreGap,
+            "target_score" => WinCondition::TargetScore(words.next()?.parse().ok()?),
+            "collect_set" => {
+                let values = words.by_ref().map(|value| value.parse().ok());
+                WinCondition::CollectSet(values.collect::<Option<_>>()?)
+            }
+            "survive_turns" => WinCondition::SurviveTurns(words.next()?.parse().ok()?),
+            _ => return None,
+        };
+        words.next().is_none().then(|| condition)
+    }
+}
+
+/// Game balance values, read by the systems they affect.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameRules {
+    /// How many cards the sleeve can hold.
+    pub sleeve_capacity: usize,
+    /// Seeds needed to distract the bird before any card was sleeved.
+    pub base_distraction_cost: usize,
+    /// Extra seeds needed to distract the bird per card sleeved this game.
+    pub distraction_cost_per_sleeve: usize,
+    /// Games the bird stays vigilant after catching the player cheating.
+    pub bird_memory_games: usize,
+    /// Extra seeds needed to distract a vigilant bird.
+    pub vigilant_distraction_cost: usize,
+    /// Most seeds the player can hold.
+    pub seed_cap: usize,
+    /// Points given instead of a seed when the player already holds
+    /// `seed_cap` seeds.
+    pub seed_overflow_points: i32,
+    /// Play the card game straight, without the sleeve and the bird.
+    pub fair_game: bool,
+    /// [`Egeq`] cards pay out even when their side loses the battle, rather
+    /// than only on a win or a tie, see [`BattleResolved::egeq_payouts`].
+    ///
+    /// [`Egeq`]: crate::war::WordOfPower::Egeq
+    /// [`BattleResolved::egeq_payouts`]: crate::game_flow::BattleResolved::egeq_payouts
+    pub egeq_pays_on_loss: bool,
+    /// Points given instead of a seed in a fair game, where seeds are of no
+    /// use.
+    pub fair_seed_points: i32,
+    /// Points the player card must beat the oppo card by to earn a shiny
+    /// trinket, see [`Inventory`].
+    ///
+    /// [`Inventory`]: crate::game_flow::Inventory
+    pub trinket_margin: i32,
+    /// Points the player card must beat the oppo card by to glimpse the value
+    /// of the next oppo card, see [`Glimpse`].
+    ///
+    /// [`Glimpse`]: crate::game_flow::Glimpse
+    pub glimpse_margin: i32,
+    /// Who leads each battle.
+    pub initiative: InitiativeRule,
+    /// Who leads the first battle, a coin flip decides when `None`, see
+    /// [`crate::coin_flip`].
+    pub first_leader: Option<Participant>,
+    /// Two local players share the player side, taking turns at the hand,
+    /// see [`CoopTurn`].
+    ///
+    /// [`CoopTurn`]: crate::game_flow::CoopTurn
+    pub coop: bool,
+    /// How the game is won, picked in the main menu.
+    pub win_condition: WinCondition,
+}
+impl Default for GameRules {
+    fn default() -> Self {
+        Self {
+            sleeve_capacity: 3,
+            base_distraction_cost: 1,
+            distraction_cost_per_sleeve: 1,
+            bird_memory_games: 2,
+            vigilant_distraction_cost: 1,
+            seed_cap: 3,
+            seed_overflow_points: 2,
+            fair_game: false,
+            egeq_pays_on_loss: false,
+            fair_seed_points: 2,
+            trinket_margin: 6,
+            glimpse_margin: 5,
+            initiative: InitiativeRule::Alternate,
+            first_leader: None,
+            coop: false,
+            win_condition: WinCondition::ScoreGap,
+        }
+    }
+}
+impl GameRules {
+    /// Seeds needed to distract the bird, the bird gets less tolerant with
+    /// each card successfully sleeved this game.
+    pub fn distraction_cost(&self, sleeved_this_game: usize) -> usize {
+        self.base_distraction_cost + self.distraction_cost_per_sleeve * sleeved_this_game
+    }
+    /// The rules saved in `entries` by [`GameRules::write_entries`], the
+    /// others are the defaults.
+    pub fn from_entries(entries: &Entries) -> Option<Self> {
+        Some(Self {
+            fair_game: entries.get("rules.fair_game")?,
+            egeq_pays_on_loss: entries.get("rules.egeq_pays_on_loss")?,
+            initiative: InitiativeRule::from_id(entries.get_str("rules.initiative")?)?,
+            coop: entries.get("rules.coop")?,
+            win_condition: WinCondition::parse(entries.get_str("rules.win_condition")?)?,
+            ..Self::default()
+        })
+    }
+    /// Save the rules a game can be played with, the balance values are the
+    /// same for all games.
+    pub fn write_entries(&self, entries: &mut Entries) {
+        entries.set("rules.fair_game", self.fair_game);
+        entries.set("rules.egeq_pays_on_loss", self.egeq_pays_on_loss);
+        entries.set("rules.initiative", self.initiative.id());
+        entries.set("rules.coop", self.coop);
+        entries.set("rules.win_condition", self.win_condition.to_text());
+    }
+}
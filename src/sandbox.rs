@@ -0,0 +1,356 @@
+//! Practice table, reached from the rules of the main menu.
+//!
+//! The player picks the value and word of a card for each side, then resolves
+//! the battle: both cards are spawned and fly to the war pile, then go to the
+//! piles of who gets them following [`Battle`]. The effect display explains
+//! the result with [`EffectEvent::BattleResult`].
+//!
+//! Only the card, pile, numbers and game ui plugins run here, there are no
+//! decks, opponent or cheating.
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use bevy_scene_hook::HookedSceneState;
+use bevy_ui_build_macros::{build_ui, rect, size, style, unit};
+use bevy_ui_navigation::prelude::*;
+use enum_map::Enum;
+
+use crate::{
+    animate::GameSpeed,
+    audio::AudioRequest,
+    card::SpawnCard,
+    cleanup_marked,
+    game_flow::{Battle, Scores},
+    game_ui::{EffectEvent, ScoreEvent},
+    lang::{Localized, Strings},
+    pile::{Pile, PileCard, PileType},
+    scene::Graveyard,
+    settings::{Action, KeyBindings},
+    state::GameState,
+    ui::{Assets as UiAssets, MenuCursor},
+    war::{Card, Value, WordOfPower},
+    CardOrigin, Participant,
+};
+
+/// Seconds the cards fly toward the war pile before the battle is resolved.
+const RESOLVE_DELAY: f32 = 1.2;
+
+#[derive(Clone, Component)]
+struct SandboxRoot;
+
+/// Cards spawned on the practice table, despawned when leaving it.
+#[derive(Component)]
+struct SandboxCard;
+
+#[derive(Clone, Copy, Component)]
+enum SandboxElem {
+    Value(Participant),
+    Word(Participant),
+    Resolve,
+    Back,
+}
+
+/// The cards picked for each side.
+struct Picks {
+    player: Card,
+    oppo: Card,
+}
+impl Default for Picks {
+    fn default() -> Self {
+        Self {
+            player: Card { value: Value::Five, word: None },
+            oppo: Card { value: Value::Three, word: None },
+        }
+    }
+}
+impl Picks {
+    fn card(&self, who: Participant) -> &Card {
+        match who {
+            Participant::Player => &self.player,
+            Participant::Oppo => &self.oppo,
+        }
+    }
+    fn card_mut(&mut self, who: Participant) -> &mut Card {
+        match who {
+            Participant::Player => &mut self.player,
+            Participant::Oppo => &mut self.oppo,
+        }
+    }
+}
+
+/// When to resolve the battle of the cards flying to the war pile, in
+/// seconds since startup.
+#[derive(Default)]
+struct PendingBattle(Option<f64>);
+
+/// The value after `value`, back to zero after nine.
+fn next_value(value: Value) -> Value {
+    Value::from_usize((value.into_usize() + 1) % Value::LENGTH)
+}
+
+/// The word after `word`, among no word and the implemented ones.
+fn next_word(word: Option<WordOfPower>) -> Option<WordOfPower> {
+    let words = (0..WordOfPower::LENGTH).map(WordOfPower::from_usize);
+    let choices: Vec<_> = std::iter::once(None)
+        .chain(words.filter(|w| w.is_implemented()).map(Some))
+        .collect();
+    let index = choices.iter().position(|choice| *choice == word);
+    choices[index.map_or(0, |index| (index + 1) % choices.len())]
+}
+
+fn elem_text(elem: SandboxElem, picks: &Picks, strings: &Strings) -> String {
+    match elem {
+        SandboxElem::Value(who) => {
+            let value = picks.card(who).value_i32().to_string();
+            strings.format("sandbox.value", &[("value", value.as_str())])
+        }
+        SandboxElem::Word(who) => {
+            let word = match picks.card(who).word {
+                Some(word) => format!("{word:?}"),
+                None => strings.get("sandbox.no_word").to_owned(),
+            };
+            strings.format("sandbox.word", &[("word", word.as_str())])
+        }
+        SandboxElem::Resolve => strings.get("sandbox.resolve").to_owned(),
+        SandboxElem::Back => strings.get("menu.back").to_owned(),
+    }
+}
+
+fn setup_sandbox(
+    mut cmds: Commands,
+    ui_assets: Res<UiAssets>,
+    strings: Res<Strings>,
+    picks: Res<Picks>,
+) {
+    use Participant::{Oppo, Player};
+    use SandboxElem::{Back, Resolve, Value, Word};
+
+    let node = NodeBundle {
+        color: Color::NONE.into(),
+        style: style! {
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::Center,
+        },
+        ..default()
+    };
+    let label = |key| ui_assets.text_bundle(strings.get(key), 30.0);
+    let button = |elem| ui_assets.large_text(&elem_text(elem, &picks, &strings));
+    let panel_position = UiRect {
+        left: Val::Px(30.0),
+        top: Val::Percent(15.0),
+        ..default()
+    };
+    let cursor = MenuCursor::spawn_ui_element(&mut cmds);
+    build_ui! {
+        #[cmd(cmds)]
+        node{ size: size!(100 pct, 100 pct) }[; Name::new("Sandbox root"), SandboxRoot](
+            id(cursor),
+            node{
+                position_type: PositionType::Absolute,
+                position: panel_position,
+                padding: rect!(20 px)
+            }[; UiColor(Color::rgba(0.1, 0.1, 0.1, 0.7)), Name::new("Sandbox panel")](
+                entity[ui_assets.large_text(strings.get("sandbox.title")); Localized("sandbox.title")],
+                entity[label("sandbox.player_card"); Localized("sandbox.player_card")],
+                entity[button(Value(Player)); Focusable::new().prioritized(), Value(Player)],
+                entity[button(Word(Player)); Focusable::default(), Word(Player)],
+                entity[label("sandbox.oppo_card"); Localized("sandbox.oppo_card")],
+                entity[button(Value(Oppo)); Focusable::default(), Value(Oppo)],
+                entity[button(Word(Oppo)); Focusable::default(), Word(Oppo)],
+                entity[button(Resolve); Focusable::default(), Resolve],
+                entity[button(Back); Focusable::default(), Back]
+            )
+        )
+    };
+}
+
+/// Spawn the picked cards and send them to the war pile.
+fn play_picks(spawner: &mut SpawnCard, piles: &mut Query<&mut Pile>, picks: &Picks) -> Option<()> {
+    let mut war = piles.iter_mut().find(|pile| pile.which == PileType::War)?;
+    // The player leads, the oppo card answers it
+    for who in [Participant::Player, Participant::Oppo] {
+        let entity = spawner.spawn_card(picks.card(who).clone(), who).id();
+        let pile_card = war.add_existing(entity);
+        spawner
+            .cmds
+            .entity(entity)
+            .insert_bundle((pile_card, SandboxCard));
+    }
+    Some(())
+}
+
+fn handle_elems(
+    mut nav_events: EventReader<NavEvent>,
+    mut game_state: ResMut<State<GameState>>,
+    mut picks: ResMut<Picks>,
+    mut pending: ResMut<PendingBattle>,
+    mut spawner: SpawnCard,
+    mut piles: Query<&mut Pile>,
+    mut effects: EventWriter<EffectEvent>,
+    mut audio: EventWriter<AudioRequest>,
+    elems: Query<&SandboxElem>,
+    scene: HookedSceneState<Graveyard>,
+    bindings: Res<KeyBindings>,
+    keys: Res<Input<KeyCode>>,
+    time: Res<Time>,
+    speed: Res<GameSpeed>,
+) {
+    let mut leave = bindings.just_pressed(Action::Pause, &keys);
+    for elem in nav_events.nav_iter().activated_in_query(&elems) {
+        match *elem {
+            SandboxElem::Value(who) => {
+                let card = picks.card_mut(who);
+                card.value = next_value(card.value);
+            }
+            SandboxElem::Word(who) => {
+                let card = picks.card_mut(who);
+                card.word = next_word(card.word);
+            }
+            SandboxElem::Resolve if pending.0.is_none() && scene.is_loaded() => {
+                if play_picks(&mut spawner, &mut piles, &picks).is_some() {
+                    effects.send(EffectEvent::Hide);
+                    audio.send(AudioRequest::PlayShuffleLong);
+                    let delay = speed.duration(RESOLVE_DELAY) as f64;
+                    pending.0 = Some(time.seconds_since_startup() + delay);
+                }
+            }
+            // Cards are already flying to the war pile
+            SandboxElem::Resolve => {}
+            SandboxElem::Back => leave = true,
+        }
+    }
+    if leave {
+        let msg = "Sandbox can always go back to the main menu";
+        game_state.set(GameState::MainMenu).expect(msg);
+    }
+}
+
+/// Give the war pile cards to who wins them, once they reached the pile.
+fn resolve_battle(
+    mut cmds: Commands,
+    mut pending: ResMut<PendingBattle>,
+    mut piles: Query<&mut Pile>,
+    mut scores: ResMut<Scores>,
+    mut score_events: EventWriter<ScoreEvent>,
+    mut effects: EventWriter<EffectEvent>,
+    cards: Query<(Entity, &Card, &CardOrigin, &PileCard), With<SandboxCard>>,
+    time: Res<Time>,
+) {
+    match pending.0 {
+        Some(at) if at <= time.seconds_since_startup() => pending.0 = None,
+        _ => return,
+    }
+    let played = |who| {
+        let in_war = |(_, _, origin, pile): &(Entity, &Card, &CardOrigin, &PileCard)| {
+            origin.0 == who && pile.which == PileType::War
+        };
+        cards.iter().find(in_war)
+    };
+    let (player, oppo) = match (played(Participant::Player), played(Participant::Oppo)) {
+        (Some(player), Some(oppo)) => (player, oppo),
+        _ => return,
+    };
+    let battle = Battle::new(player.1, oppo.1);
+    for (entity, (owner, _)) in [(player.0, battle.player_card), (oppo.0, battle.oppo_card)] {
+        for mut pile in piles.iter_mut() {
+            if pile.which == PileType::from(owner) {
+                cmds.entity(entity).insert(pile.add_existing(entity));
+            } else if pile.which == PileType::War {
+                pile.remove(entity);
+            }
+        }
+    }
+    let (mut player_points, mut oppo_points) = (0, 0);
+    for (who, points) in battle.rewards() {
+        scores.add_to_owner(who, points);
+        score_events.send(ScoreEvent::Add(who, points));
+        match who {
+            Participant::Player => player_points += points,
+            Participant::Oppo => oppo_points += points,
+        }
+    }
+    let words: Vec<_> = [player.1, oppo.1].iter().filter_map(|c| c.word).collect();
+    if !words.is_empty() {
+        effects.send(EffectEvent::PlayedWords(words));
+    }
+    effects.send(EffectEvent::BattleResult {
+        outcome: battle.outcome,
+        player: player_points,
+        oppo: oppo_points,
+    });
+}
+
+fn update_elem_texts(
+    picks: Res<Picks>,
+    strings: Res<Strings>,
+    mut elems: Query<(&mut Text, &SandboxElem)>,
+) {
+    if !picks.is_changed() && !strings.is_changed() {
+        return;
+    }
+    for (mut text, elem) in elems.iter_mut() {
+        text.sections[0].value = elem_text(*elem, &picks, &strings);
+    }
+}
+
+/// Remove the practice cards from the piles and despawn them, reset the
+/// scores they brought.
+fn cleanup(
+    mut cmds: Commands,
+    mut piles: Query<&mut Pile>,
+    mut scores: ResMut<Scores>,
+    mut pending: ResMut<PendingBattle>,
+    cards: Query<Entity, With<SandboxCard>>,
+) {
+    for entity in cards.iter() {
+        for mut pile in piles.iter_mut() {
+            pile.remove(entity);
+        }
+        cmds.entity(entity).despawn_recursive();
+    }
+    *scores = Scores::default();
+    pending.0 = None;
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        app.init_resource::<Picks>()
+            .init_resource::<PendingBattle>()
+            .add_system_set(self.0.on_enter(setup_sandbox))
+            .add_system_set(
+                self.0
+                    .on_update(handle_elems.after(NavRequestSystem))
+                    .with_system(resolve_battle)
+                    .with_system(update_elem_texts.after(handle_elems)),
+            )
+            .add_system_set(
+                self.0
+                    .on_exit(cleanup_marked::<SandboxRoot>)
+                    .with_system(cleanup),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_cycle_back_to_zero() {
+        assert_eq!(next_value(Value::Zero), Value::One);
+        assert_eq!(next_value(Value::Nine), Value::Zero);
+    }
+
+    #[test]
+    fn words_cycle_through_implemented_ones() {
+        let mut word = next_word(None);
+        let mut seen = Vec::new();
+        while let Some(shown) = word {
+            assert!(shown.is_implemented());
+            assert!(!seen.contains(&shown), "{shown:?} shown twice");
+            seen.push(shown);
+            word = next_word(word);
+        }
+        assert_eq!(seen.len(), 4);
+    }
+}
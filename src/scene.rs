@@ -1,43 +1,254 @@
 //! Load the game scene and add `Component`s from all modules to entities named
 //! in the scene.
-use std::f32::consts::TAU;
+//!
+//! Once the scene is hooked, [`check_anchors`] makes sure each [`Anchor`] the
+//! game needs is in it exactly once. Missing anchors the game can do without
+//! get a fallback entity, otherwise the scene is [`SceneCheck::Broken`] and
+//! the game doesn't start.
+use std::{f32::consts::TAU, fmt};
 
 use bevy::{
-    ecs::system::EntityCommands,
+    ecs::system::{EntityCommands, SystemParam},
     math::EulerRot::XYZ,
     pbr::wireframe::Wireframe,
     prelude::{Plugin as BevyPlugin, *},
 };
 use bevy_mod_raycast::{RayCastMesh, RayCastSource};
-use bevy_scene_hook::{HookedSceneBundle, SceneHook};
+use bevy_scene_hook::{HookedSceneBundle, HookedSceneState, SceneHook};
 
 use crate::{
     animate::Animated,
     card::{OppoCardSpawner, PlayerCardSpawner},
     cheat::{BirdPupil, BirdPupilRoot, PlayerSleeve},
-    deck::DeckAssets,
+    deck::Deck,
     game_ui::{OppoScore, PlayerScore},
-    numbers::Number,
+    numbers::{CountUp, Number},
     oppo_hand::OppoHand,
-    pile::{Pile, PileType},
-    player_hand::{CardCollisionAssets, HandDisengageArea, HandRaycast, PlayerHand, SleeveArea},
+    oppo_mood::{OppoBody, OppoEyeGlow, OppoHead},
+    pile::{Pile, PileType, TableRaycast},
+    player_hand::{
+        CardCollisionAssets, DropZone, DropZoneAssets, HandDisengageArea, HandRaycast, PlayerHand,
+        SleeveArea,
+    },
+    state::GameState,
+    system_helper::EasySystemSetCtor,
     Participant,
 };
 
 #[derive(Component)]
 pub struct Graveyard;
 
-fn hook(
+/// A component [`hook`] adds to a node of the scene, that systems expect
+/// exactly one of.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Anchor {
+    PlayerHand,
+    OppoHand,
+    PlayerCardSpawner,
+    OppoCardSpawner,
+    WarPile,
+    PlayerPile,
+    OppoPile,
+    Sleeve,
+    BirdPupil,
+    BirdPupilRoot,
+    RaycastSource,
+}
+impl Anchor {
+    const ALL: [Self; 11] = [
+        Self::PlayerHand,
+        Self::OppoHand,
+        Self::PlayerCardSpawner,
+        Self::OppoCardSpawner,
+        Self::WarPile,
+        Self::PlayerPile,
+        Self::OppoPile,
+        Self::Sleeve,
+        Self::BirdPupil,
+        Self::BirdPupilRoot,
+        Self::RaycastSource,
+    ];
+
+    /// Name of the scene node the anchor is added to.
+    pub fn node_name(self) -> &'static str {
+        match self {
+            Self::PlayerHand => "PlayerHand",
+            Self::OppoHand => "OppoHand",
+            Self::PlayerCardSpawner => "PlayerCardSpawn",
+            Self::OppoCardSpawner => "OppoCardSpawn",
+            Self::WarPile => "Pile",
+            Self::PlayerPile => "PlayerPile",
+            Self::OppoPile => "OppoPile",
+            Self::Sleeve => "PlayerSleeveStash",
+            Self::BirdPupil => "BirdPupillaSprite",
+            Self::BirdPupilRoot => "BirdEyePupilla",
+            Self::RaycastSource => "PlayerPerspective_Orientation",
+        }
+    }
+    /// Whether the game is unplayable without this anchor: the player
+    /// couldn't pick or drop cards, nor see the scores.
+    fn is_critical(self) -> bool {
+        let critical = [Self::PlayerHand, Self::RaycastSource];
+        critical.contains(&self) || self.pile().is_some()
+    }
+    fn pile(self) -> Option<PileType> {
+        match self {
+            Self::WarPile => Some(PileType::War),
+            Self::PlayerPile => Some(PileType::Player),
+            Self::OppoPile => Some(PileType::Oppo),
+            _ => None,
+        }
+    }
+    /// Spawn a stand-in for this missing anchor at the scene origin.
+    ///
+    /// Fallbacks have no raycast meshes nor children: without a sleeve
+    /// area, the player can't cheat, but can still play.
+    fn spawn_fallback(self, cmds: &mut Commands) {
+        let mut cmds = cmds.spawn_bundle(SpatialBundle::default());
+        cmds.insert(Name::new(format!("{} (fallback)", self.node_name())));
+        match self {
+            Self::OppoHand => cmds.insert_bundle((OppoHand, Animated::bob(1.0, 0.3, 6.0))),
+            Self::PlayerCardSpawner => cmds.insert(PlayerCardSpawner),
+            Self::OppoCardSpawner => cmds.insert(OppoCardSpawner),
+            Self::Sleeve => cmds.insert(PlayerSleeve),
+            Self::BirdPupil => cmds.insert(BirdPupil),
+            Self::BirdPupilRoot => {
+                cmds.insert_bundle((BirdPupilRoot, Animated::bob(5. / 4., 0.02, 5.0)))
+            }
+            critical => unreachable!("{critical:?} has no fallback"),
+        };
+    }
+}
+
+/// The anchors missing from the scene or found several times in it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnchorReport {
+    pub missing: Vec<Anchor>,
+    pub duplicate: Vec<Anchor>,
+}
+impl AnchorReport {
+    fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.duplicate.is_empty()
+    }
+    /// Whether the game can't start: a critical anchor is missing, or any
+    /// anchor is duplicated, since systems can't tell which one to use.
+    fn is_fatal(&self) -> bool {
+        let critical_missing = self.missing.iter().any(|anchor| anchor.is_critical());
+        critical_missing || !self.duplicate.is_empty()
+    }
+    /// Names of the faulty scene nodes, comma-separated.
+    pub fn node_names(&self) -> String {
+        node_names(&[&self.missing[..], &self.duplicate[..]].concat())
+    }
+}
+impl fmt::Display for AnchorReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (missing, duplicate) = (node_names(&self.missing), node_names(&self.duplicate));
+        write!(f, "missing: [{missing}], duplicate: [{duplicate}]")
+    }
+}
+
+fn node_names(anchors: &[Anchor]) -> String {
+    let names: Vec<_> = anchors.iter().map(|anchor| anchor.node_name()).collect();
+    names.join(", ")
+}
+
+/// Whether the hooked scene has the [`Anchor`]s the game needs.
+pub enum SceneCheck {
+    /// The scene is still loading.
+    Pending,
+    /// All anchors are there, some may be fallbacks.
+    Playable,
+    Broken(AnchorReport),
+}
+impl Default for SceneCheck {
+    fn default() -> Self {
+        SceneCheck::Pending
+    }
+}
+
+/// The entities of each [`Anchor`].
+#[derive(SystemParam)]
+struct Anchors<'w, 's> {
+    player_hand: Query<'w, 's, (), With<PlayerHand>>,
+    oppo_hand: Query<'w, 's, (), With<OppoHand>>,
+    player_spawner: Query<'w, 's, (), With<PlayerCardSpawner>>,
+    oppo_spawner: Query<'w, 's, (), With<OppoCardSpawner>>,
+    piles: Query<'w, 's, &'static Pile>,
+    sleeve: Query<'w, 's, (), With<PlayerSleeve>>,
+    bird_pupil: Query<'w, 's, (), With<BirdPupil>>,
+    bird_pupil_root: Query<'w, 's, (), With<BirdPupilRoot>>,
+    raycast_source: Query<'w, 's, (), With<RayCastSource<HandRaycast>>>,
+}
+impl<'w, 's> Anchors<'w, 's> {
+    fn count(&self, anchor: Anchor) -> usize {
+        match anchor {
+            Anchor::PlayerHand => self.player_hand.iter().count(),
+            Anchor::OppoHand => self.oppo_hand.iter().count(),
+            Anchor::PlayerCardSpawner => self.player_spawner.iter().count(),
+            Anchor::OppoCardSpawner => self.oppo_spawner.iter().count(),
+            Anchor::WarPile | Anchor::PlayerPile | Anchor::OppoPile => {
+                let is_anchor = |pile: &&Pile| Some(pile.which) == anchor.pile();
+                self.piles.iter().filter(is_anchor).count()
+            }
+            Anchor::Sleeve => self.sleeve.iter().count(),
+            Anchor::BirdPupil => self.bird_pupil.iter().count(),
+            Anchor::BirdPupilRoot => self.bird_pupil_root.iter().count(),
+            Anchor::RaycastSource => self.raycast_source.iter().count(),
+        }
+    }
+    fn report(&self) -> AnchorReport {
+        let mut report = AnchorReport::default();
+        for anchor in Anchor::ALL {
+            match self.count(anchor) {
+                0 => report.missing.push(anchor),
+                1 => {}
+                _ => report.duplicate.push(anchor),
+            }
+        }
+        report
+    }
+}
+
+/// Check the [`Anchor`]s of the scene once it is hooked, spawning fallbacks
+/// for the missing ones the game can do without, see [`SceneCheck`].
+fn check_anchors(
+    mut cmds: Commands,
+    mut check: ResMut<SceneCheck>,
+    scene: HookedSceneState<Graveyard>,
+    anchors: Anchors,
+) {
+    if !matches!(*check, SceneCheck::Pending) || !scene.is_loaded() {
+        return;
+    }
+    let report = anchors.report();
+    if !report.is_empty() {
+        error!("The scene doesn't match what the game expects, {report}");
+    }
+    if report.is_fatal() {
+        *check = SceneCheck::Broken(report);
+        return;
+    }
+    for anchor in &report.missing {
+        anchor.spawn_fallback(&mut cmds);
+    }
+    *check = SceneCheck::Playable;
+}
+
+/// Add the components the game needs to the scene node `name`, also used to
+/// set up the table of the scripted input tests.
+pub fn hook(
     card_meshes: &CardCollisionAssets,
-    decks: &DeckAssets,
+    drop_zones: &DropZoneAssets,
     name: &str,
     cmds: &mut EntityCommands,
 ) {
     use Participant::{Oppo, Player};
     let participant = if name.starts_with("Oppo") { Oppo } else { Player };
     match name {
-        "PlayerDeck" => cmds.insert(decks.player.clone_weak()),
-        "OppoDeck" => cmds.insert(decks.oppo.clone_weak()),
+        // Placeholders, the decks depend on the selected archetype and
+        // opponent, see `crate::deck::load_decks`
+        "PlayerDeck" | "OppoDeck" => cmds.insert(Handle::<Deck>::default()),
         "PlayerHand" => cmds
             .insert_bundle((PlayerHand, Animated::bob(2.0, 0.05, 7.0)))
             .with_children(|cmds| {
@@ -53,7 +264,10 @@ fn hook(
                         scale: Vec3::new(1.75, 1.5, 1.75),
                         translation: Vec3::ZERO,
                     },
-                ));
+                ))
+                .with_children(|cmds| {
+                    drop_zones.spawn_overlay(cmds, DropZone::Disengage, &card_meshes.circle);
+                });
             }),
         "PlayerSleeveStash" => cmds.insert(PlayerSleeve).with_children(|cmds| {
             cmds.spawn_bundle((
@@ -68,20 +282,48 @@ fn hook(
                     scale: Vec3::new(1., 0.7, 1.),
                     translation: Vec3::new(0., 1.7, 0.2),
                 },
-            ));
+            ))
+            .with_children(|cmds| {
+                drop_zones.spawn_overlay(cmds, DropZone::Sleeve, &card_meshes.circle);
+            });
         }),
         "PlayerPerspective_Orientation" => cmds.insert_bundle((
             RayCastSource::<HandRaycast>::new(),
             RayCastSource::<SleeveArea>::new(),
             RayCastSource::<HandDisengageArea>::new(),
+            RayCastSource::<TableRaycast>::new(),
         )),
         "PlayerCardSpawn" => cmds.insert(PlayerCardSpawner),
         "OppoCardSpawn" => cmds.insert(OppoCardSpawner),
         "OppoHand" => cmds.insert_bundle((OppoHand, Animated::bob(1.0, 0.3, 6.0))),
-        "Pile" => cmds.insert(Pile::new(PileType::War)),
+        "Pile" => cmds.insert(Pile::new(PileType::War)).with_children(|cmds| {
+            cmds.spawn_bundle((
+                Name::new("War pile area"),
+                card_meshes.circle.clone_weak(),
+                Wireframe,
+                RayCastMesh::<TableRaycast>::default(),
+                Visibility::default(),
+                ComputedVisibility::default(),
+                GlobalTransform::default(),
+                Transform::from_scale(Vec3::splat(1.2)),
+            ))
+            .with_children(|cmds| {
+                drop_zones.spawn_overlay(cmds, DropZone::WarPile, &card_meshes.circle);
+            });
+        }),
         "OppoPile" | "PlayerPile" => {
             cmds.insert(Pile::new(participant.into()))
                 .with_children(|cmds| {
+                    cmds.spawn_bundle((
+                        Name::new(participant.name().to_owned() + " pile area"),
+                        card_meshes.circle.clone_weak(),
+                        Wireframe,
+                        RayCastMesh::<TableRaycast>::default(),
+                        Visibility::default(),
+                        ComputedVisibility::default(),
+                        GlobalTransform::default(),
+                        Transform::from_scale(Vec3::splat(1.2)),
+                    ));
                     let pile_rotation = match participant {
                         Oppo => Quat::from_euler(XYZ, TAU / 4., 0.5, 0.0),
                         Player => Quat::from_euler(XYZ, TAU / 4., -1.1, 0.0),
@@ -94,6 +336,7 @@ fn hook(
                     let mut cmds = cmds.spawn_bundle((
                         Name::new(participant.name().to_owned() + " score"),
                         Number::new(0, participant.color()),
+                        CountUp::default(),
                     ));
                     cmds.insert_bundle(SpatialBundle { transform, ..default() });
 
@@ -103,8 +346,23 @@ fn hook(
                     };
                 })
         }
-        "ManBody" => cmds.insert(Animated::breath(0.0, 0.03, 6.0)),
-        "ManHead" => cmds.insert(Animated::bob(6. / 4., 0.1, 6.0)),
+        "ManBody" => cmds.insert_bundle((OppoBody, Animated::breath(0.0, 0.03, 6.0))),
+        "ManHead" => cmds
+            .insert_bundle((OppoHead, Animated::bob(6. / 4., 0.1, 6.0)))
+            .with_children(|cmds| {
+                let light = PointLight {
+                    color: Color::RED,
+                    intensity: 0.0,
+                    range: 2.0,
+                    ..default()
+                };
+                cmds.spawn_bundle(PointLightBundle {
+                    point_light: light,
+                    transform: Transform::from_xyz(0.0, 0.2, 0.4),
+                    ..default()
+                })
+                .insert_bundle((Name::new("Oppo eye glow"), OppoEyeGlow));
+            }),
         "Bird" => cmds.insert(Animated::breath(0.0, 0.075, 5.0)),
         "BirdPupillaSprite" => cmds.insert(BirdPupil),
         "BirdEyePupilla" => cmds.insert_bundle((BirdPupilRoot, Animated::bob(5. / 4., 0.02, 5.0))),
@@ -114,15 +372,15 @@ fn hook(
 fn load_scene(
     mut cmds: Commands,
     card_meshes: Res<CardCollisionAssets>,
-    decks: Res<DeckAssets>,
+    drop_zones: Res<DropZoneAssets>,
     asset_server: Res<AssetServer>,
 ) {
     let card_meshes = card_meshes.clone();
-    let decks = decks.clone();
+    let drop_zones = drop_zones.clone();
     cmds.spawn_bundle(HookedSceneBundle {
         hook: SceneHook::new(
             move |entity, cmds| match entity.get::<Name>().map(|n| n.as_str()) {
-                Some(name) => hook(&card_meshes, &decks, name, cmds),
+                Some(name) => hook(&card_meshes, &drop_zones, name, cmds),
                 _ => {}
             },
         ),
@@ -137,6 +395,98 @@ fn load_scene(
 pub struct Plugin;
 impl BevyPlugin for Plugin {
     fn build(&self, app: &mut App) {
-        app.add_startup_system(load_scene);
+        app.init_resource::<SceneCheck>()
+            .add_startup_system(load_scene)
+            .add_system_set(GameState::WaitLoaded.on_update(check_anchors));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::{CommandQueue, SystemState};
+
+    use super::*;
+
+    fn spawn_fallbacks(world: &mut World, anchors: &[Anchor]) {
+        let mut queue = CommandQueue::default();
+        let mut cmds = Commands::new(&mut queue, world);
+        for anchor in anchors {
+            anchor.spawn_fallback(&mut cmds);
+        }
+        queue.apply(world);
+    }
+
+    /// A scene with each anchor once, but the `missing` ones, and the
+    /// `duplicate` ones twice.
+    fn scene(missing: &[Anchor], duplicate: &[Anchor]) -> World {
+        let mut world = World::new();
+        for anchor in Anchor::ALL {
+            let count = if missing.contains(&anchor) {
+                0
+            } else if duplicate.contains(&anchor) {
+                2
+            } else {
+                1
+            };
+            for _ in 0..count {
+                let source = RayCastSource::<HandRaycast>::new();
+                match (anchor, anchor.pile()) {
+                    (_, Some(pile)) => {
+                        world.spawn().insert(Pile::new(pile));
+                    }
+                    (Anchor::PlayerHand, _) => {
+                        world.spawn().insert(PlayerHand);
+                    }
+                    (Anchor::RaycastSource, _) => {
+                        world.spawn().insert(source);
+                    }
+                    (anchor, _) => spawn_fallbacks(&mut world, &[anchor]),
+                }
+            }
+        }
+        world
+    }
+
+    fn report(world: &mut World) -> AnchorReport {
+        let mut anchors = SystemState::<Anchors>::new(world);
+        anchors.get_mut(world).report()
+    }
+
+    #[test]
+    fn complete_scenes_pass() {
+        let report = report(&mut scene(&[], &[]));
+        assert_eq!(report, AnchorReport::default());
+        assert!(report.is_empty() && !report.is_fatal());
+    }
+
+    #[test]
+    fn missing_anchors_fall_back() {
+        let missing = [Anchor::OppoHand, Anchor::Sleeve, Anchor::BirdPupilRoot];
+        let mut world = scene(&missing, &[]);
+        let found = report(&mut world);
+        assert_eq!(found.missing, missing);
+        assert!(!found.is_fatal(), "the game can do without them");
+        assert_eq!(
+            found.node_names(),
+            "OppoHand, PlayerSleeveStash, BirdEyePupilla"
+        );
+        spawn_fallbacks(&mut world, &found.missing);
+        assert!(report(&mut world).is_empty());
+    }
+
+    #[test]
+    fn critical_or_duplicate_anchors_break_the_scene() {
+        let no_war_pile = report(&mut scene(&[Anchor::WarPile], &[]));
+        assert!(no_war_pile.is_fatal());
+        let blind = report(&mut scene(&[Anchor::RaycastSource, Anchor::OppoHand], &[]));
+        assert!(blind.is_fatal());
+        assert_eq!(
+            blind.node_names(),
+            "OppoHand, PlayerPerspective_Orientation"
+        );
+        let two_hands = report(&mut scene(&[], &[Anchor::OppoHand]));
+        assert_eq!(two_hands.duplicate, [Anchor::OppoHand]);
+        assert!(two_hands.missing.is_empty());
+        assert!(two_hands.is_fatal(), "systems can't tell which hand to use");
     }
 }
@@ -0,0 +1,569 @@
+//! Screenshots of the final board, saved from the restart menu.
+//!
+//! When the game ends, [`start_capture`] spawns a [`CaptureCamera`], a copy
+//! of the player camera rendering to an image rather than the window, without
+//! the ui. The render world then copies the image to a buffer and reads it
+//! back, over the next frames, through the [`Readback`] both worlds share.
+//!
+//! The restart menu waits for the readback before showing up, since leaving
+//! [`GameState::Playing`] despawns the board, see [`Screenshot::is_pending`].
+//! It gives up on the screenshot after [`CAPTURE_TIMEOUT`] seconds.
+//!
+//! Once read back, the [`Screenshot`] is ready, and the restart menu offers to
+//! save it: [`SaveScreenshot`] writes the board, with the end reason and the
+//! final score as a banner, in a png file of the user data directory, or as a
+//! download on wasm, see [`persist::export`]. Encoding and writing the png
+//! happens on the IO task pool, the outcome shows up a few frames later.
+//!
+//! The [`Screenshot`] stays unavailable where the readback fails, or without
+//! a renderer.
+use std::num::NonZeroU32;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use ab_glyph::{point, Font as _, PxScale, ScaleFont};
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use bevy::render::{
+    camera::{Projection, RenderTarget},
+    render_asset::RenderAssets,
+    render_resource::{
+        Buffer, BufferDescriptor, BufferUsages, CommandEncoderDescriptor, Extent3d,
+        ImageCopyBuffer, ImageDataLayout, MapMode, TextureDimension, TextureFormat, TextureUsages,
+    },
+    renderer::{RenderDevice, RenderQueue},
+    texture::BevyDefault,
+    RenderApp, RenderStage,
+};
+use bevy::tasks::IoTaskPool;
+use bevy::ui::UiCameraConfig;
+
+use crate::{
+    game_event::GameEvent, game_flow::Scores, history, lang::Strings, persist, state::GameState,
+    system_helper::EasySystemSetCtor, EndReason,
+};
+
+/// Rows copied out of a texture must be a multiple of this many bytes.
+const ROW_ALIGNMENT: u32 = 256;
+/// How much the banner darkens the board under it.
+const BANNER_SHADE: f32 = 0.6;
+/// Seconds to wait for the readback before giving up on the screenshot.
+const CAPTURE_TIMEOUT: f64 = 2.0;
+
+/// The final board, read back from the render world.
+#[derive(Clone)]
+pub struct Capture {
+    rgba: Vec<u8>,
+    width: u32,
+    height: u32,
+    /// The text lines written over the board when saving it.
+    banner: Vec<String>,
+}
+impl Capture {
+    /// Write the capture with its banner as a png, where the player finds it,
+    /// `count` telling apart screenshots saved the same second.
+    fn save(mut self, font: Option<&impl ab_glyph::Font>, count: u32) -> anyhow::Result<String> {
+        if let Some(font) = font {
+            composite_banner(&mut self.rgba, self.width, self.height, font, &self.banner);
+        }
+        let png = encode_png(&self.rgba, self.width, self.height)?;
+        let name = format!("warlocks-gambit-{}-{count}.png", history::now());
+        persist::export(&name, &png)
+    }
+}
+
+/// The screenshot of the last game's final board.
+pub enum Screenshot {
+    /// No game ended yet, or capturing the board failed.
+    Unavailable,
+    /// The board is being read back, with the `banner` of the game, until
+    /// `deadline`, in seconds since startup.
+    Pending { banner: Vec<String>, deadline: f64 },
+    /// The player may save the board.
+    Ready {
+        capture: Capture,
+        /// Where it was last saved, or why saving it failed.
+        saved: Option<Result<String, String>>,
+    },
+}
+impl Default for Screenshot {
+    fn default() -> Self {
+        Screenshot::Unavailable
+    }
+}
+impl Screenshot {
+    pub fn is_ready(&self) -> bool {
+        matches!(self, Screenshot::Ready { .. })
+    }
+    /// Whether the board is still being read back, and should stay spawned.
+    pub fn is_pending(&self) -> bool {
+        matches!(self, Screenshot::Pending { .. })
+    }
+    /// Where the screenshot was last saved, or why saving it failed.
+    pub fn saved(&self) -> Option<&Result<String, String>> {
+        match self {
+            Screenshot::Ready { saved, .. } => saved.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+/// Save the [`Screenshot`], if it is ready.
+pub struct SaveScreenshot;
+
+/// The camera rendering the final board to an image.
+#[derive(Component)]
+struct CaptureCamera;
+
+/// The capture image, copied to `buffer` and waiting for it to be mapped.
+struct Mapping {
+    buffer: Buffer,
+    width: u32,
+    height: u32,
+    padded_row: u32,
+    bgra: bool,
+    /// Set by the map callback, `true` if the buffer can be read.
+    mapped: Arc<Mutex<Option<bool>>>,
+}
+impl Mapping {
+    fn read(&self) -> Vec<u8> {
+        let padded = self.buffer.slice(..).get_mapped_range();
+        let rgba = unpad_rows(&padded, self.width, self.height, self.padded_row, self.bgra);
+        drop(padded);
+        self.buffer.unmap();
+        rgba
+    }
+}
+
+enum ReadbackState {
+    Idle,
+    /// The capture camera renders to `image`, copy it once it is on the gpu.
+    Requested(Handle<Image>),
+    Mapping(Mapping),
+    Done {
+        rgba: Vec<u8>,
+        width: u32,
+        height: u32,
+    },
+    Failed,
+}
+
+/// The capture readback, shared between the main and render worlds.
+#[derive(Clone)]
+struct Readback {
+    state: Arc<Mutex<ReadbackState>>,
+    /// Whether there is a render world to read the capture back.
+    supported: bool,
+}
+impl Readback {
+    fn lock(&self) -> MutexGuard<ReadbackState> {
+        self.state.lock().unwrap()
+    }
+}
+
+/// Bytes per row of a `width` pixels wide capture, once aligned for copying.
+fn padded_row_size(width: u32) -> u32 {
+    let row = width * 4;
+    (row + ROW_ALIGNMENT - 1) / ROW_ALIGNMENT * ROW_ALIGNMENT
+}
+
+/// The rgba pixels of `padded` rows, swapping the red and blue channels of
+/// `bgra` pixels. The alpha is opaque, whatever the clear color.
+fn unpad_rows(padded: &[u8], width: u32, height: u32, padded_row: u32, bgra: bool) -> Vec<u8> {
+    let row = (width * 4) as usize;
+    let rows = padded.chunks(padded_row as usize).take(height as usize);
+    let mut rgba: Vec<u8> = rows.flat_map(|padded| &padded[..row]).copied().collect();
+    for pixel in rgba.chunks_exact_mut(4) {
+        if bgra {
+            pixel.swap(0, 2);
+        }
+        pixel[3] = 255;
+    }
+    rgba
+}
+
+/// Darken the bottom of the `width` by `height` `rgba` image, and write the
+/// `lines` over it, centered.
+pub fn composite_banner(
+    rgba: &mut [u8],
+    width: u32,
+    height: u32,
+    font: &impl ab_glyph::Font,
+    lines: &[String],
+) {
+    assert_eq!(rgba.len(), (width * height * 4) as usize);
+    let line_height = (height as f32 / 16.0).max(8.0);
+    let scaled = font.as_scaled(PxScale::from(line_height));
+    let band_height = line_height * (lines.len() as f32 + 0.5);
+    let band_top = height.saturating_sub(band_height as u32);
+    for pixel in rgba[(band_top * width * 4) as usize..].chunks_exact_mut(4) {
+        for channel in &mut pixel[..3] {
+            *channel = (*channel as f32 * (1.0 - BANNER_SHADE)) as u8;
+        }
+    }
+    for (i, line) in lines.iter().enumerate() {
+        let mut glyphs = Vec::new();
+        let mut caret = 0.0;
+        let mut previous = None;
+        for c in line.chars() {
+            let id = scaled.glyph_id(c);
+            if let Some(previous) = previous {
+                caret += scaled.kern(previous, id);
+            }
+            glyphs.push(id.with_scale_and_position(line_height, point(caret, 0.0)));
+            caret += scaled.h_advance(id);
+            previous = Some(id);
+        }
+        let left = ((width as f32 - caret) / 2.0).max(0.0);
+        let baseline = band_top as f32 + line_height * (i as f32 + 0.25) + scaled.ascent();
+        for mut glyph in glyphs {
+            glyph.position = point(glyph.position.x + left, baseline);
+            let outline = match font.outline_glyph(glyph) {
+                Some(outline) => outline,
+                None => continue,
+            };
+            let bounds = outline.px_bounds();
+            outline.draw(|x, y, coverage| {
+                let x = bounds.min.x as i64 + x as i64;
+                let y = bounds.min.y as i64 + y as i64;
+                if !(0..width as i64).contains(&x) || !(0..height as i64).contains(&y) {
+                    return;
+                }
+                let offset = ((y as u32 * width + x as u32) * 4) as usize;
+                for channel in &mut rgba[offset..offset + 3] {
+                    let blended = *channel as f32 * (1.0 - coverage) + 255.0 * coverage;
+                    *channel = blended as u8;
+                }
+            });
+        }
+    }
+}
+
+fn encode_png(rgba: &[u8], width: u32, height: u32) -> anyhow::Result<Vec<u8>> {
+    use image::{codecs::png::PngEncoder, ColorType, ImageEncoder};
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png).write_image(rgba, width, height, ColorType::Rgba8)?;
+    Ok(png)
+}
+
+/// The end reason and final score, written over the screenshot.
+fn banner_lines(strings: &Strings, reason: EndReason, scores: &Scores) -> Vec<String> {
+    let (player, oppo) = (scores.player().to_string(), scores.oppo().to_string());
+    let score = [("player", player.as_str()), ("oppo", oppo.as_str())];
+    let reason_key = format!("gameover.{}", reason.name());
+    vec![
+        strings.get(&reason_key).to_owned(),
+        strings.format("screenshot.score", &score),
+    ]
+}
+
+/// Render the board to an image when the game ends.
+fn start_capture(
+    mut cmds: Commands,
+    mut events: EventReader<GameEvent>,
+    mut screenshot: ResMut<Screenshot>,
+    mut images: ResMut<Assets<Image>>,
+    readback: Res<Readback>,
+    strings: Res<Strings>,
+    scores: Res<Scores>,
+    windows: Res<Windows>,
+    time: Res<Time>,
+    cameras: Query<(&Camera3d, &Projection, &GlobalTransform), Without<CaptureCamera>>,
+) {
+    let reason = match events.iter().find_map(GameEvent::end) {
+        Some(reason) => reason,
+        None => return,
+    };
+    *screenshot = Screenshot::Unavailable;
+    let window = windows.get_primary();
+    let size = window.map(|w| (w.physical_width(), w.physical_height()));
+    let (camera_3d, projection, transform) = match (readback.supported, cameras.get_single()) {
+        (true, Ok(camera)) => camera,
+        _ => return,
+    };
+    let (width, height) = match size {
+        Some((width, height)) if width != 0 && height != 0 => (width, height),
+        _ => return,
+    };
+    let size = Extent3d { width, height, depth_or_array_layers: 1 };
+    let format = TextureFormat::bevy_default();
+    let mut image = Image::new_fill(size, TextureDimension::D2, &[0, 0, 0, 255], format);
+    image.texture_descriptor.usage |= TextureUsages::COPY_SRC | TextureUsages::RENDER_ATTACHMENT;
+    let image = images.add(image);
+    cmds.spawn_bundle(Camera3dBundle {
+        camera: Camera {
+            target: RenderTarget::Image(image.clone()),
+            ..default()
+        },
+        camera_3d: camera_3d.clone(),
+        projection: projection.clone(),
+        transform: transform.compute_transform(),
+        global_transform: *transform,
+        ..default()
+    })
+    .insert_bundle((
+        CaptureCamera,
+        UiCameraConfig { show_ui: false },
+        Name::new("Screenshot camera"),
+    ));
+    *readback.lock() = ReadbackState::Requested(image);
+    let banner = banner_lines(&strings, reason, &scores);
+    let deadline = time.seconds_since_startup() + CAPTURE_TIMEOUT;
+    *screenshot = Screenshot::Pending { banner, deadline };
+}
+
+fn despawn_capture_cameras(
+    cmds: &mut Commands,
+    images: &mut Assets<Image>,
+    cameras: &Query<(Entity, &Camera), With<CaptureCamera>>,
+) {
+    for (entity, camera) in cameras.iter() {
+        if let RenderTarget::Image(image) = &camera.target {
+            images.remove(image);
+        }
+        cmds.entity(entity).despawn_recursive();
+    }
+}
+
+/// Make the [`Screenshot`] ready once the render world read it back, or
+/// unavailable past its deadline.
+fn finish_capture(
+    mut cmds: Commands,
+    mut screenshot: ResMut<Screenshot>,
+    mut images: ResMut<Assets<Image>>,
+    readback: Res<Readback>,
+    time: Res<Time>,
+    cameras: Query<(Entity, &Camera), With<CaptureCamera>>,
+) {
+    let mut state = readback.lock();
+    let done = matches!(*state, ReadbackState::Done { .. } | ReadbackState::Failed);
+    let late = match &*screenshot {
+        Screenshot::Pending { deadline, .. } => time.seconds_since_startup() > *deadline,
+        _ => false,
+    };
+    if !done && !late {
+        return;
+    }
+    let finished = std::mem::replace(&mut *state, ReadbackState::Idle);
+    despawn_capture_cameras(&mut cmds, &mut images, &cameras);
+    let banner = match &mut *screenshot {
+        Screenshot::Pending { banner, .. } => std::mem::take(banner),
+        _ => return,
+    };
+    *screenshot = match finished {
+        ReadbackState::Done { rgba, width, height } => {
+            let capture = Capture { rgba, width, height, banner };
+            Screenshot::Ready { capture, saved: None }
+        }
+        _ => {
+            warn!("Couldn't capture the final board");
+            Screenshot::Unavailable
+        }
+    };
+}
+
+/// Forget the screenshot when leaving the restart menu.
+fn discard_capture(
+    mut cmds: Commands,
+    mut screenshot: ResMut<Screenshot>,
+    mut images: ResMut<Assets<Image>>,
+    readback: Res<Readback>,
+    cameras: Query<(Entity, &Camera), With<CaptureCamera>>,
+) {
+    *readback.lock() = ReadbackState::Idle;
+    despawn_capture_cameras(&mut cmds, &mut images, &cameras);
+    *screenshot = Screenshot::Unavailable;
+}
+
+/// Where a save running on the IO task pool puts its outcome.
+type SaveOutcome = Arc<Mutex<Option<Result<String, String>>>>;
+
+/// Save the [`Screenshot`] on the IO task pool, and report where it was saved
+/// once done. Outcomes of saves finishing after the restart menu are dropped.
+fn save_screenshot(
+    mut events: EventReader<SaveScreenshot>,
+    mut saved_count: Local<u32>,
+    outcome: Local<SaveOutcome>,
+    mut screenshot: ResMut<Screenshot>,
+    ui_assets: Res<crate::ui::Assets>,
+    fonts: Res<Assets<Font>>,
+) {
+    let requested = events.iter().count() != 0;
+    let finished = outcome.lock().unwrap().take();
+    let (capture, saved) = match &mut *screenshot {
+        Screenshot::Ready { capture, saved } => (capture, saved),
+        _ => return,
+    };
+    if finished.is_some() {
+        *saved = finished;
+    }
+    if !requested {
+        return;
+    }
+    let font = fonts.get(&ui_assets.font).map(|font| font.font.clone());
+    let capture = capture.clone();
+    *saved_count += 1;
+    let count = *saved_count;
+    let outcome = Arc::clone(&*outcome);
+    let save = async move {
+        let saved = capture.save(font.as_ref(), count);
+        if let Err(err) = &saved {
+            warn!("Couldn't save the screenshot: {err:#}");
+        }
+        *outcome.lock().unwrap() = Some(saved.map_err(|err| format!("{err:#}")));
+    };
+    IoTaskPool::get().spawn(save).detach();
+}
+
+/// Copy the capture image to a buffer once it was rendered, and map it.
+///
+/// Runs after the render graph, in the render world.
+fn copy_capture(
+    readback: Res<Readback>,
+    gpu_images: Res<RenderAssets<Image>>,
+    device: Res<RenderDevice>,
+    queue: Res<RenderQueue>,
+) {
+    let mut state = readback.lock();
+    let image = match &*state {
+        ReadbackState::Requested(image) => image,
+        _ => return,
+    };
+    // Not rendered yet, the camera renders to it the frame it is on the gpu
+    let gpu_image = match gpu_images.get(image) {
+        Some(gpu_image) => gpu_image,
+        None => return,
+    };
+    let bgra = match gpu_image.texture_format {
+        TextureFormat::Bgra8UnormSrgb | TextureFormat::Bgra8Unorm => true,
+        TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm => false,
+        _ => {
+            *state = ReadbackState::Failed;
+            return;
+        }
+    };
+    let (width, height) = (gpu_image.size.x as u32, gpu_image.size.y as u32);
+    let padded_row = padded_row_size(width);
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("screenshot readback"),
+        size: (padded_row * height) as u64,
+        usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor::default());
+    encoder.copy_texture_to_buffer(
+        gpu_image.texture.as_image_copy(),
+        ImageCopyBuffer {
+            buffer: &buffer,
+            layout: ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(padded_row),
+                rows_per_image: None,
+            },
+        },
+        Extent3d { width, height, depth_or_array_layers: 1 },
+    );
+    queue.submit([encoder.finish()]);
+    // wgpu calls back once the copy is done, when checking on the device,
+    // which it does on each queue submission, that is, every frame.
+    let mapped = Arc::new(Mutex::new(None));
+    let callback_mapped = Arc::clone(&mapped);
+    buffer.slice(..).map_async(MapMode::Read, move |result| {
+        *callback_mapped.lock().unwrap() = Some(result.is_ok());
+    });
+    *state = ReadbackState::Mapping(Mapping { buffer, width, height, padded_row, bgra, mapped });
+}
+
+/// Read the capture buffer once it is mapped, without waiting for it.
+fn read_capture(readback: Res<Readback>) {
+    let mut state = readback.lock();
+    let mapping = match &*state {
+        ReadbackState::Mapping(mapping) => mapping,
+        _ => return,
+    };
+    let mapped = *mapping.mapped.lock().unwrap();
+    let next = match mapped {
+        None => return,
+        Some(true) => ReadbackState::Done {
+            rgba: mapping.read(),
+            width: mapping.width,
+            height: mapping.height,
+        },
+        Some(false) => ReadbackState::Failed,
+    };
+    *state = next;
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        let state = Arc::new(Mutex::new(ReadbackState::Idle));
+        let mut readback = Readback { state, supported: false };
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            readback.supported = true;
+            render_app
+                .insert_resource(readback.clone())
+                .add_system_to_stage(RenderStage::Cleanup, read_capture)
+                .add_system_to_stage(RenderStage::Cleanup, copy_capture.after(read_capture));
+        }
+        app.insert_resource(readback)
+            .init_resource::<Screenshot>()
+            .add_event::<SaveScreenshot>()
+            .add_system(start_capture.label("start_capture"))
+            .add_system(finish_capture)
+            .add_system(save_screenshot)
+            .add_system_set(GameState::RestartMenu.on_exit(discard_capture));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readback_rows_lose_their_padding() {
+        assert_eq!(padded_row_size(64), 256);
+        assert_eq!(padded_row_size(65), 512);
+        #[rustfmt::skip]
+        let padded = [
+            1, 2, 3, 0, 4, 5, 6, 0, 9, 9,
+            7, 8, 9, 0, 10, 11, 12, 0, 9, 9,
+        ];
+        let rgba = unpad_rows(&padded, 2, 2, 10, false);
+        assert_eq!(
+            rgba,
+            [1, 2, 3, 255, 4, 5, 6, 255, 7, 8, 9, 255, 10, 11, 12, 255]
+        );
+        let rgba = unpad_rows(&padded, 2, 2, 10, true);
+        assert_eq!(&rgba[..8], [3, 2, 1, 255, 6, 5, 4, 255], "bgra is swapped");
+    }
+
+    #[test]
+    fn banners_shade_the_bottom_and_write_text() {
+        let font = include_bytes!("../assets/Boogaloo-Regular.otf");
+        let font = ab_glyph::FontRef::try_from_slice(font).unwrap();
+        let (width, height) = (640, 480);
+        let mut rgba = [128, 128, 128, 255].repeat((width * height) as usize);
+        let lines = ["Victory".to_owned(), "Final score: 12 to 7".to_owned()];
+        composite_banner(&mut rgba, width, height, &font, &lines);
+        let pixel = |x: u32, y: u32| {
+            let offset = ((y * width + x) * 4) as usize;
+            [
+                rgba[offset],
+                rgba[offset + 1],
+                rgba[offset + 2],
+                rgba[offset + 3],
+            ]
+        };
+        assert_eq!(pixel(10, 10), [128, 128, 128, 255], "the top is untouched");
+        assert_eq!(
+            pixel(0, height - 1),
+            [51, 51, 51, 255],
+            "the banner is shaded"
+        );
+        let band = (height - 75..height).flat_map(|y| (0..width).map(move |x| (x, y)));
+        let brightest = band.map(|(x, y)| pixel(x, y)[0]).max().unwrap();
+        assert!(
+            brightest > 200,
+            "the text is written, brightest is {brightest}"
+        );
+    }
+}
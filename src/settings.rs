@@ -0,0 +1,505 @@
+//! Player settings, persisted between game sessions.
+//!
+//! The [`KeyBindings`], edited in the key bindings menu, the [`Theme`], the
+//! [`Language`], the [`SelectedArchetype`] and the [`GameplaySettings`],
+//! such as the [`HandSort`], selected in the main menu.
+//!
+//! The settings file format is handled in [`persist::settings`].
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use enum_map::{enum_map, Enum, EnumMap};
+
+use crate::{
+    archetype::SelectedArchetype,
+    lang::Language,
+    persist::{self, Entries},
+    theme::Theme,
+};
+
+/// Games completed before the blunder warning turns itself off, see
+/// [`GameplaySettings::blunder_warning`].
+const BLUNDER_WARNING_GAMES: u32 = 5;
+/// Longest [`GameplaySettings::announce_beat`] loaded from the settings file,
+/// in seconds.
+const MAX_ANNOUNCE_BEAT: f32 = 3.0;
+
+/// Keys the player can bind an [`Action`] to.
+///
+/// Number keys are left out, they select cards in hand.
+#[rustfmt::skip]
+const BINDABLE_KEYS: [KeyCode; 53] = {
+    use KeyCode::*;
+    [
+        A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P, Q, R, S, T, U, V, W, X, Y, Z,
+        F1, F2, F3, F4, F5, F6, F7, F8, F9, F10, F11, F12,
+        Escape, Space, Return, Tab, Back, Insert, Delete, Home, End, PageUp, PageDown,
+        LShift, RShift, LControl, RControl,
+    ]
+};
+
+/// Something the player does with the keyboard.
+#[derive(Enum, Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    UseSeed,
+    UseTrinket,
+    Pause,
+    PlaySelected,
+    SleeveSelected,
+    Help,
+    QuickRestart,
+}
+impl Action {
+    pub const ALL: [Action; 7] = {
+        use Action::*;
+        [
+            UseSeed,
+            UseTrinket,
+            Pause,
+            PlaySelected,
+            SleeveSelected,
+            Help,
+            QuickRestart,
+        ]
+    };
+    /// Key of the action name in [`Strings`].
+    ///
+    /// [`Strings`]: crate::lang::Strings
+    pub fn name_key(self) -> &'static str {
+        match self {
+            Action::UseSeed => "action.use_seed",
+            Action::UseTrinket => "action.use_trinket",
+            Action::Pause => "action.pause",
+            Action::PlaySelected => "action.play_selected",
+            Action::SleeveSelected => "action.sleeve_selected",
+            Action::Help => "action.help",
+            Action::QuickRestart => "action.quick_restart",
+        }
+    }
+    fn setting_name(self) -> &'static str {
+        match self {
+            Action::UseSeed => "key.use_seed",
+            Action::UseTrinket => "key.use_trinket",
+            Action::Pause => "key.pause",
+            Action::PlaySelected => "key.play_selected",
+            Action::SleeveSelected => "key.sleeve_selected",
+            Action::Help => "key.help",
+            Action::QuickRestart => "key.quick_restart",
+        }
+    }
+}
+
+/// Why [`KeyBindings::rebind`] refused a key.
+#[derive(Debug, PartialEq)]
+pub enum RebindError {
+    /// Another action already uses this key.
+    Conflict(Action),
+    NotBindable,
+}
+
+/// The key bound to each [`Action`], no two actions share a key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyBindings(EnumMap<Action, KeyCode>);
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self(enum_map! {
+            Action::UseSeed => KeyCode::Space,
+            Action::UseTrinket => KeyCode::T,
+            Action::Pause => KeyCode::Escape,
+            Action::PlaySelected => KeyCode::Return,
+            Action::SleeveSelected => KeyCode::S,
+            Action::Help => KeyCode::H,
+            Action::QuickRestart => KeyCode::R,
+        })
+    }
+}
+impl KeyBindings {
+    pub fn key(&self, action: Action) -> KeyCode {
+        self.0[action]
+    }
+    pub fn just_pressed(&self, action: Action, keys: &Input<KeyCode>) -> bool {
+        keys.just_pressed(self.0[action])
+    }
+    /// The action, other than `action`, already bound to `key`.
+    pub fn conflict(&self, action: Action, key: KeyCode) -> Option<Action> {
+        let other_action = |(other, bound): (Action, &KeyCode)| other != action && *bound == key;
+        self.0
+            .iter()
+            .find(|pair| other_action(*pair))
+            .map(|(other, _)| other)
+    }
+    pub fn rebind(&mut self, action: Action, key: KeyCode) -> Result<(), RebindError> {
+        if !BINDABLE_KEYS.contains(&key) {
+            return Err(RebindError::NotBindable);
+        }
+        if let Some(other) = self.conflict(action, key) {
+            return Err(RebindError::Conflict(other));
+        }
+        self.0[action] = key;
+        Ok(())
+    }
+    /// Bindings in `entries`, using the default for missing or invalid ones.
+    fn from_entries(entries: &Entries) -> Self {
+        let mut bindings = Self::default();
+        let parse = |name: String| BINDABLE_KEYS.into_iter().find(|k| format!("{k:?}") == name);
+        for action in Action::ALL {
+            let key = entries.get(action.setting_name()).and_then(parse);
+            if let Some(key) = key {
+                // Swap, so that defaults don't conflict with the loaded binding
+                if let Some(other) = bindings.conflict(action, key) {
+                    bindings.0[other] = bindings.0[action];
+                }
+                bindings.0[action] = key;
+            }
+        }
+        bindings
+    }
+    fn write_entries(&self, entries: &mut Entries) {
+        for (action, key) in self.0.iter() {
+            entries.set(action.setting_name(), format!("{key:?}"));
+        }
+    }
+}
+
+/// How cards are ordered in hand, see [`GameplaySettings::hand_sort`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HandSort {
+    /// Cards stay in the order they were drawn.
+    Manual,
+    ValueAscending,
+    ValueDescending,
+    /// Cards with a word of power first.
+    WordsFirst,
+}
+impl Default for HandSort {
+    fn default() -> Self {
+        HandSort::Manual
+    }
+}
+impl HandSort {
+    pub const ALL: [HandSort; 4] = [
+        HandSort::Manual,
+        HandSort::ValueAscending,
+        HandSort::ValueDescending,
+        HandSort::WordsFirst,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            HandSort::Manual => "manual",
+            HandSort::ValueAscending => "value_ascending",
+            HandSort::ValueDescending => "value_descending",
+            HandSort::WordsFirst => "words_first",
+        }
+    }
+    /// Key of the sort name shown to the player in [`Strings`], `name` being
+    /// used to persist the setting.
+    ///
+    /// [`Strings`]: crate::lang::Strings
+    pub fn label_key(self) -> &'static str {
+        match self {
+            HandSort::Manual => "hand_sort.manual",
+            HandSort::ValueAscending => "hand_sort.value_ascending",
+            HandSort::ValueDescending => "hand_sort.value_descending",
+            HandSort::WordsFirst => "hand_sort.words_first",
+        }
+    }
+    /// The sort after this one, for cycling through them in the menu.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|s| *s == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// Optional parts of the game the player can turn off.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameplaySettings {
+    /// Show the deck compositions before the first draw.
+    pub deck_reveal: bool,
+    /// Shorten gameplay delays, see [`GameSpeed`].
+    ///
+    /// [`GameSpeed`]: crate::animate::GameSpeed
+    pub fast_mode: bool,
+    /// Replace particle effects with their banner alone.
+    pub reduce_motion: bool,
+    /// Skip purely cosmetic graphics, such as card shadows, for weaker
+    /// computers.
+    pub low_spec: bool,
+    /// Bolder value and word faces on cards, see [`crate::card`].
+    pub high_contrast_cards: bool,
+    /// Ask before playing a card that loses the battle when another card
+    /// wouldn't. `None` until the player picks, then it warns only for the
+    /// first [`BLUNDER_WARNING_GAMES`] games.
+    pub blunder_warning: Option<bool>,
+    /// Hide what viewers of a stream shouldn't see: sleeved cards show their
+    /// back, the deck reveal is skipped and piles can't be inspected.
+    pub streaming: bool,
+    /// Order of the cards in hand, sleeved cards coming back included.
+    pub hand_sort: HandSort,
+    /// Ease the oppo AI after a losing streak, see [`crate::opponent`].
+    pub adaptive_difficulty: bool,
+    /// The oppo whispers the words it draws, see [`crate::oppo_hand`].
+    pub demon_whispers: bool,
+    /// Seconds the oppo's lead is announced before the player can answer,
+    /// `0.0` to skip it, see [`crate::game_flow`].
+    pub announce_beat: f32,
+}
+impl Default for GameplaySettings {
+    fn default() -> Self {
+        Self {
+            deck_reveal: true,
+            fast_mode: false,
+            reduce_motion: false,
+            low_spec: false,
+            high_contrast_cards: false,
+            blunder_warning: None,
+            streaming: false,
+            hand_sort: HandSort::Manual,
+            adaptive_difficulty: false,
+            demon_whispers: true,
+            announce_beat: 0.8,
+        }
+    }
+}
+impl GameplaySettings {
+    fn from_entries(entries: &Entries) -> Self {
+        let default = Self::default();
+        let get = |key| entries.get(key);
+        Self {
+            deck_reveal: get("gameplay.deck_reveal").unwrap_or(default.deck_reveal),
+            fast_mode: get("gameplay.fast_mode").unwrap_or(default.fast_mode),
+            reduce_motion: get("gameplay.reduce_motion").unwrap_or(default.reduce_motion),
+            low_spec: get("gameplay.low_spec").unwrap_or(default.low_spec),
+            high_contrast_cards: get("gameplay.high_contrast_cards")
+                .unwrap_or(default.high_contrast_cards),
+            blunder_warning: get("gameplay.blunder_warning"),
+            streaming: get("gameplay.streaming").unwrap_or(default.streaming),
+            hand_sort: entries
+                .get("gameplay.hand_sort")
+                .and_then(|name: String| HandSort::ALL.into_iter().find(|s| s.name() == name))
+                .unwrap_or(default.hand_sort),
+            adaptive_difficulty: get("gameplay.adaptive_difficulty")
+                .unwrap_or(default.adaptive_difficulty),
+            demon_whispers: get("gameplay.demon_whispers").unwrap_or(default.demon_whispers),
+            announce_beat: get("gameplay.announce_beat")
+                .filter(|beat: &f32| beat.is_finite())
+                .map(|beat| beat.clamp(0.0, MAX_ANNOUNCE_BEAT))
+                .unwrap_or(default.announce_beat),
+        }
+    }
+    /// Whether to warn about blunders, `games` being the completed games.
+    pub fn warns_blunders(&self, games: u32) -> bool {
+        self.blunder_warning
+            .unwrap_or(games < BLUNDER_WARNING_GAMES)
+    }
+    fn write_entries(&self, entries: &mut Entries) {
+        entries.set("gameplay.deck_reveal", self.deck_reveal);
+        entries.set("gameplay.fast_mode", self.fast_mode);
+        entries.set("gameplay.reduce_motion", self.reduce_motion);
+        entries.set("gameplay.low_spec", self.low_spec);
+        entries.set("gameplay.high_contrast_cards", self.high_contrast_cards);
+        entries.set("gameplay.streaming", self.streaming);
+        entries.set("gameplay.hand_sort", self.hand_sort.name());
+        entries.set("gameplay.adaptive_difficulty", self.adaptive_difficulty);
+        entries.set("gameplay.demon_whispers", self.demon_whispers);
+        entries.set("gameplay.announce_beat", self.announce_beat);
+        if let Some(blunder_warning) = self.blunder_warning {
+            entries.set("gameplay.blunder_warning", blunder_warning);
+        }
+    }
+}
+
+/// The settings file was unreadable and got reset to defaults, the main menu
+/// tells the player once.
+pub struct SettingsReset(pub bool);
+
+fn load_settings() -> persist::settings::Loaded {
+    match persist::platform() {
+        Ok(mut storage) => persist::settings::load(&mut storage),
+        Err(err) => {
+            warn!("Starting with default settings: {err:#}");
+            persist::settings::Loaded::default()
+        }
+    }
+}
+
+fn theme_from_entries(entries: &Entries) -> Theme {
+    let parse = |name: String| Theme::ALL.into_iter().find(|t| t.name() == name);
+    entries.get("theme").and_then(parse).unwrap_or_default()
+}
+
+fn language_from_entries(entries: &Entries) -> Language {
+    let parse = |code: String| Language::ALL.into_iter().find(|l| l.code() == code);
+    entries.get("language").and_then(parse).unwrap_or_default()
+}
+
+fn archetype_from_entries(entries: &Entries) -> SelectedArchetype {
+    SelectedArchetype(entries.get("archetype").unwrap_or_default())
+}
+
+fn save_settings(
+    bindings: Res<KeyBindings>,
+    theme: Res<Theme>,
+    language: Res<Language>,
+    archetype: Res<SelectedArchetype>,
+    gameplay: Res<GameplaySettings>,
+) {
+    let bindings_changed = bindings.is_changed() && !bindings.is_added();
+    let theme_changed = theme.is_changed() && !theme.is_added();
+    let language_changed = language.is_changed() && !language.is_added();
+    let archetype_changed = archetype.is_changed() && !archetype.is_added();
+    let gameplay_changed = gameplay.is_changed() && !gameplay.is_added();
+    let look_changed = theme_changed || language_changed;
+    if bindings_changed || look_changed || archetype_changed || gameplay_changed {
+        let mut entries = Entries::default();
+        bindings.write_entries(&mut entries);
+        entries.set("theme", theme.name());
+        entries.set("language", language.code());
+        if !archetype.0.is_empty() {
+            entries.set("archetype", &archetype.0);
+        }
+        gameplay.write_entries(&mut entries);
+        let saved = persist::platform()
+            .and_then(|mut storage| persist::settings::save(&mut storage, entries));
+        if let Err(err) = saved {
+            warn!("{err:#}");
+        }
+    }
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        let persist::settings::Loaded { entries: settings, reset } = load_settings();
+        app.insert_resource(SettingsReset(reset))
+            .insert_resource(KeyBindings::from_entries(&settings))
+            .insert_resource(theme_from_entries(&settings))
+            .insert_resource(language_from_entries(&settings))
+            .insert_resource(archetype_from_entries(&settings))
+            .insert_resource(GameplaySettings::from_entries(&settings))
+            .add_system_to_stage(CoreStage::PostUpdate, save_settings);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Action::*;
+
+    #[test]
+    fn rebind_free_key() {
+        let mut bindings = KeyBindings::default();
+        assert_eq!(bindings.rebind(UseSeed, KeyCode::E), Ok(()));
+        assert_eq!(bindings.key(UseSeed), KeyCode::E);
+        assert_eq!(bindings.rebind(UseSeed, KeyCode::E), Ok(()));
+    }
+
+    #[test]
+    fn rebind_conflicting_key() {
+        let mut bindings = KeyBindings::default();
+        let sleeve_key = bindings.key(SleeveSelected);
+        let conflict = bindings.rebind(UseSeed, sleeve_key);
+        assert_eq!(conflict, Err(RebindError::Conflict(SleeveSelected)));
+        assert_eq!(bindings.key(UseSeed), KeyCode::Space);
+        assert_eq!(bindings.conflict(SleeveSelected, sleeve_key), None);
+    }
+
+    #[test]
+    fn rebind_unbindable_key() {
+        let mut bindings = KeyBindings::default();
+        let unbindable = bindings.rebind(Pause, KeyCode::Key1);
+        assert_eq!(unbindable, Err(RebindError::NotBindable));
+        assert_eq!(bindings.key(Pause), KeyCode::Escape);
+    }
+
+    #[test]
+    fn bindings_roundtrip() {
+        let mut bindings = KeyBindings::default();
+        bindings.rebind(Help, KeyCode::F1).unwrap();
+        bindings.rebind(Pause, KeyCode::P).unwrap();
+        let mut entries = Entries::default();
+        bindings.write_entries(&mut entries);
+        assert_eq!(KeyBindings::from_entries(&entries), bindings);
+    }
+
+    #[test]
+    fn theme_roundtrip() {
+        let mut entries = Entries::default();
+        entries.set("theme", Theme::Moonlit.name());
+        assert_eq!(theme_from_entries(&entries), Theme::Moonlit);
+        entries.set("theme", "Neon");
+        assert_eq!(theme_from_entries(&entries), Theme::Classic);
+    }
+
+    #[test]
+    fn language_roundtrip() {
+        let mut entries = Entries::default();
+        entries.set("language", Language::French.code());
+        assert_eq!(language_from_entries(&entries), Language::French);
+        entries.set("language", "tlh");
+        assert_eq!(language_from_entries(&entries), Language::English);
+    }
+
+    #[test]
+    fn archetype_roundtrip() {
+        let mut entries = Entries::default();
+        assert_eq!(
+            archetype_from_entries(&entries),
+            SelectedArchetype::default()
+        );
+        entries.set("archetype", "archetype.gambler");
+        assert_eq!(archetype_from_entries(&entries).0, "archetype.gambler");
+    }
+
+    #[test]
+    fn gameplay_settings_roundtrip() {
+        let settings = GameplaySettings {
+            deck_reveal: false,
+            fast_mode: true,
+            reduce_motion: true,
+            low_spec: true,
+            high_contrast_cards: true,
+            blunder_warning: Some(false),
+            streaming: true,
+            hand_sort: HandSort::WordsFirst,
+            adaptive_difficulty: true,
+            demon_whispers: false,
+            announce_beat: 1.5,
+        };
+        let mut entries = Entries::default();
+        settings.write_entries(&mut entries);
+        assert_eq!(GameplaySettings::from_entries(&entries), settings);
+        let garbage = Entries::parse(
+            "gameplay.deck_reveal=maybe\ngameplay.fast_mode=2\n\
+            gameplay.reduce_motion=x\ngameplay.blunder_warning=1\n\
+            gameplay.streaming=yes\ngameplay.hand_sort=shuffled\n\
+            gameplay.adaptive_difficulty=on\ngameplay.demon_whispers=off\n\
+            gameplay.announce_beat=NaN\n",
+        );
+        assert_eq!(
+            GameplaySettings::from_entries(&garbage),
+            GameplaySettings::default()
+        );
+        let endless = Entries::parse("gameplay.announce_beat=60\n");
+        let endless = GameplaySettings::from_entries(&endless);
+        assert_eq!(endless.announce_beat, MAX_ANNOUNCE_BEAT);
+    }
+
+    #[test]
+    fn blunder_warning_defaults_to_first_games() {
+        let auto = GameplaySettings::default();
+        assert!(auto.warns_blunders(0));
+        assert!(!auto.warns_blunders(BLUNDER_WARNING_GAMES));
+        let on = GameplaySettings { blunder_warning: Some(true), ..auto.clone() };
+        assert!(on.warns_blunders(100));
+        let off = GameplaySettings { blunder_warning: Some(false), ..auto };
+        assert!(!off.warns_blunders(0));
+    }
+
+    #[test]
+    fn loaded_binding_swaps_with_conflicting_default() {
+        let entries = Entries::parse("key.use_seed=S\nkey.help=NotAKey\n");
+        let bindings = KeyBindings::from_entries(&entries);
+        assert_eq!(bindings.key(UseSeed), KeyCode::S);
+        assert_eq!(bindings.key(SleeveSelected), KeyCode::Space);
+        assert_eq!(bindings.key(Help), KeyCode::H);
+    }
+}
@@ -2,12 +2,28 @@
 #[derive(Clone, Copy, Hash, PartialEq, Eq, Debug)]
 pub enum GameState {
     MainMenu,
+    /// Key bindings screen, reached from the main menu
+    KeyBindingsMenu,
+    /// Game history screen, reached from the main menu
+    HistoryMenu,
     /// Wait until the game scene is fully loaded if not already
     WaitLoaded,
     /// The game is running
+    ///
+    /// Its `on_enter` and `on_exit` systems run once per game, they set up
+    /// and tear down everything game related, including the game ui.
     Playing,
+    /// The game is paused, pushed on top of `Playing`
+    ///
+    /// Pushing and popping it only pauses and resumes `Playing`, so the game
+    /// isn't torn down. To leave the game from the pause menu, `replace` the
+    /// state: it unwinds the stack and runs the `Playing` `on_exit` systems.
+    PauseMenu,
     /// Restart menu after gameover
     RestartMenu,
+    /// Practice table, reached from the rules of the main menu, see
+    /// [`crate::sandbox`]
+    Sandbox,
 }
 
 // LEAD: potential improvement: logic in game_flow really does not care for the
@@ -28,4 +44,7 @@ pub enum TurnState {
     Oppo,
     /// A participants has played a card
     CardPlayed,
+    /// The game is paused, pushed on top of the current turn state so that
+    /// nothing happens until the game resumes
+    Paused,
 }
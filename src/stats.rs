@@ -0,0 +1,251 @@
+//! Player statistics, persisted between game sessions.
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use enum_map::EnumMap;
+
+use crate::{
+    achievements::Achievement,
+    cheat::{CheatEvent, Distraction},
+    game_event::GameEvent,
+    opponent::MAX_EASING,
+    persist,
+    rules::GameRules,
+    settings::GameplaySettings,
+    titles::Title,
+    EndReason,
+};
+
+const STATS_FILE: &str = "stats.txt";
+/// Games won or lost in a row that shift the oppo difficulty, see
+/// [`Stats::record_streak`].
+const STREAK_GAMES: i32 = 3;
+
+/// How a game changed the oppo difficulty, see [`Stats::record_streak`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DifficultyShift {
+    Easier,
+    Harder,
+}
+impl DifficultyShift {
+    /// Key of the restart menu notice in [`Strings`].
+    ///
+    /// [`Strings`]: crate::lang::Strings
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Self::Easier => "gameover.underestimated",
+            Self::Harder => "gameover.taken_seriously",
+        }
+    }
+}
+
+/// The [`DifficultyShift`] of the game that just ended, shown on the restart
+/// menu.
+#[derive(Default)]
+pub struct LastShift(pub Option<DifficultyShift>);
+
+#[derive(Default, Debug)]
+pub struct Stats {
+    pub wins: u32,
+    pub losses: u32,
+    pub caught_cheating: u32,
+    pub conceded: u32,
+    /// Games completed in a fair game, see [`GameRules::fair_game`].
+    pub fair_games: u32,
+    /// Games completed by two players, see [`GameRules::coop`].
+    pub coop_games: u32,
+    /// Cards put in the sleeve, whether the bird saw it or not.
+    pub sleeves: u32,
+    pub seeds_used: u32,
+    /// Whether the rules were automatically shown on the restart menu.
+    pub seen_defeat_rules: bool,
+    /// Games won in a row when positive, lost in a row when negative.
+    pub streak: i32,
+    /// Steps the oppo AI is made easier than the selected opponent, see
+    /// [`crate::opponent::AiWeights::eased`].
+    pub easing: u32,
+    /// Achievements unlocked so far.
+    pub achievements: EnumMap<Achievement, bool>,
+    /// How many times each title was earned.
+    pub titles: EnumMap<Title, u32>,
+}
+impl Stats {
+    fn load() -> Self {
+        match persist::load(STATS_FILE) {
+            Ok(entries) => Self {
+                wins: entries.get("wins").unwrap_or(0),
+                losses: entries.get("losses").unwrap_or(0),
+                caught_cheating: entries.get("caught_cheating").unwrap_or(0),
+                conceded: entries.get("conceded").unwrap_or(0),
+                fair_games: entries.get("fair_games").unwrap_or(0),
+                coop_games: entries.get("coop_games").unwrap_or(0),
+                sleeves: entries.get("sleeves").unwrap_or(0),
+                seeds_used: entries.get("seeds_used").unwrap_or(0),
+                seen_defeat_rules: entries.get("seen_defeat_rules").unwrap_or(false),
+                streak: entries.get("streak").unwrap_or(0),
+                easing: entries.get("easing").unwrap_or(0).min(MAX_EASING),
+                achievements: EnumMap::from_fn(|achievement| {
+                    entries.get(&achievement_key(achievement)).unwrap_or(false)
+                }),
+                titles: EnumMap::from_fn(|title| entries.get(&title_key(title)).unwrap_or(0)),
+            },
+            Err(err) => {
+                warn!("Starting with empty stats: {err:#}");
+                Self::default()
+            }
+        }
+    }
+    fn save(&self) -> anyhow::Result<()> {
+        let mut entries = persist::Entries::default();
+        entries.set("wins", self.wins);
+        entries.set("losses", self.losses);
+        entries.set("caught_cheating", self.caught_cheating);
+        entries.set("conceded", self.conceded);
+        entries.set("fair_games", self.fair_games);
+        entries.set("coop_games", self.coop_games);
+        entries.set("sleeves", self.sleeves);
+        entries.set("seeds_used", self.seeds_used);
+        entries.set("seen_defeat_rules", self.seen_defeat_rules);
+        entries.set("streak", self.streak);
+        entries.set("easing", self.easing);
+        for (achievement, unlocked) in self.achievements {
+            entries.set(&achievement_key(achievement), unlocked);
+        }
+        for (title, earned) in self.titles {
+            entries.set(&title_key(title), earned);
+        }
+        persist::save(STATS_FILE, &entries)
+    }
+    /// Games completed, however they ended.
+    pub fn games(&self) -> u32 {
+        self.wins + self.losses + self.caught_cheating + self.conceded
+    }
+    /// Count a game that ended for `reason` in the [`Stats::streak`], and
+    /// shift the [`Stats::easing`] after [`STREAK_GAMES`] in a row.
+    ///
+    /// Caught cheating and conceded games don't count, the AI had no part in
+    /// how they ended. The easing stays between `0`, the selected opponent,
+    /// and [`MAX_EASING`].
+    fn record_streak(&mut self, reason: EndReason) -> Option<DifficultyShift> {
+        match reason {
+            EndReason::Victory => self.streak = self.streak.max(0) + 1,
+            EndReason::Loss => self.streak = self.streak.min(0) - 1,
+            EndReason::CaughtCheating | EndReason::Conceded => return None,
+        }
+        if self.streak.abs() < STREAK_GAMES {
+            return None;
+        }
+        let won = self.streak > 0;
+        self.streak = 0;
+        match (won, self.easing) {
+            (true, 0) => None,
+            (true, _) => {
+                self.easing -= 1;
+                Some(DifficultyShift::Harder)
+            }
+            (false, MAX_EASING) => None,
+            (false, _) => {
+                self.easing += 1;
+                Some(DifficultyShift::Easier)
+            }
+        }
+    }
+}
+
+fn achievement_key(achievement: Achievement) -> String {
+    format!("achievement_{}", achievement.id())
+}
+
+fn title_key(title: Title) -> String {
+    format!("title_{}", title.id())
+}
+
+fn record_game_over(
+    mut stats: ResMut<Stats>,
+    mut last_shift: ResMut<LastShift>,
+    mut events: EventReader<GameEvent>,
+    rules: Res<GameRules>,
+    gameplay: Res<GameplaySettings>,
+) {
+    for reason in events.iter().filter_map(GameEvent::end) {
+        if rules.fair_game {
+            stats.fair_games += 1;
+        }
+        if rules.coop {
+            stats.coop_games += 1;
+        }
+        match reason {
+            EndReason::Victory => stats.wins += 1,
+            EndReason::Loss => stats.losses += 1,
+            EndReason::CaughtCheating => stats.caught_cheating += 1,
+            EndReason::Conceded => stats.conceded += 1,
+        }
+        last_shift.0 = if gameplay.adaptive_difficulty {
+            stats.record_streak(reason)
+        } else {
+            None
+        };
+    }
+}
+
+fn record_cheats(mut stats: ResMut<Stats>, mut events: EventReader<CheatEvent>) {
+    for event in events.iter() {
+        match event {
+            CheatEvent::HideInSleeve(_) => stats.sleeves += 1,
+            CheatEvent::ConfuseBird(Distraction::Seed) => stats.seeds_used += 1,
+            CheatEvent::ConfuseBird(Distraction::Trinket) => {}
+        }
+    }
+}
+
+fn save_stats(stats: Res<Stats>) {
+    if stats.is_changed() && !stats.is_added() {
+        if let Err(err) = stats.save() {
+            warn!("{err:#}");
+        }
+    }
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Stats::load())
+            .init_resource::<LastShift>()
+            .add_system(record_game_over.label("record_stats"))
+            .add_system(record_cheats)
+            .add_system_to_stage(CoreStage::PostUpdate, save_stats);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use EndReason::{Conceded, Loss, Victory};
+
+    #[test]
+    fn streaks_shift_the_easing() {
+        let mut stats = Stats::default();
+        assert_eq!(stats.record_streak(Loss), None);
+        assert_eq!(stats.record_streak(Loss), None);
+        assert_eq!(stats.record_streak(Conceded), None, "doesn't count");
+        assert_eq!(stats.record_streak(Loss), Some(DifficultyShift::Easier));
+        assert_eq!((stats.streak, stats.easing), (0, 1));
+        stats.record_streak(Loss);
+        stats.record_streak(Victory);
+        assert_eq!(stats.streak, 1, "a win breaks the losing streak");
+        stats.record_streak(Victory);
+        assert_eq!(stats.record_streak(Victory), Some(DifficultyShift::Harder));
+        assert_eq!((stats.streak, stats.easing), (0, 0));
+    }
+
+    #[test]
+    fn easing_is_clamped() {
+        let mut stats = Stats::default();
+        for _ in 0..STREAK_GAMES {
+            stats.record_streak(Victory);
+        }
+        assert_eq!(stats.easing, 0, "never harder than the selected opponent");
+        for _ in 0..STREAK_GAMES * (MAX_EASING as i32 + 2) {
+            stats.record_streak(Loss);
+        }
+        assert_eq!(stats.easing, MAX_EASING);
+    }
+}
@@ -0,0 +1,433 @@
+//! A live feed of the table for streaming overlays, only built with the
+//! `telemetry` feature.
+//!
+//! Each time a [`GameEvent`] is sent or the turn changes, [`publish_snapshot`]
+//! sends a [`Snapshot`] of what the audience can see: the scores, the seeds,
+//! the turn, the cards in each pile and how many cards each hand holds, never
+//! which ones. Observers get it as JSON text over a websocket at
+//! [`ADDRESS`], see `scripts/observer.html` for an overlay reading it.
+//!
+//! The game only queues snapshots, the server thread sends them. An observer
+//! too slow to keep up misses snapshots, it only ever gets the newest one. At
+//! most [`MAX_OBSERVERS`] observers connect at once. On wasm, there is no
+//! server and nothing is published.
+use bevy::{
+    ecs::system::SystemParam,
+    prelude::{Plugin as BevyPlugin, *},
+};
+use serde::Serialize;
+
+use crate::{
+    game_event::GameEvent,
+    game_flow::{CoopPlayer, Scores, SeedCount, TurnCount},
+    oppo_hand::OppoCard,
+    pile::{Pile, PileType},
+    player_hand::HandCard,
+    state::TurnState,
+    war::Card,
+};
+
+/// Version of the [`Snapshot`] format, bumped when it changes in ways
+/// overlays must know about.
+const PROTOCOL_VERSION: u32 = 1;
+/// Only observers on this machine can connect, overlays run alongside the
+/// streaming software.
+const ADDRESS: &str = "127.0.0.1:7878";
+/// Observers connected at once, more are turned away.
+const MAX_OBSERVERS: usize = 8;
+/// Snapshots waiting for the server thread, newer ones are dropped beyond.
+const QUEUE: usize = 16;
+
+#[derive(Debug, Serialize)]
+struct BySide<T> {
+    player: T,
+    oppo: T,
+}
+
+#[derive(Debug, Serialize)]
+struct CardView {
+    value: i32,
+    word: Option<String>,
+}
+impl CardView {
+    fn of(card: &Card) -> Self {
+        let word = card.word.map(|word| format!("{word:?}").to_lowercase());
+        CardView { value: card.value as i32, word }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Piles {
+    war: Vec<CardView>,
+    player: Vec<CardView>,
+    oppo: Vec<CardView>,
+}
+
+/// What an overlay shows of the table, sent as JSON.
+#[derive(Debug, Serialize)]
+pub struct Snapshot {
+    version: u32,
+    scores: BySide<i32>,
+    /// Seeds of the first and second [`CoopPlayer`].
+    seeds: [usize; 2],
+    turn: usize,
+    /// `"player"` or `"oppo"` while one of them picks a card.
+    active: Option<&'static str>,
+    piles: Piles,
+    /// How many cards each participant holds.
+    hands: BySide<usize>,
+}
+
+/// The public state of the table.
+#[derive(SystemParam)]
+struct Table<'w, 's> {
+    scores: Res<'w, Scores>,
+    seeds: Res<'w, SeedCount>,
+    turn: Res<'w, TurnCount>,
+    turn_state: Res<'w, State<TurnState>>,
+    piles: Query<'w, 's, &'static Pile>,
+    cards: Query<'w, 's, &'static Card>,
+    player_hand: Query<'w, 's, (), With<HandCard>>,
+    oppo_hand: Query<'w, 's, (), With<OppoCard>>,
+}
+impl<'w, 's> Table<'w, 's> {
+    fn pile(&self, which: PileType) -> Vec<CardView> {
+        let pile = self.piles.iter().find(|pile| pile.which == which);
+        let cards = pile.map_or(&[][..], Pile::cards);
+        cards
+            .iter()
+            .filter_map(|entity| self.cards.get(*entity).ok())
+            .map(CardView::of)
+            .collect()
+    }
+    fn snapshot(&self) -> Snapshot {
+        let active = match self.turn_state.current() {
+            TurnState::Player => Some("player"),
+            TurnState::Oppo => Some("oppo"),
+            _ => None,
+        };
+        Snapshot {
+            version: PROTOCOL_VERSION,
+            scores: BySide {
+                player: self.scores.player(),
+                oppo: self.scores.oppo(),
+            },
+            seeds: CoopPlayer::ALL.map(|who| self.seeds.count(who)),
+            turn: self.turn.0,
+            active,
+            piles: Piles {
+                war: self.pile(PileType::War),
+                player: self.pile(PileType::Player),
+                oppo: self.pile(PileType::Oppo),
+            },
+            hands: BySide {
+                player: self.player_hand.iter().count(),
+                oppo: self.oppo_hand.iter().count(),
+            },
+        }
+    }
+}
+
+/// The queue of snapshots to send, `None` without a server.
+pub struct SnapshotQueue(Option<std::sync::mpsc::SyncSender<String>>);
+impl SnapshotQueue {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start() -> Self {
+        match server::start(ADDRESS) {
+            Ok(queue) => {
+                info!("Observers can connect to ws://{ADDRESS}");
+                SnapshotQueue(Some(queue))
+            }
+            Err(err) => {
+                warn!("Couldn't start the observer server on {ADDRESS}: {err:#}");
+                SnapshotQueue(None)
+            }
+        }
+    }
+    #[cfg(target_arch = "wasm32")]
+    fn start() -> Self {
+        SnapshotQueue(None)
+    }
+}
+
+/// Queue a [`Snapshot`] when something happened on the table, in the post
+/// update stage so that it shows what the game did this frame.
+///
+/// The turn is compared with the one `seen` last frame rather than checked
+/// with `is_changed`: the state driver touches `State` every frame.
+fn publish_snapshot(
+    queue: Res<SnapshotQueue>,
+    mut seen: Local<Option<TurnState>>,
+    mut events: EventReader<GameEvent>,
+    table: Table,
+) {
+    let turn = *table.turn_state.current();
+    let new_turn = seen.replace(turn) != Some(turn);
+    let happened = events.iter().count() != 0 || new_turn;
+    let queue = match &queue.0 {
+        Some(queue) if happened => queue,
+        _ => return,
+    };
+    match serde_json::to_string(&table.snapshot()) {
+        // When the queue is full, the server thread is late anyway
+        Ok(json) => {
+            let _ = queue.try_send(json);
+        }
+        Err(err) => warn!("Couldn't serialize the table snapshot: {err}"),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod server {
+    //! The server thread, and a thread per observer writing to its socket.
+    use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::mpsc::{sync_channel, Receiver, SyncSender};
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::{thread, time::Duration};
+
+    use anyhow::{anyhow, Context};
+    use bevy::log::info;
+    use tungstenite::Message;
+
+    use super::{MAX_OBSERVERS, QUEUE};
+
+    /// How long an observer has to complete the websocket handshake, and to
+    /// take each snapshot, before it is dropped.
+    const TIMEOUT: Duration = Duration::from_secs(5);
+    /// How long an observer waits for a snapshot before it is pinged, to
+    /// notice it left while nothing happens on the table.
+    const PING_INTERVAL: Duration = Duration::from_secs(10);
+
+    /// The newest snapshot an observer hasn't got yet, older ones are
+    /// replaced.
+    #[derive(Default)]
+    pub(super) struct Mailbox {
+        snapshot: Mutex<Option<Arc<str>>>,
+        posted: Condvar,
+        closed: AtomicBool,
+    }
+    impl Mailbox {
+        pub(super) fn post(&self, snapshot: Arc<str>) {
+            *self.snapshot.lock().unwrap() = Some(snapshot);
+            self.posted.notify_one();
+        }
+        /// Wait for the next snapshot, `None` if none was posted within
+        /// `timeout`.
+        pub(super) fn take(&self, timeout: Duration) -> Option<Arc<str>> {
+            let snapshot = self.snapshot.lock().unwrap();
+            let waiting = |snapshot: &mut Option<Arc<str>>| snapshot.is_none();
+            let (mut snapshot, _) = self
+                .posted
+                .wait_timeout_while(snapshot, timeout, waiting)
+                .unwrap();
+            snapshot.take()
+        }
+        pub(super) fn close(&self) {
+            self.closed.store(true, Ordering::Relaxed);
+        }
+        fn is_closed(&self) -> bool {
+            self.closed.load(Ordering::Relaxed)
+        }
+    }
+
+    /// The [`Mailbox`] of each connected observer.
+    #[derive(Default)]
+    pub(super) struct Observers {
+        mailboxes: Vec<Arc<Mailbox>>,
+        latest: Option<Arc<str>>,
+    }
+    impl Observers {
+        /// The mailbox of a new observer, with the latest snapshot in it.
+        /// `None` if there are already [`MAX_OBSERVERS`], those who left
+        /// free their spot once their thread notices.
+        pub(super) fn join(&mut self) -> Option<Arc<Mailbox>> {
+            self.mailboxes.retain(|mailbox| !mailbox.is_closed());
+            if self.mailboxes.len() >= MAX_OBSERVERS {
+                return None;
+            }
+            let mailbox = Arc::new(Mailbox::default());
+            if let Some(latest) = &self.latest {
+                mailbox.post(latest.clone());
+            }
+            self.mailboxes.push(mailbox.clone());
+            Some(mailbox)
+        }
+        pub(super) fn broadcast(&mut self, snapshot: Arc<str>) {
+            self.mailboxes.retain(|mailbox| !mailbox.is_closed());
+            for mailbox in &self.mailboxes {
+                mailbox.post(snapshot.clone());
+            }
+            self.latest = Some(snapshot);
+        }
+    }
+
+    /// Listen for observers on `address`, returning the queue of snapshots
+    /// to send them.
+    pub(super) fn start(address: impl ToSocketAddrs) -> anyhow::Result<SyncSender<String>> {
+        let listener = TcpListener::bind(address).context("Couldn't listen")?;
+        let (queue, snapshots) = sync_channel(QUEUE);
+        let observers = Arc::new(Mutex::new(Observers::default()));
+        let broadcast_to = observers.clone();
+        thread::Builder::new()
+            .name("observer broadcast".to_owned())
+            .spawn(move || broadcast(snapshots, &broadcast_to))?;
+        thread::Builder::new()
+            .name("observer accept".to_owned())
+            .spawn(move || accept(listener, &observers))?;
+        Ok(queue)
+    }
+
+    fn broadcast(snapshots: Receiver<String>, observers: &Mutex<Observers>) {
+        for snapshot in snapshots {
+            observers.lock().unwrap().broadcast(snapshot.into());
+        }
+    }
+
+    fn accept(listener: TcpListener, observers: &Mutex<Observers>) {
+        for stream in listener.incoming().flatten() {
+            // Dropping the stream closes it, turning away the observer
+            let mailbox = match observers.lock().unwrap().join() {
+                Some(mailbox) => mailbox,
+                None => continue,
+            };
+            let spawned = thread::Builder::new()
+                .name("observer".to_owned())
+                .spawn(move || serve(stream, &mailbox));
+            if let Err(err) = spawned {
+                info!("Couldn't serve an observer: {err}");
+            }
+        }
+    }
+
+    fn serve(stream: TcpStream, mailbox: &Mailbox) {
+        if let Err(err) = send_snapshots(stream, mailbox) {
+            info!("An observer left: {err:#}");
+        }
+        mailbox.close();
+    }
+
+    /// Send the snapshots posted to `mailbox` to the observer until it
+    /// leaves or falls [`TIMEOUT`] behind.
+    ///
+    /// Writing to a closed socket fails, the observer is pinged every
+    /// [`PING_INTERVAL`] without snapshots so that it is dropped even if
+    /// nothing happens on the table.
+    fn send_snapshots(stream: TcpStream, mailbox: &Mailbox) -> anyhow::Result<()> {
+        stream.set_read_timeout(Some(TIMEOUT))?;
+        stream.set_write_timeout(Some(TIMEOUT))?;
+        let handshake = tungstenite::accept(stream);
+        let mut socket = handshake.map_err(|err| anyhow!("Handshake failed: {err}"))?;
+        loop {
+            let message = match mailbox.take(PING_INTERVAL) {
+                Some(snapshot) => Message::Text(snapshot.to_string()),
+                None => Message::Ping(Vec::new()),
+            };
+            socket.write_message(message)?;
+        }
+    }
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SnapshotQueue::start())
+            .add_system_to_stage(CoreStage::PostUpdate, publish_snapshot);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, time::Duration};
+
+    use super::*;
+
+    #[test]
+    fn snapshots_only_count_hand_cards() {
+        let card = |card: &str| CardView::of(&card.parse().unwrap());
+        let snapshot = Snapshot {
+            version: PROTOCOL_VERSION,
+            scores: BySide { player: 12, oppo: 7 },
+            seeds: [2, 0],
+            turn: 4,
+            active: Some("oppo"),
+            piles: Piles {
+                war: vec![card("5d")],
+                player: vec![card("9_")],
+                oppo: vec![card("0z")],
+            },
+            hands: BySide { player: 3, oppo: 2 },
+        };
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let expected = concat!(
+            r#"{"version":1,"scores":{"player":12,"oppo":7},"seeds":[2,0],"#,
+            r#""turn":4,"active":"oppo","piles":{"war":[{"value":5,"word":"qube"}],"#,
+            r#""player":[{"value":9,"word":null}],"#,
+            r#""oppo":[{"value":0,"word":"geh"}]},"hands":{"player":3,"oppo":2}}"#,
+        );
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn snapshots_follow_the_table() {
+        let mut world = World::new();
+        let (sender, snapshots) = std::sync::mpsc::sync_channel(QUEUE);
+        world.insert_resource(SnapshotQueue(Some(sender)));
+        world.insert_resource(State::new(TurnState::Draw));
+        world.init_resource::<Scores>();
+        world.init_resource::<SeedCount>();
+        world.init_resource::<TurnCount>();
+        world.init_resource::<Events<GameEvent>>();
+        // Like `App::add_state`, the driver touches the state every frame
+        let mut stage = SystemStage::single_threaded()
+            .with_system_set(State::<TurnState>::get_driver())
+            .with_system(publish_snapshot);
+        let mut frame = |world: &mut World| {
+            stage.run(world);
+            snapshots.try_iter().count()
+        };
+        assert_eq!(frame(&mut world), 1, "the first turn");
+        assert_eq!(frame(&mut world), 0, "nothing happened");
+        assert_eq!(frame(&mut world), 0);
+        let mut events = world.resource_mut::<Events<GameEvent>>();
+        events.send(GameEvent::SeedGained(CoopPlayer::First));
+        assert_eq!(frame(&mut world), 1);
+        assert_eq!(frame(&mut world), 0, "events are only read once");
+        let mut turn = world.resource_mut::<State<TurnState>>();
+        turn.set(TurnState::Player).unwrap();
+        assert_eq!(frame(&mut world), 1);
+        assert_eq!(frame(&mut world), 0);
+    }
+
+    #[test]
+    fn slow_observers_get_the_newest_snapshot() {
+        let mut observers = server::Observers::default();
+        let take = |mailbox: &server::Mailbox| mailbox.take(Duration::from_millis(10));
+        let early = observers.join().unwrap();
+        assert_eq!(take(&early), None, "nothing to send yet");
+        observers.broadcast(Arc::from("first"));
+        observers.broadcast(Arc::from("second"));
+        assert_eq!(
+            take(&early).as_deref(),
+            Some("second"),
+            "the first one is dropped"
+        );
+        assert_eq!(take(&early), None, "already sent");
+        let late = observers.join().unwrap();
+        assert_eq!(
+            take(&late).as_deref(),
+            Some("second"),
+            "joining gets the latest"
+        );
+    }
+
+    #[test]
+    fn observers_are_limited() {
+        let mut observers = server::Observers::default();
+        let joined: Vec<_> = (0..MAX_OBSERVERS).map(|_| observers.join()).collect();
+        assert!(joined.iter().all(Option::is_some));
+        assert!(observers.join().is_none(), "turned away");
+        joined[0].as_ref().unwrap().close();
+        assert!(observers.join().is_some(), "a left observer frees a spot");
+    }
+}
@@ -0,0 +1,557 @@
+//! Scripted pointer input, to test the hand and the sleeve as a player would
+//! use them, only built with the `testing` feature.
+//!
+//! A [`ScriptedInput`] is a list of timestamped cursor moves and mouse button
+//! presses. While one is in the world, [`play_script`] replaces the OS cursor
+//! and mouse buttons with it: it sends the scripted [`CursorMoved`] events
+//! the [`CursorPosition`] and the hand raycasts follow, and writes the
+//! scripted buttons in the real [`Input<MouseButton>`]. It is removed once
+//! played, the OS input then takes over again.
+//!
+//! [`RECORD_KEY`] starts and stops recording a live session to
+//! [`SCRIPT_FILE`], in the same format, [`REPLAY_KEY`] plays it back.
+//!
+//! The tests run scripts against the hand and the cheat plugins, on a table
+//! set up like the game scene, but without a renderer, see `Table`. Run them
+//! with `cargo test --features testing`.
+//!
+//! [`CursorPosition`]: crate::player_hand::CursorPosition
+use std::collections::VecDeque;
+
+use bevy::{
+    input::{mouse::MouseButtonInput, ButtonState, InputSystem},
+    prelude::{Plugin as BevyPlugin, *},
+    window::WindowId,
+};
+use enum_map::{Enum, EnumMap};
+use serde::{Deserialize, Serialize};
+
+use crate::persist;
+
+const RECORD_KEY: KeyCode = KeyCode::F5;
+const REPLAY_KEY: KeyCode = KeyCode::F6;
+const SCRIPT_FILE: &str = "input_script.ron";
+
+/// The mouse buttons a script can press.
+#[derive(Clone, Copy, Debug, Deserialize, Enum, PartialEq, Serialize)]
+pub enum PointerButton {
+    Left,
+    Right,
+    Middle,
+}
+impl PointerButton {
+    fn of(button: MouseButton) -> Option<Self> {
+        match button {
+            MouseButton::Left => Some(Self::Left),
+            MouseButton::Right => Some(Self::Right),
+            MouseButton::Middle => Some(Self::Middle),
+            MouseButton::Other(_) => None,
+        }
+    }
+}
+impl From<PointerButton> for MouseButton {
+    fn from(button: PointerButton) -> Self {
+        match button {
+            PointerButton::Left => MouseButton::Left,
+            PointerButton::Right => MouseButton::Right,
+            PointerButton::Middle => MouseButton::Middle,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub enum PointerInput {
+    /// The cursor moved to `x`, `y` in the primary window, in logical pixels
+    /// from its bottom left corner.
+    Move {
+        x: f32,
+        y: f32,
+    },
+    Press(PointerButton),
+    Release(PointerButton),
+}
+
+/// A [`PointerInput`] happening `at` seconds after the script started.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ScriptStep {
+    pub at: f64,
+    pub input: PointerInput,
+}
+
+/// Pointer input to play instead of the OS one, see the [module
+/// documentation](self).
+pub struct ScriptedInput {
+    steps: VecDeque<ScriptStep>,
+    /// When the script started playing, in seconds since startup.
+    started_at: Option<f64>,
+    held: EnumMap<PointerButton, bool>,
+}
+impl ScriptedInput {
+    /// A script of `steps`, sorted by time.
+    pub fn new(steps: impl IntoIterator<Item = ScriptStep>) -> Self {
+        let mut steps: Vec<_> = steps.into_iter().collect();
+        steps.sort_by(|a, b| a.at.total_cmp(&b.at));
+        Self {
+            steps: steps.into(),
+            started_at: None,
+            held: EnumMap::default(),
+        }
+    }
+    /// Parse a script saved by the recorder, a list of [`ScriptStep`]s.
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let steps: Vec<ScriptStep> = ron::de::from_str(text)?;
+        Ok(Self::new(steps))
+    }
+    pub fn is_done(&self) -> bool {
+        self.steps.is_empty()
+    }
+    /// The inputs due at `now`, in seconds since startup. The script starts
+    /// the first time it is asked.
+    fn due(&mut self, now: f64) -> Vec<PointerInput> {
+        let elapsed = now - *self.started_at.get_or_insert(now);
+        let count = self.steps.iter().take_while(|s| s.at <= elapsed).count();
+        self.steps.drain(..count).map(|step| step.input).collect()
+    }
+}
+
+/// Replace the OS pointer input with the [`ScriptedInput`], if any.
+///
+/// Runs right after the input systems, so that the game only ever sees the
+/// scripted cursor and buttons.
+fn play_script(
+    mut cmds: Commands,
+    script: Option<ResMut<ScriptedInput>>,
+    mut cursor: ResMut<Events<CursorMoved>>,
+    mut mouse: ResMut<Input<MouseButton>>,
+    time: Res<Time>,
+) {
+    let mut script = match script {
+        Some(script) => script,
+        None => return,
+    };
+    cursor.clear();
+    for (button, held) in script.held.iter() {
+        let button = button.into();
+        mouse.reset(button);
+        if *held {
+            mouse.press(button);
+            mouse.clear_just_pressed(button);
+        }
+    }
+    let id = WindowId::primary();
+    for input in script.due(time.seconds_since_startup()) {
+        match input {
+            PointerInput::Move { x, y } => {
+                cursor.send(CursorMoved { id, position: Vec2::new(x, y) })
+            }
+            PointerInput::Press(button) => {
+                script.held[button] = true;
+                mouse.press(button.into());
+            }
+            PointerInput::Release(button) => {
+                script.held[button] = false;
+                mouse.release(button.into());
+            }
+        }
+    }
+    if script.is_done() {
+        cmds.remove_resource::<ScriptedInput>();
+    }
+}
+
+/// A live session being recorded, see [`record_input`].
+struct Recording {
+    started_at: f64,
+    steps: Vec<ScriptStep>,
+}
+impl Recording {
+    fn push(&mut self, now: f64, input: PointerInput) {
+        let at = now - self.started_at;
+        self.steps.push(ScriptStep { at, input });
+    }
+    fn to_ron(&self) -> anyhow::Result<String> {
+        let config = ron::ser::PrettyConfig::new();
+        Ok(ron::ser::to_string_pretty(&self.steps, config)?)
+    }
+}
+
+/// Record the OS pointer input between two presses of [`RECORD_KEY`], and
+/// write it to [`SCRIPT_FILE`].
+fn record_input(
+    mut recording: Local<Option<Recording>>,
+    mut keys: ResMut<Input<KeyCode>>,
+    mut cursor: EventReader<CursorMoved>,
+    mut buttons: EventReader<MouseButtonInput>,
+    time: Res<Time>,
+) {
+    let now = time.seconds_since_startup();
+    if keys.just_pressed(RECORD_KEY) {
+        keys.reset(RECORD_KEY);
+        match recording.take() {
+            Some(done) => {
+                let saved = done
+                    .to_ron()
+                    .and_then(|ron| persist::write(SCRIPT_FILE, &ron));
+                match saved {
+                    Ok(()) => debug_print!(sec: 5.0, "input script saved to {SCRIPT_FILE}"),
+                    Err(err) => debug_print!(sec: 5.0, col: Color::RED, "{err:#}"),
+                }
+            }
+            None => {
+                debug_print!(sec: 3.0, "recording input, {RECORD_KEY:?} to stop");
+                *recording = Some(Recording { started_at: now, steps: Vec::new() });
+            }
+        }
+    }
+    let recording = match recording.as_mut() {
+        Some(recording) => recording,
+        None => return,
+    };
+    for moved in cursor.iter() {
+        let Vec2 { x, y } = moved.position;
+        recording.push(now, PointerInput::Move { x, y });
+    }
+    for input in buttons.iter() {
+        let button = match PointerButton::of(input.button) {
+            Some(button) => button,
+            None => continue,
+        };
+        let input = match input.state {
+            ButtonState::Pressed => PointerInput::Press(button),
+            ButtonState::Released => PointerInput::Release(button),
+        };
+        recording.push(now, input);
+    }
+}
+
+/// Play [`SCRIPT_FILE`] back when [`REPLAY_KEY`] is pressed.
+fn replay_input(mut cmds: Commands, mut keys: ResMut<Input<KeyCode>>) {
+    if !keys.just_pressed(REPLAY_KEY) {
+        return;
+    }
+    keys.reset(REPLAY_KEY);
+    let script = persist::read(SCRIPT_FILE).and_then(|text| ScriptedInput::parse(&text));
+    match script {
+        Ok(script) => {
+            debug_print!(sec: 3.0, "replaying {SCRIPT_FILE}");
+            cmds.insert_resource(script);
+        }
+        Err(err) => debug_print!(sec: 5.0, col: Color::RED, "{err:#}"),
+    }
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(CoreStage::PreUpdate, play_script.after(InputSystem))
+            .add_system(record_input)
+            .add_system(replay_input);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{
+        asset::AssetPlugin,
+        core::CorePlugin,
+        hierarchy::HierarchyPlugin,
+        input::InputPlugin,
+        render::{camera::CameraPlugin, view::VisibilityPlugin},
+        transform::TransformPlugin,
+        window::{Window, WindowDescriptor, WindowPlugin, WindowSettings},
+    };
+    use raw_window_handle::{RawWindowHandle, WebHandle};
+
+    use super::*;
+    use crate::{
+        animate::{GameFeel, GameSpeed},
+        audio::AudioRequest,
+        autosave::RestoreHand,
+        card::{CardAssets, CardStatus, PlayerCardSpawner},
+        cheat::{self, BirdEye, CheatEvent, Distraction, SleeveCard},
+        deck::PlayerDeck,
+        game_event::GameEvent,
+        game_flow::{BattleCount, CoopTurn, Inventory, PlayCard, SeedCount},
+        game_ui::EffectEvent,
+        mood::Mood,
+        opponent::OpponentProfile,
+        player_hand::{self, CardCollisionAssets, DropZoneAssets, GrabbedCard},
+        rules::GameRules,
+        scene,
+        settings::{GameplaySettings, KeyBindings},
+        state::{GameState, TurnState},
+        stats::Stats,
+        war::Card,
+    };
+
+    const FRAME: f64 = 1.0 / 60.0;
+    const WINDOW: (u32, u32) = (1280, 720);
+
+    #[test]
+    fn scripts_roundtrip_through_recordings() {
+        let mut recording = Recording { started_at: 10.0, steps: Vec::new() };
+        recording.push(10.0, PointerInput::Move { x: 640.0, y: 360.5 });
+        recording.push(10.25, PointerInput::Press(PointerButton::Left));
+        recording.push(10.5, PointerInput::Release(PointerButton::Left));
+        let mut script = ScriptedInput::parse(&recording.to_ron().unwrap()).unwrap();
+        assert_eq!(Vec::from(script.steps.clone()), recording.steps);
+
+        assert_eq!(script.due(3.0), [PointerInput::Move { x: 640.0, y: 360.5 }]);
+        assert!(
+            script.due(3.2).is_empty(),
+            "times are relative to the start"
+        );
+        assert_eq!(script.due(3.3), [PointerInput::Press(PointerButton::Left)]);
+        assert!(!script.is_done());
+        assert_eq!(
+            script.due(9.0),
+            [PointerInput::Release(PointerButton::Left)]
+        );
+        assert!(script.is_done());
+        assert!(ScriptedInput::parse("[(at: 0.0, input: Hover)]").is_err());
+    }
+
+    /// What the tested plugins sent, events don't outlive two frames.
+    #[derive(Default)]
+    struct Sent {
+        game_events: Vec<GameEvent>,
+        plays: usize,
+    }
+    fn keep_sent(
+        mut sent: ResMut<Sent>,
+        mut game_events: EventReader<GameEvent>,
+        mut plays: EventReader<PlayCard>,
+    ) {
+        sent.game_events.extend(game_events.iter().cloned());
+        sent.plays += plays.iter().count();
+    }
+
+    /// The hand at the origin, the sleeve stash to its left and the camera
+    /// facing them, hooked like the game scene nodes of the same name.
+    fn setup_table(
+        mut cmds: Commands,
+        card_meshes: Res<CardCollisionAssets>,
+        drop_zones: Res<DropZoneAssets>,
+    ) {
+        let mut node = |name: &str, bundle| {
+            let mut entity = cmds.spawn_bundle(bundle);
+            scene::hook(&card_meshes, &drop_zones, name, &mut entity);
+        };
+        let spatial = |x, y, z| SpatialBundle::from_transform(Transform::from_xyz(x, y, z));
+        node("PlayerHand", spatial(0.0, 0.0, 0.0));
+        node("PlayerSleeveStash", spatial(-2.5, -1.2, 0.0));
+        let target = Vec3::new(-0.8, 0.4, 0.0);
+        let transform = Transform::from_translation(target + Vec3::Z * 9.0);
+        let mut camera = cmds.spawn_bundle(Camera3dBundle { transform, ..default() });
+        scene::hook(
+            &card_meshes,
+            &drop_zones,
+            "PlayerPerspective_Orientation",
+            &mut camera,
+        );
+        cmds.spawn_bundle(spatial(0.0, -2.0, 0.5))
+            .insert(PlayerCardSpawner);
+    }
+
+    /// The hand and cheat plugins, without a renderer, in a window nothing
+    /// draws to. Time advances by [`FRAME`] each frame.
+    struct Table {
+        app: App,
+        frame: u32,
+    }
+    impl Table {
+        /// A table with a player deck of `deck`, drawn from its end.
+        fn new(deck: &str) -> Self {
+            let mut app = App::new();
+            let window_settings = WindowSettings {
+                add_primary_window: false,
+                exit_on_all_closed: false,
+                ..default()
+            };
+            app.insert_resource(window_settings)
+                .add_plugin(CorePlugin)
+                .add_plugin(TransformPlugin)
+                .add_plugin(HierarchyPlugin)
+                .add_plugin(InputPlugin)
+                .add_plugin(WindowPlugin)
+                .add_plugin(AssetPlugin)
+                .add_asset::<Mesh>()
+                .add_asset::<Image>()
+                .add_asset::<StandardMaterial>()
+                .add_plugin(CameraPlugin)
+                .add_plugin(VisibilityPlugin)
+                .insert_resource(Time::default())
+                .add_state(GameState::Playing)
+                .add_state(TurnState::Draw)
+                .init_resource::<KeyBindings>()
+                .init_resource::<GameplaySettings>()
+                .init_resource::<GameSpeed>()
+                .init_resource::<GameFeel>()
+                .init_resource::<GameRules>()
+                .init_resource::<Stats>()
+                .init_resource::<Mood>()
+                .init_resource::<CoopTurn>()
+                .init_resource::<SeedCount>()
+                .init_resource::<Inventory>()
+                .init_resource::<BattleCount>()
+                .init_resource::<OpponentProfile>()
+                .init_resource::<CardAssets>()
+                .init_resource::<Sent>()
+                .add_event::<AudioRequest>()
+                .add_event::<EffectEvent>()
+                .add_event::<GameEvent>()
+                .add_event::<PlayCard>()
+                .add_event::<RestoreHand>()
+                .add_plugin(player_hand::Plugin(GameState::Playing))
+                .add_plugin(cheat::Plugin(GameState::Playing))
+                .add_plugin(Plugin)
+                .add_startup_system(setup_table)
+                .add_system_to_stage(CoreStage::Last, keep_sent);
+            #[cfg(feature = "debug")]
+            app.add_event::<crate::console::GiveCard>();
+
+            let (width, height) = WINDOW;
+            let handle = RawWindowHandle::Web(WebHandle::empty());
+            let descriptor = WindowDescriptor::default();
+            let id = WindowId::primary();
+            let window = Window::new(id, &descriptor, width, height, 1.0, None, handle);
+            app.world.resource_mut::<Windows>().add(window);
+            app.world
+                .spawn()
+                .insert(PlayerDeck::new(deck.parse().unwrap()));
+
+            let mut table = Table { app, frame: 0 };
+            table.wait(2.0);
+            let mut turn = table.app.world.resource_mut::<State<TurnState>>();
+            turn.set(TurnState::Player).unwrap();
+            table.wait(0.5);
+            table
+        }
+        fn step(&mut self) {
+            self.frame += 1;
+            let mut time = self.app.world.resource_mut::<Time>();
+            let startup = time.startup();
+            time.update_with_instant(startup + Duration::from_secs_f64(self.frame as f64 * FRAME));
+            self.app.update();
+        }
+        fn wait(&mut self, seconds: f64) {
+            for _ in 0..(seconds / FRAME).round() as u32 {
+                self.step();
+            }
+        }
+        /// Play `steps` to the end, then let the hand settle.
+        fn play(&mut self, steps: Vec<ScriptStep>) {
+            self.app.insert_resource(ScriptedInput::new(steps));
+            while self.app.world.contains_resource::<ScriptedInput>() {
+                self.step();
+            }
+            self.wait(1.0);
+        }
+        fn card(&mut self, card: &str) -> Entity {
+            let card: Card = card.parse().unwrap();
+            let mut cards = self.app.world.query::<(Entity, &Card)>();
+            let found = cards.iter(&self.app.world).find(|(_, c)| **c == card);
+            found.expect("the card is on the table").0
+        }
+        fn transform(&self, entity: Entity) -> Transform {
+            *self.app.world.get::<Transform>(entity).unwrap()
+        }
+        fn status(&self, card: Entity) -> CardStatus {
+            *self.app.world.get::<CardStatus>(card).unwrap()
+        }
+        /// Where the cursor goes to point at `position` on the table.
+        fn screen(&mut self, position: Vec3) -> Vec2 {
+            let mut cameras = self.app.world.query::<(&Camera, &GlobalTransform)>();
+            let (camera, transform) = cameras.single(&self.app.world);
+            camera.world_to_viewport(transform, position).unwrap()
+        }
+    }
+
+    fn at(at: f64, input: PointerInput) -> ScriptStep {
+        ScriptStep { at, input }
+    }
+    fn move_to(time: f64, Vec2 { x, y }: Vec2) -> ScriptStep {
+        at(time, PointerInput::Move { x, y })
+    }
+    /// Hover `from`, grab what is there and drag it to `to` in small moves,
+    /// so that the card stays under the cursor, then drop it.
+    fn drag(from: Vec2, to: Vec2) -> Vec<ScriptStep> {
+        const MOVES: usize = 30;
+        let mut steps = vec![
+            move_to(0.0, from),
+            at(0.5, PointerInput::Press(PointerButton::Left)),
+        ];
+        let moves = (1..=MOVES).map(|i| {
+            let progress = i as f32 / MOVES as f32;
+            move_to(0.6 + i as f64 * 2.0 * FRAME, from.lerp(to, progress))
+        });
+        steps.extend(moves);
+        let drop_at = 0.8 + MOVES as f64 * 2.0 * FRAME;
+        steps.push(at(drop_at, PointerInput::Release(PointerButton::Left)));
+        steps
+    }
+    /// A point of `card` that stays under the cursor once the card is
+    /// hovered, and isn't covered by the first card of the hand.
+    fn grip(table: &Table, card: Entity) -> Vec3 {
+        table.transform(card).transform_point(Vec3::Y)
+    }
+
+    #[test]
+    fn cards_dropped_on_the_disengage_area_return_to_hand() {
+        let mut table = Table::new("9_ 8_ 1_ 2_ 3_");
+        let second = table.card("2_");
+        let resting = table.transform(second);
+        let from = table.screen(grip(&table, second));
+
+        table.play(vec![move_to(0.0, from)]);
+        assert_eq!(table.status(second), CardStatus::Hovered);
+        let hovered = table.transform(second);
+        assert!(hovered.translation.distance(resting.translation) > 0.5);
+
+        let to = table.screen(Vec3::new(0.3, 0.3, 0.0));
+        table.play(drag(from, to));
+        let world = &table.app.world;
+        assert!(world.get::<GrabbedCard>(second).is_none(), "dropped");
+        assert!(world.get::<SleeveCard>(second).is_none());
+        assert_eq!(world.resource::<Sent>().plays, 0, "not played");
+        let back = table.transform(second).translation;
+        let in_slot = |slot: Transform| slot.translation.distance(back) < 0.05;
+        assert!(
+            in_slot(resting) || in_slot(hovered),
+            "{back} isn't in the hand"
+        );
+    }
+
+    #[test]
+    fn cards_dropped_on_the_sleeve_of_a_distracted_bird_are_sleeved() {
+        let mut table = Table::new("9_ 8_ 1_ 2_ 3_");
+        let first = table.card("1_");
+        let from = table.screen(grip(&table, first));
+        table.play(vec![move_to(0.0, from)]);
+        assert_eq!(table.status(first), CardStatus::Hovered);
+
+        let distract = CheatEvent::ConfuseBird(Distraction::Seed);
+        table
+            .app
+            .world
+            .resource_mut::<Events<CheatEvent>>()
+            .send(distract);
+        table.wait(0.1);
+        assert!(!table.app.world.resource::<BirdEye>().is_watching);
+
+        let sleeve = table.screen(Vec3::new(-2.5, 0.5, 0.2));
+        table.play(drag(from, sleeve));
+        let world = &table.app.world;
+        assert!(world.get::<SleeveCard>(first).is_some(), "sleeved");
+        let sent = &world.resource::<Sent>().game_events;
+        let one: Card = "1_".parse().unwrap();
+        assert!(sent.contains(&GameEvent::CardSleeved(one)));
+        assert!(!sent.iter().any(|e| matches!(e, GameEvent::CheatCaught(_))));
+        let eight: Card = "8_".parse().unwrap();
+        let drawn = GameEvent::CardDrawn { who: crate::Participant::Player, card: eight };
+        assert!(sent.contains(&drawn), "a card replaces the sleeved one");
+        assert!(world.resource::<BirdEye>().is_watching, "one card per seed");
+    }
+}
@@ -0,0 +1,163 @@
+//! Cosmetic themes, changing the card backs and the table lighting.
+//!
+//! The table lighting is the [`Moods`] table, built from the theme colors.
+//!
+//! The selected [`Theme`] is a resource, persisted with the other settings.
+//! Theme card backs are loaded the first time the theme is selected, from
+//! `assets/themes/<name>/`.
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use enum_map::{Enum, EnumMap};
+
+use crate::{
+    card::{CardAssets, CardBack, CardGlow},
+    mood::Moods,
+};
+
+#[derive(Enum, Clone, Copy, Debug, PartialEq)]
+pub enum Theme {
+    Classic,
+    Graveyard,
+    Crimson,
+    Moonlit,
+}
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::Classic
+    }
+}
+impl Theme {
+    pub const ALL: [Theme; 4] = [
+        Theme::Classic,
+        Theme::Graveyard,
+        Theme::Crimson,
+        Theme::Moonlit,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Classic => "Classic",
+            Theme::Graveyard => "Graveyard",
+            Theme::Crimson => "Crimson",
+            Theme::Moonlit => "Moonlit",
+        }
+    }
+    /// Key of the theme name shown to the player in [`Strings`], `name` being
+    /// used to persist the setting.
+    ///
+    /// [`Strings`]: crate::lang::Strings
+    pub fn label_key(self) -> &'static str {
+        match self {
+            Theme::Classic => "theme.classic",
+            Theme::Graveyard => "theme.graveyard",
+            Theme::Crimson => "theme.crimson",
+            Theme::Moonlit => "theme.moonlit",
+        }
+    }
+    /// The theme after this one, for cycling through them in the menu.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|t| *t == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+    /// Directory in `assets/themes` with the theme textures, `None` for the
+    /// default card back.
+    fn directory(self) -> Option<&'static str> {
+        match self {
+            Theme::Classic => None,
+            Theme::Graveyard => Some("graveyard"),
+            Theme::Crimson => Some("crimson"),
+            Theme::Moonlit => Some("moonlit"),
+        }
+    }
+    fn glow_tint(self) -> Color {
+        match self {
+            Theme::Classic => Color::WHITE,
+            Theme::Graveyard => Color::rgb(0.7, 1.0, 0.7),
+            Theme::Crimson => Color::rgb(1.0, 0.6, 0.5),
+            Theme::Moonlit => Color::rgb(0.7, 0.8, 1.0),
+        }
+    }
+    fn ambient_color(self) -> Color {
+        match self {
+            Theme::Classic => Color::WHITE,
+            Theme::Graveyard => Color::rgb(0.8, 0.95, 0.8),
+            Theme::Crimson => Color::rgb(1.0, 0.75, 0.7),
+            Theme::Moonlit => Color::rgb(0.65, 0.75, 1.0),
+        }
+    }
+    fn clear_color(self) -> Color {
+        match self {
+            Theme::Classic => Color::rgb(0.293, 0.3828, 0.4023),
+            Theme::Graveyard => Color::rgb(0.16, 0.2, 0.17),
+            Theme::Crimson => Color::rgb(0.25, 0.06, 0.07),
+            Theme::Moonlit => Color::rgb(0.05, 0.07, 0.18),
+        }
+    }
+    /// The lighting of each [`Mood`], themes may override the default ones
+    /// here.
+    ///
+    /// [`Mood`]: crate::mood::Mood
+    fn moods(self) -> Moods {
+        Moods::new(self.ambient_color(), self.clear_color())
+    }
+}
+
+/// Card back materials of the themes selected so far.
+#[derive(Default)]
+struct ThemeBacks(EnumMap<Theme, Option<Handle<StandardMaterial>>>);
+
+fn apply_theme(
+    theme: Res<Theme>,
+    assets: Res<AssetServer>,
+    mut backs: ResMut<ThemeBacks>,
+    mut card_assets: ResMut<CardAssets>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut moods: ResMut<Moods>,
+    mut card_backs: Query<&mut Handle<StandardMaterial>, With<CardBack>>,
+    glows: Query<&Handle<StandardMaterial>, (With<CardGlow>, Without<CardBack>)>,
+) {
+    if !theme.is_changed() {
+        return;
+    }
+    let theme = *theme;
+    let back = backs.0[theme].get_or_insert_with(|| match theme.directory() {
+        Some(directory) => materials.add(StandardMaterial {
+            base_color_texture: Some(assets.load(&format!("themes/{directory}/BackFace.png"))),
+            ..default()
+        }),
+        None => card_assets.default_backface.clone(),
+    });
+    card_assets.backface = back.clone();
+    for mut material in card_backs.iter_mut() {
+        *material = back.clone();
+    }
+    let tint = theme.glow_tint();
+    let glow_materials = glows.iter().chain([&card_assets.glow]);
+    for handle in glow_materials {
+        if let Some(glow) = materials.get_mut(handle) {
+            glow.base_color = tint;
+        }
+    }
+    *moods = theme.moods();
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ThemeBacks>().add_system(apply_theme);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn next_cycles_through_all_themes() {
+        let mut theme = Theme::default();
+        for expected in Theme::ALL.iter().skip(1) {
+            theme = theme.next();
+            assert_eq!(theme, *expected);
+        }
+        assert_eq!(theme.next(), Theme::default());
+    }
+}
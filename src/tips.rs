@@ -0,0 +1,157 @@
+//! Tips and lore shown on the loading screen.
+//!
+//! A [`LoadingTip`] text cycles through [`TIPS`], favoring tips about what
+//! the player never did according to their [`Stats`].
+use bevy::prelude::{Plugin as BevyPlugin, *};
+
+use crate::{lang::Strings, state::GameState, stats::Stats};
+
+/// Seconds each tip is shown, fades included.
+const TIP_DURATION: f32 = 3.0;
+/// Seconds it takes for a tip to fade in or out.
+const FADE_DURATION: f32 = 0.5;
+/// How much more likely tips about unused features are to show up.
+const UNUSED_FEATURE_WEIGHT: u32 = 3;
+
+/// A feature of the game the player may not know about.
+#[derive(Clone, Copy)]
+enum Feature {
+    Sleeve,
+    Seed,
+}
+
+struct Tip {
+    /// Key of the tip text in the [`Strings`] table.
+    key: &'static str,
+    /// The tip is about this feature, shown more often if never used.
+    feature: Option<Feature>,
+}
+
+#[rustfmt::skip]
+const TIPS: [Tip; 9] = [
+    Tip { key: "tip.sleeve_hide", feature: Some(Feature::Sleeve) },
+    Tip { key: "tip.sleeve_return", feature: Some(Feature::Sleeve) },
+    Tip { key: "tip.seed_distract", feature: Some(Feature::Seed) },
+    Tip { key: "tip.distracted_bird", feature: Some(Feature::Seed) },
+    Tip { key: "tip.trinket", feature: Some(Feature::Seed) },
+    Tip { key: "tip.score_pile", feature: None },
+    Tip { key: "tip.hover_word", feature: None },
+    Tip { key: "tip.bird_count", feature: None },
+    Tip { key: "tip.graveyard", feature: None },
+];
+
+/// The loading screen text showing tips.
+#[derive(Component, Default)]
+pub struct LoadingTip {
+    /// Index in [`TIPS`] of the tip shown.
+    shown: Option<usize>,
+    /// Seconds since the tip was first shown.
+    elapsed: f32,
+}
+
+fn tip_weight(tip: &Tip, stats: &Stats) -> u32 {
+    let unused = match tip.feature {
+        Some(Feature::Sleeve) => stats.sleeves == 0,
+        Some(Feature::Seed) => stats.seeds_used == 0,
+        None => false,
+    };
+    if unused {
+        UNUSED_FEATURE_WEIGHT
+    } else {
+        1
+    }
+}
+
+/// Pick a tip index, with `roll` between 0 and 1, never the `previous` one.
+fn pick_tip(weights: &[u32], previous: Option<usize>, roll: f32) -> usize {
+    let weight = |i: usize| if Some(i) == previous { 0 } else { weights[i] };
+    let total: u32 = (0..weights.len()).map(weight).sum();
+    let mut target = roll * total as f32;
+    for i in 0..weights.len() {
+        let tip_weight = weight(i) as f32;
+        if target < tip_weight {
+            return i;
+        }
+        target -= tip_weight;
+    }
+    // Only reachable through float rounding or all weights being 0
+    let last_allowed = (0..weights.len()).rev().find(|i| weight(*i) != 0);
+    last_allowed.unwrap_or(0)
+}
+
+/// Opacity of a tip shown for `elapsed` seconds.
+fn tip_alpha(elapsed: f32) -> f32 {
+    let fade_in = elapsed / FADE_DURATION;
+    let fade_out = (TIP_DURATION - elapsed) / FADE_DURATION;
+    fade_in.min(fade_out).clamp(0.0, 1.0)
+}
+
+fn cycle_tips(
+    time: Res<Time>,
+    stats: Res<Stats>,
+    strings: Res<Strings>,
+    mut tips: Query<(&mut Text, &mut LoadingTip)>,
+) {
+    for (mut text, mut tip) in tips.iter_mut() {
+        tip.elapsed += time.delta_seconds();
+        if tip.shown.is_none() || tip.elapsed >= TIP_DURATION {
+            let weights = TIPS.map(|t| tip_weight(&t, &stats));
+            let index = pick_tip(&weights, tip.shown, fastrand::f32());
+            tip.shown = Some(index);
+            tip.elapsed = 0.0;
+            text.sections[0].value = strings.get(TIPS[index].key).to_owned();
+        }
+        text.sections[0].style.color.set_a(tip_alpha(tip.elapsed));
+    }
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        app.add_system_set(self.0.on_update(cycle_tips));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pick_tip_avoids_repeats() {
+        let weights = [1, 1, 1];
+        for roll in [0.0, 0.3, 0.5, 0.7, 0.999] {
+            assert_ne!(pick_tip(&weights, Some(1), roll), 1);
+        }
+        assert_eq!(pick_tip(&weights, Some(0), 0.0), 1);
+        assert_eq!(pick_tip(&weights, Some(2), 0.999), 1);
+        assert_eq!(pick_tip(&[1], Some(0), 0.5), 0);
+    }
+
+    #[test]
+    fn pick_tip_follows_weights() {
+        let weights = [3, 1];
+        assert_eq!(pick_tip(&weights, None, 0.0), 0);
+        assert_eq!(pick_tip(&weights, None, 0.7), 0);
+        assert_eq!(pick_tip(&weights, None, 0.8), 1);
+        assert_eq!(pick_tip(&weights, None, 1.0), 1);
+    }
+
+    #[test]
+    fn unused_features_weigh_more() {
+        let mut stats = Stats::default();
+        let seed_tip = &TIPS[2];
+        assert_eq!(tip_weight(seed_tip, &stats), UNUSED_FEATURE_WEIGHT);
+        stats.seeds_used = 1;
+        assert_eq!(tip_weight(seed_tip, &stats), 1);
+        assert_eq!(tip_weight(&TIPS[6], &stats), 1);
+    }
+
+    #[test]
+    fn tips_fade_in_and_out() {
+        assert_eq!(tip_alpha(0.0), 0.0);
+        assert_eq!(tip_alpha(FADE_DURATION), 1.0);
+        assert_eq!(tip_alpha(TIP_DURATION / 2.0), 1.0);
+        assert_eq!(tip_alpha(TIP_DURATION), 0.0);
+    }
+}
@@ -0,0 +1,211 @@
+//! Titles, a nickname earned at the end of a game for how the player played.
+//!
+//! [`track_play_style`] sums up the game in a [`GameSummary`], and picks its
+//! [`Title`] once the game ends, see [`pick_title`]. The restart menu shows
+//! it, the [`Stats`] count how many times each title was earned.
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use enum_map::Enum;
+
+use crate::{
+    cheat::CheatEvent,
+    game_event::GameEvent,
+    game_flow::{ScoreBonuses, Scores},
+    state::GameState,
+    stats::Stats,
+    EndReason,
+};
+
+/// Cards to sleeve without getting caught for [`Title::SleightMaster`].
+const SLEIGHT_CARDS: usize = 3;
+/// Bonus points from words for [`Title::WordWizard`].
+const WIZARD_POINTS: i32 = 10;
+/// The [`Title::Grinder`] wins by less than this many points.
+const GRINDER_MARGIN: i32 = 5;
+
+/// In priority order: when a game earns several titles, the first one is
+/// picked.
+#[derive(Clone, Copy, Debug, Enum, PartialEq)]
+pub enum Title {
+    /// Sleeved [`SLEIGHT_CARDS`] cards, never caught.
+    SleightMaster,
+    /// Got [`WIZARD_POINTS`] bonus points from words.
+    WordWizard,
+    /// Won by less than [`GRINDER_MARGIN`] points.
+    Grinder,
+    /// Lost without cheating.
+    HonestLoser,
+}
+impl Title {
+    pub const ALL: [Self; 4] = [
+        Self::SleightMaster,
+        Self::WordWizard,
+        Self::Grinder,
+        Self::HonestLoser,
+    ];
+    /// Name of the title in the stats file.
+    pub fn id(self) -> &'static str {
+        match self {
+            Self::SleightMaster => "sleight_master",
+            Self::WordWizard => "word_wizard",
+            Self::Grinder => "grinder",
+            Self::HonestLoser => "honest_loser",
+        }
+    }
+    /// Key of the title name in [`Strings`].
+    ///
+    /// [`Strings`]: crate::lang::Strings
+    pub fn name_key(self) -> &'static str {
+        match self {
+            Self::SleightMaster => "title.sleight_master",
+            Self::WordWizard => "title.word_wizard",
+            Self::Grinder => "title.grinder",
+            Self::HonestLoser => "title.honest_loser",
+        }
+    }
+    /// Whether a game that ended for `end` earns this title. Only games
+    /// played to the end earn one, so the sleeved cards were never caught.
+    fn earned(self, end: EndReason, summary: &GameSummary) -> bool {
+        use EndReason::{Loss, Victory};
+        match (self, end) {
+            (_, EndReason::CaughtCheating | EndReason::Conceded) => false,
+            (Self::SleightMaster, _) => summary.sleeved >= SLEIGHT_CARDS,
+            (Self::WordWizard, _) => summary.word_points >= WIZARD_POINTS,
+            (Self::Grinder, Victory) => summary.margin < GRINDER_MARGIN,
+            (Self::HonestLoser, Loss) => !summary.cheated,
+            (Self::Grinder | Self::HonestLoser, _) => false,
+        }
+    }
+}
+
+/// How the player played the current game.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GameSummary {
+    /// The player sleeved cards or distracted the bird.
+    pub cheated: bool,
+    /// Cards slipped in the sleeve unseen by the bird.
+    pub sleeved: usize,
+    /// Bonus points the player got from words of power.
+    pub word_points: i32,
+    /// How many more points the player scored than the oppo.
+    pub margin: i32,
+}
+
+/// The title earned by a game that ended for `end`, the first of
+/// [`Title::ALL`] that `summary` earns.
+pub fn pick_title(end: EndReason, summary: &GameSummary) -> Option<Title> {
+    Title::ALL
+        .into_iter()
+        .find(|title| title.earned(end, summary))
+}
+
+/// The [`Title`] of the game that just ended, shown on the restart menu.
+#[derive(Default)]
+pub struct LastTitle(pub Option<Title>);
+
+/// Keep the [`GameSummary`] up to date, and pick the [`LastTitle`] when the
+/// game ends.
+fn track_play_style(
+    mut summary: ResMut<GameSummary>,
+    mut last_title: ResMut<LastTitle>,
+    mut stats: ResMut<Stats>,
+    mut cheats: EventReader<CheatEvent>,
+    mut events: EventReader<GameEvent>,
+    bonuses: Res<ScoreBonuses>,
+    scores: Res<Scores>,
+) {
+    if cheats.iter().count() != 0 {
+        summary.cheated = true;
+    }
+    for event in events.iter() {
+        match event {
+            GameEvent::CardSleeved(_) => summary.sleeved += 1,
+            GameEvent::GameEnded(end) => {
+                summary.word_points = bonuses.player();
+                summary.margin = scores.player() - scores.oppo();
+                last_title.0 = pick_title(*end, &summary);
+                if let Some(title) = last_title.0 {
+                    stats.titles[title] += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn reset_summary(mut summary: ResMut<GameSummary>, mut last_title: ResMut<LastTitle>) {
+    *summary = GameSummary::default();
+    last_title.0 = None;
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        // Labelled with the stats, so that the restart menu shows the title
+        // of the game that just ended, before leaving it resets the scores.
+        app.init_resource::<GameSummary>()
+            .init_resource::<LastTitle>()
+            .add_system(track_play_style.label("record_stats"))
+            .add_system_set(self.0.on_enter(reset_summary));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use EndReason::{CaughtCheating, Conceded, Loss, Victory};
+
+    fn summary(cheated: bool, sleeved: usize, word_points: i32, margin: i32) -> GameSummary {
+        GameSummary { cheated, sleeved, word_points, margin }
+    }
+
+    #[test]
+    fn honest_loser_never_cheated() {
+        let honest = summary(false, 0, 0, -12);
+        assert_eq!(pick_title(Loss, &honest), Some(Title::HonestLoser));
+        assert_eq!(pick_title(Loss, &summary(true, 0, 0, -12)), None);
+        assert_eq!(pick_title(Conceded, &honest), None, "didn't play it out");
+    }
+
+    #[test]
+    fn sleight_master_sleeved_three_cards() {
+        let sleight = summary(true, 3, 0, 20);
+        assert_eq!(pick_title(Victory, &sleight), Some(Title::SleightMaster));
+        assert_eq!(pick_title(Loss, &sleight), Some(Title::SleightMaster));
+        assert_eq!(pick_title(CaughtCheating, &sleight), None);
+        assert_eq!(pick_title(Victory, &summary(true, 2, 0, 20)), None);
+    }
+
+    #[test]
+    fn word_wizard_got_ten_word_points() {
+        let wizard = summary(false, 0, 10, 20);
+        assert_eq!(pick_title(Victory, &wizard), Some(Title::WordWizard));
+        assert_eq!(pick_title(Victory, &summary(false, 0, 9, 20)), None);
+        let losing_wizard = summary(false, 0, 14, -3);
+        assert_eq!(pick_title(Loss, &losing_wizard), Some(Title::WordWizard));
+    }
+
+    #[test]
+    fn grinder_won_by_a_hair() {
+        let grinder = summary(true, 1, 4, 4);
+        assert_eq!(pick_title(Victory, &grinder), Some(Title::Grinder));
+        assert_eq!(pick_title(Victory, &summary(true, 1, 4, 5)), None);
+        assert_eq!(pick_title(Loss, &grinder), None);
+    }
+
+    #[test]
+    fn titles_follow_their_priority() {
+        let everything = summary(true, 3, 12, 1);
+        assert_eq!(pick_title(Victory, &everything), Some(Title::SleightMaster));
+        let words_and_grind = summary(false, 0, 12, 1);
+        assert_eq!(
+            pick_title(Victory, &words_and_grind),
+            Some(Title::WordWizard)
+        );
+        let words_and_honesty = summary(false, 0, 12, -8);
+        assert_eq!(
+            pick_title(Loss, &words_and_honesty),
+            Some(Title::WordWizard)
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Toasts announcing unlocked achievements, in the top right corner.
+//!
+//! Toasts are queued, they slide in one at a time whatever the game state.
+use std::collections::VecDeque;
+
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use bevy_ui_build_macros::{build_ui, rect, size, style, unit};
+
+use super::common::UiAssets;
+use crate::{
+    achievements::{Achievement, AchievementUnlocked},
+    lang::Strings,
+};
+
+/// Seconds a toast takes to slide in, and out.
+const SLIDE_DURATION: f32 = 0.4;
+/// Seconds a toast stays fully in view.
+const SHOWN_DURATION: f32 = 3.0;
+const TOAST_WIDTH: f32 = 420.0;
+const MARGIN: f32 = 20.0;
+
+/// Achievements waiting for their toast.
+#[derive(Default)]
+struct ToastQueue(VecDeque<Achievement>);
+
+/// The toast shown, with the seconds since it started sliding in.
+#[derive(Component)]
+struct Toast(f32);
+
+/// How far right of its resting place the toast is, as a fraction of its
+/// width, `elapsed` seconds after it started sliding in. `None` once it slid
+/// out.
+fn slide_offset(elapsed: f32) -> Option<f32> {
+    let slide_out = SLIDE_DURATION + SHOWN_DURATION;
+    match elapsed {
+        t if t < SLIDE_DURATION => Some(1.0 - t / SLIDE_DURATION),
+        t if t < slide_out => Some(0.0),
+        t if t < slide_out + SLIDE_DURATION => Some((t - slide_out) / SLIDE_DURATION),
+        _ => None,
+    }
+}
+
+fn queue_toasts(mut queue: ResMut<ToastQueue>, mut unlocks: EventReader<AchievementUnlocked>) {
+    queue
+        .0
+        .extend(unlocks.iter().map(|AchievementUnlocked(a)| *a));
+}
+
+fn spawn_toast(
+    mut cmds: Commands,
+    mut queue: ResMut<ToastQueue>,
+    toasts: Query<(), With<Toast>>,
+    ui_assets: Res<UiAssets>,
+    strings: Res<Strings>,
+) {
+    if !toasts.is_empty() {
+        return;
+    }
+    let achievement = match queue.0.pop_front() {
+        Some(achievement) => achievement,
+        None => return,
+    };
+    let name = strings.get(achievement.name_key());
+    let text = strings.format("achievement.unlocked", &[("name", name)]);
+    let node = NodeBundle {
+        color: Color::rgba(0.1, 0.1, 0.1, 0.9).into(),
+        ..default()
+    };
+    build_ui! {
+        #[cmd(cmds)]
+        node{
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                top: Val::Px(MARGIN),
+                right: Val::Px(MARGIN - TOAST_WIDTH),
+                ..default()
+            },
+            size: size!(TOAST_WIDTH px, auto),
+            padding: rect!(15 px)
+        }[; Name::new("Achievement toast"), Toast(0.0)](
+            entity[ui_assets.text_bundle(&text, 30.0);]
+        )
+    };
+}
+
+fn slide_toast(
+    mut cmds: Commands,
+    mut toasts: Query<(Entity, &mut Toast, &mut Style)>,
+    time: Res<Time>,
+) {
+    for (entity, mut toast, mut style) in toasts.iter_mut() {
+        toast.0 += time.delta_seconds();
+        match slide_offset(toast.0) {
+            Some(offset) => style.position.right = Val::Px(MARGIN - offset * TOAST_WIDTH),
+            None => cmds.entity(entity).despawn_recursive(),
+        }
+    }
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ToastQueue>()
+            .add_system(queue_toasts)
+            .add_system(spawn_toast.after(queue_toasts))
+            .add_system(slide_toast.after(spawn_toast));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toasts_slide_in_then_out() {
+        assert_eq!(slide_offset(0.0), Some(1.0));
+        assert_eq!(slide_offset(SLIDE_DURATION / 2.0), Some(0.5));
+        assert_eq!(slide_offset(SLIDE_DURATION), Some(0.0));
+        assert_eq!(slide_offset(SLIDE_DURATION + SHOWN_DURATION), Some(0.0));
+        let gone = 2.0 * SLIDE_DURATION + SHOWN_DURATION;
+        let sliding_out = slide_offset(gone - SLIDE_DURATION / 2.0).unwrap();
+        assert!((sliding_out - 0.5).abs() < 1e-4, "{sliding_out}");
+        assert_eq!(slide_offset(gone), None);
+    }
+}
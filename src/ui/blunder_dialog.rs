@@ -0,0 +1,72 @@
+//! Dialog asking the player to confirm a [`PendingPlay`], a card that loses
+//! the battle while another card in hand wouldn't.
+//!
+//! The dialog is hidden while the game is paused, and shows up again when
+//! it resumes.
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use bevy_ui_navigation::prelude::*;
+
+use super::common::UiAssets;
+use crate::{
+    cleanup_marked,
+    lang::Strings,
+    player_hand::{PendingPlay, ResolvePendingPlay},
+    state::GameState,
+};
+
+#[derive(Clone, Component)]
+struct BlunderDialog;
+
+#[derive(Clone, Component)]
+enum BlunderElem {
+    PlayAnyway,
+    TakeBack,
+}
+
+fn spawn_dialog(
+    mut cmds: Commands,
+    ui_assets: Res<UiAssets>,
+    strings: Res<Strings>,
+    pending: Query<(), With<PendingPlay>>,
+    dialogs: Query<(), With<BlunderDialog>>,
+) {
+    if pending.is_empty() || !dialogs.is_empty() {
+        return;
+    }
+    let question = strings.get("blunder.question");
+    let (yes, no) = (BlunderElem::PlayAnyway, BlunderElem::TakeBack);
+    let dialog = ui_assets.spawn_confirm_dialog(&mut cmds, &strings, question, yes, no);
+    cmds.entity(dialog).insert(BlunderDialog);
+}
+
+fn answer_dialog(
+    mut cmds: Commands,
+    mut nav_events: EventReader<NavEvent>,
+    mut answers: EventWriter<ResolvePendingPlay>,
+    elems: Query<&BlunderElem>,
+    dialogs: Query<Entity, With<BlunderDialog>>,
+) {
+    let play_anyway = match nav_events.nav_iter().activated_in_query(&elems).next() {
+        Some(BlunderElem::PlayAnyway) => true,
+        Some(BlunderElem::TakeBack) => false,
+        None => return,
+    };
+    answers.send(ResolvePendingPlay { play_anyway });
+    for dialog in dialogs.iter() {
+        cmds.entity(dialog).despawn_recursive();
+    }
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        app.add_system_set(
+            self.0
+                .on_update(spawn_dialog)
+                .with_system(answer_dialog.after(NavRequestSystem)),
+        )
+        .add_system_set(SystemSet::on_pause(self.0).with_system(cleanup_marked::<BlunderDialog>))
+        .add_system_set(self.0.on_exit(cleanup_marked::<BlunderDialog>));
+    }
+}
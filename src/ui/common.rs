@@ -1,8 +1,15 @@
 use bevy::math::Vec3Swizzles;
 use bevy::prelude::{Plugin as BevyPlugin, *};
-use bevy_ui_build_macros::{rect, size, style, unit};
+use bevy::window::WindowResized;
+use bevy_ui_build_macros::{build_ui, rect, size, style, unit};
 use bevy_ui_navigation::prelude::*;
 use bevy_ui_navigation::systems::InputMapping;
+use enum_map::{enum_map, EnumMap};
+
+use crate::{
+    lang::{Localized, Strings},
+    war::WordOfPower,
+};
 
 #[derive(Clone, Component, Default)]
 pub struct MenuCursor {
@@ -25,9 +32,45 @@ impl MenuCursor {
     }
 }
 
+/// Label of a setting toggle, `key` being a text with a `{state}` part.
+pub fn on_off_text(strings: &Strings, key: &str, enabled: bool) -> String {
+    let state = strings.get(if enabled { "menu.on" } else { "menu.off" });
+    strings.format(key, &[("state", state)])
+}
+
+/// Root of the dialog spawned with [`UiAssets::spawn_confirm_dialog`].
+#[derive(Component, Clone)]
+pub struct ConfirmDialog;
+
+/// A section of the game rules, see [`UiAssets::spawn_rules`].
+#[derive(Clone, Copy, PartialEq)]
+pub enum RulesSection {
+    /// How turns work and how points are scored.
+    Turns,
+    Effects,
+    Cheating,
+}
+impl RulesSection {
+    fn title_key(self) -> &'static str {
+        match self {
+            Self::Turns => "rules.turns.title",
+            Self::Effects => "rules.effects.title",
+            Self::Cheating => "rules.cheating.title",
+        }
+    }
+    fn text_key(self) -> &'static str {
+        match self {
+            Self::Turns => "rules.turns.text",
+            Self::Effects => "rules.effects.text",
+            Self::Cheating => "rules.cheating.text",
+        }
+    }
+}
+
 pub struct UiAssets {
     pub font: Handle<Font>,
     pub background_image: Handle<Image>,
+    pub words: EnumMap<WordOfPower, Handle<Image>>,
 }
 impl FromWorld for UiAssets {
     fn from_world(world: &mut World) -> Self {
@@ -35,6 +78,7 @@ impl FromWorld for UiAssets {
         Self {
             font: assets.load("Boogaloo-Regular.otf"),
             background_image: assets.load("main_menu_bg.jpg"),
+            words: enum_map! { word => assets.load(&format!("cards/Word{word:?}.png")) },
         }
     }
 }
@@ -49,6 +93,92 @@ impl UiAssets {
     pub fn large_text(&self, content: &str) -> TextBundle {
         self.text_bundle(content, 60.)
     }
+    /// Spawn a dialog asking the player to confirm `question`.
+    ///
+    /// The "Yes" and "No" entries are focusables with respectively the `yes`
+    /// and `no` components, so that the caller can tell which one was
+    /// activated. The caller should despawn the [`ConfirmDialog`] once answered.
+    pub fn spawn_confirm_dialog<T: Component + Clone>(
+        &self,
+        cmds: &mut Commands,
+        strings: &Strings,
+        question: &str,
+        yes: T,
+        no: T,
+    ) -> Entity {
+        let node = NodeBundle {
+            color: Color::NONE.into(),
+            style: style! {
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+            },
+            ..default()
+        };
+        let margin = style! { margin: rect!(30 px), };
+        build_ui! {
+            #[cmd(cmds)]
+            node{
+                position_type: PositionType::Absolute,
+                size: size!(100 pct, 100 pct)
+            }[; UiColor(Color::rgba(0., 0., 0., 0.8)), Name::new("Confirm dialog"), ConfirmDialog](
+                entity[self.large_text(question);],
+                node{ flex_direction: FlexDirection::Row }(
+                    entity[self.large_text(strings.get("dialog.yes")); Focusable::default(), yes, margin.clone()],
+                    entity[self.large_text(strings.get("dialog.no")); Focusable::new().prioritized(), no, margin]
+                )
+            )
+        }
+        .id()
+    }
+    /// Spawn the game rules, with the `highlight` section standing out.
+    ///
+    /// The returned node has no background and is displayed, the caller
+    /// should add it to its own overlay or panel. Section texts wrap, so that
+    /// translations of any length fit.
+    pub fn spawn_rules(
+        &self,
+        cmds: &mut Commands,
+        strings: &Strings,
+        highlight: Option<RulesSection>,
+    ) -> Entity {
+        use RulesSection::{Cheating, Effects, Turns};
+        let node = NodeBundle {
+            color: Color::NONE.into(),
+            style: style! {
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::Center,
+            },
+            ..default()
+        };
+        let sections = [Turns, Effects, Cheating].map(|section| {
+            let highlighted = highlight == Some(section);
+            let color = if highlighted {
+                Color::rgba(0.8, 0.6, 0.2, 0.3)
+            } else {
+                Color::NONE
+            };
+            let mut section_node =
+                cmds.spawn_bundle(NodeBundle { color: color.into(), ..node.clone() });
+            section_node.with_children(|cmds| {
+                let title_key = section.title_key();
+                cmds.spawn_bundle(self.large_text(strings.get(title_key)))
+                    .insert(Localized(title_key));
+                let mut text = self.text_bundle(strings.get(section.text_key()), 30.0);
+                text.style = style! { max_size: size!(700 px, auto), };
+                if highlighted {
+                    text.text.sections[0].style.color = Color::GOLD;
+                }
+                cmds.spawn_bundle(text)
+                    .insert(Localized(section.text_key()));
+            });
+            section_node.insert(Name::new(section.title_key())).id()
+        });
+        cmds.spawn_bundle(node)
+            .insert(Name::new("Rules"))
+            .push_children(&sections)
+            .id()
+    }
     pub fn background(&self) -> ImageBundle {
         use PositionType::Absolute;
         ImageBundle {
@@ -59,30 +189,69 @@ impl UiAssets {
     }
 }
 
+/// Fit the cursor rectangle at `position` of `size` inside the window.
+fn clamp_to_window(position: Vec2, size: Vec2, window: Vec2) -> (Vec2, Vec2) {
+    let size = size.min(window);
+    (position.clamp(Vec2::ZERO, window - size), size)
+}
+
+/// Move the [`MenuCursor`] toward the focused element.
+///
+/// The cursor snaps to it rather than moving when it wasn't shown, and for
+/// two frames after the window is resized, so that it picks up the focused
+/// element's new layout. It is hidden while nothing is focused.
 fn update_highlight(
     mut highlight: Query<(&mut Style, &mut Node, &mut MenuCursor), Without<Focused>>,
     focused: Query<(&Node, &GlobalTransform), With<Focused>>,
+    mut resized: EventReader<WindowResized>,
+    mut snap_frames: Local<u8>,
+    windows: Res<Windows>,
 ) {
     use Val::Px;
-    let query = (highlight.get_single_mut(), focused.get_single());
-    if let (Ok((mut style, mut cursor_node, mut target)), Ok((node, transform))) = query {
-        target.set_target(node, transform);
-        if let (Px(left), Px(bot), Px(width), Px(height)) = (
-            style.position.left,
-            style.position.bottom,
-            style.size.width,
-            style.size.height,
-        ) {
-            let size = cursor_node.size;
-            cursor_node.size += (target.size - size) * 0.4;
-            style.size.width += (target.size.x - width) * 0.4;
-            style.size.height += (target.size.y - height) * 0.4;
-            style.position.left += (target.position.x - left) * 0.4;
-            style.position.bottom += (target.position.y - bot) * 0.4;
-        } else {
-            style.position = rect!(1 px);
-            style.size = size!(1 px, 1 px);
+    if resized.iter().count() != 0 {
+        *snap_frames = 2;
+    }
+    let snap = *snap_frames != 0;
+    *snap_frames = snap_frames.saturating_sub(1);
+    let (mut style, mut cursor_node, mut target) = match highlight.get_single_mut() {
+        Ok(highlight) => highlight,
+        Err(_) => return,
+    };
+    let (node, transform) = match focused.get_single() {
+        Ok(focused) => focused,
+        Err(_) => {
+            style.display = Display::None;
+            return;
         }
+    };
+    let window = match windows.get_primary() {
+        Some(window) => Vec2::new(window.width(), window.height()),
+        None => return,
+    };
+    target.set_target(node, transform);
+    let (position, size) = clamp_to_window(target.position, target.size, window);
+    let was_hidden = style.display == Display::None;
+    style.display = Display::Flex;
+    if let (Px(left), Px(bot), Px(width), Px(height), false) = (
+        style.position.left,
+        style.position.bottom,
+        style.size.width,
+        style.size.height,
+        snap || was_hidden,
+    ) {
+        cursor_node.size += (size - cursor_node.size) * 0.4;
+        style.size.width += (size.x - width) * 0.4;
+        style.size.height += (size.y - height) * 0.4;
+        style.position.left += (position.x - left) * 0.4;
+        style.position.bottom += (position.y - bot) * 0.4;
+    } else {
+        cursor_node.size = size;
+        style.size = Size::new(Px(size.x), Px(size.y));
+        style.position = UiRect {
+            left: Px(position.x),
+            bottom: Px(position.y),
+            ..default()
+        };
     }
 }
 
@@ -95,3 +264,21 @@ impl BevyPlugin for Plugin {
             .add_system_to_stage(CoreStage::PostUpdate, update_highlight);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cursor_stays_in_window() {
+        let window = Vec2::new(800.0, 600.0);
+        let inside = (Vec2::new(10.0, 20.0), Vec2::new(100.0, 50.0));
+        assert_eq!(clamp_to_window(inside.0, inside.1, window), inside);
+        let off_screen = clamp_to_window(Vec2::new(-30.0, 580.0), Vec2::new(100.0, 50.0), window);
+        assert_eq!(off_screen, (Vec2::new(0.0, 550.0), Vec2::new(100.0, 50.0)));
+        let too_large = clamp_to_window(Vec2::new(-10.0, -10.0), Vec2::new(900.0, 50.0), window);
+        assert_eq!(too_large, (Vec2::new(0.0, 0.0), Vec2::new(800.0, 50.0)));
+        let minimized = clamp_to_window(Vec2::new(10.0, 10.0), Vec2::new(100.0, 50.0), Vec2::ZERO);
+        assert_eq!(minimized, (Vec2::ZERO, Vec2::ZERO));
+    }
+}
@@ -0,0 +1,63 @@
+//! In-game rules overlay, toggled with the [`Action::Help`] key.
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use bevy_ui_build_macros::{build_ui, rect, size, style, unit};
+
+use super::{common::UiAssets, word_gallery::spawn_word_gallery};
+use crate::{
+    cleanup_marked,
+    lang::Strings,
+    settings::{Action, KeyBindings},
+    state::GameState,
+};
+
+#[derive(Clone, Component)]
+struct HelpOverlay;
+
+fn toggle_help(
+    mut cmds: Commands,
+    mut keys: ResMut<Input<KeyCode>>,
+    ui_assets: Res<UiAssets>,
+    strings: Res<Strings>,
+    bindings: Res<KeyBindings>,
+    overlays: Query<Entity, With<HelpOverlay>>,
+) {
+    let help_key = bindings.key(Action::Help);
+    if !keys.just_pressed(help_key) {
+        return;
+    }
+    keys.reset(help_key);
+    if !overlays.is_empty() {
+        for overlay in overlays.iter() {
+            cmds.entity(overlay).despawn_recursive();
+        }
+        return;
+    }
+    let rules = ui_assets.spawn_rules(&mut cmds, &strings, None);
+    let gallery = spawn_word_gallery(&mut cmds, &ui_assets, &strings);
+    let key = format!("{help_key:?}");
+    let hint = strings.format("help.close_hint", &[("key", key.as_str())]);
+    build_ui! {
+        #[cmd(cmds)]
+        node{
+            position_type: PositionType::Absolute,
+            position: rect!(10 pct),
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center
+        }[; UiColor(Color::rgba(0.1, 0.1, 0.1, 0.9)), Name::new("Help overlay"), HelpOverlay](
+            id(rules),
+            id(gallery),
+            entity[ui_assets.text_bundle(&hint, 30.0);]
+        )
+    };
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        app.add_system_set(self.0.on_update(toggle_help))
+            .add_system_set(self.0.on_exit(cleanup_marked::<HelpOverlay>))
+            .add_system_set(SystemSet::on_pause(self.0).with_system(cleanup_marked::<HelpOverlay>));
+    }
+}
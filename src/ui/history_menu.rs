@@ -0,0 +1,367 @@
+//! Game history screen, reached from the main menu.
+//!
+//! Lists the last [`SHOWN_GAMES`] games of the [`History`], [`PAGE_SIZE`] at
+//! a time. Activating a game shows its details next to the list. The
+//! [`Achievement`]s are listed on the side, the locked ones dimmed, followed
+//! by how many times each [`Title`] was earned.
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use bevy_ui_build_macros::{build_ui, rect, size, style, unit};
+use bevy_ui_navigation::prelude::*;
+
+use super::common::{MenuCursor, UiAssets};
+use crate::{
+    achievements::Achievement,
+    cleanup_marked,
+    history::{GameRecord, History},
+    lang::{Localized, Strings},
+    state::GameState,
+    stats::Stats,
+    titles::Title,
+};
+
+/// Most games listed.
+const SHOWN_GAMES: usize = 50;
+/// Games listed at once.
+const PAGE_SIZE: usize = 10;
+
+#[derive(Clone, Component)]
+struct HistoryRoot;
+
+#[derive(Component, Clone, PartialEq)]
+enum HistoryElem {
+    /// The row of the page showing the game at this index.
+    Game(usize),
+    Newer,
+    Older,
+    Back,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Column {
+    Date,
+    End,
+    Score,
+    Turns,
+}
+impl Column {
+    const ALL: [Self; 4] = [Self::Date, Self::End, Self::Score, Self::Turns];
+    fn width(self) -> f32 {
+        match self {
+            Self::Date => 240.0,
+            Self::End => 260.0,
+            Self::Score => 120.0,
+            Self::Turns => 80.0,
+        }
+    }
+    fn header_key(self) -> &'static str {
+        match self {
+            Self::Date => "history.date",
+            Self::End => "history.end",
+            Self::Score => "history.score",
+            Self::Turns => "history.turns",
+        }
+    }
+    fn text(self, record: &GameRecord, strings: &Strings) -> String {
+        match self {
+            Self::Date => record.date_text(),
+            Self::End => strings.get(record.end_key()).to_owned(),
+            Self::Score => format!("{} - {}", record.player_score, record.oppo_score),
+            Self::Turns => record.turns.to_string(),
+        }
+    }
+}
+
+/// A cell of the games table, for the game at this row of the page.
+#[derive(Component)]
+struct Cell(usize, Column);
+
+#[derive(Component)]
+struct PageText;
+
+#[derive(Component)]
+struct DetailText;
+
+#[derive(Component)]
+struct AchievementText(Achievement);
+
+#[derive(Component)]
+struct TitleText(Title);
+
+/// The page of the list shown, the most recent games are on page 0.
+#[derive(Default)]
+struct Page(usize);
+
+fn page_count(games: usize) -> usize {
+    ((games + PAGE_SIZE - 1) / PAGE_SIZE).max(1)
+}
+
+/// The game at `row` of `page`, if there is one.
+fn game_at(history: &History, page: usize, row: usize) -> Option<&GameRecord> {
+    history.latest(SHOWN_GAMES).nth(page * PAGE_SIZE + row)
+}
+
+fn detail_text(record: &GameRecord, strings: &Strings) -> String {
+    let (player, oppo) = (
+        record.player_score.to_string(),
+        record.oppo_score.to_string(),
+    );
+    let args = [("player", player.as_str()), ("oppo", oppo.as_str())];
+    let score = strings.format("history.detail_score", &args);
+    let turns = record.turns.to_string();
+    let turns = strings.format("history.detail_turns", &[("turns", turns.as_str())]);
+    let end = strings.get(record.end_key());
+    let mut text = format!("{}\n{end}\n{score}\n{turns}", record.date_text());
+    if record.fair_game {
+        text.push('\n');
+        text.push_str(strings.get("history.fair_game"));
+    }
+    if record.deck_handicap != 0 {
+        let points = record.deck_handicap.to_string();
+        text.push('\n');
+        text.push_str(&strings.format("history.deck_handicap", &[("points", points.as_str())]));
+    }
+    text
+}
+
+/// The text and color of `achievement` in the list.
+fn achievement_line(achievement: Achievement, stats: &Stats, strings: &Strings) -> (String, Color) {
+    let name = strings.get(achievement.name_key());
+    if stats.achievements[achievement] {
+        (name.to_owned(), Color::GOLD)
+    } else {
+        let locked = strings.format("achievement.locked", &[("name", name)]);
+        (locked, Color::GRAY)
+    }
+}
+
+/// The text and color of `title` in the list, dimmed if never earned.
+fn title_line(title: Title, stats: &Stats, strings: &Strings) -> (String, Color) {
+    let (name, earned) = (strings.get(title.name_key()), stats.titles[title]);
+    let count = earned.to_string();
+    let args = [("name", name), ("count", count.as_str())];
+    let line = strings.format("title.count", &args);
+    (line, if earned == 0 { Color::GRAY } else { Color::GOLD })
+}
+
+/// Spawn a row of the games table, with [`Cell`]s if it shows the game at
+/// `row` of the page.
+fn spawn_row(
+    cmds: &mut Commands,
+    cell: &dyn Fn(Column) -> TextBundle,
+    row: Option<usize>,
+) -> Entity {
+    let mut row_node = cmds.spawn_bundle(NodeBundle {
+        color: Color::NONE.into(),
+        style: style! { flex_direction: FlexDirection::Row, },
+        ..default()
+    });
+    row_node.with_children(|cmds| {
+        for column in Column::ALL {
+            let style = style! { size: size!(column.width() px, auto), };
+            let mut cell_entity = cmds.spawn_bundle(TextBundle { style, ..cell(column) });
+            if let Some(row) = row {
+                cell_entity.insert(Cell(row, column));
+            }
+        }
+    });
+    row_node.id()
+}
+
+fn setup_menu(mut cmds: Commands, ui_assets: Res<UiAssets>, strings: Res<Strings>) {
+    use HistoryElem::{Back, Game, Newer, Older};
+
+    let node = NodeBundle {
+        color: Color::NONE.into(),
+        style: style! {
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+        },
+        ..default()
+    };
+    let header_cell =
+        |column: Column| ui_assets.text_bundle(strings.get(column.header_key()), 30.0);
+    let mut rows = vec![spawn_row(&mut cmds, &header_cell, None)];
+    for row in 0..PAGE_SIZE {
+        let entity = spawn_row(&mut cmds, &|_| ui_assets.text_bundle("", 30.0), Some(row));
+        cmds.entity(entity)
+            .insert_bundle((Focusable::default(), Game(row)));
+        rows.push(entity);
+    }
+    let table = cmds
+        .spawn_bundle(node.clone())
+        .insert(Name::new("Games table"))
+        .push_children(&rows)
+        .id();
+    let achievements: Vec<_> = Achievement::ALL
+        .iter()
+        .map(|achievement| {
+            let text = ui_assets.text_bundle("", 24.0);
+            let elem = AchievementText(*achievement);
+            cmds.spawn_bundle(text).insert(elem).id()
+        })
+        .collect();
+    let achievement_list = cmds
+        .spawn_bundle(node.clone())
+        .insert(Name::new("Achievements list"))
+        .push_children(&achievements)
+        .id();
+    let titles: Vec<_> = Title::ALL
+        .iter()
+        .map(|title| {
+            let text = ui_assets.text_bundle("", 24.0);
+            cmds.spawn_bundle(text).insert(TitleText(*title)).id()
+        })
+        .collect();
+    let title_list = cmds
+        .spawn_bundle(node.clone())
+        .insert(Name::new("Titles list"))
+        .push_children(&titles)
+        .id();
+    let mut detail = ui_assets.text_bundle(strings.get("history.select_hint"), 30.0);
+    detail.style = style! { size: size!(300 px, auto), margin: rect!(30 px), };
+    let margin = style! { margin: rect!(20 px, 0 px), };
+    let large_text = |key| ui_assets.large_text(strings.get(key));
+    let cursor = MenuCursor::spawn_ui_element(&mut cmds);
+    build_ui! {
+        #[cmd(cmds)]
+        node{ size: size!(100 pct, 100 pct) }[; Name::new("History root"), HistoryRoot](
+            entity[ui_assets.background();],
+            id(cursor),
+            node[; Name::new("History column")](
+                entity[large_text("history.title"); Localized("history.title")],
+                node{ flex_direction: FlexDirection::Row, align_items: AlignItems::FlexStart }(
+                    id(table),
+                    entity[detail; DetailText],
+                    node[; Name::new("Achievements column")](
+                        entity[ui_assets.text_bundle(strings.get("achievement.title"), 30.0); Localized("achievement.title")],
+                        id(achievement_list),
+                        entity[ui_assets.text_bundle(strings.get("title.heading"), 30.0); Localized("title.heading")],
+                        id(title_list)
+                    )
+                ),
+                node{ flex_direction: FlexDirection::Row }(
+                    entity[large_text("history.newer"); Localized("history.newer"), Focusable::default(), Newer, margin.clone()],
+                    entity[ui_assets.text_bundle("", 30.0); PageText],
+                    entity[large_text("history.older"); Localized("history.older"), Focusable::default(), Older, margin]
+                ),
+                entity[large_text("menu.back"); Localized("menu.back"), Focusable::new().prioritized(), Back]
+            )
+        )
+    };
+}
+
+fn update_menu(
+    mut nav_events: EventReader<NavEvent>,
+    mut game_state: ResMut<State<GameState>>,
+    mut page: ResMut<Page>,
+    mut detail: Query<&mut Text, With<DetailText>>,
+    elems: Query<&HistoryElem>,
+    history: Res<History>,
+    strings: Res<Strings>,
+) {
+    use NavEvent::NoChanges;
+    use NavRequest::Action as Activate;
+    let last_page = page_count(history.latest(SHOWN_GAMES).count()) - 1;
+    for (event_type, from) in nav_events.nav_iter().types() {
+        match (event_type, elems.get(from)) {
+            (NoChanges { request: Activate, .. }, Ok(HistoryElem::Game(row))) => {
+                if let Some(record) = game_at(&history, page.0, *row) {
+                    let text = detail_text(record, &strings);
+                    for mut detail in detail.iter_mut() {
+                        detail.sections[0].value = text.clone();
+                    }
+                }
+            }
+            (NoChanges { request: Activate, .. }, Ok(HistoryElem::Newer)) => {
+                page.0 = page.0.saturating_sub(1);
+            }
+            (NoChanges { request: Activate, .. }, Ok(HistoryElem::Older)) => {
+                page.0 = (page.0 + 1).min(last_page);
+            }
+            (NoChanges { request: Activate, .. }, Ok(HistoryElem::Back)) => {
+                game_state.set(GameState::MainMenu).unwrap();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_page(
+    page: Res<Page>,
+    history: Res<History>,
+    stats: Res<Stats>,
+    strings: Res<Strings>,
+    new_menus: Query<(), Added<HistoryRoot>>,
+    mut cells: Query<(&mut Text, &Cell), (Without<PageText>, Without<AchievementText>)>,
+    mut page_text: Query<&mut Text, (With<PageText>, Without<AchievementText>)>,
+    mut achievements: Query<(&mut Text, &AchievementText), Without<TitleText>>,
+    mut titles: Query<(&mut Text, &TitleText), (Without<Cell>, Without<PageText>)>,
+) {
+    if !page.is_changed() && !strings.is_changed() && new_menus.is_empty() {
+        return;
+    }
+    for (mut text, AchievementText(achievement)) in achievements.iter_mut() {
+        let (value, color) = achievement_line(*achievement, &stats, &strings);
+        text.sections[0].value = value;
+        text.sections[0].style.color = color;
+    }
+    for (mut text, TitleText(title)) in titles.iter_mut() {
+        let (value, color) = title_line(*title, &stats, &strings);
+        text.sections[0].value = value;
+        text.sections[0].style.color = color;
+    }
+    for (mut text, Cell(row, column)) in cells.iter_mut() {
+        let record = game_at(&history, page.0, *row);
+        text.sections[0].value = record.map_or_else(String::new, |r| column.text(r, &strings));
+    }
+    let games = history.latest(SHOWN_GAMES).count();
+    let text = if games == 0 {
+        strings.get("history.empty").to_owned()
+    } else {
+        let (current, total) = ((page.0 + 1).to_string(), page_count(games).to_string());
+        let args = [("page", current.as_str()), ("pages", total.as_str())];
+        strings.format("history.page", &args)
+    };
+    for mut page_text in page_text.iter_mut() {
+        page_text.sections[0].value = text.clone();
+    }
+}
+
+fn reset_page(mut page: ResMut<Page>) {
+    page.0 = 0;
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        app.init_resource::<Page>()
+            .add_system_set(self.0.on_enter(setup_menu))
+            .add_system_set(
+                self.0
+                    .on_exit(cleanup_marked::<HistoryRoot>)
+                    .with_system(reset_page),
+            )
+            .add_system_set(
+                SystemSet::on_update(self.0)
+                    .with_system(update_menu.after(NavRequestSystem))
+                    .with_system(update_page.after(update_menu)),
+            );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_count_rounds_up() {
+        assert_eq!(page_count(0), 1);
+        assert_eq!(page_count(1), 1);
+        assert_eq!(page_count(PAGE_SIZE), 1);
+        assert_eq!(page_count(PAGE_SIZE + 1), 2);
+        assert_eq!(page_count(SHOWN_GAMES), SHOWN_GAMES / PAGE_SIZE);
+    }
+}
@@ -0,0 +1,212 @@
+//! Key bindings screen, reached from the main menu.
+//!
+//! Activating a binding locks the navigation until the player presses the new
+//! key for it, see [`capture_key`].
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use bevy_ui_build_macros::{build_ui, rect, size, style, unit};
+use bevy_ui_navigation::prelude::*;
+
+use super::common::{MenuCursor, UiAssets};
+use crate::{
+    cleanup_marked,
+    lang::Strings,
+    settings::{Action, KeyBindings, RebindError},
+    state::GameState,
+};
+
+#[derive(Clone, Component)]
+struct KeyBindingsRoot;
+
+#[derive(Component, Clone, PartialEq)]
+enum BindingsElem {
+    Binding(Action),
+    ResetDefaults,
+    Back,
+}
+
+/// Text showing the key bound to an [`Action`].
+#[derive(Component)]
+struct BindingText(Action);
+
+/// Text telling the player what happens with the binding they are editing.
+#[derive(Component)]
+struct StatusText;
+
+/// The action waiting for the player to press its new key.
+#[derive(Default)]
+struct Rebinding(Option<Action>);
+
+fn binding_text(action: Action, bindings: &KeyBindings, strings: &Strings) -> String {
+    let key = format!("{:?}", bindings.key(action));
+    let args = [
+        ("action", strings.get(action.name_key())),
+        ("key", key.as_str()),
+    ];
+    strings.format("key_bindings.binding", &args)
+}
+
+fn setup_menu(
+    mut cmds: Commands,
+    ui_assets: Res<UiAssets>,
+    strings: Res<Strings>,
+    bindings: Res<KeyBindings>,
+) {
+    use BindingsElem::{Back, Binding, ResetDefaults};
+
+    let node = NodeBundle {
+        color: Color::NONE.into(),
+        style: style! {
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+        },
+        ..default()
+    };
+    let margin = style! { margin: rect!(0 px, 0 px, 0 px, 30 px,), };
+    let large_text = |key| ui_assets.large_text(strings.get(key));
+    let entries = Action::ALL.map(|action| {
+        let text = ui_assets.large_text(&binding_text(action, &bindings, &strings));
+        let name = Name::new(action.name_key());
+        cmds.spawn_bundle(text)
+            .insert_bundle((
+                Focusable::lock(),
+                Binding(action),
+                BindingText(action),
+                name,
+            ))
+            .id()
+    });
+    let cursor = MenuCursor::spawn_ui_element(&mut cmds);
+    build_ui! {
+        #[cmd(cmds)]
+        node{ size: size!(100 pct, 100 pct) }[; Name::new("Key bindings root"), KeyBindingsRoot](
+            entity[ui_assets.background();],
+            id(cursor),
+            node[; Name::new("Bindings column")](
+                entity[large_text("key_bindings.title"); margin.clone()],
+                node[; Name::new("Bindings")](
+                    id(entries[0]),
+                    id(entries[1]),
+                    id(entries[2]),
+                    id(entries[3]),
+                    id(entries[4]),
+                    id(entries[5]),
+                ),
+                entity[ui_assets.text_bundle(strings.get("key_bindings.hint"), 30.0); StatusText, margin],
+                entity[large_text("key_bindings.reset"); Focusable::default(), ResetDefaults],
+                entity[large_text("menu.back"); Focusable::new().prioritized(), Back],
+            )
+        )
+    };
+}
+
+fn update_menu(
+    mut nav_events: EventReader<NavEvent>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<KeyBindings>,
+    mut game_state: ResMut<State<GameState>>,
+    mut status: Query<&mut Text, With<StatusText>>,
+    elems: Query<&BindingsElem>,
+    strings: Res<Strings>,
+) {
+    use NavEvent::{Locked, NoChanges};
+    use NavRequest::Action as Activate;
+    for (event_type, from) in nav_events.nav_iter().types() {
+        match (event_type, elems.get(from)) {
+            (Locked(..), Ok(BindingsElem::Binding(action))) => {
+                rebinding.0 = Some(*action);
+                let action = strings.get(action.name_key());
+                let message = strings.format("key_bindings.press_new", &[("action", action)]);
+                for mut text in status.iter_mut() {
+                    text.sections[0].value = message.clone();
+                }
+            }
+            (NoChanges { request: Activate, .. }, Ok(BindingsElem::ResetDefaults)) => {
+                *bindings = KeyBindings::default();
+            }
+            (NoChanges { request: Activate, .. }, Ok(BindingsElem::Back)) => {
+                game_state.set(GameState::MainMenu).unwrap();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Bind the key the player just pressed to the [`Rebinding`] action, then
+/// unlock the navigation.
+fn capture_key(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<KeyBindings>,
+    mut nav_requests: EventWriter<NavRequest>,
+    mut status: Query<&mut Text, With<StatusText>>,
+    strings: Res<Strings>,
+) {
+    let action = match rebinding.0 {
+        Some(action) => action,
+        None => return,
+    };
+    let key = match keys.get_just_pressed().next() {
+        Some(key) => *key,
+        None => return,
+    };
+    let key_name = format!("{key:?}");
+    let message = match bindings.rebind(action, key) {
+        Ok(()) => {
+            let action = strings.get(action.name_key());
+            let args = [("action", action), ("key", key_name.as_str())];
+            strings.format("key_bindings.bound", &args)
+        }
+        Err(RebindError::Conflict(other)) => {
+            let other = strings.get(other.name_key());
+            let args = [("action", other), ("key", key_name.as_str())];
+            strings.format("key_bindings.conflict", &args)
+        }
+        Err(RebindError::NotBindable) => {
+            strings.format("key_bindings.not_bindable", &[("key", key_name.as_str())])
+        }
+    };
+    for mut text in status.iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+    rebinding.0 = None;
+    keys.reset(key);
+    nav_requests.send(NavRequest::Unlock);
+}
+
+fn update_binding_texts(
+    bindings: Res<KeyBindings>,
+    strings: Res<Strings>,
+    mut texts: Query<(&mut Text, &BindingText)>,
+) {
+    if !bindings.is_changed() {
+        return;
+    }
+    for (mut text, BindingText(action)) in texts.iter_mut() {
+        text.sections[0].value = binding_text(*action, &bindings, &strings);
+    }
+}
+
+fn reset_rebinding(mut rebinding: ResMut<Rebinding>) {
+    rebinding.0 = None;
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        app.init_resource::<Rebinding>()
+            .add_system_set(self.0.on_enter(setup_menu))
+            .add_system_set(
+                self.0
+                    .on_exit(cleanup_marked::<KeyBindingsRoot>)
+                    .with_system(reset_rebinding),
+            )
+            .add_system_set(
+                SystemSet::on_update(self.0)
+                    .with_system(capture_key.before(NavRequestSystem))
+                    .with_system(update_menu.after(NavRequestSystem))
+                    .with_system(update_binding_texts),
+            );
+    }
+}
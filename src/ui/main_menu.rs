@@ -1,45 +1,97 @@
-use super::common::{MenuCursor, UiAssets};
+use super::common::{on_off_text, MenuCursor, UiAssets};
+use super::slider::{SliderParams, VolumeSliderBuilder};
+use super::word_gallery::{spawn_word_gallery, GalleryArrow};
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::{Plugin as BevyPlugin, *};
-use bevy::{app::AppExit, input::mouse::MouseMotion, window::WindowMode};
-use bevy_debug_text_overlay::screen_print;
+use bevy::{app::AppExit, window::WindowMode};
 use bevy_ui_build_macros::{build_ui, rect, size, style, unit};
 use bevy_ui_navigation::prelude::*;
 
 use crate::{
-    audio::{AudioChannel, AudioRequest, AudioRequestSystem, SfxParam},
+    archetype::{ArchetypeList, SelectedArchetype},
+    audio::{AudioChannel, AudioRequest, SfxParam},
     cleanup_marked,
+    deck::{Deck, DeckAssets, DeckError},
+    lang::{Language, Localized, Strings},
+    opponent::{OpponentProfile, SelectedOpponent},
+    rules::GameRules,
+    scene::{AnchorReport, SceneCheck},
+    settings::{GameplaySettings, SettingsReset},
     state::GameState,
+    stats::Stats,
+    theme::Theme,
+    war::ParseError,
 };
 
-#[derive(Component)]
-struct MovingSlider;
-
 #[derive(Component, Clone)]
 struct RulesOverlay;
 
+/// The menu entries, hidden while the rules are shown so that navigation
+/// stays within the rules.
+#[derive(Component, Clone)]
+struct MenuColumns;
+
 #[derive(Component, Clone)]
 struct CreditOverlay;
 
 #[derive(Clone, Component)]
 struct MainMenuRoot;
 
+/// Text explaining why the game can't be started, see [`DeckAssets::check`]
+/// and [`SceneCheck`].
+#[derive(Component)]
+pub(super) struct DeckErrorBanner;
+
+const DECK_ERROR_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
+
+/// Name of the selected archetype, greyed out if its deck is broken.
+#[derive(Component)]
+struct ArchetypeName;
+
+#[derive(Component)]
+struct ArchetypeDescription;
+
+const BROKEN_ARCHETYPE_COLOR: Color = Color::GRAY;
+
 #[derive(Component, Clone, PartialEq)]
 enum MainMenuElem {
     Start,
+    Opponent,
+    PreviousArchetype,
+    NextArchetype,
+    FairGame,
+    Initiative,
+    Objective,
+    Coop,
     Exit,
     Credits,
     Rules,
+    /// Leads to the practice table, from the rules.
+    Practice,
+    CloseRules,
+    KeyBindings,
+    History,
+    Theme,
+    DeckReveal,
+    FastMode,
+    ReduceMotion,
+    LowSpec,
+    HighContrastCards,
+    BlunderWarning,
+    Streaming,
+    HandSort,
+    AdaptiveDifficulty,
+    DemonWhispers,
+    Language,
     LockMouse,
     ToggleFullScreen,
     Set16_9,
-    AudioSlider(AudioChannel, f64),
+    AudioSlider,
 }
 
 pub struct MenuAssets {
     team_name: Handle<Image>,
     title_image: Handle<Image>,
-    slider_handle: Handle<Image>,
-    slider_bg: Handle<Image>,
 }
 impl FromWorld for MenuAssets {
     fn from_world(world: &mut World) -> Self {
@@ -47,85 +99,176 @@ impl FromWorld for MenuAssets {
         Self {
             team_name: assets.load("team_name.png"),
             title_image: assets.load("title_image.png"),
-            slider_bg: assets.load("slider_bg.png"),
-            slider_handle: assets.load("slider_handle.png"),
         }
     }
 }
 
-fn update_sliders(
-    mut styles: Query<(Entity, &mut Style, &mut MainMenuElem), With<MovingSlider>>,
-    mut mouse_motion: EventReader<MouseMotion>,
-    mut cmds: Commands,
-    mut audio_requests: EventWriter<AudioRequest>,
-    mut nav_requests: EventWriter<NavRequest>,
-    focused: Query<Entity, With<Focused>>,
-    elems: Query<&MainMenuElem, Without<MovingSlider>>,
-    mut mouse_buttons: ResMut<Input<MouseButton>>,
-) {
-    use MainMenuElem::AudioSlider;
-    if let Ok((entity, mut style, mut elem)) = styles.get_single_mut() {
-        if let (Val::Percent(left), AudioSlider(channel, strength)) =
-            (style.position.left, elem.as_mut())
-        {
-            let horizontal_delta: f64 = mouse_motion.iter().map(|m| m.delta.x as f64).sum();
-            let new_left = (left as f64 / 0.9 + horizontal_delta * 0.40)
-                .min(100.0)
-                .max(0.0);
-            *strength = new_left;
-            audio_requests.send(AudioRequest::SetVolume(*channel, new_left / 100.0));
-            style.position.left = Val::Percent(new_left as f32 * 0.9)
+/// Check the game can start before leaving the menu, telling the player
+/// what's wrong otherwise.
+#[derive(SystemParam)]
+pub(super) struct StartCheck<'w, 's> {
+    deck_assets: Res<'w, DeckAssets>,
+    opponent: Res<'w, OpponentProfile>,
+    scene_check: Res<'w, SceneCheck>,
+    decks: Res<'w, Assets<Deck>>,
+    server: Res<'w, AssetServer>,
+    strings: Res<'w, Strings>,
+    banner: Query<'w, 's, &'static mut Text, With<DeckErrorBanner>>,
+}
+impl<'w, 's> StartCheck<'w, 's> {
+    pub(super) fn can_start(&mut self) -> bool {
+        let check = self
+            .deck_assets
+            .check(&self.opponent, &self.server, &self.decks);
+        let message = match (&*self.scene_check, check) {
+            (SceneCheck::Broken(report), _) => scene_error_text(&self.strings, report),
+            (_, Ok(())) => return true,
+            (_, Err(err)) => {
+                debug_print!(sec: 5.0, col: Color::RED, "Can't start: {err}");
+                deck_error_text(&self.strings, &err)
+            }
         };
-        if mouse_buttons.just_released(MouseButton::Left) {
-            mouse_buttons.clear_just_released(MouseButton::Left);
-            nav_requests.send(NavRequest::Unlock);
-            audio_requests.send(AudioRequest::StopSfxLoop);
-            cmds.entity(entity).remove::<MovingSlider>();
+        for mut text in self.banner.iter_mut() {
+            text.sections[0].value = message.clone();
         }
+        false
     }
-    if let Ok(entity) = focused.get_single() {
-        let is_volume_slider = matches!(elems.get(entity), Ok(AudioSlider(..)));
-        if mouse_buttons.just_pressed(MouseButton::Left) && is_volume_slider {
-            nav_requests.send(NavRequest::Action);
-            audio_requests.send(AudioRequest::PlayWoodClink(SfxParam::StartLoop));
-            cmds.entity(entity).insert(MovingSlider);
+}
+
+fn scene_error_text(strings: &Strings, report: &AnchorReport) -> String {
+    let nodes = report.node_names();
+    let error = strings.format("scene_error.anchors", &[("nodes", nodes.as_str())]);
+    strings.format("menu.cant_start", &[("error", error.as_str())])
+}
+
+fn deck_error_text(strings: &Strings, err: &DeckError) -> String {
+    let error = match err {
+        DeckError::Loading(file) => {
+            strings.format("deck_error.loading", &[("file", file.as_str())])
         }
-    }
+        DeckError::Invalid(file) => {
+            strings.format("deck_error.invalid", &[("file", file.as_str())])
+        }
+        DeckError::Malformed {
+            file,
+            error: ParseError::At { line, column, token, .. },
+        } => {
+            let (line, column) = (line.to_string(), column.to_string());
+            let args = [
+                ("file", file.as_str()),
+                ("line", line.as_str()),
+                ("column", column.as_str()),
+                ("token", token.as_str()),
+            ];
+            strings.format("deck_error.malformed", &args)
+        }
+        DeckError::Malformed { file, .. } => {
+            strings.format("deck_error.invalid", &[("file", file.as_str())])
+        }
+        DeckError::Empty(file) => strings.format("deck_error.empty", &[("file", file.as_str())]),
+        DeckError::Mismatched { player, oppo } => {
+            let (player, oppo) = (player.to_string(), oppo.to_string());
+            let args = [("player", player.as_str()), ("oppo", oppo.as_str())];
+            strings.format("deck_error.mismatched", &args)
+        }
+    };
+    strings.format("menu.cant_start", &[("error", error.as_str())])
 }
 
 fn update_menu(
     mut events: EventReader<NavEvent>,
     mut exit: EventWriter<AppExit>,
-    mut cmds: Commands,
     mut audio_requests: EventWriter<AudioRequest>,
     mut windows: ResMut<Windows>,
     mut credit_overlay: Query<&mut Style, With<CreditOverlay>>,
-    mut rules_overlay: Query<&mut Style, (Without<CreditOverlay>, With<RulesOverlay>)>,
     mut game_state: ResMut<State<GameState>>,
+    mut theme: ResMut<Theme>,
+    mut opponent: ResMut<SelectedOpponent>,
+    mut game_rules: ResMut<GameRules>,
+    mut language: ResMut<Language>,
+    mut gameplay: ResMut<GameplaySettings>,
+    mut start_check: StartCheck,
     elems: Query<&MainMenuElem>,
+    stats: Res<Stats>,
 ) {
-    use NavEvent::{FocusChanged, Locked, NoChanges};
+    use NavEvent::{Locked, NoChanges};
     use NavRequest::Action;
     let window_msg = "There is at least one game window open";
     for (event_type, from) in events.nav_iter().types() {
         match (event_type, elems.get(from)) {
-            (FocusChanged { .. }, Ok(MainMenuElem::AudioSlider(..))) => {
-                cmds.entity(from).remove::<MovingSlider>();
-            }
             (Locked(..), Ok(MainMenuElem::Credits)) => {
                 let mut style = credit_overlay.single_mut();
                 style.display = Display::Flex;
             }
-            (Locked(..), Ok(MainMenuElem::Rules)) => {
-                let mut style = rules_overlay.single_mut();
-                style.display = Display::Flex;
-            }
             (NoChanges { request: Action, .. }, Ok(MainMenuElem::Exit)) => exit.send(AppExit),
             (NoChanges { request: Action, .. }, Ok(MainMenuElem::Start)) => {
-                screen_print!("Player pressed the start button");
+                debug_print!("Player pressed the start button");
+                if !start_check.can_start() {
+                    continue;
+                }
                 audio_requests.send(AudioRequest::PlayWoodClink(SfxParam::PlayOnce));
                 game_state.set(GameState::WaitLoaded).unwrap();
             }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::Practice)) => {
+                game_state.set(GameState::Sandbox).unwrap();
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::KeyBindings)) => {
+                game_state.set(GameState::KeyBindingsMenu).unwrap();
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::History)) => {
+                game_state.set(GameState::HistoryMenu).unwrap();
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::Theme)) => {
+                *theme = theme.next();
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::Opponent)) => {
+                opponent.0 = opponent.0.wrapping_add(1);
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::FairGame)) => {
+                game_rules.fair_game = !game_rules.fair_game;
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::Initiative)) => {
+                game_rules.initiative = game_rules.initiative.next();
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::Objective)) => {
+                game_rules.win_condition = game_rules.win_condition.next();
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::Coop)) => {
+                game_rules.coop = !game_rules.coop;
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::DeckReveal)) => {
+                gameplay.deck_reveal = !gameplay.deck_reveal;
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::FastMode)) => {
+                gameplay.fast_mode = !gameplay.fast_mode;
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::ReduceMotion)) => {
+                gameplay.reduce_motion = !gameplay.reduce_motion;
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::LowSpec)) => {
+                gameplay.low_spec = !gameplay.low_spec;
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::HighContrastCards)) => {
+                gameplay.high_contrast_cards = !gameplay.high_contrast_cards;
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::HandSort)) => {
+                gameplay.hand_sort = gameplay.hand_sort.next();
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::AdaptiveDifficulty)) => {
+                gameplay.adaptive_difficulty = !gameplay.adaptive_difficulty;
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::DemonWhispers)) => {
+                gameplay.demon_whispers = !gameplay.demon_whispers;
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::Streaming)) => {
+                gameplay.streaming = !gameplay.streaming;
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::BlunderWarning)) => {
+                gameplay.blunder_warning = Some(!gameplay.warns_blunders(stats.games()));
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::Language)) => {
+                *language = language.next();
+            }
             (NoChanges { request: Action, .. }, Ok(MainMenuElem::LockMouse)) => {
                 let window = windows.get_primary_mut().expect(window_msg);
                 let prev_lock_mode = window.cursor_locked();
@@ -143,8 +286,9 @@ fn update_menu(
             }
             (NoChanges { request: Action, .. }, Ok(MainMenuElem::Set16_9)) => {
                 let window = windows.get_primary_mut().expect(window_msg);
-                if window.mode() == WindowMode::Windowed {
-                    let height = window.height();
+                let height = window.height();
+                // The window may be minimized and have no height
+                if window.mode() == WindowMode::Windowed && height >= 1.0 {
                     window.set_resolution(height * 16.0 / 9.0, height);
                 }
             }
@@ -157,9 +301,65 @@ fn update_menu(
     }
 }
 
+/// Cycle through the archetypes with the arrows around the archetype name.
+fn choose_archetype(
+    mut events: EventReader<NavEvent>,
+    mut selected: ResMut<SelectedArchetype>,
+    list: Res<ArchetypeList>,
+    elems: Query<&MainMenuElem>,
+) {
+    for elem in events.nav_iter().activated_in_query(&elems) {
+        let step = match elem {
+            MainMenuElem::PreviousArchetype => -1,
+            MainMenuElem::NextArchetype => 1,
+            _ => continue,
+        };
+        if let Some(next) = list.cycle(&selected, step) {
+            *selected = next;
+        }
+    }
+}
+
+/// Show the selected archetype, its deck may break or get fixed at any time
+/// with hot reloading.
 #[allow(clippy::type_complexity)]
+fn update_archetype_texts(
+    strings: Res<Strings>,
+    selected: Res<SelectedArchetype>,
+    list: Res<ArchetypeList>,
+    server: Res<AssetServer>,
+    decks: Res<Assets<Deck>>,
+    mut names: Query<&mut Text, (With<ArchetypeName>, Without<ArchetypeDescription>)>,
+    mut descriptions: Query<&mut Text, With<ArchetypeDescription>>,
+) {
+    let archetype = match list.selected(&selected) {
+        Some(archetype) => archetype,
+        None => return,
+    };
+    let name = strings.get(&archetype.name_key);
+    let name = strings.format("menu.archetype", &[("archetype", name)]);
+    let color = if archetype.is_broken(&server, &decks) {
+        BROKEN_ARCHETYPE_COLOR
+    } else {
+        Color::ANTIQUE_WHITE
+    };
+    let description = strings.get(&archetype.description_key);
+    for mut text in names.iter_mut() {
+        let section = &text.sections[0];
+        if section.value != name || section.style.color != color {
+            text.sections[0].value = name.clone();
+            text.sections[0].style.color = color;
+        }
+    }
+    for mut text in descriptions.iter_mut() {
+        if text.sections[0].value != description {
+            text.sections[0].value = description.to_owned();
+        }
+    }
+}
+
 fn leave_overlay(
-    mut overlay: Query<&mut Style, Or<(With<CreditOverlay>, With<RulesOverlay>)>>,
+    mut overlay: Query<&mut Style, With<CreditOverlay>>,
     mut nav_requests: EventWriter<NavRequest>,
     gamepad: Res<Input<GamepadButton>>,
     mouse: Res<Input<MouseButton>>,
@@ -178,14 +378,182 @@ fn leave_overlay(
     }
 }
 
+fn set_display(style: &mut Style, display: Display) {
+    if style.display != display {
+        style.display = display;
+    }
+}
+
+/// Show the rules in place of the menu entries when activating them, hide
+/// them when going back or when the focus leaves the rules.
+#[allow(clippy::type_complexity)]
+fn toggle_rules(
+    mut events: EventReader<NavEvent>,
+    mut nav_requests: EventWriter<NavRequest>,
+    mut overlay: Query<&mut Style, With<RulesOverlay>>,
+    mut columns: Query<&mut Style, (With<MenuColumns>, Without<RulesOverlay>)>,
+    elems: Query<&MainMenuElem>,
+    rules_entry: Query<(Entity, &MainMenuElem)>,
+    arrows: Query<Entity, With<GalleryArrow>>,
+) {
+    use NavEvent::{FocusChanged, NoChanges};
+    use NavRequest::{Action, Cancel};
+    let in_rules = |entity| {
+        let rules_elem = matches!(
+            elems.get(entity),
+            Ok(MainMenuElem::Practice | MainMenuElem::CloseRules)
+        );
+        arrows.contains(entity) || rules_elem
+    };
+    let mut shown = None;
+    for (event_type, from) in events.nav_iter().types() {
+        match (event_type, elems.get(from)) {
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::Rules)) => {
+                if let Some(arrow) = arrows.iter().next() {
+                    nav_requests.send(NavRequest::FocusOn(arrow));
+                }
+                shown = Some(true);
+            }
+            (NoChanges { request: Action, .. }, Ok(MainMenuElem::CloseRules))
+            | (NoChanges { request: Cancel, .. }, _)
+                if in_rules(from) =>
+            {
+                let rules = rules_entry.iter().find(|(_, e)| **e == MainMenuElem::Rules);
+                if let Some((entry, _)) = rules {
+                    nav_requests.send(NavRequest::FocusOn(entry));
+                }
+                shown = Some(false);
+            }
+            (FocusChanged { .. }, _) if !in_rules(from) => shown = Some(false),
+            _ => {}
+        }
+    }
+    let (rules, menu) = match shown {
+        Some(true) => (Display::Flex, Display::None),
+        Some(false) => (Display::None, Display::Flex),
+        None => return,
+    };
+    overlay.for_each_mut(|mut style| set_display(&mut style, rules));
+    columns.for_each_mut(|mut style| set_display(&mut style, menu));
+}
+
+/// Label of the settings toggles, `None` for other elements.
+fn setting_text(
+    elem: &MainMenuElem,
+    strings: &Strings,
+    theme: Theme,
+    language: Language,
+    gameplay: &GameplaySettings,
+    opponent: &OpponentProfile,
+    rules: &GameRules,
+    stats: &Stats,
+) -> Option<String> {
+    Some(match elem {
+        MainMenuElem::Opponent => {
+            let name = strings.get(&opponent.name_key);
+            strings.format("menu.opponent", &[("opponent", name)])
+        }
+        MainMenuElem::FairGame => on_off_text(strings, "menu.fair_game", rules.fair_game),
+        MainMenuElem::Initiative => {
+            let rule = strings.get(rules.initiative.label_key());
+            strings.format("menu.initiative", &[("rule", rule)])
+        }
+        MainMenuElem::Objective => {
+            let objective = rules.win_condition.label(strings);
+            strings.format("menu.objective", &[("objective", objective.as_str())])
+        }
+        MainMenuElem::Coop => on_off_text(strings, "menu.coop", rules.coop),
+        MainMenuElem::AdaptiveDifficulty => {
+            let adaptive = gameplay.adaptive_difficulty;
+            on_off_text(strings, "menu.adaptive_difficulty", adaptive)
+        }
+        MainMenuElem::DemonWhispers => {
+            on_off_text(strings, "menu.demon_whispers", gameplay.demon_whispers)
+        }
+        MainMenuElem::Theme => {
+            let theme = strings.get(theme.label_key());
+            strings.format("menu.theme", &[("theme", theme)])
+        }
+        MainMenuElem::Language => strings.format("menu.language", &[("language", language.name())]),
+        MainMenuElem::DeckReveal => on_off_text(strings, "menu.deck_reveal", gameplay.deck_reveal),
+        MainMenuElem::FastMode => on_off_text(strings, "menu.fast_mode", gameplay.fast_mode),
+        MainMenuElem::ReduceMotion => {
+            on_off_text(strings, "menu.reduce_motion", gameplay.reduce_motion)
+        }
+        MainMenuElem::LowSpec => on_off_text(strings, "menu.low_spec", gameplay.low_spec),
+        MainMenuElem::HighContrastCards => {
+            let high_contrast = gameplay.high_contrast_cards;
+            on_off_text(strings, "menu.high_contrast_cards", high_contrast)
+        }
+        MainMenuElem::Streaming => on_off_text(strings, "menu.streaming", gameplay.streaming),
+        MainMenuElem::HandSort => {
+            let sort = strings.get(gameplay.hand_sort.label_key());
+            strings.format("menu.hand_sort", &[("sort", sort)])
+        }
+        MainMenuElem::BlunderWarning => {
+            let warns = gameplay.warns_blunders(stats.games());
+            on_off_text(strings, "menu.blunder_warning", warns)
+        }
+        _ => return None,
+    })
+}
+
+fn update_setting_texts(
+    strings: Res<Strings>,
+    theme: Res<Theme>,
+    language: Res<Language>,
+    gameplay: Res<GameplaySettings>,
+    opponent: Res<OpponentProfile>,
+    rules: Res<GameRules>,
+    stats: Res<Stats>,
+    mut texts: Query<(&mut Text, &MainMenuElem)>,
+) {
+    let changed = strings.is_changed() || theme.is_changed() || language.is_changed();
+    let game_changed = gameplay.is_changed() || opponent.is_changed() || rules.is_changed();
+    if !changed && !game_changed {
+        return;
+    }
+    for (mut text, elem) in texts.iter_mut() {
+        let label = setting_text(
+            elem, &strings, *theme, *language, &gameplay, &opponent, &rules, &stats,
+        );
+        if let Some(label) = label {
+            text.sections[0].value = label;
+        }
+    }
+}
+
 /// Spawns the UI tree
-fn setup_main_menu(mut cmds: Commands, menu_assets: Res<MenuAssets>, ui_assets: Res<UiAssets>) {
+fn setup_main_menu(
+    mut cmds: Commands,
+    menu_assets: Res<MenuAssets>,
+    ui_assets: Res<UiAssets>,
+    theme: Res<Theme>,
+    language: Res<Language>,
+    gameplay: Res<GameplaySettings>,
+    strings: Res<Strings>,
+    deck_assets: Res<DeckAssets>,
+    decks: Res<Assets<Deck>>,
+    server: Res<AssetServer>,
+    opponent: Res<OpponentProfile>,
+    rules: Res<GameRules>,
+    stats: Res<Stats>,
+    scene_check: Res<SceneCheck>,
+    sliders: SliderParams,
+    mut settings_reset: ResMut<SettingsReset>,
+) {
     use FlexDirection as FD;
     use MainMenuElem::*;
     use PositionType as PT;
 
-    let text_bundle = |content: &str, font_size: f32| ui_assets.text_bundle(content, font_size);
-    let large_text = |content| ui_assets.large_text(content);
+    let text_bundle = |key, font_size: f32| ui_assets.text_bundle(strings.get(key), font_size);
+    let large_text = |key| ui_assets.large_text(strings.get(key));
+    let setting = |elem| {
+        let text = setting_text(
+            &elem, &strings, *theme, *language, &gameplay, &opponent, &rules, &stats,
+        );
+        ui_assets.large_text(&text.unwrap_or_default())
+    };
     let focusable = Focusable::default();
     let image =
         |image: &Handle<Image>| ImageBundle { image: image.clone().into(), ..Default::default() };
@@ -198,44 +566,33 @@ fn setup_main_menu(mut cmds: Commands, menu_assets: Res<MenuAssets>, ui_assets:
         },
         ..Default::default()
     };
-    let mut slider = |name: &str, channel: AudioChannel, strength: f64| {
-        let volume_name = name.to_string() + " volume";
-        let handle_name = Name::new(name.to_string() + " volume slider handle");
-        let slider_name = Name::new(name.to_string() + " volume slider");
-        let position = UiRect {
-            bottom: Val::Px(-10.0),
-            left: Val::Percent(strength as f32 * 0.9),
-            ..Default::default()
-        };
-        build_ui! {
-            #[cmd(cmds)]
-            node { flex_direction: FD::Row }[; slider_name](
-                node[text_bundle(&volume_name, 30.0); style! { margin: rect!(10 px), }],
-                node(
-                    entity[
-                        image(&menu_assets.slider_bg);
-                        style! { size: size!( 200 px, 20 px), }
-                    ],
-                    entity[
-                        image(&menu_assets.slider_handle);
-                        Focusable::lock(),
-                        MainMenuElem::AudioSlider(channel, strength),
-                        handle_name,
-                        style! {
-                            size: size!( 40 px, 40 px),
-                            position_type: PT::Absolute,
-                            position: position,
-                        }
-                    ]
-                )
-            )
-        }
-        .id()
+    let mut slider = |name, key, channel| {
+        let builder = VolumeSliderBuilder::new(name, key, channel);
+        builder.spawn(&mut cmds, &sliders, &ui_assets, &strings, AudioSlider)
     };
-    let master_slider = slider("Master", AudioChannel::Master, 100.0);
-    let sfx_slider = slider("Sfx", AudioChannel::Sfx, 50.0);
-    let music_slider = slider("Music", AudioChannel::Music, 50.0);
+    let master_slider = slider("Master", "menu.volume.master", AudioChannel::Master);
+    let sfx_slider = slider("Sfx", "menu.volume.sfx", AudioChannel::Sfx);
+    let music_slider = slider("Music", "menu.volume.music", AudioChannel::Music);
     let cursor = MenuCursor::spawn_ui_element(&mut cmds);
+    let rules = ui_assets.spawn_rules(&mut cmds, &strings, None);
+    let gallery = spawn_word_gallery(&mut cmds, &ui_assets, &strings);
+    // Decks still loading are not worth a warning, they will likely be ready
+    // once the player presses start.
+    let deck_error = match (&*scene_check, deck_assets.check(&opponent, &server, &decks)) {
+        (SceneCheck::Broken(report), _) => scene_error_text(&strings, report),
+        (_, Err(err)) if !err.is_loading() => deck_error_text(&strings, &err),
+        _ => String::new(),
+    };
+    let mut banner = ui_assets.text_bundle(&deck_error, 30.0);
+    banner.text.sections[0].style.color = DECK_ERROR_COLOR;
+    let reset_notice = if settings_reset.0 {
+        settings_reset.0 = false;
+        strings.get("menu.settings_reset")
+    } else {
+        ""
+    };
+    let mut reset_notice = ui_assets.text_bundle(reset_notice, 30.0);
+    reset_notice.text.sections[0].style.color = DECK_ERROR_COLOR;
 
     build_ui! {
         #[cmd(cmds)]
@@ -251,47 +608,69 @@ fn setup_main_menu(mut cmds: Commands, menu_assets: Res<MenuAssets>, ui_assets:
                 Name::new("Title card"),
                 style! { size: size!(auto, 45 pct), }
             ],
-            node{ flex_direction: FD::Row }[; Name::new("Menu columns")](
+            entity[banner; Name::new("Deck error banner"), DeckErrorBanner],
+            entity[reset_notice; Name::new("Settings reset notice")],
+            node{ flex_direction: FD::Row }[; Name::new("Menu columns"), MenuColumns](
                 node[; Name::new("Menu node")](
-                    node[large_text("Start"); Focusable::new().prioritized(), Name::new("Start"), Start],
-                    node[large_text("Credits"); Focusable::lock(), Name::new("Credits"), Credits],
-                    node[large_text("How to play"); Focusable::lock(), Name::new("Rules"), Rules],
+                    node[large_text("menu.start"); Focusable::new().prioritized(), Localized("menu.start"), Name::new("Start"), Start],
+                    node[setting(Opponent); focusable, Name::new("Opponent"), Opponent],
+                    node{ flex_direction: FD::Row }[; Name::new("Archetype chooser")](
+                        node[ui_assets.large_text("<"); focusable, Name::new("Previous archetype"), PreviousArchetype],
+                        node[; Name::new("Archetype")](
+                            entity[ui_assets.large_text(""); Name::new("Archetype name"), ArchetypeName],
+                            entity[ui_assets.text_bundle("", 30.0); Name::new("Archetype description"), ArchetypeDescription],
+                        ),
+                        node[ui_assets.large_text(">"); focusable, Name::new("Next archetype"), NextArchetype],
+                    ),
+                    node[setting(FairGame); focusable, Name::new("Fair game"), FairGame],
+                    node[setting(Initiative); focusable, Name::new("Initiative"), Initiative],
+                    node[setting(Objective); focusable, Name::new("Objective"), Objective],
+                    node[setting(Coop); focusable, Name::new("Co-op"), Coop],
+                    node[setting(AdaptiveDifficulty); focusable, Name::new("Adaptive difficulty"), AdaptiveDifficulty],
+                    node[large_text("menu.credits"); Focusable::lock(), Localized("menu.credits"), Name::new("Credits"), Credits],
+                    node[large_text("menu.rules"); focusable, Localized("menu.rules"), Name::new("Rules"), Rules],
+                    node[large_text("menu.key_bindings"); focusable, Localized("menu.key_bindings"), Name::new("Key bindings"), KeyBindings],
+                    node[large_text("menu.history"); focusable, Localized("menu.history"), Name::new("History"), History],
                     if (!cfg!(target_arch = "wasm32")) {
-                        node[large_text("Exit"); focusable, Name::new("Exit"), Exit]
+                        node[large_text("menu.exit"); focusable, Localized("menu.exit"), Name::new("Exit"), Exit]
                     },
                 ),
                 node{ align_items: AlignItems::FlexEnd, margin: rect!(50 px) }[; Name::new("Audio settings")](
                     id(master_slider),
                     id(music_slider),
                     id(sfx_slider),
+                    node[setting(DemonWhispers); focusable, Name::new("Demon whispers"), DemonWhispers],
                 ),
                 node[; Name::new("Graphics column")](
                     if (!cfg!(target_arch = "wasm32")) {
-                        node[large_text("Lock mouse cursor"); focusable, LockMouse],
-                        node[large_text("Fit window to 16:9"); focusable, Set16_9],
+                        node[large_text("menu.lock_mouse"); focusable, Localized("menu.lock_mouse"), LockMouse],
+                        node[large_text("menu.fit_16_9"); focusable, Localized("menu.fit_16_9"), Set16_9],
                     },
-                    node[large_text("Toggle Full screen"); focusable, ToggleFullScreen],
+                    node[large_text("menu.full_screen"); focusable, Localized("menu.full_screen"), ToggleFullScreen],
+                    node[setting(MainMenuElem::Theme); focusable, MainMenuElem::Theme],
+                    node[setting(MainMenuElem::Language); focusable, MainMenuElem::Language],
+                    node[setting(DeckReveal); focusable, DeckReveal],
+                    node[setting(FastMode); focusable, FastMode],
+                    node[setting(ReduceMotion); focusable, ReduceMotion],
+                    node[setting(LowSpec); focusable, LowSpec],
+                    node[setting(HighContrastCards); focusable, HighContrastCards],
+                    node[setting(BlunderWarning); focusable, BlunderWarning],
+                    node[setting(Streaming); focusable, Streaming],
+                    node[setting(MainMenuElem::HandSort); focusable, MainMenuElem::HandSort],
                 )
             ),
             node{
                 position_type: PT::Absolute,
                 position: rect!(10 pct),
                 display: Display::None,
+                flex_direction: FlexDirection::ColumnReverse,
                 align_items: AlignItems::Center,
                 justify_content: JustifyContent::Center
             }[; UiColor(Color::rgb(0.1, 0.1, 0.1)), Name::new("Rules overlay"), RulesOverlay](
-                node[large_text("Turns");],
-                node[text_bundle("The game is like War, but in turns, each player plays the", 30.0);],
-                node[text_bundle("first card. The one with the most point at the end wins.", 30.0);],
-                node[large_text("Effects");],
-                node[text_bundle("Cards may have special effects, hover over it to see", 30.0);],
-                node[text_bundle("what they do.", 30.0);],
-                node[large_text("Cheating");],
-                node[text_bundle("Drag a card toward your sleeve to store it.", 30.0);],
-                node[text_bundle("Cards stored in your sleeve return to your", 30.0);],
-                node[text_bundle("hand next time players draw cards, this replaces", 30.0);],
-                node[text_bundle("the card you would have otherwise drawn", 30.0);],
-                node[text_bundle("from the deck.", 30.0);],
+                id(rules),
+                id(gallery),
+                node[large_text("sandbox.open"); focusable, Localized("sandbox.open"), Name::new("Practice"), Practice],
+                node[large_text("menu.back"); focusable, Localized("menu.back"), Name::new("Close rules"), CloseRules],
             ),
             node{
                 position_type: PT::Absolute,
@@ -305,13 +684,13 @@ fn setup_main_menu(mut cmds: Commands, menu_assets: Res<MenuAssets>, ui_assets:
                     Name::new("Team name"),
                     style! { size: size!(auto, 30 pct), }
                 ],
-                node[large_text("music, sfx: Samuel_sound");],
-                node[large_text("graphics: Xolotl");],
-                node[large_text("code, voices, design: Gibonus");],
-                node[large_text("more code: vasukas");],
-                node[large_text("thanks: BLucky (devops), Lorithan (game idea)");],
-                node[large_text("Also the BEVY community <3 <3 <3");],
-                node[text_bundle("(Click anywhere to exit)", 30.0);]
+                node[large_text("credits.music"); Localized("credits.music")],
+                node[large_text("credits.graphics"); Localized("credits.graphics")],
+                node[large_text("credits.code"); Localized("credits.code")],
+                node[large_text("credits.more_code"); Localized("credits.more_code")],
+                node[large_text("credits.thanks"); Localized("credits.thanks")],
+                node[large_text("credits.community"); Localized("credits.community")],
+                node[text_bundle("credits.exit_hint", 30.0); Localized("credits.exit_hint")]
             )
         )
     };
@@ -326,13 +705,12 @@ impl BevyPlugin for Plugin {
             .add_system_set(self.0.on_exit(cleanup_marked::<MainMenuRoot>))
             .add_system_set(
                 SystemSet::on_update(self.0)
-                    .with_system(
-                        update_sliders
-                            .before(NavRequestSystem)
-                            .before(AudioRequestSystem),
-                    )
                     .with_system(leave_overlay.before(NavRequestSystem))
-                    .with_system(update_menu.after(NavRequestSystem)),
+                    .with_system(update_menu.after(NavRequestSystem))
+                    .with_system(toggle_rules.after(NavRequestSystem))
+                    .with_system(choose_archetype.after(NavRequestSystem))
+                    .with_system(update_archetype_texts.after(choose_archetype))
+                    .with_system(update_setting_texts.after(update_menu)),
             );
     }
 }
@@ -1,9 +1,18 @@
-//! Menu and gameover screen ui.
+//! Menu, pause and gameover screen ui.
+mod achievement_toast;
+mod blunder_dialog;
 mod common;
+mod help_overlay;
+mod history_menu;
+mod key_bindings_menu;
 mod main_menu;
+mod pause_menu;
+mod recovery_dialog;
 mod restart_menu;
+mod slider;
+mod word_gallery;
 
-pub use common::UiAssets as Assets;
+pub use common::{MenuCursor, UiAssets as Assets};
 
 use bevy::prelude::{Plugin as BevyPlugin, *};
 
@@ -12,21 +21,21 @@ use crate::GameState;
 #[cfg(feature = "debug")]
 fn debug_buttons(
     mut ctx: ResMut<bevy_inspector_egui::bevy_egui::EguiContext>,
-    mut events: EventWriter<crate::GameOver>,
+    mut events: EventWriter<crate::game_event::GameEvent>,
 ) {
-    use crate::{EndReason, GameOver};
+    use crate::{game_event::GameEvent::GameEnded, EndReason};
     use bevy_inspector_egui::egui::*;
     Area::new("gameover::debug_buttons")
         .anchor(Align2::RIGHT_BOTTOM, vec2(0., 0.))
         .show(ctx.ctx_mut(), |ui| {
             if ui.button("PlayerWon").clicked() {
-                events.send(GameOver(EndReason::Victory));
+                events.send(GameEnded(EndReason::Victory));
             }
             if ui.button("PlayerLost").clicked() {
-                events.send(GameOver(EndReason::Loss));
+                events.send(GameEnded(EndReason::Loss));
             }
             if ui.button("CheatSpotted").clicked() {
-                events.send(GameOver(EndReason::CaughtCheating));
+                events.send(GameEnded(EndReason::CaughtCheating));
             }
         });
 }
@@ -38,7 +47,17 @@ impl BevyPlugin for Plugin {
         app.add_system(debug_buttons);
 
         app.add_plugin(common::Plugin)
+            .add_plugin(achievement_toast::Plugin)
             .add_plugin(main_menu::Plugin(GameState::MainMenu))
-            .add_plugin(restart_menu::Plugin);
+            .add_plugin(key_bindings_menu::Plugin(GameState::KeyBindingsMenu))
+            .add_plugin(history_menu::Plugin(GameState::HistoryMenu))
+            .add_plugin(help_overlay::Plugin(GameState::Playing))
+            .add_plugin(blunder_dialog::Plugin(GameState::Playing))
+            .add_plugin(recovery_dialog::Plugin(GameState::MainMenu))
+            .add_plugin(pause_menu::Plugin(GameState::PauseMenu))
+            .add_plugin(restart_menu::Plugin)
+            .add_plugin(slider::Plugin)
+            .add_plugin(word_gallery::Plugin(GameState::MainMenu))
+            .add_plugin(word_gallery::Plugin(GameState::Playing));
     }
 }
@@ -0,0 +1,277 @@
+//! Pause menu, pushed on top of [`GameState::Playing`] with the pause key.
+//!
+//! While paused, [`TurnState::Paused`] is pushed on top of the turn state, so
+//! that no gameplay system runs until the game resumes.
+//!
+//! Exiting to desktop from the pause menu asks for confirmation first, and so
+//! does closing the window during a game, on native builds: it opens the
+//! pause menu with the same [`ConfirmDialog`]. Browsers don't reliably report
+//! the page closing, so wasm builds close right away.
+use bevy::app::AppExit;
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use bevy_ui_build_macros::{build_ui, rect, size, style, unit};
+use bevy_ui_navigation::prelude::*;
+
+use super::common::{on_off_text, ConfirmDialog, MenuCursor, UiAssets};
+use super::slider::{SliderParams, VolumeSliderBuilder};
+use crate::{
+    audio::AudioChannel,
+    cleanup_marked,
+    game_event::GameEvent,
+    lang::Strings,
+    settings::{Action, GameplaySettings, KeyBindings},
+    state::{GameState, TurnState},
+    EndReason,
+};
+
+#[derive(Clone, Component)]
+struct PauseMenuRoot;
+
+/// The player asked to exit to desktop, the pause menu asks them to confirm.
+#[derive(Default)]
+struct ExitRequested(bool);
+
+#[derive(Component, Clone, PartialEq)]
+enum PauseElem {
+    Resume,
+    FastMode,
+    Streaming,
+    AudioSlider,
+    Concede,
+    ConfirmConcede,
+    CancelConcede,
+    ExitDesktop,
+    ConfirmExit,
+    CancelExit,
+}
+
+/// Text of the settings toggled from the pause menu, `None` for other elems.
+fn setting_text(
+    strings: &Strings,
+    elem: &PauseElem,
+    gameplay: &GameplaySettings,
+) -> Option<String> {
+    match elem {
+        PauseElem::FastMode => Some(on_off_text(strings, "menu.fast_mode", gameplay.fast_mode)),
+        PauseElem::Streaming => Some(on_off_text(strings, "menu.streaming", gameplay.streaming)),
+        _ => None,
+    }
+}
+
+fn toggle_pause_menu(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+    bindings: Res<KeyBindings>,
+) {
+    let pause_key = bindings.key(Action::Pause);
+    if !keys.just_pressed(pause_key) {
+        return;
+    }
+    let toggled = match state.current() {
+        GameState::Playing => state.push(GameState::PauseMenu),
+        GameState::PauseMenu => state.pop(),
+        _ => return,
+    };
+    if let Err(err) = toggled {
+        debug_print!(sec: 3.0, col: Color::RED, "Could not toggle pause: {err}");
+    }
+    keys.reset(pause_key);
+}
+
+fn freeze_turn(mut turn: ResMut<State<TurnState>>) {
+    if let Err(err) = turn.push(TurnState::Paused) {
+        debug_print!(sec: 3.0, col: Color::RED, "Could not freeze turn: {err}");
+    }
+}
+
+fn unfreeze_turn(mut turn: ResMut<State<TurnState>>) {
+    if *turn.current() == TurnState::Paused {
+        if let Err(err) = turn.pop() {
+            debug_print!(sec: 3.0, col: Color::RED, "Could not unfreeze turn: {err}");
+        }
+    }
+}
+
+fn setup_pause_menu(
+    mut cmds: Commands,
+    ui_assets: Res<UiAssets>,
+    strings: Res<Strings>,
+    gameplay: Res<GameplaySettings>,
+    sliders: SliderParams,
+) {
+    use PauseElem::{AudioSlider, Concede, ExitDesktop, FastMode, Resume, Streaming};
+
+    let node = NodeBundle {
+        color: Color::NONE.into(),
+        style: style! {
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+        },
+        ..default()
+    };
+    let cursor = MenuCursor::spawn_ui_element(&mut cmds);
+    let mut slider = |name, key, channel| {
+        let builder = VolumeSliderBuilder::new(name, key, channel);
+        builder.spawn(&mut cmds, &sliders, &ui_assets, &strings, AudioSlider)
+    };
+    let music_slider = slider("Music", "menu.volume.music", AudioChannel::Music);
+    let sfx_slider = slider("Sfx", "menu.volume.sfx", AudioChannel::Sfx);
+    let large_text = |key| ui_assets.large_text(strings.get(key));
+    let setting = |elem| {
+        let text = setting_text(&strings, &elem, &gameplay).unwrap_or_default();
+        ui_assets.large_text(&text)
+    };
+    build_ui! {
+        #[cmd(cmds)]
+        node{ size: size!(100 pct, 100 pct) }[; Name::new("Pause menu root"), PauseMenuRoot](
+            id(cursor),
+            node[;
+                UiColor(Color::rgba(0., 0., 0., 0.7)),
+                Name::new("Shadow"),
+                style! { position_type: PositionType::Absolute, size: size!(100 pct, 100 pct), }
+            ],
+            node[; Name::new("Menu column")](
+                entity[
+                    large_text("pause.title");
+                    style! { margin: rect!(0 px, 0 px, 0 px, 60 px,), }
+                ],
+                entity[large_text("pause.resume"); Focusable::new().prioritized(), Resume],
+                entity[setting(FastMode); Focusable::default(), FastMode],
+                entity[setting(Streaming); Focusable::default(), Streaming],
+                node{ align_items: AlignItems::FlexEnd }[; Name::new("Audio settings")](
+                    id(music_slider),
+                    id(sfx_slider),
+                ),
+                entity[large_text("pause.concede"); Focusable::default(), Concede],
+                entity[large_text("pause.exit_desktop"); Focusable::default(), ExitDesktop],
+            )
+        )
+    };
+}
+
+fn update_pause_menu(
+    mut cmds: Commands,
+    mut nav_events: EventReader<NavEvent>,
+    mut state: ResMut<State<GameState>>,
+    mut game_events: EventWriter<GameEvent>,
+    mut app_exit: EventWriter<AppExit>,
+    mut exit_requested: ResMut<ExitRequested>,
+    mut gameplay: ResMut<GameplaySettings>,
+    mut texts: Query<(&mut Text, &PauseElem)>,
+    ui_assets: Res<UiAssets>,
+    strings: Res<Strings>,
+    elems: Query<&PauseElem>,
+    dialogs: Query<Entity, With<ConfirmDialog>>,
+) {
+    use PauseElem::*;
+    match nav_events.nav_iter().activated_in_query(&elems).next() {
+        Some(Resume) => {
+            if let Err(err) = state.pop() {
+                debug_print!(sec: 3.0, col: Color::RED, "Could not resume: {err}");
+            }
+        }
+        Some(FastMode) => gameplay.fast_mode = !gameplay.fast_mode,
+        Some(Streaming) => gameplay.streaming = !gameplay.streaming,
+        Some(Concede) if dialogs.is_empty() => {
+            let question = strings.get("pause.concede_question");
+            let (yes, no) = (ConfirmConcede, CancelConcede);
+            ui_assets.spawn_confirm_dialog(&mut cmds, &strings, question, yes, no);
+        }
+        // The restart menu takes care of popping the pause menu
+        Some(ConfirmConcede) => game_events.send(GameEvent::GameEnded(EndReason::Conceded)),
+        Some(CancelConcede | CancelExit) => {
+            exit_requested.0 = false;
+            for dialog in dialogs.iter() {
+                cmds.entity(dialog).despawn_recursive();
+            }
+        }
+        Some(ExitDesktop) => exit_requested.0 = true,
+        Some(ConfirmExit) => app_exit.send(AppExit),
+        Some(Concede | AudioSlider) | None => {}
+    }
+    if gameplay.is_changed() {
+        for (mut text, elem) in texts.iter_mut() {
+            if let Some(value) = setting_text(&strings, elem, &gameplay) {
+                text.sections[0].value = value;
+            }
+        }
+    }
+}
+
+/// Ask the player to confirm exiting to desktop, replacing any other dialog.
+fn ask_exit(
+    mut cmds: Commands,
+    exit_requested: Res<ExitRequested>,
+    ui_assets: Res<UiAssets>,
+    strings: Res<Strings>,
+    dialogs: Query<Entity, With<ConfirmDialog>>,
+    menus: Query<(), With<PauseMenuRoot>>,
+    elems: Query<&PauseElem>,
+) {
+    let asking = elems.iter().any(|elem| *elem == PauseElem::ConfirmExit);
+    if !exit_requested.0 || asking || menus.is_empty() {
+        return;
+    }
+    for dialog in dialogs.iter() {
+        cmds.entity(dialog).despawn_recursive();
+    }
+    let question = strings.get("pause.exit_question");
+    let (yes, no) = (PauseElem::ConfirmExit, PauseElem::CancelExit);
+    ui_assets.spawn_confirm_dialog(&mut cmds, &strings, question, yes, no);
+}
+
+fn forget_exit_request(mut exit_requested: ResMut<ExitRequested>) {
+    exit_requested.0 = false;
+}
+
+/// Confirm closing the window during a game in the pause menu, close it
+/// right away otherwise.
+///
+/// Bevy doesn't close the window itself, see `main`.
+#[cfg(not(target_arch = "wasm32"))]
+fn intercept_close(
+    mut close_requests: EventReader<bevy::window::WindowCloseRequested>,
+    mut state: ResMut<State<GameState>>,
+    mut exit_requested: ResMut<ExitRequested>,
+    mut app_exit: EventWriter<AppExit>,
+) {
+    if close_requests.iter().count() == 0 {
+        return;
+    }
+    match state.current() {
+        GameState::Playing => {
+            if let Err(err) = state.push(GameState::PauseMenu) {
+                debug_print!(sec: 3.0, col: Color::RED, "Could not pause: {err}");
+                return;
+            }
+            exit_requested.0 = true;
+        }
+        GameState::PauseMenu => exit_requested.0 = true,
+        _ => app_exit.send(AppExit),
+    }
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        #[cfg(not(target_arch = "wasm32"))]
+        app.add_system(intercept_close);
+        app.init_resource::<ExitRequested>()
+            .add_system(toggle_pause_menu)
+            .add_system_set(self.0.on_enter(setup_pause_menu).with_system(freeze_turn))
+            .add_system_set(
+                self.0
+                    .on_exit(cleanup_marked::<PauseMenuRoot>)
+                    .with_system(cleanup_marked::<ConfirmDialog>)
+                    .with_system(unfreeze_turn)
+                    .with_system(forget_exit_request),
+            )
+            .add_system_set(
+                self.0
+                    .on_update(update_pause_menu.after(NavRequestSystem))
+                    .with_system(ask_exit.after(update_pause_menu)),
+            );
+    }
+}
@@ -0,0 +1,82 @@
+//! Dialog offering to resume the game interrupted in the previous session,
+//! see [`crate::autosave`].
+//!
+//! Shown over the main menu until answered. Resuming starts a game with the
+//! rules and opponent of the interrupted one, its table replaces the new one
+//! before the first draw.
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use bevy_ui_navigation::prelude::*;
+
+use super::{common::UiAssets, main_menu::StartCheck};
+use crate::{
+    audio::{AudioRequest, SfxParam},
+    autosave::Autosaves,
+    cleanup_marked,
+    lang::Strings,
+    opponent::SelectedOpponent,
+    rules::GameRules,
+    state::GameState,
+};
+
+#[derive(Clone, Component)]
+struct RecoveryDialog;
+
+#[derive(Clone, Component)]
+enum RecoveryElem {
+    Resume,
+    Discard,
+}
+
+fn spawn_dialog(
+    mut cmds: Commands,
+    ui_assets: Res<UiAssets>,
+    strings: Res<Strings>,
+    autosaves: Res<Autosaves>,
+) {
+    if !autosaves.interrupted() {
+        return;
+    }
+    let question = strings.get("recovery.question");
+    let (yes, no) = (RecoveryElem::Resume, RecoveryElem::Discard);
+    let dialog = ui_assets.spawn_confirm_dialog(&mut cmds, &strings, question, yes, no);
+    cmds.entity(dialog).insert(RecoveryDialog);
+}
+
+fn answer_dialog(
+    mut cmds: Commands,
+    mut nav_events: EventReader<NavEvent>,
+    mut autosaves: ResMut<Autosaves>,
+    mut game_state: ResMut<State<GameState>>,
+    mut audio_requests: EventWriter<AudioRequest>,
+    mut start_check: StartCheck,
+    mut rules: ResMut<GameRules>,
+    mut opponent: ResMut<SelectedOpponent>,
+    elems: Query<&RecoveryElem>,
+    dialogs: Query<Entity, With<RecoveryDialog>>,
+) {
+    match nav_events.nav_iter().activated_in_query(&elems).next() {
+        // Like the start button, the deck error banner tells what's wrong,
+        // the game is offered again next time the menu opens
+        Some(RecoveryElem::Resume) if !start_check.can_start() => {}
+        Some(RecoveryElem::Resume) => {
+            autosaves.resume(&mut rules, &mut opponent);
+            audio_requests.send(AudioRequest::PlayWoodClink(SfxParam::PlayOnce));
+            game_state.set(GameState::WaitLoaded).unwrap();
+        }
+        Some(RecoveryElem::Discard) => autosaves.discard(),
+        None => return,
+    }
+    for dialog in dialogs.iter() {
+        cmds.entity(dialog).despawn_recursive();
+    }
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        use crate::system_helper::EasySystemSetCtor;
+        app.add_system_set(self.0.on_enter(spawn_dialog))
+            .add_system_set(self.0.on_update(answer_dialog.after(NavRequestSystem)))
+            .add_system_set(self.0.on_exit(cleanup_marked::<RecoveryDialog>));
+    }
+}
@@ -1,10 +1,26 @@
-use super::common::{MenuCursor, UiAssets};
+use super::common::{MenuCursor, RulesSection, UiAssets};
 use bevy::app::AppExit;
 use bevy::prelude::*;
 use bevy_ui_build_macros::{build_ui, rect, size, style, unit};
 use bevy_ui_navigation::prelude::*;
 
-use crate::{cleanup_marked, state::GameState, EndReason, GameOver};
+use crate::{
+    audio::AudioRequest,
+    cheat::LastCatch,
+    cleanup_marked,
+    game_event::GameEvent,
+    game_flow::CoopPlayer,
+    lang::Strings,
+    opponent::OpponentProfile,
+    pile::PileCard,
+    rules::GameRules,
+    screenshot::{SaveScreenshot, Screenshot},
+    settings::{Action, KeyBindings},
+    state::{GameState, TurnState},
+    stats::{DifficultyShift, LastShift, Stats},
+    titles::LastTitle,
+    CardOrigin, EndReason,
+};
 
 struct RestartAssets {
     defeat: Handle<Image>,
@@ -25,29 +41,81 @@ impl FromWorld for RestartAssets {
 enum Button {
     MainMenu,
     Restart,
+    Rules,
+    SaveScreenshot,
     ExitApp,
 }
 
 #[derive(Clone, Component)]
 struct RestartMenuRoot;
 
+#[derive(Clone, Component)]
+struct RulesPanel;
+
+/// Where the save screenshot button goes, once the screenshot is ready.
+#[derive(Clone, Component)]
+struct ScreenshotSlot;
+
+/// Freeze the table while the [`Screenshot`] of the final board is read
+/// back: the game is over, but the board stays up until the restart menu
+/// shows, and the player shouldn't keep dragging, playing or sleeving cards.
+///
+/// Pushed over whatever the turn was, the pause menu's way, and unwound with
+/// the rest of the turn state when leaving [`GameState::Playing`].
+fn freeze_ended_game(screenshot: Res<Screenshot>, mut turn: ResMut<State<TurnState>>) {
+    if screenshot.is_pending() && *turn.current() != TurnState::Paused {
+        if let Err(err) = turn.overwrite_push(TurnState::Paused) {
+            debug_print!(sec: 3.0, col: Color::RED, "Could not freeze ended game: {err}");
+        }
+    }
+}
+
+/// Show the restart menu once the game ended, and the [`Screenshot`] of the
+/// final board is read back.
 fn handle_gameover_event(
     mut commands: Commands,
     ui_assets: Res<UiAssets>,
+    strings: Res<Strings>,
     assets: Res<RestartAssets>,
     mut state: ResMut<State<GameState>>,
-    mut events: EventReader<GameOver>,
+    mut stats: ResMut<Stats>,
+    mut events: EventReader<GameEvent>,
+    mut ended: Local<Option<EndReason>>,
+    mut audio: EventWriter<AudioRequest>,
+    screenshot: Res<Screenshot>,
+    bindings: Res<KeyBindings>,
+    opponent: Res<OpponentProfile>,
+    rules: Res<GameRules>,
+    last_catch: Res<LastCatch>,
+    last_shift: Res<LastShift>,
+    last_title: Res<LastTitle>,
 ) {
-    use self::Button::{ExitApp, MainMenu, Restart};
-    use EndReason::{CaughtCheating, Loss, Victory};
-    if let Some(GameOver(reason)) = events.iter().next() {
-        state.set(GameState::RestartMenu).unwrap();
-        let continue_text = match *reason {
-            Victory => "Congratulation! You won!",
-            Loss => "You couldn't make up the point difference!",
-            CaughtCheating => "The BIRD saw you cheating!",
+    use self::Button::{ExitApp, MainMenu, Restart, Rules};
+    use EndReason::{CaughtCheating, Conceded, Loss, Victory};
+    if let Some(reason) = events.iter().find_map(GameEvent::end) {
+        // Requested before the state change, the sfx channel keeps playing
+        // over the restart menu.
+        if ended.is_none() && matches!(reason, CaughtCheating) {
+            audio.send(AudioRequest::PlayCaught);
+        }
+        ended.get_or_insert(reason);
+    }
+    // Leaving `Playing` despawns the board before the capture renders
+    if screenshot.is_pending() {
+        return;
+    }
+    if let Some(reason) = ended.take() {
+        // `replace` rather than `set`: the game may be over while the pause
+        // menu is pushed on top of `Playing`, this unwinds the whole stack.
+        state.replace(GameState::RestartMenu).unwrap();
+        let continue_key = opponent.gameover_key(reason);
+        let continue_text = strings.get(&continue_key);
+        // Empty for other end reasons, or catches without details
+        let catch_recap = match (reason, &last_catch.0) {
+            (CaughtCheating, Some(catch)) => catch.recap(&strings),
+            _ => String::new(),
         };
-        let won = matches!(*reason, Victory);
+        let won = matches!(reason, Victory);
         let image = if won { &assets.victory } else { &assets.defeat };
         let image = ImageBundle { image: image.clone().into(), ..Default::default() };
 
@@ -61,9 +129,49 @@ fn handle_gameover_event(
             ..Default::default()
         };
 
+        // Players who never won probably didn't read the rules, show them
+        // once, with the part relevant to how they lost standing out.
+        let highlight = match reason {
+            Loss => Some(RulesSection::Turns),
+            CaughtCheating => Some(RulesSection::Cheating),
+            Victory | Conceded => None,
+        };
+        let show_rules = highlight.is_some() && stats.wins == 0 && !stats.seen_defeat_rules;
+        if show_rules {
+            stats.seen_defeat_rules = true;
+        }
+        let rules_display = if show_rules { Display::Flex } else { Display::None };
+
         let focusable = Focusable::default();
         let cursor = MenuCursor::spawn_ui_element(&mut commands);
-        let defeat_hint = "Having difficulties? The game rules are in the main menu.";
+        let rules = ui_assets.spawn_rules(&mut commands, &strings, highlight);
+        let large_text = |key| ui_assets.large_text(strings.get(key));
+        let defeat_hint = strings.get("gameover.defeat_hint");
+        let restart_key = format!("{:?}", bindings.key(Action::QuickRestart));
+        let restart_hint =
+            strings.format("gameover.restart_hint", &[("key", restart_key.as_str())]);
+        let (first, second) = (
+            strings.get(CoopPlayer::First.name_key()),
+            strings.get(CoopPlayer::Second.name_key()),
+        );
+        let players = [("first", first), ("second", second)];
+        let coop_credit = strings.format("gameover.coop_credit", &players);
+        // Subtle, but not hidden: the player should know the AI changed
+        let shift_key = last_shift.0.map(DifficultyShift::label_key);
+        let shift_notice = shift_key.map_or("", |key| strings.get(key));
+        let objective_key = match reason {
+            Victory => Some("gameover.objective_met"),
+            Loss => Some("gameover.objective_failed"),
+            CaughtCheating | Conceded => None,
+        };
+        let title = last_title.0.map_or_else(String::new, |title| {
+            let name = strings.get(title.name_key());
+            strings.format("gameover.title", &[("title", name)])
+        });
+        let objective = rules.win_condition.describe(&strings);
+        let objective = objective_key.map_or_else(String::new, |key| {
+            strings.format(key, &[("objective", objective.as_str())])
+        });
         build_ui! {
             #[cmd(commands)]
             node{ size: size!(100 pct, 100 pct) }[;Name::new("Restart Menu root"), RestartMenuRoot](
@@ -73,49 +181,158 @@ fn handle_gameover_event(
                     Name::new("Shadow"),
                     style! { position_type: PositionType::Absolute, size: size!(100 pct, 100 pct), }
                 ],
-                node[; Name::new("Menu columns")](
-                    entity[image; style! { size: size!(auto, 45 pct), }],
-                    entity[
-                        ui_assets.large_text(continue_text);
-                        style! { margin: rect!(0 px, 0 px, 0 px, 60 px,), }
-                    ],
-                    if (matches!(*reason, Loss | CaughtCheating)) {
+                node{ flex_direction: FlexDirection::Row }[; Name::new("Menu row")](
+                    node[; Name::new("Menu columns")](
+                        entity[image; style! { size: size!(auto, 45 pct), }],
                         entity[
-                            ui_assets.text_bundle(defeat_hint, 30.0);
-                            style! { margin: rect!(0 px, 0 px, 0 px, 30 px,), }
-                        ]
-                    },
-                    entity[ui_assets.large_text("Main menu"); focusable, MainMenu],
-                    if (cfg!(target_arch = "wasm32")) {
-                        entity[ui_assets.large_text("(Press space to restart)");]
-                    } else {
-                        entity[ui_assets.large_text("Restart"); focusable, Restart],
-                        entity[ui_assets.large_text("Exit to desktop"); focusable, ExitApp],
-                    }
+                            ui_assets.large_text(continue_text);
+                            style! { margin: rect!(0 px, 0 px, 0 px, 60 px,), }
+                        ],
+                        if (!title.is_empty()) {
+                            entity[
+                                ui_assets.text_bundle(&title, 30.0);
+                                style! { margin: rect!(0 px, 0 px, 0 px, 30 px,), }
+                            ]
+                        },
+                        if (!objective.is_empty()) {
+                            entity[
+                                ui_assets.text_bundle(&objective, 30.0);
+                                style! { margin: rect!(0 px, 0 px, 0 px, 30 px,), }
+                            ]
+                        },
+                        if (!catch_recap.is_empty()) {
+                            entity[
+                                ui_assets.text_bundle(&catch_recap, 30.0);
+                                style! { margin: rect!(0 px, 0 px, 0 px, 30 px,), }
+                            ]
+                        },
+                        if (rules.fair_game) {
+                            entity[
+                                ui_assets.text_bundle(strings.get("gameover.fair_game"), 30.0);
+                                style! { margin: rect!(0 px, 0 px, 0 px, 30 px,), }
+                            ]
+                        },
+                        if (rules.coop) {
+                            entity[
+                                ui_assets.text_bundle(&coop_credit, 30.0);
+                                style! { margin: rect!(0 px, 0 px, 0 px, 30 px,), }
+                            ]
+                        },
+                        if (!shift_notice.is_empty()) {
+                            entity[
+                                ui_assets.text_bundle(shift_notice, 30.0);
+                                style! { margin: rect!(0 px, 0 px, 0 px, 30 px,), }
+                            ]
+                        },
+                        if (highlight.is_some() && !show_rules) {
+                            entity[
+                                ui_assets.text_bundle(defeat_hint, 30.0);
+                                style! { margin: rect!(0 px, 0 px, 0 px, 30 px,), }
+                            ]
+                        },
+                        entity[large_text("gameover.main_menu"); focusable, MainMenu],
+                        entity[large_text("menu.rules"); focusable, Rules],
+                        node[; Name::new("Screenshot slot"), ScreenshotSlot],
+                        if (cfg!(target_arch = "wasm32")) {
+                            entity[ui_assets.large_text(&restart_hint);]
+                        } else {
+                            entity[large_text("gameover.restart"); focusable, Restart],
+                            entity[large_text("gameover.exit_desktop"); focusable, ExitApp],
+                            entity[ui_assets.text_bundle(&restart_hint, 30.0);],
+                        }
+                    ),
+                    node{ display: rules_display, margin: rect!(30 px) }[;
+                        UiColor(Color::rgba(0.1, 0.1, 0.1, 0.9)),
+                        Name::new("Rules panel"),
+                        RulesPanel
+                    ](
+                        id(rules)
+                    )
                 )
             )
         };
     }
 }
 
+/// Despawn the cards still flying to a hand behind the menu.
+///
+/// Leaving the game already despawns all cards, this catches the ones drawn
+/// in the frames between the gameover and the state change.
+fn despawn_drawn_cards(
+    mut cmds: Commands,
+    cards: Query<Entity, (With<CardOrigin>, Without<PileCard>)>,
+) {
+    for entity in cards.iter() {
+        cmds.entity(entity).despawn_recursive();
+    }
+}
+
+/// Show the save screenshot button once the final board is captured, and
+/// where it was saved.
+fn update_screenshot_button(
+    mut cmds: Commands,
+    ui_assets: Res<UiAssets>,
+    strings: Res<Strings>,
+    screenshot: Res<Screenshot>,
+    slots: Query<Entity, (With<ScreenshotSlot>, Without<Children>)>,
+    mut buttons: Query<(&Button, &mut Text)>,
+) {
+    if !screenshot.is_changed() || !screenshot.is_ready() {
+        return;
+    }
+    let text = match screenshot.saved() {
+        None => strings.get("gameover.save_screenshot").to_owned(),
+        Some(Ok(file)) => strings.format("gameover.screenshot_saved", &[("file", file.as_str())]),
+        Some(Err(_)) => strings.get("gameover.screenshot_failed").to_owned(),
+    };
+    for slot in slots.iter() {
+        cmds.entity(slot).with_children(|cmds| {
+            let bundle = ui_assets.text_bundle(&text, 40.0);
+            cmds.spawn_bundle(bundle)
+                .insert_bundle((Focusable::default(), Button::SaveScreenshot));
+        });
+    }
+    for (button, mut button_text) in buttons.iter_mut() {
+        if let Button::SaveScreenshot = button {
+            button_text.sections[0].value = text.clone();
+        }
+    }
+}
+
 fn update(
     mut nav_events: EventReader<NavEvent>,
     buttons: Query<&Button>,
     mut state: ResMut<State<GameState>>,
     mut app_exit: EventWriter<AppExit>,
+    mut save_screenshot: EventWriter<SaveScreenshot>,
+    mut rules_panel: Query<&mut Style, With<RulesPanel>>,
 ) {
     match nav_events.nav_iter().activated_in_query(&buttons).next() {
         Some(Button::ExitApp) => app_exit.send(AppExit),
+        Some(Button::Rules) => {
+            for mut style in rules_panel.iter_mut() {
+                style.display = match style.display {
+                    Display::Flex => Display::None,
+                    Display::None => Display::Flex,
+                };
+            }
+        }
+        Some(Button::SaveScreenshot) => save_screenshot.send(SaveScreenshot),
         Some(Button::Restart) => state.set(GameState::Playing).unwrap(),
         Some(Button::MainMenu) => state.set(GameState::MainMenu).unwrap(),
         None => {}
     }
 }
 
-fn continue_on_space(mut keys: ResMut<Input<KeyCode>>, mut state: ResMut<State<GameState>>) {
-    if keys.just_pressed(KeyCode::Space) {
+fn quick_restart(
+    mut keys: ResMut<Input<KeyCode>>,
+    mut state: ResMut<State<GameState>>,
+    bindings: Res<KeyBindings>,
+) {
+    let restart_key = bindings.key(Action::QuickRestart);
+    if keys.just_pressed(restart_key) {
         state.set(GameState::Playing).unwrap();
-        keys.reset(KeyCode::Space);
+        keys.reset(restart_key);
     }
 }
 
@@ -123,13 +340,21 @@ pub struct Plugin;
 impl bevy::app::Plugin for Plugin {
     fn build(&self, app: &mut App) {
         use crate::system_helper::EasySystemSetCtor;
-        app.init_resource::<RestartAssets>().add_event::<GameOver>();
-        app.add_system(handle_gameover_event);
+        app.init_resource::<RestartAssets>();
+        app.add_system(freeze_ended_game.after("start_capture"));
+        app.add_system(
+            handle_gameover_event
+                .label("gameover")
+                .after("record_stats")
+                .after("start_capture"),
+        );
+        app.add_system_set(GameState::RestartMenu.on_enter(despawn_drawn_cards));
         app.add_system_set(GameState::RestartMenu.on_exit(cleanup_marked::<RestartMenuRoot>));
         app.add_system_set(
             SystemSet::on_update(GameState::RestartMenu)
                 .with_system(update)
-                .with_system(continue_on_space),
+                .with_system(update_screenshot_button)
+                .with_system(quick_restart),
         );
     }
 }
@@ -0,0 +1,338 @@
+//! Volume sliders, shared by the main and pause menus.
+//!
+//! [`VolumeSliderBuilder`] spawns a [`VolumeSlider`] for an [`AudioChannel`],
+//! showing the volume as a percentage next to its handle, unless
+//! [`VolumeSliderBuilder::hide_value`].
+//!
+//! The navigation locks on a slider while the player holds it, clicking it or
+//! pressing the action key. Moving the mouse or pressing the left and right
+//! arrows then moves it, until the mouse button is released or the action key
+//! pressed again. The new volume is sent as a single
+//! [`AudioRequest::SetVolume`] once the slider is released, rather than every
+//! frame the slider moves.
+use std::marker::PhantomData;
+
+use bevy::ecs::system::SystemParam;
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use bevy_ui_build_macros::{build_ui, rect, size, style, unit};
+use bevy_ui_navigation::prelude::*;
+
+use super::common::UiAssets;
+use crate::{
+    audio::{AudioChannel, AudioRequest, AudioRequestSystem, ChannelVolumes, SfxParam},
+    lang::{Localized, Strings},
+};
+
+/// How far the arrow keys move a held slider, in percents.
+const KEY_STEP: f64 = 5.0;
+/// How far a pixel of mouse motion moves a held slider, in percents.
+const MOUSE_STEP: f64 = 0.4;
+
+pub struct SliderAssets {
+    handle: Handle<Image>,
+    background: Handle<Image>,
+}
+impl FromWorld for SliderAssets {
+    fn from_world(world: &mut World) -> Self {
+        let assets = world.get_resource::<AssetServer>().unwrap();
+        Self {
+            handle: assets.load("slider_handle.png"),
+            background: assets.load("slider_bg.png"),
+        }
+    }
+}
+
+/// What [`VolumeSliderBuilder::spawn`] needs besides the menu assets.
+#[derive(SystemParam)]
+pub struct SliderParams<'w, 's> {
+    assets: Res<'w, SliderAssets>,
+    volumes: Res<'w, ChannelVolumes>,
+    #[system_param(ignore)]
+    _queries: PhantomData<&'s ()>,
+}
+
+/// The handle of a volume slider, at `volume` percents.
+#[derive(Component)]
+pub struct VolumeSlider {
+    channel: AudioChannel,
+    volume: f64,
+}
+impl VolumeSlider {
+    fn slide(&mut self, delta: f64) {
+        self.volume = (self.volume + delta).clamp(0.0, 100.0);
+    }
+    fn percent(&self) -> i32 {
+        self.volume.round() as i32
+    }
+    fn handle_position(&self) -> Val {
+        Val::Percent(self.volume as f32 * 0.9)
+    }
+    fn request(&self) -> AudioRequest {
+        AudioRequest::SetVolume(self.channel, self.volume / 100.0)
+    }
+}
+
+/// A [`VolumeSlider`] the player is holding.
+#[derive(Component)]
+struct HeldSlider;
+
+/// The text showing the volume of its parent [`VolumeSlider`].
+#[derive(Component)]
+struct SliderValue;
+
+fn value_text(strings: &Strings, slider: &VolumeSlider) -> String {
+    let percent = slider.percent().to_string();
+    strings.format("menu.volume.value", &[("percent", percent.as_str())])
+}
+
+/// Spawns a labelled [`VolumeSlider`].
+pub struct VolumeSliderBuilder {
+    name: &'static str,
+    key: &'static str,
+    channel: AudioChannel,
+    show_value: bool,
+}
+impl VolumeSliderBuilder {
+    /// A slider for `channel`, labelled with the `key` text.
+    pub fn new(name: &'static str, key: &'static str, channel: AudioChannel) -> Self {
+        Self { name, key, channel, show_value: true }
+    }
+    /// Don't show the volume next to the slider handle.
+    pub fn hide_value(self) -> Self {
+        Self { show_value: false, ..self }
+    }
+    /// Spawn the slider at the current volume of its channel, `elem` being
+    /// the menu element of its handle.
+    pub fn spawn(
+        self,
+        cmds: &mut Commands,
+        params: &SliderParams,
+        ui_assets: &UiAssets,
+        strings: &Strings,
+        elem: impl Component,
+    ) -> Entity {
+        let assets = &params.assets;
+        let slider = VolumeSlider {
+            channel: self.channel,
+            volume: params.volumes.get(self.channel) * 100.0,
+        };
+        let handle_name = Name::new(format!("{} volume slider handle", self.name));
+        let slider_name = Name::new(format!("{} volume slider", self.name));
+        let position = UiRect {
+            bottom: Val::Px(-10.0),
+            left: slider.handle_position(),
+            ..default()
+        };
+        let value_position = UiRect { bottom: Val::Px(40.0), ..default() };
+        let value = ui_assets.text_bundle(&value_text(strings, &slider), 20.0);
+        let label = ui_assets.text_bundle(strings.get(self.key), 30.0);
+        let image =
+            |image: &Handle<Image>| ImageBundle { image: image.clone().into(), ..default() };
+        let node = NodeBundle { color: Color::NONE.into(), ..default() };
+        build_ui! {
+            #[cmd(cmds)]
+            node { flex_direction: FlexDirection::Row }[; slider_name](
+                node[label; Localized(self.key), style! { margin: rect!(10 px), }],
+                node(
+                    entity[
+                        image(&assets.background);
+                        style! { size: size!( 200 px, 20 px), }
+                    ],
+                    node[
+                        image(&assets.handle);
+                        Focusable::lock(),
+                        slider,
+                        elem,
+                        handle_name,
+                        style! {
+                            size: size!( 40 px, 40 px),
+                            position_type: PositionType::Absolute,
+                            position: position,
+                        }
+                    ](
+                        if (self.show_value) {
+                            entity[
+                                value;
+                                SliderValue,
+                                style! {
+                                    position_type: PositionType::Absolute,
+                                    position: value_position,
+                                }
+                            ]
+                        }
+                    )
+                )
+            )
+        }
+        .id()
+    }
+}
+
+/// Move the held slider with the mouse and the arrow keys, and hold or
+/// release the focused slider with the mouse and the action keys.
+fn move_sliders(
+    mut held: Query<(&mut VolumeSlider, &mut Style), With<HeldSlider>>,
+    mut mouse_motion: EventReader<MouseMotion>,
+    mut nav_requests: EventWriter<NavRequest>,
+    mut mouse_buttons: ResMut<Input<MouseButton>>,
+    keys: Res<Input<KeyCode>>,
+    focused: Query<(), (With<Focused>, With<VolumeSlider>)>,
+) {
+    let horizontal_delta: f64 = mouse_motion.iter().map(|m| m.delta.x as f64).sum();
+    if let Ok((mut slider, mut style)) = held.get_single_mut() {
+        let nudge =
+            keys.just_pressed(KeyCode::Right) as i32 - keys.just_pressed(KeyCode::Left) as i32;
+        let delta = horizontal_delta * MOUSE_STEP + nudge as f64 * KEY_STEP;
+        if delta != 0.0 {
+            slider.slide(delta);
+            style.position.left = slider.handle_position();
+        }
+        let action = keys.just_pressed(KeyCode::Return) || keys.just_pressed(KeyCode::Space);
+        let released = mouse_buttons.just_released(MouseButton::Left);
+        if released {
+            mouse_buttons.clear_just_released(MouseButton::Left);
+        }
+        if released || action {
+            nav_requests.send(NavRequest::Unlock);
+        }
+    } else if mouse_buttons.just_pressed(MouseButton::Left) && !focused.is_empty() {
+        nav_requests.send(NavRequest::Action);
+    }
+}
+
+/// Hold the sliders the navigation locks on, release them and send their
+/// volume once it unlocks.
+///
+/// Sliders despawned while held, when their menu closes, are released too,
+/// with the last volume they had.
+fn hold_sliders(
+    mut cmds: Commands,
+    mut nav_events: EventReader<NavEvent>,
+    mut audio_requests: EventWriter<AudioRequest>,
+    mut last_held: Local<Option<AudioRequest>>,
+    sliders: Query<(), With<VolumeSlider>>,
+    held: Query<(Entity, &VolumeSlider), With<HeldSlider>>,
+) {
+    use NavEvent::{FocusChanged, Locked, Unlocked};
+    let mut release = false;
+    for (event_type, from) in nav_events.nav_iter().types() {
+        match event_type {
+            Locked(..) if sliders.contains(from) => {
+                audio_requests.send(AudioRequest::PlayWoodClink(SfxParam::StartLoop));
+                cmds.entity(from).insert(HeldSlider);
+            }
+            FocusChanged { .. } | Unlocked(..) => release = true,
+            _ => {}
+        }
+    }
+    let current = held.get_single().ok();
+    if let (false, Some((_, slider))) = (release, current) {
+        *last_held = Some(slider.request());
+        return;
+    }
+    let request = current.map(|(_, slider)| slider.request());
+    if let Some(request) = request.or_else(|| last_held.take()) {
+        audio_requests.send(request);
+        audio_requests.send(AudioRequest::StopSfxLoop);
+    }
+    *last_held = None;
+    for (entity, _) in held.iter() {
+        cmds.entity(entity).remove::<HeldSlider>();
+    }
+}
+
+fn update_slider_values(
+    strings: Res<Strings>,
+    sliders: Query<(&VolumeSlider, ChangeTrackers<VolumeSlider>, &Children)>,
+    mut texts: Query<&mut Text, With<SliderValue>>,
+) {
+    for (slider, tracker, children) in sliders.iter() {
+        if !tracker.is_changed() && !strings.is_changed() {
+            continue;
+        }
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value = value_text(&strings, slider);
+            }
+        }
+    }
+}
+
+pub struct Plugin;
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SliderAssets>()
+            .add_system(move_sliders.before(NavRequestSystem))
+            .add_system(
+                hold_sliders
+                    .after(NavRequestSystem)
+                    .before(AudioRequestSystem),
+            )
+            .add_system(update_slider_values.after(move_sliders));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{asset::AssetPlugin, core::CorePlugin, ecs::system::SystemState};
+
+    use super::*;
+    use crate::lang::Language;
+
+    #[derive(Component)]
+    struct TestSlider;
+
+    /// The value texts of a slider spawned with `builder`.
+    fn spawned_values(builder: VolumeSliderBuilder) -> Vec<String> {
+        let mut app = App::new();
+        app.add_plugin(CorePlugin)
+            .add_plugin(AssetPlugin)
+            .add_asset::<Image>()
+            .add_asset::<Font>()
+            .init_resource::<UiAssets>()
+            .init_resource::<SliderAssets>()
+            .init_resource::<ChannelVolumes>()
+            .insert_resource(Strings::new(Language::English));
+        type Spawn<'w, 's> = (
+            Commands<'w, 's>,
+            SliderParams<'w, 's>,
+            Res<'w, UiAssets>,
+            Res<'w, Strings>,
+        );
+        let mut spawn = SystemState::<Spawn>::new(&mut app.world);
+        let (mut cmds, params, ui_assets, strings) = spawn.get_mut(&mut app.world);
+        builder.spawn(&mut cmds, &params, &ui_assets, &strings, TestSlider);
+        spawn.apply(&mut app.world);
+        let mut values = app.world.query_filtered::<&Text, With<SliderValue>>();
+        let values = values.iter(&app.world);
+        values.map(|text| text.sections[0].value.clone()).collect()
+    }
+
+    #[test]
+    fn slider_values_can_be_hidden() {
+        let builder =
+            || VolumeSliderBuilder::new("Music", "menu.volume.music", AudioChannel::Music);
+        let strings = Strings::new(Language::English);
+        let half = strings.format("menu.volume.value", &[("percent", "50")]);
+        assert_eq!(spawned_values(builder()), [half]);
+        assert_eq!(spawned_values(builder().hide_value()), [] as [String; 0]);
+    }
+
+    #[test]
+    fn sliders_stay_in_range() {
+        let mut slider = VolumeSlider { channel: AudioChannel::Music, volume: 50.0 };
+        slider.slide(12.4);
+        assert_eq!(slider.percent(), 62);
+        slider.slide(0.2);
+        assert_eq!(slider.percent(), 63, "rounded to the nearest percent");
+        slider.slide(80.0);
+        assert_eq!(slider.percent(), 100);
+        assert_eq!(slider.handle_position(), Val::Percent(90.0));
+        slider.slide(-250.0);
+        assert_eq!(slider.percent(), 0);
+        assert!(
+            matches!(slider.request(), AudioRequest::SetVolume(AudioChannel::Music, v) if v == 0.0)
+        );
+    }
+}
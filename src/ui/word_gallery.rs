@@ -0,0 +1,202 @@
+//! Browsable list of the words of power, with an example battle for each.
+//!
+//! Examples are computed with [`battle_points`], so they always follow the
+//! actual scoring rules. The shown word is shared by all galleries, it is
+//! changed by activating the arrows around it, like any other menu element.
+use bevy::prelude::{Plugin as BevyPlugin, *};
+use bevy_ui_build_macros::{build_ui, rect, size, style, unit};
+use bevy_ui_navigation::prelude::*;
+
+use super::common::UiAssets;
+use crate::{
+    game_flow::battle_points,
+    lang::{Localized, Strings},
+    state::GameState,
+    war::{Card, WordOfPower},
+};
+
+/// Index in [`gallery_words`] of the word shown in galleries.
+#[derive(Default)]
+struct ShownWord(usize);
+
+#[derive(Component, Clone)]
+struct WordGallery;
+
+/// Activating it shows the previous (-1) or next (1) word.
+#[derive(Component, Clone, Copy)]
+pub struct GalleryArrow(i32);
+
+/// Part of the gallery page showing the [`ShownWord`].
+#[derive(Component, Clone, Copy)]
+enum GalleryPart {
+    Picture,
+    WordName,
+    Flavor,
+    Example,
+    Counter,
+}
+
+/// The words worth showing, those with an effect in game.
+fn gallery_words() -> Vec<WordOfPower> {
+    use enum_map::Enum;
+    let all = (0..WordOfPower::LENGTH).map(WordOfPower::from_usize);
+    all.filter(|word| word.is_implemented()).collect()
+}
+
+/// An example battle showing off `word`, the player card having it.
+fn example_cards(word: WordOfPower) -> (Card, Card) {
+    use WordOfPower::*;
+    let (player, oppo) = match word {
+        Egeq => ("7seed", "3_"),
+        Qube => ("7doub", "3_"),
+        Zihbm => ("3swap", "7_"),
+        Geh => ("0zero", "9_"),
+        Het | Meb => ("5_", "5_"),
+    };
+    (player.parse().unwrap(), oppo.parse().unwrap())
+}
+
+/// Points the player scores in the `word` example, without and with the word.
+fn example_points(word: WordOfPower) -> (i32, i32) {
+    let (player, oppo) = example_cards(word);
+    let plain: Card = format!("{}_", player.value_i32()).parse().unwrap();
+    (battle_points(&plain, &oppo), battle_points(&player, &oppo))
+}
+
+fn example_text(strings: &Strings, word: WordOfPower) -> String {
+    let (player, oppo) = example_cards(word);
+    let (plain, with) = example_points(word);
+    let key = match word {
+        WordOfPower::Egeq => "gallery.example_seed",
+        _ => "gallery.example",
+    };
+    let (player, oppo) = (player.value_i32().to_string(), oppo.value_i32().to_string());
+    let (plain, with, word) = (plain.to_string(), with.to_string(), format!("{word:?}"));
+    let args = [
+        ("player", player.as_str()),
+        ("oppo", oppo.as_str()),
+        ("plain", plain.as_str()),
+        ("with", with.as_str()),
+        ("word", word.as_str()),
+    ];
+    strings.format(key, &args)
+}
+
+/// Spawn a gallery showing a word at a time, to add to a rules overlay.
+pub fn spawn_word_gallery(cmds: &mut Commands, ui_assets: &UiAssets, strings: &Strings) -> Entity {
+    let node = NodeBundle {
+        color: Color::NONE.into(),
+        style: style! {
+            flex_direction: FlexDirection::ColumnReverse,
+            align_items: AlignItems::Center,
+        },
+        ..default()
+    };
+    let text = |size| ui_assets.text_bundle("", size);
+    let arrow = |content| {
+        let mut bundle = ui_assets.large_text(content);
+        bundle.style = style! { margin: rect!(20 px, 0 px), };
+        bundle
+    };
+    let image = ImageBundle {
+        style: style! { size: size!(100 px, 100 px), margin: rect!(10 px), },
+        ..default()
+    };
+    let mut flavor = text(30.0);
+    flavor.text.sections[0].style.color = Color::GOLD;
+    let mut example = text(30.0);
+    example.style = style! { max_size: size!(600 px, auto), };
+    let (title_key, hint_key) = ("gallery.title", "gallery.hint");
+    use GalleryPart::{Counter, Example, Flavor, Picture, WordName};
+    build_ui! {
+        #[cmd(cmds)]
+        node[; Name::new("Word gallery"), WordGallery](
+            entity[ui_assets.large_text(strings.get(title_key)); Localized(title_key)],
+            node{ flex_direction: FlexDirection::Row, align_items: AlignItems::Center }(
+                entity[arrow("<"); Focusable::default(), GalleryArrow(-1)],
+                entity[image; Picture],
+                node(
+                    entity[text(45.0); WordName],
+                    entity[flavor; Flavor],
+                    entity[example; Example]
+                ),
+                entity[arrow(">"); Focusable::default(), GalleryArrow(1)]
+            ),
+            entity[text(30.0); Counter],
+            entity[ui_assets.text_bundle(strings.get(hint_key), 30.0); Localized(hint_key)]
+        )
+    }
+    .id()
+}
+
+fn browse_gallery(
+    mut events: EventReader<NavEvent>,
+    mut shown: ResMut<ShownWord>,
+    arrows: Query<&GalleryArrow>,
+) {
+    let count = gallery_words().len() as i32;
+    for GalleryArrow(direction) in events.nav_iter().activated_in_query(&arrows) {
+        shown.0 = (shown.0 as i32 + direction).rem_euclid(count) as usize;
+    }
+}
+
+fn update_gallery(
+    shown: Res<ShownWord>,
+    strings: Res<Strings>,
+    ui_assets: Res<UiAssets>,
+    new_galleries: Query<(), Added<WordGallery>>,
+    mut parts: Query<(&GalleryPart, Option<&mut Text>, Option<&mut UiImage>)>,
+) {
+    if !shown.is_changed() && !strings.is_changed() && new_galleries.is_empty() {
+        return;
+    }
+    let words = gallery_words();
+    let word = words[shown.0];
+    for (part, text, image) in parts.iter_mut() {
+        let content = match part {
+            GalleryPart::Picture => {
+                if let Some(mut image) = image {
+                    image.0 = ui_assets.words[word].clone();
+                }
+                continue;
+            }
+            GalleryPart::WordName => format!("{word:?}"),
+            GalleryPart::Flavor => strings.get(word.flavor_key()).to_owned(),
+            GalleryPart::Example => example_text(&strings, word),
+            GalleryPart::Counter => format!("{} / {}", shown.0 + 1, words.len()),
+        };
+        if let Some(mut text) = text {
+            text.sections[0].value = content;
+        }
+    }
+}
+
+pub struct Plugin(pub GameState);
+impl BevyPlugin for Plugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShownWord>().add_system_set(
+            SystemSet::on_update(self.0)
+                .with_system(browse_gallery.after(NavRequestSystem))
+                .with_system(update_gallery.after(browse_gallery)),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use WordOfPower::*;
+
+    #[test]
+    fn gallery_skips_unimplemented_words() {
+        assert_eq!(gallery_words(), [Egeq, Qube, Zihbm, Geh]);
+    }
+
+    #[test]
+    fn examples_follow_scoring_rules() {
+        assert_eq!(example_points(Egeq), (10, 10));
+        assert_eq!(example_points(Qube), (10, 20));
+        assert_eq!(example_points(Zihbm), (0, 10));
+        assert_eq!(example_points(Geh), (9, 21));
+    }
+}
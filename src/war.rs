@@ -31,6 +31,16 @@ pub enum ParseError {
     BadValue(String),
     BadWord(String),
     EmptyWord,
+    /// A `[section]` header that isn't closed or has no name.
+    BadSection(String),
+    /// Where in a deck file `token` failed to parse, line and column start
+    /// at 1.
+    At {
+        line: usize,
+        column: usize,
+        token: String,
+        error: Box<ParseError>,
+    },
 }
 impl std::fmt::Display for ParseError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -38,12 +48,16 @@ impl std::fmt::Display for ParseError {
             ParseError::EmptyWord => write!(f, "The word is specified as non-existing"),
             ParseError::BadWord(word) => write!(f, "The word {word} is invalid"),
             ParseError::BadValue(value) => write!(f, "The value {value} is invalid"),
+            ParseError::BadSection(header) => write!(f, "The section {header} is invalid"),
+            ParseError::At { line, column, token, error } => {
+                write!(f, "{token} at line {line}, column {column}: {error}")
+            }
         }
     }
 }
 impl std::error::Error for ParseError {}
 
-#[derive(Clone, Copy, PartialEq, PartialOrd)]
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub enum BattleOutcome {
     Loss,
     Tie,
@@ -134,14 +148,22 @@ impl WordOfPower {
             Zihbm => Color::PINK,
         }
     }
-    pub fn flavor_text(&self) -> &'static str {
+    /// Whether the word has an effect in game, the others are placeholders.
+    pub fn is_implemented(self) -> bool {
+        use WordOfPower::*;
+        matches!(self, Egeq | Qube | Zihbm | Geh)
+    }
+    /// Key of the text describing the word effect in [`Strings`].
+    ///
+    /// [`Strings`]: crate::lang::Strings
+    pub fn flavor_key(&self) -> &'static str {
         use WordOfPower::*;
         match self {
-            Egeq => "Gain a seed",
-            Qube => "Double points",
-            Zihbm => "Swap winners",
-            Geh => "Zero earns 12",
-            _ => "Unimplemented",
+            Egeq => "word.egeq",
+            Qube => "word.qube",
+            Zihbm => "word.zihbm",
+            Geh => "word.geh",
+            _ => "word.unimplemented",
         }
     }
 }
@@ -193,7 +215,8 @@ impl FromStr for Card {
     type Err = ParseError;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         use ParseError::EmptyWord;
-        let (value, word) = s.split_at(1);
+        let value_len = s.chars().next().map_or(0, char::len_utf8);
+        let (value, word) = s.split_at(value_len);
         let word = word.parse().map_or_else(
             |err| if matches!(err, EmptyWord) { Ok(None) } else { Err(err) },
             |word| Ok(Some(word)),
@@ -201,6 +224,22 @@ impl FromStr for Card {
         Ok(Card { value: value.parse()?, word: word? })
     }
 }
+/// Writes the card in the deck file format, the inverse of [`Card::from_str`].
+impl std::fmt::Display for Card {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use WordOfPower::*;
+        let word = match self.word {
+            None => "_",
+            Some(Egeq) => "s",
+            Some(Qube) => "d",
+            Some(Zihbm) => "w",
+            Some(Geh) => "z",
+            Some(Het) => "het",
+            Some(Meb) => "meb",
+        };
+        write!(f, "{}{word}", self.value_i32())
+    }
+}
 impl Card {
     pub fn beats(&self, other: &Self) -> BattleOutcome {
         use BattleOutcome::{Loss, Tie, Win};
@@ -263,4 +302,11 @@ mod tests {
         assert_eq!((1, 1), bonus_for!(1d, 1_));
         assert_eq!((2, 2), bonus_for!(1d, 1d));
     }
+    #[test]
+    fn display_parses_back() {
+        for text in ["0z", "9_", "5s", "3d", "7w", "1het", "2meb"] {
+            let card: Card = text.parse().unwrap();
+            assert_eq!(card.to_string(), text);
+        }
+    }
 }